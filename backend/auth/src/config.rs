@@ -1,63 +1,408 @@
+use database::schemas::{sessions::JwtKeyAlgorithm, users::Role};
 use serde::Deserialize;
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+use tracing::error;
 
-use crate::{google::GoogleOpenId, microsoft::MicrosoftOpenId, openid::OpenIdProvider};
+use crate::{
+    error::{Error, Result},
+    openid::{OpenIdKeys, OpenIdResponse},
+    throttling::LoginThrottle,
+};
 
 #[derive(Deserialize, Clone)]
 pub struct Jwt {
-    pub secret: String,
     pub issuer: String,
     pub audience: String,
+    /// Number of seconds during which a JWT is valid, kept short since the services verifying it
+    /// with the JWKS cannot know whether its session was revoked
     pub expiration: u64,
+    #[serde(default)]
+    pub algorithm: JwtKeyAlgorithm,
+    /// Number of seconds during which a session can be refreshed after the login
+    #[serde(default = "default_refresh_expiration")]
+    pub refresh_expiration: u64,
+    /// Number of seconds after which a new signing key is generated, the previous one still
+    /// being published until the JWTs it signed expire
+    #[serde(default = "default_key_rotation_interval")]
+    pub key_rotation_interval: u64,
 }
 
+fn default_refresh_expiration() -> u64 {
+    30 * 24 * 60 * 60
+}
+
+fn default_key_rotation_interval() -> u64 {
+    7 * 24 * 60 * 60
+}
+
+/// OpenID Connect provider, such as Google, Microsoft, Keycloak, Okta or Authentik
 #[derive(Deserialize, Clone)]
 pub struct OpenId {
     pub enabled: bool,
+    /// Name of the provider displayed to users
+    pub name: String,
     pub redirect_uri: String,
+    /// URL of the provider's `.well-known/openid-configuration` discovery document
     pub openid_url: String,
     pub client_id: String,
     pub client_secret: String,
+    #[serde(default = "default_scopes")]
+    pub scopes: Vec<String>,
+    /// Value of the `prompt` parameter sent to the provider, such as `select_account`
+    pub prompt: Option<String>,
+    #[serde(default)]
+    pub claims: ClaimMappings,
+    #[serde(default)]
+    pub roles: RoleMappings,
+}
+
+fn default_scopes() -> Vec<String> {
+    ["openid", "email", "profile"]
+        .into_iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// Names of the ID token claims containing the information about the user
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct ClaimMappings {
+    pub subject: String,
+    pub email: String,
+    pub email_verified: String,
+    /// Falls back to the email when the claim is missing
+    pub name: String,
+    pub given_name: String,
+    pub family_name: String,
+    pub picture: String,
+    pub locale: String,
+}
+
+impl Default for ClaimMappings {
+    fn default() -> Self {
+        Self {
+            subject: "sub".to_string(),
+            email: "email".to_string(),
+            email_verified: "email_verified".to_string(),
+            name: "name".to_string(),
+            given_name: "given_name".to_string(),
+            family_name: "family_name".to_string(),
+            picture: "picture".to_string(),
+            locale: "locale".to_string(),
+        }
+    }
+}
+
+/// Rules granting roles to the users based on their ID token claims, applied on every login
+#[derive(Deserialize, Clone, Default)]
+pub struct RoleMappings {
+    /// Whether the provider is the source of truth, the roles it does not grant being removed
+    /// from the user instead of being kept
+    #[serde(default)]
+    pub source_of_truth: bool,
+    #[serde(default)]
+    pub rules: Vec<RoleMapping>,
+}
+
+/// Grants the role when the claim is equal to, or is an array containing, the value
+#[derive(Deserialize, Clone)]
+pub struct RoleMapping {
+    pub claim: String,
+    pub value: String,
+    pub role: Role,
+}
+
+impl RoleMappings {
+    pub fn roles(&self, claims: &Value) -> HashSet<Role> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.matches(claims))
+            .map(|rule| rule.role)
+            .collect()
+    }
+}
+
+impl RoleMapping {
+    fn matches(&self, claims: &Value) -> bool {
+        match claims.get(&self.claim) {
+            Some(Value::String(value)) => value == &self.value,
+            Some(Value::Array(values)) => values
+                .iter()
+                .any(|value| value.as_str() == Some(self.value.as_str())),
+            _ => false,
+        }
+    }
 }
 
 #[derive(Deserialize, Clone)]
 pub struct Auth {
     pub frontend_redirect_path: String,
     pub frontend_redirect_hosts: Vec<String>,
+    /// Emails of the users made enabled admins when they login with their verified email, so
+    /// that the platform can be administered before any admin exists
+    #[serde(default)]
+    pub admin_emails: Vec<String>,
     pub jwt: Jwt,
-    pub google: OpenId,
-    pub microsoft: OpenId,
+    /// OpenID Connect providers, keyed by the name used in their URLs
+    #[serde(default)]
+    pub openid: BTreeMap<String, OpenId>,
+    #[serde(default)]
+    pub password: Password,
+    #[serde(default)]
+    pub mfa: Mfa,
+    #[serde(default)]
+    pub login_throttling: LoginThrottling,
 }
 
 impl Auth {
-    pub fn open_id(&self, provider: &str) -> &OpenId {
-        match provider {
-            "google" => &self.google,
-            "microsoft" => &self.microsoft,
-            _ => panic!("Unknown provider: {}", provider),
+    /// Whether the user with the verified email is to be made an admin
+    pub fn is_admin_email(&self, email: &str) -> bool {
+        self.admin_emails
+            .iter()
+            .any(|admin_email| admin_email.eq_ignore_ascii_case(email))
+    }
+}
+
+/// Local accounts authenticated with a password
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct Password {
+    /// Whether users need to verify their email before being able to login
+    pub require_email_verification: bool,
+    /// Number of seconds during which an email verification link can be used
+    pub email_verification_expiration: u64,
+    /// Number of seconds during which a password reset link can be used
+    pub password_reset_expiration: u64,
+    pub policy: PasswordPolicy,
+}
+
+impl Default for Password {
+    fn default() -> Self {
+        Self {
+            require_email_verification: true,
+            email_verification_expiration: 24 * 60 * 60,
+            password_reset_expiration: 60 * 60,
+            policy: Default::default(),
         }
     }
 }
 
+/// Second authentication factors of local accounts
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct Mfa {
+    /// Name shown in authenticator apps and to security keys
+    pub issuer: String,
+    /// Number of seconds during which the second factor step of a login can be completed
+    pub challenge_expiration: u64,
+    /// Number of invalid second factors after which the second factor step of a login is
+    /// refused, the user needing to login again
+    pub max_challenge_attempts: u32,
+    /// Number of seconds during which a security key registration can be completed
+    pub webauthn_registration_expiration: u64,
+}
+
+impl Default for Mfa {
+    fn default() -> Self {
+        Self {
+            issuer: "Indicator Aggregator".to_string(),
+            challenge_expiration: 5 * 60,
+            max_challenge_attempts: 5,
+            webauthn_registration_expiration: 5 * 60,
+        }
+    }
+}
+
+/// Limits on the failed logins of local accounts, either through the login endpoint or Basic
+/// authentication
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct LoginThrottling {
+    pub enabled: bool,
+    /// Number of failed logins after which an account is locked
+    pub max_account_attempts: u32,
+    /// Number of failed logins after which an IP address is locked, higher than for accounts
+    /// since users can share an IP address
+    pub max_ip_attempts: u32,
+    /// Number of seconds without any failed login after which the previous ones are forgotten
+    pub attempts_window: u64,
+    /// Number of seconds during which a locked account or IP address cannot login
+    pub lockout_duration: u64,
+    /// Milliseconds by which a login following a failed one is delayed, doubling with every other
+    /// failed login
+    pub base_delay: u64,
+    /// Upper bound in milliseconds of the delay of a login
+    pub max_delay: u64,
+    /// Interval in seconds between the removal of the expired failed logins
+    pub sweep_interval: u64,
+}
+
+impl Default for LoginThrottling {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_account_attempts: 5,
+            max_ip_attempts: 50,
+            attempts_window: 15 * 60,
+            lockout_duration: 15 * 60,
+            base_delay: 250,
+            max_delay: 5000,
+            sweep_interval: 300,
+        }
+    }
+}
+
+/// Requirements that the passwords of local accounts need to meet
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    /// Upper bound avoiding the hashing of arbitrarily long passwords
+    pub max_length: usize,
+    pub require_lowercase: bool,
+    pub require_uppercase: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 12,
+            max_length: 128,
+            require_lowercase: false,
+            require_uppercase: false,
+            require_digit: false,
+            require_symbol: false,
+        }
+    }
+}
+
+/// Number of seconds to wait before discovering again a provider whose discovery failed, so that
+/// an unavailable provider is not queried on every login
+const DISCOVERY_RETRY_INTERVAL: u64 = 30;
+
+/// Discovered configuration of the enabled OpenID Connect providers, the ones whose discovery
+/// failed being discovered again when used
+#[derive(Clone, Default)]
+pub struct Providers {
+    discovered: Arc<RwLock<HashMap<String, OpenIdResponse>>>,
+    /// Time of the last discovery of the providers that were not discovered yet
+    attempted_at: Arc<Mutex<HashMap<String, Instant>>>,
+}
+
+impl Providers {
+    /// Gets the discovered configuration of the provider, discovering it if it was not yet
+    pub async fn get_or_discover(
+        &self,
+        provider: &str,
+        open_id: &OpenId,
+    ) -> Option<OpenIdResponse> {
+        if let Some(response) = self.get(provider) {
+            return Some(response);
+        }
+
+        self.discover(provider, open_id).await
+    }
+
+    fn get(&self, provider: &str) -> Option<OpenIdResponse> {
+        self.discovered
+            .read()
+            .expect("OpenID providers lock poisoned")
+            .get(provider)
+            .cloned()
+    }
+
+    async fn discover(&self, provider: &str, open_id: &OpenId) -> Option<OpenIdResponse> {
+        if !self.reserve_discovery(provider) {
+            return None;
+        }
+
+        match OpenIdKeys::new(&open_id.openid_url).await {
+            Ok(response) => {
+                self.discovered
+                    .write()
+                    .expect("OpenID providers lock poisoned")
+                    .insert(provider.to_string(), response.clone());
+
+                Some(response)
+            }
+            Err(err) => {
+                error!(%err, provider, "Failed to discover OpenID provider");
+                None
+            }
+        }
+    }
+
+    /// Whether the provider can be discovered, in which case it cannot be discovered again until
+    /// the interval elapsed
+    fn reserve_discovery(&self, provider: &str) -> bool {
+        let mut attempted_at = self
+            .attempted_at
+            .lock()
+            .expect("OpenID discovery lock poisoned");
+
+        if attempted_at.get(provider).is_some_and(|attempted_at| {
+            attempted_at.elapsed() < Duration::from_secs(DISCOVERY_RETRY_INTERVAL)
+        }) {
+            return false;
+        }
+
+        attempted_at.insert(provider.to_string(), Instant::now());
+        true
+    }
+}
+
 #[derive(Clone)]
 pub struct State {
     pub auth: Auth,
-    pub google_keys: Option<GoogleOpenId>,
-    pub microsoft_keys: Option<MicrosoftOpenId>,
+    pub providers: Providers,
     pub jwt_manager: crate::jwt::JwtManager,
+    pub login_throttle: LoginThrottle,
 }
 
 impl State {
-    pub fn provider_data(&self, provider: &str) -> Option<Box<dyn OpenIdProvider>> {
-        match provider {
-            "google" => self
-                .google_keys
-                .as_ref()
-                .map(|a| Box::new(a.clone()) as Box<dyn OpenIdProvider>),
-            "microsoft" => self
-                .microsoft_keys
-                .as_ref()
-                .map(|a| Box::new(a.clone()) as Box<dyn OpenIdProvider>),
-            _ => panic!("Unknown provider: {}", provider),
+    /// Discovers the configuration of the enabled OpenID Connect providers, the ones that could
+    /// not be discovered being discovered again when used
+    pub async fn new(
+        auth: Auth,
+        jwt_manager: crate::jwt::JwtManager,
+        login_throttle: LoginThrottle,
+    ) -> Self {
+        let providers = Providers::default();
+
+        for (provider, open_id) in auth.openid.iter().filter(|(_, open_id)| open_id.enabled) {
+            providers.discover(provider, open_id).await;
         }
+
+        Self {
+            auth,
+            providers,
+            jwt_manager,
+            login_throttle,
+        }
+    }
+
+    /// Gets the configuration of the enabled provider along with its discovered configuration,
+    /// discovering it if it was not yet
+    pub async fn provider(&self, provider: &str) -> Result<(&OpenId, OpenIdResponse)> {
+        let open_id = self
+            .auth
+            .openid
+            .get(provider)
+            .filter(|open_id| open_id.enabled)
+            .ok_or(Error::NotProperlySetup)?;
+        let response = self
+            .providers
+            .get_or_discover(provider, open_id)
+            .await
+            .ok_or(Error::NotProperlySetup)?;
+
+        Ok((open_id, response))
     }
 }
@@ -3,14 +3,26 @@ pub type Result<T> = std::result::Result<T, Error>;
 #[derive(Debug)]
 pub enum Error {
     NotProperlySetup,
-    MissingRoles(Vec<String>),
+    MissingPermission(database::schemas::users::Permission),
+    /// Request outside of what the API token used to authenticate is restricted to
+    OutOfScope(String),
+    /// Resource of a team the user is not a member of
+    OutOfTeam(String),
     BadRequest(String),
     Unauthorized(String),
+    WeakPassword(String),
     SerdeJson(serde_json::Error),
     Reqwest(reqwest::Error),
     Database(database::Error),
     Jsonwebtoken(jsonwebtoken::errors::Error),
     PasswordHash(shared::crypto::PasswordHashError),
+    Encryption(shared::crypto::Error),
+    /// Failure to generate or load a JWT signing key
+    SigningKey(String),
+    /// Login refused because of too many failed ones, with the number of seconds until it is
+    /// allowed again
+    LockedOut(u64),
+    Cache(cache::CacheError),
 }
 
 impl From<serde_json::Error> for Error {
@@ -42,3 +54,15 @@ impl From<shared::crypto::PasswordHashError> for Error {
         Self::PasswordHash(e)
     }
 }
+
+impl From<shared::crypto::Error> for Error {
+    fn from(e: shared::crypto::Error) -> Self {
+        Self::Encryption(e)
+    }
+}
+
+impl From<cache::CacheError> for Error {
+    fn from(e: cache::CacheError) -> Self {
+        Self::Cache(e)
+    }
+}
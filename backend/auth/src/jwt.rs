@@ -1,37 +1,366 @@
-use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Validation};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{Duration, NaiveDateTime, Utc};
+use database::{
+    logic::sessions,
+    schemas::{
+        sessions::{CreateJwtKey, JwtKey, JwtKeyAlgorithm},
+        users::Permission,
+    },
+    PgPool,
+};
+use jsonwebtoken::{
+    jwk::{
+        AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+        OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+    },
+    Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+use ring::{
+    rand::SystemRandom,
+    signature::{Ed25519KeyPair, KeyPair},
+};
+use rsa::{
+    pkcs1::{DecodeRsaPublicKey, EncodeRsaPrivateKey, EncodeRsaPublicKey},
+    traits::PublicKeyParts,
+    RsaPrivateKey, RsaPublicKey,
+};
 use serde::{Deserialize, Serialize};
+use shared::crypto::Crypto;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, RwLock},
+    time::Instant,
+};
+use tokio::task::JoinHandle;
+use tracing::{info, instrument, warn};
 use typeshare::typeshare;
 
-use crate::{config, Result};
+use crate::{config, Error, Result};
 
+const RSA_KEY_SIZE: usize = 2048;
+/// Number of seconds between synchronizations of the signing keys with the database, picking up
+/// the keys generated by other server instances
+const KEY_SYNC_INTERVAL: u64 = 60;
+/// Minimum number of seconds between the synchronizations triggered by JWTs signed with an
+/// unknown key, which anyone can forge
+const UNKNOWN_KEY_SYNC_INTERVAL: u64 = 10;
+
+struct SigningKey {
+    id: String,
+    algorithm: Algorithm,
+    encoding_key: EncodingKey,
+    expires_at: NaiveDateTime,
+}
+
+struct Keys {
+    /// Most recent key of the configured algorithm, signing the new JWTs
+    signing: Option<Arc<SigningKey>>,
+    decoding: HashMap<String, (Algorithm, DecodingKey)>,
+    jwks: JwkSet,
+}
+
+impl Default for Keys {
+    fn default() -> Self {
+        Self {
+            signing: None,
+            decoding: HashMap::new(),
+            jwks: JwkSet { keys: Vec::new() },
+        }
+    }
+}
+
+/// Signs and verifies the JWTs with asymmetric keys stored in the database, shared by all the
+/// server instances and rotated periodically
 #[derive(Clone)]
 pub struct JwtManager {
-    decoding_key: DecodingKey,
-    encoding_key: EncodingKey,
+    pool: PgPool,
+    crypto: Crypto,
+    keys: Arc<RwLock<Keys>>,
+    /// Last synchronization triggered by a JWT signed with an unknown key
+    unknown_key_synced_at: Arc<Mutex<Option<Instant>>>,
     validation: Validation,
     issuer: String,
     audience: String,
     expiration: u64,
+    refresh_expiration: u64,
+    algorithm: JwtKeyAlgorithm,
+    key_rotation_interval: u64,
+}
+
+fn to_algorithm(algorithm: JwtKeyAlgorithm) -> Algorithm {
+    match algorithm {
+        JwtKeyAlgorithm::Rs256 => Algorithm::RS256,
+        JwtKeyAlgorithm::EdDsa => Algorithm::EdDSA,
+    }
+}
+
+fn key_error<E: ToString>(err: E) -> Error {
+    Error::SigningKey(err.to_string())
+}
+
+/// Generates a DER encoded private key, PKCS#1 for RSA and PKCS#8 for Ed25519, along with its
+/// public key
+fn generate_key_pair(algorithm: JwtKeyAlgorithm) -> Result<(Vec<u8>, Vec<u8>)> {
+    match algorithm {
+        JwtKeyAlgorithm::Rs256 => {
+            let private_key =
+                RsaPrivateKey::new(&mut rand::thread_rng(), RSA_KEY_SIZE).map_err(key_error)?;
+            let public_key = RsaPublicKey::from(&private_key);
+
+            Ok((
+                private_key
+                    .to_pkcs1_der()
+                    .map_err(key_error)?
+                    .as_bytes()
+                    .to_vec(),
+                public_key.to_pkcs1_der().map_err(key_error)?.into_vec(),
+            ))
+        }
+        JwtKeyAlgorithm::EdDsa => {
+            let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).map_err(key_error)?;
+            let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).map_err(key_error)?;
+
+            Ok((
+                pkcs8.as_ref().to_vec(),
+                key_pair.public_key().as_ref().to_vec(),
+            ))
+        }
+    }
+}
+
+/// Public key in the format expected by [`DecodingKey`] and its JWKS parameters
+fn public_key(key: &JwtKey) -> Result<(DecodingKey, AlgorithmParameters)> {
+    match key.algorithm {
+        JwtKeyAlgorithm::Rs256 => {
+            let public_key = RsaPublicKey::from_pkcs1_der(&key.public_key).map_err(key_error)?;
+
+            Ok((
+                DecodingKey::from_rsa_der(&key.public_key),
+                AlgorithmParameters::RSA(RSAKeyParameters {
+                    key_type: RSAKeyType::RSA,
+                    n: URL_SAFE_NO_PAD.encode(public_key.n().to_bytes_be()),
+                    e: URL_SAFE_NO_PAD.encode(public_key.e().to_bytes_be()),
+                }),
+            ))
+        }
+        JwtKeyAlgorithm::EdDsa => Ok((
+            DecodingKey::from_ed_der(&key.public_key),
+            AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                key_type: OctetKeyPairType::OctetKeyPair,
+                curve: EllipticCurve::Ed25519,
+                x: URL_SAFE_NO_PAD.encode(&key.public_key),
+            }),
+        )),
+    }
 }
 
 impl JwtManager {
-    pub fn new(config: &config::Jwt) -> Self {
-        let mut validation = Validation::new(Algorithm::HS256);
+    /// The signing keys are loaded, or generated, the first time they are needed
+    pub fn new(config: &config::Jwt, pool: PgPool, crypto: Crypto) -> Self {
+        let mut validation = Validation::new(to_algorithm(config.algorithm));
         validation.set_audience(&[&config.audience]);
         validation.set_issuer(&[&config.issuer]);
         validation.validate_exp = true;
 
         Self {
-            decoding_key: DecodingKey::from_secret(config.secret.as_ref()),
-            encoding_key: EncodingKey::from_secret(config.secret.as_ref()),
+            pool,
+            crypto,
+            keys: Default::default(),
+            unknown_key_synced_at: Default::default(),
             validation,
             issuer: config.issuer.clone(),
             audience: config.audience.clone(),
             expiration: config.expiration,
+            refresh_expiration: config.refresh_expiration,
+            algorithm: config.algorithm,
+            key_rotation_interval: config.key_rotation_interval,
+        }
+    }
+
+    pub fn refresh_expiration(&self) -> u64 {
+        self.refresh_expiration
+    }
+
+    /// Public keys verifying the JWTs, published for other services
+    pub fn jwks(&self) -> JwkSet {
+        self.keys
+            .read()
+            .expect("JWT keys lock poisoned")
+            .jwks
+            .clone()
+    }
+
+    /// Loads the keys from the database, generating a new signing key when the current one is
+    /// due for rotation and removing the ones whose JWTs are all expired
+    #[instrument(skip(self), err(Debug))]
+    pub async fn sync_keys(&self) -> Result<()> {
+        let mut keys = sessions::get_valid_jwt_keys(&self.pool).await?;
+
+        let rotation_due = keys
+            .iter()
+            .find(|key| key.algorithm == self.algorithm)
+            .is_none_or(|key| {
+                Utc::now().naive_utc() - key.created_at
+                    >= Duration::seconds(self.key_rotation_interval as i64)
+            });
+
+        // Server instances rotating at the same time each generate a key, which is harmless
+        // since they all end up signing with the most recent one
+        if rotation_due {
+            self.create_key().await?;
+            keys = sessions::get_valid_jwt_keys(&self.pool).await?;
+        }
+
+        let loaded = self.load_keys(&keys)?;
+        *self.keys.write().expect("JWT keys lock poisoned") = loaded;
+
+        sessions::delete_expired_jwt_keys(&self.pool).await?;
+
+        Ok(())
+    }
+
+    async fn create_key(&self) -> Result<()> {
+        let algorithm = self.algorithm;
+        // Generating an RSA key takes a while
+        let (private_key, public_key) =
+            tokio::task::spawn_blocking(move || generate_key_pair(algorithm))
+                .await
+                .map_err(key_error)??;
+
+        // The key signs JWTs until the next rotation, which need to be verifiable until they expire
+        let expires_at = Utc::now().naive_utc()
+            + Duration::seconds((self.key_rotation_interval + self.expiration) as i64);
+
+        let id = sessions::create_jwt_key(
+            &self.pool,
+            &CreateJwtKey {
+                algorithm,
+                private_key: self.crypto.encrypt(URL_SAFE_NO_PAD.encode(private_key))?,
+                public_key,
+                expires_at,
+            },
+        )
+        .await?;
+        info!(id, ?algorithm, "generated a new JWT signing key");
+
+        Ok(())
+    }
+
+    fn load_keys(&self, keys: &[JwtKey]) -> Result<Keys> {
+        let mut loaded = Keys::default();
+
+        for key in keys {
+            let algorithm = to_algorithm(key.algorithm);
+            let (decoding_key, parameters) = public_key(key)?;
+
+            if loaded.signing.is_none() && key.algorithm == self.algorithm {
+                let private_key = URL_SAFE_NO_PAD
+                    .decode(self.crypto.decrypt(&key.private_key)?)
+                    .map_err(key_error)?;
+                let encoding_key = match key.algorithm {
+                    JwtKeyAlgorithm::Rs256 => EncodingKey::from_rsa_der(&private_key),
+                    JwtKeyAlgorithm::EdDsa => EncodingKey::from_ed_der(&private_key),
+                };
+
+                loaded.signing = Some(Arc::new(SigningKey {
+                    id: key.id.clone(),
+                    algorithm,
+                    encoding_key,
+                    expires_at: key.expires_at,
+                }));
+            }
+
+            loaded.jwks.keys.push(Jwk {
+                common: CommonParameters {
+                    public_key_use: Some(PublicKeyUse::Signature),
+                    key_algorithm: Some(match key.algorithm {
+                        JwtKeyAlgorithm::Rs256 => KeyAlgorithm::RS256,
+                        JwtKeyAlgorithm::EdDsa => KeyAlgorithm::EdDSA,
+                    }),
+                    key_id: Some(key.id.clone()),
+                    ..Default::default()
+                },
+                algorithm: parameters,
+            });
+            loaded
+                .decoding
+                .insert(key.id.clone(), (algorithm, decoding_key));
         }
+
+        Ok(loaded)
+    }
+
+    /// Key signing the new JWTs, which need to expire before the key does
+    fn signing_key(&self) -> Option<Arc<SigningKey>> {
+        let expires_at = Utc::now().naive_utc() + Duration::seconds(self.expiration as i64);
+
+        self.keys
+            .read()
+            .expect("JWT keys lock poisoned")
+            .signing
+            .clone()
+            .filter(|key| key.expires_at >= expires_at)
+    }
+
+    fn decoding_key(&self, id: &str) -> Option<(Algorithm, DecodingKey)> {
+        self.keys
+            .read()
+            .expect("JWT keys lock poisoned")
+            .decoding
+            .get(id)
+            .cloned()
+    }
+
+    /// Whether the keys can be synchronized because of a JWT signed with an unknown key, in which
+    /// case the next ones cannot be until the interval elapsed
+    fn reserve_unknown_key_sync(&self) -> bool {
+        let mut synced_at = self
+            .unknown_key_synced_at
+            .lock()
+            .expect("JWT key sync lock poisoned");
+
+        if synced_at.is_some_and(|synced_at| {
+            synced_at.elapsed() < std::time::Duration::from_secs(UNKNOWN_KEY_SYNC_INTERVAL)
+        }) {
+            return false;
+        }
+
+        *synced_at = Some(Instant::now());
+        true
     }
 
-    pub fn generate_jwt(&self, claims: Claims) -> Result<String> {
+    /// Periodically synchronizes the keys with the database, rotating them when needed
+    pub fn spawn_key_sync(&self) -> JoinHandle<()> {
+        let manager = self.clone();
+
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(std::time::Duration::from_secs(KEY_SYNC_INTERVAL));
+
+            loop {
+                interval.tick().await;
+
+                if manager.pool.is_closed() {
+                    break;
+                }
+
+                if let Err(error) = manager.sync_keys().await {
+                    warn!(?error, "failed to synchronize the JWT signing keys");
+                }
+            }
+        })
+    }
+
+    pub async fn generate_jwt(&self, claims: Claims) -> Result<String> {
+        let signing_key = match self.signing_key() {
+            Some(signing_key) => signing_key,
+            None => {
+                self.sync_keys().await?;
+                self.signing_key()
+                    .ok_or_else(|| Error::SigningKey("No valid signing key".to_string()))?
+            }
+        };
+
         let iat = chrono::Utc::now().to_utc().timestamp();
 
         let all_claims = AllClaims {
@@ -43,16 +372,37 @@ impl JwtManager {
             data: claims,
         };
 
+        let mut header = Header::new(signing_key.algorithm);
+        header.kid = Some(signing_key.id.clone());
+
         jsonwebtoken::encode(
-            &Default::default(),
+            &header,
             &serde_json::to_value(all_claims)?,
-            &self.encoding_key,
+            &signing_key.encoding_key,
         )
         .map_err(Into::into)
     }
 
-    pub fn get_claims(&self, token: &str) -> Result<AllClaims> {
-        jsonwebtoken::decode(token, &self.decoding_key, &self.validation)
+    pub async fn get_claims(&self, token: &str) -> Result<AllClaims> {
+        let header = jsonwebtoken::decode_header(token)?;
+        let Some(key_id) = header.kid else {
+            return Err(Error::Unauthorized("JWT without key ID".to_string()));
+        };
+
+        // The key may have been generated by another server instance since the last
+        // synchronization, the unknown keys being refused until the next one otherwise
+        if self.decoding_key(&key_id).is_none() && self.reserve_unknown_key_sync() {
+            self.sync_keys().await?;
+        }
+
+        let Some((algorithm, decoding_key)) = self.decoding_key(&key_id) else {
+            return Err(Error::Unauthorized("Unknown JWT key".to_string()));
+        };
+
+        let mut validation = self.validation.clone();
+        validation.algorithms = vec![algorithm];
+
+        jsonwebtoken::decode(token, &decoding_key, &validation)
             .map(|t| t.claims)
             .map_err(Into::into)
     }
@@ -64,6 +414,8 @@ impl JwtManager {
 pub struct Claims {
     /// Database ID of the user
     pub sub: String,
+    /// Database ID of the session the JWT was issued for
+    pub sid: String,
     /// Email of the user
     pub email: String,
     /// Whether the user has verified their email
@@ -78,6 +430,9 @@ pub struct Claims {
     pub locale: Option<String>,
     /// Roles of the user granting access to parts of the platform
     pub roles: Vec<String>,
+    /// Permissions granted by the roles of the user
+    #[serde(default)]
+    pub permissions: Vec<Permission>,
     /// Provider of the user
     pub provider: String,
 }
@@ -92,3 +447,105 @@ pub struct AllClaims {
     #[serde(flatten)]
     pub data: Claims,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generate_key(algorithm: JwtKeyAlgorithm) -> (Vec<u8>, JwtKey) {
+        let (private_key, public_key) = generate_key_pair(algorithm).unwrap();
+        let now = Utc::now().naive_utc();
+
+        let key = JwtKey {
+            id: "key".to_string(),
+            created_at: now,
+            updated_at: now,
+            algorithm,
+            private_key: Vec::new(),
+            public_key,
+            expires_at: now,
+        };
+
+        (private_key, key)
+    }
+
+    #[test]
+    fn given_generated_key_pair_when_verifying_signed_token_then_claims_match() {
+        for algorithm in [JwtKeyAlgorithm::Rs256, JwtKeyAlgorithm::EdDsa] {
+            let (private_key, key) = generate_key(algorithm);
+            let encoding_key = match algorithm {
+                JwtKeyAlgorithm::Rs256 => EncodingKey::from_rsa_der(&private_key),
+                JwtKeyAlgorithm::EdDsa => EncodingKey::from_ed_der(&private_key),
+            };
+            let (decoding_key, _) = public_key(&key).unwrap();
+
+            let token = jsonwebtoken::encode(
+                &Header::new(to_algorithm(algorithm)),
+                &serde_json::json!({ "sub": "user" }),
+                &encoding_key,
+            )
+            .unwrap();
+
+            let mut validation = Validation::new(to_algorithm(algorithm));
+            validation.required_spec_claims.clear();
+            validation.validate_exp = false;
+
+            let claims =
+                jsonwebtoken::decode::<serde_json::Value>(&token, &decoding_key, &validation)
+                    .unwrap()
+                    .claims;
+            assert_eq!(claims["sub"], "user");
+        }
+    }
+
+    #[tokio::test]
+    async fn given_unknown_keys_when_getting_claims_then_syncs_keys_at_most_once_per_interval() {
+        let pool = PgPool::connect_lazy("postgres://localhost/unreachable").unwrap();
+        // Synchronizing the keys fails right away instead of reaching the database
+        pool.close().await;
+
+        let config = config::Jwt {
+            issuer: "test".to_string(),
+            audience: "test".to_string(),
+            expiration: 900,
+            algorithm: JwtKeyAlgorithm::EdDsa,
+            refresh_expiration: 3600,
+            key_rotation_interval: 3600,
+        };
+        let manager = JwtManager::new(&config, pool, Crypto::new("0".repeat(32)));
+
+        let token = |key_id: &str| {
+            let mut header = Header::new(Algorithm::HS256);
+            header.kid = Some(key_id.to_string());
+            jsonwebtoken::encode(
+                &header,
+                &serde_json::json!({ "sub": "user" }),
+                &EncodingKey::from_secret(b"forged"),
+            )
+            .unwrap()
+        };
+
+        assert!(matches!(
+            manager.get_claims(&token("first")).await,
+            Err(Error::Database(_))
+        ));
+        assert!(matches!(
+            manager.get_claims(&token("second")).await,
+            Err(Error::Unauthorized(_))
+        ));
+    }
+
+    #[test]
+    fn given_ed25519_key_when_publishing_then_jwk_is_an_octet_key_pair() {
+        let (_, key) = generate_key(JwtKeyAlgorithm::EdDsa);
+        let (_, parameters) = public_key(&key).unwrap();
+
+        let jwk = serde_json::to_value(parameters).unwrap();
+        assert_eq!(jwk["kty"], "OKP");
+        assert_eq!(jwk["crv"], "Ed25519");
+        assert_eq!(
+            URL_SAFE_NO_PAD.decode(jwk["x"].as_str().unwrap()).unwrap(),
+            key.public_key
+        );
+    }
+}
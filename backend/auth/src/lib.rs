@@ -1,28 +1,42 @@
 pub mod config;
 pub mod error;
-pub mod google;
 pub mod jwt;
-pub mod microsoft;
+pub mod mfa;
 pub mod middleware;
 pub mod openid;
+pub mod password;
+pub mod scope;
+pub mod sessions;
+pub mod teams;
+pub mod throttling;
+pub mod webauthn;
 
+use database::schemas::users::{Permission, User};
 use rand::distributions::Alphanumeric;
 use rand::{thread_rng, Rng};
 
 use error::{Error, Result};
 
-pub fn require_roles(user_roles: &[String], required_roles: &[&str]) -> Result<()> {
-    let missing_roles = required_roles
-        .iter()
-        // TODO: rework this to not need to convert to string
-        .filter(|role| !user_roles.contains(&role.to_string()))
-        .map(|role| role.to_string())
-        .collect::<Vec<_>>();
+/// Ensures that the user's roles grant them the permission
+pub fn require_permission(user: &User, permission: Permission) -> Result<()> {
+    if user.permissions().contains(&permission) {
+        Ok(())
+    } else {
+        Err(Error::MissingPermission(permission))
+    }
+}
 
-    if missing_roles.is_empty() {
+/// Ensures that the user is the one targeted by the request or that their roles grant them the
+/// permission
+pub fn require_self_or_permission(
+    user: &User,
+    user_id: &str,
+    permission: Permission,
+) -> Result<()> {
+    if user.id == user_id {
         Ok(())
     } else {
-        Err(Error::MissingRoles(missing_roles))
+        require_permission(user, permission)
     }
 }
 
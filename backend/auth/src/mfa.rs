@@ -0,0 +1,267 @@
+use base32::Alphabet;
+use database::{logic::mfa, PgPool};
+use hmac::{Hmac, Mac};
+use rand::{thread_rng, RngCore};
+use reqwest::Url;
+use serde::Serialize;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use shared::crypto::Crypto;
+use typeshare::typeshare;
+use utoipa::ToSchema;
+
+use crate::{generate_random_string, Error, Result};
+
+const TOTP_PERIOD: i64 = 30;
+const TOTP_DIGITS: u32 = 6;
+/// Number of time steps accepted before and after the current one, tolerating clock drift
+const TOTP_SKEW: i64 = 1;
+const BASE32_ALPHABET: Alphabet = Alphabet::Rfc4648 { padding: false };
+const NUM_RECOVERY_CODES: usize = 10;
+
+/// Secret to add to an authenticator app, either by hand or by scanning the URI as a QR code
+#[derive(Serialize, ToSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct TotpSetup {
+    /// Base32 encoded secret
+    pub secret: String,
+    /// `otpauth://` URI containing the secret
+    pub uri: String,
+}
+
+fn generate_totp_secret() -> String {
+    let mut secret = [0; 20];
+    thread_rng().fill_bytes(&mut secret);
+
+    base32::encode(BASE32_ALPHABET, &secret)
+}
+
+fn totp_uri(issuer: &str, account: &str, secret: &str) -> String {
+    let mut uri = Url::parse("otpauth://totp/").expect("Valid TOTP URI");
+
+    uri.set_path(&format!("{issuer}:{account}"));
+    uri.query_pairs_mut()
+        .append_pair("secret", secret)
+        .append_pair("issuer", issuer)
+        .append_pair("algorithm", "SHA1")
+        .append_pair("digits", &TOTP_DIGITS.to_string())
+        .append_pair("period", &TOTP_PERIOD.to_string());
+
+    uri.to_string()
+}
+
+/// HMAC-based one-time password from RFC 4226
+fn hotp(secret: &[u8], counter: u64) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).expect("HMAC accepts keys of any size");
+    mac.update(&counter.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let code = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]);
+
+    code % 10u32.pow(TOTP_DIGITS)
+}
+
+/// Time step at which the code is valid, if any, around the timestamp
+fn find_totp_step(secret: &str, code: &str, timestamp: i64) -> Option<i64> {
+    let code = code.trim();
+
+    if code.len() != TOTP_DIGITS as usize || !code.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let code: u32 = code.parse().ok()?;
+    let secret = base32::decode(BASE32_ALPHABET, secret)?;
+    let current_step = timestamp / TOTP_PERIOD;
+
+    (current_step - TOTP_SKEW..=current_step + TOTP_SKEW)
+        .find(|step| hotp(&secret, *step as u64) == code)
+}
+
+/// Starts a TOTP enrollment, replacing any pending one, that needs to be confirmed with a code
+pub async fn setup_totp(
+    pool: &PgPool,
+    crypto: &Crypto,
+    issuer: &str,
+    user_id: &str,
+    email: &str,
+) -> Result<TotpSetup> {
+    if let Some(totp) = mfa::get_user_totp(pool, user_id).await? {
+        if totp.enabled {
+            return Err(Error::BadRequest("TOTP already enabled".to_string()));
+        }
+    }
+
+    let secret = generate_totp_secret();
+    mfa::create_or_replace_user_totp(pool, user_id, &crypto.encrypt(secret.clone())?).await?;
+
+    Ok(TotpSetup {
+        uri: totp_uri(issuer, email, &secret),
+        secret,
+    })
+}
+
+/// Step of the code if it is valid for the user's TOTP secret, whether it is enabled or not
+async fn find_user_totp_step(
+    pool: &PgPool,
+    crypto: &Crypto,
+    user_id: &str,
+    code: &str,
+    enabled: bool,
+) -> Result<Option<i64>> {
+    let Some(totp) = mfa::get_user_totp(pool, user_id).await? else {
+        return Ok(None);
+    };
+
+    if totp.enabled != enabled {
+        return Ok(None);
+    }
+
+    let secret = crypto.decrypt(&totp.secret)?;
+
+    Ok(find_totp_step(
+        &secret,
+        code,
+        chrono::Utc::now().timestamp(),
+    ))
+}
+
+/// Enables a pending TOTP enrollment if the code is valid
+pub async fn confirm_totp(
+    pool: &PgPool,
+    crypto: &Crypto,
+    user_id: &str,
+    code: &str,
+) -> Result<bool> {
+    match find_user_totp_step(pool, crypto, user_id, code, false).await? {
+        Some(step) => Ok(mfa::enable_user_totp(pool, user_id, step).await?),
+        None => Ok(false),
+    }
+}
+
+/// Validates a code of the user's enabled TOTP, each code only being usable once
+pub async fn verify_totp(
+    pool: &PgPool,
+    crypto: &Crypto,
+    user_id: &str,
+    code: &str,
+) -> Result<bool> {
+    match find_user_totp_step(pool, crypto, user_id, code, true).await? {
+        Some(step) => Ok(mfa::use_user_totp_step(pool, user_id, step).await?),
+        None => Ok(false),
+    }
+}
+
+/// Recovery codes are random enough to not need a slow password hash, dashes and case being
+/// ignored to make them easier to type
+fn hash_recovery_code(code: &str) -> String {
+    let normalized = code
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect::<String>()
+        .to_lowercase();
+
+    format!("{:x}", Sha256::digest(normalized))
+}
+
+/// Replaces the recovery codes of the user, the new ones only being returned once
+pub async fn generate_recovery_codes(pool: &PgPool, user_id: &str) -> Result<Vec<String>> {
+    let codes = (0..NUM_RECOVERY_CODES)
+        .map(|_| {
+            let code = generate_random_string(10).to_lowercase();
+            format!("{}-{}", &code[..5], &code[5..])
+        })
+        .collect::<Vec<_>>();
+
+    let hashed_codes = codes
+        .iter()
+        .map(|code| hash_recovery_code(code))
+        .collect::<Vec<_>>();
+    mfa::replace_user_recovery_codes(pool, user_id, &hashed_codes).await?;
+
+    Ok(codes)
+}
+
+/// Generates recovery codes when the user has none left, such as when enrolling their first
+/// second factor
+pub async fn ensure_recovery_codes(pool: &PgPool, user_id: &str) -> Result<Vec<String>> {
+    if mfa::count_unused_user_recovery_codes(pool, user_id).await? > 0 {
+        return Ok(Vec::new());
+    }
+
+    generate_recovery_codes(pool, user_id).await
+}
+
+/// Validates a recovery code of the user, marking it as used
+pub async fn use_recovery_code(pool: &PgPool, user_id: &str, code: &str) -> Result<bool> {
+    Ok(mfa::use_user_recovery_code(pool, user_id, &hash_recovery_code(code)).await?)
+}
+
+/// Removes the recovery codes once the user has no second factor left
+pub async fn cleanup_recovery_codes(pool: &PgPool, user_id: &str) -> Result<()> {
+    if !mfa::user_has_mfa(pool, user_id).await? {
+        mfa::delete_user_recovery_codes(pool, user_id).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Secret of the test vectors of RFC 6238
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    #[test]
+    fn given_rfc_test_vectors_when_finding_totp_step_then_codes_match() {
+        let secret = base32::encode(BASE32_ALPHABET, RFC_SECRET);
+
+        assert_eq!(find_totp_step(&secret, "287082", 59), Some(1));
+        assert_eq!(
+            find_totp_step(&secret, "081804", 1111111109),
+            Some(37037036)
+        );
+        assert_eq!(
+            find_totp_step(&secret, "050471", 1111111111),
+            Some(37037037)
+        );
+        assert_eq!(
+            find_totp_step(&secret, "005924", 1234567890),
+            Some(41152263)
+        );
+    }
+
+    #[test]
+    fn given_code_outside_of_skew_when_finding_totp_step_then_returns_none() {
+        let secret = base32::encode(BASE32_ALPHABET, RFC_SECRET);
+
+        assert_eq!(
+            find_totp_step(&secret, "287082", 59 + 2 * TOTP_PERIOD),
+            None
+        );
+        assert_eq!(find_totp_step(&secret, "28708", 59), None);
+        assert_eq!(find_totp_step(&secret, "28708a", 59), None);
+    }
+
+    #[test]
+    fn given_totp_secret_when_building_uri_then_contains_issuer_and_account() {
+        let uri = totp_uri("Indicator Aggregator", "jane@example.com", "ABCDEF");
+
+        assert_eq!(uri, "otpauth://totp/Indicator%20Aggregator:jane@example.com?secret=ABCDEF&issuer=Indicator+Aggregator&algorithm=SHA1&digits=6&period=30");
+    }
+
+    #[test]
+    fn given_recovery_code_typed_differently_when_hashing_then_hashes_match() {
+        assert_eq!(
+            hash_recovery_code("abcde-12345"),
+            hash_recovery_code(" ABCDE12345 ")
+        );
+    }
+}
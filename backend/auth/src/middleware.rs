@@ -4,8 +4,11 @@ use axum_extra::headers::{
     Authorization, UserAgent,
 };
 use database::{
-    logic::{api_tokens, users},
-    schemas::users::UserLog,
+    logic::{api_tokens, mfa, server_config, sessions, teams, users},
+    schemas::{
+        api_tokens::ApiTokenScope,
+        users::{User, UserLog},
+    },
     PgPool,
 };
 use http::HeaderValue;
@@ -13,7 +16,9 @@ use shared::crypto::verify_password;
 use std::net::SocketAddr;
 use tracing::Span;
 
-use crate::{Error, Result};
+use crate::{
+    password::verify_user_password, scope::is_ip_allowed, throttling::LoginAttempt, Error, Result,
+};
 
 #[derive(Clone, PartialEq, Debug)]
 pub struct Token(String);
@@ -44,19 +49,26 @@ pub async fn auth_middleware(
     user_agent: UserAgent,
     request: &mut Request,
 ) -> Result<()> {
+    let mut api_token_id = None;
+
     let user = match (bearer_auth, token_auth, basic_auth) {
         (Some(auth), None, None) => {
-            let id = state
+            let claims = state
                 .jwt_manager
                 .get_claims(auth.token())
+                .await
                 .map_err(|err| {
                     tracing::error!(?err, "Failed to get claims from token");
                     Error::Unauthorized("Invalid token".to_string())
                 })?
-                .data
-                .sub;
+                .data;
 
-            users::get_user(&pool, &id).await?
+            // Lets revoked sessions take effect right away instead of once their JWT expires
+            if !sessions::is_user_session_active(&pool, &claims.sid, &claims.sub).await? {
+                return Err(Error::Unauthorized("Session revoked".to_string()));
+            }
+
+            users::get_user(&pool, &claims.sub).await?
         }
         (None, Some(auth), None) => {
             let raw_token = auth.0 .0.clone();
@@ -72,24 +84,59 @@ pub async fn auth_middleware(
                 return Err(Error::Unauthorized("Invalid API token".to_string()));
             }
 
-            users::get_user(&pool, &token.user_id).await?
+            if let Some(ranges) = &token.allowed_ip_ranges {
+                if !is_ip_allowed(ranges, addr.ip()) {
+                    return Err(Error::Unauthorized(
+                        "API token not allowed from this IP address".to_string(),
+                    ));
+                }
+            }
+
+            api_token_id = Some(id.to_string());
+
+            let scope = ApiTokenScope::from(&token);
+            users::get_user(&pool, &token.user_id)
+                .await?
+                .map(|user| User {
+                    api_token_scope: (!scope.is_unrestricted()).then_some(scope),
+                    ..user
+                })
         }
         (None, None, Some(auth)) => {
-            let email = auth.username();
-            let password = auth.password();
+            let attempt = LoginAttempt {
+                email: auth.username(),
+                addr,
+                user_agent: user_agent.to_string(),
+                uri: request.uri().to_string(),
+                method: request.method().to_string(),
+            };
+            state.login_throttle.check(&attempt).await?;
 
-            let Some(data) = users::get_user_from_email(&pool, email).await? else {
-                return Err(Error::Unauthorized("API token unused".to_string()));
+            let Some(data) = verify_user_password(&pool, attempt.email, auth.password()).await?
+            else {
+                state.login_throttle.record_failure(&pool, &attempt).await?;
+                return Err(Error::Unauthorized("Invalid credentials".to_string()));
             };
 
-            let Some(password_hash) = &data.password else {
+            state.login_throttle.record_success(&attempt).await?;
+
+            if state.auth.password.require_email_verification && !data.verified {
                 return Err(Error::Unauthorized(
-                    "User not using password authentication".to_string(),
+                    "User email is not verified".to_string(),
                 ));
-            };
+            }
 
-            if !verify_password(password, password_hash)? {
-                return Err(Error::Unauthorized("Invalid API token".to_string()));
+            // Basic authentication would otherwise bypass the second factor
+            if mfa::user_has_mfa(&pool, &data.id).await?
+                || *server_config::get_config_with_defaults_and_db_results(&pool)
+                    .await?
+                    .mfa_required
+                    .get_value()
+            {
+                return Err(Error::Unauthorized(
+                    "Basic authentication is disabled for users needing a second factor"
+                        .to_string(),
+                ));
             }
 
             Some(data.into())
@@ -97,7 +144,7 @@ pub async fn auth_middleware(
         _ => return Err(Error::Unauthorized("Missing token".to_string())),
     };
 
-    let Some(user) = user else {
+    let Some(mut user) = user else {
         return Err(Error::Unauthorized("User does not exist".to_string()));
     };
 
@@ -107,12 +154,15 @@ pub async fn auth_middleware(
         return Err(Error::Unauthorized("User is disabled".to_string()));
     }
 
+    user.team_ids = teams::get_user_team_ids(&pool, &user.id).await?;
+
     let user_log = UserLog {
         user_id: user.id.clone(),
         ip_address: addr.to_string(),
         user_agent: user_agent.to_string(),
         uri: request.uri().to_string(),
         method: request.method().to_string(),
+        event: None,
     };
 
     request.extensions_mut().insert(user);
@@ -124,6 +174,14 @@ pub async fn auth_middleware(
             .unwrap_or_else(|err| {
                 tracing::error!(%err, "Failed to create user log");
             });
+
+        if let Some(api_token_id) = api_token_id {
+            api_tokens::record_api_token_usage(&pool, &api_token_id, &addr.ip().to_string())
+                .await
+                .unwrap_or_else(|err| {
+                    tracing::error!(%err, "Failed to record API token usage");
+                });
+        }
     });
 
     Ok(())
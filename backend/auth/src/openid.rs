@@ -1,4 +1,3 @@
-use async_trait::async_trait;
 use axum_core::response::{IntoResponse, Response};
 use axum_extra::headers::UserAgent;
 use base64::{engine::general_purpose, Engine as _};
@@ -7,23 +6,25 @@ use database::{
     logic::{auth, users},
     schemas::{
         auth::{CreateLoginRequest, LoginRequest},
-        users::{CreateUser, UserLog},
+        users::{CreateUser, Role, UpdateUser, User, UserLog},
     },
     PgPool,
 };
 use http::{header::LOCATION, StatusCode};
 use hyper::{HeaderMap, Uri};
 use jsonwebtoken::{jwk::Jwk, Algorithm, DecodingKey, Validation};
-use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::{net::SocketAddr, time::Duration};
 use tokio::{sync::watch, time::sleep};
 use tracing::{error, info};
 use utoipa::{IntoParams, ToSchema};
 
 use crate::{
-    config::{Auth, OpenId, State},
+    config::{Auth, ClaimMappings, OpenId, State},
     generate_random_string,
     jwt::{Claims, JwtManager},
+    sessions::create_session,
     Error, Result,
 };
 
@@ -86,6 +87,22 @@ pub fn parse_and_validate_referer(headers: &HeaderMap, auth: &Auth) -> Result<Ur
     Ok(parsed_referer)
 }
 
+/// Origin of the frontend sending the request, such as `https://example.com`, from its validated
+/// referer
+pub fn parse_referer_origin(headers: &HeaderMap, auth: &Auth) -> Result<String> {
+    let parsed_referer = parse_and_validate_referer(headers, auth)?;
+
+    Ok(format!(
+        "{}://{}",
+        parsed_referer.scheme_str().ok_or(Error::BadRequest(
+            "Invalid referer address scheme".to_string()
+        ))?,
+        parsed_referer.authority().ok_or(Error::BadRequest(
+            "Invalid referer address authority".to_string()
+        ))?
+    ))
+}
+
 impl OpenIdResponse {
     #[allow(clippy::too_many_arguments)]
     #[tracing::instrument(skip_all)]
@@ -99,17 +116,7 @@ impl OpenIdResponse {
         provider: &str,
         headers: HeaderMap,
     ) -> Result<impl IntoResponse> {
-        let parsed_referer = parse_and_validate_referer(&headers, auth)?;
-
-        let redirect_uri = format!(
-            "{}://{}",
-            parsed_referer.scheme_str().ok_or(Error::BadRequest(
-                "Invalid referer address scheme".to_string()
-            ))?,
-            parsed_referer.authority().ok_or(Error::BadRequest(
-                "Invalid referer address authority".to_string()
-            ))?
-        );
+        let redirect_uri = parse_referer_origin(&headers, auth)?;
 
         let nonce = generate_random_string(64);
         let state_nonce = generate_random_string(64);
@@ -126,8 +133,9 @@ impl OpenIdResponse {
 
         let login_request_id = auth::create_login_request(pool, login_request).await?;
 
+        let open_id = auth.openid.get(provider).ok_or(Error::NotProperlySetup)?;
         let state = general_purpose::URL_SAFE.encode(format!("{state_nonce}:{login_request_id}"));
-        let auth_url = self.generate_auth_url(auth.open_id(provider), &state, &nonce);
+        let auth_url = self.generate_auth_url(open_id, &state, &nonce)?;
 
         Ok(temporary_redirect(&auth_url))
     }
@@ -138,34 +146,62 @@ impl OpenIdResponse {
         OpenId {
             client_id,
             redirect_uri,
+            scopes,
+            prompt,
             ..
         }: &OpenId,
         state: &str,
         nonce: &str,
-    ) -> String {
-        format!(
-            "{}?client_id={client_id}&redirect_uri={redirect_uri}&response_type=code&scope=openid%20email%20profile&prompt=select_account&nonce={nonce}&state={state}",
-            self.authorization_endpoint,
-        )
+    ) -> Result<String> {
+        let scope = scopes.join(" ");
+        let mut params = vec![
+            ("client_id", client_id.as_str()),
+            ("redirect_uri", redirect_uri.as_str()),
+            ("response_type", "code"),
+            ("scope", scope.as_str()),
+            ("nonce", nonce),
+            ("state", state),
+        ];
+
+        if let Some(prompt) = prompt {
+            params.push(("prompt", prompt.as_str()));
+        }
+
+        let url = reqwest::Url::parse_with_params(&self.authorization_endpoint, params).map_err(
+            |err| {
+                error!(%err, self.authorization_endpoint, "Invalid OpenID authorization endpoint");
+                Error::NotProperlySetup
+            },
+        )?;
+
+        Ok(url.to_string())
     }
 
     #[tracing::instrument(skip_all)]
-    pub async fn validate_and_decode_jwt<T: DeserializeOwned>(
-        &self,
-        token: &str,
-        client_id: &str,
-    ) -> Option<T> {
+    pub async fn validate_and_decode_jwt(&self, token: &str, client_id: &str) -> Option<Value> {
         let mut decoded_token = None;
 
-        let mut validation = Validation::new(Algorithm::RS256);
+        let header = jsonwebtoken::decode_header(token).ok()?;
+
+        // ID tokens signed with the client secret are not supported, only the provider's keys
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return None;
+        }
+
+        let mut validation = Validation::new(header.alg);
         validation.set_audience(&[client_id]);
         validation.set_issuer(&[&self.issuer]);
         validation.validate_exp = true;
         validation.validate_nbf = true;
 
-        for jwk in &self.keys.borrow().jwks {
+        for jwk in self.keys.borrow().jwks.iter().filter(|jwk| {
+            header.kid.is_none() || jwk.common.key_id.is_none() || jwk.common.key_id == header.kid
+        }) {
             if let Ok(key) = DecodingKey::from_jwk(jwk) {
-                if let Ok(claims) = jsonwebtoken::decode::<T>(token, &key, &validation) {
+                if let Ok(claims) = jsonwebtoken::decode::<Value>(token, &key, &validation) {
                     decoded_token = Some(claims);
                     break;
                 }
@@ -176,26 +212,27 @@ impl OpenIdResponse {
     }
 }
 
+/// Number of seconds to wait before fetching the keys again when the provider does not specify it
+const DEFAULT_KEYS_MAX_AGE: u64 = 60 * 60;
+
 impl OpenIdKeys {
     #[tracing::instrument(skip_all)]
     async fn get_keys(certs_url: &str) -> reqwest::Result<(Self, u64)> {
         info!(certs_url, "Fetching OpenID auth keys");
         let response = reqwest::get(certs_url).await?;
 
-        let cache_header = response.headers().get("cache-control").unwrap();
-
-        let max_age = cache_header
-            .to_str()
-            .unwrap_or_default()
-            .split(',')
-            .map(|s| s.trim())
-            .find(|s| s.starts_with("max-age"))
-            .unwrap()
-            .split_once('=')
-            .unwrap()
-            .1
-            .parse()
-            .unwrap_or(0);
+        let max_age = response
+            .headers()
+            .get("cache-control")
+            .and_then(|header| header.to_str().ok())
+            .and_then(|header| {
+                header
+                    .split(',')
+                    .map(|s| s.trim())
+                    .find_map(|s| s.strip_prefix("max-age="))
+            })
+            .and_then(|max_age| max_age.parse().ok())
+            .unwrap_or(DEFAULT_KEYS_MAX_AGE);
 
         let jwks = response.json::<CertsResponse>().await?;
         let fetched_at = chrono::Utc::now().naive_utc();
@@ -227,7 +264,7 @@ impl OpenIdKeys {
 
     #[tracing::instrument(skip_all)]
     pub async fn new(openid_url: &str) -> reqwest::Result<OpenIdResponse> {
-        info!("Initial fetching OpenID auth keys");
+        info!(openid_url, "Initial fetching OpenID auth keys");
 
         let OpenIdRawResponse {
             token_endpoint,
@@ -246,13 +283,13 @@ impl OpenIdKeys {
 
                 let Ok((keys, new_max_age)) = Self::get_keys(&jwks_uri)
                     .await
-                    .map_err(|err| error!(%err, "Failed to fetch Google keys"))
+                    .map_err(|err| error!(%err, "Failed to fetch OpenID auth keys"))
                 else {
                     continue;
                 };
 
                 if tx.send(keys).is_err() {
-                    error!("Failed to send Google keys");
+                    error!("Failed to send OpenID auth keys");
                 }
 
                 max_age = new_max_age;
@@ -268,23 +305,16 @@ impl OpenIdKeys {
     }
 }
 
-#[async_trait]
-pub trait OpenIdProvider: Send {
-    async fn validate_and_decode_jwt(
-        &self,
-        token: &str,
-        client_id: &str,
-    ) -> Option<CreateUserClaims>;
-
-    fn open_id_response(&self) -> &OpenIdResponse;
-}
-
 #[derive(Deserialize, Serialize, Debug)]
 pub struct OpenIdAuthResponse {
     access_token: String,
-    expires_in: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    expires_in: Option<u64>,
     pub id_token: String,
-    scope: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    scope: Option<String>,
     token_type: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
@@ -334,26 +364,71 @@ pub struct CreateUserClaims {
 }
 
 impl CreateUserClaims {
+    /// Extracts the user's information from the ID token claims, returning `None` when the
+    /// subject or the email are missing
+    pub fn from_claims(claims: &Value, mappings: &ClaimMappings, provider: &str) -> Option<Self> {
+        let string_claim = |name: &str| claims.get(name)?.as_str().map(ToString::to_string);
+
+        let email = string_claim(&mappings.email)?;
+        let email_verified = claims
+            .get(&mappings.email_verified)
+            .and_then(|value| match value {
+                Value::Bool(verified) => Some(*verified),
+                Value::String(verified) => verified.parse().ok(),
+                _ => None,
+            });
+
+        Some(Self {
+            sub: string_claim(&mappings.subject)?,
+            name: string_claim(&mappings.name).unwrap_or_else(|| email.clone()),
+            email,
+            email_verified,
+            given_name: string_claim(&mappings.given_name),
+            family_name: string_claim(&mappings.family_name),
+            picture: string_claim(&mappings.picture),
+            locale: string_claim(&mappings.locale),
+            provider: provider.to_string(),
+        })
+    }
+
+    /// Information of a user already stored in the database, such as when refreshing a session
+    pub fn from_user(user: &User) -> Self {
+        Self {
+            sub: user.id.clone(),
+            email: user.email.clone(),
+            email_verified: Some(user.verified),
+            name: user.name.clone(),
+            given_name: user.given_name.clone(),
+            family_name: user.family_name.clone(),
+            picture: None,
+            locale: user.locale.clone(),
+            provider: user.provider.clone(),
+        }
+    }
+
     #[tracing::instrument(skip_all)]
-    pub fn generate_jwt(
+    pub async fn generate_jwt(
         self,
         jwt_manager: &JwtManager,
         roles: Vec<String>,
         id: String,
+        session_id: String,
     ) -> Result<String> {
         let claims = Claims {
             sub: id,
+            sid: session_id,
             email: self.email,
             email_verified: self.email_verified,
             name: self.name,
             given_name: self.given_name,
             family_name: self.family_name,
             locale: self.locale,
+            permissions: Role::permissions_of(&roles).into_iter().collect(),
             roles,
             provider: self.provider,
         };
 
-        jwt_manager.generate_jwt(claims)
+        jwt_manager.generate_jwt(claims).await
     }
 }
 
@@ -470,31 +545,41 @@ pub async fn logic(
     let login_request =
         validate_state_nonce(&query.state, pool, addr, user_agent, provider).await?;
 
-    let data = state
-        .auth
-        .open_id(provider)
-        .exchange_auth_code(
-            &query.code,
-            &state
-                .provider_data(provider)
-                .ok_or_else(|| Error::NotProperlySetup)?
-                .open_id_response()
-                .token_endpoint,
-        )
+    let (open_id, response) = state.provider(provider).await?;
+
+    let data = open_id
+        .exchange_auth_code(&query.code, &response.token_endpoint)
         .await?;
 
-    let Some(claims) = state
-        .provider_data(provider)
-        .ok_or_else(|| Error::NotProperlySetup)?
-        .validate_and_decode_jwt(&data.id_token, &state.auth.open_id(provider).client_id)
+    let Some(id_token) = response
+        .validate_and_decode_jwt(&data.id_token, &open_id.client_id)
         .await
     else {
         return Err(Error::Unauthorized("Invalid authentication".to_string()));
     };
 
-    let enabled = users::is_user_enabled(pool, &claims.sub)
+    if id_token.get("nonce").and_then(Value::as_str) != Some(login_request.nonce.as_str()) {
+        error!(provider, "ID token nonce does not match the login request");
+        return Err(Error::Unauthorized("Invalid authentication".to_string()));
+    }
+
+    let Some(claims) = CreateUserClaims::from_claims(&id_token, &open_id.claims, provider) else {
+        error!(
+            provider,
+            "ID token is missing the subject and/or email claims"
+        );
+        return Err(Error::Unauthorized("Invalid authentication".to_string()));
+    };
+
+    let enabled = users::is_user_enabled(pool, &claims.sub, provider)
         .await?
         .unwrap_or_default();
+    let admin = claims.email_verified == Some(true) && state.auth.is_admin_email(&claims.email);
+
+    let mut roles = open_id.roles.roles(&id_token);
+    if admin {
+        roles.insert(Role::Admin);
+    }
 
     let picture = if let Some(url) = &claims.picture {
         let response = reqwest::get(url).await?;
@@ -513,12 +598,24 @@ pub async fn logic(
         picture,
         given_name: claims.given_name.clone(),
         family_name: claims.family_name.clone(),
-        enabled,
+        enabled: enabled || admin,
         locale: claims.locale.clone(),
-        roles: Default::default(),
+        roles,
+        replace_roles: open_id.roles.source_of_truth,
         hashed_password: None,
     };
-    let user = users::create_or_update_user(pool, &create_user).await?;
+    let mut user = users::create_or_update_user(pool, &create_user).await?;
+
+    // Only set when creating the user, admins being enabled even if they were disabled
+    if admin && !user.enabled {
+        let update_user = UpdateUser {
+            enabled: Some(true),
+            roles: None,
+            owner_user_id: None,
+        };
+        users::update_user(pool, &user.id, &update_user, &user.id).await?;
+        user.enabled = true;
+    }
 
     let user_log = UserLog {
         user_id: user.id.clone(),
@@ -526,6 +623,7 @@ pub async fn logic(
         user_agent: user_agent.to_string(),
         uri: uri.to_string(),
         method: "GET".to_string(),
+        event: None,
     };
     let _ = users::create_user_log(pool, &user_log)
         .await
@@ -538,12 +636,21 @@ pub async fn logic(
         login_request.redirect_uri, state.auth.frontend_redirect_path
     );
 
-    if !enabled {
+    if !user.enabled {
         let redirect_uri = format!("{base_redirect_url}?disabled=true");
         return Ok(temporary_redirect(&redirect_uri));
     }
 
-    let jwt = claims.generate_jwt(&state.jwt_manager, user.roles, user.id)?;
+    let tokens = create_session(
+        pool,
+        &state.jwt_manager,
+        claims,
+        user.roles,
+        user.id,
+        &addr.to_string(),
+        &user_agent.to_string(),
+    )
+    .await?;
 
     let next_part = if let Some(next) = login_request.browser_state {
         format!("&next={}", next)
@@ -551,6 +658,9 @@ pub async fn logic(
         "".to_string()
     };
 
-    let redirect_uri = format!("{base_redirect_url}?token={jwt}{next_part}",);
+    let redirect_uri = format!(
+        "{base_redirect_url}?token={}&refreshToken={}{next_part}",
+        tokens.jwt_token, tokens.refresh_token
+    );
     Ok(temporary_redirect(&redirect_uri))
 }
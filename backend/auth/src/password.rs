@@ -0,0 +1,187 @@
+use chrono::Duration;
+use database::{
+    logic::{auth, users},
+    schemas::{
+        auth::{CreateUserToken, UserTokenKind},
+        users::UserWithPassword,
+    },
+    PgPool,
+};
+use shared::crypto::{hash_password, verify_password};
+
+use crate::{config::PasswordPolicy, generate_random_string, Error, Result};
+
+impl PasswordPolicy {
+    /// Ensures that the password meets every requirement of the policy
+    pub fn validate(&self, password: &str) -> Result<()> {
+        let length = password.chars().count();
+        let mut violations = Vec::new();
+
+        if length < self.min_length {
+            violations.push(format!("at least {} characters", self.min_length));
+        }
+
+        if length > self.max_length {
+            violations.push(format!("at most {} characters", self.max_length));
+        }
+
+        let requirements = [
+            (
+                self.require_lowercase,
+                "a lowercase letter",
+                char::is_lowercase as fn(char) -> bool,
+            ),
+            (
+                self.require_uppercase,
+                "an uppercase letter",
+                char::is_uppercase,
+            ),
+            (self.require_digit, "a digit", |c: char| c.is_ascii_digit()),
+            (self.require_symbol, "a symbol", |c: char| {
+                !c.is_alphanumeric()
+            }),
+        ];
+
+        for (required, description, matches) in requirements {
+            if required && !password.chars().any(matches) {
+                violations.push(description.to_string());
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::WeakPassword(format!(
+                "Password must contain {}",
+                violations.join(", ")
+            )))
+        }
+    }
+}
+
+/// Gets the local account with the email, only if the password is theirs
+pub async fn verify_user_password(
+    pool: &PgPool,
+    email: &str,
+    password: &str,
+) -> Result<Option<UserWithPassword>> {
+    let Some(user) = users::get_user_from_email(pool, email).await? else {
+        return Ok(None);
+    };
+
+    let Some(password_hash) = &user.password else {
+        return Ok(None);
+    };
+
+    let valid = verify_password(password, password_hash)?;
+
+    Ok(valid.then_some(user))
+}
+
+/// Creates a single use token sent to the user, formatted as `{id}_{secret}` like API tokens
+pub async fn create_user_token(
+    pool: &PgPool,
+    user_id: &str,
+    kind: UserTokenKind,
+    expiration: u64,
+) -> Result<String> {
+    let secret = generate_random_string(48);
+
+    let token = CreateUserToken {
+        user_id: user_id.to_string(),
+        kind,
+        token: hash_password(&secret)?,
+        expires_at: chrono::Utc::now().naive_utc() + Duration::seconds(expiration as i64),
+    };
+    let id = auth::create_user_token(pool, &token).await?;
+
+    Ok(format!("{id}_{secret}"))
+}
+
+fn invalid_user_token() -> Error {
+    Error::Unauthorized("Invalid or expired token".to_string())
+}
+
+/// Validates the token without using it, returning the ID of the user it was sent to
+pub async fn validate_user_token(
+    pool: &PgPool,
+    token: &str,
+    kind: UserTokenKind,
+) -> Result<String> {
+    let (id, secret) = token.split_once('_').ok_or_else(invalid_user_token)?;
+
+    let Some(user_token) = auth::get_valid_user_token(pool, id, kind).await? else {
+        return Err(invalid_user_token());
+    };
+
+    if !verify_password(secret, &user_token.token)? {
+        return Err(invalid_user_token());
+    }
+
+    Ok(user_token.user_id)
+}
+
+/// Counts a failed attempt at completing what the token was sent for, such as a second factor,
+/// using up the token after too many of them. Returns whether it was used up.
+pub async fn record_user_token_failure(
+    pool: &PgPool,
+    token: &str,
+    max_failed_attempts: u32,
+) -> Result<bool> {
+    let (id, _) = token.split_once('_').ok_or_else(invalid_user_token)?;
+
+    Ok(auth::record_user_token_failure(pool, id, max_failed_attempts as i32).await?)
+}
+
+/// Validates the token and marks it as used, returning the ID of the user it was sent to
+pub async fn consume_user_token(pool: &PgPool, token: &str, kind: UserTokenKind) -> Result<String> {
+    let user_id = validate_user_token(pool, token, kind).await?;
+
+    let (id, _) = token.split_once('_').ok_or_else(invalid_user_token)?;
+
+    if !auth::use_user_token(pool, id).await? {
+        return Err(invalid_user_token());
+    }
+
+    Ok(user_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_default_policy_when_validating_then_only_length_is_required() {
+        let policy = PasswordPolicy::default();
+
+        assert!(policy.validate("correct horse battery staple").is_ok());
+        assert!(matches!(
+            policy.validate("short"),
+            Err(Error::WeakPassword(message)) if message == "Password must contain at least 12 characters"
+        ));
+        assert!(policy.validate(&"a".repeat(129)).is_err());
+    }
+
+    #[test]
+    fn given_strict_policy_when_validating_then_lists_every_violation() {
+        let policy = PasswordPolicy {
+            require_lowercase: true,
+            require_uppercase: true,
+            require_digit: true,
+            require_symbol: true,
+            ..Default::default()
+        };
+
+        assert!(policy.validate("Correct horse battery staple 1").is_ok());
+        assert!(matches!(
+            policy.validate("correct horse battery staple"),
+            Err(Error::WeakPassword(message))
+                if message == "Password must contain an uppercase letter, a digit"
+        ));
+        assert!(matches!(
+            policy.validate("ÉCOLE1234567"),
+            Err(Error::WeakPassword(message))
+                if message == "Password must contain a lowercase letter, a symbol"
+        ));
+    }
+}
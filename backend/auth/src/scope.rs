@@ -0,0 +1,108 @@
+use database::schemas::{indicators::IndicatorKind, users::User};
+use ipnet::IpNet;
+use std::net::IpAddr;
+
+use crate::{Error, Result};
+
+fn parse_ip_range(range: &str) -> Option<IpNet> {
+    range
+        .parse::<IpNet>()
+        .ok()
+        .or_else(|| range.parse::<IpAddr>().ok().map(IpNet::from))
+}
+
+/// Ensures that every entry is either an IP address or a CIDR range
+pub fn validate_ip_ranges(ranges: &[String]) -> Result<()> {
+    match ranges.iter().find(|range| parse_ip_range(range).is_none()) {
+        Some(range) => Err(Error::BadRequest(format!(
+            "Invalid IP address or CIDR range: {range}"
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Whether the IP address is part of one of the IP addresses or CIDR ranges
+pub fn is_ip_allowed(ranges: &[String], ip: IpAddr) -> bool {
+    let ip = ip.to_canonical();
+
+    ranges
+        .iter()
+        .filter_map(|range| parse_ip_range(range))
+        .any(|range| range.contains(&ip))
+}
+
+/// Ensures that the user did not authenticate with a restricted API token, which could otherwise
+/// create or update API tokens without its restrictions
+pub fn require_unscoped(user: &User) -> Result<()> {
+    match user.api_token_scope {
+        Some(_) => Err(Error::OutOfScope(
+            "Restricted API token cannot manage API tokens".to_string(),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Ensures that the API token the user authenticated with, if any, can request the indicator kind
+/// from the sources. When no source is specified, they are limited to the ones of the token.
+pub fn require_request_scope(
+    user: &User,
+    kind: &IndicatorKind,
+    source_ids: &mut Vec<String>,
+) -> Result<()> {
+    let Some(scope) = &user.api_token_scope else {
+        return Ok(());
+    };
+
+    if !scope.allows_indicator_kind(kind) {
+        return Err(Error::OutOfScope(format!(
+            "API token cannot request {kind} indicators"
+        )));
+    }
+
+    let Some(allowed_source_ids) = &scope.source_ids else {
+        return Ok(());
+    };
+
+    if source_ids.is_empty() {
+        source_ids.clone_from(allowed_source_ids);
+
+        // An empty list of sources would otherwise mean all of them
+        if source_ids.is_empty() {
+            return Err(Error::OutOfScope(
+                "API token cannot request any source".to_string(),
+            ));
+        }
+    } else if let Some(source_id) = source_ids
+        .iter()
+        .find(|source_id| !allowed_source_ids.contains(source_id))
+    {
+        return Err(Error::OutOfScope(format!(
+            "API token cannot request source {source_id}"
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_given_ip_ranges_when_checking_ip_then_matches_addresses_and_cidr_ranges() {
+        let ranges = vec!["10.0.0.0/8".to_string(), "192.168.1.10".to_string()];
+
+        assert!(is_ip_allowed(&ranges, "10.1.2.3".parse().unwrap()));
+        assert!(is_ip_allowed(&ranges, "192.168.1.10".parse().unwrap()));
+        assert!(is_ip_allowed(&ranges, "::ffff:10.1.2.3".parse().unwrap()));
+        assert!(!is_ip_allowed(&ranges, "192.168.1.11".parse().unwrap()));
+        assert!(!is_ip_allowed(&[], "10.1.2.3".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_given_invalid_ip_range_when_validating_then_returns_error() {
+        assert!(validate_ip_ranges(&["10.0.0.0/8".to_string(), "::1".to_string()]).is_ok());
+        assert!(validate_ip_ranges(&["10.0.0.0/33".to_string()]).is_err());
+        assert!(validate_ip_ranges(&["localhost".to_string()]).is_err());
+    }
+}
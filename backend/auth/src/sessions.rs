@@ -0,0 +1,171 @@
+use chrono::Duration;
+use database::{
+    logic::{sessions, users},
+    schemas::sessions::CreateUserSession,
+    PgPool,
+};
+use serde::Serialize;
+use shared::crypto::{hash_password, verify_password};
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+use typeshare::typeshare;
+use utoipa::ToSchema;
+
+use crate::{generate_random_string, jwt::JwtManager, openid::CreateUserClaims, Error, Result};
+
+/// Short lived JWT authenticating the requests and the token used to get a new one
+#[derive(Serialize, ToSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct SessionTokens {
+    /// The JWT that can be used to authenticate yourself
+    pub jwt_token: String,
+    /// Single use token to get new tokens with `/auth/refresh` once the JWT expires
+    pub refresh_token: String,
+}
+
+/// Number of seconds between removals of the sessions that can no longer be refreshed
+const SWEEP_INTERVAL: u64 = 60 * 60;
+
+fn invalid_refresh_token() -> Error {
+    Error::Unauthorized("Invalid or expired refresh token".to_string())
+}
+
+/// Starts a session once the user logged in, refresh tokens being formatted as `{id}_{secret}`
+/// like API tokens
+pub async fn create_session(
+    pool: &PgPool,
+    jwt_manager: &JwtManager,
+    claims: CreateUserClaims,
+    roles: Vec<String>,
+    user_id: String,
+    ip_address: &str,
+    user_agent: &str,
+) -> Result<SessionTokens> {
+    let secret = generate_random_string(48);
+
+    let session = CreateUserSession {
+        user_id: user_id.clone(),
+        refresh_token: hash_password(&secret)?,
+        ip_address: ip_address.to_string(),
+        user_agent: user_agent.to_string(),
+        expires_at: chrono::Utc::now().naive_utc()
+            + Duration::seconds(jwt_manager.refresh_expiration() as i64),
+    };
+    let session_id = sessions::create_user_session(pool, &session).await?;
+
+    let jwt_token = claims
+        .generate_jwt(jwt_manager, roles, user_id, session_id.clone())
+        .await?;
+
+    Ok(SessionTokens {
+        jwt_token,
+        refresh_token: format!("{session_id}_{secret}"),
+    })
+}
+
+/// Replaces the refresh token by a new one, along with a new JWT reflecting the current state of
+/// the user. A refresh token used twice revokes its session, since either the client or an
+/// attacker holds a stolen copy of it.
+pub async fn refresh_session(
+    pool: &PgPool,
+    jwt_manager: &JwtManager,
+    refresh_token: &str,
+) -> Result<SessionTokens> {
+    let (id, secret) = refresh_token
+        .split_once('_')
+        .ok_or_else(invalid_refresh_token)?;
+
+    let Some(session) = sessions::get_user_session(pool, id).await? else {
+        return Err(invalid_refresh_token());
+    };
+
+    if session.revoked_at.is_some() || session.expires_at <= chrono::Utc::now().naive_utc() {
+        return Err(invalid_refresh_token());
+    }
+
+    if !verify_password(secret, &session.refresh_token)? {
+        if let Some(previous_refresh_token) = &session.previous_refresh_token {
+            if verify_password(secret, previous_refresh_token)? {
+                warn!(
+                    session_id = session.id,
+                    user_id = session.user_id,
+                    "Refresh token reused, revoking its session"
+                );
+                sessions::revoke_user_session(pool, &session.id, &session.user_id).await?;
+            }
+        }
+
+        return Err(invalid_refresh_token());
+    }
+
+    let Some(user) = users::get_user(pool, &session.user_id).await? else {
+        return Err(invalid_refresh_token());
+    };
+
+    if !user.enabled {
+        return Err(Error::Unauthorized("User is disabled".to_string()));
+    }
+
+    let new_secret = generate_random_string(48);
+
+    if !sessions::rotate_user_session_refresh_token(
+        pool,
+        &session.id,
+        &session.refresh_token,
+        &hash_password(&new_secret)?,
+    )
+    .await?
+    {
+        return Err(invalid_refresh_token());
+    }
+
+    let jwt_token = CreateUserClaims::from_user(&user)
+        .generate_jwt(jwt_manager, user.roles, user.id, session.id.clone())
+        .await?;
+
+    Ok(SessionTokens {
+        jwt_token,
+        refresh_token: format!("{}_{new_secret}", session.id),
+    })
+}
+
+/// Signs out the client holding the refresh token
+pub async fn revoke_session(pool: &PgPool, refresh_token: &str) -> Result<()> {
+    let (id, secret) = refresh_token
+        .split_once('_')
+        .ok_or_else(invalid_refresh_token)?;
+
+    let Some(session) = sessions::get_user_session(pool, id).await? else {
+        return Err(invalid_refresh_token());
+    };
+
+    if !verify_password(secret, &session.refresh_token)? {
+        return Err(invalid_refresh_token());
+    }
+
+    sessions::revoke_user_session(pool, &session.id, &session.user_id).await?;
+
+    Ok(())
+}
+
+/// Periodically removes the sessions that can no longer be refreshed
+pub fn spawn_sweeper(pool: PgPool) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(SWEEP_INTERVAL));
+
+        loop {
+            interval.tick().await;
+
+            if pool.is_closed() {
+                break;
+            }
+
+            match sessions::delete_inactive_user_sessions(&pool).await {
+                Ok(0) => {}
+                Ok(removed) => debug!(removed, "removed inactive sessions"),
+                Err(error) => warn!(?error, "failed to remove inactive sessions"),
+            }
+        }
+    })
+}
@@ -0,0 +1,89 @@
+use database::schemas::users::User;
+
+use crate::{Error, Result};
+
+/// Resolves the team owning a resource created or moved by the user and ensures they can assign
+/// resources to it. An empty team ID stands for a shared resource, without a team, and no team ID
+/// defaults to the first team of the user. Once part of a team, only users able to manage teams
+/// can create shared resources.
+pub fn require_resource_team(user: &User, team_id: Option<&str>) -> Result<Option<String>> {
+    let team_id = match team_id {
+        Some("") => None,
+        Some(team_id) => Some(team_id.to_string()),
+        None => user
+            .visible_team_ids()
+            .and_then(|team_ids| team_ids.first().cloned()),
+    };
+
+    match (&team_id, user.visible_team_ids()) {
+        (Some(team_id), _) if !user.can_access_team(Some(team_id)) => Err(Error::OutOfTeam(
+            format!("User is not a member of team {team_id}"),
+        )),
+        (None, Some(team_ids)) if !team_ids.is_empty() => Err(Error::OutOfTeam(
+            "Only users managing teams can create shared resources".to_string(),
+        )),
+        _ => Ok(team_id),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use database::schemas::users::Role;
+
+    use super::*;
+
+    fn user(roles: &[Role], team_ids: &[&str]) -> User {
+        User {
+            id: "id".to_string(),
+            created_at: Default::default(),
+            updated_at: Default::default(),
+            auth_id: None,
+            provider: "Indicator Aggregator".to_string(),
+            enabled: true,
+            email: "test@test.test".to_string(),
+            verified: true,
+            name: "Test User".to_string(),
+            given_name: None,
+            family_name: None,
+            locale: None,
+            picture: None,
+            roles: roles.iter().map(ToString::to_string).collect(),
+            last_modified_user_id: None,
+            service_account: false,
+            owner_user_id: None,
+            api_token_scope: None,
+            team_ids: team_ids.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    #[test]
+    fn test_given_team_member_when_resolving_resource_team_then_defaults_to_their_first_team() {
+        let member = user(&[Role::SourceEditor], &["first", "second"]);
+
+        assert_eq!(
+            require_resource_team(&member, None).unwrap(),
+            Some("first".to_string())
+        );
+        assert_eq!(
+            require_resource_team(&member, Some("second")).unwrap(),
+            Some("second".to_string())
+        );
+        assert!(require_resource_team(&member, Some("other")).is_err());
+        assert!(require_resource_team(&member, Some("")).is_err());
+    }
+
+    #[test]
+    fn test_given_user_without_team_or_admin_when_resolving_resource_team_then_can_share() {
+        let loner = user(&[Role::SourceEditor], &[]);
+        assert_eq!(require_resource_team(&loner, None).unwrap(), None);
+        assert!(require_resource_team(&loner, Some("team")).is_err());
+
+        let admin = user(&[Role::Admin], &["first"]);
+        assert_eq!(require_resource_team(&admin, None).unwrap(), None);
+        assert_eq!(require_resource_team(&admin, Some("")).unwrap(), None);
+        assert_eq!(
+            require_resource_team(&admin, Some("other")).unwrap(),
+            Some("other".to_string())
+        );
+    }
+}
@@ -0,0 +1,554 @@
+use cache::{Cache, CacheImpl, CacheKey, CreateCacheEntry, PostgresCacheConfig};
+use chrono::NaiveDateTime;
+use database::{
+    logic::users,
+    schemas::users::{UserLog, UserLogEvent},
+    PgPool,
+};
+use serde::{Deserialize, Serialize};
+use std::{net::SocketAddr, time::Duration};
+use tracing::warn;
+use typeshare::typeshare;
+use utoipa::ToSchema;
+
+use crate::{config::LoginThrottling, Error, Result};
+
+/// Recent failed logins of an account or an IP address
+#[derive(Serialize, Deserialize, ToSchema, Debug, Default, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct FailedLogins {
+    /// Number of failed logins since the last successful one, or since they were forgotten
+    pub attempts: u32,
+    /// Time until which logins are refused, if locked
+    pub locked_until: Option<NaiveDateTime>,
+}
+
+impl FailedLogins {
+    /// Number of seconds until logins are allowed again, if locked
+    fn remaining_lockout(&self, now: NaiveDateTime) -> Option<u64> {
+        self.locked_until
+            .map(|locked_until| (locked_until - now).num_seconds())
+            .filter(|seconds| *seconds > 0)
+            .map(|seconds| seconds as u64)
+    }
+}
+
+/// Request logging in a local account, either to the login endpoint or with Basic authentication
+pub struct LoginAttempt<'a> {
+    pub email: &'a str,
+    pub addr: SocketAddr,
+    pub user_agent: String,
+    pub uri: String,
+    pub method: String,
+}
+
+impl LoginAttempt<'_> {
+    fn account_key(&self) -> CacheKey {
+        account_key(self.email)
+    }
+
+    fn mfa_key(&self) -> CacheKey {
+        mfa_key(self.email)
+    }
+
+    fn ip_key(&self) -> CacheKey {
+        vec![
+            "failed_logins".to_string(),
+            "ip".to_string(),
+            self.addr.ip().to_string(),
+        ]
+        .into()
+    }
+}
+
+fn account_key(email: &str) -> CacheKey {
+    vec![
+        "failed_logins".to_string(),
+        "account".to_string(),
+        email.to_lowercase(),
+    ]
+    .into()
+}
+
+/// Counted apart from the failed logins of the account since a valid password forgets them
+fn mfa_key(email: &str) -> CacheKey {
+    vec![
+        "failed_logins".to_string(),
+        "mfa".to_string(),
+        email.to_lowercase(),
+    ]
+    .into()
+}
+
+/// Number of failed logins, incremented atomically apart from the failed logins stored under the
+/// key once locked
+fn attempts_key(key: &CacheKey) -> CacheKey {
+    let mut key = key.clone();
+    key.0.push("attempts".to_string());
+    key
+}
+
+impl LoginThrottling {
+    /// Delay of a login after the number of failed ones, doubling with every failed login
+    fn delay(&self, attempts: u32) -> Duration {
+        if attempts == 0 {
+            return Duration::ZERO;
+        }
+
+        let delay = self
+            .base_delay
+            .saturating_mul(2u64.saturating_pow(attempts - 1))
+            .min(self.max_delay);
+
+        Duration::from_millis(delay)
+    }
+}
+
+/// Table of the failed logins, kept apart from the cache entries so that filling or clearing the
+/// cache never forgets them
+pub const TABLE: &str = "login_throttling_entries";
+
+/// Tracks the failed logins per account and per IP address in their own cache, delaying the logins
+/// following failed ones and locking the accounts and IP addresses with too many of them
+#[derive(Clone)]
+pub struct LoginThrottle {
+    config: LoginThrottling,
+    cache: CacheImpl,
+}
+
+impl LoginThrottle {
+    pub fn new(config: LoginThrottling, cache: CacheImpl) -> Self {
+        Self { config, cache }
+    }
+
+    /// Throttle storing the failed logins in their own table, shared by every server instance and
+    /// never evicted
+    pub fn postgres(config: LoginThrottling, pool: PgPool) -> Self {
+        let cache = CacheImpl::postgres_table(
+            pool,
+            TABLE,
+            PostgresCacheConfig {
+                sweep_interval: Some(config.sweep_interval),
+            },
+        );
+
+        Self::new(config, cache)
+    }
+
+    /// Starts the background removal of the expired failed logins
+    pub fn spawn_sweeper(&self) {
+        self.cache.spawn_sweeper();
+    }
+
+    /// Gets the failed logins that locked the keys, or the number of failed logins otherwise
+    async fn get_failed_logins(&self, keys: Vec<CacheKey>) -> Result<Vec<FailedLogins>> {
+        let attempts = self
+            .cache
+            .clone()
+            .get_many::<u32, _>(keys.iter().map(attempts_key).collect())
+            .await?;
+        let locked = self.cache.clone().get_many::<FailedLogins, _>(keys).await?;
+
+        Ok(locked
+            .into_iter()
+            .zip(attempts)
+            .map(|(locked, attempts)| match locked {
+                Some(locked) => locked.value,
+                None => FailedLogins {
+                    attempts: attempts.map(|entry| entry.value).unwrap_or_default(),
+                    locked_until: None,
+                },
+            })
+            .collect())
+    }
+
+    /// Forgets the failed logins of the key, unlocking it, returning whether it had any
+    async fn forget(&self, key: CacheKey) -> Result<bool> {
+        let attempts = self.cache.clone().invalidate(attempts_key(&key)).await?;
+        let locked = self.cache.clone().invalidate(key).await?;
+
+        Ok(attempts || locked)
+    }
+
+    /// Refuses the login while the account or the IP address is locked, otherwise delays it
+    /// based on their recent failed logins
+    pub async fn check(&self, attempt: &LoginAttempt<'_>) -> Result<()> {
+        self.check_keys(vec![attempt.account_key(), attempt.ip_key()])
+            .await
+    }
+
+    /// Refuses the second factor while the account is locked after too many invalid ones,
+    /// otherwise delays it based on them
+    pub async fn check_mfa(&self, attempt: &LoginAttempt<'_>) -> Result<()> {
+        self.check_keys(vec![attempt.mfa_key()]).await
+    }
+
+    async fn check_keys(&self, keys: Vec<CacheKey>) -> Result<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        let failed_logins = self.get_failed_logins(keys).await?;
+
+        let now = chrono::Utc::now().naive_utc();
+        if let Some(seconds) = failed_logins
+            .iter()
+            .filter_map(|failed_logins| failed_logins.remaining_lockout(now))
+            .max()
+        {
+            return Err(Error::LockedOut(seconds));
+        }
+
+        let attempts = failed_logins
+            .iter()
+            .map(|failed_logins| failed_logins.attempts)
+            .max()
+            .unwrap_or_default();
+        let delay = self.config.delay(attempts);
+
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+
+        Ok(())
+    }
+
+    /// Counts a failed login, returning whether it locked the account or the IP address. The
+    /// count is incremented atomically so that concurrent failed logins are all counted, and
+    /// starts over once locked since the lockout is stored apart.
+    async fn increment(&self, key: CacheKey, max_attempts: u32) -> Result<bool> {
+        let attempts = self
+            .cache
+            .clone()
+            .increment(
+                attempts_key(&key),
+                Some(self.config.attempts_window as usize),
+            )
+            .await?;
+
+        if attempts < max_attempts as u64 {
+            return Ok(false);
+        }
+
+        let failed_logins = FailedLogins {
+            attempts: attempts as u32,
+            locked_until: Some(
+                chrono::Utc::now().naive_utc()
+                    + chrono::Duration::seconds(self.config.lockout_duration as i64),
+            ),
+        };
+
+        self.cache
+            .clone()
+            .set(CreateCacheEntry {
+                key: key.clone(),
+                value: failed_logins,
+                expiration: Some(self.config.lockout_duration as usize),
+            })
+            .await?;
+        self.cache.clone().invalidate(attempts_key(&key)).await?;
+
+        Ok(true)
+    }
+
+    /// Counts a failed login of the account and the IP address, returning whether it locked the
+    /// account
+    async fn count_failure(&self, attempt: &LoginAttempt<'_>) -> Result<bool> {
+        if self
+            .increment(attempt.ip_key(), self.config.max_ip_attempts)
+            .await?
+        {
+            warn!(ip = %attempt.addr.ip(), "IP address locked after too many failed logins");
+        }
+
+        self.increment(attempt.account_key(), self.config.max_account_attempts)
+            .await
+    }
+
+    /// Counts a failed login, logging the lockout of the account to its user logs
+    pub async fn record_failure(&self, pool: &PgPool, attempt: &LoginAttempt<'_>) -> Result<()> {
+        if !self.config.enabled || !self.count_failure(attempt).await? {
+            return Ok(());
+        }
+
+        warn!(
+            email = attempt.email,
+            "Account locked after too many failed logins"
+        );
+
+        log_lockout(pool, attempt).await
+    }
+
+    /// Counts an invalid second factor, logging the lockout of the account to its user logs
+    pub async fn record_mfa_failure(
+        &self,
+        pool: &PgPool,
+        attempt: &LoginAttempt<'_>,
+    ) -> Result<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        if !self
+            .increment(attempt.mfa_key(), self.config.max_account_attempts)
+            .await?
+        {
+            return Ok(());
+        }
+
+        warn!(
+            email = attempt.email,
+            "Account locked after too many invalid second factors"
+        );
+
+        log_lockout(pool, attempt).await
+    }
+
+    /// Forgets the invalid second factors of the account once one succeeded
+    pub async fn record_mfa_success(&self, attempt: &LoginAttempt<'_>) -> Result<()> {
+        if self.config.enabled {
+            self.forget(attempt.mfa_key()).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Forgets the failed logins of the account once one succeeded, the ones of the IP address
+    /// being kept since an attacker could otherwise reset them with their own account
+    pub async fn record_success(&self, attempt: &LoginAttempt<'_>) -> Result<()> {
+        if self.config.enabled {
+            self.forget(attempt.account_key()).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Gets the recent failed logins, or invalid second factors, of the account with the email,
+    /// whichever locked it or is the closest to
+    pub async fn get_account_failed_logins(&self, email: &str) -> Result<FailedLogins> {
+        Ok(self
+            .get_failed_logins(vec![account_key(email), mfa_key(email)])
+            .await?
+            .into_iter()
+            .max_by_key(|failed_logins| (failed_logins.locked_until, failed_logins.attempts))
+            .unwrap_or_default())
+    }
+
+    /// Unlocks the account with the email and forgets its failed logins and invalid second
+    /// factors, returning whether it had any
+    pub async fn unlock_account(&self, email: &str) -> Result<bool> {
+        let logins = self.forget(account_key(email)).await?;
+        let mfa = self.forget(mfa_key(email)).await?;
+
+        Ok(logins || mfa)
+    }
+}
+
+/// Logs the lockout of the account to its user logs
+async fn log_lockout(pool: &PgPool, attempt: &LoginAttempt<'_>) -> Result<()> {
+    if let Some(user) = users::get_user_from_email(pool, attempt.email).await? {
+        let user_log = UserLog {
+            user_id: user.id,
+            ip_address: attempt.addr.to_string(),
+            user_agent: attempt.user_agent.clone(),
+            uri: attempt.uri.clone(),
+            method: attempt.method.clone(),
+            event: Some(UserLogEvent::AccountLocked),
+        };
+        users::create_user_log(pool, &user_log).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn throttle(max_account_attempts: u32, max_ip_attempts: u32) -> LoginThrottle {
+        LoginThrottle::new(
+            LoginThrottling {
+                max_account_attempts,
+                max_ip_attempts,
+                base_delay: 0,
+                ..Default::default()
+            },
+            CacheImpl::in_memory(),
+        )
+    }
+
+    fn attempt<'a>(email: &'a str, ip: &str) -> LoginAttempt<'a> {
+        LoginAttempt {
+            email,
+            addr: SocketAddr::new(ip.parse().unwrap(), 1234),
+            user_agent: "test".to_string(),
+            uri: "/api/v1/auth/login".to_string(),
+            method: "POST".to_string(),
+        }
+    }
+
+    #[test]
+    fn given_failed_logins_when_computing_delay_then_doubles_up_to_max() {
+        let config = LoginThrottling::default();
+
+        assert_eq!(config.delay(0), Duration::ZERO);
+        assert_eq!(config.delay(1), Duration::from_millis(250));
+        assert_eq!(config.delay(3), Duration::from_millis(1000));
+        assert_eq!(config.delay(10), Duration::from_millis(5000));
+        assert_eq!(config.delay(u32::MAX), Duration::from_millis(5000));
+    }
+
+    #[tokio::test]
+    async fn given_too_many_failed_logins_when_checking_then_account_is_locked_until_unlocked() {
+        let throttle = throttle(3, 100);
+        let first_ip = attempt("Jane@Example.com", "10.0.0.1");
+        let other_ip = attempt("jane@example.com", "10.0.0.2");
+
+        assert!(!throttle.count_failure(&first_ip).await.unwrap());
+        assert!(!throttle.count_failure(&first_ip).await.unwrap());
+        throttle.check(&first_ip).await.unwrap();
+        assert!(throttle.count_failure(&first_ip).await.unwrap());
+
+        assert!(matches!(
+            throttle.check(&other_ip).await,
+            Err(Error::LockedOut(seconds)) if seconds > 0
+        ));
+
+        assert!(throttle.unlock_account("jane@example.com").await.unwrap());
+        throttle.check(&other_ip).await.unwrap();
+        assert_eq!(
+            throttle
+                .get_account_failed_logins("jane@example.com")
+                .await
+                .unwrap(),
+            FailedLogins::default()
+        );
+    }
+
+    #[tokio::test]
+    async fn given_failed_logins_on_many_accounts_when_checking_then_ip_is_locked() {
+        let throttle = throttle(100, 2);
+
+        throttle
+            .count_failure(&attempt("first@example.com", "10.0.0.1"))
+            .await
+            .unwrap();
+        throttle
+            .count_failure(&attempt("second@example.com", "10.0.0.1"))
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            throttle
+                .check(&attempt("third@example.com", "10.0.0.1"))
+                .await,
+            Err(Error::LockedOut(_))
+        ));
+        throttle
+            .check(&attempt("third@example.com", "10.0.0.2"))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn given_concurrent_failed_logins_when_counting_then_every_one_is_counted() {
+        let throttle = throttle(100, 100);
+        // Only used to log lockouts, which do not happen below the maximum number of attempts
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+
+        let handles: Vec<_> = (0..50)
+            .map(|_| {
+                let throttle = throttle.clone();
+                let pool = pool.clone();
+                tokio::spawn(async move {
+                    let attempt = attempt("jane@example.com", "10.0.0.1");
+                    throttle.count_failure(&attempt).await.unwrap();
+                    throttle.record_mfa_failure(&pool, &attempt).await.unwrap();
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        let attempt = attempt("jane@example.com", "10.0.0.1");
+        let failed_logins = throttle
+            .get_failed_logins(vec![
+                attempt.account_key(),
+                attempt.mfa_key(),
+                attempt.ip_key(),
+            ])
+            .await
+            .unwrap();
+
+        assert!(failed_logins
+            .iter()
+            .all(|failed_logins| failed_logins.attempts == 50));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn given_concurrent_failed_logins_when_reaching_max_then_account_is_locked() {
+        let throttle = throttle(5, 100);
+
+        let handles: Vec<_> = (0..5)
+            .map(|_| {
+                let throttle = throttle.clone();
+                tokio::spawn(async move {
+                    throttle
+                        .count_failure(&attempt("jane@example.com", "10.0.0.1"))
+                        .await
+                        .unwrap()
+                })
+            })
+            .collect();
+
+        let mut locked = 0;
+        for handle in handles {
+            locked += handle.await.unwrap() as u32;
+        }
+
+        assert_eq!(locked, 1);
+        assert!(matches!(
+            throttle
+                .check(&attempt("jane@example.com", "10.0.0.2"))
+                .await,
+            Err(Error::LockedOut(_))
+        ));
+        assert_eq!(
+            throttle
+                .get_account_failed_logins("jane@example.com")
+                .await
+                .unwrap()
+                .attempts,
+            5
+        );
+    }
+
+    #[tokio::test]
+    async fn given_successful_login_when_recording_then_only_account_failures_are_forgotten() {
+        let throttle = throttle(100, 100);
+        let attempt = attempt("jane@example.com", "10.0.0.1");
+
+        throttle.count_failure(&attempt).await.unwrap();
+        throttle.record_success(&attempt).await.unwrap();
+
+        assert_eq!(
+            throttle
+                .get_account_failed_logins("jane@example.com")
+                .await
+                .unwrap()
+                .attempts,
+            0
+        );
+        assert_eq!(
+            throttle
+                .get_failed_logins(vec![attempt.ip_key()])
+                .await
+                .unwrap()[0]
+                .attempts,
+            1
+        );
+    }
+}
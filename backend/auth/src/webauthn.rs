@@ -0,0 +1,490 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ciborium::Value;
+use database::schemas::mfa::WebAuthnCredential;
+use p256::{
+    ecdsa::{signature::Verifier, Signature, VerifyingKey},
+    EncodedPoint,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use typeshare::typeshare;
+use utoipa::ToSchema;
+
+use crate::{Error, Result};
+
+/// COSE identifier of ECDSA with P-256 and SHA-256, the only algorithm supported
+const COSE_ALGORITHM_ES256: i32 = -7;
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_ATTESTED_CREDENTIAL_DATA: u8 = 0x40;
+/// Number of milliseconds the user has to use their security key
+const TIMEOUT: u64 = 60_000;
+
+/// Options to pass to `navigator.credentials.create`, binary values being base64 URL encoded
+#[derive(Serialize, ToSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct WebAuthnCreationOptions {
+    pub challenge: String,
+    pub rp_id: String,
+    pub rp_name: String,
+    pub user_id: String,
+    pub user_name: String,
+    pub user_display_name: String,
+    /// COSE identifiers of the supported public key algorithms
+    pub algorithms: Vec<i32>,
+    /// Credentials already registered by the user
+    pub exclude_credentials: Vec<String>,
+    pub timeout: u64,
+}
+
+/// Options to pass to `navigator.credentials.get`, binary values being base64 URL encoded
+#[derive(Serialize, ToSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct WebAuthnRequestOptions {
+    pub challenge: String,
+    pub rp_id: String,
+    /// Credentials registered by the user
+    pub allow_credentials: Vec<String>,
+    pub timeout: u64,
+}
+
+/// Security key created with `navigator.credentials.create`, binary values being base64 URL
+/// encoded
+#[derive(Deserialize, ToSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct WebAuthnRegistration {
+    /// Name given by the user to the security key
+    pub name: String,
+    pub client_data_json: String,
+    pub attestation_object: String,
+}
+
+/// Signature of the challenge returned by `navigator.credentials.get`, binary values being base64
+/// URL encoded
+#[derive(Deserialize, ToSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct WebAuthnAssertion {
+    pub credential_id: String,
+    pub client_data_json: String,
+    pub authenticator_data: String,
+    pub signature: String,
+}
+
+/// Credential of a security key whose registration was verified
+#[derive(Debug)]
+pub struct VerifiedCredential {
+    /// Base64 URL encoded ID of the credential
+    pub credential_id: String,
+    /// SEC1 encoded P-256 public key
+    pub public_key: Vec<u8>,
+    pub sign_count: u32,
+}
+
+#[derive(Deserialize)]
+struct ClientData {
+    #[serde(rename = "type")]
+    kind: String,
+    challenge: String,
+    origin: String,
+}
+
+struct AuthenticatorData<'a> {
+    rp_id_hash: &'a [u8],
+    flags: u8,
+    sign_count: u32,
+    attested_credential_data: &'a [u8],
+}
+
+impl<'a> AuthenticatorData<'a> {
+    fn parse(data: &'a [u8]) -> Result<Self> {
+        if data.len() < 37 {
+            return Err(Error::BadRequest(
+                "Invalid WebAuthn authenticator data".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            rp_id_hash: &data[..32],
+            flags: data[32],
+            sign_count: u32::from_be_bytes([data[33], data[34], data[35], data[36]]),
+            attested_credential_data: &data[37..],
+        })
+    }
+}
+
+fn decode(value: &str, name: &str) -> Result<Vec<u8>> {
+    URL_SAFE_NO_PAD
+        .decode(value.trim_end_matches('='))
+        .map_err(|_| Error::BadRequest(format!("Invalid base64 URL encoded WebAuthn {name}")))
+}
+
+fn cbor_map_get<'a>(map: &'a [(Value, Value)], key: &Value) -> Option<&'a Value> {
+    map.iter().find(|(k, _)| k == key).map(|(_, value)| value)
+}
+
+/// Extracts the public key from a COSE encoded ES256 key
+fn parse_cose_key(data: &[u8]) -> Result<Vec<u8>> {
+    let invalid = || {
+        Error::BadRequest("Unsupported WebAuthn public key, only ES256 is supported".to_string())
+    };
+
+    let key: Value = ciborium::from_reader(data).map_err(|_| invalid())?;
+    let map = key.as_map().ok_or_else(invalid)?;
+
+    let integer = |key: i64| cbor_map_get(map, &Value::from(key));
+    let kty = integer(1).and_then(Value::as_integer).map(i128::from);
+    let alg = integer(3).and_then(Value::as_integer).map(i128::from);
+    let crv = integer(-1).and_then(Value::as_integer).map(i128::from);
+
+    if kty != Some(2) || alg != Some(COSE_ALGORITHM_ES256 as i128) || crv != Some(1) {
+        return Err(invalid());
+    }
+
+    let x = integer(-2).and_then(Value::as_bytes).ok_or_else(invalid)?;
+    let y = integer(-3).and_then(Value::as_bytes).ok_or_else(invalid)?;
+
+    if x.len() != 32 || y.len() != 32 {
+        return Err(invalid());
+    }
+
+    let point = EncodedPoint::from_affine_coordinates(x[..].into(), y[..].into(), false);
+    VerifyingKey::from_encoded_point(&point).map_err(|_| invalid())?;
+
+    Ok(point.as_bytes().to_vec())
+}
+
+/// The frontend relying on security keys to authenticate users, its ID being its host
+pub struct RelyingParty {
+    pub id: String,
+    pub name: String,
+    pub origin: String,
+}
+
+impl RelyingParty {
+    /// Relying party of the frontend at `origin`, such as `https://example.com`
+    pub fn new(origin: &str, name: &str) -> Result<Self> {
+        let uri: http::Uri = origin
+            .parse()
+            .map_err(|_| Error::BadRequest("Invalid origin".to_string()))?;
+        let id = uri
+            .host()
+            .ok_or(Error::BadRequest("Invalid origin host".to_string()))?;
+
+        Ok(Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            origin: origin.to_string(),
+        })
+    }
+
+    /// Options to register a new security key, `challenge` being a token only usable once
+    pub fn creation_options(
+        &self,
+        challenge: &str,
+        user_id: &str,
+        email: &str,
+        name: &str,
+        credentials: &[WebAuthnCredential],
+    ) -> WebAuthnCreationOptions {
+        WebAuthnCreationOptions {
+            challenge: URL_SAFE_NO_PAD.encode(challenge),
+            rp_id: self.id.clone(),
+            rp_name: self.name.clone(),
+            user_id: URL_SAFE_NO_PAD.encode(user_id),
+            user_name: email.to_string(),
+            user_display_name: name.to_string(),
+            algorithms: vec![COSE_ALGORITHM_ES256],
+            exclude_credentials: credentials
+                .iter()
+                .map(|credential| credential.credential_id.clone())
+                .collect(),
+            timeout: TIMEOUT,
+        }
+    }
+
+    /// Options to sign `challenge`, a token only usable once, with a registered security key
+    pub fn request_options(
+        &self,
+        challenge: &str,
+        credentials: &[WebAuthnCredential],
+    ) -> WebAuthnRequestOptions {
+        WebAuthnRequestOptions {
+            challenge: URL_SAFE_NO_PAD.encode(challenge),
+            rp_id: self.id.clone(),
+            allow_credentials: credentials
+                .iter()
+                .map(|credential| credential.credential_id.clone())
+                .collect(),
+            timeout: TIMEOUT,
+        }
+    }
+
+    /// Returns the challenge signed by the security key, once the client data is validated
+    pub fn client_challenge(&self, client_data_json: &str, kind: &str) -> Result<String> {
+        let client_data: ClientData =
+            serde_json::from_slice(&decode(client_data_json, "client data")?)?;
+
+        if client_data.kind != kind {
+            return Err(Error::BadRequest(format!(
+                "Invalid WebAuthn client data type, expected {kind}"
+            )));
+        }
+
+        if client_data.origin != self.origin {
+            return Err(Error::Unauthorized("Invalid WebAuthn origin".to_string()));
+        }
+
+        String::from_utf8(decode(&client_data.challenge, "challenge")?)
+            .map_err(|_| Error::Unauthorized("Invalid WebAuthn challenge".to_string()))
+    }
+
+    fn verify_authenticator_data(&self, data: &AuthenticatorData) -> Result<()> {
+        if data.rp_id_hash != Sha256::digest(&self.id).as_slice() {
+            return Err(Error::Unauthorized(
+                "WebAuthn credential of another relying party".to_string(),
+            ));
+        }
+
+        if data.flags & FLAG_USER_PRESENT == 0 {
+            return Err(Error::Unauthorized("WebAuthn user not present".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Verifies a security key registration, the attestation of the authenticator not being
+    /// verified since none is requested. The challenge needs to be checked beforehand with
+    /// [`RelyingParty::client_challenge`].
+    pub fn verify_registration(
+        &self,
+        registration: &WebAuthnRegistration,
+    ) -> Result<VerifiedCredential> {
+        let invalid = || Error::BadRequest("Invalid WebAuthn attestation object".to_string());
+
+        let attestation: Value = ciborium::from_reader(
+            &decode(&registration.attestation_object, "attestation object")?[..],
+        )
+        .map_err(|_| invalid())?;
+        let raw_authenticator_data = attestation
+            .as_map()
+            .and_then(|map| cbor_map_get(map, &Value::from("authData")))
+            .and_then(Value::as_bytes)
+            .ok_or_else(invalid)?;
+
+        let authenticator_data = AuthenticatorData::parse(raw_authenticator_data)?;
+        self.verify_authenticator_data(&authenticator_data)?;
+
+        if authenticator_data.flags & FLAG_ATTESTED_CREDENTIAL_DATA == 0 {
+            return Err(invalid());
+        }
+
+        // AAGUID of 16 bytes followed by the length of the credential ID on 2 bytes
+        let data = authenticator_data.attested_credential_data;
+        if data.len() < 18 {
+            return Err(invalid());
+        }
+
+        let credential_id_length = u16::from_be_bytes([data[16], data[17]]) as usize;
+        if data.len() < 18 + credential_id_length {
+            return Err(invalid());
+        }
+
+        Ok(VerifiedCredential {
+            credential_id: URL_SAFE_NO_PAD.encode(&data[18..18 + credential_id_length]),
+            public_key: parse_cose_key(&data[18 + credential_id_length..])?,
+            sign_count: authenticator_data.sign_count,
+        })
+    }
+
+    /// Verifies the signature of a registered security key, returning its new signature counter.
+    /// The challenge needs to be checked beforehand with [`RelyingParty::client_challenge`].
+    pub fn verify_assertion(
+        &self,
+        credential: &WebAuthnCredential,
+        assertion: &WebAuthnAssertion,
+    ) -> Result<u32> {
+        let raw_authenticator_data = decode(&assertion.authenticator_data, "authenticator data")?;
+        let authenticator_data = AuthenticatorData::parse(&raw_authenticator_data)?;
+        self.verify_authenticator_data(&authenticator_data)?;
+
+        let public_key = VerifyingKey::from_sec1_bytes(&credential.public_key)
+            .map_err(|_| Error::BadRequest("Invalid stored WebAuthn public key".to_string()))?;
+        let signature = Signature::from_der(&decode(&assertion.signature, "signature")?)
+            .map_err(|_| Error::BadRequest("Invalid WebAuthn signature".to_string()))?;
+
+        let mut message = raw_authenticator_data.clone();
+        message.extend(Sha256::digest(decode(
+            &assertion.client_data_json,
+            "client data",
+        )?));
+
+        public_key
+            .verify(&message, &signature)
+            .map_err(|_| Error::Unauthorized("Invalid WebAuthn signature".to_string()))?;
+
+        // Authenticators not implementing the counter always return 0, otherwise a counter not
+        // increasing hints at a cloned security key
+        let sign_count = authenticator_data.sign_count;
+        if (sign_count != 0 || credential.sign_count != 0)
+            && i64::from(sign_count) <= credential.sign_count
+        {
+            return Err(Error::Unauthorized(
+                "WebAuthn signature counter did not increase".to_string(),
+            ));
+        }
+
+        Ok(sign_count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p256::ecdsa::{signature::Signer, SigningKey};
+    use serde_json::json;
+
+    use super::*;
+
+    const ORIGIN: &str = "https://indicators.example.com";
+    const CHALLENGE: &str = "token-id_token-secret";
+
+    fn client_data_json(kind: &str) -> String {
+        let client_data = json!({
+            "type": kind,
+            "challenge": URL_SAFE_NO_PAD.encode(CHALLENGE),
+            "origin": ORIGIN,
+        });
+
+        URL_SAFE_NO_PAD.encode(client_data.to_string())
+    }
+
+    fn authenticator_data(flags: u8, sign_count: u32) -> Vec<u8> {
+        let mut data = Sha256::digest("indicators.example.com").to_vec();
+        data.push(flags);
+        data.extend(sign_count.to_be_bytes());
+        data
+    }
+
+    fn registration(key: &SigningKey) -> WebAuthnRegistration {
+        let point = key.verifying_key().to_encoded_point(false);
+        let cose_key = Value::Map(vec![
+            (Value::from(1), Value::from(2)),
+            (Value::from(3), Value::from(COSE_ALGORITHM_ES256)),
+            (Value::from(-1), Value::from(1)),
+            (Value::from(-2), Value::Bytes(point.x().unwrap().to_vec())),
+            (Value::from(-3), Value::Bytes(point.y().unwrap().to_vec())),
+        ]);
+
+        let mut data = authenticator_data(FLAG_USER_PRESENT | FLAG_ATTESTED_CREDENTIAL_DATA, 1);
+        data.extend([0; 16]);
+        data.extend(4u16.to_be_bytes());
+        data.extend([1, 2, 3, 4]);
+        ciborium::into_writer(&cose_key, &mut data).unwrap();
+
+        let attestation = Value::Map(vec![
+            (Value::from("fmt"), Value::from("none")),
+            (Value::from("attStmt"), Value::Map(vec![])),
+            (Value::from("authData"), Value::Bytes(data)),
+        ]);
+        let mut attestation_object = Vec::new();
+        ciborium::into_writer(&attestation, &mut attestation_object).unwrap();
+
+        WebAuthnRegistration {
+            name: "Security key".to_string(),
+            client_data_json: client_data_json("webauthn.create"),
+            attestation_object: URL_SAFE_NO_PAD.encode(attestation_object),
+        }
+    }
+
+    fn assertion(key: &SigningKey, sign_count: u32) -> WebAuthnAssertion {
+        let client_data_json = client_data_json("webauthn.get");
+        let data = authenticator_data(FLAG_USER_PRESENT, sign_count);
+
+        let mut message = data.clone();
+        message.extend(Sha256::digest(
+            URL_SAFE_NO_PAD.decode(&client_data_json).unwrap(),
+        ));
+        let signature: Signature = key.sign(&message);
+
+        WebAuthnAssertion {
+            credential_id: URL_SAFE_NO_PAD.encode([1, 2, 3, 4]),
+            client_data_json,
+            authenticator_data: URL_SAFE_NO_PAD.encode(data),
+            signature: URL_SAFE_NO_PAD.encode(signature.to_der()),
+        }
+    }
+
+    fn stored_credential(verified: VerifiedCredential) -> WebAuthnCredential {
+        WebAuthnCredential {
+            id: "id".to_string(),
+            created_at: Default::default(),
+            updated_at: Default::default(),
+            name: "Security key".to_string(),
+            credential_id: verified.credential_id,
+            public_key: verified.public_key,
+            sign_count: verified.sign_count.into(),
+            last_used_at: None,
+            user_id: "user".to_string(),
+        }
+    }
+
+    #[test]
+    fn given_registered_security_key_when_verifying_assertion_then_returns_new_counter() {
+        let rp = RelyingParty::new(ORIGIN, "Indicator Aggregator").unwrap();
+        let key = SigningKey::random(&mut rand::rngs::OsRng);
+
+        let registration = registration(&key);
+        assert_eq!(
+            rp.client_challenge(&registration.client_data_json, "webauthn.create")
+                .unwrap(),
+            CHALLENGE
+        );
+        let verified = rp.verify_registration(&registration).unwrap();
+        assert_eq!(verified.credential_id, URL_SAFE_NO_PAD.encode([1, 2, 3, 4]));
+
+        let credential = stored_credential(verified);
+        let assertion = assertion(&key, 2);
+        assert_eq!(
+            rp.client_challenge(&assertion.client_data_json, "webauthn.get")
+                .unwrap(),
+            CHALLENGE
+        );
+        assert_eq!(rp.verify_assertion(&credential, &assertion).unwrap(), 2);
+    }
+
+    #[test]
+    fn given_other_security_key_when_verifying_assertion_then_returns_error() {
+        let rp = RelyingParty::new(ORIGIN, "Indicator Aggregator").unwrap();
+        let key = SigningKey::random(&mut rand::rngs::OsRng);
+        let other_key = SigningKey::random(&mut rand::rngs::OsRng);
+
+        let credential = stored_credential(rp.verify_registration(&registration(&key)).unwrap());
+
+        assert!(rp
+            .verify_assertion(&credential, &assertion(&other_key, 2))
+            .is_err());
+    }
+
+    #[test]
+    fn given_counter_not_increasing_when_verifying_assertion_then_returns_error() {
+        let rp = RelyingParty::new(ORIGIN, "Indicator Aggregator").unwrap();
+        let key = SigningKey::random(&mut rand::rngs::OsRng);
+
+        let credential = stored_credential(rp.verify_registration(&registration(&key)).unwrap());
+
+        assert!(rp
+            .verify_assertion(&credential, &assertion(&key, 1))
+            .is_err());
+    }
+
+    #[test]
+    fn given_other_origin_when_getting_client_challenge_then_returns_error() {
+        let rp = RelyingParty::new("https://other.example.com", "Indicator Aggregator").unwrap();
+
+        assert!(rp
+            .client_challenge(&client_data_json("webauthn.get"), "webauthn.get")
+            .is_err());
+    }
+}
@@ -1,43 +1,286 @@
 use async_trait::async_trait;
-use serde::de::DeserializeOwned;
-use std::{collections::HashMap, sync::Arc};
-use tokio::sync::RwLock;
-use tracing::instrument;
+use chrono::NaiveDateTime;
+use serde::{de::DeserializeOwned, Deserialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{sync::RwLock, task::JoinHandle};
+use tracing::{debug, instrument};
+
+use crate::{
+    Cache, CacheCounters, CacheEntry, CacheKey, CacheStats, CreateCacheEntry, InnerCache, Result,
+    SerializedCacheEntry,
+};
+
+/// Limits of the in memory cache, once reached the least recently used entries are evicted
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct InMemoryCacheConfig {
+    /// Maximum number of entries kept in the cache
+    pub max_entries: Option<usize>,
+    /// Maximum total size in bytes of the keys and values kept in the cache
+    pub max_bytes: Option<usize>,
+    /// Interval in seconds between the removal of expired entries, if not set expired entries
+    /// are only removed when accessed
+    pub sweep_interval: Option<u64>,
+}
 
-use crate::{Cache, CacheEntry, CacheKey, InnerCache, Result};
+impl InMemoryCacheConfig {
+    fn is_exceeded(&self, entries: usize, size: usize) -> bool {
+        self.max_entries.is_some_and(|max| entries > max)
+            || self.max_bytes.is_some_and(|max| size > max)
+    }
+}
 
-#[derive(Default, Clone)]
-pub struct InMemoryCacheInner(HashMap<CacheKey, Vec<u8>>);
+fn entry_size(key: &CacheKey, data: &[u8]) -> usize {
+    key.0.iter().map(String::len).sum::<usize>() + data.len()
+}
+
+struct InMemoryEntry {
+    data: Vec<u8>,
+    expires_at: Option<NaiveDateTime>,
+    last_used: u64,
+}
+
+impl InMemoryEntry {
+    fn is_expired(&self, now: NaiveDateTime) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at < now)
+    }
+}
+
+#[derive(Default)]
+struct InMemoryCacheInner {
+    entries: HashMap<CacheKey, InMemoryEntry>,
+    /// Keys of the entries, ordered from the least to the most recently used
+    usage: BTreeMap<u64, CacheKey>,
+    tick: u64,
+    size: usize,
+}
+
+impl InMemoryCacheInner {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn insert(&mut self, key: CacheKey, data: Vec<u8>, expires_at: Option<NaiveDateTime>) -> bool {
+        let already_exists = self.remove(&key).is_some();
+        let last_used = self.next_tick();
+
+        self.size += entry_size(&key, &data);
+        self.usage.insert(last_used, key.clone());
+        self.entries.insert(
+            key,
+            InMemoryEntry {
+                data,
+                expires_at,
+                last_used,
+            },
+        );
+
+        already_exists
+    }
+
+    fn touch(&mut self, key: &CacheKey) -> Option<&InMemoryEntry> {
+        let tick = self.next_tick();
+        let entry = self.entries.get_mut(key)?;
+
+        self.usage.remove(&entry.last_used);
+        self.usage.insert(tick, key.clone());
+        entry.last_used = tick;
+
+        Some(entry)
+    }
+
+    fn remove(&mut self, key: &CacheKey) -> Option<InMemoryEntry> {
+        let entry = self.entries.remove(key)?;
+
+        self.usage.remove(&entry.last_used);
+        self.size -= entry_size(key, &entry.data);
+
+        Some(entry)
+    }
+
+    fn remove_where(&mut self, predicate: impl Fn(&CacheKey, &InMemoryEntry) -> bool) -> u64 {
+        let keys: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|(key, entry)| predicate(key, entry))
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        for key in &keys {
+            self.remove(key);
+        }
+
+        keys.len() as u64
+    }
+
+    fn evict(&mut self, config: &InMemoryCacheConfig) -> u64 {
+        let mut evicted = 0;
+
+        while config.is_exceeded(self.entries.len(), self.size) {
+            let Some((_, key)) = self.usage.pop_first() else {
+                break;
+            };
+
+            self.remove(&key);
+            evicted += 1;
+        }
+
+        evicted
+    }
+}
 
 #[derive(Default, Clone)]
-pub struct InMemoryCache(Arc<RwLock<InMemoryCacheInner>>);
+pub struct InMemoryCache {
+    inner: Arc<RwLock<InMemoryCacheInner>>,
+    counters: Arc<CacheCounters>,
+    config: InMemoryCacheConfig,
+}
 
 impl InMemoryCache {
     pub fn new() -> Self {
         Self::default()
     }
+
+    pub fn with_config(config: InMemoryCacheConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Inserts the entry, evicting the least recently used entries once the limits are exceeded
+    fn insert(&self, inner: &mut InMemoryCacheInner, entry: SerializedCacheEntry) -> bool {
+        let expires_at = entry
+            .expiration
+            .and_then(|expiration| chrono::Duration::try_seconds(expiration as i64))
+            .map(|expiration| chrono::Utc::now().naive_utc() + expiration);
+
+        let already_exists = inner.insert(entry.key, entry.data, expires_at);
+
+        let evicted = inner.evict(&self.config);
+        if evicted > 0 {
+            self.counters.evicted(evicted);
+            debug!(evicted, "evicted least recently used cache entries");
+        }
+
+        already_exists
+    }
+
+    /// Periodically removes expired entries from the cache, stopping once every clone of the
+    /// cache has been dropped
+    pub fn spawn_sweeper(&self) -> Option<JoinHandle<()>> {
+        let period = self
+            .config
+            .sweep_interval
+            .filter(|interval| *interval > 0)
+            .map(Duration::from_secs)?;
+        let inner = Arc::downgrade(&self.inner);
+        let counters = self.counters.clone();
+
+        Some(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+
+            loop {
+                interval.tick().await;
+
+                let Some(inner) = inner.upgrade() else {
+                    break;
+                };
+
+                let now = chrono::Utc::now().naive_utc();
+                let expired = inner
+                    .write()
+                    .await
+                    .remove_where(|_, entry| entry.is_expired(now));
+
+                if expired > 0 {
+                    counters.expired(expired);
+                    debug!(expired, "removed expired cache entries");
+                }
+            }
+        }))
+    }
 }
 
 #[async_trait]
 impl InnerCache for InMemoryCache {
     #[instrument(skip_all)]
-    async fn set_inner(&mut self, key: CacheKey, entry: Vec<u8>) -> Result<bool> {
-        let data = self.0.write().await.0.insert(key, entry);
+    async fn set_inner(&mut self, entry: SerializedCacheEntry) -> Result<bool> {
+        let mut inner = self.inner.write().await;
 
-        Ok(data.is_some())
+        Ok(self.insert(&mut inner, entry))
     }
 
     #[instrument(skip_all)]
     async fn get_inner(&self, key: CacheKey) -> Result<Option<Vec<u8>>> {
-        Ok(self.0.read().await.0.get(&key).cloned())
+        let mut inner = self.inner.write().await;
+
+        let now = chrono::Utc::now().naive_utc();
+        if inner
+            .entries
+            .get(&key)
+            .is_some_and(|entry| entry.is_expired(now))
+        {
+            inner.remove(&key);
+            self.counters.expired(1);
+            self.counters.miss();
+
+            return Ok(None);
+        }
+
+        let data = inner.touch(&key).map(|entry| entry.data.clone());
+
+        if data.is_some() {
+            self.counters.hit();
+        } else {
+            self.counters.miss();
+        }
+
+        Ok(data)
     }
 }
 
 #[async_trait]
 impl Cache for InMemoryCache {
+    #[instrument(skip_all)]
+    async fn increment<S: Into<CacheKey> + Send>(
+        &mut self,
+        key: S,
+        expiration: Option<usize>,
+    ) -> Result<u64> {
+        let key = key.into();
+        let now = chrono::Utc::now().naive_utc();
+
+        // Held from the read to the write so that concurrent increments are not lost
+        let mut inner = self.inner.write().await;
+
+        let value = match inner.entries.get(&key) {
+            Some(entry) if !entry.is_expired(now) => {
+                serde_json::from_slice::<CacheEntry<u64>>(&entry.data)?.value + 1
+            }
+            _ => 1,
+        };
+
+        let entry = SerializedCacheEntry::new(
+            CreateCacheEntry {
+                key,
+                value,
+                expiration,
+            },
+            now,
+        )?;
+        self.insert(&mut inner, entry);
+
+        Ok(value)
+    }
+
     #[instrument(skip_all)]
     async fn invalidate<S: Into<CacheKey> + Send>(&mut self, key: S) -> Result<bool> {
-        Ok(self.0.write().await.0.remove(&key.into()).is_some())
+        Ok(self.inner.write().await.remove(&key.into()).is_some())
     }
 
     #[instrument(skip_all)]
@@ -47,29 +290,28 @@ impl Cache for InMemoryCache {
     ) -> Result<u64> {
         let partial_key = partial_key.into();
 
-        let mut keys = Vec::new();
-
-        for key in self.0.read().await.0.keys() {
-            if key.matches_partial_cache_key(&partial_key) {
-                keys.push(key.clone());
-                self.0.write().await.0.remove(key);
-            }
-        }
-
-        Ok(keys.len() as u64)
+        Ok(self
+            .inner
+            .write()
+            .await
+            .remove_where(|key, _| key.matches_partial_cache_key(&partial_key)))
     }
 
     #[instrument(skip_all)]
     async fn clear(&mut self) -> Result<u64> {
-        let len = self.0.read().await.0.len();
-        self.0.write().await.0.clear();
+        let mut inner = self.inner.write().await;
+        let len = inner.entries.len();
+
+        inner.entries.clear();
+        inner.usage.clear();
+        inner.size = 0;
 
         Ok(len as u64)
     }
 
     #[instrument(skip_all)]
     async fn keys(&self) -> Result<Vec<CacheKey>> {
-        Ok(self.0.read().await.0.keys().cloned().collect())
+        Ok(self.inner.read().await.entries.keys().cloned().collect())
     }
 
     #[instrument(skip_all)]
@@ -78,18 +320,29 @@ impl Cache for InMemoryCache {
     ) -> Result<HashMap<CacheKey, CacheEntry<T>>> {
         let mut data = HashMap::new();
 
-        for (key, value) in &self.0.read().await.0 {
-            data.insert(key.clone(), serde_json::from_slice(value)?);
+        for (key, entry) in &self.inner.read().await.entries {
+            data.insert(key.clone(), serde_json::from_slice(&entry.data)?);
         }
 
         Ok(data)
     }
+
+    #[instrument(skip_all)]
+    async fn stats(&self) -> Result<CacheStats> {
+        let inner = self.inner.read().await;
+
+        Ok(self.counters.stats(CacheStats {
+            entries: inner.entries.len() as u64,
+            size_bytes: inner.size as u64,
+            max_entries: self.config.max_entries.map(|max| max as u64),
+            max_bytes: self.config.max_bytes.map(|max| max as u64),
+            ..Default::default()
+        }))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::CreateCacheEntry;
-
     use super::*;
 
     #[tokio::test]
@@ -161,4 +414,192 @@ mod tests {
 
         assert!(result.is_none());
     }
+
+    #[tokio::test]
+    async fn test_partial_invalidate() {
+        let mut cache = InMemoryCache::new();
+
+        for key in [vec!["a", "1"], vec!["a", "2"], vec!["b", "1"]] {
+            let entry = CreateCacheEntry {
+                key,
+                value: serde_json::json!({}),
+                expiration: None,
+            };
+
+            cache.set(entry).await.unwrap();
+        }
+
+        let result = cache.partial_invalidate("a").await.unwrap();
+
+        assert_eq!(2, result);
+
+        let keys = cache.keys().await.unwrap();
+
+        assert_eq!(vec![CacheKey::from(vec!["b", "1"])], keys);
+    }
+
+    async fn set_entries(cache: &mut InMemoryCache, keys: &[&str], expiration: Option<usize>) {
+        for key in keys {
+            let entry = CreateCacheEntry {
+                key: *key,
+                value: serde_json::json!({}),
+                expiration,
+            };
+
+            cache.set(entry).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_max_entries_eviction() {
+        let mut cache = InMemoryCache::with_config(InMemoryCacheConfig {
+            max_entries: Some(2),
+            ..Default::default()
+        });
+
+        set_entries(&mut cache, &["a", "b"], None).await;
+
+        cache
+            .get::<serde_json::Value, _>("a")
+            .await
+            .unwrap()
+            .unwrap();
+
+        set_entries(&mut cache, &["c"], None).await;
+
+        let mut keys = cache.keys().await.unwrap();
+        keys.sort_by_key(|key| key.0.clone());
+
+        assert_eq!(vec![CacheKey::from("a"), CacheKey::from("c")], keys);
+        assert_eq!(1, cache.stats().await.unwrap().evictions);
+    }
+
+    #[tokio::test]
+    async fn test_max_bytes_eviction() {
+        let mut cache = InMemoryCache::new();
+        set_entries(&mut cache, &["a"], None).await;
+        let entry_size = cache.stats().await.unwrap().size_bytes as usize;
+
+        let mut cache = InMemoryCache::with_config(InMemoryCacheConfig {
+            max_bytes: Some(entry_size * 2),
+            ..Default::default()
+        });
+
+        set_entries(&mut cache, &["a", "b", "c"], None).await;
+
+        let stats = cache.stats().await.unwrap();
+
+        assert_eq!(2, stats.entries);
+        assert!(stats.size_bytes as usize <= entry_size * 2);
+        assert!(cache
+            .get::<serde_json::Value, _>("a")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_sweeper() {
+        let mut cache = InMemoryCache::with_config(InMemoryCacheConfig {
+            sweep_interval: Some(1),
+            ..Default::default()
+        });
+
+        set_entries(&mut cache, &["a"], Some(1)).await;
+        set_entries(&mut cache, &["b"], None).await;
+
+        let sweeper = cache.spawn_sweeper().unwrap();
+
+        tokio::time::sleep(tokio::time::Duration::from_millis(2500)).await;
+
+        assert_eq!(vec![CacheKey::from("b")], cache.keys().await.unwrap());
+        assert_eq!(1, cache.stats().await.unwrap().expirations);
+
+        drop(cache);
+        tokio::time::timeout(tokio::time::Duration::from_secs(2), sweeper)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_increments() {
+        let cache = InMemoryCache::new();
+
+        let handles: Vec<_> = (0..50)
+            .map(|_| {
+                let mut cache = cache.clone();
+                tokio::spawn(async move { cache.increment("counter", Some(60)).await.unwrap() })
+            })
+            .collect();
+
+        let mut values = Vec::new();
+        for handle in handles {
+            values.push(handle.await.unwrap());
+        }
+        values.sort();
+
+        assert_eq!((1..=50).collect::<Vec<u64>>(), values);
+        assert_eq!(
+            50,
+            cache
+                .clone()
+                .get::<u64, _>("counter")
+                .await
+                .unwrap()
+                .unwrap()
+                .value
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stats() {
+        let mut cache = InMemoryCache::new();
+
+        set_entries(&mut cache, &["a"], None).await;
+
+        cache.get::<serde_json::Value, _>("a").await.unwrap();
+        cache.get::<serde_json::Value, _>("b").await.unwrap();
+        cache.get::<serde_json::Value, _>("c").await.unwrap();
+
+        let stats = cache.stats().await.unwrap();
+
+        assert_eq!(1, stats.entries);
+        assert_eq!(1, stats.hits);
+        assert_eq!(2, stats.misses);
+        assert_eq!(0, stats.evictions);
+    }
+
+    #[tokio::test]
+    async fn test_batch() {
+        let mut cache = InMemoryCache::new();
+
+        let entries = ["a", "b"]
+            .into_iter()
+            .map(|key| CreateCacheEntry {
+                key,
+                value: serde_json::json!({ "key": key }),
+                expiration: None,
+            })
+            .collect();
+
+        cache.set_many(entries).await.unwrap();
+
+        let result = cache
+            .get_many::<serde_json::Value, _>(vec!["a", "c", "b"])
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.map(|entry| entry.value))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                Some(serde_json::json!({ "key": "a" })),
+                None,
+                Some(serde_json::json!({ "key": "b" })),
+            ],
+            result
+        );
+    }
 }
@@ -1,27 +1,76 @@
 mod memory;
+mod postgres;
 mod redis;
+mod tiered;
 
 use async_trait::async_trait;
 use serde::de::DeserializeOwned;
+use sqlx::PgPool;
 use std::collections::HashMap;
 use tracing::instrument;
 
-use crate::{Cache, CacheEntry, CacheKey, InnerCache, Result};
+pub use memory::InMemoryCacheConfig;
+pub use postgres::PostgresCacheConfig;
+
+use crate::{Cache, CacheEntry, CacheKey, CacheStats, InnerCache, Result, SerializedCacheEntry};
 
 #[derive(Clone)]
 pub enum CacheImpl {
     InMemory(memory::InMemoryCache),
     Redis(redis::RedisCache),
+    Postgres(postgres::PostgresCache),
+    Tiered(tiered::TieredCache),
 }
 
 impl CacheImpl {
-    pub fn redis(url: &str) -> Result<Self> {
-        Ok(Self::Redis(redis::RedisCache::new(url)?))
+    pub fn redis(url: &str, key_prefix: Option<String>) -> Result<Self> {
+        Ok(Self::Redis(redis::RedisCache::new(url, key_prefix)?))
     }
 
     pub fn in_memory() -> Self {
         Self::InMemory(memory::InMemoryCache::new())
     }
+
+    pub fn bounded_in_memory(config: InMemoryCacheConfig) -> Self {
+        Self::InMemory(memory::InMemoryCache::with_config(config))
+    }
+
+    pub fn postgres(pool: PgPool, config: PostgresCacheConfig) -> Self {
+        Self::Postgres(postgres::PostgresCache::new(pool, config))
+    }
+
+    /// Postgres cache storing its entries in the given table, which needs the same columns as
+    /// `cache_entries`, keeping them apart from the entries of the other caches
+    pub fn postgres_table(pool: PgPool, table: &'static str, config: PostgresCacheConfig) -> Self {
+        Self::Postgres(postgres::PostgresCache::with_table(pool, table, config))
+    }
+
+    /// In memory cache in front of the Postgres cache, keeping the most recently used entries in
+    /// memory while persisting all of them
+    pub fn tiered(
+        memory_config: InMemoryCacheConfig,
+        pool: PgPool,
+        postgres_config: PostgresCacheConfig,
+    ) -> Self {
+        Self::Tiered(tiered::TieredCache::new(
+            memory::InMemoryCache::with_config(memory_config),
+            postgres::PostgresCache::new(pool, postgres_config),
+        ))
+    }
+
+    /// Starts the background removal of expired entries, Redis expiring entries by itself
+    pub fn spawn_sweeper(&self) {
+        match self {
+            Self::InMemory(cache) => {
+                cache.spawn_sweeper();
+            }
+            Self::Redis(_) => {}
+            Self::Postgres(cache) => {
+                cache.spawn_sweeper();
+            }
+            Self::Tiered(cache) => cache.spawn_sweepers(),
+        }
+    }
 }
 
 impl From<memory::InMemoryCache> for CacheImpl {
@@ -36,13 +85,27 @@ impl From<redis::RedisCache> for CacheImpl {
     }
 }
 
+impl From<postgres::PostgresCache> for CacheImpl {
+    fn from(cache: postgres::PostgresCache) -> Self {
+        Self::Postgres(cache)
+    }
+}
+
+impl From<tiered::TieredCache> for CacheImpl {
+    fn from(cache: tiered::TieredCache) -> Self {
+        Self::Tiered(cache)
+    }
+}
+
 #[async_trait]
 impl InnerCache for CacheImpl {
     #[instrument(skip_all)]
-    async fn set_inner(&mut self, key: CacheKey, entry: Vec<u8>) -> Result<bool> {
+    async fn set_inner(&mut self, entry: SerializedCacheEntry) -> Result<bool> {
         match self {
-            Self::InMemory(cache) => cache.set_inner(key, entry).await,
-            Self::Redis(cache) => cache.set_inner(key, entry).await,
+            Self::InMemory(cache) => cache.set_inner(entry).await,
+            Self::Redis(cache) => cache.set_inner(entry).await,
+            Self::Postgres(cache) => cache.set_inner(entry).await,
+            Self::Tiered(cache) => cache.set_inner(entry).await,
         }
     }
 
@@ -51,17 +114,55 @@ impl InnerCache for CacheImpl {
         match self {
             Self::InMemory(cache) => cache.get_inner(key).await,
             Self::Redis(cache) => cache.get_inner(key).await,
+            Self::Postgres(cache) => cache.get_inner(key).await,
+            Self::Tiered(cache) => cache.get_inner(key).await,
+        }
+    }
+
+    #[instrument(skip_all)]
+    async fn set_many_inner(&mut self, entries: Vec<SerializedCacheEntry>) -> Result<()> {
+        match self {
+            Self::InMemory(cache) => cache.set_many_inner(entries).await,
+            Self::Redis(cache) => cache.set_many_inner(entries).await,
+            Self::Postgres(cache) => cache.set_many_inner(entries).await,
+            Self::Tiered(cache) => cache.set_many_inner(entries).await,
+        }
+    }
+
+    #[instrument(skip_all)]
+    async fn get_many_inner(&self, keys: Vec<CacheKey>) -> Result<Vec<Option<Vec<u8>>>> {
+        match self {
+            Self::InMemory(cache) => cache.get_many_inner(keys).await,
+            Self::Redis(cache) => cache.get_many_inner(keys).await,
+            Self::Postgres(cache) => cache.get_many_inner(keys).await,
+            Self::Tiered(cache) => cache.get_many_inner(keys).await,
         }
     }
 }
 
 #[async_trait]
 impl Cache for CacheImpl {
+    #[instrument(skip_all)]
+    async fn increment<S: Into<CacheKey> + Send>(
+        &mut self,
+        key: S,
+        expiration: Option<usize>,
+    ) -> Result<u64> {
+        match self {
+            Self::InMemory(cache) => cache.increment(key, expiration).await,
+            Self::Redis(cache) => cache.increment(key, expiration).await,
+            Self::Postgres(cache) => cache.increment(key, expiration).await,
+            Self::Tiered(cache) => cache.increment(key, expiration).await,
+        }
+    }
+
     #[instrument(skip_all)]
     async fn invalidate<S: Into<CacheKey> + Send>(&mut self, key: S) -> Result<bool> {
         match self {
             Self::InMemory(cache) => cache.invalidate(key).await,
             Self::Redis(cache) => cache.invalidate(key).await,
+            Self::Postgres(cache) => cache.invalidate(key).await,
+            Self::Tiered(cache) => cache.invalidate(key).await,
         }
     }
 
@@ -73,6 +174,8 @@ impl Cache for CacheImpl {
         match self {
             Self::InMemory(cache) => cache.partial_invalidate(partial_key).await,
             Self::Redis(cache) => cache.partial_invalidate(partial_key).await,
+            Self::Postgres(cache) => cache.partial_invalidate(partial_key).await,
+            Self::Tiered(cache) => cache.partial_invalidate(partial_key).await,
         }
     }
 
@@ -81,6 +184,8 @@ impl Cache for CacheImpl {
         match self {
             Self::InMemory(cache) => cache.clear().await,
             Self::Redis(cache) => cache.clear().await,
+            Self::Postgres(cache) => cache.clear().await,
+            Self::Tiered(cache) => cache.clear().await,
         }
     }
 
@@ -89,6 +194,8 @@ impl Cache for CacheImpl {
         match self {
             Self::InMemory(cache) => cache.keys().await,
             Self::Redis(cache) => cache.keys().await,
+            Self::Postgres(cache) => cache.keys().await,
+            Self::Tiered(cache) => cache.keys().await,
         }
     }
 
@@ -99,6 +206,18 @@ impl Cache for CacheImpl {
         match self {
             Self::InMemory(cache) => cache.get_all().await,
             Self::Redis(cache) => cache.get_all().await,
+            Self::Postgres(cache) => cache.get_all().await,
+            Self::Tiered(cache) => cache.get_all().await,
+        }
+    }
+
+    #[instrument(skip_all)]
+    async fn stats(&self) -> Result<CacheStats> {
+        match self {
+            Self::InMemory(cache) => cache.stats().await,
+            Self::Redis(cache) => cache.stats().await,
+            Self::Postgres(cache) => cache.stats().await,
+            Self::Tiered(cache) => cache.stats().await,
         }
     }
 }
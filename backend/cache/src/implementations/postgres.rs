@@ -0,0 +1,482 @@
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Deserialize};
+use sqlx::{types::Json, PgPool};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::task::JoinHandle;
+use tracing::{debug, instrument, warn};
+
+use crate::{
+    Cache, CacheCounters, CacheEntry, CacheKey, CacheStats, CreateCacheEntry, InnerCache, Result,
+    SerializedCacheEntry,
+};
+
+/// Configuration of the Postgres cache, persisting entries across restarts
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct PostgresCacheConfig {
+    /// Interval in seconds between the removal of expired entries, if not set expired entries
+    /// are kept in the table but never returned
+    pub sweep_interval: Option<u64>,
+}
+
+/// Table of the entries of the cache, unless another one is given
+const DEFAULT_TABLE: &str = "cache_entries";
+
+/// Cached data along with the number of seconds left before it expires, if it expires
+pub(crate) type EntryWithTtl = (Vec<u8>, Option<u32>);
+
+#[derive(Clone)]
+pub struct PostgresCache {
+    pool: PgPool,
+    /// Table holding the entries, with the same columns as `cache_entries`
+    table: &'static str,
+    counters: Arc<CacheCounters>,
+    config: PostgresCacheConfig,
+}
+
+impl PostgresCache {
+    pub fn new(pool: PgPool, config: PostgresCacheConfig) -> Self {
+        Self::with_table(pool, DEFAULT_TABLE, config)
+    }
+
+    /// Cache storing its entries in the given table, apart from the entries of the other caches
+    pub fn with_table(pool: PgPool, table: &'static str, config: PostgresCacheConfig) -> Self {
+        Self {
+            pool,
+            table,
+            counters: Default::default(),
+            config,
+        }
+    }
+
+    /// Periodically removes expired entries from the table, stopping once the pool is closed
+    pub fn spawn_sweeper(&self) -> Option<JoinHandle<()>> {
+        let period = self
+            .config
+            .sweep_interval
+            .filter(|interval| *interval > 0)
+            .map(Duration::from_secs)?;
+        let pool = self.pool.clone();
+        let table = self.table;
+        let counters = self.counters.clone();
+
+        Some(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+
+            loop {
+                interval.tick().await;
+
+                if pool.is_closed() {
+                    break;
+                }
+
+                match delete_expired(&pool, table).await {
+                    Ok(0) => {}
+                    Ok(expired) => {
+                        counters.expired(expired);
+                        debug!(expired, "removed expired cache entries");
+                    }
+                    Err(error) => warn!(?error, "failed to remove expired cache entries"),
+                }
+            }
+        }))
+    }
+
+    /// Gets the valid entries in the same order as the keys, along with their remaining time to
+    /// live, allowing another cache layered in front of this one to expire them at the same time
+    #[instrument(skip_all)]
+    pub(crate) async fn get_many_with_ttl(
+        &self,
+        keys: &[CacheKey],
+    ) -> Result<Vec<Option<EntryWithTtl>>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let rows: Vec<(Option<Vec<u8>>, Option<i32>)> = sqlx::query_as(&format!(
+            r#"SELECT "{table}"."data", CEIL(EXTRACT(EPOCH FROM "{table}"."expires_at" - NOW()))::INT
+FROM JSONB_ARRAY_ELEMENTS($1) WITH ORDINALITY AS "keys" ("key", "index")
+LEFT JOIN "{table}" ON "{table}"."key" = ARRAY(SELECT JSONB_ARRAY_ELEMENTS_TEXT("keys"."key"))
+    AND ("{table}"."expires_at" IS NULL OR "{table}"."expires_at" > NOW())
+ORDER BY "keys"."index""#,
+            table = self.table
+        ))
+        .bind(Json(keys))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(data, ttl)| {
+                if data.is_some() {
+                    self.counters.hit();
+                } else {
+                    self.counters.miss();
+                }
+
+                data.map(|data| (data, ttl.map(|ttl| ttl.max(0) as u32)))
+            })
+            .collect())
+    }
+}
+
+async fn delete_expired(pool: &PgPool, table: &str) -> Result<u64> {
+    let result = sqlx::query(&format!(
+        r#"DELETE FROM "{table}" WHERE "expires_at" <= NOW()"#
+    ))
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+async fn upsert<'a, E: sqlx::PgExecutor<'a>>(
+    executor: E,
+    table: &str,
+    entry: SerializedCacheEntry,
+) -> Result<bool> {
+    // xmax is only set when the row was updated instead of inserted
+    let already_exists = sqlx::query_scalar(&format!(
+        r#"INSERT INTO "{table}" ("key", "data", "expires_at")
+VALUES ($1, $2, NOW() + $3::INT * INTERVAL '1 second')
+ON CONFLICT ("key") DO UPDATE SET "data" = EXCLUDED."data", "expires_at" = EXCLUDED."expires_at", "created_at" = NOW()
+RETURNING "xmax"::TEXT <> '0'"#
+    ))
+    .bind(entry.key.0)
+    .bind(entry.data)
+    .bind(entry.expiration.map(|expiration| expiration as i32))
+    .fetch_one(executor)
+    .await?;
+
+    Ok(already_exists)
+}
+
+#[async_trait]
+impl InnerCache for PostgresCache {
+    #[instrument(skip_all)]
+    async fn set_inner(&mut self, entry: SerializedCacheEntry) -> Result<bool> {
+        upsert(&self.pool, self.table, entry).await
+    }
+
+    #[instrument(skip_all)]
+    async fn get_inner(&self, key: CacheKey) -> Result<Option<Vec<u8>>> {
+        Ok(self
+            .get_many_with_ttl(&[key])
+            .await?
+            .pop()
+            .flatten()
+            .map(|(data, _)| data))
+    }
+
+    #[instrument(skip_all)]
+    async fn set_many_inner(&mut self, entries: Vec<SerializedCacheEntry>) -> Result<()> {
+        let mut transaction = self.pool.begin().await?;
+
+        for entry in entries {
+            upsert(&mut *transaction, self.table, entry).await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all)]
+    async fn get_many_inner(&self, keys: Vec<CacheKey>) -> Result<Vec<Option<Vec<u8>>>> {
+        Ok(self
+            .get_many_with_ttl(&keys)
+            .await?
+            .into_iter()
+            .map(|entry| entry.map(|(data, _)| data))
+            .collect())
+    }
+}
+
+#[async_trait]
+impl Cache for PostgresCache {
+    /// Increments the value of the JSON entry within the upsert, which locks the row so that
+    /// concurrent increments wait for each other
+    #[instrument(skip_all)]
+    async fn increment<S: Into<CacheKey> + Send>(
+        &mut self,
+        key: S,
+        expiration: Option<usize>,
+    ) -> Result<u64> {
+        let entry = SerializedCacheEntry::new(
+            CreateCacheEntry {
+                key,
+                value: 1u64,
+                expiration,
+            },
+            chrono::Utc::now().naive_utc(),
+        )?;
+
+        let value: i64 = sqlx::query_scalar(&format!(
+            r#"INSERT INTO "{table}" ("key", "data", "expires_at")
+VALUES ($1, $2, NOW() + $3::INT * INTERVAL '1 second')
+ON CONFLICT ("key") DO UPDATE SET "data" = CASE
+        WHEN "{table}"."expires_at" <= NOW() THEN EXCLUDED."data"
+        ELSE CONVERT_TO(JSONB_SET(
+            CONVERT_FROM(EXCLUDED."data", 'UTF8')::JSONB,
+            '{{value}}',
+            TO_JSONB((CONVERT_FROM("{table}"."data", 'UTF8')::JSONB ->> 'value')::BIGINT + 1)
+        )::TEXT, 'UTF8')
+    END,
+    "expires_at" = EXCLUDED."expires_at",
+    "created_at" = NOW()
+RETURNING (CONVERT_FROM("data", 'UTF8')::JSONB ->> 'value')::BIGINT"#,
+            table = self.table
+        ))
+        .bind(entry.key.0)
+        .bind(entry.data)
+        .bind(entry.expiration.map(|expiration| expiration as i32))
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(value as u64)
+    }
+
+    #[instrument(skip_all)]
+    async fn invalidate<S: Into<CacheKey> + Send>(&mut self, key: S) -> Result<bool> {
+        let result = sqlx::query(&format!(r#"DELETE FROM "{}" WHERE "key" = $1"#, self.table))
+            .bind(key.into().0)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(skip_all)]
+    async fn partial_invalidate<S: Into<CacheKey> + Send>(
+        &mut self,
+        partial_key: S,
+    ) -> Result<u64> {
+        let result = sqlx::query(&format!(
+            r#"DELETE FROM "{}" WHERE "key"[1:CARDINALITY($1::TEXT[])] = $1"#,
+            self.table
+        ))
+        .bind(partial_key.into().0)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    #[instrument(skip_all)]
+    async fn clear(&mut self) -> Result<u64> {
+        let result = sqlx::query(&format!(r#"DELETE FROM "{}""#, self.table))
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    #[instrument(skip_all)]
+    async fn keys(&self) -> Result<Vec<CacheKey>> {
+        let keys: Vec<Vec<String>> = sqlx::query_scalar(&format!(
+            r#"SELECT "key" FROM "{}" WHERE "expires_at" IS NULL OR "expires_at" > NOW()"#,
+            self.table
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(keys.into_iter().map(CacheKey).collect())
+    }
+
+    #[instrument(skip_all)]
+    async fn get_all<T: DeserializeOwned + Send>(
+        &self,
+    ) -> Result<HashMap<CacheKey, CacheEntry<T>>> {
+        let rows: Vec<(Vec<String>, Vec<u8>)> = sqlx::query_as(&format!(
+            r#"SELECT "key", "data" FROM "{}" WHERE "expires_at" IS NULL OR "expires_at" > NOW()"#,
+            self.table
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut data = HashMap::new();
+
+        for (key, value) in rows {
+            data.insert(CacheKey(key), serde_json::from_slice(&value)?);
+        }
+
+        Ok(data)
+    }
+
+    #[instrument(skip_all)]
+    async fn stats(&self) -> Result<CacheStats> {
+        let (entries, size_bytes): (i64, i64) = sqlx::query_as(&format!(
+            r#"SELECT COUNT(*), COALESCE(SUM(OCTET_LENGTH("data")), 0)::BIGINT FROM "{}" WHERE "expires_at" IS NULL OR "expires_at" > NOW()"#,
+            self.table
+        ))
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(self.counters.stats(CacheStats {
+            entries: entries as u64,
+            size_bytes: size_bytes as u64,
+            ..Default::default()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn set_entries(
+        cache: &mut PostgresCache,
+        keys: Vec<Vec<&str>>,
+        expiration: Option<usize>,
+    ) {
+        for key in keys {
+            let entry = CreateCacheEntry {
+                key,
+                value: serde_json::json!({}),
+                expiration,
+            };
+
+            cache.set(entry).await.unwrap();
+        }
+    }
+
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn test_given(pool: PgPool) {
+        let mut cache = PostgresCache::new(pool, Default::default());
+
+        let key = vec!["test", "URL", "http://example.com"];
+        let value = serde_json::json!({"test": "test"});
+
+        let entry = CreateCacheEntry {
+            key: key.clone(),
+            value: value.clone(),
+            expiration: None,
+        };
+
+        assert!(!cache.set(entry).await.unwrap().already_exists);
+
+        let result = cache
+            .get::<serde_json::Value, _>(key.clone())
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(value, result.value);
+        assert_eq!(1, cache.get_all::<serde_json::Value>().await.unwrap().len());
+
+        assert!(cache.invalidate(key.clone()).await.unwrap());
+        assert!(cache
+            .get::<serde_json::Value, _>(key)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn test_expiration(pool: PgPool) {
+        let mut cache = PostgresCache::new(
+            pool,
+            PostgresCacheConfig {
+                sweep_interval: Some(1),
+            },
+        );
+
+        set_entries(&mut cache, vec![vec!["a"]], Some(1)).await;
+        set_entries(&mut cache, vec![vec!["b"]], None).await;
+
+        let sweeper = cache.spawn_sweeper().unwrap();
+
+        tokio::time::sleep(tokio::time::Duration::from_millis(2500)).await;
+        sweeper.abort();
+
+        assert!(cache
+            .get::<serde_json::Value, _>("a")
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(vec![CacheKey::from("b")], cache.keys().await.unwrap());
+
+        let stats = cache.stats().await.unwrap();
+
+        assert_eq!(1, stats.entries);
+        assert_eq!(1, stats.expirations);
+    }
+
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn test_partial_invalidate(pool: PgPool) {
+        let mut cache = PostgresCache::new(pool, Default::default());
+
+        set_entries(
+            &mut cache,
+            vec![vec!["a", "1"], vec!["a", "2"], vec!["ab", "1"]],
+            None,
+        )
+        .await;
+
+        assert_eq!(2, cache.partial_invalidate("a").await.unwrap());
+        assert_eq!(
+            vec![CacheKey::from(vec!["ab", "1"])],
+            cache.keys().await.unwrap()
+        );
+        assert_eq!(1, cache.clear().await.unwrap());
+    }
+
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn test_concurrent_increments(pool: PgPool) {
+        let cache = PostgresCache::new(pool, Default::default());
+
+        let handles: Vec<_> = (0..20)
+            .map(|_| {
+                let mut cache = cache.clone();
+                tokio::spawn(async move { cache.increment("counter", Some(60)).await.unwrap() })
+            })
+            .collect();
+
+        let mut values = Vec::new();
+        for handle in handles {
+            values.push(handle.await.unwrap());
+        }
+        values.sort();
+
+        assert_eq!((1..=20).collect::<Vec<u64>>(), values);
+
+        let entry = cache
+            .clone()
+            .get::<u64, _>("counter")
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(20, entry.value);
+        assert_eq!(Some(60), entry.expiration);
+    }
+
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn test_batch(pool: PgPool) {
+        let mut cache = PostgresCache::new(pool, Default::default());
+
+        let entries = ["a", "b"]
+            .into_iter()
+            .map(|key| CreateCacheEntry {
+                key,
+                value: serde_json::json!({ "key": key }),
+                expiration: Some(60),
+            })
+            .collect();
+
+        cache.set_many(entries).await.unwrap();
+
+        let result = cache
+            .get_many_with_ttl(&["a", "c", "b"].map(CacheKey::from))
+            .await
+            .unwrap();
+
+        assert!(matches!(result[0], Some((_, Some(59..=61)))));
+        assert!(result[1].is_none());
+        assert!(result[2].is_some());
+
+        let stats = cache.stats().await.unwrap();
+
+        assert_eq!(2, stats.hits);
+        assert_eq!(1, stats.misses);
+    }
+}
@@ -1,67 +1,194 @@
 use async_trait::async_trait;
-use redis::FromRedisValue;
-use redis_lib::{AsyncCommands, RedisWrite, ToRedisArgs};
+use redis::aio::MultiplexedConnection;
+use redis_lib::AsyncCommands;
 use serde::de::DeserializeOwned;
 use std::collections::HashMap;
 use tracing::instrument;
 
-use crate::{Cache, CacheEntry, CacheKey, InnerCache, Result};
+use crate::{
+    Cache, CacheEntry, CacheKey, CacheStats, CreateCacheEntry, InnerCache, Result,
+    SerializedCacheEntry,
+};
+
+/// Number of keys requested per `SCAN` iteration and deleted per `DEL` command
+const BATCH_SIZE: usize = 1000;
+
+/// Replaces the entry with the given one, whose value starts at 1, incrementing the value of the
+/// current entry if any. Scripts run atomically, so that concurrent increments are not lost.
+const INCREMENT_SCRIPT: &str = r#"
+local entry = cjson.decode(ARGV[1])
+local current = redis.call('GET', KEYS[1])
+if current then
+    entry.value = cjson.decode(current).value + 1
+end
+local expiration = tonumber(ARGV[2])
+if expiration > 0 then
+    redis.call('SET', KEYS[1], cjson.encode(entry), 'EX', expiration)
+else
+    redis.call('SET', KEYS[1], cjson.encode(entry))
+end
+return entry.value
+"#;
 
 #[derive(Clone)]
 pub struct RedisCache {
     client: redis::Client,
+    /// Prefix added to every key, allowing multiple instances to share the same Redis database
+    key_prefix: Option<String>,
 }
 
 impl RedisCache {
-    pub fn new(url: &str) -> Result<Self> {
+    pub fn new(url: &str, key_prefix: Option<String>) -> Result<Self> {
         Ok(Self {
             client: redis::Client::open(url)?,
+            key_prefix,
         })
     }
-}
 
-impl ToRedisArgs for CacheKey {
-    fn write_redis_args<W>(&self, out: &mut W)
-    where
-        W: ?Sized + RedisWrite,
-    {
-        String::from(self).write_redis_args(out)
+    async fn connection(&self) -> Result<MultiplexedConnection> {
+        Ok(self.client.get_multiplexed_async_connection().await?)
     }
-}
 
-impl FromRedisValue for CacheKey {
-    fn from_redis_value(v: &redis::Value) -> redis::RedisResult<Self> {
-        let s = String::from_redis_value(v)?;
-        let parts: Vec<_> = s.split(':').map(|s| s.to_string()).collect();
+    fn redis_key(&self, key: &CacheKey) -> String {
+        let key = String::from(key);
+
+        match &self.key_prefix {
+            Some(prefix) => format!("{prefix}:{key}"),
+            None => key,
+        }
+    }
+
+    fn cache_key(&self, redis_key: &str) -> CacheKey {
+        let key = self
+            .key_prefix
+            .as_ref()
+            .and_then(|prefix| redis_key.strip_prefix(prefix.as_str())?.strip_prefix(':'))
+            .unwrap_or(redis_key);
+
+        CacheKey(key.split(':').map(|part| part.to_string()).collect())
+    }
+
+    /// Pattern matching every key starting with the partial key, within the key prefix
+    fn scan_pattern(&self, partial_key: &CacheKey) -> String {
+        let partial_key = String::from(partial_key);
+        let mut pattern = String::with_capacity(partial_key.len() + 1);
+
+        for c in partial_key.chars() {
+            if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+                pattern.push('\\');
+            }
+            pattern.push(c);
+        }
+        pattern.push('*');
 
-        Ok(Self(parts))
+        match &self.key_prefix {
+            Some(prefix) => format!("{prefix}:{pattern}"),
+            None => pattern,
+        }
+    }
+
+    /// Iterates over the keys matching the partial key with `SCAN`, to avoid blocking Redis like
+    /// `KEYS` would
+    async fn scan(
+        &self,
+        conn: &mut MultiplexedConnection,
+        partial_key: &CacheKey,
+    ) -> Result<Vec<String>> {
+        let mut cmd = redis::cmd("SCAN");
+        cmd.cursor_arg(0)
+            .arg("MATCH")
+            .arg(self.scan_pattern(partial_key))
+            .arg("COUNT")
+            .arg(BATCH_SIZE);
+
+        let mut iter = cmd.iter_async::<String>(conn).await?;
+
+        let mut keys = Vec::new();
+        while let Some(key) = iter.next_item().await {
+            keys.push(key);
+        }
+
+        // SCAN matches on the string representation, keep only the keys matching whole parts
+        Ok(keys
+            .into_iter()
+            .filter(|key| self.cache_key(key).matches_partial_cache_key(partial_key))
+            .collect())
+    }
+
+    async fn delete(&self, conn: &mut MultiplexedConnection, keys: &[String]) -> Result<u64> {
+        let mut num_deleted = 0;
+
+        for chunk in keys.chunks(BATCH_SIZE) {
+            num_deleted += conn.del::<_, u64>(chunk).await?;
+        }
+
+        Ok(num_deleted)
+    }
+
+    fn add_set_command(&self, pipe: &mut redis::Pipeline, entry: SerializedCacheEntry) {
+        let key = self.redis_key(&entry.key);
+
+        match entry.expiration.filter(|expiration| *expiration > 0) {
+            Some(expiration) => pipe.set_ex(key, entry.data, expiration as u64),
+            None => pipe.set(key, entry.data),
+        };
     }
 }
 
 #[async_trait]
 impl InnerCache for RedisCache {
     #[instrument(skip_all)]
-    async fn set_inner(&mut self, key: CacheKey, entry: Vec<u8>) -> Result<bool> {
-        let mut conn = self.client.get_multiplexed_async_connection().await?;
+    async fn set_inner(&mut self, entry: SerializedCacheEntry) -> Result<bool> {
+        let mut conn = self.connection().await?;
 
         let mut pipe = redis::pipe();
+        pipe.atomic().exists(self.redis_key(&entry.key));
+        self.add_set_command(&mut pipe, entry);
+        pipe.ignore();
 
-        let data: (bool,) = pipe
-            .atomic()
-            .exists(&key)
-            .set(key, entry)
-            .ignore()
-            .query_async(&mut conn)
-            .await?;
+        let data: (bool,) = pipe.query_async(&mut conn).await?;
 
         Ok(data.0)
     }
 
     #[instrument(skip_all)]
     async fn get_inner(&self, key: CacheKey) -> Result<Option<Vec<u8>>> {
-        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let mut conn = self.connection().await?;
 
-        let data: Option<Vec<u8>> = conn.get(&key).await?;
+        let data: Option<Vec<u8>> = conn.get(self.redis_key(&key)).await?;
+
+        Ok(data)
+    }
+
+    #[instrument(skip_all)]
+    async fn set_many_inner(&mut self, entries: Vec<SerializedCacheEntry>) -> Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        let mut conn = self.connection().await?;
+
+        let mut pipe = redis::pipe();
+        for entry in entries {
+            self.add_set_command(&mut pipe, entry);
+            pipe.ignore();
+        }
+
+        pipe.query_async::<_, ()>(&mut conn).await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all)]
+    async fn get_many_inner(&self, keys: Vec<CacheKey>) -> Result<Vec<Option<Vec<u8>>>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut conn = self.connection().await?;
+
+        let keys: Vec<_> = keys.iter().map(|key| self.redis_key(key)).collect();
+        let data = redis::cmd("MGET").arg(keys).query_async(&mut conn).await?;
 
         Ok(data)
     }
@@ -69,10 +196,37 @@ impl InnerCache for RedisCache {
 
 #[async_trait]
 impl Cache for RedisCache {
+    #[instrument(skip_all)]
+    async fn increment<S: Into<CacheKey> + Send>(
+        &mut self,
+        key: S,
+        expiration: Option<usize>,
+    ) -> Result<u64> {
+        let entry = SerializedCacheEntry::new(
+            CreateCacheEntry {
+                key,
+                value: 1u64,
+                expiration,
+            },
+            chrono::Utc::now().naive_utc(),
+        )?;
+
+        let mut conn = self.connection().await?;
+
+        let value = redis::Script::new(INCREMENT_SCRIPT)
+            .key(self.redis_key(&entry.key))
+            .arg(entry.data)
+            .arg(entry.expiration.unwrap_or_default())
+            .invoke_async(&mut conn)
+            .await?;
+
+        Ok(value)
+    }
+
     #[instrument(skip_all)]
     async fn invalidate<S: Into<CacheKey> + Send>(&mut self, key: S) -> Result<bool> {
-        let mut conn = self.client.get_multiplexed_async_connection().await?;
-        let value: bool = conn.del::<_, i32>(key.into()).await? > 0;
+        let mut conn = self.connection().await?;
+        let value: bool = conn.del::<_, i32>(self.redis_key(&key.into())).await? > 0;
 
         Ok(value)
     }
@@ -82,48 +236,98 @@ impl Cache for RedisCache {
         &mut self,
         partial_key: S,
     ) -> Result<u64> {
-        let partial_key = String::from(&partial_key.into());
+        let mut conn = self.connection().await?;
 
-        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let keys = self.scan(&mut conn, &partial_key.into()).await?;
 
-        let keys: Vec<CacheKey> = conn.keys(&format!("{partial_key}*")).await?;
-        let len = keys.len();
-
-        conn.del(keys.clone()).await?;
-
-        Ok(len as u64)
+        self.delete(&mut conn, &keys).await
     }
 
     #[instrument(skip_all)]
     async fn clear(&mut self) -> Result<u64> {
-        let mut conn = self.client.get_multiplexed_async_connection().await?;
-        let num = conn.del("*").await?;
+        let mut conn = self.connection().await?;
+
+        let keys = self.scan(&mut conn, &CacheKey::default()).await?;
 
-        Ok(num)
+        self.delete(&mut conn, &keys).await
     }
 
     #[instrument(skip_all)]
     async fn keys(&self) -> Result<Vec<CacheKey>> {
-        let mut conn = self.client.get_multiplexed_async_connection().await?;
-        let keys = conn.keys("*").await?;
+        let mut conn = self.connection().await?;
 
-        Ok(keys)
+        let keys = self.scan(&mut conn, &CacheKey::default()).await?;
+
+        Ok(keys.iter().map(|key| self.cache_key(key)).collect())
     }
 
     #[instrument(skip_all)]
     async fn get_all<T: DeserializeOwned + Send>(
         &self,
     ) -> Result<HashMap<CacheKey, CacheEntry<T>>> {
-        let mut conn = self.client.get_multiplexed_async_connection().await?;
         let keys = self.keys().await?;
+        let values = self.get_many_inner(keys.clone()).await?;
 
         let mut data = HashMap::new();
 
-        for key in keys {
-            let value: Vec<u8> = conn.get(&key).await?;
-            data.insert(key, serde_json::from_slice(&value)?);
+        for (key, value) in keys.into_iter().zip(values) {
+            // The entry could have expired between the scan and the retrieval
+            if let Some(value) = value {
+                data.insert(key, serde_json::from_slice(&value)?);
+            }
         }
 
         Ok(data)
     }
+
+    /// Counters are the ones of the whole Redis server, as tracked by Redis itself
+    #[instrument(skip_all)]
+    async fn stats(&self) -> Result<CacheStats> {
+        let mut conn = self.connection().await?;
+
+        let entries = if self.key_prefix.is_some() {
+            self.scan(&mut conn, &CacheKey::default()).await?.len() as u64
+        } else {
+            redis::cmd("DBSIZE").query_async(&mut conn).await?
+        };
+        let info: redis::InfoDict = redis::cmd("INFO").query_async(&mut conn).await?;
+
+        Ok(CacheStats {
+            entries,
+            size_bytes: info.get("used_memory").unwrap_or_default(),
+            max_entries: None,
+            max_bytes: info.get("maxmemory").filter(|max: &u64| *max > 0),
+            hits: info.get("keyspace_hits").unwrap_or_default(),
+            misses: info.get("keyspace_misses").unwrap_or_default(),
+            evictions: info.get("evicted_keys").unwrap_or_default(),
+            expirations: info.get("expired_keys").unwrap_or_default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_prefix() {
+        let cache = RedisCache::new("redis://localhost", Some("iocagg".to_string())).unwrap();
+        let key = CacheKey::from(vec!["source", "DOMAIN", "example.com"]);
+
+        let redis_key = cache.redis_key(&key);
+
+        assert_eq!("iocagg:source:DOMAIN:example.com", redis_key);
+        assert_eq!(key, cache.cache_key(&redis_key));
+    }
+
+    #[test]
+    fn test_scan_pattern() {
+        let cache = RedisCache::new("redis://localhost", None).unwrap();
+
+        assert_eq!("*", cache.scan_pattern(&CacheKey::default()));
+        assert_eq!(
+            "source:URL:https\\?a=\\[1\\]*",
+            cache.scan_pattern(&CacheKey::from(vec!["source", "URL", "https?a=[1]"]))
+        );
+    }
 }
@@ -0,0 +1,227 @@
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use tracing::instrument;
+
+use super::{
+    memory::InMemoryCache,
+    postgres::{EntryWithTtl, PostgresCache},
+};
+use crate::{Cache, CacheEntry, CacheKey, CacheStats, InnerCache, Result, SerializedCacheEntry};
+
+/// In memory cache layered in front of the Postgres cache, the Postgres cache being the source of
+/// truth while the in memory cache avoids a database query for the most recently used entries
+#[derive(Clone)]
+pub struct TieredCache {
+    memory: InMemoryCache,
+    persistent: PostgresCache,
+}
+
+impl TieredCache {
+    pub fn new(memory: InMemoryCache, persistent: PostgresCache) -> Self {
+        Self { memory, persistent }
+    }
+
+    pub fn spawn_sweepers(&self) {
+        self.memory.spawn_sweeper();
+        self.persistent.spawn_sweeper();
+    }
+
+    /// Copies entries found in the Postgres cache into the in memory cache, expiring at the same
+    /// time as the persisted entries
+    async fn backfill(&self, entries: Vec<(CacheKey, EntryWithTtl)>) -> Result<()> {
+        let entries = entries
+            .into_iter()
+            .map(|(key, (data, expiration))| SerializedCacheEntry {
+                key,
+                data,
+                expiration,
+            })
+            .collect();
+
+        self.memory.clone().set_many_inner(entries).await
+    }
+}
+
+#[async_trait]
+impl InnerCache for TieredCache {
+    #[instrument(skip_all)]
+    async fn set_inner(&mut self, entry: SerializedCacheEntry) -> Result<bool> {
+        let already_exists = self.persistent.set_inner(entry.clone()).await?;
+        self.memory.set_inner(entry).await?;
+
+        Ok(already_exists)
+    }
+
+    #[instrument(skip_all)]
+    async fn get_inner(&self, key: CacheKey) -> Result<Option<Vec<u8>>> {
+        Ok(self.get_many_inner(vec![key]).await?.pop().flatten())
+    }
+
+    #[instrument(skip_all)]
+    async fn set_many_inner(&mut self, entries: Vec<SerializedCacheEntry>) -> Result<()> {
+        self.persistent.set_many_inner(entries.clone()).await?;
+        self.memory.set_many_inner(entries).await
+    }
+
+    #[instrument(skip_all)]
+    async fn get_many_inner(&self, keys: Vec<CacheKey>) -> Result<Vec<Option<Vec<u8>>>> {
+        let mut data = self.memory.get_many_inner(keys.clone()).await?;
+
+        let missing: Vec<_> = keys
+            .into_iter()
+            .zip(data.iter())
+            .enumerate()
+            .filter(|(_, (_, data))| data.is_none())
+            .map(|(index, (key, _))| (index, key))
+            .collect();
+
+        if missing.is_empty() {
+            return Ok(data);
+        }
+
+        let missing_keys: Vec<_> = missing.iter().map(|(_, key)| key.clone()).collect();
+        let persisted = self.persistent.get_many_with_ttl(&missing_keys).await?;
+
+        let mut found = Vec::new();
+
+        for ((index, key), entry) in missing.into_iter().zip(persisted) {
+            if let Some((value, ttl)) = entry {
+                data[index] = Some(value.clone());
+                found.push((key, (value, ttl)));
+            }
+        }
+
+        if !found.is_empty() {
+            self.backfill(found).await?;
+        }
+
+        Ok(data)
+    }
+}
+
+#[async_trait]
+impl Cache for TieredCache {
+    /// Increments the persisted entry, the in memory one being dropped instead of updated since
+    /// concurrent increments could otherwise overwrite it with an older value
+    #[instrument(skip_all)]
+    async fn increment<S: Into<CacheKey> + Send>(
+        &mut self,
+        key: S,
+        expiration: Option<usize>,
+    ) -> Result<u64> {
+        let key = key.into();
+
+        let value = self.persistent.increment(key.clone(), expiration).await?;
+        self.memory.invalidate(key).await?;
+
+        Ok(value)
+    }
+
+    #[instrument(skip_all)]
+    async fn invalidate<S: Into<CacheKey> + Send>(&mut self, key: S) -> Result<bool> {
+        let key = key.into();
+
+        self.memory.invalidate(key.clone()).await?;
+        self.persistent.invalidate(key).await
+    }
+
+    #[instrument(skip_all)]
+    async fn partial_invalidate<S: Into<CacheKey> + Send>(
+        &mut self,
+        partial_key: S,
+    ) -> Result<u64> {
+        let partial_key = partial_key.into();
+
+        self.memory.partial_invalidate(partial_key.clone()).await?;
+        self.persistent.partial_invalidate(partial_key).await
+    }
+
+    #[instrument(skip_all)]
+    async fn clear(&mut self) -> Result<u64> {
+        self.memory.clear().await?;
+        self.persistent.clear().await
+    }
+
+    #[instrument(skip_all)]
+    async fn keys(&self) -> Result<Vec<CacheKey>> {
+        self.persistent.keys().await
+    }
+
+    #[instrument(skip_all)]
+    async fn get_all<T: DeserializeOwned + Send>(
+        &self,
+    ) -> Result<HashMap<CacheKey, CacheEntry<T>>> {
+        self.persistent.get_all().await
+    }
+
+    /// Entries are the persisted ones, a lookup is only a miss when it missed both tiers
+    #[instrument(skip_all)]
+    async fn stats(&self) -> Result<CacheStats> {
+        let memory = self.memory.stats().await?;
+        let persistent = self.persistent.stats().await?;
+
+        Ok(CacheStats {
+            entries: persistent.entries,
+            size_bytes: persistent.size_bytes,
+            max_entries: None,
+            max_bytes: None,
+            hits: memory.hits + persistent.hits,
+            misses: persistent.misses,
+            evictions: memory.evictions,
+            expirations: persistent.expirations,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::CreateCacheEntry;
+
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn test_backfill(pool: PgPool) {
+        let mut persistent = PostgresCache::new(pool, Default::default());
+
+        let entry = CreateCacheEntry {
+            key: "a",
+            value: serde_json::json!({}),
+            expiration: Some(60),
+        };
+
+        persistent.set(entry).await.unwrap();
+
+        let mut cache = TieredCache::new(InMemoryCache::new(), persistent);
+
+        assert!(cache
+            .get::<serde_json::Value, _>("a")
+            .await
+            .unwrap()
+            .is_some());
+        assert!(cache
+            .get::<serde_json::Value, _>("a")
+            .await
+            .unwrap()
+            .is_some());
+        assert!(cache
+            .get::<serde_json::Value, _>("b")
+            .await
+            .unwrap()
+            .is_none());
+
+        assert_eq!(
+            vec![CacheKey::from("a")],
+            cache.memory.keys().await.unwrap()
+        );
+
+        let stats = cache.stats().await.unwrap();
+
+        assert_eq!(2, stats.hits);
+        assert_eq!(1, stats.misses);
+
+        assert_eq!(1, cache.partial_invalidate("a").await.unwrap());
+        assert!(cache.memory.keys().await.unwrap().is_empty());
+    }
+}
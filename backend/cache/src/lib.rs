@@ -1,19 +1,24 @@
 use async_trait::async_trait;
-use std::collections::HashMap;
-use tracing::{instrument, warn};
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicU64, Ordering},
+};
+use tracing::instrument;
 use typeshare::typeshare;
+use utoipa::ToSchema;
 extern crate redis as redis_lib;
 use chrono::NaiveDateTime;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 mod implementations;
 
-pub use implementations::CacheImpl;
+pub use implementations::{CacheImpl, InMemoryCacheConfig, PostgresCacheConfig};
 
 #[derive(Debug)]
 pub enum CacheError {
     SerdeJson(serde_json::Error),
     Redis(redis_lib::RedisError),
+    Sqlx(sqlx::Error),
 }
 
 impl From<serde_json::Error> for CacheError {
@@ -28,6 +33,12 @@ impl From<redis_lib::RedisError> for CacheError {
     }
 }
 
+impl From<sqlx::Error> for CacheError {
+    fn from(error: sqlx::Error) -> Self {
+        Self::Sqlx(error)
+    }
+}
+
 pub type Result<T> = std::result::Result<T, CacheError>;
 
 /// A cache entry, encapsulating the value and the timestamp of when it was created
@@ -42,6 +53,90 @@ pub struct CacheEntry<T> {
     pub expiration: Option<u32>,
 }
 
+impl<T> CacheEntry<T> {
+    /// Timestamp after which the cache entry is considered stale, if it has an expiration
+    pub fn expires_at(&self) -> Option<NaiveDateTime> {
+        self.expiration
+            .and_then(|expiration| chrono::Duration::try_seconds(expiration as i64))
+            .map(|expiration| self.timestamp + expiration)
+    }
+
+    /// Whether the cache entry is stale and should not be returned anymore
+    pub fn is_expired(&self) -> bool {
+        self.expires_at()
+            .is_some_and(|expires_at| expires_at < chrono::Utc::now().naive_utc())
+    }
+}
+
+/// Statistics about the usage of the cache
+#[derive(Serialize, Debug, Default, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct CacheStats {
+    /// Number of entries currently in the cache
+    #[typeshare(serialized_as = "number")]
+    pub entries: u64,
+    /// Total size in bytes of the entries currently in the cache
+    #[typeshare(serialized_as = "number")]
+    pub size_bytes: u64,
+    /// Maximum number of entries kept in the cache, if bounded
+    #[typeshare(serialized_as = "Option<number>")]
+    pub max_entries: Option<u64>,
+    /// Maximum total size in bytes of the entries kept in the cache, if bounded
+    #[typeshare(serialized_as = "Option<number>")]
+    pub max_bytes: Option<u64>,
+    /// Number of lookups that returned a valid entry
+    #[typeshare(serialized_as = "number")]
+    pub hits: u64,
+    /// Number of lookups that did not return a valid entry
+    #[typeshare(serialized_as = "number")]
+    pub misses: u64,
+    /// Number of entries removed to keep the cache within its limits
+    #[typeshare(serialized_as = "number")]
+    pub evictions: u64,
+    /// Number of entries removed because they expired
+    #[typeshare(serialized_as = "number")]
+    pub expirations: u64,
+}
+
+/// Counters shared between the clones of a cache implementation
+#[derive(Debug, Default)]
+pub(crate) struct CacheCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    expirations: AtomicU64,
+}
+
+impl CacheCounters {
+    pub(crate) fn hit(&self) {
+        self.hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn miss(&self) {
+        self.misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn evicted(&self, count: u64) {
+        self.evictions.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub(crate) fn expired(&self, count: u64) {
+        self.expirations.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Fills the counter fields of the stats with the current values
+    pub(crate) fn stats(&self, stats: CacheStats) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            expirations: self.expirations.load(Ordering::Relaxed),
+            ..stats
+        }
+    }
+}
+
 pub struct SetCacheEntryResult {
     pub already_exists: bool,
     pub timestamp: NaiveDateTime,
@@ -104,10 +199,61 @@ pub struct CreateCacheEntry<T: Serialize, S: Into<CacheKey>> {
     pub expiration: Option<usize>,
 }
 
+/// Cache entry serialized into bytes, as stored by the cache implementations
+#[derive(Clone)]
+pub struct SerializedCacheEntry {
+    pub key: CacheKey,
+    pub data: Vec<u8>,
+    /// Timespan in seconds after which the cache implementation can drop the entry, if any
+    pub expiration: Option<u32>,
+}
+
+impl SerializedCacheEntry {
+    fn new<T: Serialize, S: Into<CacheKey>>(
+        entry: CreateCacheEntry<T, S>,
+        timestamp: NaiveDateTime,
+    ) -> Result<Self> {
+        let expiration = entry.expiration.map(|e| e as u32);
+
+        let data = serde_json::to_vec(&CacheEntry {
+            timestamp,
+            value: entry.value,
+            expiration,
+        })?;
+
+        Ok(Self {
+            key: entry.key.into(),
+            data,
+            expiration,
+        })
+    }
+}
+
 #[async_trait]
-pub trait InnerCache {
-    async fn set_inner(&mut self, key: CacheKey, entry: Vec<u8>) -> Result<bool>;
+pub trait InnerCache: Send + Sync {
+    async fn set_inner(&mut self, entry: SerializedCacheEntry) -> Result<bool>;
     async fn get_inner(&self, key: CacheKey) -> Result<Option<Vec<u8>>>;
+
+    /// Sets multiple entries at once, implementations should override this to batch the operations
+    async fn set_many_inner(&mut self, entries: Vec<SerializedCacheEntry>) -> Result<()> {
+        for entry in entries {
+            self.set_inner(entry).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Gets multiple entries at once, in the same order as the keys, implementations should
+    /// override this to batch the operations
+    async fn get_many_inner(&self, keys: Vec<CacheKey>) -> Result<Vec<Option<Vec<u8>>>> {
+        let mut data = Vec::with_capacity(keys.len());
+
+        for key in keys {
+            data.push(self.get_inner(key).await?);
+        }
+
+        Ok(data)
+    }
 }
 
 #[async_trait]
@@ -119,13 +265,9 @@ pub trait Cache: Clone + InnerCache {
     ) -> Result<SetCacheEntryResult> {
         let timestamp = chrono::Utc::now().naive_utc();
 
-        let serialized_entry = serde_json::to_vec(&CacheEntry {
-            timestamp,
-            value: entry.value,
-            expiration: entry.expiration.map(|e| e as u32),
-        })?;
-
-        let already_exists = self.set_inner(entry.key.into(), serialized_entry).await?;
+        let already_exists = self
+            .set_inner(SerializedCacheEntry::new(entry, timestamp)?)
+            .await?;
 
         Ok((already_exists, timestamp).into())
     }
@@ -142,19 +284,8 @@ pub trait Cache: Clone + InnerCache {
         };
 
         let entry: CacheEntry<T> = serde_json::from_slice(&data)?;
-        let Some(expiration) = entry.expiration else {
-            return Ok(Some(entry));
-        };
-
-        let Some(expiration_timespan) = chrono::Duration::try_seconds(expiration as i64) else {
-            warn!(
-                ?key,
-                "Failed to parse expiration timespan, will not invalidate cache entry"
-            );
-            return Ok(Some(entry));
-        };
 
-        if entry.timestamp + expiration_timespan < chrono::Utc::now().naive_utc() {
+        if entry.is_expired() {
             self.invalidate(key).await?;
             return Ok(None);
         }
@@ -162,6 +293,59 @@ pub trait Cache: Clone + InnerCache {
         Ok(Some(entry))
     }
 
+    /// Sets multiple entries at once, returning the timestamp at which they were cached
+    #[instrument(skip_all)]
+    async fn set_many<T: Serialize + Send, S: Into<CacheKey> + Send>(
+        &mut self,
+        entries: Vec<CreateCacheEntry<T, S>>,
+    ) -> Result<NaiveDateTime> {
+        let timestamp = chrono::Utc::now().naive_utc();
+
+        let entries = entries
+            .into_iter()
+            .map(|entry| SerializedCacheEntry::new(entry, timestamp))
+            .collect::<Result<Vec<_>>>()?;
+
+        self.set_many_inner(entries).await?;
+
+        Ok(timestamp)
+    }
+
+    /// Gets multiple entries at once, in the same order as the keys
+    #[instrument(skip_all)]
+    async fn get_many<T: DeserializeOwned + Send, S: Into<CacheKey> + Send>(
+        &mut self,
+        keys: Vec<S>,
+    ) -> Result<Vec<Option<CacheEntry<T>>>> {
+        let keys: Vec<CacheKey> = keys.into_iter().map(Into::into).collect();
+        let data = self.get_many_inner(keys.clone()).await?;
+
+        let mut entries = Vec::with_capacity(keys.len());
+
+        for (key, data) in keys.into_iter().zip(data) {
+            let entry = data
+                .map(|data| serde_json::from_slice::<CacheEntry<T>>(&data))
+                .transpose()?;
+
+            match entry {
+                Some(entry) if entry.is_expired() => {
+                    self.invalidate(key).await?;
+                    entries.push(None);
+                }
+                entry => entries.push(entry),
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Atomically increments the counter stored in the entry, starting from 1 when it is missing or
+    /// expired, and returns its new value. The expiration restarts with every increment.
+    async fn increment<S: Into<CacheKey> + Send>(
+        &mut self,
+        key: S,
+        expiration: Option<usize>,
+    ) -> Result<u64>;
     async fn invalidate<S: Into<CacheKey> + Send>(&mut self, key: S) -> Result<bool>;
     async fn partial_invalidate<S: Into<CacheKey> + Send>(&mut self, partial_key: S)
         -> Result<u64>;
@@ -169,4 +353,5 @@ pub trait Cache: Clone + InnerCache {
     async fn keys(&self) -> Result<Vec<CacheKey>>;
     async fn get_all<T: DeserializeOwned + Send>(&self)
         -> Result<HashMap<CacheKey, CacheEntry<T>>>;
+    async fn stats(&self) -> Result<CacheStats>;
 }
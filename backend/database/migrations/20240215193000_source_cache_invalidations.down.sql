@@ -0,0 +1,3 @@
+DROP TABLE "source_cache_invalidations";
+
+DROP TYPE "cache_invalidation_reason";
@@ -0,0 +1,21 @@
+CREATE TYPE "cache_invalidation_reason" AS ENUM ('source_updated', 'source_secrets_updated', 'secret_updated');
+
+CREATE TABLE IF NOT EXISTS "source_cache_invalidations" (
+    "id" TEXT PRIMARY KEY DEFAULT nanoid(),
+    "created_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+    "updated_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+
+    "reason" "cache_invalidation_reason" NOT NULL,
+    "num_entries" INT NOT NULL,
+
+    "trace_id" TEXT NOT NULL,
+    "source_id" TEXT NOT NULL,
+    "user_id" TEXT NOT NULL,
+
+    FOREIGN KEY ("source_id") REFERENCES "sources" ("id") ON DELETE CASCADE ON UPDATE CASCADE,
+    FOREIGN KEY ("user_id") REFERENCES "users" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE INDEX "source_cache_invalidations_source_id_idx" ON "source_cache_invalidations" ("source_id");
+
+CREATE TRIGGER "source_cache_invalidations_updated_at" BEFORE UPDATE ON "source_cache_invalidations" FOR EACH ROW EXECUTE PROCEDURE "moddatetime" ("updated_at");
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS "cache_entries" (
+    "key" TEXT[] PRIMARY KEY,
+    "created_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+    "updated_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+
+    "data" BYTEA NOT NULL,
+    "expires_at" TIMESTAMP(3)
+);
+
+CREATE INDEX "cache_entries_expires_at_idx" ON "cache_entries" ("expires_at");
+
+CREATE TRIGGER "cache_entries_updated_at" BEFORE UPDATE ON "cache_entries" FOR EACH ROW EXECUTE PROCEDURE "moddatetime" ("updated_at");
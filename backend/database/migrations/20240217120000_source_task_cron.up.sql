@@ -0,0 +1 @@
+ALTER TABLE "sources" ADD COLUMN "task_cron" TEXT;
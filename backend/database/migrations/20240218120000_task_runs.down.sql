@@ -0,0 +1,5 @@
+DROP TABLE "task_runs";
+
+DROP TYPE "task_run_trigger";
+
+DROP TYPE "task_run_status";
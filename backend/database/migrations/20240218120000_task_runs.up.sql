@@ -0,0 +1,27 @@
+CREATE TYPE "task_run_status" AS ENUM ('running', 'succeeded', 'failed');
+
+CREATE TYPE "task_run_trigger" AS ENUM ('scheduled', 'manual');
+
+CREATE TABLE IF NOT EXISTS "task_runs" (
+    "id" TEXT PRIMARY KEY DEFAULT nanoid(),
+    "created_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+    "updated_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+
+    "started_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+    "ended_at" TIMESTAMP(3),
+    "status" "task_run_status" NOT NULL DEFAULT 'running',
+    "trigger" "task_run_trigger" NOT NULL,
+    "error" TEXT,
+    "records_ingested" INT,
+
+    "trace_id" TEXT NOT NULL,
+    "source_id" TEXT NOT NULL,
+    "user_id" TEXT,
+
+    FOREIGN KEY ("source_id") REFERENCES "sources" ("id") ON DELETE CASCADE ON UPDATE CASCADE,
+    FOREIGN KEY ("user_id") REFERENCES "users" ("id") ON DELETE SET NULL ON UPDATE CASCADE
+);
+
+CREATE INDEX "task_runs_source_id_started_at_idx" ON "task_runs" ("source_id", "started_at" DESC);
+
+CREATE TRIGGER "task_runs_updated_at" BEFORE UPDATE ON "task_runs" FOR EACH ROW EXECUTE PROCEDURE "moddatetime" ("updated_at");
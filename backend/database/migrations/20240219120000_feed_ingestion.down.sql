@@ -0,0 +1,32 @@
+ALTER TABLE "task_runs"
+  DROP COLUMN "records_added",
+  DROP COLUMN "records_updated",
+  DROP COLUMN "records_removed";
+
+ALTER TABLE "phishtank_refreshes"
+  DROP COLUMN "etag",
+  DROP COLUMN "last_modified",
+  DROP COLUMN "not_modified",
+  DROP COLUMN "num_added",
+  DROP COLUMN "num_updated",
+  DROP COLUMN "num_removed";
+
+ALTER TABLE "url_haus_refreshes"
+  DROP COLUMN "etag",
+  DROP COLUMN "last_modified",
+  DROP COLUMN "not_modified",
+  DROP COLUMN "num_added",
+  DROP COLUMN "num_updated",
+  DROP COLUMN "num_removed";
+
+ALTER TABLE "phishtank"
+  DROP COLUMN "updated_at",
+  DROP COLUMN "first_seen",
+  DROP COLUMN "last_seen",
+  DROP COLUMN "active";
+
+ALTER TABLE "url_haus"
+  DROP COLUMN "updated_at",
+  DROP COLUMN "first_seen",
+  DROP COLUMN "last_seen",
+  DROP COLUMN "active";
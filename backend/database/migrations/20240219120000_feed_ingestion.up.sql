@@ -0,0 +1,36 @@
+ALTER TABLE "url_haus"
+  ADD COLUMN "updated_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+  ADD COLUMN "first_seen" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+  ADD COLUMN "last_seen" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+  ADD COLUMN "active" BOOLEAN NOT NULL DEFAULT TRUE;
+
+UPDATE "url_haus" SET "updated_at" = "created_at", "first_seen" = "created_at", "last_seen" = "created_at";
+
+ALTER TABLE "phishtank"
+  ADD COLUMN "updated_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+  ADD COLUMN "first_seen" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+  ADD COLUMN "last_seen" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+  ADD COLUMN "active" BOOLEAN NOT NULL DEFAULT TRUE;
+
+UPDATE "phishtank" SET "updated_at" = "created_at", "first_seen" = "created_at", "last_seen" = "created_at";
+
+ALTER TABLE "url_haus_refreshes"
+  ADD COLUMN "etag" TEXT,
+  ADD COLUMN "last_modified" TEXT,
+  ADD COLUMN "not_modified" BOOLEAN NOT NULL DEFAULT FALSE,
+  ADD COLUMN "num_added" INTEGER NOT NULL DEFAULT 0,
+  ADD COLUMN "num_updated" INTEGER NOT NULL DEFAULT 0,
+  ADD COLUMN "num_removed" INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE "phishtank_refreshes"
+  ADD COLUMN "etag" TEXT,
+  ADD COLUMN "last_modified" TEXT,
+  ADD COLUMN "not_modified" BOOLEAN NOT NULL DEFAULT FALSE,
+  ADD COLUMN "num_added" INTEGER NOT NULL DEFAULT 0,
+  ADD COLUMN "num_updated" INTEGER NOT NULL DEFAULT 0,
+  ADD COLUMN "num_removed" INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE "task_runs"
+  ADD COLUMN "records_added" INTEGER,
+  ADD COLUMN "records_updated" INTEGER,
+  ADD COLUMN "records_removed" INTEGER;
@@ -0,0 +1,21 @@
+DROP TABLE "feed_refreshes";
+
+DROP TABLE "feed_entries";
+
+DELETE FROM "sources" WHERE "kind" = 'feed';
+
+ALTER TABLE "sources" DROP CONSTRAINT "source_code_check";
+
+ALTER TYPE "source_kind" RENAME TO "source_kind_old";
+
+CREATE TYPE "source_kind" AS ENUM ('system', 'javascript', 'python');
+
+ALTER TABLE "sources" ALTER COLUMN "kind" TYPE "source_kind" USING "kind"::TEXT::"source_kind";
+
+DROP TYPE "source_kind_old";
+
+ALTER TABLE "sources" ADD CONSTRAINT "source_code_check" CHECK (
+    ("kind" = 'system') OR
+    ("kind" = 'javascript' AND "source_code" IS NOT NULL) OR
+    ("kind" = 'python' AND "source_code" IS NOT NULL)
+);
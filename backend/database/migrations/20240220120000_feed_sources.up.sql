@@ -0,0 +1,45 @@
+ALTER TYPE "source_kind" ADD VALUE IF NOT EXISTS 'feed';
+
+-- Feed sources are configured through their config instead of source code, the new enum value
+-- cannot be referenced within the transaction adding it
+ALTER TABLE "sources" DROP CONSTRAINT "source_code_check";
+
+ALTER TABLE "sources" ADD CONSTRAINT "source_code_check" CHECK (
+    ("kind" NOT IN ('javascript', 'python')) OR ("source_code" IS NOT NULL)
+);
+
+CREATE TABLE IF NOT EXISTS "feed_entries" (
+    "id" BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    "created_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+    "updated_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+    "first_seen" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+    "last_seen" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+    "active" BOOLEAN NOT NULL DEFAULT TRUE,
+
+    "kind" TEXT NOT NULL,
+    "data" TEXT NOT NULL,
+    "metadata" JSONB NOT NULL DEFAULT '{}',
+
+    "source_id" TEXT NOT NULL,
+
+    FOREIGN KEY ("source_id") REFERENCES "sources" ("id") ON DELETE CASCADE ON UPDATE CASCADE,
+    UNIQUE ("source_id", "kind", "data")
+);
+
+CREATE TABLE IF NOT EXISTS "feed_refreshes" (
+    "id" INTEGER GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    "refreshed_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+    "response_headers" TEXT NOT NULL,
+    "etag" TEXT,
+    "last_modified" TEXT,
+    "not_modified" BOOLEAN NOT NULL DEFAULT FALSE,
+    "num_added" INTEGER NOT NULL DEFAULT 0,
+    "num_updated" INTEGER NOT NULL DEFAULT 0,
+    "num_removed" INTEGER NOT NULL DEFAULT 0,
+
+    "source_id" TEXT NOT NULL,
+
+    FOREIGN KEY ("source_id") REFERENCES "sources" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE INDEX "feed_refreshes_source_id_refreshed_at_idx" ON "feed_refreshes" ("source_id", "refreshed_at" DESC);
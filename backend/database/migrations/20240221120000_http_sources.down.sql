@@ -0,0 +1,15 @@
+DELETE FROM "sources" WHERE "kind" = 'http';
+
+ALTER TABLE "sources" DROP CONSTRAINT "source_code_check";
+
+ALTER TYPE "source_kind" RENAME TO "source_kind_old";
+
+CREATE TYPE "source_kind" AS ENUM ('system', 'javascript', 'python', 'feed');
+
+ALTER TABLE "sources" ALTER COLUMN "kind" TYPE "source_kind" USING "kind"::TEXT::"source_kind";
+
+DROP TYPE "source_kind_old";
+
+ALTER TABLE "sources" ADD CONSTRAINT "source_code_check" CHECK (
+    ("kind" NOT IN ('javascript', 'python')) OR ("source_code" IS NOT NULL)
+);
@@ -0,0 +1,3 @@
+-- HTTP sources are configured through their config, like feed sources, and therefore are already
+-- allowed without source code by the "source_code_check" constraint
+ALTER TYPE "source_kind" ADD VALUE IF NOT EXISTS 'http';
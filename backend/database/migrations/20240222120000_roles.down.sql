@@ -0,0 +1,6 @@
+UPDATE "users" SET "roles" = ARRAY(
+    SELECT DISTINCT "permission"
+    FROM UNNEST("roles") AS "role",
+    UNNEST(CASE "role" WHEN 'viewer' THEN ARRAY['request_view'] ELSE ARRAY['request_create', 'request_view'] END) AS "permission"
+    WHERE "role" IN ('admin', 'source_editor', 'analyst', 'viewer')
+);
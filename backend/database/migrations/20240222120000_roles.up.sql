@@ -0,0 +1,10 @@
+-- Roles used to be permissions checked as is, they are now mapped to the roles granting them
+UPDATE "users" SET "roles" = ARRAY(
+    SELECT DISTINCT CASE "role" WHEN 'request_create' THEN 'analyst' ELSE 'viewer' END
+    FROM UNNEST("roles") AS "role"
+    WHERE "role" IN ('request_create', 'request_view')
+);
+
+-- Every user was able to manage the platform, the oldest enabled user keeps that ability
+UPDATE "users" SET "roles" = ARRAY_APPEND("roles", 'admin')
+WHERE "id" = (SELECT "id" FROM "users" WHERE "enabled" ORDER BY "created_at" LIMIT 1);
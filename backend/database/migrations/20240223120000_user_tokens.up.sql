@@ -0,0 +1,20 @@
+CREATE TYPE "user_token_kind" AS ENUM ('email_verification', 'password_reset');
+
+CREATE TABLE IF NOT EXISTS "user_tokens" (
+    "id" TEXT PRIMARY KEY DEFAULT nanoid(),
+    "created_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+    "updated_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+
+    "kind" "user_token_kind" NOT NULL,
+    "token" TEXT NOT NULL,
+    "expires_at" TIMESTAMP(3) NOT NULL,
+    "used_at" TIMESTAMP(3),
+
+    "user_id" TEXT NOT NULL,
+
+    FOREIGN KEY ("user_id") REFERENCES "users" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE INDEX "user_tokens_user_id_kind_idx" ON "user_tokens" ("user_id", "kind");
+
+CREATE TRIGGER "user_tokens_updated_at" BEFORE UPDATE ON "user_tokens" FOR EACH ROW EXECUTE PROCEDURE "moddatetime" ("updated_at");
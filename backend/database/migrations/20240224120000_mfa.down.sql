@@ -0,0 +1,9 @@
+DROP TABLE IF EXISTS "user_webauthn_credentials";
+DROP TABLE IF EXISTS "user_recovery_codes";
+DROP TABLE IF EXISTS "user_totp";
+
+DELETE FROM "user_tokens" WHERE "kind" IN ('mfa_challenge', 'webauthn_registration');
+ALTER TYPE "user_token_kind" RENAME TO "user_token_kind_old";
+CREATE TYPE "user_token_kind" AS ENUM ('email_verification', 'password_reset');
+ALTER TABLE "user_tokens" ALTER COLUMN "kind" TYPE "user_token_kind" USING "kind"::TEXT::"user_token_kind";
+DROP TYPE "user_token_kind_old";
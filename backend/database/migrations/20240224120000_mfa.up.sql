@@ -0,0 +1,53 @@
+ALTER TYPE "user_token_kind" ADD VALUE IF NOT EXISTS 'mfa_challenge';
+ALTER TYPE "user_token_kind" ADD VALUE IF NOT EXISTS 'webauthn_registration';
+
+CREATE TABLE IF NOT EXISTS "user_totp" (
+    "user_id" TEXT PRIMARY KEY,
+    "created_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+    "updated_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+
+    "secret" BYTEA NOT NULL,
+    "enabled" BOOLEAN NOT NULL DEFAULT FALSE,
+    "last_used_step" BIGINT,
+
+    FOREIGN KEY ("user_id") REFERENCES "users" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE TRIGGER "user_totp_updated_at" BEFORE UPDATE ON "user_totp" FOR EACH ROW EXECUTE PROCEDURE "moddatetime" ("updated_at");
+
+CREATE TABLE IF NOT EXISTS "user_recovery_codes" (
+    "id" TEXT PRIMARY KEY DEFAULT nanoid(),
+    "created_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+    "updated_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+
+    "code" TEXT NOT NULL,
+    "used_at" TIMESTAMP(3),
+
+    "user_id" TEXT NOT NULL,
+
+    FOREIGN KEY ("user_id") REFERENCES "users" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE INDEX "user_recovery_codes_user_id_idx" ON "user_recovery_codes" ("user_id");
+
+CREATE TRIGGER "user_recovery_codes_updated_at" BEFORE UPDATE ON "user_recovery_codes" FOR EACH ROW EXECUTE PROCEDURE "moddatetime" ("updated_at");
+
+CREATE TABLE IF NOT EXISTS "user_webauthn_credentials" (
+    "id" TEXT PRIMARY KEY DEFAULT nanoid(),
+    "created_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+    "updated_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+
+    "name" TEXT NOT NULL,
+    "credential_id" TEXT NOT NULL UNIQUE,
+    "public_key" BYTEA NOT NULL,
+    "sign_count" BIGINT NOT NULL DEFAULT 0,
+    "last_used_at" TIMESTAMP(3),
+
+    "user_id" TEXT NOT NULL,
+
+    FOREIGN KEY ("user_id") REFERENCES "users" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE INDEX "user_webauthn_credentials_user_id_idx" ON "user_webauthn_credentials" ("user_id");
+
+CREATE TRIGGER "user_webauthn_credentials_updated_at" BEFORE UPDATE ON "user_webauthn_credentials" FOR EACH ROW EXECUTE PROCEDURE "moddatetime" ("updated_at");
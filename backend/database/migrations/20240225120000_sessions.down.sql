@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS "user_sessions";
+DROP TABLE IF EXISTS "jwt_keys";
+DROP TYPE IF EXISTS "jwt_key_algorithm";
@@ -0,0 +1,36 @@
+CREATE TYPE "jwt_key_algorithm" AS ENUM ('rs256', 'ed_dsa');
+
+CREATE TABLE IF NOT EXISTS "jwt_keys" (
+    "id" TEXT PRIMARY KEY DEFAULT nanoid(),
+    "created_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+    "updated_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+
+    "algorithm" "jwt_key_algorithm" NOT NULL,
+    "private_key" BYTEA NOT NULL,
+    "public_key" BYTEA NOT NULL,
+    "expires_at" TIMESTAMP(3) NOT NULL
+);
+
+CREATE TRIGGER "jwt_keys_updated_at" BEFORE UPDATE ON "jwt_keys" FOR EACH ROW EXECUTE PROCEDURE "moddatetime" ("updated_at");
+
+CREATE TABLE IF NOT EXISTS "user_sessions" (
+    "id" TEXT PRIMARY KEY DEFAULT nanoid(),
+    "created_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+    "updated_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+
+    "refresh_token" TEXT NOT NULL,
+    "previous_refresh_token" TEXT,
+    "ip_address" TEXT NOT NULL,
+    "user_agent" TEXT NOT NULL,
+    "last_used_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+    "expires_at" TIMESTAMP(3) NOT NULL,
+    "revoked_at" TIMESTAMP(3),
+
+    "user_id" TEXT NOT NULL,
+
+    FOREIGN KEY ("user_id") REFERENCES "users" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE INDEX "user_sessions_user_id_idx" ON "user_sessions" ("user_id");
+
+CREATE TRIGGER "user_sessions_updated_at" BEFORE UPDATE ON "user_sessions" FOR EACH ROW EXECUTE PROCEDURE "moddatetime" ("updated_at");
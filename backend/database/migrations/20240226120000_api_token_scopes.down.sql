@@ -0,0 +1,8 @@
+ALTER TABLE "api_tokens"
+    DROP COLUMN "permissions",
+    DROP COLUMN "source_ids",
+    DROP COLUMN "indicator_kinds",
+    DROP COLUMN "allowed_ip_ranges",
+    DROP COLUMN "last_used_at",
+    DROP COLUMN "last_used_ip_address",
+    DROP COLUMN "request_count";
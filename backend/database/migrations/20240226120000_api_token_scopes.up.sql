@@ -0,0 +1,8 @@
+ALTER TABLE "api_tokens"
+    ADD COLUMN "permissions" TEXT[],
+    ADD COLUMN "source_ids" TEXT[],
+    ADD COLUMN "indicator_kinds" TEXT[],
+    ADD COLUMN "allowed_ip_ranges" TEXT[],
+    ADD COLUMN "last_used_at" TIMESTAMP(3),
+    ADD COLUMN "last_used_ip_address" TEXT,
+    ADD COLUMN "request_count" INTEGER NOT NULL DEFAULT 0;
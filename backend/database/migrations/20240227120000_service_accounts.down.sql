@@ -0,0 +1,5 @@
+DELETE FROM "users" WHERE "service_account";
+
+ALTER TABLE "users"
+    DROP COLUMN "owner_user_id",
+    DROP COLUMN "service_account";
@@ -0,0 +1,4 @@
+ALTER TABLE "users"
+    ADD COLUMN "service_account" BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN "owner_user_id" TEXT,
+    ADD FOREIGN KEY ("owner_user_id") REFERENCES "users" ("id") ON DELETE SET NULL ON UPDATE CASCADE;
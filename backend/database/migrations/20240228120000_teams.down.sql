@@ -0,0 +1,7 @@
+ALTER TABLE "requests" DROP COLUMN "team_id";
+ALTER TABLE "ignore_lists" DROP COLUMN "team_id";
+ALTER TABLE "secrets" DROP COLUMN "team_id";
+ALTER TABLE "sources" DROP COLUMN "team_id";
+
+DROP TABLE IF EXISTS "team_members";
+DROP TABLE IF EXISTS "teams";
@@ -0,0 +1,54 @@
+CREATE TABLE IF NOT EXISTS "teams" (
+    "id" TEXT PRIMARY KEY DEFAULT nanoid(),
+    "created_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+    "updated_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+
+    "name" TEXT NOT NULL UNIQUE,
+    "slug" TEXT NOT NULL UNIQUE,
+    "description" TEXT NOT NULL,
+
+    "created_user_id" TEXT NOT NULL,
+    "updated_user_id" TEXT,
+
+    FOREIGN KEY ("created_user_id") REFERENCES "users" ("id") ON DELETE CASCADE ON UPDATE CASCADE,
+    FOREIGN KEY ("updated_user_id") REFERENCES "users" ("id") ON DELETE SET NULL ON UPDATE CASCADE
+);
+
+CREATE TRIGGER "teams_updated_at" BEFORE UPDATE ON "teams" FOR EACH ROW EXECUTE PROCEDURE "moddatetime" ("updated_at");
+
+CREATE TABLE IF NOT EXISTS "team_members" (
+    "created_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+
+    "team_id" TEXT NOT NULL,
+    "user_id" TEXT NOT NULL,
+    "created_user_id" TEXT NOT NULL,
+
+    PRIMARY KEY ("team_id", "user_id"),
+    FOREIGN KEY ("team_id") REFERENCES "teams" ("id") ON DELETE CASCADE ON UPDATE CASCADE,
+    FOREIGN KEY ("user_id") REFERENCES "users" ("id") ON DELETE CASCADE ON UPDATE CASCADE,
+    FOREIGN KEY ("created_user_id") REFERENCES "users" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE INDEX "team_members_user_id_idx" ON "team_members" ("user_id");
+
+-- Resources without a team are shared with everyone and managed through roles only
+ALTER TABLE "sources"
+    ADD COLUMN "team_id" TEXT,
+    ADD FOREIGN KEY ("team_id") REFERENCES "teams" ("id") ON DELETE RESTRICT ON UPDATE CASCADE;
+
+ALTER TABLE "secrets"
+    ADD COLUMN "team_id" TEXT,
+    ADD FOREIGN KEY ("team_id") REFERENCES "teams" ("id") ON DELETE RESTRICT ON UPDATE CASCADE;
+
+ALTER TABLE "ignore_lists"
+    ADD COLUMN "team_id" TEXT,
+    ADD FOREIGN KEY ("team_id") REFERENCES "teams" ("id") ON DELETE RESTRICT ON UPDATE CASCADE;
+
+ALTER TABLE "requests"
+    ADD COLUMN "team_id" TEXT,
+    ADD FOREIGN KEY ("team_id") REFERENCES "teams" ("id") ON DELETE CASCADE ON UPDATE CASCADE;
+
+CREATE INDEX "sources_team_id_idx" ON "sources" ("team_id");
+CREATE INDEX "secrets_team_id_idx" ON "secrets" ("team_id");
+CREATE INDEX "ignore_lists_team_id_idx" ON "ignore_lists" ("team_id");
+CREATE INDEX "requests_team_id_idx" ON "requests" ("team_id");
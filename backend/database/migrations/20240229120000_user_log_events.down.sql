@@ -0,0 +1,3 @@
+ALTER TABLE "user_logs" DROP COLUMN "event";
+
+DROP TYPE IF EXISTS "user_log_event";
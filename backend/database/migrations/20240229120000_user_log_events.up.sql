@@ -0,0 +1,3 @@
+CREATE TYPE "user_log_event" AS ENUM ('account_locked');
+
+ALTER TABLE "user_logs" ADD COLUMN "event" "user_log_event";
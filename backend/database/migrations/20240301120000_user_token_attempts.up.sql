@@ -0,0 +1 @@
+ALTER TABLE "user_tokens" ADD COLUMN "failed_attempts" INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,13 @@
+-- Failed logins, kept apart from the cache entries so that they are never evicted or cleared along with them
+CREATE TABLE IF NOT EXISTS "login_throttling_entries" (
+    "key" TEXT[] PRIMARY KEY,
+    "created_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+    "updated_at" TIMESTAMP(3) NOT NULL DEFAULT NOW(),
+
+    "data" BYTEA NOT NULL,
+    "expires_at" TIMESTAMP(3)
+);
+
+CREATE INDEX "login_throttling_entries_expires_at_idx" ON "login_throttling_entries" ("expires_at");
+
+CREATE TRIGGER "login_throttling_entries_updated_at" BEFORE UPDATE ON "login_throttling_entries" FOR EACH ROW EXECUTE PROCEDURE "moddatetime" ("updated_at");
@@ -0,0 +1,3 @@
+ALTER TABLE "users" DROP CONSTRAINT "users_auth_id_provider_key";
+ALTER TABLE "users" ADD CONSTRAINT "users_auth_id_key" UNIQUE ("auth_id");
+ALTER TABLE "login_requests" ADD CONSTRAINT "login_requests_user_id_fkey" FOREIGN KEY ("user_id") REFERENCES "users" ("auth_id") ON DELETE SET NULL ON UPDATE CASCADE;
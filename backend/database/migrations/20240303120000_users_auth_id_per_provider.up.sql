@@ -0,0 +1,4 @@
+-- Subjects are only unique within their OpenID provider, like emails
+ALTER TABLE "login_requests" DROP CONSTRAINT "login_requests_user_id_fkey";
+ALTER TABLE "users" DROP CONSTRAINT "users_auth_id_key";
+ALTER TABLE "users" ADD CONSTRAINT "users_auth_id_provider_key" UNIQUE ("auth_id", "provider");
@@ -1,3 +1,5 @@
+#![allow(clippy::useless_conversion)]
+
 use sqlx::{
     postgres::{PgConnectOptions, PgPoolOptions},
     ConnectOptions,
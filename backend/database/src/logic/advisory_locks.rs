@@ -0,0 +1,72 @@
+use sqlx::{Connection, Executor, PgConnection, PgPool, Result};
+use tracing::instrument;
+
+/// Postgres session level advisory lock, identified by a string key. The lock is held by a
+/// dedicated connection, taken out of the pool, meaning Postgres releases it as soon as the
+/// connection is closed, including when the process holding it dies.
+pub struct AdvisoryLock {
+    key: String,
+    conn: PgConnection,
+}
+
+impl AdvisoryLock {
+    /// Acquires the lock if no other session holds it, without waiting
+    #[instrument(skip(pool), err)]
+    pub async fn try_acquire(pool: &PgPool, key: &str) -> Result<Option<Self>> {
+        let mut conn = pool.acquire().await?.detach();
+
+        // Lets Postgres notice within a minute that the host holding the lock is unreachable,
+        // instead of relying on the operating system's TCP keepalive settings
+        conn.execute(
+            "SET tcp_keepalives_idle = 30; SET tcp_keepalives_interval = 10; SET tcp_keepalives_count = 3",
+        )
+        .await?;
+
+        let acquired =
+            sqlx::query_scalar!("SELECT pg_try_advisory_lock(hashtextextended($1, 0))", key)
+                .fetch_one(&mut conn)
+                .await?
+                .unwrap_or_default();
+
+        if !acquired {
+            conn.close().await?;
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            key: key.to_string(),
+            conn,
+        }))
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Ensures the lock is still held, the lock being lost if the connection holding it was closed
+    #[instrument(skip(self), fields(key = self.key), err)]
+    pub async fn renew(&mut self) -> Result<()> {
+        self.conn.ping().await
+    }
+
+    /// Releases the lock so that another session can acquire it right away
+    #[instrument(skip(self), fields(key = self.key), err)]
+    pub async fn release(mut self) -> Result<()> {
+        sqlx::query!(
+            "SELECT pg_advisory_unlock(hashtextextended($1, 0))",
+            self.key
+        )
+        .fetch_one(&mut self.conn)
+        .await?;
+
+        self.conn.close().await
+    }
+}
+
+/// Key of the lock held while a source's background task is executing
+pub fn task_run_lock_key(source_id: &str) -> String {
+    format!("background_tasks:{source_id}")
+}
+
+/// Key of the lock held by the server instance scheduling the background tasks
+pub const TASK_SCHEDULER_LOCK_KEY: &str = "background_tasks";
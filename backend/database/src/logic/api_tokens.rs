@@ -1,7 +1,9 @@
 use sqlx::{PgPool, Result};
 use tracing::instrument;
 
-use crate::schemas::api_tokens::{ApiToken, CreateApiToken, GetApiToken, InternalUpdateApiToken};
+use crate::schemas::api_tokens::{
+    to_strings, ApiToken, CreateApiToken, GetApiToken, InternalUpdateApiToken,
+};
 
 #[instrument(skip(pool), ret, err)]
 pub async fn update_or_create_api_token(
@@ -10,12 +12,19 @@ pub async fn update_or_create_api_token(
     user_id: &str,
     hashed_token: &str,
 ) -> Result<String> {
+    let permissions = data.permissions.as_deref().map(to_strings);
+    let indicator_kinds = data.indicator_kinds.as_deref().map(to_strings);
+
     sqlx::query_scalar!(
-        r#"INSERT INTO api_tokens (user_id, note, expires_at, token) VALUES ($1, $2, $3, $4) ON CONFLICT ("user_id", "note") DO UPDATE SET note = $2 RETURNING id"#,
+        r#"INSERT INTO api_tokens (user_id, note, expires_at, token, permissions, source_ids, indicator_kinds, allowed_ip_ranges) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) ON CONFLICT ("user_id", "note") DO UPDATE SET note = $2 RETURNING id"#,
         user_id,
         data.note,
         data.expires_at,
         hashed_token,
+        permissions.as_deref(),
+        data.source_ids.as_deref(),
+        indicator_kinds.as_deref(),
+        data.allowed_ip_ranges.as_deref(),
     )
     .fetch_one(pool)
     .await
@@ -52,10 +61,14 @@ pub async fn update_api_token(
     user_id: &str,
 ) -> Result<u64> {
     sqlx::query!(
-        "UPDATE api_tokens SET token = COALESCE($1, token), note = COALESCE($2, note), expires_at = COALESCE($3, expires_at) WHERE id = $4 AND user_id = $5",
+        "UPDATE api_tokens SET token = COALESCE($1, token), note = COALESCE($2, note), expires_at = COALESCE($3, expires_at), permissions = COALESCE($4, permissions), source_ids = COALESCE($5, source_ids), indicator_kinds = COALESCE($6, indicator_kinds), allowed_ip_ranges = COALESCE($7, allowed_ip_ranges) WHERE id = $8 AND user_id = $9",
         data.token,
         data.note,
         data.expires_at,
+        data.permissions.as_deref(),
+        data.source_ids.as_deref(),
+        data.indicator_kinds.as_deref(),
+        data.allowed_ip_ranges.as_deref(),
         id,
         user_id
     )
@@ -69,7 +82,7 @@ pub async fn update_api_token(
 pub async fn get_user_api_keys(pool: &PgPool, user_id: &str) -> Result<Vec<ApiToken>> {
     sqlx::query_as!(
         ApiToken,
-        r#"SELECT id, created_at, updated_at, note, expires_at FROM api_tokens WHERE user_id = $1 ORDER BY created_at DESC"#,
+        r#"SELECT id, created_at, updated_at, note, expires_at, permissions, source_ids, indicator_kinds, allowed_ip_ranges, last_used_at, last_used_ip_address, request_count FROM api_tokens WHERE user_id = $1 ORDER BY created_at DESC"#,
         user_id
     )
     .fetch_all(pool)
@@ -81,9 +94,22 @@ pub async fn get_user_api_keys(pool: &PgPool, user_id: &str) -> Result<Vec<ApiTo
 pub async fn get_api_token_from_id(pool: &PgPool, id: &str) -> Result<Option<GetApiToken>> {
     sqlx::query_as!(
         GetApiToken,
-        r#"SELECT token, user_id FROM api_tokens WHERE id = $1"#,
+        r#"SELECT token, user_id, permissions, source_ids, indicator_kinds, allowed_ip_ranges FROM api_tokens WHERE id = $1"#,
         id
     )
     .fetch_optional(pool)
     .await
 }
+
+#[instrument(skip(pool), err)]
+pub async fn record_api_token_usage(pool: &PgPool, id: &str, ip_address: &str) -> Result<()> {
+    sqlx::query!(
+        "UPDATE api_tokens SET last_used_at = NOW(), last_used_ip_address = $2, request_count = request_count + 1 WHERE id = $1",
+        id,
+        ip_address
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
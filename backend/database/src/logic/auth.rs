@@ -1,7 +1,9 @@
 use sqlx::PgPool;
 use tracing::instrument;
 
-use crate::schemas::auth::{CreateLoginRequest, LoginRequest};
+use crate::schemas::auth::{
+    CreateLoginRequest, CreateUserToken, LoginRequest, UserToken, UserTokenKind,
+};
 
 #[instrument(skip(pool), ret, err)]
 pub async fn create_login_request(
@@ -61,3 +63,88 @@ WHERE id = $2;
 
     Ok(())
 }
+
+/// Creates a token replacing the unused ones of the same kind previously sent to the user
+#[instrument(skip(pool), ret, err)]
+pub async fn create_user_token(pool: &PgPool, token: &CreateUserToken) -> sqlx::Result<String> {
+    let mut transaction = pool.begin().await?;
+
+    sqlx::query!(
+        "DELETE FROM user_tokens WHERE user_id = $1 AND kind = $2 AND used_at IS NULL;",
+        token.user_id,
+        token.kind as _,
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    let id = sqlx::query_scalar!(
+        "INSERT INTO user_tokens (user_id, kind, token, expires_at) VALUES ($1, $2, $3, $4) RETURNING id;",
+        token.user_id,
+        token.kind as _,
+        token.token,
+        token.expires_at,
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+
+    transaction.commit().await?;
+
+    Ok(id)
+}
+
+/// Gets a token that was neither used nor has expired
+#[instrument(skip(pool), ret, err)]
+pub async fn get_valid_user_token(
+    pool: &PgPool,
+    id: &str,
+    kind: UserTokenKind,
+) -> sqlx::Result<Option<UserToken>> {
+    sqlx::query_as!(
+        UserToken,
+        r#"
+SELECT id, created_at, updated_at, kind as "kind: _", token, expires_at, used_at, user_id
+FROM user_tokens
+WHERE id = $1 AND kind = $2 AND used_at IS NULL AND expires_at > NOW();
+        "#,
+        id,
+        kind as _,
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Marks the token as used, returning whether it was not already used
+#[instrument(skip(pool), ret, err)]
+pub async fn use_user_token(pool: &PgPool, id: &str) -> sqlx::Result<bool> {
+    sqlx::query!(
+        "UPDATE user_tokens SET used_at = NOW() WHERE id = $1 AND used_at IS NULL;",
+        id
+    )
+    .execute(pool)
+    .await
+    .map(|result| result.rows_affected() == 1)
+}
+
+/// Counts a failed attempt at using the token, using it up once it reached the maximum number of
+/// failed attempts. Returns whether it was used up.
+#[instrument(skip(pool), ret, err)]
+pub async fn record_user_token_failure(
+    pool: &PgPool,
+    id: &str,
+    max_failed_attempts: i32,
+) -> sqlx::Result<bool> {
+    Ok(sqlx::query_scalar!(
+        r#"
+UPDATE user_tokens
+SET failed_attempts = failed_attempts + 1,
+    used_at = CASE WHEN failed_attempts + 1 >= $2 THEN NOW() END
+WHERE id = $1 AND used_at IS NULL
+RETURNING used_at IS NOT NULL as "used_up!";
+        "#,
+        id,
+        max_failed_attempts
+    )
+    .fetch_optional(pool)
+    .await?
+    .unwrap_or_default())
+}
@@ -0,0 +1,129 @@
+use sqlx::{PgExecutor, PgPool, QueryBuilder, Result};
+use tracing::instrument;
+
+use crate::schemas::{
+    feeds::{FeedEntry, NewFeedEntry},
+    ingestion::{FeedValidators, IngestionCounts},
+};
+
+#[instrument(skip(pool), err)]
+pub async fn get_feed_entries(
+    pool: &PgPool,
+    source_id: &str,
+    kind: &str,
+    data: &str,
+) -> Result<Vec<FeedEntry>> {
+    sqlx::query_as!(
+        FeedEntry,
+        "SELECT kind, data, metadata, first_seen, last_seen, active FROM feed_entries WHERE source_id = $1 AND kind = $2 AND data = $3",
+        source_id,
+        kind,
+        data
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Inserts the new entries and updates the existing ones of a feed, marking all of them as seen at
+/// the start of the current transaction
+#[instrument(skip(entries, pool), err)]
+pub async fn upsert_feed_entries<'e>(
+    source_id: &str,
+    mut entries: Vec<NewFeedEntry>,
+    pool: impl PgExecutor<'e>,
+) -> Result<IngestionCounts> {
+    // An entry can only be upserted once per statement, keeping the last occurrence
+    entries.reverse();
+    entries.sort_by(|a, b| (&a.kind, &a.data).cmp(&(&b.kind, &b.data)));
+    entries.dedup_by(|a, b| a.kind == b.kind && a.data == b.data);
+
+    let mut query_builder = QueryBuilder::new(
+        "WITH upserted AS (INSERT INTO feed_entries (source_id, kind, data, metadata) ",
+    );
+
+    query_builder.push_values(&entries, |mut b, entry| {
+        b.push_bind(source_id)
+            .push_bind(&entry.kind)
+            .push_bind(&entry.data)
+            .push_bind(&entry.metadata);
+    });
+
+    query_builder.push(
+        r#"
+ON CONFLICT (source_id, kind, data) DO UPDATE SET
+metadata = EXCLUDED.metadata,
+updated_at = CASE WHEN NOT feed_entries.active OR feed_entries.metadata IS DISTINCT FROM EXCLUDED.metadata THEN NOW() ELSE feed_entries.updated_at END,
+last_seen = NOW(),
+active = TRUE
+RETURNING xmax = 0 AS inserted, updated_at = NOW()::TIMESTAMP(3) AS changed)
+SELECT COUNT(*) FILTER (WHERE inserted)::INT, COUNT(*) FILTER (WHERE changed AND NOT inserted)::INT FROM upserted"#,
+    );
+
+    let (added, updated): (i32, i32) = query_builder.build_query_as().fetch_one(pool).await?;
+
+    Ok(IngestionCounts {
+        added,
+        updated,
+        removed: 0,
+    })
+}
+
+/// Marks the entries of a feed that were not seen since the start of the current transaction as
+/// inactive
+#[instrument(skip(pool), ret, err)]
+pub async fn deactivate_unseen_feed_entries<'e>(
+    source_id: &str,
+    pool: impl PgExecutor<'e>,
+) -> Result<u64> {
+    sqlx::query!(
+        "UPDATE feed_entries SET active = FALSE, updated_at = NOW() WHERE source_id = $1 AND active AND last_seen < NOW()::TIMESTAMP(3)",
+        source_id
+    )
+    .execute(pool)
+    .await
+    .map(|i| i.rows_affected())
+}
+
+/// Validators of the last download of a feed, ignoring the downloads made before the source was
+/// last updated as its URL may have changed since
+#[instrument(skip(pool), err)]
+pub async fn get_feed_validators(pool: &PgPool, source_id: &str) -> Result<FeedValidators> {
+    sqlx::query_as!(
+        FeedValidators,
+        r#"
+        SELECT etag, last_modified FROM feed_refreshes
+        WHERE source_id = $1 AND NOT not_modified AND refreshed_at > (SELECT updated_at FROM sources WHERE id = $1)
+        ORDER BY refreshed_at DESC LIMIT 1
+        "#,
+        source_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map(Option::unwrap_or_default)
+}
+
+#[instrument(skip(headers, pool), err)]
+pub async fn insert_feed_refresh<'e>(
+    source_id: &str,
+    headers: &str,
+    validators: &FeedValidators,
+    not_modified: bool,
+    counts: IngestionCounts,
+    pool: impl PgExecutor<'e>,
+) -> Result<()> {
+    sqlx::query!(
+        "INSERT INTO feed_refreshes (source_id, response_headers, etag, last_modified, not_modified, num_added, num_updated, num_removed) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        source_id,
+        headers,
+        validators.etag,
+        validators.last_modified,
+        not_modified,
+        counts.added,
+        counts.updated,
+        counts.removed
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -17,12 +17,13 @@ use crate::{
 pub async fn create_list(pool: &PgPool, data: CreateIgnoreList, user_id: &str) -> Result<IdSlug> {
     sqlx::query_as!(
         IdSlug,
-        "INSERT INTO ignore_lists (name, slug, description, enabled, created_user_id) VALUES ($1, $2, $3, $4, $5) RETURNING id, slug",
+        "INSERT INTO ignore_lists (name, slug, description, enabled, created_user_id, team_id) VALUES ($1, $2, $3, $4, $5, $6) RETURNING id, slug",
         data.name,
         slugify(&data.name),
         data.description,
         data.enabled,
-        user_id
+        user_id,
+        data.team_id
     )
     .fetch_one(pool)
     .await
@@ -35,6 +36,7 @@ pub async fn update_list(
     id: &str,
     data: UpdateIgnoreList,
     user_id: &str,
+    team_ids: Option<&[String]>,
 ) -> Result<u64> {
     sqlx::query!(
         r#"UPDATE ignore_lists SET
@@ -43,15 +45,18 @@ slug = COALESCE($2, slug),
 description = COALESCE($3, description),
 enabled = COALESCE($4, enabled),
 global = COALESCE($5, global),
-updated_user_id = $6
-WHERE id = $7"#,
+updated_user_id = $6,
+team_id = CASE WHEN $8::TEXT IS NULL THEN team_id ELSE NULLIF($8, '') END
+WHERE id = $7 AND (team_id IS NULL OR $9::TEXT[] IS NULL OR team_id = ANY($9))"#,
         data.name,
         data.name.as_ref().map(|n| slugify(&n)),
         data.description,
         data.enabled,
         data.global,
         user_id,
-        id
+        id,
+        data.team_id,
+        team_ids
     )
     .execute(pool)
     .await
@@ -60,20 +65,29 @@ WHERE id = $7"#,
 }
 
 #[instrument(skip(pool), ret, err)]
-pub async fn delete_list(pool: &PgPool, list_id: &str) -> Result<u64> {
-    sqlx::query!("DELETE FROM ignore_lists WHERE id = $1", list_id)
-        .execute(pool)
-        .await
-        .map_err(Into::into)
-        .map(|i| i.rows_affected())
+pub async fn delete_list(pool: &PgPool, list_id: &str, team_ids: Option<&[String]>) -> Result<u64> {
+    sqlx::query!(
+        "DELETE FROM ignore_lists WHERE id = $1 AND (team_id IS NULL OR $2::TEXT[] IS NULL OR team_id = ANY($2))",
+        list_id,
+        team_ids
+    )
+    .execute(pool)
+    .await
+    .map_err(Into::into)
+    .map(|i| i.rows_affected())
 }
 
 #[instrument(skip(pool), ret, err)]
-pub async fn get_list(pool: &PgPool, list_id: &str) -> Result<Option<IgnoreList>> {
+pub async fn get_list(
+    pool: &PgPool,
+    list_id: &str,
+    team_ids: Option<&[String]>,
+) -> Result<Option<IgnoreList>> {
     sqlx::query_as!(
         IgnoreList,
-        "SELECT * FROM ignore_lists WHERE id = $1",
-        list_id
+        "SELECT * FROM ignore_lists WHERE id = $1 AND (team_id IS NULL OR $2::TEXT[] IS NULL OR team_id = ANY($2))",
+        list_id,
+        team_ids
     )
     .fetch_optional(pool)
     .await
@@ -81,27 +95,66 @@ pub async fn get_list(pool: &PgPool, list_id: &str) -> Result<Option<IgnoreList>
 }
 
 #[instrument(skip(pool), ret, err)]
-pub async fn get_global_lists(pool: &PgPool) -> Result<Vec<IgnoreList>> {
-    sqlx::query_as!(IgnoreList, "SELECT * FROM ignore_lists WHERE global = TRUE")
-        .fetch_all(pool)
-        .await
-        .map_err(Into::into)
+pub async fn get_global_lists(
+    pool: &PgPool,
+    team_ids: Option<&[String]>,
+) -> Result<Vec<IgnoreList>> {
+    sqlx::query_as!(
+        IgnoreList,
+        "SELECT * FROM ignore_lists WHERE global = TRUE AND (team_id IS NULL OR $1::TEXT[] IS NULL OR team_id = ANY($1))",
+        team_ids
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// Gets the ignore lists, limited to the shared ones and the ones of the given teams unless
+/// `team_ids` is `None`
+#[instrument(skip(pool), ret, err)]
+pub async fn get_lists(pool: &PgPool, team_ids: Option<&[String]>) -> Result<Vec<IgnoreList>> {
+    sqlx::query_as!(
+        IgnoreList,
+        "SELECT * FROM ignore_lists WHERE team_id IS NULL OR $1::TEXT[] IS NULL OR team_id = ANY($1)",
+        team_ids
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
 }
 
+/// Filters the ignore lists to the ones sources of the team can use, either shared or part of the
+/// team
 #[instrument(skip(pool), ret, err)]
-pub async fn get_lists(pool: &PgPool) -> Result<Vec<IgnoreList>> {
-    sqlx::query_as!(IgnoreList, "SELECT * FROM ignore_lists")
-        .fetch_all(pool)
-        .await
-        .map_err(Into::into)
+pub async fn get_team_usable_list_ids(
+    pool: &PgPool,
+    list_ids: &[String],
+    team_id: Option<&str>,
+) -> Result<Vec<String>> {
+    sqlx::query_scalar!(
+        "SELECT id FROM ignore_lists WHERE id = ANY($1) AND (team_id IS NULL OR team_id = $2)",
+        list_ids,
+        team_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
 }
 
 #[instrument(skip(pool), ret, err)]
-pub async fn get_ignore_list_id_from_slug(pool: &PgPool, slug: &str) -> Result<Option<String>> {
-    sqlx::query_scalar!("SELECT id FROM ignore_lists WHERE slug = $1", slug)
-        .fetch_optional(pool)
-        .await
-        .map_err(Into::into)
+pub async fn get_ignore_list_id_from_slug(
+    pool: &PgPool,
+    slug: &str,
+    team_ids: Option<&[String]>,
+) -> Result<Option<String>> {
+    sqlx::query_scalar!(
+        "SELECT id FROM ignore_lists WHERE slug = $1 AND (team_id IS NULL OR $2::TEXT[] IS NULL OR team_id = ANY($2))",
+        slug,
+        team_ids
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(Into::into)
 }
 
 #[instrument(skip(pool), ret, err)]
@@ -171,7 +224,11 @@ ORDER BY providers.enabled DESC, providers.name"#,
 }
 
 #[instrument(skip(pool), ret, err)]
-pub async fn get_list_sources(pool: &PgPool, list_id: &str) -> Result<Vec<Source>> {
+pub async fn get_list_sources(
+    pool: &PgPool,
+    list_id: &str,
+    team_ids: Option<&[String]>,
+) -> Result<Vec<Source>> {
     sqlx::query_as!(
         Source,
         r#"SELECT sources.id,
@@ -188,6 +245,7 @@ sources.supported_indicators,
 sources.disabled_indicators,
 sources.task_enabled,
 sources.task_interval,
+sources.task_cron,
 sources.config,
 sources.config_values,
 sources.limit_enabled,
@@ -199,25 +257,31 @@ sources.provider_id,
 sources.kind as "kind: _",
 sources.source_code,
 sources.created_user_id,
-sources.updated_user_id
+sources.updated_user_id,
+sources.team_id
 FROM sources
 INNER JOIN source_ignore_lists ON source_ignore_lists.source_id = sources.id
-WHERE source_ignore_lists.ignore_list_id = $1"#,
-        list_id
+WHERE source_ignore_lists.ignore_list_id = $1 AND (sources.team_id IS NULL OR $2::TEXT[] IS NULL OR sources.team_id = ANY($2))"#,
+        list_id,
+        team_ids
     )
     .fetch_all(pool)
     .await
     .map_err(Into::into)
 }
 
+/// Unsets the sources affected by an ignore list, limited to the shared ones and the ones of the
+/// given teams unless `team_ids` is `None`
 #[instrument(skip(pool), ret, err)]
 pub async fn delete_all_ignore_list_sources<'e>(
     pool: impl PgExecutor<'e>,
     ignore_list_id: &str,
+    team_ids: Option<&[String]>,
 ) -> Result<u64> {
     sqlx::query!(
-        "DELETE FROM source_ignore_lists WHERE ignore_list_id = $1",
-        ignore_list_id
+        "DELETE FROM source_ignore_lists WHERE ignore_list_id = $1 AND source_id IN (SELECT id FROM sources WHERE team_id IS NULL OR $2::TEXT[] IS NULL OR team_id = ANY($2))",
+        ignore_list_id,
+        team_ids
     )
     .execute(pool)
     .await
@@ -294,11 +358,16 @@ pub async fn delete_all_ignore_list_entries<'e>(
 }
 
 #[instrument(skip(pool), ret, err)]
-pub async fn get_user_ignore_lists(pool: &PgPool, user_id: &str) -> Result<Vec<IgnoreList>> {
+pub async fn get_user_ignore_lists(
+    pool: &PgPool,
+    user_id: &str,
+    team_ids: Option<&[String]>,
+) -> Result<Vec<IgnoreList>> {
     sqlx::query_as!(
         IgnoreList,
-        r#"SELECT ignore_lists.* FROM ignore_lists WHERE created_user_id = $1"#,
-        user_id
+        r#"SELECT ignore_lists.* FROM ignore_lists WHERE created_user_id = $1 AND (team_id IS NULL OR $2::TEXT[] IS NULL OR team_id = ANY($2))"#,
+        user_id,
+        team_ids
     )
     .fetch_all(pool)
     .await
@@ -0,0 +1,215 @@
+use sqlx::{PgPool, Result};
+use tracing::instrument;
+
+use crate::schemas::mfa::{CreateWebAuthnCredential, UserTotp, WebAuthnCredential};
+
+/// Whether the user enrolled a second factor that is needed to login
+#[instrument(skip(pool), ret, err)]
+pub async fn user_has_mfa(pool: &PgPool, user_id: &str) -> Result<bool> {
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM user_totp WHERE user_id = $1 AND enabled) OR EXISTS(SELECT 1 FROM user_webauthn_credentials WHERE user_id = $1) AS "enabled!";"#,
+        user_id
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Creates a pending TOTP enrollment, replacing any previous one
+#[instrument(skip(pool, secret), ret, err)]
+pub async fn create_or_replace_user_totp(
+    pool: &PgPool,
+    user_id: &str,
+    secret: &[u8],
+) -> Result<()> {
+    sqlx::query!(
+        "INSERT INTO user_totp (user_id, secret) VALUES ($1, $2) ON CONFLICT (user_id) DO UPDATE SET secret = $2, enabled = FALSE, last_used_step = NULL;",
+        user_id,
+        secret
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[instrument(skip(pool), err)]
+pub async fn get_user_totp(pool: &PgPool, user_id: &str) -> Result<Option<UserTotp>> {
+    sqlx::query_as!(
+        UserTotp,
+        "SELECT * FROM user_totp WHERE user_id = $1;",
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Confirms a pending TOTP enrollment, returning whether it was pending
+#[instrument(skip(pool), ret, err)]
+pub async fn enable_user_totp(pool: &PgPool, user_id: &str, step: i64) -> Result<bool> {
+    sqlx::query!(
+        "UPDATE user_totp SET enabled = TRUE, last_used_step = $2 WHERE user_id = $1 AND NOT enabled;",
+        user_id,
+        step
+    )
+    .execute(pool)
+    .await
+    .map(|result| result.rows_affected() == 1)
+}
+
+/// Records the time step of a TOTP code used to login, returning whether it was not used yet
+#[instrument(skip(pool), ret, err)]
+pub async fn use_user_totp_step(pool: &PgPool, user_id: &str, step: i64) -> Result<bool> {
+    sqlx::query!(
+        "UPDATE user_totp SET last_used_step = $2 WHERE user_id = $1 AND enabled AND (last_used_step IS NULL OR last_used_step < $2);",
+        user_id,
+        step
+    )
+    .execute(pool)
+    .await
+    .map(|result| result.rows_affected() == 1)
+}
+
+#[instrument(skip(pool), ret, err)]
+pub async fn delete_user_totp(pool: &PgPool, user_id: &str) -> Result<u64> {
+    sqlx::query!("DELETE FROM user_totp WHERE user_id = $1;", user_id)
+        .execute(pool)
+        .await
+        .map(|result| result.rows_affected())
+}
+
+/// Replaces the recovery codes of the user by the hashed ones
+#[instrument(skip(pool, hashed_codes), ret, err)]
+pub async fn replace_user_recovery_codes(
+    pool: &PgPool,
+    user_id: &str,
+    hashed_codes: &[String],
+) -> Result<()> {
+    let mut transaction = pool.begin().await?;
+
+    sqlx::query!(
+        "DELETE FROM user_recovery_codes WHERE user_id = $1;",
+        user_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    sqlx::query!(
+        "INSERT INTO user_recovery_codes (user_id, code) SELECT $1, UNNEST($2::TEXT[]);",
+        user_id,
+        hashed_codes
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    transaction.commit().await
+}
+
+#[instrument(skip(pool), ret, err)]
+pub async fn count_unused_user_recovery_codes(pool: &PgPool, user_id: &str) -> Result<i64> {
+    sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM user_recovery_codes WHERE user_id = $1 AND used_at IS NULL;"#,
+        user_id
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Marks the recovery code as used, returning whether it exists and was not already used
+#[instrument(skip(pool, hashed_code), ret, err)]
+pub async fn use_user_recovery_code(
+    pool: &PgPool,
+    user_id: &str,
+    hashed_code: &str,
+) -> Result<bool> {
+    sqlx::query!(
+        "UPDATE user_recovery_codes SET used_at = NOW() WHERE user_id = $1 AND code = $2 AND used_at IS NULL;",
+        user_id,
+        hashed_code
+    )
+    .execute(pool)
+    .await
+    .map(|result| result.rows_affected() > 0)
+}
+
+#[instrument(skip(pool), ret, err)]
+pub async fn delete_user_recovery_codes(pool: &PgPool, user_id: &str) -> Result<u64> {
+    sqlx::query!(
+        "DELETE FROM user_recovery_codes WHERE user_id = $1;",
+        user_id
+    )
+    .execute(pool)
+    .await
+    .map(|result| result.rows_affected())
+}
+
+#[instrument(skip(pool), ret, err)]
+pub async fn create_webauthn_credential(
+    pool: &PgPool,
+    credential: &CreateWebAuthnCredential,
+) -> Result<String> {
+    sqlx::query_scalar!(
+        "INSERT INTO user_webauthn_credentials (user_id, name, credential_id, public_key, sign_count) VALUES ($1, $2, $3, $4, $5) RETURNING id;",
+        credential.user_id,
+        credential.name,
+        credential.credential_id,
+        credential.public_key,
+        credential.sign_count,
+    )
+    .fetch_one(pool)
+    .await
+}
+
+#[instrument(skip(pool), err)]
+pub async fn get_user_webauthn_credentials(
+    pool: &PgPool,
+    user_id: &str,
+) -> Result<Vec<WebAuthnCredential>> {
+    sqlx::query_as!(
+        WebAuthnCredential,
+        "SELECT * FROM user_webauthn_credentials WHERE user_id = $1 ORDER BY created_at;",
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+#[instrument(skip(pool), err)]
+pub async fn get_user_webauthn_credential(
+    pool: &PgPool,
+    user_id: &str,
+    credential_id: &str,
+) -> Result<Option<WebAuthnCredential>> {
+    sqlx::query_as!(
+        WebAuthnCredential,
+        "SELECT * FROM user_webauthn_credentials WHERE user_id = $1 AND credential_id = $2;",
+        user_id,
+        credential_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+#[instrument(skip(pool), ret, err)]
+pub async fn use_webauthn_credential(pool: &PgPool, id: &str, sign_count: i64) -> Result<()> {
+    sqlx::query!(
+        "UPDATE user_webauthn_credentials SET sign_count = $2, last_used_at = NOW() WHERE id = $1;",
+        id,
+        sign_count
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[instrument(skip(pool), ret, err)]
+pub async fn delete_webauthn_credential(pool: &PgPool, id: &str, user_id: &str) -> Result<u64> {
+    sqlx::query!(
+        "DELETE FROM user_webauthn_credentials WHERE id = $1 AND user_id = $2;",
+        id,
+        user_id
+    )
+    .execute(pool)
+    .await
+    .map(|result| result.rows_affected())
+}
@@ -1,7 +1,7 @@
 use sqlx::{PgPool, Result};
 use tracing::instrument;
 
-use crate::schemas::notifications::{MinimalSource, NotificationKind};
+use crate::schemas::notifications::{FailingSourceTask, MinimalSource, NotificationKind};
 
 #[instrument(skip(pool), ret, err)]
 pub async fn get_sources_without_secrets_set(pool: &PgPool) -> Result<Vec<NotificationKind>> {
@@ -26,3 +26,31 @@ HAVING COUNT(source_secrets) > 0"#
             .collect()
     })
 }
+
+#[instrument(skip(pool), ret, err)]
+pub async fn get_sources_with_failing_tasks(
+    pool: &PgPool,
+    threshold: i32,
+) -> Result<Vec<NotificationKind>> {
+    sqlx::query_as!(
+        FailingSourceTask,
+        r#"SELECT sources.id,
+sources.slug,
+sources.name,
+COUNT(task_runs)::INT as "num_consecutive_failures!: _",
+(ARRAY_AGG(task_runs.error ORDER BY task_runs.started_at DESC))[1] as last_error
+FROM sources
+INNER JOIN task_runs ON sources.id = task_runs.source_id
+WHERE sources.enabled = TRUE AND sources.task_enabled = TRUE AND task_runs.status = 'failed' AND task_runs.started_at > COALESCE(
+    (SELECT MAX(started_at) FROM task_runs AS succeeded_runs WHERE succeeded_runs.source_id = sources.id AND succeeded_runs.status = 'succeeded'),
+    '-infinity'
+)
+GROUP BY sources.id
+HAVING COUNT(task_runs) >= $1"#,
+        threshold as i64
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+    .map(|n| n.into_iter().map(NotificationKind::FailingSourceTask).collect())
+}
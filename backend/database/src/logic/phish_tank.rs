@@ -1,13 +1,16 @@
-use sqlx::{PgPool, QueryBuilder, Result};
+use sqlx::{PgExecutor, PgPool, QueryBuilder, Result};
 use tracing::instrument;
 
-use crate::schemas::phish_tank::Entry;
+use crate::schemas::{
+    ingestion::{FeedValidators, IngestionCounts},
+    phish_tank::Entry,
+};
 
 #[instrument(skip(pool), err)]
 pub async fn get_phish_tank_by_url(url: &str, pool: &PgPool) -> Result<Vec<Entry>> {
     sqlx::query_as!(
         Entry,
-        "SELECT phish_id, phish_detail_url, url, submission_time, verification_time, target, details FROM phishtank WHERE url = $1",
+        "SELECT phish_id, phish_detail_url, url, submission_time, verification_time, target, details, first_seen, last_seen, active FROM phishtank WHERE url = $1",
         url
     )
     .fetch_all(pool)
@@ -18,18 +21,27 @@ pub async fn get_phish_tank_by_url(url: &str, pool: &PgPool) -> Result<Vec<Entry
 pub async fn get_phish_tank_by_domain(domain: &str, pool: &PgPool) -> Result<Vec<Entry>> {
     sqlx::query_as!(
         Entry,
-        "SELECT phish_id, phish_detail_url, url, submission_time, verification_time, target, details FROM phishtank WHERE url ILIKE '%' || $1 || '%'",
+        "SELECT phish_id, phish_detail_url, url, submission_time, verification_time, target, details, first_seen, last_seen, active FROM phishtank WHERE url ILIKE '%' || $1 || '%'",
         domain
     )
     .fetch_all(pool)
     .await
 }
 
-#[instrument(skip_all)]
-pub async fn insert_entries(entries: &[Entry], pool: &PgPool) -> Result<()> {
-    let mut query_builder = QueryBuilder::new("INSERT INTO phishtank (phish_id, phish_detail_url, url, submission_time, verification_time, target, details) ");
+/// Inserts the new entries and updates the existing ones, marking all of them as seen at the
+/// start of the current transaction
+#[instrument(skip_all, err)]
+pub async fn upsert_entries<'e>(
+    mut entries: Vec<Entry>,
+    pool: impl PgExecutor<'e>,
+) -> Result<IngestionCounts> {
+    // An entry can only be upserted once per statement
+    entries.sort_unstable_by_key(|entry| entry.phish_id);
+    entries.dedup_by_key(|entry| entry.phish_id);
 
-    query_builder.push_values(entries, |mut b, entry| {
+    let mut query_builder = QueryBuilder::new("WITH upserted AS (INSERT INTO phishtank (phish_id, phish_detail_url, url, submission_time, verification_time, target, details) ");
+
+    query_builder.push_values(&entries, |mut b, entry| {
         b.push_bind(entry.phish_id)
             .push_bind(&entry.phish_detail_url)
             .push_bind(&entry.url)
@@ -39,20 +51,68 @@ pub async fn insert_entries(entries: &[Entry], pool: &PgPool) -> Result<()> {
             .push_bind(&entry.details);
     });
 
-    query_builder.push(" ON CONFLICT DO NOTHING");
+    query_builder.push(
+        r#"
+ON CONFLICT (phish_id) DO UPDATE SET
+phish_detail_url = EXCLUDED.phish_detail_url,
+url = EXCLUDED.url,
+submission_time = EXCLUDED.submission_time,
+verification_time = EXCLUDED.verification_time,
+target = EXCLUDED.target,
+details = EXCLUDED.details,
+updated_at = CASE WHEN NOT phishtank.active OR (phishtank.phish_detail_url, phishtank.url, phishtank.submission_time, phishtank.verification_time, phishtank.target, phishtank.details) IS DISTINCT FROM (EXCLUDED.phish_detail_url, EXCLUDED.url, EXCLUDED.submission_time, EXCLUDED.verification_time, EXCLUDED.target, EXCLUDED.details) THEN NOW() ELSE phishtank.updated_at END,
+last_seen = NOW(),
+active = TRUE
+RETURNING xmax = 0 AS inserted, updated_at = NOW()::TIMESTAMP(3) AS changed)
+SELECT COUNT(*) FILTER (WHERE inserted)::INT, COUNT(*) FILTER (WHERE changed AND NOT inserted)::INT FROM upserted"#,
+    );
 
-    let query = query_builder.build();
+    let (added, updated): (i32, i32) = query_builder.build_query_as().fetch_one(pool).await?;
 
-    query.execute(pool).await?;
+    Ok(IngestionCounts {
+        added,
+        updated,
+        removed: 0,
+    })
+}
 
-    Ok(())
+/// Marks the entries that were not seen since the start of the current transaction as inactive
+#[instrument(skip_all, ret, err)]
+pub async fn deactivate_unseen_entries<'e>(pool: impl PgExecutor<'e>) -> Result<u64> {
+    sqlx::query!("UPDATE phishtank SET active = FALSE, updated_at = NOW() WHERE active AND last_seen < NOW()::TIMESTAMP(3)")
+        .execute(pool)
+        .await
+        .map(|i| i.rows_affected())
+}
+
+#[instrument(skip_all, err)]
+pub async fn get_phish_tank_validators(pool: &PgPool) -> Result<FeedValidators> {
+    sqlx::query_as!(
+        FeedValidators,
+        "SELECT etag, last_modified FROM phishtank_refreshes WHERE NOT not_modified ORDER BY refreshed_at DESC LIMIT 1"
+    )
+    .fetch_optional(pool)
+    .await
+    .map(Option::unwrap_or_default)
 }
 
-#[instrument(skip_all)]
-pub async fn insert_phish_tank_refresh(headers: &str, pool: &PgPool) -> Result<()> {
+#[instrument(skip_all, err)]
+pub async fn insert_phish_tank_refresh<'e>(
+    headers: &str,
+    validators: &FeedValidators,
+    not_modified: bool,
+    counts: IngestionCounts,
+    pool: impl PgExecutor<'e>,
+) -> Result<()> {
     sqlx::query!(
-        "INSERT INTO phishtank_refreshes (response_headers) VALUES ($1)",
-        headers
+        "INSERT INTO phishtank_refreshes (response_headers, etag, last_modified, not_modified, num_added, num_updated, num_removed) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        headers,
+        validators.etag,
+        validators.last_modified,
+        not_modified,
+        counts.added,
+        counts.updated,
+        counts.removed
     )
     .execute(pool)
     .await?;
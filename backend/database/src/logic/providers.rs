@@ -122,14 +122,16 @@ pub async fn delete_provider(pool: &PgPool, id: &str) -> Result<u64> {
 pub async fn get_provider_ignore_lists(
     pool: &PgPool,
     provider_id: &str,
+    team_ids: Option<&[String]>,
 ) -> Result<Vec<IgnoreList>> {
     sqlx::query_as!(
         IgnoreList,
         r#"SELECT ignore_lists.*
 FROM ignore_lists
 INNER JOIN provider_ignore_lists ON provider_ignore_lists.ignore_list_id = ignore_lists.id
-WHERE provider_ignore_lists.source_provider_id = $1"#,
-        provider_id
+WHERE provider_ignore_lists.source_provider_id = $1 AND (ignore_lists.team_id IS NULL OR $2::TEXT[] IS NULL OR ignore_lists.team_id = ANY($2))"#,
+        provider_id,
+        team_ids
     )
     .fetch_all(pool)
     .await
@@ -7,19 +7,25 @@ use crate::schemas::{
 };
 
 #[instrument(skip(pool), ret, err)]
-pub async fn create_request(pool: &PgPool, indicator: &Indicator, user_id: &str) -> Result<String> {
+pub async fn create_request(
+    pool: &PgPool,
+    indicator: &Indicator,
+    user_id: &str,
+    team_id: Option<&str>,
+) -> Result<String> {
     let trace_id = shared::telemetry::Telemetry::get_trace_id();
 
     sqlx::query_scalar!(
         r#"
-        INSERT INTO requests (data, kind, trace_id, user_id)
-        VALUES ($1, $2, $3, $4)
+        INSERT INTO requests (data, kind, trace_id, user_id, team_id)
+        VALUES ($1, $2, $3, $4, $5)
         RETURNING id
         "#,
         indicator.data,
         indicator.db_kind(),
         trace_id,
-        user_id
+        user_id,
+        team_id
     )
     .fetch_one(pool)
     .await
@@ -72,23 +78,38 @@ pub async fn create_source_request(
     .map_err(Into::into)
 }
 
+/// Gets the requests, limited to the shared ones and the ones of the given teams unless
+/// `team_ids` is `None`
 #[instrument(skip(pool), ret, err)]
-pub async fn get_requests(pool: &PgPool) -> Result<Vec<Request>> {
-    sqlx::query_as!(Request, "SELECT * FROM requests ORDER BY created_at DESC")
-        .fetch_all(pool)
-        .await
-        .map_err(Into::into)
+pub async fn get_requests(pool: &PgPool, team_ids: Option<&[String]>) -> Result<Vec<Request>> {
+    sqlx::query_as!(
+        Request,
+        r#"
+        SELECT * FROM requests
+        WHERE team_id IS NULL OR $1::TEXT[] IS NULL OR team_id = ANY($1)
+        ORDER BY created_at DESC
+        "#,
+        team_ids
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
 }
 
 #[instrument(skip(pool), ret, err)]
-pub async fn get_request(pool: &PgPool, request_id: &str) -> Result<Option<Request>> {
+pub async fn get_request(
+    pool: &PgPool,
+    request_id: &str,
+    team_ids: Option<&[String]>,
+) -> Result<Option<Request>> {
     sqlx::query_as!(
         Request,
         r#"
         SELECT * FROM requests
-        WHERE id = $1
+        WHERE id = $1 AND (team_id IS NULL OR $2::TEXT[] IS NULL OR team_id = ANY($2))
         "#,
         request_id,
+        team_ids,
     )
     .fetch_optional(pool)
     .await
@@ -99,15 +120,18 @@ pub async fn get_request(pool: &PgPool, request_id: &str) -> Result<Option<Reque
 pub async fn get_request_source_requests(
     pool: &PgPool,
     request_id: &str,
+    team_ids: Option<&[String]>,
 ) -> Result<Vec<SourceRequest>> {
     sqlx::query_as!(
         SourceRequest,
         r#"
-        SELECT * FROM source_requests
-        WHERE request_id = $1
+        SELECT source_requests.* FROM source_requests
+        INNER JOIN requests ON requests.id = source_requests.request_id
+        WHERE request_id = $1 AND (requests.team_id IS NULL OR $2::TEXT[] IS NULL OR requests.team_id = ANY($2))
         ORDER BY started_at DESC
         "#,
         request_id,
+        team_ids,
     )
     .fetch_all(pool)
     .await
@@ -115,16 +139,21 @@ pub async fn get_request_source_requests(
 }
 
 #[instrument(skip(pool), ret, err)]
-pub async fn get_source_requests(pool: &PgPool, source_id: &str) -> Result<Vec<Request>> {
+pub async fn get_source_requests(
+    pool: &PgPool,
+    source_id: &str,
+    team_ids: Option<&[String]>,
+) -> Result<Vec<Request>> {
     sqlx::query_as!(
         Request,
         r#"
         SELECT requests.* FROM requests
         INNER JOIN source_requests ON requests.id = source_requests.request_id
-        WHERE source_id = $1
+        WHERE source_id = $1 AND (requests.team_id IS NULL OR $2::TEXT[] IS NULL OR requests.team_id = ANY($2))
         ORDER BY started_at DESC
         "#,
         source_id,
+        team_ids,
     )
     .fetch_all(pool)
     .await
@@ -132,15 +161,20 @@ pub async fn get_source_requests(pool: &PgPool, source_id: &str) -> Result<Vec<R
 }
 
 #[instrument(skip(pool), ret, err)]
-pub async fn get_user_requests(pool: &PgPool, user_id: &str) -> Result<Vec<Request>> {
+pub async fn get_user_requests(
+    pool: &PgPool,
+    user_id: &str,
+    team_ids: Option<&[String]>,
+) -> Result<Vec<Request>> {
     sqlx::query_as!(
         Request,
         r#"
         SELECT requests.* FROM requests
-        WHERE user_id = $1
+        WHERE user_id = $1 AND (team_id IS NULL OR $2::TEXT[] IS NULL OR team_id = ANY($2))
         ORDER BY created_at DESC
         "#,
         user_id,
+        team_ids,
     )
     .fetch_all(pool)
     .await
@@ -16,13 +16,14 @@ pub async fn create_secret(
     user_id: &str,
 ) -> Result<String> {
     sqlx::query_scalar!(
-        "INSERT INTO secrets (name, value, description, expires_at, created_user_id) VALUES ($1, pgp_sym_encrypt_bytea($2, $3), $4, $5, $6) RETURNING id",
+        "INSERT INTO secrets (name, value, description, expires_at, created_user_id, team_id) VALUES ($1, pgp_sym_encrypt_bytea($2, $3), $4, $5, $6, $7) RETURNING id",
         data.name,
         crypto.encrypt(data.value).unwrap(),
         db_secret,
         data.description,
         data.expires_at,
-        user_id
+        user_id,
+        data.team_id
     )
     .fetch_one(pool)
     .await
@@ -30,12 +31,16 @@ pub async fn create_secret(
 }
 
 #[instrument(skip(pool), ret, err)]
-pub async fn delete_secret(pool: &PgPool, id: &str) -> Result<u64> {
-    sqlx::query!("DELETE FROM secrets WHERE id = $1", id)
-        .execute(pool)
-        .await
-        .map_err(Into::into)
-        .map(|i| i.rows_affected())
+pub async fn delete_secret(pool: &PgPool, id: &str, team_ids: Option<&[String]>) -> Result<u64> {
+    sqlx::query!(
+        "DELETE FROM secrets WHERE id = $1 AND (team_id IS NULL OR $2::TEXT[] IS NULL OR team_id = ANY($2))",
+        id,
+        team_ids
+    )
+    .execute(pool)
+    .await
+    .map_err(Into::into)
+    .map(|i| i.rows_affected())
 }
 
 #[instrument(skip_all, fields(id = %id), ret, err)]
@@ -46,14 +51,17 @@ pub async fn patch_secret(
     crypto: &Crypto,
     db_secret: &str,
     user_id: &str,
+    team_ids: Option<&[String]>,
 ) -> Result<u64> {
     sqlx::query!(
-        "UPDATE secrets SET name = COALESCE($1, name), value = COALESCE(pgp_sym_encrypt_bytea($2, $3), value), updated_user_id = $4 WHERE id = $5",
+        "UPDATE secrets SET name = COALESCE($1, name), value = COALESCE(pgp_sym_encrypt_bytea($2, $3), value), updated_user_id = $4, team_id = CASE WHEN $6::TEXT IS NULL THEN team_id ELSE NULLIF($6, '') END WHERE id = $5 AND (team_id IS NULL OR $7::TEXT[] IS NULL OR team_id = ANY($7))",
         secret.name,
         secret.value.map(|v| crypto.encrypt(v)).transpose().unwrap(),
         db_secret,
         user_id,
-        id
+        id,
+        secret.team_id,
+        team_ids
     )
     .execute(pool)
     .await
@@ -61,11 +69,42 @@ pub async fn patch_secret(
     .map_err(Into::into)
 }
 
+/// Filters the secrets to the ones sources of the team can use, either shared or part of the team
+#[instrument(skip(pool), ret, err)]
+pub async fn get_team_usable_secret_ids(
+    pool: &PgPool,
+    secret_ids: &[String],
+    team_id: Option<&str>,
+) -> Result<Vec<String>> {
+    sqlx::query_scalar!(
+        "SELECT id FROM secrets WHERE id = ANY($1) AND (team_id IS NULL OR team_id = $2)",
+        secret_ids,
+        team_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+#[instrument(skip(pool), ret, err)]
+pub async fn get_secret_source_ids(pool: &PgPool, secret_id: &str) -> Result<Vec<String>> {
+    sqlx::query_scalar!(
+        "SELECT DISTINCT source_id FROM source_secrets WHERE secret_id = $1",
+        secret_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// Gets the secrets, limited to the shared ones and the ones of the given teams unless `team_ids`
+/// is `None`
 #[instrument(skip(pool), ret, err)]
-pub async fn get_secrets(pool: &PgPool) -> Result<Vec<Secret>> {
+pub async fn get_secrets(pool: &PgPool, team_ids: Option<&[String]>) -> Result<Vec<Secret>> {
     sqlx::query_as!(
         Secret,
-        r#"SELECT secrets.id, secrets.created_at, secrets.updated_at, secrets.name, secrets.description, secrets.expires_at, secrets.created_user_id, secrets.updated_user_id, count(source_secrets.id)::INT as "num_sources!" FROM secrets LEFT JOIN source_secrets ON secrets.id = source_secrets.secret_id GROUP BY secrets.id"#
+        r#"SELECT secrets.id, secrets.created_at, secrets.updated_at, secrets.name, secrets.description, secrets.expires_at, secrets.created_user_id, secrets.updated_user_id, secrets.team_id, count(source_secrets.id)::INT as "num_sources!" FROM secrets LEFT JOIN source_secrets ON secrets.id = source_secrets.secret_id WHERE secrets.team_id IS NULL OR $1::TEXT[] IS NULL OR secrets.team_id = ANY($1) GROUP BY secrets.id"#,
+        team_ids
     )
     .fetch_all(pool)
     .await
@@ -118,8 +157,9 @@ pub async fn get_secret_value(
     id: &str,
     crypto: &Crypto,
     db_secret: &str,
+    team_ids: Option<&[String]>,
 ) -> Result<String> {
-    let value: Vec<u8> = sqlx::query_scalar!(r#"SELECT pgp_sym_decrypt_bytea(value, $1)::BYTEA as "value!: _" FROM secrets WHERE id = $2"#, db_secret, id)
+    let value: Vec<u8> = sqlx::query_scalar!(r#"SELECT pgp_sym_decrypt_bytea(value, $1)::BYTEA as "value!: _" FROM secrets WHERE id = $2 AND (team_id IS NULL OR $3::TEXT[] IS NULL OR team_id = ANY($3))"#, db_secret, id, team_ids)
         .fetch_one(pool)
         .await?;
 
@@ -0,0 +1,137 @@
+use sqlx::{PgPool, Result};
+use tracing::instrument;
+
+use crate::schemas::sessions::{CreateJwtKey, CreateUserSession, JwtKey, UserSession};
+
+#[instrument(skip(pool, key), ret, err)]
+pub async fn create_jwt_key(pool: &PgPool, key: &CreateJwtKey) -> Result<String> {
+    sqlx::query_scalar!(
+        "INSERT INTO jwt_keys (algorithm, private_key, public_key, expires_at) VALUES ($1, $2, $3, $4) RETURNING id;",
+        key.algorithm as _,
+        key.private_key,
+        key.public_key,
+        key.expires_at,
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Keys whose JWTs may still be valid, the most recent first
+#[instrument(skip(pool), err)]
+pub async fn get_valid_jwt_keys(pool: &PgPool) -> Result<Vec<JwtKey>> {
+    sqlx::query_as!(
+        JwtKey,
+        r#"SELECT id, created_at, updated_at, algorithm AS "algorithm: _", private_key, public_key, expires_at FROM jwt_keys WHERE expires_at > NOW() ORDER BY created_at DESC;"#
+    )
+    .fetch_all(pool)
+    .await
+}
+
+#[instrument(skip(pool), ret, err)]
+pub async fn delete_expired_jwt_keys(pool: &PgPool) -> Result<u64> {
+    sqlx::query!("DELETE FROM jwt_keys WHERE expires_at <= NOW();")
+        .execute(pool)
+        .await
+        .map(|result| result.rows_affected())
+}
+
+#[instrument(skip(pool, session), ret, err)]
+pub async fn create_user_session(pool: &PgPool, session: &CreateUserSession) -> Result<String> {
+    sqlx::query_scalar!(
+        "INSERT INTO user_sessions (user_id, refresh_token, ip_address, user_agent, expires_at) VALUES ($1, $2, $3, $4, $5) RETURNING id;",
+        session.user_id,
+        session.refresh_token,
+        session.ip_address,
+        session.user_agent,
+        session.expires_at,
+    )
+    .fetch_one(pool)
+    .await
+}
+
+#[instrument(skip(pool), err)]
+pub async fn get_user_session(pool: &PgPool, id: &str) -> Result<Option<UserSession>> {
+    sqlx::query_as!(
+        UserSession,
+        "SELECT * FROM user_sessions WHERE id = $1;",
+        id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Sessions of the user that can still be refreshed, the most recently used first
+#[instrument(skip(pool), err)]
+pub async fn get_active_user_sessions(pool: &PgPool, user_id: &str) -> Result<Vec<UserSession>> {
+    sqlx::query_as!(
+        UserSession,
+        "SELECT * FROM user_sessions WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > NOW() ORDER BY last_used_at DESC;",
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+#[instrument(skip(pool), ret, err)]
+pub async fn is_user_session_active(pool: &PgPool, id: &str, user_id: &str) -> Result<bool> {
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM user_sessions WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL AND expires_at > NOW()) AS "active!";"#,
+        id,
+        user_id
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Replaces the refresh token of an active session, returning whether the current one was still
+/// the expected one, preventing a refresh token from being used twice concurrently
+#[instrument(skip(pool, current_refresh_token, new_refresh_token), ret, err)]
+pub async fn rotate_user_session_refresh_token(
+    pool: &PgPool,
+    id: &str,
+    current_refresh_token: &str,
+    new_refresh_token: &str,
+) -> Result<bool> {
+    sqlx::query!(
+        "UPDATE user_sessions SET previous_refresh_token = refresh_token, refresh_token = $3, last_used_at = NOW() WHERE id = $1 AND refresh_token = $2 AND revoked_at IS NULL AND expires_at > NOW();",
+        id,
+        current_refresh_token,
+        new_refresh_token
+    )
+    .execute(pool)
+    .await
+    .map(|result| result.rows_affected() == 1)
+}
+
+#[instrument(skip(pool), ret, err)]
+pub async fn revoke_user_session(pool: &PgPool, id: &str, user_id: &str) -> Result<u64> {
+    sqlx::query!(
+        "UPDATE user_sessions SET revoked_at = NOW() WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL;",
+        id,
+        user_id
+    )
+    .execute(pool)
+    .await
+    .map(|result| result.rows_affected())
+}
+
+/// Signs the user out everywhere
+#[instrument(skip(pool), ret, err)]
+pub async fn revoke_user_sessions(pool: &PgPool, user_id: &str) -> Result<u64> {
+    sqlx::query!(
+        "UPDATE user_sessions SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL;",
+        user_id
+    )
+    .execute(pool)
+    .await
+    .map(|result| result.rows_affected())
+}
+
+/// Removes the sessions that can no longer be refreshed
+#[instrument(skip(pool), ret, err)]
+pub async fn delete_inactive_user_sessions(pool: &PgPool) -> Result<u64> {
+    sqlx::query!("DELETE FROM user_sessions WHERE revoked_at IS NOT NULL OR expires_at <= NOW();")
+        .execute(pool)
+        .await
+        .map(|result| result.rows_affected())
+}
@@ -5,14 +5,21 @@ use crate::{
     schemas::{
         ignore_lists::IgnoreList,
         indicators::Indicator,
-        sources::{CreateSource, InternalRequest, Source, SourceCode, SourceKind, UpdateSource},
+        sources::{
+            CacheInvalidationReason, CreateSource, InternalRequest, Source,
+            SourceCacheInvalidation, SourceCode, SourceKind, UpdateSource,
+        },
         IdSlug,
     },
     slug::slugify,
 };
 
 #[instrument(skip(pool), err)]
-pub async fn get_provider_sources(pool: &PgPool, provider_id: &str) -> Result<Vec<Source>> {
+pub async fn get_provider_sources(
+    pool: &PgPool,
+    provider_id: &str,
+    team_ids: Option<&[String]>,
+) -> Result<Vec<Source>> {
     sqlx::query_as!(
         Source,
         r#"SELECT id,
@@ -29,6 +36,7 @@ supported_indicators,
 disabled_indicators,
 task_enabled,
 task_interval,
+task_cron,
 config,
 config_values,
 limit_enabled,
@@ -40,9 +48,11 @@ provider_id,
 kind as "kind: _",
 source_code,
 created_user_id,
-updated_user_id
-FROM sources WHERE provider_id = $1 ORDER BY name"#,
-        provider_id
+updated_user_id,
+team_id
+FROM sources WHERE provider_id = $1 AND (team_id IS NULL OR $2::TEXT[] IS NULL OR team_id = ANY($2)) ORDER BY name"#,
+        provider_id,
+        team_ids
     )
     .fetch_all(pool)
     .await
@@ -54,6 +64,7 @@ pub async fn get_sources_for_internal_request(
     pool: &PgPool,
     indicator: &Indicator,
     source_ids: &[String],
+    team_id: Option<&str>,
 ) -> Result<Vec<InternalRequest>> {
     sqlx::query_as!(
         InternalRequest,
@@ -78,22 +89,25 @@ FROM sources
 LEFT JOIN providers ON providers.id = sources.provider_id
 LEFT JOIN source_secrets ON source_secrets.source_id = sources.id AND source_secrets.required = TRUE AND source_secrets.secret_id IS NULL
 LEFT JOIN source_ignore_lists ON source_ignore_lists.source_id = sources.id
-LEFT JOIN ignore_lists ON ignore_lists.id = source_ignore_lists.ignore_list_id OR ignore_lists."global" = TRUE
+LEFT JOIN ignore_lists ON ignore_lists.id = source_ignore_lists.ignore_list_id OR (ignore_lists."global" = TRUE AND (ignore_lists.team_id IS NULL OR ignore_lists.team_id = $4))
 LEFT JOIN ignore_list_entries on ignore_lists.id = ignore_list_entries.ignore_list_id AND ignore_list_entries.indicator_kind = $1 AND ignore_list_entries.data LIKE '%' || $2 || '%'
-WHERE CARDINALITY($3::TEXT[]) = 0 OR sources.id = ANY($3::TEXT[])
+WHERE (CARDINALITY($3::TEXT[]) = 0 OR sources.id = ANY($3::TEXT[])) AND (sources.team_id IS NULL OR sources.team_id = $4)
 GROUP BY sources.id, providers.id;
 "#,
         indicator.db_kind(),
         indicator.data,
         source_ids,
+        team_id,
     )
     .fetch_all(pool)
     .await
     .map_err(Into::into)
 }
 
+/// Gets the sources, limited to the shared ones and the ones of the given teams unless `team_ids`
+/// is `None`
 #[instrument(skip(pool), err)]
-pub async fn get_sources(pool: &PgPool) -> Result<Vec<Source>> {
+pub async fn get_sources(pool: &PgPool, team_ids: Option<&[String]>) -> Result<Vec<Source>> {
     sqlx::query_as!(
         Source,
         r#"SELECT id,
@@ -110,6 +124,7 @@ supported_indicators,
 disabled_indicators,
 task_enabled,
 task_interval,
+task_cron,
 config,
 config_values,
 limit_enabled,
@@ -121,8 +136,12 @@ provider_id,
 kind as "kind: _",
 source_code,
 created_user_id,
-updated_user_id
- FROM sources ORDER BY name"#
+updated_user_id,
+team_id
+FROM sources
+WHERE team_id IS NULL OR $1::TEXT[] IS NULL OR team_id = ANY($1)
+ORDER BY name"#,
+        team_ids
     )
     .fetch_all(pool)
     .await
@@ -150,6 +169,7 @@ sources.supported_indicators,
 sources.disabled_indicators,
 sources.task_enabled,
 sources.task_interval,
+sources.task_cron,
 sources.config,
 sources.config_values,
 sources.limit_enabled,
@@ -161,7 +181,8 @@ sources.provider_id,
 sources.kind as "kind: _",
 sources.source_code,
 sources.created_user_id,
-sources.updated_user_id
+sources.updated_user_id,
+sources.team_id
 FROM sources
 LEFT JOIN providers ON providers.id = sources.provider_id
 WHERE $1 = ANY(sources.supported_indicators) AND NOT ($1 = ANY(sources.disabled_indicators)) AND sources.enabled = TRUE AND (providers IS NULL OR providers.enabled = TRUE)
@@ -174,7 +195,7 @@ ORDER BY name"#,
 }
 
 #[instrument(skip(pool), err)]
-pub async fn get_source(pool: &PgPool, id: &str) -> Result<Source> {
+pub async fn get_source(pool: &PgPool, id: &str, team_ids: Option<&[String]>) -> Result<Source> {
     sqlx::query_as!(
         Source,
         r#"SELECT id,
@@ -191,6 +212,7 @@ supported_indicators,
 disabled_indicators,
 task_enabled,
 task_interval,
+task_cron,
 config,
 config_values,
 limit_enabled,
@@ -202,9 +224,11 @@ provider_id,
 kind as "kind: _",
 source_code,
 created_user_id,
-updated_user_id
-FROM sources WHERE id = $1"#,
-        id
+updated_user_id,
+team_id
+FROM sources WHERE id = $1 AND (team_id IS NULL OR $2::TEXT[] IS NULL OR team_id = ANY($2))"#,
+        id,
+        team_ids
     )
     .fetch_one(pool)
     .await
@@ -212,11 +236,16 @@ FROM sources WHERE id = $1"#,
 }
 
 #[instrument(skip(pool), err)]
-pub async fn get_source_ignore_lists(pool: &PgPool, source_id: &str) -> Result<Vec<IgnoreList>> {
+pub async fn get_source_ignore_lists(
+    pool: &PgPool,
+    source_id: &str,
+    team_ids: Option<&[String]>,
+) -> Result<Vec<IgnoreList>> {
     sqlx::query_as!(
         IgnoreList,
-        "SELECT ignore_lists.* FROM ignore_lists INNER JOIN source_ignore_lists ON source_ignore_lists.ignore_list_id = ignore_lists.id WHERE source_ignore_lists.source_id = $1",
-        source_id
+        "SELECT ignore_lists.* FROM ignore_lists INNER JOIN source_ignore_lists ON source_ignore_lists.ignore_list_id = ignore_lists.id WHERE source_ignore_lists.source_id = $1 AND (ignore_lists.team_id IS NULL OR $2::TEXT[] IS NULL OR ignore_lists.team_id = ANY($2))",
+        source_id,
+        team_ids
     )
     .fetch_all(pool)
     .await
@@ -259,6 +288,27 @@ pub async fn delete_source_ignore_lists(
     .map_err(Into::into)
 }
 
+/// Filters the sources to the ones that can use an ignore list of the team, all of them for a
+/// shared ignore list, limited to the shared ones and the ones of the given teams unless
+/// `team_ids` is `None`
+#[instrument(skip(pool), ret, err)]
+pub async fn get_list_usable_source_ids(
+    pool: &PgPool,
+    source_ids: &[String],
+    list_team_id: Option<&str>,
+    team_ids: Option<&[String]>,
+) -> Result<Vec<String>> {
+    sqlx::query_scalar!(
+        "SELECT id FROM sources WHERE id = ANY($1) AND ($2::TEXT IS NULL OR team_id = $2) AND (team_id IS NULL OR $3::TEXT[] IS NULL OR team_id = ANY($3))",
+        source_ids,
+        list_team_id,
+        team_ids
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
 #[instrument(skip(pool), err, ret)]
 pub async fn delete_all_source_ignore_lists<'e>(
     pool: impl PgExecutor<'e>,
@@ -282,21 +332,37 @@ pub async fn delete_source(pool: &PgPool, id: &str) -> Result<u64> {
         .map_err(Into::into)
 }
 
-#[instrument(skip(pool), ret, err)]
-pub async fn get_source_id_from_slug(pool: &PgPool, slug: &str) -> Result<Option<String>> {
-    sqlx::query_scalar!("SELECT id FROM sources WHERE slug = $1", slug)
-        .fetch_optional(pool)
+#[instrument(skip(pool), err)]
+pub async fn get_source_config(pool: &PgPool, id: &str) -> Result<Vec<serde_json::Value>> {
+    sqlx::query_scalar!("SELECT config FROM sources WHERE id = $1", id)
+        .fetch_one(pool)
         .await
         .map_err(Into::into)
 }
 
+#[instrument(skip(pool), ret, err)]
+pub async fn get_source_id_from_slug(
+    pool: &PgPool,
+    slug: &str,
+    team_ids: Option<&[String]>,
+) -> Result<Option<String>> {
+    sqlx::query_scalar!(
+        "SELECT id FROM sources WHERE slug = $1 AND (team_id IS NULL OR $2::TEXT[] IS NULL OR team_id = ANY($2))",
+        slug,
+        team_ids
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(Into::into)
+}
+
 #[instrument(skip(pool), err, ret)]
 pub async fn create_source(pool: &PgPool, data: &CreateSource, user_id: &str) -> Result<IdSlug> {
     sqlx::query_as!(
         IdSlug,
         r#"
-INSERT INTO sources (name, slug, description, url, favicon, tags, enabled, supported_indicators, disabled_indicators, task_enabled, task_interval, config, config_values, limit_count, limit_interval, provider_id, kind, source_code, cache_enabled, cache_interval, created_user_id)
-VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21)
+INSERT INTO sources (name, slug, description, url, favicon, tags, enabled, supported_indicators, disabled_indicators, task_enabled, task_interval, config, config_values, limit_count, limit_interval, provider_id, kind, source_code, cache_enabled, cache_interval, created_user_id, task_cron, team_id)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23)
 RETURNING id, slug"#,
         data.name,
         slugify(&data.name),
@@ -318,7 +384,9 @@ RETURNING id, slug"#,
         data.source_code,
         data.cache_enabled,
         data.cache_interval,
-        user_id
+        user_id,
+        data.task_cron,
+        data.team_id
     )
     .fetch_one(pool)
     .await
@@ -331,6 +399,7 @@ pub async fn update_source(
     id: &str,
     data: UpdateSource,
     user_id: &str,
+    team_ids: Option<&[String]>,
 ) -> Result<u64> {
     sqlx::query!(
         r#"UPDATE sources SET
@@ -355,8 +424,10 @@ kind = COALESCE($18, kind),
 source_code = COALESCE($19, source_code),
 cache_enabled = COALESCE($20, cache_enabled),
 cache_interval = COALESCE($21, cache_interval),
-updated_user_id = $22
-WHERE id = $23"#,
+updated_user_id = $22,
+task_cron = CASE WHEN $24::TEXT IS NULL THEN task_cron ELSE NULLIF($24, '') END,
+team_id = CASE WHEN $25::TEXT IS NULL THEN team_id ELSE NULLIF($25, '') END
+WHERE id = $23 AND (team_id IS NULL OR $26::TEXT[] IS NULL OR team_id = ANY($26))"#,
         data.name,
         data.name.as_ref().map(|n| slugify(&n)),
         data.description,
@@ -379,7 +450,10 @@ WHERE id = $23"#,
         data.cache_enabled,
         data.cache_interval,
         user_id,
-        id
+        id,
+        data.task_cron,
+        data.team_id,
+        team_ids
     )
     .execute(pool)
     .await
@@ -400,7 +474,11 @@ pub async fn get_source_code_by_kind(pool: &PgPool, kind: &SourceKind) -> Result
 }
 
 #[instrument(skip(pool), ret, err)]
-pub async fn get_user_sources(pool: &PgPool, user_id: &str) -> Result<Vec<Source>> {
+pub async fn get_user_sources(
+    pool: &PgPool,
+    user_id: &str,
+    team_ids: Option<&[String]>,
+) -> Result<Vec<Source>> {
     sqlx::query_as!(
         Source,
         r#"SELECT id,
@@ -417,6 +495,7 @@ supported_indicators,
 disabled_indicators,
 task_enabled,
 task_interval,
+task_cron,
 config,
 config_values,
 limit_enabled,
@@ -428,10 +507,59 @@ provider_id,
 kind as "kind: _",
 source_code,
 created_user_id,
-updated_user_id
-FROM sources WHERE created_user_id = $1 ORDER BY name"#,
+updated_user_id,
+team_id
+FROM sources WHERE created_user_id = $1 AND (team_id IS NULL OR $2::TEXT[] IS NULL OR team_id = ANY($2)) ORDER BY name"#,
+        user_id,
+        team_ids
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+#[instrument(skip(pool), ret, err)]
+pub async fn create_source_cache_invalidation(
+    pool: &PgPool,
+    source_id: &str,
+    reason: CacheInvalidationReason,
+    num_entries: i32,
+    user_id: &str,
+) -> Result<String> {
+    let trace_id = shared::telemetry::Telemetry::get_trace_id();
+
+    sqlx::query_scalar!(
+        r#"
+        INSERT INTO source_cache_invalidations (reason, num_entries, trace_id, source_id, user_id)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id
+        "#,
+        reason as _,
+        num_entries,
+        trace_id,
+        source_id,
         user_id
     )
+    .fetch_one(pool)
+    .await
+    .map_err(Into::into)
+}
+
+#[instrument(skip(pool), ret, err)]
+pub async fn get_source_cache_invalidations(
+    pool: &PgPool,
+    source_id: &str,
+) -> Result<Vec<SourceCacheInvalidation>> {
+    sqlx::query_as!(
+        SourceCacheInvalidation,
+        r#"
+        SELECT id, created_at, reason as "reason: _", num_entries, trace_id, source_id, user_id
+        FROM source_cache_invalidations
+        WHERE source_id = $1
+        ORDER BY created_at DESC
+        "#,
+        source_id
+    )
     .fetch_all(pool)
     .await
     .map_err(Into::into)
@@ -3,22 +3,25 @@ use tracing::instrument;
 
 use crate::schemas::stats::{Count, CountPerHour, CountPerIdWrapper};
 
+/// Counts the items, the requests, sources and ignore lists being limited to the shared ones and
+/// the ones of the given teams unless `team_ids` is `None`
 #[instrument(skip(pool), ret, err)]
-pub async fn count(pool: &PgPool) -> Result<Count> {
+pub async fn count(pool: &PgPool, team_ids: Option<&[String]>) -> Result<Count> {
     sqlx::query_as!(
         Count,
         r#"SELECT
-(SELECT count(*) FROM requests)::int as "history!",
-(SELECT count(*)::int FROM requests WHERE created_at > NOW() - INTERVAL '24 hours') as "history_last_24hrs!",
+(SELECT count(*) FROM requests WHERE team_id IS NULL OR $1::TEXT[] IS NULL OR team_id = ANY($1))::int as "history!",
+(SELECT count(*)::int FROM requests WHERE created_at > NOW() - INTERVAL '24 hours' AND (team_id IS NULL OR $1::TEXT[] IS NULL OR team_id = ANY($1))) as "history_last_24hrs!",
 (SELECT count(*) FROM providers)::int as "providers!",
 (SELECT count(*)::int FROM providers WHERE enabled)::int as "enabled_providers!",
-(SELECT count(*) FROM sources)::int as "sources!",
-(SELECT count(*)::int FROM sources WHERE enabled)::int as "enabled_sources!",
-(SELECT count(*) FROM ignore_lists)::int as "ignore_lists!",
-(SELECT count(*)::int FROM ignore_lists WHERE enabled)::int as "enabled_ignore_lists!",
+(SELECT count(*) FROM sources WHERE team_id IS NULL OR $1::TEXT[] IS NULL OR team_id = ANY($1))::int as "sources!",
+(SELECT count(*)::int FROM sources WHERE enabled AND (team_id IS NULL OR $1::TEXT[] IS NULL OR team_id = ANY($1)))::int as "enabled_sources!",
+(SELECT count(*) FROM ignore_lists WHERE team_id IS NULL OR $1::TEXT[] IS NULL OR team_id = ANY($1))::int as "ignore_lists!",
+(SELECT count(*)::int FROM ignore_lists WHERE enabled AND (team_id IS NULL OR $1::TEXT[] IS NULL OR team_id = ANY($1)))::int as "enabled_ignore_lists!",
 (SELECT count(*) FROM users)::int as "users!",
 (SELECT count(*)::int FROM users WHERE enabled)::int as "enabled_users!"
-        "#
+        "#,
+        team_ids
     )
     .fetch_one(pool)
     .await
@@ -26,7 +29,10 @@ pub async fn count(pool: &PgPool) -> Result<Count> {
 }
 
 #[instrument(skip(pool), ret, err)]
-pub async fn requests_per_source_last_day(pool: &PgPool) -> Result<Vec<CountPerIdWrapper>> {
+pub async fn requests_per_source_last_day(
+    pool: &PgPool,
+    team_ids: Option<&[String]>,
+) -> Result<Vec<CountPerIdWrapper>> {
     sqlx::query_as(
         r#"SELECT
     COALESCE(
@@ -52,21 +58,28 @@ FROM (
 		    date_trunc('hour', NOW()), 
 		    '1 hour'
 		) AS time_window
-	LEFT JOIN source_requests 
-	LEFT JOIN sources ON sources.id = source_requests.source_id
+	LEFT JOIN (
+		source_requests
+		INNER JOIN requests ON requests.id = source_requests.request_id AND (requests.team_id IS NULL OR $1::TEXT[] IS NULL OR requests.team_id = ANY($1))
+		LEFT JOIN sources ON sources.id = source_requests.source_id
+	)
 	ON date_trunc('hour', source_requests.created_at) = time_window
 	GROUP BY time_window, sources.id
 ) nested_data
 GROUP BY time_window
 ORDER BY time_window ASC;"#,
     )
+    .bind(team_ids)
     .fetch_all(pool)
     .await
     .map_err(Into::into)
 }
 
 #[instrument(skip(pool), ret, err)]
-pub async fn requests_per_provider_last_day(pool: &PgPool) -> Result<Vec<CountPerIdWrapper>> {
+pub async fn requests_per_provider_last_day(
+    pool: &PgPool,
+    team_ids: Option<&[String]>,
+) -> Result<Vec<CountPerIdWrapper>> {
     sqlx::query_as(
         r#"SELECT
     COALESCE(
@@ -92,22 +105,29 @@ from (
 		    date_trunc('hour', NOW()), 
 		    '1 hour'
 		) AS time_window
-	LEFT JOIN source_requests 
-	LEFT JOIN sources ON sources.id = source_requests.source_id
-	LEFT JOIN providers ON providers.id = sources.provider_id
+	LEFT JOIN (
+		source_requests
+		INNER JOIN requests ON requests.id = source_requests.request_id AND (requests.team_id IS NULL OR $1::TEXT[] IS NULL OR requests.team_id = ANY($1))
+		LEFT JOIN sources ON sources.id = source_requests.source_id
+		LEFT JOIN providers ON providers.id = sources.provider_id
+	)
 	ON date_trunc('hour', source_requests.created_at) = time_window
 	GROUP BY time_window, providers.id
 ) nested_data
 GROUP BY time_window
 ORDER BY time_window ASC;"#,
     )
+    .bind(team_ids)
     .fetch_all(pool)
     .await
     .map_err(Into::into)
 }
 
 #[instrument(skip(pool), ret, err)]
-pub async fn requests_per_hour_last_day(pool: &PgPool) -> Result<Vec<CountPerHour>> {
+pub async fn requests_per_hour_last_day(
+    pool: &PgPool,
+    team_ids: Option<&[String]>,
+) -> Result<Vec<CountPerHour>> {
     sqlx::query_as!(
         CountPerHour,
         r#"SELECT
@@ -120,10 +140,14 @@ FROM
 	    date_trunc('hour', NOW()), 
 	    '1 hour'
 	) AS time_window
-LEFT JOIN source_requests 
+LEFT JOIN (
+	source_requests
+	INNER JOIN requests ON requests.id = source_requests.request_id AND (requests.team_id IS NULL OR $1::TEXT[] IS NULL OR requests.team_id = ANY($1))
+)
 ON date_trunc('hour', source_requests.created_at) = time_window
 GROUP BY time_window
-ORDER BY time_window ASC;"#
+ORDER BY time_window ASC;"#,
+        team_ids
     )
     .fetch_all(pool)
     .await
@@ -131,7 +155,10 @@ ORDER BY time_window ASC;"#
 }
 
 #[instrument(skip(pool), ret, err)]
-pub async fn requests_per_kind_last_day(pool: &PgPool) -> Result<Vec<CountPerIdWrapper>> {
+pub async fn requests_per_kind_last_day(
+    pool: &PgPool,
+    team_ids: Option<&[String]>,
+) -> Result<Vec<CountPerIdWrapper>> {
     sqlx::query_as(
         r#"SELECT
     COALESCE(
@@ -157,12 +184,13 @@ from (
 		    '1 hour'
 		) AS time_window
 	LEFT JOIN requests 
-	ON date_trunc('hour', requests.created_at) = time_window
+	ON date_trunc('hour', requests.created_at) = time_window AND (requests.team_id IS NULL OR $1::TEXT[] IS NULL OR requests.team_id = ANY($1))
 	GROUP BY time_window, requests.kind
 ) nested_data
 GROUP BY time_window
 ORDER BY time_window ASC;"#,
     )
+    .bind(team_ids)
     .fetch_all(pool)
     .await
     .map_err(Into::into)
@@ -0,0 +1,122 @@
+use sqlx::{PgPool, Result};
+use tracing::instrument;
+
+use crate::schemas::{
+    ingestion::IngestionCounts,
+    task_runs::{TaskRun, TaskRunStatus, TaskRunTrigger},
+};
+
+#[instrument(skip(pool), ret, err)]
+pub async fn create_task_run(
+    pool: &PgPool,
+    source_id: &str,
+    trigger: TaskRunTrigger,
+    user_id: Option<&str>,
+) -> Result<String> {
+    let trace_id = shared::telemetry::Telemetry::get_trace_id();
+
+    sqlx::query_scalar!(
+        r#"
+        INSERT INTO task_runs (trigger, trace_id, source_id, user_id)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id
+        "#,
+        trigger as _,
+        trace_id,
+        source_id,
+        user_id
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(Into::into)
+}
+
+#[instrument(skip(pool), ret, err)]
+pub async fn finish_task_run(
+    pool: &PgPool,
+    id: &str,
+    status: TaskRunStatus,
+    error: Option<&str>,
+    records_ingested: Option<i32>,
+    counts: Option<IngestionCounts>,
+) -> Result<u64> {
+    sqlx::query!(
+        r#"
+        UPDATE task_runs SET ended_at = NOW(), status = $1, error = $2, records_ingested = $3, records_added = $4, records_updated = $5, records_removed = $6
+        WHERE id = $7
+        "#,
+        status as _,
+        error,
+        records_ingested,
+        counts.map(|c| c.added),
+        counts.map(|c| c.updated),
+        counts.map(|c| c.removed),
+        id
+    )
+    .execute(pool)
+    .await
+    .map(|i| i.rows_affected())
+    .map_err(Into::into)
+}
+
+#[instrument(skip(pool), ret, err)]
+pub async fn get_source_task_runs(
+    pool: &PgPool,
+    source_id: &str,
+    limit: i64,
+) -> Result<Vec<TaskRun>> {
+    sqlx::query_as!(
+        TaskRun,
+        r#"
+        SELECT id, started_at, ended_at, status as "status: _", trigger as "trigger: _", error, records_ingested, records_added, records_updated, records_removed, trace_id, source_id, user_id
+        FROM task_runs
+        WHERE source_id = $1
+        ORDER BY started_at DESC
+        LIMIT $2
+        "#,
+        source_id,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// Number of failed runs of the source's task since its last successful run
+#[instrument(skip(pool), ret, err)]
+pub async fn get_consecutive_task_failures(pool: &PgPool, source_id: &str) -> Result<i32> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*)::INT as "count!"
+        FROM task_runs
+        WHERE source_id = $1 AND status = 'failed' AND started_at > COALESCE(
+            (SELECT MAX(started_at) FROM task_runs WHERE source_id = $1 AND status = 'succeeded'),
+            '-infinity'
+        )
+        "#,
+        source_id
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// Marks the runs left running by a server instance that stopped, detected by the run's advisory
+/// lock no longer being held, as failed
+#[instrument(skip(pool), ret, err)]
+pub async fn fail_interrupted_task_runs(pool: &PgPool) -> Result<u64> {
+    sqlx::query!(
+        r#"
+        UPDATE task_runs SET ended_at = NOW(), status = 'failed', error = 'Interrupted by a server restart'
+        WHERE status = 'running' AND NOT EXISTS (
+            SELECT FROM pg_locks
+            WHERE locktype = 'advisory' AND granted AND objsubid = 1
+            AND ((classid::BIGINT << 32) | objid::BIGINT) = hashtextextended('background_tasks:' || task_runs.source_id, 0)
+        )
+        "#
+    )
+    .execute(pool)
+    .await
+    .map(|i| i.rows_affected())
+    .map_err(Into::into)
+}
@@ -0,0 +1,201 @@
+use sqlx::{PgExecutor, PgPool, Result};
+use tracing::instrument;
+
+use crate::{
+    schemas::{
+        teams::{CreateTeam, Team, UpdateTeam},
+        users::User,
+        IdSlug,
+    },
+    slug::slugify,
+};
+
+#[instrument(skip(pool), ret, err)]
+pub async fn create_team(pool: &PgPool, data: CreateTeam, user_id: &str) -> Result<IdSlug> {
+    sqlx::query_as!(
+        IdSlug,
+        "INSERT INTO teams (name, slug, description, created_user_id) VALUES ($1, $2, $3, $4) RETURNING id, slug",
+        data.name,
+        slugify(&data.name),
+        data.description,
+        user_id
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(Into::into)
+}
+
+#[instrument(skip(pool), ret, err)]
+pub async fn update_team(pool: &PgPool, id: &str, data: UpdateTeam, user_id: &str) -> Result<u64> {
+    sqlx::query!(
+        r#"UPDATE teams SET
+name = COALESCE($1, name),
+slug = COALESCE($2, slug),
+description = COALESCE($3, description),
+updated_user_id = $4
+WHERE id = $5"#,
+        data.name,
+        data.name.as_ref().map(|n| slugify(n)),
+        data.description,
+        user_id,
+        id
+    )
+    .execute(pool)
+    .await
+    .map(|i| i.rows_affected())
+    .map_err(Into::into)
+}
+
+#[instrument(skip(pool), ret, err)]
+pub async fn delete_team(pool: &PgPool, id: &str) -> Result<u64> {
+    sqlx::query!("DELETE FROM teams WHERE id = $1", id)
+        .execute(pool)
+        .await
+        .map(|i| i.rows_affected())
+        .map_err(Into::into)
+}
+
+/// Gets the teams, limited to the given ones unless `team_ids` is `None`
+#[instrument(skip(pool), ret, err)]
+pub async fn get_teams(pool: &PgPool, team_ids: Option<&[String]>) -> Result<Vec<Team>> {
+    sqlx::query_as!(
+        Team,
+        r#"SELECT teams.id,
+teams.created_at,
+teams.updated_at,
+teams.name,
+teams.slug,
+teams.description,
+teams.created_user_id,
+teams.updated_user_id,
+COUNT(team_members.user_id)::INT AS "num_members!"
+FROM teams
+LEFT JOIN team_members ON team_members.team_id = teams.id
+WHERE $1::TEXT[] IS NULL OR teams.id = ANY($1)
+GROUP BY teams.id
+ORDER BY teams.name"#,
+        team_ids
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// Gets a team, only if it is part of the given ones or if `team_ids` is `None`
+#[instrument(skip(pool), ret, err)]
+pub async fn get_team(
+    pool: &PgPool,
+    id: &str,
+    team_ids: Option<&[String]>,
+) -> Result<Option<Team>> {
+    sqlx::query_as!(
+        Team,
+        r#"SELECT teams.id,
+teams.created_at,
+teams.updated_at,
+teams.name,
+teams.slug,
+teams.description,
+teams.created_user_id,
+teams.updated_user_id,
+COUNT(team_members.user_id)::INT AS "num_members!"
+FROM teams
+LEFT JOIN team_members ON team_members.team_id = teams.id
+WHERE teams.id = $1 AND ($2::TEXT[] IS NULL OR teams.id = ANY($2))
+GROUP BY teams.id"#,
+        id,
+        team_ids
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(Into::into)
+}
+
+#[instrument(skip(pool), ret, err)]
+pub async fn get_user_teams(pool: &PgPool, user_id: &str) -> Result<Vec<Team>> {
+    sqlx::query_as!(
+        Team,
+        r#"SELECT teams.id,
+teams.created_at,
+teams.updated_at,
+teams.name,
+teams.slug,
+teams.description,
+teams.created_user_id,
+teams.updated_user_id,
+(SELECT COUNT(*) FROM team_members WHERE team_members.team_id = teams.id)::INT AS "num_members!"
+FROM teams
+INNER JOIN team_members ON team_members.team_id = teams.id
+WHERE team_members.user_id = $1
+ORDER BY teams.name"#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// Gets the database IDs of the teams of a user, the oldest membership first
+#[instrument(skip(pool), ret, err)]
+pub async fn get_user_team_ids(pool: &PgPool, user_id: &str) -> Result<Vec<String>> {
+    sqlx::query_scalar!(
+        "SELECT team_id FROM team_members WHERE user_id = $1 ORDER BY created_at, team_id",
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+#[instrument(skip(pool), ret, err)]
+pub async fn get_team_members(pool: &PgPool, team_id: &str) -> Result<Vec<User>> {
+    sqlx::query_as(
+        r#"SELECT users.id, users.created_at, users.updated_at, users.auth_id, users.provider, users.enabled, users.email, users.verified, users.name, users.given_name, users.family_name, users.locale, users.picture, users.roles, users.last_modified_user_id, users.service_account, users.owner_user_id
+FROM users
+INNER JOIN team_members ON team_members.user_id = users.id
+WHERE team_members.team_id = $1
+ORDER BY users.name"#,
+    )
+    .bind(team_id)
+    .fetch_all(pool)
+    .await
+    .map_err(Into::into)
+}
+
+/// Removes the members of a team that are not part of the given users
+#[instrument(skip(pool), ret, err)]
+pub async fn delete_other_team_members<'e>(
+    pool: impl PgExecutor<'e>,
+    team_id: &str,
+    user_ids: &[String],
+) -> Result<u64> {
+    sqlx::query!(
+        "DELETE FROM team_members WHERE team_id = $1 AND NOT (user_id = ANY($2))",
+        team_id,
+        user_ids
+    )
+    .execute(pool)
+    .await
+    .map(|i| i.rows_affected())
+    .map_err(Into::into)
+}
+
+/// Adds users to a team, keeping the existing memberships as is
+#[instrument(skip(pool), ret, err)]
+pub async fn add_team_members<'e>(
+    pool: impl PgExecutor<'e>,
+    team_id: &str,
+    user_ids: &[String],
+    user_id: &str,
+) -> Result<u64> {
+    sqlx::query!(
+        "INSERT INTO team_members (team_id, user_id, created_user_id) VALUES ($1, UNNEST($2::TEXT[]), $3) ON CONFLICT DO NOTHING",
+        team_id,
+        user_ids,
+        user_id
+    )
+    .execute(pool)
+    .await
+    .map(|i| i.rows_affected())
+    .map_err(Into::into)
+}
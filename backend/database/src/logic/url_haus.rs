@@ -1,13 +1,16 @@
-use sqlx::{PgPool, QueryBuilder, Result};
+use sqlx::{PgExecutor, PgPool, QueryBuilder, Result};
 use tracing::instrument;
 
-use crate::schemas::url_haus::Entry;
+use crate::schemas::{
+    ingestion::{FeedValidators, IngestionCounts},
+    url_haus::Entry,
+};
 
 #[instrument(skip(pool), err)]
 pub async fn get_url_haus_by_url(url: &str, pool: &PgPool) -> Result<Vec<Entry>> {
     sqlx::query_as!(
         Entry,
-        "SELECT id, dateadded, url, url_status, last_online, threat, tags, urlhaus_link, reporter FROM url_haus WHERE url = $1",
+        "SELECT id, dateadded, url, url_status, last_online, threat, tags, urlhaus_link, reporter, first_seen, last_seen, active FROM url_haus WHERE url = $1",
         url
     )
     .fetch_all(pool)
@@ -18,18 +21,27 @@ pub async fn get_url_haus_by_url(url: &str, pool: &PgPool) -> Result<Vec<Entry>>
 pub async fn get_url_haus_by_domain(domain: &str, pool: &PgPool) -> Result<Vec<Entry>> {
     sqlx::query_as!(
         Entry,
-        "SELECT id, dateadded, url, url_status, last_online, threat, tags, urlhaus_link, reporter FROM url_haus WHERE url ILIKE '%' || $1 || '%'",
+        "SELECT id, dateadded, url, url_status, last_online, threat, tags, urlhaus_link, reporter, first_seen, last_seen, active FROM url_haus WHERE url ILIKE '%' || $1 || '%'",
         domain
     )
     .fetch_all(pool)
     .await
 }
 
-#[instrument(skip_all)]
-pub async fn insert_entries(entries: &[Entry], pool: &PgPool) -> Result<()> {
-    let mut query_builder = QueryBuilder::new("INSERT INTO url_haus (id, dateadded, url, url_status, last_online, threat, tags, urlhaus_link, reporter) ");
+/// Inserts the new entries and updates the existing ones, marking all of them as seen at the
+/// start of the current transaction
+#[instrument(skip_all, err)]
+pub async fn upsert_entries<'e>(
+    mut entries: Vec<Entry>,
+    pool: impl PgExecutor<'e>,
+) -> Result<IngestionCounts> {
+    // An entry can only be upserted once per statement
+    entries.sort_unstable_by_key(|entry| entry.id);
+    entries.dedup_by_key(|entry| entry.id);
 
-    query_builder.push_values(entries, |mut b, entry| {
+    let mut query_builder = QueryBuilder::new("WITH upserted AS (INSERT INTO url_haus (id, dateadded, url, url_status, last_online, threat, tags, urlhaus_link, reporter) ");
+
+    query_builder.push_values(&entries, |mut b, entry| {
         b.push_bind(entry.id)
             .push_bind(entry.dateadded)
             .push_bind(&entry.url)
@@ -41,23 +53,148 @@ pub async fn insert_entries(entries: &[Entry], pool: &PgPool) -> Result<()> {
             .push_bind(&entry.reporter);
     });
 
-    query_builder.push(" ON CONFLICT DO NOTHING");
+    query_builder.push(
+        r#"
+ON CONFLICT (id) DO UPDATE SET
+dateadded = EXCLUDED.dateadded,
+url = EXCLUDED.url,
+url_status = EXCLUDED.url_status,
+last_online = EXCLUDED.last_online,
+threat = EXCLUDED.threat,
+tags = EXCLUDED.tags,
+urlhaus_link = EXCLUDED.urlhaus_link,
+reporter = EXCLUDED.reporter,
+updated_at = CASE WHEN NOT url_haus.active OR (url_haus.dateadded, url_haus.url, url_haus.url_status, url_haus.last_online, url_haus.threat, url_haus.tags, url_haus.urlhaus_link, url_haus.reporter) IS DISTINCT FROM (EXCLUDED.dateadded, EXCLUDED.url, EXCLUDED.url_status, EXCLUDED.last_online, EXCLUDED.threat, EXCLUDED.tags, EXCLUDED.urlhaus_link, EXCLUDED.reporter) THEN NOW() ELSE url_haus.updated_at END,
+last_seen = NOW(),
+active = TRUE
+RETURNING xmax = 0 AS inserted, updated_at = NOW()::TIMESTAMP(3) AS changed)
+SELECT COUNT(*) FILTER (WHERE inserted)::INT, COUNT(*) FILTER (WHERE changed AND NOT inserted)::INT FROM upserted"#,
+    );
 
-    let query = query_builder.build();
+    let (added, updated): (i32, i32) = query_builder.build_query_as().fetch_one(pool).await?;
 
-    query.execute(pool).await?;
+    Ok(IngestionCounts {
+        added,
+        updated,
+        removed: 0,
+    })
+}
 
-    Ok(())
+/// Marks the entries that were not seen since the start of the current transaction as inactive
+#[instrument(skip_all, ret, err)]
+pub async fn deactivate_unseen_entries<'e>(pool: impl PgExecutor<'e>) -> Result<u64> {
+    sqlx::query!(
+        "UPDATE url_haus SET active = FALSE, updated_at = NOW() WHERE active AND last_seen < NOW()::TIMESTAMP(3)"
+    )
+    .execute(pool)
+    .await
+    .map(|i| i.rows_affected())
+}
+
+#[instrument(skip_all, err)]
+pub async fn get_url_haus_validators(pool: &PgPool) -> Result<FeedValidators> {
+    sqlx::query_as!(
+        FeedValidators,
+        "SELECT etag, last_modified FROM url_haus_refreshes WHERE NOT not_modified ORDER BY refreshed_at DESC LIMIT 1"
+    )
+    .fetch_optional(pool)
+    .await
+    .map(Option::unwrap_or_default)
 }
 
-#[instrument(skip_all)]
-pub async fn insert_url_haus_refresh(headers: &str, pool: &PgPool) -> Result<()> {
+#[instrument(skip_all, err)]
+pub async fn insert_url_haus_refresh<'e>(
+    headers: &str,
+    validators: &FeedValidators,
+    not_modified: bool,
+    counts: IngestionCounts,
+    pool: impl PgExecutor<'e>,
+) -> Result<()> {
     sqlx::query!(
-        "INSERT INTO url_haus_refreshes (response_headers) VALUES ($1)",
-        headers
+        "INSERT INTO url_haus_refreshes (response_headers, etag, last_modified, not_modified, num_added, num_updated, num_removed) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        headers,
+        validators.etag,
+        validators.last_modified,
+        not_modified,
+        counts.added,
+        counts.updated,
+        counts.removed
     )
     .execute(pool)
     .await?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: i64, url_status: &str) -> Entry {
+        Entry {
+            id,
+            dateadded: Default::default(),
+            url: format!("http://example.com/{id}"),
+            url_status: url_status.to_string(),
+            last_online: None,
+            threat: "malware_download".to_string(),
+            tags: "".to_string(),
+            urlhaus_link: format!("https://urlhaus.abuse.ch/url/{id}/"),
+            reporter: "test".to_string(),
+            first_seen: Default::default(),
+            last_seen: Default::default(),
+            active: true,
+        }
+    }
+
+    async fn ingest(pool: &PgPool, entries: Vec<Entry>) -> IngestionCounts {
+        let mut transaction = pool.begin().await.unwrap();
+
+        let mut counts = upsert_entries(entries, &mut *transaction).await.unwrap();
+        counts.removed = deactivate_unseen_entries(&mut *transaction).await.unwrap() as i32;
+
+        transaction.commit().await.unwrap();
+
+        counts
+    }
+
+    #[sqlx::test]
+    async fn given_successive_feeds_when_ingesting_then_counts_added_updated_and_removed_entries(
+        pool: PgPool,
+    ) {
+        let counts = ingest(&pool, vec![entry(1, "online"), entry(2, "online")]).await;
+        assert_eq!(
+            IngestionCounts {
+                added: 2,
+                updated: 0,
+                removed: 0
+            },
+            counts
+        );
+
+        let counts = ingest(&pool, vec![entry(1, "offline"), entry(3, "online")]).await;
+        assert_eq!(
+            IngestionCounts {
+                added: 1,
+                updated: 1,
+                removed: 1
+            },
+            counts
+        );
+
+        let removed = get_url_haus_by_url("http://example.com/2", &pool)
+            .await
+            .unwrap();
+        assert!(!removed[0].active);
+
+        let counts = ingest(&pool, vec![entry(1, "offline"), entry(2, "online")]).await;
+        assert_eq!(
+            IngestionCounts {
+                added: 0,
+                updated: 1,
+                removed: 1
+            },
+            counts
+        );
+    }
+}
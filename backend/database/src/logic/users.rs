@@ -1,15 +1,24 @@
 use sqlx::{PgPool, Result};
 use tracing::instrument;
 
-use crate::schemas::users::{
-    CreateUser, DbUserLog, UpdateUser, User, UserLog, UserWithNumLogs, UserWithPassword,
+use crate::{
+    schemas::users::{
+        CreateServiceAccount, CreateUser, DbUserLog, UpdateUser, User, UserLog, UserWithNumLogs,
+        UserWithPassword, SERVICE_ACCOUNT_PROVIDER,
+    },
+    slug::slugify,
 };
 
 #[instrument(skip(pool), ret, err)]
-pub async fn is_user_enabled(pool: &PgPool, user_auth_id: &str) -> Result<Option<bool>> {
+pub async fn is_user_enabled(
+    pool: &PgPool,
+    user_auth_id: &str,
+    provider: &str,
+) -> Result<Option<bool>> {
     Ok(sqlx::query!(
-        "SELECT enabled FROM users WHERE auth_id = $1;",
-        user_auth_id
+        "SELECT enabled FROM users WHERE auth_id = $1 AND provider = $2;",
+        user_auth_id,
+        provider
     )
     .fetch_optional(pool)
     .await?
@@ -29,27 +38,97 @@ pub async fn create_or_update_user(pool: &PgPool, user: &CreateUser) -> Result<U
         .bind(&user.family_name)
         .bind(&user.locale)
         .bind(&user.picture)
-        .bind(user.roles.iter().cloned().collect::<Vec<_>>())
+        .bind(
+            user.roles
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+        )
         .bind(&user.hashed_password)
+        .bind(user.replace_roles)
         .fetch_one(pool)
         .await
 }
 
+/// Creates the user, unless a user with the same email and provider already exists
+#[instrument(skip(pool), ret, err)]
+pub async fn create_user(pool: &PgPool, user: &CreateUser) -> Result<Option<User>> {
+    sqlx::query_as(
+        r#"
+INSERT INTO users (auth_id, provider, enabled, email, verified, name, given_name, family_name, locale, picture, roles, password)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+ON CONFLICT (email, provider) DO NOTHING
+RETURNING *;
+        "#,
+    )
+    .bind(&user.auth_id)
+    .bind(&user.provider)
+    .bind(user.enabled)
+    .bind(&user.email)
+    .bind(user.verified)
+    .bind(&user.name)
+    .bind(&user.given_name)
+    .bind(&user.family_name)
+    .bind(&user.locale)
+    .bind(&user.picture)
+    .bind(
+        user.roles
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+    )
+    .bind(&user.hashed_password)
+    .fetch_optional(pool)
+    .await
+}
+
+#[instrument(skip(pool), ret, err)]
+pub async fn create_service_account(
+    pool: &PgPool,
+    data: &CreateServiceAccount,
+    request_user_id: &str,
+) -> Result<User> {
+    // Service accounts cannot receive emails, but need a unique one
+    let email = format!("{}@service-account.invalid", slugify(&data.name));
+
+    sqlx::query_as(
+        r#"
+INSERT INTO users (provider, enabled, email, verified, name, roles, service_account, owner_user_id, last_modified_user_id)
+VALUES ($1, TRUE, $2, TRUE, $3, $4, TRUE, COALESCE($5, $6), $6)
+RETURNING *;
+        "#,
+    )
+    .bind(SERVICE_ACCOUNT_PROVIDER)
+    .bind(email)
+    .bind(&data.name)
+    .bind(
+        data.roles
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+    )
+    .bind(&data.owner_user_id)
+    .bind(request_user_id)
+    .fetch_one(pool)
+    .await
+}
+
 #[instrument(skip(pool), ret, err)]
 pub async fn create_user_log(pool: &PgPool, log: &UserLog) -> Result<()> {
     let trace_id = shared::telemetry::Telemetry::get_trace_id();
 
     sqlx::query!(
         r#"
-INSERT INTO user_logs (user_id, ip_address, user_agent, uri, method, trace_id)
-VALUES ($1, $2, $3, $4, $5, $6);
+INSERT INTO user_logs (user_id, ip_address, user_agent, uri, method, trace_id, event)
+VALUES ($1, $2, $3, $4, $5, $6, $7);
         "#,
         log.user_id,
         log.ip_address,
         log.user_agent,
         log.uri,
         log.method,
-        trace_id
+        trace_id,
+        log.event as _
     )
     .execute(pool)
     .await?;
@@ -59,18 +138,23 @@ VALUES ($1, $2, $3, $4, $5, $6);
 
 #[instrument(skip(pool), ret, err)]
 pub async fn get_user(pool: &PgPool, user_id: &str) -> Result<Option<User>> {
-    sqlx::query_as!(User, "SELECT id, created_at, updated_at, auth_id, provider, enabled, email, verified, name, given_name, family_name, locale, picture, roles, last_modified_user_id FROM users WHERE id = $1;", user_id)
+    sqlx::query_as("SELECT id, created_at, updated_at, auth_id, provider, enabled, email, verified, name, given_name, family_name, locale, picture, roles, last_modified_user_id, service_account, owner_user_id FROM users WHERE id = $1;")
+        .bind(user_id)
         .fetch_optional(pool)
         .await
 }
 
 #[instrument(skip(pool), ret, err)]
-pub async fn get_user_by_auth_id(pool: &PgPool, user_auth_id: &str) -> Result<Option<User>> {
-    sqlx::query_as!(
-        User,
-        "SELECT id, created_at, updated_at, auth_id, provider, enabled, email, verified, name, given_name, family_name, locale, picture, roles, last_modified_user_id FROM users WHERE auth_id = $1;",
-        user_auth_id
+pub async fn get_user_by_auth_id(
+    pool: &PgPool,
+    user_auth_id: &str,
+    provider: &str,
+) -> Result<Option<User>> {
+    sqlx::query_as(
+        "SELECT id, created_at, updated_at, auth_id, provider, enabled, email, verified, name, given_name, family_name, locale, picture, roles, last_modified_user_id, service_account, owner_user_id FROM users WHERE auth_id = $1 AND provider = $2;",
     )
+    .bind(user_auth_id)
+    .bind(provider)
     .fetch_optional(pool)
     .await
 }
@@ -79,7 +163,7 @@ pub async fn get_user_by_auth_id(pool: &PgPool, user_auth_id: &str) -> Result<Op
 pub async fn get_users(pool: &PgPool) -> Result<Vec<UserWithNumLogs>> {
     sqlx::query_as(
         r#"
-SELECT users.id, users.created_at, users.updated_at, users.auth_id, users.provider, users.enabled, users.email, users.verified, users.name, users.given_name, users.family_name, users.locale, users.picture, users.roles, users.last_modified_user_id, count(DISTINCT user_logs.id)::int as "num_logs" FROM users
+SELECT users.id, users.created_at, users.updated_at, users.auth_id, users.provider, users.enabled, users.email, users.verified, users.name, users.given_name, users.family_name, users.locale, users.picture, users.roles, users.last_modified_user_id, users.service_account, users.owner_user_id, count(DISTINCT user_logs.id)::int as "num_logs" FROM users
 LEFT JOIN user_logs ON user_logs.user_id = users.id
 GROUP BY users.id
             "#,
@@ -92,7 +176,7 @@ GROUP BY users.id
 pub async fn get_user_logs(pool: &PgPool, user_id: &str) -> Result<Vec<DbUserLog>> {
     sqlx::query_as!(
         DbUserLog,
-        "SELECT * FROM user_logs WHERE user_id = $1;",
+        r#"SELECT id, created_at, updated_at, user_id, ip_address, user_agent, uri, method, trace_id, event as "event: _" FROM user_logs WHERE user_id = $1;"#,
         user_id
     )
     .fetch_all(pool)
@@ -106,27 +190,29 @@ pub async fn update_user(
     user: &UpdateUser,
     request_user_id: &str,
 ) -> Result<()> {
-    if user.enabled.is_none() && user.roles.is_none() {
+    if user.enabled.is_none() && user.roles.is_none() && user.owner_user_id.is_none() {
         return Ok(());
     }
 
     let roles = user
         .roles
-        .clone()
-        .map(|roles| roles.into_iter().collect::<Vec<_>>());
+        .as_ref()
+        .map(|roles| roles.iter().map(ToString::to_string).collect::<Vec<_>>());
 
     sqlx::query!(
         r#"
     UPDATE users
     SET enabled = COALESCE($2, enabled),
         roles = COALESCE($3, roles),
+        owner_user_id = CASE WHEN service_account THEN COALESCE($5, owner_user_id) END,
         last_modified_user_id = $4
     WHERE id = $1;
             "#,
         user_id,
         user.enabled,
         roles as _,
-        request_user_id
+        request_user_id,
+        user.owner_user_id
     )
     .execute(pool)
     .await?;
@@ -134,13 +220,53 @@ pub async fn update_user(
     Ok(())
 }
 
+#[instrument(skip(pool), err)]
+pub async fn get_user_with_password(
+    pool: &PgPool,
+    user_id: &str,
+) -> Result<Option<UserWithPassword>> {
+    sqlx::query_as!(
+        UserWithPassword,
+        "SELECT * FROM users WHERE id = $1;",
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
 #[instrument(skip(pool), ret, err)]
 pub async fn get_user_from_email(pool: &PgPool, email: &str) -> Result<Option<UserWithPassword>> {
     sqlx::query_as!(
         UserWithPassword,
-        "SELECT * FROM users WHERE email = $1;",
+        "SELECT * FROM users WHERE email = $1 AND NOT service_account;",
         email
     )
     .fetch_optional(pool)
     .await
 }
+
+#[instrument(skip(pool), ret, err)]
+pub async fn set_user_verified(pool: &PgPool, user_id: &str) -> Result<()> {
+    sqlx::query!("UPDATE users SET verified = TRUE WHERE id = $1;", user_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+#[instrument(skip(pool, hashed_password), ret, err)]
+pub async fn update_user_password(
+    pool: &PgPool,
+    user_id: &str,
+    hashed_password: &str,
+) -> Result<()> {
+    sqlx::query!(
+        "UPDATE users SET password = $2 WHERE id = $1;",
+        user_id,
+        hashed_password
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -1,9 +1,12 @@
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::{collections::BTreeSet, str::FromStr};
 use typeshare::typeshare;
 use utoipa::{IntoParams, ToSchema};
 
+use super::{indicators::IndicatorKind, users::Permission};
+
 /// An token used for authentication with the API
 #[derive(FromRow, Serialize, ToSchema, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -19,10 +22,24 @@ pub struct ApiToken {
     pub note: String,
     /// The time the token expires
     pub expires_at: Option<NaiveDateTime>,
+    /// Permissions the token is restricted to, among the ones of its user, all of them if missing
+    pub permissions: Option<Vec<String>>,
+    /// Database IDs of the sources the token can request, all of them if missing
+    pub source_ids: Option<Vec<String>>,
+    /// Indicator kinds the token can request, all of them if missing
+    pub indicator_kinds: Option<Vec<String>>,
+    /// IP addresses or CIDR ranges the token can be used from, any of them if missing
+    pub allowed_ip_ranges: Option<Vec<String>>,
+    /// The time the token was last used
+    pub last_used_at: Option<NaiveDateTime>,
+    /// IP address of the client that last used the token
+    pub last_used_ip_address: Option<String>,
+    /// Number of requests authenticated with the token
+    pub request_count: i32,
 }
 
 /// Parameters for creating a new API token
-#[derive(Deserialize, FromRow, ToSchema, Debug, Default)]
+#[derive(Deserialize, ToSchema, Debug, Default)]
 #[serde(rename_all = "camelCase")]
 #[typeshare]
 pub struct CreateApiToken {
@@ -30,6 +47,14 @@ pub struct CreateApiToken {
     pub note: String,
     /// The time the token expires
     pub expires_at: Option<NaiveDateTime>,
+    /// Permissions the token is restricted to, among the ones of its user, all of them if missing
+    pub permissions: Option<Vec<Permission>>,
+    /// Database IDs of the sources the token can request, all of them if missing
+    pub source_ids: Option<Vec<String>>,
+    /// Indicator kinds the token can request, all of them if missing
+    pub indicator_kinds: Option<Vec<IndicatorKind>>,
+    /// IP addresses or CIDR ranges the token can be used from, any of them if missing
+    pub allowed_ip_ranges: Option<Vec<String>>,
 }
 
 /// Parameters for updating an API token
@@ -41,6 +66,14 @@ pub struct UpdateApiToken {
     pub note: Option<String>,
     /// The time the token expires
     pub expires_at: Option<NaiveDateTime>,
+    /// Permissions the token is restricted to, among the ones of its user
+    pub permissions: Option<Vec<Permission>>,
+    /// Database IDs of the sources the token can request
+    pub source_ids: Option<Vec<String>>,
+    /// Indicator kinds the token can request
+    pub indicator_kinds: Option<Vec<IndicatorKind>>,
+    /// IP addresses or CIDR ranges the token can be used from
+    pub allowed_ip_ranges: Option<Vec<String>>,
 }
 
 impl From<UpdateApiToken> for InternalUpdateApiToken {
@@ -49,6 +82,10 @@ impl From<UpdateApiToken> for InternalUpdateApiToken {
             token: None,
             note: value.note,
             expires_at: value.expires_at,
+            permissions: value.permissions.as_deref().map(to_strings),
+            source_ids: value.source_ids,
+            indicator_kinds: value.indicator_kinds.as_deref().map(to_strings),
+            allowed_ip_ranges: value.allowed_ip_ranges,
         }
     }
 }
@@ -58,10 +95,62 @@ pub struct InternalUpdateApiToken {
     pub token: Option<String>,
     pub note: Option<String>,
     pub expires_at: Option<NaiveDateTime>,
+    pub permissions: Option<Vec<String>>,
+    pub source_ids: Option<Vec<String>>,
+    pub indicator_kinds: Option<Vec<String>>,
+    pub allowed_ip_ranges: Option<Vec<String>>,
+}
+
+pub(crate) fn to_strings<T: ToString>(values: &[T]) -> Vec<String> {
+    values.iter().map(ToString::to_string).collect()
 }
 
 #[derive(FromRow, Debug)]
 pub struct GetApiToken {
     pub token: String,
     pub user_id: String,
+    pub permissions: Option<Vec<String>>,
+    pub source_ids: Option<Vec<String>>,
+    pub indicator_kinds: Option<Vec<String>>,
+    pub allowed_ip_ranges: Option<Vec<String>>,
+}
+
+/// Restrictions of the API token a request was authenticated with
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ApiTokenScope {
+    /// Permissions the token is restricted to, among the ones of its user
+    pub permissions: Option<BTreeSet<Permission>>,
+    /// Database IDs of the sources the token can request
+    pub source_ids: Option<Vec<String>>,
+    /// Indicator kinds the token can request
+    pub indicator_kinds: Option<Vec<String>>,
+}
+
+impl ApiTokenScope {
+    /// Whether the token is not restricted at all
+    pub fn is_unrestricted(&self) -> bool {
+        self.permissions.is_none() && self.source_ids.is_none() && self.indicator_kinds.is_none()
+    }
+
+    /// Whether the token can request indicators of the kind
+    pub fn allows_indicator_kind(&self, kind: &IndicatorKind) -> bool {
+        self.indicator_kinds
+            .as_ref()
+            .is_none_or(|kinds| kinds.contains(&kind.to_string()))
+    }
+}
+
+impl From<&GetApiToken> for ApiTokenScope {
+    fn from(value: &GetApiToken) -> Self {
+        Self {
+            permissions: value.permissions.as_ref().map(|permissions| {
+                permissions
+                    .iter()
+                    .filter_map(|permission| Permission::from_str(permission).ok())
+                    .collect()
+            }),
+            source_ids: value.source_ids.clone(),
+            indicator_kinds: value.indicator_kinds.clone(),
+        }
+    }
 }
@@ -1,5 +1,5 @@
 use chrono::NaiveDateTime;
-use sqlx::FromRow;
+use sqlx::{FromRow, Type};
 
 #[derive(Debug)]
 pub struct CreateLoginRequest {
@@ -25,3 +25,35 @@ pub struct LoginRequest {
     pub browser_state: Option<String>,
     pub redirect_uri: String,
 }
+
+/// Purpose of a single use token sent to a user
+#[derive(Debug, Type, Clone, Copy, PartialEq, Eq)]
+#[sqlx(rename_all = "snake_case", type_name = "user_token_kind")]
+pub enum UserTokenKind {
+    EmailVerification,
+    PasswordReset,
+    /// Second factor step of a login, after the password was verified
+    MfaChallenge,
+    WebauthnRegistration,
+}
+
+#[derive(Debug)]
+pub struct CreateUserToken {
+    pub user_id: String,
+    pub kind: UserTokenKind,
+    /// Hash of the secret part of the token
+    pub token: String,
+    pub expires_at: NaiveDateTime,
+}
+
+#[derive(FromRow, Debug)]
+pub struct UserToken {
+    pub id: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub kind: UserTokenKind,
+    pub token: String,
+    pub expires_at: NaiveDateTime,
+    pub used_at: Option<NaiveDateTime>,
+    pub user_id: String,
+}
@@ -0,0 +1,115 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use typeshare::typeshare;
+use utoipa::ToSchema;
+
+use super::indicators::IndicatorKind;
+
+/// Format of the content of a feed
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Eq, PartialEq, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[typeshare]
+pub enum FeedFormat {
+    /// Comma separated values, or any other delimiter, one entry per row
+    Csv,
+    /// Array of JSON objects, one entry per object
+    Json,
+    /// Plain text, one indicator per line
+    Txt,
+}
+
+/// Compression of a feed
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Eq, PartialEq, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[typeshare]
+pub enum FeedCompression {
+    Gzip,
+    /// Archive whose first file is the feed
+    Zip,
+}
+
+/// Field of a feed entry containing an indicator
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct FeedIndicatorMapping {
+    /// Column name, or index if the CSV has no headers, or JSONPath relative to the JSON entry,
+    /// ignored for TXT feeds
+    pub field: String,
+    /// Kind of the indicators contained in the field
+    pub kind: IndicatorKind,
+}
+
+/// Field of a feed entry stored as metadata of the entry's indicators
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct FeedMetadataMapping {
+    /// Name of the metadata
+    pub name: String,
+    /// Column name, or index if the CSV has no headers, or JSONPath relative to the JSON entry
+    pub field: String,
+}
+
+/// Declarative definition of a feed source, stored as the first element of the source's config
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct FeedConfig {
+    /// URL the feed is downloaded from
+    pub url: String,
+    /// Format of the feed
+    pub format: FeedFormat,
+    /// Compression of the feed, if any
+    pub compression: Option<FeedCompression>,
+    /// Delimiter between the columns of a CSV feed, defaults to a comma
+    pub delimiter: Option<String>,
+    /// Whether the first row of a CSV feed contains the column names, defaults to true
+    pub has_headers: Option<bool>,
+    /// Prefix of the lines to ignore in CSV and TXT feeds, defaults to `#`
+    pub comment_prefix: Option<String>,
+    /// JSONPath to the array of entries of a JSON feed, defaults to the whole document
+    pub entries_path: Option<String>,
+    /// Fields of the entries containing indicators
+    pub indicators: Vec<FeedIndicatorMapping>,
+    /// Fields of the entries stored with their indicators
+    #[serde(default)]
+    pub metadata: Vec<FeedMetadataMapping>,
+    /// Interval in seconds between refreshes of the feed, used if the source's task has no
+    /// interval or cron expression of its own
+    pub refresh_interval: Option<i32>,
+}
+
+impl FeedConfig {
+    /// Parses the feed definition from a source's config
+    pub fn from_source_config(config: &[serde_json::Value]) -> serde_json::Result<Self> {
+        serde_json::from_value(config.first().cloned().unwrap_or_default())
+    }
+}
+
+/// Indicator found in a feed
+#[derive(Serialize, FromRow, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedEntry {
+    /// Kind of the indicator
+    pub kind: String,
+    /// Data of the indicator
+    pub data: String,
+    /// Metadata of the feed entry containing the indicator
+    pub metadata: serde_json::Value,
+    /// When the indicator was first seen in the feed
+    pub first_seen: NaiveDateTime,
+    /// When the indicator was last seen in the feed
+    pub last_seen: NaiveDateTime,
+    /// Whether the indicator is still in the feed
+    pub active: bool,
+}
+
+/// Indicator parsed from a feed, to be ingested
+#[derive(Debug, Clone)]
+pub struct NewFeedEntry {
+    pub kind: String,
+    pub data: String,
+    pub metadata: serde_json::Value,
+}
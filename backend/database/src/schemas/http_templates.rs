@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+use utoipa::ToSchema;
+
+use super::indicators::IndicatorKind;
+
+/// HTTP method of the request sent by an HTTP source
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, Eq, PartialEq, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[typeshare]
+pub enum HttpMethod {
+    #[default]
+    Get,
+    Post,
+}
+
+/// Header sent by an HTTP source, its value being a template
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct HttpHeader {
+    /// Name of the header
+    pub name: String,
+    /// Template of the value of the header, usually referencing a secret
+    pub value: String,
+}
+
+/// Value of the `{{kind}}` placeholder for an indicator kind
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct HttpKindMapping {
+    /// Kind of the indicator
+    pub kind: IndicatorKind,
+    /// Value replacing the `{{kind}}` placeholder, such as a path segment or a query parameter
+    pub value: String,
+}
+
+/// Declarative definition of an HTTP source, stored as the first element of the source's config.
+///
+/// Templates may contain the `{{indicator}}`, `{{kind}}` and `{{secrets.NAME}}` placeholders, the
+/// latter being replaced by the value of the source's secret named `NAME`.
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct HttpTemplateConfig {
+    /// HTTP method of the request, defaults to GET
+    #[serde(default)]
+    pub method: HttpMethod,
+    /// Template of the URL of the request, the values replacing placeholders are URL encoded
+    pub url: String,
+    /// Headers of the request
+    #[serde(default)]
+    pub headers: Vec<HttpHeader>,
+    /// Template of the JSON body of the request, the values replacing placeholders are JSON
+    /// escaped
+    pub body: Option<String>,
+    /// Values of the `{{kind}}` placeholder per indicator kind, defaults to the lowercased kind
+    #[serde(default)]
+    pub kinds: Vec<HttpKindMapping>,
+    /// JSONPath selecting the data to return from the JSON response, defaults to the whole
+    /// response
+    pub response_path: Option<String>,
+}
+
+impl HttpTemplateConfig {
+    /// Parses the HTTP request definition from a source's config
+    pub fn from_source_config(config: &[serde_json::Value]) -> serde_json::Result<Self> {
+        serde_json::from_value(config.first().cloned().unwrap_or_default())
+    }
+}
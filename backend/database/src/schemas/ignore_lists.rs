@@ -29,6 +29,8 @@ pub struct IgnoreList {
     pub created_user_id: String,
     /// Database ID of the user who last updated the ignore list
     pub updated_user_id: Option<String>,
+    /// Database ID of the team owning the ignore list, shared with everyone when not set
+    pub team_id: Option<String>,
 }
 
 /// Parameters for creating a new ignore list
@@ -44,6 +46,9 @@ pub struct CreateIgnoreList {
     pub enabled: bool,
     /// Whether the ignore list is global and used to ignore all requests, regardless of it's source
     pub global: bool,
+    /// Database ID of the team owning the ignore list, an empty string sharing it with everyone.
+    /// Defaults to the first team of the user.
+    pub team_id: Option<String>,
 }
 
 /// Parameters for updating an ignore list
@@ -59,6 +64,8 @@ pub struct UpdateIgnoreList {
     pub enabled: Option<bool>,
     /// Whether the ignore list is global and used to ignore all requests, regardless of it's source
     pub global: Option<bool>,
+    /// Database ID of the team to move the ignore list to, an empty string sharing it with everyone
+    pub team_id: Option<String>,
 }
 
 /// Entry in an ignore list
@@ -1,10 +1,11 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 use utoipa::ToSchema;
 
 /// Kind of the indicator
 #[derive(
     Deserialize,
+    Serialize,
     Debug,
     strum::Display,
     PartialEq,
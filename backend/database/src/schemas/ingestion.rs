@@ -0,0 +1,27 @@
+/// Number of records changed by the ingestion of a feed
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct IngestionCounts {
+    /// Records seen for the first time
+    pub added: i32,
+    /// Records whose content changed or that were seen again after being removed from the feed
+    pub updated: i32,
+    /// Records no longer in the feed, marked as inactive
+    pub removed: i32,
+}
+
+impl std::ops::AddAssign for IngestionCounts {
+    fn add_assign(&mut self, other: Self) {
+        self.added += other.added;
+        self.updated += other.updated;
+        self.removed += other.removed;
+    }
+}
+
+/// Validators of the last download of a feed, sent back to only download it again if it changed
+#[derive(Debug, Default, Clone)]
+pub struct FeedValidators {
+    /// Value of the `ETag` response header
+    pub etag: Option<String>,
+    /// Value of the `Last-Modified` response header
+    pub last_modified: Option<String>,
+}
@@ -0,0 +1,54 @@
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use sqlx::FromRow;
+use typeshare::typeshare;
+use utoipa::ToSchema;
+
+#[derive(FromRow, Debug)]
+pub struct UserTotp {
+    pub user_id: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    /// Encrypted base32 secret shared with the authenticator app
+    pub secret: Vec<u8>,
+    /// Whether the user confirmed the enrollment with a valid code
+    pub enabled: bool,
+    /// Last time step used to login, preventing a code from being used twice
+    pub last_used_step: Option<i64>,
+}
+
+/// A WebAuthn security key registered by a user as second factor
+#[derive(FromRow, Serialize, ToSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct WebAuthnCredential {
+    /// The database ID of the security key
+    pub id: String,
+    /// The time the security key was registered
+    pub created_at: NaiveDateTime,
+    /// The time the security key was last updated
+    pub updated_at: NaiveDateTime,
+    /// Name given by the user to the security key
+    pub name: String,
+    /// Base64 URL encoded ID of the credential generated by the security key
+    pub credential_id: String,
+    /// SEC1 encoded P-256 public key of the credential
+    #[serde(skip)]
+    pub public_key: Vec<u8>,
+    /// Signature counter of the security key, used to detect cloned keys
+    #[serde(skip)]
+    pub sign_count: i64,
+    /// The time the security key was last used to login
+    pub last_used_at: Option<NaiveDateTime>,
+    #[serde(skip)]
+    pub user_id: String,
+}
+
+#[derive(Debug)]
+pub struct CreateWebAuthnCredential {
+    pub user_id: String,
+    pub name: String,
+    pub credential_id: String,
+    pub public_key: Vec<u8>,
+    pub sign_count: i64,
+}
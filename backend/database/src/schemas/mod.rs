@@ -5,16 +5,23 @@ use utoipa::ToSchema;
 
 pub mod api_tokens;
 pub mod auth;
+pub mod feeds;
+pub mod http_templates;
 pub mod ignore_lists;
 pub mod indicators;
+pub mod ingestion;
+pub mod mfa;
 pub mod notifications;
 pub mod phish_tank;
 pub mod providers;
 pub mod requests;
 pub mod secrets;
 pub mod server_config;
+pub mod sessions;
 pub mod sources;
 pub mod stats;
+pub mod task_runs;
+pub mod teams;
 pub mod url_haus;
 pub mod users;
 
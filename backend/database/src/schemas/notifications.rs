@@ -9,6 +9,7 @@ use utoipa::ToSchema;
 #[typeshare]
 pub enum NotificationKind {
     MissingRequiredSourceSecret(MinimalSource),
+    FailingSourceTask(FailingSourceTask),
     // TODO: MissingSourceCode,
     // TODO: ExpiredSecret,
 }
@@ -27,3 +28,20 @@ pub struct MinimalSource {
     /// Number of secrets needed for the source
     pub num_missing_secrets: i32,
 }
+
+/// A source whose background task failed multiple times in a row
+#[derive(Serialize, FromRow, ToSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct FailingSourceTask {
+    /// Database ID of the source
+    pub id: String,
+    /// Name of the source
+    pub name: String,
+    /// URL friendly name of the provider
+    pub slug: String,
+    /// Number of failed runs of the task since its last successful run
+    pub num_consecutive_failures: i32,
+    /// Error of the last failed run of the task
+    pub last_error: Option<String>,
+}
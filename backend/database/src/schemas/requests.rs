@@ -23,6 +23,8 @@ pub struct Request {
     pub trace_id: String,
     /// Database ID of the user who requested the data
     pub user_id: String,
+    /// Database ID of the team the request was made for, visible to everyone when not set
+    pub team_id: Option<String>,
 }
 
 /// Response of a source following a user request against it and snapshot values of the source (in case the source gets deleted/modified after the request)
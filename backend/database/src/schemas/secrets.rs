@@ -25,6 +25,8 @@ pub struct Secret {
     pub created_user_id: String,
     /// Database ID of the user who last updated the secret
     pub updated_user_id: Option<String>,
+    /// Database ID of the team owning the secret, shared with everyone when not set
+    pub team_id: Option<String>,
     /// The number of sources that use this secret
     pub num_sources: i32,
 }
@@ -67,6 +69,9 @@ pub struct CreateSecret {
     pub description: Option<String>,
     /// The time the secret expires
     pub expires_at: Option<NaiveDateTime>,
+    /// Database ID of the team owning the secret, an empty string sharing it with everyone.
+    /// Defaults to the first team of the user.
+    pub team_id: Option<String>,
 }
 
 #[derive(FromRow)]
@@ -88,6 +93,8 @@ pub struct UpdateSecret {
     pub description: Option<String>,
     /// The time the secret expires
     pub expires_at: Option<NaiveDateTime>,
+    /// Database ID of the team to move the secret to, an empty string sharing it with everyone
+    pub team_id: Option<String>,
 }
 
 /// Parameters for creating a new source secret
@@ -28,6 +28,8 @@ pub enum ServerConfigCategory {
     Proxy,
     Sse,
     Runners,
+    Tasks,
+    Auth,
 }
 
 /// Configuration entry for the server
@@ -100,6 +102,8 @@ pub struct ServerConfig {
     pub javascript_runner_enabled: ServerConfigEntryBool,
     pub python_runner_grpc_address: ServerConfigEntryString,
     pub python_runner_enabled: ServerConfigEntryBool,
+    pub task_failure_notification_threshold: ServerConfigEntryU32,
+    pub mfa_required: ServerConfigEntryBool,
 }
 
 impl Default for ServerConfig {
@@ -178,7 +182,8 @@ def background_task():
             javascript_runner_grpc_address: ServerConfigEntry {
                 default_value: "http://indicator_aggregator_javascript_runner:50051".to_string(),
                 friendly_name: "Javascript Runner GRPC Address".to_string(),
-                description: "GRPC address of the Javascript runner".to_string(),
+                description: "GRPC addresses of the Javascript runner instances, separated by commas"
+                    .to_string(),
                 kind: ServerConfigKind::String,
                 category: ServerConfigCategory::Runners,
                 ..Default::default()
@@ -194,7 +199,8 @@ def background_task():
             python_runner_grpc_address: ServerConfigEntry {
                 default_value: "http://indicator_aggregator_python_runner:50051".to_string(),
                 friendly_name: "Python Runner GRPC Address".to_string(),
-                description: "GRPC address of the Python runner".to_string(),
+                description: "GRPC addresses of the Python runner instances, separated by commas"
+                    .to_string(),
                 kind: ServerConfigKind::String,
                 category: ServerConfigCategory::Runners,
                 ..Default::default()
@@ -207,11 +213,27 @@ def background_task():
                 category: ServerConfigCategory::Runners,
                 ..Default::default()
             },
+            task_failure_notification_threshold: ServerConfigEntry {
+                default_value: 3,
+                friendly_name: "Task Failure Notification Threshold".to_string(),
+                description: "Number of consecutive failed runs of a source's background task before a notification is shown".to_string(),
+                kind: ServerConfigKind::Number,
+                category: ServerConfigCategory::Tasks,
+                ..Default::default()
+            },
+            mfa_required: ServerConfigEntry {
+                default_value: false,
+                friendly_name: "MFA Required".to_string(),
+                description: "Require local accounts to login with a second factor, enrolling one on their next login if needed".to_string(),
+                kind: ServerConfigKind::Boolean,
+                category: ServerConfigCategory::Auth,
+                ..Default::default()
+            },
         }
     }
 }
 
-pub const SERVER_CONFIG_ENTRIES: [&str; 11] = [
+pub const SERVER_CONFIG_ENTRIES: [&str; 13] = [
     "javascript_source_template",
     "python_source_template",
     "proxy_enabled",
@@ -223,6 +245,8 @@ pub const SERVER_CONFIG_ENTRIES: [&str; 11] = [
     "javascript_runner_enabled",
     "python_runner_grpc_address",
     "python_runner_enabled",
+    "task_failure_notification_threshold",
+    "mfa_required",
 ];
 
 impl ServerConfig {
@@ -318,6 +342,24 @@ impl ServerConfig {
                     self.python_runner_enabled.last_modified_user_id =
                         Some(db_result.last_modified_user_id);
                 }
+                "task_failure_notification_threshold" => {
+                    self.task_failure_notification_threshold.id = Some(db_result.id);
+                    self.task_failure_notification_threshold.created_at =
+                        Some(db_result.created_at);
+                    self.task_failure_notification_threshold.updated_at =
+                        Some(db_result.updated_at);
+                    self.task_failure_notification_threshold.value =
+                        Some(db_result.value.parse().unwrap());
+                    self.task_failure_notification_threshold
+                        .last_modified_user_id = Some(db_result.last_modified_user_id);
+                }
+                "mfa_required" => {
+                    self.mfa_required.id = Some(db_result.id);
+                    self.mfa_required.created_at = Some(db_result.created_at);
+                    self.mfa_required.updated_at = Some(db_result.updated_at);
+                    self.mfa_required.value = Some(db_result.value.parse().unwrap());
+                    self.mfa_required.last_modified_user_id = Some(db_result.last_modified_user_id);
+                }
                 _ => (),
             };
         }
@@ -331,11 +373,18 @@ impl ServerConfig {
         }
     }
 
-    pub fn runner_endpoint(&self, source_kind: &SourceKind) -> &str {
-        match source_kind {
+    /// Addresses of the instances of a runner, separated by commas in the config
+    pub fn runner_endpoints(&self, source_kind: &SourceKind) -> Vec<&str> {
+        let addresses = match source_kind {
             SourceKind::JavaScript => self.javascript_runner_grpc_address.get_value(),
             SourceKind::Python => self.python_runner_grpc_address.get_value(),
             _ => panic!("Invalid source kind"),
-        }
+        };
+
+        addresses
+            .split(',')
+            .map(str::trim)
+            .filter(|address| !address.is_empty())
+            .collect()
     }
 }
@@ -0,0 +1,83 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, Type};
+use typeshare::typeshare;
+use utoipa::ToSchema;
+
+/// Asymmetric algorithm of the keys signing the JWTs
+#[derive(Debug, Deserialize, Type, Clone, Copy, PartialEq, Eq, Default)]
+#[sqlx(rename_all = "snake_case", type_name = "jwt_key_algorithm")]
+pub enum JwtKeyAlgorithm {
+    /// RSASSA-PKCS1-v1_5 with SHA-256, using 2048 bits keys
+    #[serde(rename = "RS256")]
+    Rs256,
+    /// Ed25519 signatures
+    #[default]
+    #[serde(rename = "EdDSA")]
+    EdDsa,
+}
+
+#[derive(FromRow, Debug)]
+pub struct JwtKey {
+    /// ID of the key, set as the `kid` of the JWTs it signs
+    pub id: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub algorithm: JwtKeyAlgorithm,
+    /// Encrypted DER encoded private key, PKCS#1 for RSA and PKCS#8 for Ed25519
+    pub private_key: Vec<u8>,
+    /// DER encoded PKCS#1 public key for RSA, raw public key for Ed25519
+    pub public_key: Vec<u8>,
+    /// Time after which the JWTs signed by the key are all expired, the key being removed
+    pub expires_at: NaiveDateTime,
+}
+
+#[derive(Debug)]
+pub struct CreateJwtKey {
+    pub algorithm: JwtKeyAlgorithm,
+    pub private_key: Vec<u8>,
+    pub public_key: Vec<u8>,
+    pub expires_at: NaiveDateTime,
+}
+
+/// A login of a user, kept alive by refreshing its short lived JWTs
+#[derive(FromRow, Serialize, ToSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct UserSession {
+    /// The database ID of the session
+    pub id: String,
+    /// The time the user logged in
+    pub created_at: NaiveDateTime,
+    /// The time the session was last updated
+    pub updated_at: NaiveDateTime,
+    /// Hash of the secret part of the current refresh token
+    #[serde(skip)]
+    pub refresh_token: String,
+    /// Hash of the secret part of the refresh token replaced by the current one, whose reuse
+    /// means that it was stolen
+    #[serde(skip)]
+    pub previous_refresh_token: Option<String>,
+    /// IP address of the client that logged in
+    pub ip_address: String,
+    /// User agent of the client that logged in
+    pub user_agent: String,
+    /// The time the session was last refreshed
+    pub last_used_at: NaiveDateTime,
+    /// The time after which the session can no longer be refreshed
+    pub expires_at: NaiveDateTime,
+    /// The time the session was revoked, if it was
+    pub revoked_at: Option<NaiveDateTime>,
+    /// The database ID of the user of the session
+    pub user_id: String,
+}
+
+#[derive(Debug)]
+pub struct CreateUserSession {
+    pub user_id: String,
+    /// Hash of the secret part of the refresh token
+    pub refresh_token: String,
+    pub ip_address: String,
+    pub user_agent: String,
+    pub expires_at: NaiveDateTime,
+}
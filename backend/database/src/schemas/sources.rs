@@ -13,6 +13,17 @@ pub enum SourceKind {
     System,
     Python,
     JavaScript,
+    /// Generic feed source, configured through the source's config
+    Feed,
+    /// Generic HTTP source, configured through the source's config
+    Http,
+}
+
+impl SourceKind {
+    /// Whether the source's code is executed by a runner
+    pub fn runs_on_runner(&self) -> bool {
+        matches!(self, Self::Python | Self::JavaScript)
+    }
 }
 
 impl std::fmt::Display for SourceKind {
@@ -56,6 +67,9 @@ pub struct Source {
     pub task_enabled: bool,
     /// Interval in seconds between the source's background task executions
     pub task_interval: Option<i32>,
+    /// Cron expression of the source's background task executions, taking precedence over the
+    /// interval
+    pub task_cron: Option<String>,
     /// Configuration of the source
     pub config: Vec<serde_json::Value>,
     /// Values of the source's configuration
@@ -80,6 +94,8 @@ pub struct Source {
     pub created_user_id: String,
     /// Database ID of the user who last updated the source
     pub updated_user_id: Option<String>,
+    /// Database ID of the team owning the source, shared with everyone when not set
+    pub team_id: Option<String>,
 }
 
 /// Parameters to create a source
@@ -107,6 +123,9 @@ pub struct CreateSource {
     pub task_enabled: bool,
     /// Interval in seconds between the source's background task executions
     pub task_interval: Option<i32>,
+    /// Cron expression of the source's background task executions, taking precedence over the
+    /// interval
+    pub task_cron: Option<String>,
     /// Configuration of the source
     pub config: Vec<serde_json::Value>,
     /// Values of the source's configuration
@@ -127,6 +146,9 @@ pub struct CreateSource {
     pub kind: SourceKind,
     /// Source code of the source
     pub source_code: Option<String>,
+    /// Database ID of the team owning the source, an empty string sharing it with everyone.
+    /// Defaults to the first team of the user.
+    pub team_id: Option<String>,
 }
 
 /// Parameters to partially update a source
@@ -154,6 +176,9 @@ pub struct UpdateSource {
     pub task_enabled: Option<bool>,
     /// Interval in seconds between the source's background task executions
     pub task_interval: Option<i32>,
+    /// Cron expression of the source's background task executions, taking precedence over the
+    /// interval, an empty string removes it
+    pub task_cron: Option<String>,
     /// Configuration of the source
     pub config: Option<Vec<serde_json::Value>>,
     /// Values of the source's configuration
@@ -174,6 +199,20 @@ pub struct UpdateSource {
     pub kind: Option<SourceKind>,
     /// Source code of the source
     pub source_code: Option<String>,
+    /// Database ID of the team to move the source to, an empty string sharing it with everyone
+    pub team_id: Option<String>,
+}
+
+impl UpdateSource {
+    /// Whether the update modifies fields affecting the data returned by the source, making its
+    /// cached data stale
+    pub fn invalidates_cache(&self) -> bool {
+        self.source_code.is_some()
+            || self.config.is_some()
+            || self.config_values.is_some()
+            || self.supported_indicators.is_some()
+            || self.kind.is_some()
+    }
 }
 
 #[derive(FromRow, Debug, Clone)]
@@ -200,3 +239,38 @@ pub struct SourceCode {
     pub id: String,
     pub source_code: Option<String>,
 }
+
+/// Reason why the cached data of a source was invalidated
+#[derive(Deserialize, Serialize, Debug, Type, Clone, Copy, Eq, PartialEq, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sqlx(rename_all = "snake_case", type_name = "cache_invalidation_reason")]
+#[typeshare]
+pub enum CacheInvalidationReason {
+    /// The source's code, configuration or supported indicators were modified
+    SourceUpdated,
+    /// The secrets linked to the source were modified
+    SourceSecretsUpdated,
+    /// The value of a secret linked to the source was modified
+    SecretUpdated,
+}
+
+/// Record of the cached data of a source being invalidated
+#[derive(FromRow, Serialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct SourceCacheInvalidation {
+    /// Database ID of the cache invalidation
+    pub id: String,
+    /// Timestamp of when the cache was invalidated
+    pub created_at: NaiveDateTime,
+    /// Reason why the cache was invalidated
+    pub reason: CacheInvalidationReason,
+    /// Number of cache entries removed
+    pub num_entries: i32,
+    /// Trace ID of the request that caused the invalidation
+    pub trace_id: String,
+    /// Database ID of the source whose cache was invalidated
+    pub source_id: String,
+    /// Database ID of the user who caused the invalidation
+    pub user_id: String,
+}
@@ -0,0 +1,64 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{prelude::FromRow, Type};
+use typeshare::typeshare;
+use utoipa::ToSchema;
+
+/// Outcome of a source's background task execution
+#[derive(Deserialize, Serialize, Debug, Type, Clone, Copy, Eq, PartialEq, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sqlx(rename_all = "snake_case", type_name = "task_run_status")]
+#[typeshare]
+pub enum TaskRunStatus {
+    /// The execution has not finished yet
+    Running,
+    /// The execution finished without errors
+    Succeeded,
+    /// The execution finished with an error
+    Failed,
+}
+
+/// What caused a source's background task to be executed
+#[derive(Deserialize, Serialize, Debug, Type, Clone, Copy, Eq, PartialEq, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sqlx(rename_all = "snake_case", type_name = "task_run_trigger")]
+#[typeshare]
+pub enum TaskRunTrigger {
+    /// Executed by the scheduler following the source's interval or cron expression
+    Scheduled,
+    /// Executed on demand by a user
+    Manual,
+}
+
+/// Record of an execution of a source's background task
+#[derive(FromRow, Serialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct TaskRun {
+    /// Database ID of the task run
+    pub id: String,
+    /// Timestamp of when the execution started
+    pub started_at: NaiveDateTime,
+    /// Timestamp of when the execution ended, if it has ended
+    pub ended_at: Option<NaiveDateTime>,
+    /// Outcome of the execution
+    pub status: TaskRunStatus,
+    /// What caused the execution
+    pub trigger: TaskRunTrigger,
+    /// Error encountered by the execution, if it failed
+    pub error: Option<String>,
+    /// Number of records ingested by the execution, if the task reports it
+    pub records_ingested: Option<i32>,
+    /// Number of records seen for the first time by the execution, if the task reports it
+    pub records_added: Option<i32>,
+    /// Number of records changed by the execution, if the task reports it
+    pub records_updated: Option<i32>,
+    /// Number of records removed by the execution, if the task reports it
+    pub records_removed: Option<i32>,
+    /// Trace ID of the execution
+    pub trace_id: String,
+    /// Database ID of the source whose task was executed
+    pub source_id: String,
+    /// Database ID of the user who triggered the execution, if triggered manually
+    pub user_id: Option<String>,
+}
@@ -0,0 +1,52 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use typeshare::typeshare;
+use utoipa::{IntoParams, ToSchema};
+
+/// Group of users sharing sources, secrets, ignore lists and requests
+#[derive(FromRow, Serialize, ToSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct Team {
+    /// Database ID of the team
+    pub id: String,
+    /// Timestamp of when the team was created
+    pub created_at: NaiveDateTime,
+    /// Timestamp of when the team was last updated
+    pub updated_at: NaiveDateTime,
+    /// Name of the team
+    pub name: String,
+    /// URL friendly name of the team
+    pub slug: String,
+    /// Description of the team
+    pub description: String,
+    /// Database ID of the user who created the team
+    pub created_user_id: String,
+    /// Database ID of the user who last updated the team
+    pub updated_user_id: Option<String>,
+    /// Number of users member of the team
+    pub num_members: i32,
+}
+
+/// Parameters for creating a new team
+#[derive(Deserialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct CreateTeam {
+    /// Name of the team
+    pub name: String,
+    /// Description of the team
+    pub description: String,
+}
+
+/// Parameters for updating a team
+#[derive(Deserialize, Debug, ToSchema, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct UpdateTeam {
+    /// Name of the team
+    pub name: Option<String>,
+    /// Description of the team
+    pub description: Option<String>,
+}
@@ -15,4 +15,13 @@ pub struct Entry {
     pub tags: String,
     pub urlhaus_link: String,
     pub reporter: String,
+    /// Not part of the feed, when the entry was first ingested
+    #[serde(skip_deserializing)]
+    pub first_seen: NaiveDateTime,
+    /// Not part of the feed, when the entry was last seen in the feed
+    #[serde(skip_deserializing)]
+    pub last_seen: NaiveDateTime,
+    /// Not part of the feed, whether the entry is still in the feed
+    #[serde(skip_deserializing)]
+    pub active: bool,
 }
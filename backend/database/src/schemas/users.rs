@@ -1,10 +1,19 @@
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
-use std::collections::HashSet;
+use sqlx::{FromRow, Type};
+use std::{
+    collections::{BTreeSet, HashSet},
+    str::FromStr,
+};
+use strum::IntoEnumIterator;
 use typeshare::typeshare;
 use utoipa::ToSchema;
 
+use super::api_tokens::ApiTokenScope;
+
+/// Authentication provider of the service accounts
+pub const SERVICE_ACCOUNT_PROVIDER: &str = "Service Account";
+
 /// User able to query and make modifications to Indicator Aggregator
 #[derive(FromRow, Serialize, Deserialize, Clone, Debug, ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -40,6 +49,185 @@ pub struct User {
     pub roles: Vec<String>,
     /// User that last modified the user
     pub last_modified_user_id: Option<String>,
+    /// Whether the user is a service account used by integrations, unable to login and only
+    /// authenticating with API tokens
+    pub service_account: bool,
+    /// User responsible for the service account
+    pub owner_user_id: Option<String>,
+    /// Restrictions of the API token the user authenticated with, if any
+    #[serde(skip)]
+    #[sqlx(skip)]
+    pub api_token_scope: Option<ApiTokenScope>,
+    /// Database IDs of the teams the user is a member of, the oldest membership first
+    #[serde(skip)]
+    #[sqlx(skip)]
+    pub team_ids: Vec<String>,
+}
+
+impl User {
+    /// Permissions granted to the user through their roles, limited to the ones of the API token
+    /// they authenticated with
+    pub fn permissions(&self) -> BTreeSet<Permission> {
+        let permissions = Role::permissions_of(&self.roles);
+
+        match self
+            .api_token_scope
+            .as_ref()
+            .and_then(|scope| scope.permissions.as_ref())
+        {
+            Some(scope) => permissions.intersection(scope).copied().collect(),
+            None => permissions,
+        }
+    }
+
+    /// Teams whose resources the user can access, on top of the shared ones without a team.
+    /// `None` when the user can access the resources of every team.
+    pub fn visible_team_ids(&self) -> Option<&[String]> {
+        (!self.permissions().contains(&Permission::TeamEdit)).then_some(&self.team_ids)
+    }
+
+    /// Whether the user can access the resources of a team, shared resources without a team
+    /// being accessible to everyone
+    pub fn can_access_team(&self, team_id: Option<&str>) -> bool {
+        match (team_id, self.visible_team_ids()) {
+            (Some(team_id), Some(team_ids)) => team_ids.iter().any(|id| id == team_id),
+            _ => true,
+        }
+    }
+}
+
+/// Role of a user, granting them a set of permissions
+#[derive(
+    Deserialize,
+    Serialize,
+    Debug,
+    strum::Display,
+    PartialEq,
+    Eq,
+    Hash,
+    Clone,
+    Copy,
+    ToSchema,
+    strum::EnumIter,
+    strum::EnumString,
+)]
+#[typeshare]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum Role {
+    /// Every permission, including managing users, teams and the server configuration
+    Admin,
+    /// Analyst permissions, and managing sources, providers, ignore lists, secrets and runners
+    SourceEditor,
+    /// Viewer permissions, and executing requests
+    Analyst,
+    /// Read-only access to requests, sources, providers, ignore lists, statistics and configuration
+    Viewer,
+}
+
+impl Role {
+    /// Permissions granted by the role
+    pub fn permissions(&self) -> BTreeSet<Permission> {
+        use Permission::*;
+
+        match self {
+            Self::Admin => Permission::iter().collect(),
+            Self::SourceEditor => Self::Analyst
+                .permissions()
+                .into_iter()
+                .chain([
+                    SourceEdit,
+                    ProviderEdit,
+                    IgnoreListEdit,
+                    SecretView,
+                    SecretEdit,
+                    RunnerView,
+                ])
+                .collect(),
+            Self::Analyst => Self::Viewer
+                .permissions()
+                .into_iter()
+                .chain([RequestCreate])
+                .collect(),
+            Self::Viewer => BTreeSet::from([
+                RequestView,
+                SourceView,
+                ProviderView,
+                IgnoreListView,
+                StatsView,
+                ConfigView,
+            ]),
+        }
+    }
+
+    /// Permissions granted by roles stored in the database, ignoring unknown roles
+    pub fn permissions_of(roles: &[String]) -> BTreeSet<Permission> {
+        roles
+            .iter()
+            .filter_map(|role| Role::from_str(role).ok())
+            .flat_map(|role| role.permissions())
+            .collect()
+    }
+}
+
+/// Fine-grained action a user is allowed to do, granted through their roles
+#[derive(
+    Deserialize,
+    Serialize,
+    Debug,
+    strum::Display,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Clone,
+    Copy,
+    ToSchema,
+    strum::EnumIter,
+    strum::EnumString,
+)]
+#[typeshare]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum Permission {
+    /// View requests and their results
+    RequestView,
+    /// Execute requests to sources
+    RequestCreate,
+    /// View sources and their task runs
+    SourceView,
+    /// Create, update and delete sources, run their tasks and test their code
+    SourceEdit,
+    /// View providers
+    ProviderView,
+    /// Create, update and delete providers
+    ProviderEdit,
+    /// View ignore lists and their entries
+    IgnoreListView,
+    /// Create, update and delete ignore lists
+    IgnoreListEdit,
+    /// View secrets, without their value
+    SecretView,
+    /// Create, update and delete secrets, and assign them to sources
+    SecretEdit,
+    /// View the value of secrets
+    SecretValueView,
+    /// View the status of the runners
+    RunnerView,
+    /// View statistics about the service
+    StatsView,
+    /// View the server configuration
+    ConfigView,
+    /// Update the server configuration
+    ConfigEdit,
+    /// View users and their activity
+    UserView,
+    /// Enable, disable and assign roles to users, and revoke their API tokens
+    UserEdit,
+    /// Create, update and delete teams, manage their members and access the resources of every
+    /// team
+    TeamEdit,
 }
 
 #[derive(FromRow, Clone, Debug)]
@@ -60,6 +248,8 @@ pub struct UserWithPassword {
     pub roles: Vec<String>,
     pub password: Option<String>,
     pub last_modified_user_id: Option<String>,
+    pub service_account: bool,
+    pub owner_user_id: Option<String>,
 }
 
 impl From<UserWithPassword> for User {
@@ -80,6 +270,10 @@ impl From<UserWithPassword> for User {
             picture: value.picture,
             roles: value.roles,
             last_modified_user_id: value.last_modified_user_id,
+            service_account: value.service_account,
+            owner_user_id: value.owner_user_id,
+            api_token_scope: None,
+            team_ids: Vec::new(),
         }
     }
 }
@@ -92,7 +286,22 @@ pub struct UpdateUser {
     /// Wheter to enable the user or not
     pub enabled: Option<bool>,
     /// Roles to assign to the user, if any
-    pub roles: Option<HashSet<String>>,
+    pub roles: Option<HashSet<Role>>,
+    /// User responsible for the service account, if it is one
+    pub owner_user_id: Option<String>,
+}
+
+/// Data needed to create a service account
+#[derive(Deserialize, Clone, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct CreateServiceAccount {
+    /// Name of the service account, usually the integration using it
+    pub name: String,
+    /// Roles of the service account
+    pub roles: HashSet<Role>,
+    /// User responsible for the service account, defaults to the one creating it
+    pub owner_user_id: Option<String>,
 }
 
 /// Container for a user and the number of logs they have
@@ -119,7 +328,9 @@ pub struct CreateUser {
     pub family_name: Option<String>,
     pub locale: Option<String>,
     pub picture: Option<Vec<u8>>,
-    pub roles: HashSet<String>,
+    pub roles: HashSet<Role>,
+    /// Whether the roles replace the ones of an existing user instead of being added to them
+    pub replace_roles: bool,
     pub hashed_password: Option<String>,
 }
 
@@ -131,6 +342,17 @@ pub struct UserLog {
     pub user_agent: String,
     pub uri: String,
     pub method: String,
+    pub event: Option<UserLogEvent>,
+}
+
+/// Security event related to the account of a user, logged along with the request causing it
+#[derive(Deserialize, Serialize, Debug, Type, Clone, Copy, Eq, PartialEq, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sqlx(rename_all = "snake_case", type_name = "user_log_event")]
+#[typeshare]
+pub enum UserLogEvent {
+    /// The account was temporarily locked after too many failed logins
+    AccountLocked,
 }
 
 /// User request log
@@ -156,4 +378,103 @@ pub struct DbUserLog {
     pub method: String,
     /// Opentelemetry trace ID of the request, can be used to correlate logs with a tool like Jaeger
     pub trace_id: String,
+    /// Security event caused by the request, if any
+    pub event: Option<UserLogEvent>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_given_roles_when_getting_permissions_then_higher_roles_include_lower_ones() {
+        let viewer = Role::Viewer.permissions();
+        let analyst = Role::Analyst.permissions();
+        let source_editor = Role::SourceEditor.permissions();
+        let admin = Role::Admin.permissions();
+
+        assert!(viewer.is_subset(&analyst) && analyst.contains(&Permission::RequestCreate));
+        assert!(
+            analyst.is_subset(&source_editor) && !source_editor.contains(&Permission::UserEdit)
+        );
+        assert!(source_editor.is_subset(&admin));
+        assert_eq!(admin.len(), Permission::iter().count());
+    }
+
+    #[test]
+    fn test_given_unknown_roles_when_getting_permissions_then_ignores_them() {
+        let roles = vec!["request_create".to_string(), "viewer".to_string()];
+
+        assert_eq!(Role::permissions_of(&roles), Role::Viewer.permissions());
+    }
+
+    #[test]
+    fn test_given_scoped_api_token_when_getting_permissions_then_limits_them_to_the_token() {
+        let mut user = User {
+            id: "id".to_string(),
+            created_at: Default::default(),
+            updated_at: Default::default(),
+            auth_id: None,
+            provider: "Indicator Aggregator".to_string(),
+            enabled: true,
+            email: "test@test.test".to_string(),
+            verified: true,
+            name: "Test User".to_string(),
+            given_name: None,
+            family_name: None,
+            locale: None,
+            picture: None,
+            roles: vec![Role::Viewer.to_string()],
+            last_modified_user_id: None,
+            service_account: false,
+            owner_user_id: None,
+            api_token_scope: Some(ApiTokenScope {
+                permissions: Some([Permission::RequestView, Permission::RequestCreate].into()),
+                ..Default::default()
+            }),
+            team_ids: Vec::new(),
+        };
+
+        assert_eq!(user.permissions(), [Permission::RequestView].into());
+
+        user.api_token_scope = Some(Default::default());
+        assert_eq!(user.permissions(), Role::Viewer.permissions());
+    }
+
+    #[test]
+    fn test_given_team_member_when_accessing_teams_then_only_sees_theirs_unless_team_editor() {
+        let mut user = User {
+            id: "id".to_string(),
+            created_at: Default::default(),
+            updated_at: Default::default(),
+            auth_id: None,
+            provider: "Indicator Aggregator".to_string(),
+            enabled: true,
+            email: "test@test.test".to_string(),
+            verified: true,
+            name: "Test User".to_string(),
+            given_name: None,
+            family_name: None,
+            locale: None,
+            picture: None,
+            roles: vec![Role::SourceEditor.to_string()],
+            last_modified_user_id: None,
+            service_account: false,
+            owner_user_id: None,
+            api_token_scope: None,
+            team_ids: vec!["team".to_string()],
+        };
+
+        assert_eq!(
+            user.visible_team_ids(),
+            Some(["team".to_string()].as_slice())
+        );
+        assert!(user.can_access_team(None));
+        assert!(user.can_access_team(Some("team")));
+        assert!(!user.can_access_team(Some("other")));
+
+        user.roles = vec![Role::Admin.to_string()];
+        assert_eq!(user.visible_team_ids(), None);
+        assert!(user.can_access_team(Some("other")));
+    }
 }
@@ -6,5 +6,9 @@ ON CONFLICT (email, provider) DO UPDATE SET
     given_name = EXCLUDED.given_name,
     family_name = EXCLUDED.family_name,
     locale = EXCLUDED.locale,
-    picture = EXCLUDED.picture
+    picture = EXCLUDED.picture,
+    roles = CASE
+        WHEN $13 THEN EXCLUDED.roles
+        ELSE users.roles || ARRAY(SELECT unnest(EXCLUDED.roles) EXCEPT SELECT unnest(users.roles))
+    END
 RETURNING *;
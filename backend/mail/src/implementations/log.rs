@@ -0,0 +1,21 @@
+use async_trait::async_trait;
+use tracing::warn;
+
+use crate::{Email, Mailer, Result};
+
+#[derive(Clone)]
+pub struct LogMailer;
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, email: &Email) -> Result<()> {
+        warn!(
+            to = email.to,
+            subject = email.subject,
+            body = email.body,
+            "No mail server configured, email not sent"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,25 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::{Email, Mailer, Result};
+
+#[derive(Clone, Default)]
+pub struct InMemoryMailer {
+    sent: Arc<Mutex<Vec<Email>>>,
+}
+
+impl InMemoryMailer {
+    pub async fn sent(&self) -> Vec<Email> {
+        self.sent.lock().await.clone()
+    }
+}
+
+#[async_trait]
+impl Mailer for InMemoryMailer {
+    async fn send(&self, email: &Email) -> Result<()> {
+        self.sent.lock().await.push(email.clone());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+mod log;
+mod memory;
+mod smtp;
+
+use async_trait::async_trait;
+use tracing::instrument;
+
+pub use smtp::{SmtpConfig, SmtpTls};
+
+use crate::{Email, Mailer, Result};
+
+#[derive(Clone)]
+pub enum MailerImpl {
+    Smtp(Box<smtp::SmtpMailer>),
+    Log(log::LogMailer),
+    InMemory(memory::InMemoryMailer),
+}
+
+impl MailerImpl {
+    pub fn smtp(config: &SmtpConfig, from: &str) -> Result<Self> {
+        Ok(Self::Smtp(Box::new(smtp::SmtpMailer::new(config, from)?)))
+    }
+
+    /// Only logs the emails instead of sending them, for when no mail server is configured
+    pub fn log() -> Self {
+        Self::Log(log::LogMailer)
+    }
+
+    /// Keeps the emails in memory instead of sending them, mostly useful for tests
+    pub fn in_memory() -> Self {
+        Self::InMemory(Default::default())
+    }
+
+    /// Emails sent so far, only kept by the in memory mailer
+    pub async fn sent(&self) -> Vec<Email> {
+        match self {
+            Self::InMemory(mailer) => mailer.sent().await,
+            Self::Smtp(_) | Self::Log(_) => Vec::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl Mailer for MailerImpl {
+    #[instrument(skip_all, err(Debug))]
+    async fn send(&self, email: &Email) -> Result<()> {
+        match self {
+            Self::Smtp(mailer) => mailer.send(email).await,
+            Self::Log(mailer) => mailer.send(email).await,
+            Self::InMemory(mailer) => mailer.send(email).await,
+        }
+    }
+}
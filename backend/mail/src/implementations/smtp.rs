@@ -0,0 +1,167 @@
+use async_trait::async_trait;
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use serde::Deserialize;
+use tracing::{info, instrument};
+
+use crate::{Email, Mailer, Result};
+
+/// Encryption of the connection to the SMTP server
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpTls {
+    /// Unencrypted connection, only meant for local mail servers
+    None,
+    /// Connection upgraded to TLS with the STARTTLS command
+    #[default]
+    StartTls,
+    /// Connection using TLS from the start
+    Tls,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    /// Defaults to the standard port of the TLS mode
+    pub port: Option<u16>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    #[serde(default)]
+    pub tls: SmtpTls,
+}
+
+#[derive(Clone)]
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn new(config: &SmtpConfig, from: &str) -> Result<Self> {
+        let mut builder = match config.tls {
+            SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host),
+            SmtpTls::StartTls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)?
+            }
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)?,
+        };
+
+        if let Some(port) = config.port {
+            builder = builder.port(port);
+        }
+
+        if let Some(username) = &config.username {
+            builder = builder.credentials(Credentials::new(
+                username.clone(),
+                config.password.clone().unwrap_or_default(),
+            ));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from: from.parse()?,
+        })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    #[instrument(skip_all)]
+    async fn send(&self, email: &Email) -> Result<()> {
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(email.to.parse()?)
+            .subject(&email.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(email.body.clone())?;
+
+        self.transport.send(message).await?;
+        info!(subject = email.subject, "Sent email");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+        sync::oneshot,
+    };
+
+    use super::*;
+
+    /// Accepts a single SMTP session and returns the received message data
+    async fn spawn_smtp_sink() -> (u16, oneshot::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (tx, rx) = oneshot::channel();
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            let mut data = String::new();
+            let mut in_data = false;
+
+            writer.write_all(b"220 sink\r\n").await.unwrap();
+
+            while let Some(line) = lines.next_line().await.unwrap() {
+                let reply: &[u8] = if in_data {
+                    if line != "." {
+                        data.push_str(&line);
+                        data.push('\n');
+                        continue;
+                    }
+                    in_data = false;
+                    b"250 queued\r\n"
+                } else if line.starts_with("DATA") {
+                    in_data = true;
+                    b"354 go ahead\r\n"
+                } else if line.starts_with("QUIT") {
+                    writer.write_all(b"221 bye\r\n").await.unwrap();
+                    break;
+                } else {
+                    b"250 ok\r\n"
+                };
+
+                writer.write_all(reply).await.unwrap();
+            }
+
+            tx.send(data).unwrap();
+        });
+
+        (port, rx)
+    }
+
+    #[tokio::test]
+    async fn given_smtp_server_when_sending_email_then_server_receives_message() {
+        let (port, received) = spawn_smtp_sink().await;
+        let config = SmtpConfig {
+            host: "127.0.0.1".to_string(),
+            port: Some(port),
+            username: None,
+            password: None,
+            tls: SmtpTls::None,
+        };
+        let mailer = SmtpMailer::new(&config, "Indicator Aggregator <noreply@test.test>").unwrap();
+
+        let email = Email {
+            to: "jane@example.com".to_string(),
+            subject: "Verify your email".to_string(),
+            body: "Hello Jane".to_string(),
+        };
+        mailer.send(&email).await.unwrap();
+        drop(mailer);
+
+        let data = received.await.unwrap();
+        assert!(data.contains("From: \"Indicator Aggregator\" <noreply@test.test>"));
+        assert!(data.contains("To: jane@example.com"));
+        assert!(data.contains("Subject: Verify your email"));
+        assert!(data.contains("Hello Jane"));
+    }
+}
@@ -0,0 +1,45 @@
+use async_trait::async_trait;
+
+mod implementations;
+
+pub use implementations::{MailerImpl, SmtpConfig, SmtpTls};
+
+#[derive(Debug)]
+pub enum MailError {
+    Address(lettre::address::AddressError),
+    Message(lettre::error::Error),
+    Smtp(lettre::transport::smtp::Error),
+}
+
+impl From<lettre::address::AddressError> for MailError {
+    fn from(error: lettre::address::AddressError) -> Self {
+        Self::Address(error)
+    }
+}
+
+impl From<lettre::error::Error> for MailError {
+    fn from(error: lettre::error::Error) -> Self {
+        Self::Message(error)
+    }
+}
+
+impl From<lettre::transport::smtp::Error> for MailError {
+    fn from(error: lettre::transport::smtp::Error) -> Self {
+        Self::Smtp(error)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, MailError>;
+
+/// Plain text email sent to a single recipient
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[async_trait]
+pub trait Mailer {
+    async fn send(&self, email: &Email) -> Result<()>;
+}
@@ -2,7 +2,7 @@ use figment::{
     providers::{Env, Format, Toml},
     Figment,
 };
-use serde::Deserialize;
+use serde::{de::DeserializeOwned, Deserialize};
 use std::net::{AddrParseError, SocketAddr};
 use tracing::info;
 
@@ -25,11 +25,17 @@ pub struct Config {
 
 impl Config {
     pub fn new(prefix: &str, config: &str) -> figment::error::Result<Self> {
-        info!("Fetching config");
-
-        Figment::new()
-            .merge(Toml::string(config))
-            .merge(Env::prefixed(&format!("{prefix}__")).split("__"))
-            .extract()
+        load(prefix, config)
     }
 }
+
+/// Loads the config of a runner from its default config, overridden by the environment variables
+/// starting with the prefix, for runners needing more than the common config
+pub fn load<T: DeserializeOwned>(prefix: &str, config: &str) -> figment::error::Result<T> {
+    info!("Fetching config");
+
+    Figment::new()
+        .merge(Toml::string(config))
+        .merge(Env::prefixed(&format!("{prefix}__")).split("__"))
+        .extract()
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::result_large_err)]
+
 mod runner {
     tonic::include_proto!("runner");
 }
@@ -8,6 +10,7 @@ use std::{
 };
 
 pub use runner::*;
+use sha2::{Digest, Sha256};
 use tonic::{Request, Response, Status};
 pub use tonic_health as health;
 
@@ -27,18 +30,26 @@ where
     }
 }
 
+fn validate_indicator(indicator: Option<&runner::Indicator>) -> Option<&'static str> {
+    let Some(indicator) = indicator else {
+        return Some("indicator is required");
+    };
+
+    if indicator.data.is_empty() {
+        return Some("indicator data is required");
+    }
+
+    if indicator.kind.is_empty() {
+        return Some("indicator kind is required");
+    }
+
+    None
+}
+
 impl Validator for runner::FetchDataRequest {
     fn validate_inner(&self) -> Option<&str> {
-        if let Some(indicator) = &self.indicator {
-            if indicator.data.is_empty() {
-                return Some("indicator data is required");
-            }
-
-            if indicator.kind.is_empty() {
-                return Some("indicator kind is required");
-            }
-        } else {
-            return Some("indicator is required");
+        if let Some(err) = validate_indicator(self.indicator.as_ref()) {
+            return Some(err);
         }
 
         if self.source.is_empty() {
@@ -49,6 +60,20 @@ impl Validator for runner::FetchDataRequest {
     }
 }
 
+impl Validator for runner::TestFetchDataRequest {
+    fn validate_inner(&self) -> Option<&str> {
+        if let Some(err) = validate_indicator(self.indicator.as_ref()) {
+            return Some(err);
+        }
+
+        if self.source_code.is_empty() {
+            return Some("source_code is required");
+        }
+
+        None
+    }
+}
+
 impl Validator for runner::BackgroundTaskRequest {
     fn validate_inner(&self) -> Option<&str> {
         if self.source.is_empty() {
@@ -79,26 +104,66 @@ impl Validator for runner::InitRequest {
     }
 }
 
+/// Hex encoded SHA-256 of a source's code, identifying the version of the code held by a runner
+pub fn source_code_hash(source_code: &str) -> String {
+    Sha256::digest(source_code.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[derive(Debug)]
+struct SourceCode {
+    code: String,
+    hash: String,
+}
+
+impl TryFrom<UpdateRequest> for SourceCode {
+    type Error = Status;
+
+    fn try_from(update_request: UpdateRequest) -> Result<Self, Self::Error> {
+        let hash = source_code_hash(&update_request.source_code);
+
+        if !update_request.hash.is_empty() && update_request.hash != hash {
+            return Err(Status::invalid_argument(format!(
+                "hash of source {} does not match its code",
+                update_request.source
+            )));
+        }
+
+        Ok(Self {
+            code: update_request.source_code,
+            hash,
+        })
+    }
+}
+
 #[derive(Default, Debug)]
-pub struct SourceCodeMapping(Arc<Mutex<BTreeMap<String, String>>>);
+pub struct SourceCodeMapping(Arc<Mutex<BTreeMap<String, SourceCode>>>);
 
 impl SourceCodeMapping {
     fn insert(&self, update_request: UpdateRequest) -> Result<(), tonic::Status> {
+        let source = update_request.source.clone();
+        let source_code = update_request.try_into()?;
+
         self.0
             .lock()
             .map_err(|_| tonic::Status::internal("failed to lock source code mapping".to_string()))?
-            .insert(update_request.source, update_request.source_code);
+            .insert(source, source_code);
 
         Ok(())
     }
 
     fn bulk_insert(&self, data: Vec<UpdateRequest>) -> Result<(), tonic::Status> {
+        let data = data
+            .into_iter()
+            .map(|a| Ok((a.source.clone(), a.try_into()?)))
+            .collect::<Result<Vec<_>, Status>>()?;
+
         self.0
             .lock()
             .map_err(|_| tonic::Status::internal("failed to lock source code mapping".to_string()))?
-            .extend(BTreeMap::from_iter(
-                data.into_iter().map(|a| (a.source, a.source_code)),
-            ));
+            .extend(data);
 
         Ok(())
     }
@@ -110,9 +175,24 @@ impl SourceCodeMapping {
 
         binding
             .get(source)
-            .cloned()
+            .map(|source_code| source_code.code.clone())
             .ok_or_else(|| tonic::Status::not_found(format!("source {} not found", source)))
     }
+
+    fn list(&self) -> Result<Vec<SourceVersion>, tonic::Status> {
+        let binding = self.0.lock().map_err(|_| {
+            tonic::Status::internal("failed to lock source code mapping".to_string())
+        })?;
+
+        Ok(binding
+            .iter()
+            .map(|(source, source_code)| SourceVersion {
+                source: source.clone(),
+                hash: source_code.hash.clone(),
+                environment: None,
+            })
+            .collect())
+    }
 }
 
 pub fn handle_update(
@@ -149,8 +229,105 @@ pub fn handle_delete(
     Ok(Response::new(Empty {}))
 }
 
-impl From<String> for runner::FetchDataReply {
-    fn from(data: String) -> Self {
-        runner::FetchDataReply { data }
+pub fn handle_list_sources(
+    source_code: &SourceCodeMapping,
+) -> Result<Response<ListSourcesReply>, Status> {
+    Ok(Response::new(ListSourcesReply {
+        sources: source_code.list()?,
+    }))
+}
+
+/// Combines the replies of a streamed fetch into a single reply, the data of the partial replies
+/// being gathered in a JSON array. The data is left empty if any reply failed.
+pub fn merge_fetch_data_replies(
+    replies: impl IntoIterator<Item = FetchDataReply>,
+) -> FetchDataReply {
+    let mut merged = FetchDataReply::default();
+    let mut partials = Vec::new();
+
+    for reply in replies {
+        merged.logs.extend(reply.logs);
+
+        if reply.error.is_some() {
+            merged.error = reply.error;
+        } else if !reply.partial {
+            merged.data = reply.data;
+        } else if !reply.data.is_empty() {
+            partials.push(reply.data);
+        }
+    }
+
+    if merged.error.is_some() {
+        merged.data.clear();
+    } else if merged.data.is_empty() {
+        merged.data = format!("[{}]", partials.join(","));
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_partial_replies_when_merging_then_gathers_data_and_logs() {
+        let reply = |data: &str, log: &str| FetchDataReply {
+            data: data.to_string(),
+            logs: vec![Log {
+                level: LogLevel::Info as i32,
+                message: log.to_string(),
+            }],
+            partial: true,
+            ..Default::default()
+        };
+
+        let merged =
+            merge_fetch_data_replies([reply("{\"a\":1}", "a"), reply("2", "b"), reply("", "c")]);
+        assert_eq!("[{\"a\":1},2]", merged.data);
+        assert_eq!(3, merged.logs.len());
+        assert!(merged.error.is_none());
+
+        let merged = merge_fetch_data_replies([
+            reply("1", "a"),
+            FetchDataReply {
+                error: Some(FetchDataError {
+                    kind: FetchDataErrorKind::RateLimited as i32,
+                    message: "RateLimited: too many requests".to_string(),
+                    ..Default::default()
+                }),
+                partial: true,
+                ..Default::default()
+            },
+        ]);
+        assert!(merged.data.is_empty());
+        assert_eq!(
+            FetchDataErrorKind::RateLimited,
+            merged.error.unwrap().kind()
+        );
+
+        assert_eq!("[]", merge_fetch_data_replies([]).data);
+    }
+
+    #[test]
+    fn given_update_with_hash_when_inserting_then_rejects_mismatching_hash() {
+        let mapping = SourceCodeMapping::default();
+
+        let update = |source: &str, hash: &str| UpdateRequest {
+            source: source.to_string(),
+            source_code: "def fetch_data(): pass".to_string(),
+            hash: hash.to_string(),
+        };
+
+        assert!(mapping.insert(update("a", "invalid")).is_err());
+        mapping
+            .insert(update("a", &source_code_hash("def fetch_data(): pass")))
+            .unwrap();
+        mapping.insert(update("b", "")).unwrap();
+
+        let sources = mapping.list().unwrap();
+        assert_eq!(2, sources.len());
+        assert_eq!(sources[0].hash, sources[1].hash);
+        assert_eq!(source_code_hash("def fetch_data(): pass"), sources[0].hash);
     }
 }
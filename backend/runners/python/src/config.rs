@@ -0,0 +1,22 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Environments {
+    /// Directory containing the virtual environments of the sources
+    pub path: String,
+    /// Python executable creating the virtual environments, which should be the same version as
+    /// the one embedded in the runner
+    pub python: String,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct Config {
+    pub server: common::config::Server,
+    pub environments: Environments,
+}
+
+impl Config {
+    pub fn new() -> figment::error::Result<Self> {
+        common::config::load("PYTHON_RUNNER", include_str!("../config.toml"))
+    }
+}
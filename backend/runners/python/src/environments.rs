@@ -0,0 +1,312 @@
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use common::{Environment, EnvironmentStatus, Package};
+use figment::{
+    providers::{Format, Toml},
+    Figment,
+};
+use serde::{Deserialize, Serialize};
+use tokio::{process::Command, sync::watch};
+use tracing::{error, info, instrument};
+
+use crate::config;
+
+/// File written in an environment once its requirements are installed, listing the installed
+/// packages so that the environment is reused when the runner restarts
+const INSTALLED_FILE: &str = "installed.json";
+
+/// Maximum number of lines of the output of a failed command kept in the error
+const MAX_ERROR_LINES: usize = 20;
+
+/// Virtual environments containing the packages required by the sources, an environment being
+/// shared by the sources declaring the same requirements
+#[derive(Clone, Debug)]
+pub struct Environments {
+    config: config::Environments,
+    states: Arc<Mutex<HashMap<String, watch::Sender<State>>>>,
+}
+
+#[derive(Clone, Debug)]
+enum State {
+    Installing,
+    Ready(Installed),
+    Failed(String),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Installed {
+    site_packages: String,
+    packages: Vec<InstalledPackage>,
+}
+
+/// Package as listed by `pip list --format=json`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct InstalledPackage {
+    name: String,
+    version: String,
+}
+
+#[derive(Deserialize)]
+struct ScriptMetadata {
+    #[serde(default)]
+    dependencies: Vec<String>,
+}
+
+/// Requirements of a source, declared in its code through an inline script metadata block, such
+/// as:
+///
+/// ```python
+/// # /// script
+/// # dependencies = ["requests<3", "dnspython"]
+/// # ///
+/// ```
+pub fn requirements(source_code: &str) -> Result<Vec<String>, String> {
+    let mut lines = source_code
+        .lines()
+        .map(str::trim_end)
+        .skip_while(|line| *line != "# /// script");
+
+    if lines.next().is_none() {
+        return Ok(Vec::new());
+    }
+
+    let mut metadata = String::new();
+
+    for line in lines {
+        if line == "# ///" {
+            return Figment::from(Toml::string(&metadata))
+                .extract::<ScriptMetadata>()
+                .map(|metadata| metadata.dependencies)
+                .map_err(|err| format!("invalid script metadata: {err}"));
+        }
+
+        let content = match line.strip_prefix("# ") {
+            Some(content) => content,
+            None if line == "#" => "",
+            None => return Err(format!("invalid script metadata line {line:?}")),
+        };
+
+        metadata.push_str(content);
+        metadata.push('\n');
+    }
+
+    Err("unclosed script metadata block".to_string())
+}
+
+impl Environments {
+    pub fn new(config: config::Environments) -> Self {
+        Self {
+            config,
+            states: Default::default(),
+        }
+    }
+
+    /// Environment of a source, whose installation is started if it was not yet
+    pub fn environment(&self, source_code: &str) -> Option<Environment> {
+        let requirements = match requirements(source_code) {
+            Ok(requirements) if requirements.is_empty() => return None,
+            Ok(requirements) => requirements,
+            Err(err) => {
+                return Some(Environment {
+                    status: EnvironmentStatus::Failed as i32,
+                    error: err,
+                    ..Default::default()
+                })
+            }
+        };
+
+        let state = self.prepare(&requirements, false).borrow().clone();
+
+        Some(match state {
+            State::Installing => Environment {
+                requirements,
+                status: EnvironmentStatus::Installing as i32,
+                ..Default::default()
+            },
+            State::Ready(installed) => Environment {
+                requirements,
+                status: EnvironmentStatus::Ready as i32,
+                packages: installed
+                    .packages
+                    .into_iter()
+                    .map(|package| Package {
+                        name: package.name,
+                        version: package.version,
+                    })
+                    .collect(),
+                ..Default::default()
+            },
+            State::Failed(error) => Environment {
+                requirements,
+                status: EnvironmentStatus::Failed as i32,
+                error,
+                ..Default::default()
+            },
+        })
+    }
+
+    /// Directory of the packages installed for a source, waiting for them to be installed, if the
+    /// source declares requirements. The installation is retried if it previously failed.
+    pub async fn site_packages(&self, source_code: &str) -> Result<Option<String>, String> {
+        let requirements = requirements(source_code)?;
+
+        if requirements.is_empty() {
+            return Ok(None);
+        }
+
+        let mut state = self.prepare(&requirements, true).subscribe();
+        let state = state
+            .wait_for(|state| !matches!(state, State::Installing))
+            .await
+            .map_err(|err| err.to_string())?
+            .clone();
+
+        match state {
+            State::Ready(installed) => Ok(Some(installed.site_packages)),
+            State::Failed(err) => Err(format!("unable to install requirements: {err}")),
+            State::Installing => unreachable!(),
+        }
+    }
+
+    fn prepare(&self, requirements: &[String], retry_failed: bool) -> watch::Sender<State> {
+        let mut sorted = requirements.to_vec();
+        sorted.sort();
+        let key = common::source_code_hash(&sorted.join("\n"));
+
+        let mut states = self.states.lock().unwrap_or_else(|err| err.into_inner());
+
+        if let Some(state) = states
+            .get(&key)
+            .filter(|state| !retry_failed || !matches!(*state.borrow(), State::Failed(_)))
+        {
+            return state.clone();
+        }
+
+        let state = watch::Sender::new(State::Installing);
+        states.insert(key.clone(), state.clone());
+
+        let dir = Path::new(&self.config.path).join(key);
+        let python = self.config.python.clone();
+        let requirements = requirements.to_vec();
+        let sender = state.clone();
+
+        tokio::spawn(async move {
+            let result = match install(&python, &dir, &requirements).await {
+                Ok(installed) => State::Ready(installed),
+                Err(err) => {
+                    error!(?requirements, err, "unable to install requirements");
+                    State::Failed(err)
+                }
+            };
+
+            sender.send_replace(result);
+        });
+
+        state
+    }
+}
+
+#[instrument(skip(python))]
+async fn install(python: &str, dir: &Path, requirements: &[String]) -> Result<Installed, String> {
+    let installed_file = dir.join(INSTALLED_FILE);
+
+    if let Ok(content) = tokio::fs::read(&installed_file).await {
+        if let Ok(installed) = serde_json::from_slice(&content) {
+            return Ok(installed);
+        }
+    }
+
+    info!("installing requirements");
+
+    // Leftover of an interrupted installation
+    let _ = tokio::fs::remove_dir_all(dir).await;
+
+    run(Command::new(python).arg("-m").arg("venv").arg(dir)).await?;
+
+    let python = dir.join("bin").join("python");
+
+    // Requirements are passed after `--` so that they cannot be interpreted as pip options
+    run(Command::new(&python)
+        .args([
+            "-m",
+            "pip",
+            "install",
+            "--disable-pip-version-check",
+            "--no-input",
+            "--",
+        ])
+        .args(requirements))
+    .await?;
+
+    let site_packages = run(Command::new(&python).args([
+        "-c",
+        "import sysconfig; print(sysconfig.get_path('purelib'))",
+    ]))
+    .await?
+    .trim()
+    .to_string();
+
+    let packages = run(Command::new(&python).args([
+        "-m",
+        "pip",
+        "list",
+        "--format=json",
+        "--disable-pip-version-check",
+    ]))
+    .await?;
+
+    let installed = Installed {
+        site_packages,
+        packages: serde_json::from_str(&packages).map_err(|err| err.to_string())?,
+    };
+
+    let content = serde_json::to_vec(&installed).map_err(|err| err.to_string())?;
+    tokio::fs::write(&installed_file, content)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    Ok(installed)
+}
+
+async fn run(command: &mut Command) -> Result<String, String> {
+    let output = command
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|err| format!("unable to run {:?}: {err}", command.as_std()))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let lines = stderr.trim().lines().collect::<Vec<_>>();
+
+        return Err(format!(
+            "{:?} failed: {}",
+            command.as_std(),
+            lines[lines.len().saturating_sub(MAX_ERROR_LINES)..].join("\n")
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_script_metadata_when_parsing_requirements_then_returns_dependencies() {
+        let source_code = "# /// script\n# dependencies = [\n#   \"requests<3\",\n#   \"dnspython\",\n# ]\n# ///\n\nimport requests\n";
+
+        assert_eq!(
+            Ok(vec!["requests<3".to_string(), "dnspython".to_string()]),
+            requirements(source_code)
+        );
+        assert_eq!(Ok(Vec::new()), requirements("import json\n"));
+        assert!(requirements("# /// script\n# dependencies = [\n").is_err());
+        assert!(requirements("# /// script\ndependencies = []\n# ///\n").is_err());
+    }
+}
@@ -1,6 +1,17 @@
-use common::Indicator;
+use std::sync::{Mutex, PoisonError};
+
+use common::{FetchDataError, FetchDataErrorKind, FetchDataReply, Indicator, Log, LogLevel};
 use pyo3::{prelude::*, types::PyTuple};
 
+/// Name of the file of the source's code in tracebacks
+const SOURCE_FILE_NAME: &str = "<source>";
+
+const HELPERS_MODULE: &str = "indicator_aggregator";
+
+/// Packages of an environment are importable from the whole interpreter while a source using it
+/// runs, such sources being run one at a time
+static ENVIRONMENT_LOCK: Mutex<()> = Mutex::new(());
+
 fn execute_code<T, U>(
     source_code: &str,
     function: &str,
@@ -12,14 +23,175 @@ where
 {
     Python::with_gil(|py| {
         let code = PyModule::from_code_bound(py, source_code, "", "")?;
+        let helpers = py.import_bound(HELPERS_MODULE)?;
 
         let args = PyTuple::new_bound(py, args);
-        code.getattr(function)?.call1(args)?.extract()
+        let result = code.getattr(function)?.call1(args)?;
+        helpers.getattr("resolve")?.call1((result,))?.extract()
+    })
+}
+
+/// Registers the module of helpers that sources can import, containing the errors they can raise
+pub fn init() -> PyResult<()> {
+    Python::with_gil(|py| {
+        let helpers = PyModule::from_code_bound(
+            py,
+            include_str!("indicator_aggregator.py"),
+            "indicator_aggregator.py",
+            HELPERS_MODULE,
+        )?;
+
+        py.import_bound("sys")?
+            .getattr("modules")?
+            .set_item(HELPERS_MODULE, helpers)
     })
 }
 
-pub fn fetch_data(source_code: &str, indicator: Indicator) -> PyResult<String> {
-    execute_code(source_code, "fetch_data", [indicator.data, indicator.kind])
+/// Runs the `fetch_data` function of a source, sending a partial reply for every result yielded if
+/// it is a generator, or a single reply otherwise. Stops as soon as `send` returns false.
+///
+/// The packages installed in `site_packages` can be imported by the source while it runs.
+pub fn fetch_data(
+    source_code: &str,
+    indicator: Indicator,
+    site_packages: Option<&str>,
+    mut send: impl FnMut(FetchDataReply) -> bool,
+) {
+    // Acquired before the GIL, which is released while sources wait on I/O
+    let _guard = site_packages.map(|_| {
+        ENVIRONMENT_LOCK
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    });
+
+    let outcome = Python::with_gil(|py| {
+        let helpers = py.import_bound(HELPERS_MODULE)?;
+
+        let Some(site_packages) = site_packages else {
+            return run_fetch_data(py, &helpers, source_code, indicator, &mut send);
+        };
+
+        let modules = helpers
+            .getattr("enter_environment")?
+            .call1((site_packages,))?;
+        let outcome = run_fetch_data(py, &helpers, source_code, indicator, &mut send);
+        helpers
+            .getattr("exit_environment")?
+            .call1((site_packages, modules))?;
+
+        outcome
+    });
+
+    if let Err(err) = outcome {
+        send(FetchDataReply {
+            error: Some(FetchDataError {
+                kind: FetchDataErrorKind::Internal as i32,
+                message: err.to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+    }
+}
+
+fn run_fetch_data(
+    py: Python<'_>,
+    helpers: &Bound<'_, PyModule>,
+    source_code: &str,
+    indicator: Indicator,
+    send: &mut impl FnMut(FetchDataReply) -> bool,
+) -> PyResult<()> {
+    let logs = helpers.getattr("Logs")?.call0()?;
+
+    let drain_logs = || {
+        let records: Vec<(String, String)> = logs.call_method0("drain")?.extract()?;
+
+        Ok::<_, PyErr>(
+            records
+                .into_iter()
+                .map(|(level, message)| Log {
+                    level: LogLevel::from_str_name(&level).unwrap_or(LogLevel::Info) as i32,
+                    message,
+                })
+                .collect::<Vec<_>>(),
+        )
+    };
+
+    let reply = |data: PyResult<Bound<'_, PyAny>>, partial: bool| {
+        let data = data.and_then(|data| helpers.getattr("dumps")?.call1((data,))?.extract());
+
+        Ok::<_, PyErr>(match data {
+            Ok(data) => FetchDataReply {
+                data,
+                logs: drain_logs()?,
+                partial,
+                ..Default::default()
+            },
+            Err(err) => {
+                let kind: String = helpers
+                    .getattr("error_kind")?
+                    .call1((err.value_bound(py),))?
+                    .extract()?;
+
+                FetchDataReply {
+                    error: Some(FetchDataError {
+                        kind: FetchDataErrorKind::from_str_name(&kind)
+                            .unwrap_or(FetchDataErrorKind::Internal)
+                            as i32,
+                        message: err.to_string(),
+                        traceback: err
+                            .traceback_bound(py)
+                            .and_then(|traceback| traceback.format().ok())
+                            .unwrap_or_default(),
+                    }),
+                    logs: drain_logs()?,
+                    partial,
+                    ..Default::default()
+                }
+            }
+        })
+    };
+
+    let result =
+        PyModule::from_code_bound(py, source_code, SOURCE_FILE_NAME, "").and_then(|code| {
+            code.setattr("print", logs.getattr("print")?)?;
+            code.setattr("logger", &logs)?;
+            let result = code
+                .getattr("fetch_data")?
+                .call1((indicator.data, indicator.kind))?;
+            helpers.getattr("resolve")?.call1((result,))
+        });
+
+    let is_stream = match &result {
+        Ok(result) => helpers.getattr("is_stream")?.call1((result,))?.extract()?,
+        Err(_) => false,
+    };
+
+    if !is_stream {
+        send(reply(result, false)?);
+        return Ok(());
+    }
+
+    for item in helpers.getattr("iterate")?.call1((result?,))?.iter()? {
+        let failed = item.is_err();
+
+        if !send(reply(item, true)?) || failed {
+            return Ok(());
+        }
+    }
+
+    // Sends what was logged after the last result
+    let logs = drain_logs()?;
+
+    if !logs.is_empty() {
+        send(FetchDataReply {
+            logs,
+            partial: true,
+            ..Default::default()
+        });
+    }
+
+    Ok(())
 }
 
 pub fn background_task(source_code: &str) -> PyResult<String> {
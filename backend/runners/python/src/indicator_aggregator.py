@@ -0,0 +1,110 @@
+"""Helpers available to the sources' code through `import indicator_aggregator`"""
+
+import asyncio
+import inspect
+import json
+import sys
+
+
+class SourceError(Exception):
+    """Error raised by a source, reported as an internal error unless a subclass is raised"""
+
+    kind = "INTERNAL"
+
+
+class NotFound(SourceError):
+    """The indicator is unknown to the source"""
+
+    kind = "NOT_FOUND"
+
+
+class RateLimited(SourceError):
+    """The source's API rate limited the request"""
+
+    kind = "RATE_LIMITED"
+
+
+class Unauthorized(SourceError):
+    """The source's API rejected the credentials"""
+
+    kind = "UNAUTHORIZED"
+
+
+class Timeout(SourceError):
+    """The source's API did not respond in time"""
+
+    kind = "TIMEOUT"
+
+
+class Logs:
+    """Output of a source's code, replacing `print` and exposed as `logger` within the source"""
+
+    def __init__(self):
+        self.records = []
+
+    def print(self, *args, sep=" ", end="\n", file=None, flush=False):
+        level = "ERROR" if file is sys.stderr else "INFO"
+        self.records.append((level, sep.join(map(str, args))))
+
+    def info(self, message):
+        self.records.append(("INFO", str(message)))
+
+    def warning(self, message):
+        self.records.append(("WARNING", str(message)))
+
+    def error(self, message):
+        self.records.append(("ERROR", str(message)))
+
+    def drain(self):
+        records, self.records = self.records, []
+        return records
+
+
+def error_kind(error):
+    return error.kind if isinstance(error, SourceError) else "INTERNAL"
+
+
+def dumps(data):
+    return json.dumps(data, default=str)
+
+
+def resolve(result):
+    """Waits for the result of a coroutine, such as the one returned by an `async def` function"""
+    return asyncio.run(result) if inspect.iscoroutine(result) else result
+
+
+def is_stream(result):
+    return inspect.isgenerator(result) or inspect.isasyncgen(result)
+
+
+def iterate(result):
+    """Iterates over the results yielded by a generator or an asynchronous generator"""
+    if not inspect.isasyncgen(result):
+        yield from result
+        return
+
+    loop = asyncio.new_event_loop()
+
+    try:
+        while True:
+            try:
+                yield loop.run_until_complete(result.__anext__())
+            except StopAsyncIteration:
+                return
+    finally:
+        loop.run_until_complete(loop.shutdown_asyncgens())
+        loop.close()
+
+
+def enter_environment(site_packages):
+    """Makes the packages of a source's environment importable, returning the modules imported so far"""
+    sys.path.insert(0, site_packages)
+    return set(sys.modules)
+
+
+def exit_environment(site_packages, modules):
+    """Forgets the packages imported from a source's environment, which other sources may not share"""
+    sys.path.remove(site_packages)
+
+    for name in set(sys.modules) - modules:
+        del sys.modules[name]
@@ -1,5 +1,7 @@
-#![allow(clippy::blocks_in_conditions)]
+#![allow(clippy::blocks_in_conditions, clippy::result_large_err)]
 
+mod config;
+mod environments;
 mod executor;
 mod server;
 
@@ -1,17 +1,22 @@
 use common::{
-    handle_delete, handle_init, handle_update,
+    handle_delete, handle_init, handle_list_sources, handle_update, merge_fetch_data_replies,
     runner_server::{Runner, RunnerServer},
-    BackgroundTaskRequest, DeleteRequest, Empty, FetchDataReply, FetchDataRequest, InitRequest,
-    SourceCodeMapping, UpdateRequest, Validator,
+    BackgroundTaskRequest, DeleteRequest, Empty, FetchDataError, FetchDataErrorKind,
+    FetchDataReply, FetchDataRequest, InitRequest, ListSourcesReply, SourceCodeMapping,
+    TestFetchDataReply, TestFetchDataRequest, UpdateRequest, Validator,
 };
+use std::time::Instant;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
 use tonic::{transport::Server, Request, Response, Status};
 use tracing::{info, instrument};
 
-use crate::executor;
+use crate::{config::Config, environments::Environments, executor};
 
-#[derive(Default, Debug)]
+#[derive(Debug)]
 pub struct PythonRunner {
     source_code: SourceCodeMapping,
+    environments: Environments,
 }
 
 #[tonic::async_trait]
@@ -26,10 +31,107 @@ impl Runner for PythonRunner {
         let request_data = request.into_inner().validate()?;
         let source_code = self.source_code.get(request_data.source.as_str())?;
 
-        let data = executor::fetch_data(&source_code, request_data.indicator.unwrap())
-            .map_err(|e| Status::internal(format!("Error fetching data: {}", e)))?;
+        let site_packages = match self.site_packages(&source_code).await {
+            Ok(site_packages) => site_packages,
+            Err(reply) => return Ok(Response::new(reply)),
+        };
+
+        let replies = tokio::task::spawn_blocking(move || {
+            let mut replies = Vec::new();
+
+            executor::fetch_data(
+                &source_code,
+                request_data.indicator.unwrap(),
+                site_packages.as_deref(),
+                |reply| {
+                    replies.push(reply);
+                    true
+                },
+            );
+
+            replies
+        })
+        .await
+        .map_err(|e| Status::internal(format!("Error fetching data: {}", e)))?;
+
+        Ok(Response::new(merge_fetch_data_replies(replies)))
+    }
+
+    type FetchDataStreamStream = ReceiverStream<Result<FetchDataReply, Status>>;
+
+    #[instrument(err, skip_all, fields(request = ?request.get_ref()))]
+    async fn fetch_data_stream(
+        &self,
+        request: Request<FetchDataRequest>,
+    ) -> Result<Response<Self::FetchDataStreamStream>, Status> {
+        tracing::info!("received request");
 
-        Ok(Response::new(data.into()))
+        let request_data = request.into_inner().validate()?;
+        let source_code = self.source_code.get(request_data.source.as_str())?;
+
+        let (tx, rx) = mpsc::channel(16);
+
+        match self.site_packages(&source_code).await {
+            Ok(site_packages) => {
+                tokio::task::spawn_blocking(move || {
+                    executor::fetch_data(
+                        &source_code,
+                        request_data.indicator.unwrap(),
+                        site_packages.as_deref(),
+                        |reply| tx.blocking_send(Ok(reply)).is_ok(),
+                    );
+                });
+            }
+            Err(reply) => {
+                let _ = tx.send(Ok(reply)).await;
+            }
+        }
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    #[instrument(err, skip_all)]
+    async fn test_fetch_data(
+        &self,
+        request: Request<TestFetchDataRequest>,
+    ) -> Result<Response<TestFetchDataReply>, Status> {
+        tracing::info!("received request");
+
+        let request_data = request.into_inner().validate()?;
+
+        let site_packages = match self.site_packages(&request_data.source_code).await {
+            Ok(site_packages) => site_packages,
+            Err(reply) => {
+                return Ok(Response::new(TestFetchDataReply {
+                    reply: Some(reply),
+                    duration_ms: 0,
+                }))
+            }
+        };
+
+        let (replies, duration) = tokio::task::spawn_blocking(move || {
+            let started_at = Instant::now();
+            let mut replies = Vec::new();
+
+            executor::fetch_data(
+                &request_data.source_code,
+                request_data.indicator.unwrap(),
+                site_packages.as_deref(),
+                |reply| {
+                    replies.push(reply);
+                    true
+                },
+            );
+
+            (replies, started_at.elapsed())
+        })
+        .await
+        .map_err(|e| Status::internal(format!("Error fetching data: {}", e)))?;
+
+        Ok(Response::new(TestFetchDataReply {
+            reply: Some(merge_fetch_data_replies(replies)),
+            duration_ms: duration.as_millis() as u64,
+        }))
     }
 
     #[instrument(err, ret)]
@@ -50,27 +152,76 @@ impl Runner for PythonRunner {
 
     #[instrument(err, ret)]
     async fn update(&self, request: Request<UpdateRequest>) -> Result<Response<Empty>, Status> {
-        handle_update(&self.source_code, request)
+        let source_code = request.get_ref().source_code.clone();
+        let response = handle_update(&self.source_code, request)?;
+
+        self.environments.environment(&source_code);
+
+        Ok(response)
     }
 
     #[instrument(err, ret)]
     async fn init(&self, request: Request<InitRequest>) -> Result<Response<Empty>, Status> {
-        handle_init(&self.source_code, request)
+        let sources_code = request
+            .get_ref()
+            .updates
+            .iter()
+            .map(|update| update.source_code.clone())
+            .collect::<Vec<_>>();
+        let response = handle_init(&self.source_code, request)?;
+
+        for source_code in sources_code {
+            self.environments.environment(&source_code);
+        }
+
+        Ok(response)
     }
 
     #[instrument(err, ret)]
     async fn delete(&self, request: Request<DeleteRequest>) -> Result<Response<Empty>, Status> {
         handle_delete(&self.source_code, request)
     }
+
+    #[instrument(err, ret)]
+    async fn list_sources(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<ListSourcesReply>, Status> {
+        let mut reply = handle_list_sources(&self.source_code)?.into_inner();
+
+        for version in &mut reply.sources {
+            let source_code = self.source_code.get(&version.source)?;
+            version.environment = self.environments.environment(&source_code);
+        }
+
+        Ok(Response::new(reply))
+    }
 }
 
 impl PythonRunner {
+    /// Directory of the packages required by a source, installing them if needed, or the reply
+    /// explaining why they could not be installed
+    async fn site_packages(&self, source_code: &str) -> Result<Option<String>, FetchDataReply> {
+        self.environments
+            .site_packages(source_code)
+            .await
+            .map_err(|message| FetchDataReply {
+                error: Some(FetchDataError {
+                    kind: FetchDataErrorKind::Internal as i32,
+                    message,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+    }
+
     pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
         let (_, health_service) = common::health::server::health_reporter();
 
-        let config = common::config::Config::new("PYTHON_RUNNER", include_str!("../config.toml"))?;
+        let config = Config::new()?;
 
         pyo3::prepare_freethreaded_python();
+        executor::init()?;
 
         let addr = config.server.address()?;
 
@@ -79,7 +230,10 @@ impl PythonRunner {
         Server::builder()
             .trace_fn(|_| tracing::info_span!("python_runner"))
             .add_service(health_service)
-            .add_service(RunnerServer::new(PythonRunner::default()))
+            .add_service(RunnerServer::new(PythonRunner {
+                source_code: Default::default(),
+                environments: Environments::new(config.environments),
+            }))
             .serve(addr)
             .await?;
 
@@ -1,7 +1,14 @@
 #!/bin/bash
+set -e
 
-for _ in {1..8}; do
-  (cd ./database && sqlx migrate revert)
+# Reverts the applied migrations one at a time until none is left
+while true; do
+  output=$(cd ./database && sqlx migrate revert)
+  echo "$output"
+
+  if [[ $output == *"No migrations available to revert"* ]]; then
+    break
+  fi
 done
 
 (cd ./database && sqlx migrate run)
@@ -1,70 +1,681 @@
 use database::{
     self,
-    schemas::sources::{Source, SourceKind},
+    logic::{
+        advisory_locks::{self, AdvisoryLock},
+        task_runs,
+    },
+    schemas::{
+        feeds::FeedConfig,
+        sources::{Source, SourceKind},
+        task_runs::{TaskRunStatus, TaskRunTrigger},
+    },
 };
-use futures_util::future::join_all;
-use sources::{integrations, FetchState};
-use std::time::Duration;
-use tracing::{error, info, info_span, instrument, Instrument};
-
-use crate::{Result, ServerState};
-
-#[instrument(skip_all, name = "run_tasks", err)]
-pub async fn run_background_tasks(state: &ServerState) -> Result<()> {
-    let sources = database::logic::sources::get_sources(&state.pool).await?;
-
-    join_all(
-        sources
-            .into_iter()
-            .map(|source| prapare_background_task(state, source)),
-    )
-    .await
-    .into_iter()
-    .collect::<Result<_>>()?;
+use futures_util::FutureExt;
+use sources::{integrations, schemas::TaskSummary};
+use std::{collections::HashMap, panic::AssertUnwindSafe, str::FromStr, sync::Arc, time::Duration};
+use tokio::{
+    sync::{oneshot, Mutex, Notify, OwnedMutexGuard},
+    time::MissedTickBehavior,
+};
+use tracing::{error, info, info_span, instrument, warn, Instrument};
 
-    Ok(())
+use crate::{Error, Result, ServerState};
+
+/// When a source's background task is executed
+#[derive(Debug, Clone, PartialEq)]
+pub enum TaskSchedule {
+    /// Executed right away, then at a fixed interval
+    Interval(Duration),
+    /// Executed at the times matching the cron expression, in UTC
+    Cron(Box<cron::Schedule>),
 }
 
-#[instrument(skip_all, name = "prep_task", err)]
-async fn prapare_background_task(state: &ServerState, source: Source) -> Result<()> {
-    if !source.enabled || !source.task_enabled {
-        return Ok(());
+impl TaskSchedule {
+    /// Parses a cron expression, accepting the standard 5 fields format as well as the 6 and 7
+    /// fields formats including seconds and years
+    pub fn parse_cron(expression: &str) -> Result<Self> {
+        let expression = expression.trim();
+
+        let expression = if expression.split_whitespace().count() == 5 {
+            format!("0 {expression}")
+        } else {
+            expression.to_string()
+        };
+
+        cron::Schedule::from_str(&expression)
+            .map(|schedule| Self::Cron(Box::new(schedule)))
+            .map_err(|err| Error::BadRequest(format!("invalid cron expression: {err}")))
+    }
+
+    /// Schedule of the source's background task, if it should be running
+    pub fn from_source(source: &Source) -> Option<Self> {
+        if !source.enabled || !source.task_enabled {
+            return None;
+        }
+
+        if let Some(expression) = source.task_cron.as_deref().filter(|c| !c.is_empty()) {
+            return match Self::parse_cron(expression) {
+                Ok(schedule) => Some(schedule),
+                Err(err) => {
+                    warn!(
+                        source_id = source.id,
+                        ?err,
+                        "ignoring source background task"
+                    );
+                    None
+                }
+            };
+        }
+
+        // Feeds define how often they are refreshed, unless overridden by the source's task
+        let feed_interval = || {
+            (source.kind == SourceKind::Feed)
+                .then(|| FeedConfig::from_source_config(&source.config).ok())
+                .flatten()
+                .and_then(|config| config.refresh_interval)
+        };
+
+        source
+            .task_interval
+            .or_else(feed_interval)
+            .filter(|interval| *interval > 0)
+            .map(|interval| Self::Interval(Duration::from_secs(interval as u64)))
     }
 
-    if let Some(interval_secs) = source.task_interval {
-        let fetch_state = state.into_fetch_state(&source.id).await?;
+    fn ticker(&self) -> Ticker {
+        match self {
+            Self::Interval(period) => {
+                let mut interval = tokio::time::interval(*period);
+                interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+                Ticker::Interval(interval)
+            }
+            Self::Cron(schedule) => Ticker::Cron(schedule.clone()),
+        }
+    }
+}
 
-        let info_span = info_span!("background_task", id = %source.id, name = source.name);
+enum Ticker {
+    Interval(tokio::time::Interval),
+    Cron(Box<cron::Schedule>),
+}
+
+impl Ticker {
+    async fn tick(&mut self) {
+        match self {
+            Self::Interval(interval) => {
+                interval.tick().await;
+            }
+            Self::Cron(schedule) => match schedule.upcoming(chrono::Utc).next() {
+                Some(next) => {
+                    let delay = (next - chrono::Utc::now()).to_std().unwrap_or_default();
+                    tokio::time::sleep(delay).await;
+                }
+                None => std::future::pending().await,
+            },
+        }
+    }
+}
+
+/// What identifies a running task, if any of it changes the task is restarted
+#[derive(Debug, Clone, PartialEq)]
+struct TaskSpec {
+    name: String,
+    kind: SourceKind,
+    schedule: TaskSchedule,
+}
+
+struct ScheduledTask {
+    spec: TaskSpec,
+    /// Dropping the sender stops the task once its current execution, if any, is done
+    _stop: oneshot::Sender<()>,
+}
+
+/// Held while a source's task is executing, locally to prevent overlapping executions when a task
+/// is restarted and in the database to prevent executions on other server instances
+struct RunGuard {
+    _local: OwnedMutexGuard<()>,
+    lock: AdvisoryLock,
+}
+
+impl RunGuard {
+    async fn try_acquire(
+        pool: &database::PgPool,
+        source_id: &str,
+        run_lock: Arc<Mutex<()>>,
+    ) -> Result<Option<Self>> {
+        let Ok(local) = run_lock.try_lock_owned() else {
+            return Ok(None);
+        };
+
+        let lock =
+            AdvisoryLock::try_acquire(pool, &advisory_locks::task_run_lock_key(source_id)).await?;
+
+        Ok(lock.map(|lock| Self {
+            _local: local,
+            lock,
+        }))
+    }
+
+    async fn release(self) {
+        if let Err(err) = self.lock.release().await {
+            warn!(error = ?err, "unable to release task run lock");
+        }
+    }
+}
+
+#[derive(Default)]
+struct SchedulerInner {
+    notify: Notify,
+    tasks: Mutex<HashMap<String, ScheduledTask>>,
+    /// Held while a source's task is executing, shared between the successive tasks of a source
+    /// to prevent overlapping executions when a task is restarted
+    run_locks: std::sync::Mutex<HashMap<String, Arc<Mutex<()>>>>,
+}
+
+/// Keeps the sources' background tasks in sync with their configuration in the database
+#[derive(Clone, Default)]
+pub struct Scheduler {
+    inner: Arc<SchedulerInner>,
+}
+
+impl Scheduler {
+    /// Requests the tasks to be reconciled with the database, to be called when a source changes
+    pub fn reconcile(&self) {
+        self.inner.notify.notify_one();
+    }
+
+    /// Database IDs of the sources with a scheduled background task
+    pub async fn task_ids(&self) -> Vec<String> {
+        let mut ids: Vec<_> = self.inner.tasks.lock().await.keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    fn run_lock(&self, source_id: &str) -> Arc<Mutex<()>> {
+        self.inner
+            .run_locks
+            .lock()
+            .unwrap()
+            .entry(source_id.to_string())
+            .or_default()
+            .clone()
+    }
+
+    /// Executes the source's background task right away, returning the database ID of the run
+    #[instrument(skip(self, state), err)]
+    pub async fn trigger(
+        &self,
+        state: &ServerState,
+        source_id: &str,
+        user_id: &str,
+    ) -> Result<String> {
+        let source = database::logic::sources::get_source(&state.pool, source_id, None).await?;
+
+        if !source.enabled {
+            return Err(Error::BadRequest("source is disabled".to_string()));
+        }
+
+        let integration =
+            integrations::source(&source.name, &source.kind).ok_or(Error::MissingSourceCode)?;
+
+        if !integration.has_background_task() {
+            return Err(sources::Error::MissingBackgroundTask.into());
+        }
+
+        let guard = RunGuard::try_acquire(&state.pool, source_id, self.run_lock(source_id))
+            .await?
+            .ok_or_else(|| Error::Conflict("task is already running".to_string()))?;
+
+        let run_id = task_runs::create_task_run(
+            &state.pool,
+            source_id,
+            TaskRunTrigger::Manual,
+            Some(user_id),
+        )
+        .await?;
+
+        let state = state.clone();
+        let source_id = source_id.to_string();
+        let task_run_id = run_id.clone();
+        let info_span = info_span!("background_task", id = %source_id, name = source.name);
 
         tokio::task::spawn(
             async move {
-                run_background_task(source.name, source.kind, fetch_state, interval_secs).await
+                let _ = execute_task(
+                    &state,
+                    &source_id,
+                    &task_run_id,
+                    integration.as_ref(),
+                    guard,
+                )
+                .await;
             }
             .instrument(info_span),
         );
+
+        Ok(run_id)
     }
 
-    Ok(())
+    /// Starts, restarts and stops tasks to match the sources in the database
+    #[instrument(skip_all, name = "reconcile_tasks", err)]
+    pub async fn sync(&self, state: &ServerState) -> Result<()> {
+        let sources = database::logic::sources::get_sources(&state.pool, None).await?;
+
+        let desired: HashMap<_, _> = sources
+            .iter()
+            // A source's integration depends on its kind, which can change to one without a task
+            .filter(|source| {
+                integrations::source(&source.name, &source.kind)
+                    .is_some_and(|integration| integration.has_background_task())
+            })
+            .filter_map(|source| {
+                TaskSchedule::from_source(source).map(|schedule| {
+                    let spec = TaskSpec {
+                        name: source.name.clone(),
+                        kind: source.kind.clone(),
+                        schedule,
+                    };
+
+                    (source.id.clone(), spec)
+                })
+            })
+            .collect();
+
+        let mut tasks = self.inner.tasks.lock().await;
+
+        tasks.retain(|source_id, task| {
+            let keep = desired.get(source_id) == Some(&task.spec);
+            if !keep {
+                info!(source_id, "stopping task");
+            }
+            keep
+        });
+
+        for (source_id, spec) in desired {
+            if tasks.contains_key(&source_id) {
+                continue;
+            }
+
+            let (stop, stopped) = oneshot::channel();
+            let run_lock = self.run_lock(&source_id);
+
+            let info_span = info_span!("background_task", id = %source_id, name = spec.name);
+            tokio::task::spawn(
+                run_background_task(
+                    state.clone(),
+                    source_id.clone(),
+                    spec.clone(),
+                    run_lock,
+                    stopped,
+                )
+                .instrument(info_span),
+            );
+
+            tasks.insert(source_id, ScheduledTask { spec, _stop: stop });
+        }
+
+        self.inner
+            .run_locks
+            .lock()
+            .unwrap()
+            .retain(|source_id, _| sources.iter().any(|source| &source.id == source_id));
+
+        Ok(())
+    }
+
+    /// Stops all tasks, letting their current execution, if any, finish
+    async fn stop_all(&self) {
+        let mut tasks = self.inner.tasks.lock().await;
+
+        if !tasks.is_empty() {
+            info!(num_tasks = tasks.len(), "stopping all tasks");
+            tasks.clear();
+        }
+    }
+
+    /// Renews the lease of the instance scheduling the tasks if this instance holds it, otherwise
+    /// tries to take it over, returning the lease if this instance holds it afterwards
+    #[instrument(skip_all)]
+    async fn elect(
+        &self,
+        pool: &database::PgPool,
+        lease: Option<AdvisoryLock>,
+    ) -> Option<AdvisoryLock> {
+        if let Some(mut lease) = lease {
+            match lease.renew().await {
+                Ok(()) => return Some(lease),
+                Err(err) => {
+                    warn!(error = ?err, "lost task scheduling lease, stepping down");
+                    self.stop_all().await;
+                }
+            }
+        }
+
+        match AdvisoryLock::try_acquire(pool, advisory_locks::TASK_SCHEDULER_LOCK_KEY).await {
+            Ok(Some(lease)) => {
+                info!("acquired task scheduling lease, scheduling tasks on this instance");
+                Some(lease)
+            }
+            Ok(None) => None,
+            Err(err) => {
+                error!(error = ?err, "unable to acquire task scheduling lease");
+                None
+            }
+        }
+    }
+}
+
+/// Schedules the sources' background tasks on a single server instance, the one holding the
+/// scheduling lease, the other instances taking it over if that instance stops or is unreachable.
+/// Never returns, it is meant to run alongside the server.
+#[instrument(skip_all, name = "run_tasks")]
+pub async fn run_background_tasks(state: &ServerState) {
+    let scheduler = state.scheduler.clone();
+    let renewal_interval = Duration::from_secs(state.config.server.tasks.lease_renewal_interval);
+
+    let mut lease = None;
+
+    loop {
+        lease = scheduler.elect(&state.pool, lease).await;
+
+        if lease.is_some() {
+            // Changes made through another instance's API or directly in the database are only
+            // picked up here, every renewal of the lease
+            let _ = scheduler.sync(state).await;
+            let _ = task_runs::fail_interrupted_task_runs(&state.pool).await;
+        }
+
+        tokio::select! {
+            _ = scheduler.inner.notify.notified() => {},
+            _ = tokio::time::sleep(renewal_interval) => {},
+        }
+    }
 }
 
 #[instrument(skip_all, name = "task")]
 async fn run_background_task(
-    source_name: String,
-    source_kind: SourceKind,
-    fetch_state: FetchState,
-    interval_secs: i32,
+    state: ServerState,
+    source_id: String,
+    spec: TaskSpec,
+    run_lock: Arc<Mutex<()>>,
+    mut stopped: oneshot::Receiver<()>,
 ) {
-    info!(interval_secs, "starting task");
+    info!(schedule = ?spec.schedule, "starting task");
+
+    let Some(integration) = integrations::source(&spec.name, &spec.kind) else {
+        error!("source has no integration, not starting task");
+        return;
+    };
 
-    let mut interval = tokio::time::interval(Duration::from_secs(interval_secs as u64));
-    let integration = integrations::source(&source_name, &source_kind).unwrap();
+    let mut ticker = spec.schedule.ticker();
 
     loop {
-        interval.tick().await;
-        if let Err(err) = integration.background_task(&fetch_state).await {
-            error!(error = ?err, "tick failed");
+        tokio::select! {
+            _ = &mut stopped => break,
+            _ = ticker.tick() => {},
         }
 
+        let guard = match RunGuard::try_acquire(&state.pool, &source_id, run_lock.clone()).await {
+            Ok(Some(guard)) => guard,
+            Ok(None) => {
+                warn!("previous execution still running, skipping tick");
+                continue;
+            }
+            Err(err) => {
+                error!(error = ?err, "unable to lock task run, skipping tick");
+                continue;
+            }
+        };
+
+        let run_id = match task_runs::create_task_run(
+            &state.pool,
+            &source_id,
+            TaskRunTrigger::Scheduled,
+            None,
+        )
+        .await
+        {
+            Ok(run_id) => run_id,
+            Err(err) => {
+                error!(error = ?err, "unable to record task run, skipping tick");
+                guard.release().await;
+                continue;
+            }
+        };
+
+        let _ = execute_task(&state, &source_id, &run_id, integration.as_ref(), guard).await;
+
         info!("tick finished, pausing task");
     }
+
+    info!("task stopped");
+}
+
+/// Executes the source's background task once and records its outcome, holding the source's run
+/// lock for the whole execution
+#[instrument(skip(state, integration, guard), err)]
+async fn execute_task(
+    state: &ServerState,
+    source_id: &str,
+    run_id: &str,
+    integration: &dyn sources::Source,
+    guard: RunGuard,
+) -> Result<()> {
+    let result = record_task(state, source_id, run_id, integration).await;
+
+    guard.release().await;
+
+    result
+}
+
+async fn record_task(
+    state: &ServerState,
+    source_id: &str,
+    run_id: &str,
+    integration: &dyn sources::Source,
+) -> Result<()> {
+    // Fetched on every execution to use the latest secrets
+    let result: Result<TaskSummary> = match state.into_fetch_state(source_id).await {
+        Ok(fetch_state) => AssertUnwindSafe(integration.background_task(&fetch_state))
+            .catch_unwind()
+            .await
+            .unwrap_or(Err(sources::Error::InternalError))
+            .map_err(Into::into),
+        Err(err) => Err(err),
+    };
+
+    match result {
+        Ok(summary) => {
+            task_runs::finish_task_run(
+                &state.pool,
+                run_id,
+                TaskRunStatus::Succeeded,
+                None,
+                summary.records_ingested,
+                summary.changes,
+            )
+            .await?;
+        }
+        Err(err) => {
+            error!(error = ?err, "tick failed");
+
+            task_runs::finish_task_run(
+                &state.pool,
+                run_id,
+                TaskRunStatus::Failed,
+                Some(&format!("{err:?}")),
+                None,
+                None,
+            )
+            .await?;
+
+            warn_if_failing(state, source_id).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Logs when the task reaches the number of consecutive failures after which a notification is
+/// shown to users
+async fn warn_if_failing(state: &ServerState, source_id: &str) -> Result<()> {
+    let threshold =
+        *database::logic::server_config::get_config_with_defaults_and_db_results(&state.pool)
+            .await?
+            .task_failure_notification_threshold
+            .get_value();
+
+    let num_consecutive_failures =
+        task_runs::get_consecutive_task_failures(&state.pool, source_id).await?;
+
+    if num_consecutive_failures == threshold as i32 {
+        error!(num_consecutive_failures, "task keeps failing");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{state::test::create_state, test_utils::*};
+
+    #[test]
+    fn given_cron_expressions_when_parsing_then_accepts_standard_and_extended_formats() {
+        assert!(TaskSchedule::parse_cron("*/5 * * * *").is_ok());
+        assert!(TaskSchedule::parse_cron("0 */5 * * * *").is_ok());
+        assert!(TaskSchedule::parse_cron("0 0 1 1 * * 2100").is_ok());
+        assert!(TaskSchedule::parse_cron("every minute").is_err());
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_source_task_changes_when_syncing_then_tasks_are_reconciled(pool: PgPool) {
+        sqlx::query!("UPDATE sources SET task_enabled = FALSE")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let source_id = sqlx::query_scalar!(
+            "UPDATE sources SET task_enabled = TRUE, task_cron = '0 0 1 1 *' WHERE slug = 'urlhaus' RETURNING id"
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        let state = create_state(pool.clone());
+
+        state.scheduler.sync(&state).await.unwrap();
+        assert_eq!(vec![source_id.clone()], state.scheduler.task_ids().await);
+
+        sqlx::query!(
+            "UPDATE sources SET enabled = FALSE WHERE id = $1",
+            source_id
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        state.scheduler.sync(&state).await.unwrap();
+        assert!(state.scheduler.task_ids().await.is_empty());
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_sources_without_background_task_when_syncing_then_no_task_is_started(
+        pool: PgPool,
+    ) {
+        sqlx::query!("UPDATE sources SET task_enabled = FALSE")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query!(
+            "UPDATE sources SET task_enabled = TRUE, task_interval = 3600 WHERE slug = 'dns'"
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let feed_id = sqlx::query_scalar!(
+            "INSERT INTO sources (name, slug, description, url, kind, task_enabled, task_interval, created_user_id)
+            SELECT 'Feed', 'feed', '', '', 'feed', TRUE, 3600, created_user_id FROM sources WHERE slug = 'dns'
+            RETURNING id"
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        let state = create_state(pool.clone());
+
+        state.scheduler.sync(&state).await.unwrap();
+        assert_eq!(vec![feed_id.clone()], state.scheduler.task_ids().await);
+
+        sqlx::query!("UPDATE sources SET kind = 'http' WHERE id = $1", feed_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        state.scheduler.sync(&state).await.unwrap();
+        assert!(state.scheduler.task_ids().await.is_empty());
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_two_instances_when_electing_then_only_one_schedules_until_it_steps_down(
+        pool: PgPool,
+    ) {
+        let first = Scheduler::default();
+        let second = Scheduler::default();
+
+        let lease = first.elect(&pool, None).await;
+        assert!(lease.is_some());
+        assert!(second.elect(&pool, None).await.is_none());
+
+        let lease = first.elect(&pool, lease).await;
+        assert!(lease.is_some());
+        assert!(second.elect(&pool, None).await.is_none());
+
+        lease.unwrap().release().await.unwrap();
+        assert!(second.elect(&pool, None).await.is_some());
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_running_task_runs_when_failing_interrupted_runs_then_only_unlocked_runs_fail(
+        pool: PgPool,
+    ) {
+        let source_ids: Vec<String> = sqlx::query_scalar!("SELECT id FROM sources LIMIT 2")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+
+        let mut run_ids = Vec::new();
+        for source_id in &source_ids {
+            run_ids.push(
+                task_runs::create_task_run(&pool, source_id, TaskRunTrigger::Scheduled, None)
+                    .await
+                    .unwrap(),
+            );
+        }
+
+        let lock =
+            AdvisoryLock::try_acquire(&pool, &advisory_locks::task_run_lock_key(&source_ids[0]))
+                .await
+                .unwrap()
+                .unwrap();
+
+        assert_eq!(
+            1,
+            task_runs::fail_interrupted_task_runs(&pool).await.unwrap()
+        );
+
+        let statuses: Vec<String> = sqlx::query_scalar!(
+            r#"SELECT status::TEXT as "status!" FROM task_runs WHERE id = ANY($1) ORDER BY array_position($1, id)"#,
+            &run_ids
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(vec!["running", "failed"], statuses);
+
+        lock.release().await.unwrap();
+    }
 }
@@ -1,8 +1,10 @@
 use auth::config::Auth;
+use cache::{InMemoryCacheConfig, PostgresCacheConfig};
 use figment::{
     providers::{Env, Format, Toml},
     Figment,
 };
+use mail::SmtpConfig;
 use serde::Deserialize;
 use std::net::{AddrParseError, SocketAddr};
 use tracing::info;
@@ -40,9 +42,41 @@ impl ServerInstance {
     }
 }
 
+#[derive(Deserialize, Clone)]
+pub struct Tasks {
+    /// Interval in seconds at which the instance scheduling the background tasks confirms it
+    /// still is, and at which the other instances try to take over
+    pub lease_renewal_interval: u64,
+}
+
+impl Default for Tasks {
+    fn default() -> Self {
+        Self {
+            lease_renewal_interval: 15,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct Runners {
+    /// Interval in seconds at which the sources' code held by each runner instance is compared
+    /// with the database and re-synchronized if it drifted
+    pub sync_interval: u64,
+}
+
+impl Default for Runners {
+    fn default() -> Self {
+        Self { sync_interval: 60 }
+    }
+}
+
 #[derive(Deserialize, Clone)]
 pub struct Server {
     pub http: ServerInstance,
+    #[serde(default)]
+    pub tasks: Tasks,
+    #[serde(default)]
+    pub runners: Runners,
 }
 
 #[derive(Deserialize, Clone)]
@@ -51,9 +85,53 @@ pub struct Encryption {
     pub server_key: String,
 }
 
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheBackend {
+    Memory,
+    Redis,
+    Postgres,
+    /// In memory cache in front of the Postgres cache
+    Tiered,
+}
+
 #[derive(Deserialize, Clone)]
 pub struct Cache {
+    /// Defaults to Redis if a Redis URL is set, otherwise to the in memory cache
+    pub backend: Option<CacheBackend>,
     pub redis_url: Option<String>,
+    pub redis_key_prefix: Option<String>,
+    #[serde(default)]
+    pub memory: InMemoryCacheConfig,
+    #[serde(default)]
+    pub postgres: PostgresCacheConfig,
+}
+
+impl Cache {
+    pub fn backend(&self) -> CacheBackend {
+        self.backend.unwrap_or(if self.redis_url.is_some() {
+            CacheBackend::Redis
+        } else {
+            CacheBackend::Memory
+        })
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct Mail {
+    /// Address the emails are sent from
+    pub from: String,
+    /// Emails are only logged when no SMTP server is configured
+    pub smtp: Option<SmtpConfig>,
+}
+
+impl Default for Mail {
+    fn default() -> Self {
+        Self {
+            from: "Indicator Aggregator <noreply@localhost>".to_string(),
+            smtp: None,
+        }
+    }
 }
 
 #[derive(Deserialize, Clone)]
@@ -63,6 +141,8 @@ pub struct Config {
     pub encryption: Encryption,
     pub cache: Cache,
     pub auth: Auth,
+    #[serde(default)]
+    pub mail: Mail,
 }
 
 impl Config {
@@ -78,7 +158,7 @@ impl Config {
 
 #[cfg(test)]
 pub mod test {
-    use auth::config::{Jwt, OpenId};
+    use auth::config::Jwt;
 
     use super::*;
 
@@ -97,36 +177,38 @@ pub mod test {
                     host: "localhost".to_string(),
                     port: 8080,
                 },
+                tasks: Default::default(),
+                runners: Default::default(),
             },
             encryption: Encryption {
                 db_key: "tqQxsKu7z2CWMGuWJ@HAXF_oRHtsyFHR7HHEmNhmyNw4gCzqiez.T4hbN2jLH.LrVJbtAecRyb3J9Ke6!qjK4RzY_NqgFBEN*C6h".to_string(),
                 server_key: "AsuKXKermWGsAPo3e9XnEHMzt*7svap#".to_string(), 
             },
-            cache: Cache { redis_url: None },
+            cache: Cache {
+                backend: None,
+                redis_url: None,
+                redis_key_prefix: None,
+                memory: Default::default(),
+                postgres: Default::default(),
+            },
             auth: Auth {
                 frontend_redirect_path: "/auth".to_string(),
                 frontend_redirect_hosts: vec!["localhost".to_string()],
+                admin_emails: Vec::new(),
                 jwt: Jwt {
-                    secret: "tqQxsKu7z2".to_string(),
                     issuer: "test".to_string(),
                     audience: "test".to_string(),
-                    expiration: 3600,
+                    expiration: 900,
+                    algorithm: Default::default(),
+                    refresh_expiration: 24 * 60 * 60,
+                    key_rotation_interval: 24 * 60 * 60,
                 },
-                google: OpenId {
-                    enabled: false,
-                    redirect_uri: "http://localhost:8080/auth/google/callback".to_string(),
-                    openid_url: "https://accounts.google.com".to_string(),
-                    client_id: "test".to_string(),
-                    client_secret: "test".to_string(),
-                },
-                microsoft: OpenId {
-                    enabled: false,
-                    redirect_uri: "http://localhost:8080/auth/microsoft/callback".to_string(),
-                    openid_url: "https://login.microsoftonline.com".to_string(),
-                    client_id: "test".to_string(),
-                    client_secret: "test".to_string(),
-                },
-            }
+                openid: Default::default(),
+                password: Default::default(),
+                mfa: Default::default(),
+                login_throttling: Default::default(),
+            },
+            mail: Default::default(),
         }
     }
 }
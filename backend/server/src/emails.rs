@@ -0,0 +1,78 @@
+use auth::password::create_user_token;
+use database::schemas::auth::UserTokenKind;
+use mail::{Email, Mailer};
+
+use crate::{Result, ServerState};
+
+/// Recipient of an email sent to a user of a local account
+pub struct Recipient<'a> {
+    pub user_id: &'a str,
+    pub name: &'a str,
+    pub email: &'a str,
+}
+
+/// Sends a link to the frontend, at `origin`, verifying the user's email
+pub async fn send_email_verification(
+    state: &ServerState,
+    recipient: &Recipient<'_>,
+    origin: &str,
+) -> Result<()> {
+    let token = create_user_token(
+        &state.pool,
+        recipient.user_id,
+        UserTokenKind::EmailVerification,
+        state.config.auth.password.email_verification_expiration,
+    )
+    .await?;
+
+    let email = Email {
+        to: recipient.email.to_string(),
+        subject: "Verify your Indicator Aggregator email".to_string(),
+        body: format!(
+            "Hello {},\n\nVerify your email by opening the following link:\n\n{origin}/auth/verify?token={token}\n\nIf you did not create an account, you can ignore this email.",
+            recipient.name
+        ),
+    };
+
+    state.mailer.send(&email).await.map_err(Into::into)
+}
+
+/// Sends a link to the frontend, at `origin`, resetting the user's password
+pub async fn send_password_reset(
+    state: &ServerState,
+    recipient: &Recipient<'_>,
+    origin: &str,
+) -> Result<()> {
+    let token = create_user_token(
+        &state.pool,
+        recipient.user_id,
+        UserTokenKind::PasswordReset,
+        state.config.auth.password.password_reset_expiration,
+    )
+    .await?;
+
+    let email = Email {
+        to: recipient.email.to_string(),
+        subject: "Reset your Indicator Aggregator password".to_string(),
+        body: format!(
+            "Hello {},\n\nReset your password by opening the following link:\n\n{origin}/auth/reset-password?token={token}\n\nIf you did not request a password reset, you can ignore this email.",
+            recipient.name
+        ),
+    };
+
+    state.mailer.send(&email).await.map_err(Into::into)
+}
+
+/// Notifies the user that an admin approved their account
+pub async fn send_account_approved(state: &ServerState, recipient: &Recipient<'_>) -> Result<()> {
+    let email = Email {
+        to: recipient.email.to_string(),
+        subject: "Your Indicator Aggregator account was approved".to_string(),
+        body: format!(
+            "Hello {},\n\nYour account was approved, you can now login to Indicator Aggregator.",
+            recipient.name
+        ),
+    };
+
+    state.mailer.send(&email).await.map_err(Into::into)
+}
@@ -1,5 +1,5 @@
 use axum::{
-    http::StatusCode,
+    http::{header, StatusCode},
     response::{IntoResponse, Response},
 };
 use database::schemas::indicators::IndicatorKind;
@@ -38,7 +38,13 @@ pub enum Error {
     PasswordHash(shared::crypto::PasswordHashError),
     InvalidCredentials,
     DisabledUser,
+    UnverifiedUser,
+    WeakPassword(String),
+    Mail(mail::MailError),
     Forbidden,
+    /// Login refused because of too many failed ones, with the number of seconds until it is
+    /// allowed again
+    LockedOut(u64),
 }
 
 impl std::error::Error for Error {}
@@ -103,6 +109,12 @@ impl From<shared::crypto::PasswordHashError> for Error {
     }
 }
 
+impl From<mail::MailError> for Error {
+    fn from(error: mail::MailError) -> Self {
+        Self::Mail(error)
+    }
+}
+
 impl From<auth::error::Error> for Error {
     fn from(error: auth::error::Error) -> Self {
         error!(error=?error);
@@ -111,13 +123,20 @@ impl From<auth::error::Error> for Error {
         match error {
             auth::error::Error::Database(err) => Self::SqlxError(err),
             auth::error::Error::Unauthorized(_) => Self::Unauthorized,
-            auth::error::Error::MissingRoles(_) => Self::Forbidden,
+            auth::error::Error::WeakPassword(err) => Self::WeakPassword(err),
+            auth::error::Error::MissingPermission(_) => Self::Forbidden,
+            auth::error::Error::OutOfScope(_) => Self::Forbidden,
+            auth::error::Error::OutOfTeam(_) => Self::Forbidden,
             auth::error::Error::BadRequest(err) => Self::BadRequest(err),
             auth::error::Error::SerdeJson(_) => Self::InternalError,
             auth::error::Error::Reqwest(err) => Self::Reqwest(err),
             auth::error::Error::Jsonwebtoken(_) => Self::InternalError,
             auth::error::Error::PasswordHash(err) => Self::PasswordHash(err),
+            auth::error::Error::Encryption(err) => Self::EncryptionError(err),
+            auth::error::Error::SigningKey(_) => Self::InternalError,
             auth::error::Error::NotProperlySetup => Self::NotFound,
+            auth::error::Error::LockedOut(seconds) => Self::LockedOut(seconds),
+            auth::error::Error::Cache(err) => Self::CacheError(err),
         }
     }
 }
@@ -151,8 +170,18 @@ impl IntoResponse for Error {
             Self::DisabledUser => {
                 (StatusCode::UNAUTHORIZED, "Your account is disabled").into_response()
             }
+            Self::UnverifiedUser => {
+                (StatusCode::UNAUTHORIZED, "Your email is not verified").into_response()
+            }
+            Self::WeakPassword(err) => (StatusCode::BAD_REQUEST, err).into_response(),
             Self::NotFound => StatusCode::NOT_FOUND.into_response(),
             Self::Forbidden => StatusCode::FORBIDDEN.into_response(),
+            Self::LockedOut(seconds) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(header::RETRY_AFTER, seconds.to_string())],
+                "Too many failed logins, try again later",
+            )
+                .into_response(),
             _ => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
         }
     }
@@ -167,14 +196,19 @@ impl From<sources::Error> for Error {
             sources::Error::ResponseError => Self::ResponseError,
             sources::Error::Timeout => Self::Timeout,
             sources::Error::InternalError => Self::InternalError,
+            sources::Error::InvalidConfig(err) => Self::InvalidConfig(err),
             sources::Error::IoError(err) => Self::IoError(err),
             sources::Error::MissingSourceCode => Self::MissingSourceCode,
+            sources::Error::MissingBackgroundTask => {
+                Self::BadRequest("source has no background task".to_string())
+            }
             sources::Error::TonicTransportError(err) => Self::TonicTransportError(err),
             sources::Error::TonicStatus(err) => Self::TonicStatus(err),
             sources::Error::Postgres(err) => Self::SqlxError(err),
             sources::Error::Reqwest(err) => Self::Reqwest(err),
             sources::Error::InvalidHeaderValue(err) => Self::InvalidHeaderValue(err),
             sources::Error::ZipError(err) => Self::ZipError(err),
+            sources::Error::SerdeJson(_) => Self::ResponseError,
         }
     }
 }
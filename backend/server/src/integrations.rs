@@ -1,9 +1,9 @@
-use cache::Cache;
+use cache::{Cache, CacheEntry, CacheKey};
 use database::{
     logic::server_config::get_config_with_defaults_and_db_results,
     schemas::{
         indicators::{Indicator, IndicatorKind},
-        sources::{InternalRequest, SourceKind},
+        sources::{CacheInvalidationReason, InternalRequest, SourceKind},
     },
     PgPool,
 };
@@ -28,7 +28,30 @@ pub async fn handle_indicator_request(
         return Err(Error::InvalidIndicatorKind(indicator.kind));
     }
 
-    get_source_data(&indicator, state, &request.source_ids, user_id).await
+    get_source_data(
+        &indicator,
+        state,
+        &request.source_ids,
+        user_id,
+        request.team_id.as_deref(),
+    )
+    .await
+}
+
+/// Ensures that the config of a source is valid for its kind, only feed and HTTP sources being
+/// configured through it
+pub fn validate_source_config(kind: &SourceKind, config: &[serde_json::Value]) -> Result<()> {
+    match kind {
+        SourceKind::Feed => {
+            integrations::parse_feed_config(config).map_err(Error::BadRequest)?;
+        }
+        SourceKind::Http => {
+            integrations::parse_http_config(config).map_err(Error::BadRequest)?;
+        }
+        _ => {}
+    }
+
+    Ok(())
 }
 
 async fn get_source_data(
@@ -36,46 +59,57 @@ async fn get_source_data(
     state: &ServerState,
     source_ids: &[String],
     user_id: &str,
+    team_id: Option<&str>,
 ) -> Result<Vec<Data>> {
     let (sources, request_id) = join(
         database::logic::sources::get_sources_for_internal_request(
             &state.pool,
             indicator,
             source_ids,
+            team_id,
         ),
-        database::logic::requests::create_request(&state.pool, indicator, user_id),
+        database::logic::requests::create_request(&state.pool, indicator, user_id, team_id),
     )
     .await;
 
     let sources = sources?;
     let request_id = request_id?;
 
+    let cache_lookups = get_cached_data(state, indicator, &sources).await;
+
     let source_integrations = sources
         .iter()
-        .map(|source| {
+        .zip(cache_lookups)
+        .map(|(source, cache_lookup)| {
             (
                 integrations::source(&source.source_name, &source.source_kind),
                 source,
+                cache_lookup,
             )
         })
         .collect::<Vec<_>>();
 
-    let data = join_all(
-        source_integrations
-            .into_iter()
-            .map(|(integration, source)| {
-                let request_id = request_id.clone();
-
-                async move {
-                    if let Some(integration) = integration {
-                        get_indicator(indicator, source, state, request_id, integration.as_ref())
-                            .await
-                    } else {
-                        Err(Error::MissingSourceCode)
-                    }
+    let data = join_all(source_integrations.into_iter().map(
+        |(integration, source, cache_lookup)| {
+            let request_id = request_id.clone();
+
+            async move {
+                if let Some(integration) = integration {
+                    get_indicator(
+                        indicator,
+                        source,
+                        state,
+                        request_id,
+                        integration.as_ref(),
+                        cache_lookup,
+                    )
+                    .await
+                } else {
+                    Err(Error::MissingSourceCode)
                 }
-            }),
-    )
+            }
+        },
+    ))
     .await
     .into_iter()
     .zip(sources.iter())
@@ -111,16 +145,73 @@ async fn get_source_data(
     Ok(data)
 }
 
+/// Result of looking up the cached data of a source before fetching its data
+pub enum CacheLookup {
+    /// The cache has not been looked up yet
+    Pending,
+    /// The cache has already been looked up, containing the entry if there was one
+    Done(Option<CacheEntry<serde_json::Value>>),
+}
+
+fn cache_key(source: &InternalRequest, indicator: &Indicator) -> CacheKey {
+    CacheKey::from(vec![
+        source.source_id.clone(),
+        indicator.kind.to_string(),
+        indicator.data.clone(),
+    ])
+}
+
+/// Looks up the cached data of all sources in a single batch, falling back to looking them up
+/// individually if the batch fails
+async fn get_cached_data(
+    state: &ServerState,
+    indicator: &Indicator,
+    sources: &[InternalRequest],
+) -> Vec<CacheLookup> {
+    let keys = sources
+        .iter()
+        .filter(|source| source.source_cache_enabled)
+        .map(|source| cache_key(source, indicator))
+        .collect::<Vec<_>>();
+
+    let entries = match state.cache.clone().get_many(keys).await {
+        Ok(entries) => entries,
+        Err(error) => {
+            warn!(?error, "failed to get cached data in batch");
+            return sources.iter().map(|_| CacheLookup::Pending).collect();
+        }
+    };
+
+    let mut entries = entries.into_iter();
+
+    sources
+        .iter()
+        .map(|source| {
+            if source.source_cache_enabled {
+                CacheLookup::Done(entries.next().flatten())
+            } else {
+                CacheLookup::Done(None)
+            }
+        })
+        .collect()
+}
+
 async fn get_data<S: Source + ?Sized>(
     source: &InternalRequest,
     state: &mut ServerState,
     indicator: &Indicator,
     source_impl: &S,
+    cache_lookup: CacheLookup,
 ) -> Result<(Option<serde_json::Value>, DataCache)> {
-    let cache_key = format!("{}:{}:{}", source.source_id, indicator.kind, indicator.data);
+    let cache_key = cache_key(source, indicator);
 
     if source.source_cache_enabled {
-        if let Some(data) = state.cache.get(cache_key.clone()).await? {
+        let cached_data = match cache_lookup {
+            CacheLookup::Pending => state.cache.get(cache_key.clone()).await?,
+            CacheLookup::Done(entry) => entry,
+        };
+
+        if let Some(data) = cached_data {
             return Ok((
                 Some(data.value),
                 DataCache::new(
@@ -129,7 +220,7 @@ async fn get_data<S: Source + ?Sized>(
                     data.expiration
                         .map(|e| e as i64)
                         .map(|e| data.timestamp + chrono::Duration::seconds(e)),
-                    Some(cache_key),
+                    Some(String::from(&cache_key)),
                 ),
             ));
         }
@@ -154,7 +245,7 @@ async fn get_data<S: Source + ?Sized>(
             data_cache.cached_at = Some(set_cache_data.timestamp);
             data_cache.expires_at =
                 Some(set_cache_data.timestamp + chrono::Duration::seconds(interval as i64));
-            data_cache.cache_key = Some(cache_key);
+            data_cache.cache_key = Some(String::from(&cache_key));
         } else {
             warn!(
                 "source {} has cache enabled but no cache interval set",
@@ -166,6 +257,33 @@ async fn get_data<S: Source + ?Sized>(
     Ok((Some(data), data_cache))
 }
 
+/// Removes all cached data of a source, since cache keys are prefixed by the source ID, and
+/// records the invalidation in the source's history
+#[instrument(skip(state), ret, err)]
+pub async fn invalidate_source_cache(
+    state: &ServerState,
+    source_id: &str,
+    reason: CacheInvalidationReason,
+    user_id: &str,
+) -> Result<u64> {
+    let num_entries = state
+        .cache
+        .clone()
+        .partial_invalidate(source_id.to_string())
+        .await?;
+
+    database::logic::sources::create_source_cache_invalidation(
+        &state.pool,
+        source_id,
+        reason,
+        num_entries as i32,
+        user_id,
+    )
+    .await?;
+
+    Ok(num_entries)
+}
+
 async fn get_source_errors(
     source: &InternalRequest,
     indicator_kind: &IndicatorKind,
@@ -188,7 +306,7 @@ async fn get_source_errors(
         ));
     }
 
-    if source.source_kind != SourceKind::System {
+    if source.source_kind.runs_on_runner() {
         let server_config = get_config_with_defaults_and_db_results(pool).await?;
 
         if !server_config.runner_enabled(&source.source_kind) {
@@ -222,13 +340,21 @@ pub async fn get_indicator<S: Source + ?Sized>(
     state: &ServerState,
     request_id: String,
     source_impl: &S,
+    cache_lookup: CacheLookup,
 ) -> Result<Data> {
     let started_at = chrono::Utc::now().naive_utc();
 
     let errors = get_source_errors(source, &indicator.kind, &state.pool).await?;
 
     let (data, cache) = if errors.is_empty() {
-        get_data(source, &mut state.clone(), indicator, source_impl).await?
+        get_data(
+            source,
+            &mut state.clone(),
+            indicator,
+            source_impl,
+            cache_lookup,
+        )
+        .await?
     } else {
         (None, Default::default())
     };
@@ -1,7 +1,8 @@
-#![allow(clippy::blocks_in_conditions)]
+#![allow(clippy::blocks_in_conditions, clippy::result_large_err)]
 
 mod background_tasks;
 mod config;
+mod emails;
 mod error;
 mod integrations;
 mod routes;
@@ -12,11 +13,11 @@ mod state;
 #[cfg(test)]
 mod test_utils;
 
-use database::schemas::sources::SourceKind;
 pub use error::{Error, Result};
 pub use state::ServerState;
 
-const ENV_FILTER: &str = "server=debug,cache=debug,tower_http=debug,shared=debug,database=debug";
+const ENV_FILTER: &str =
+    "server=debug,cache=debug,mail=debug,tower_http=debug,shared=debug,database=debug";
 const SERVICE_NAME: &str = "indicator-aggregator-server";
 
 fn main() {
@@ -38,16 +39,11 @@ async fn start() -> std::result::Result<(), Box<dyn std::error::Error>> {
 
     database::run_migrations(&state.pool).await?;
 
-    let (runners_init, background_tasks, servers) = futures_util::future::join3(
-        runners::send_init_request(&state.pool, SourceKind::Python),
-        background_tasks::run_background_tasks(&state),
-        routes::server::RestServer::new(state.clone()).start(),
-    )
-    .await;
+    runners::connect_runners(&state.pool, &state.runners).await?;
 
-    runners_init?;
-    background_tasks?;
-    servers?;
-
-    Ok(())
+    // The scheduler never returns, the process exits as soon as the server stops or fails to start
+    tokio::select! {
+        _ = background_tasks::run_background_tasks(&state) => Ok(()),
+        servers = routes::server::RestServer::new(state.clone()).start() => servers,
+    }
 }
@@ -1,3 +1,4 @@
+use auth::scope::{require_unscoped, validate_ip_ranges};
 use axum::{
     extract::{Path, State},
     http::StatusCode,
@@ -19,6 +20,8 @@ use crate::Result;
     tag = "apiTokens",
     responses(
         (status = 204, description = "API token updated successfully"),
+        (status = 400, description = "Invalid IP address or CIDR range"),
+        (status = 403, description = "Authenticated with a restricted API token"),
         (status = 404, description = "API token not found"),
     ),
     request_body(
@@ -34,6 +37,12 @@ pub async fn update_api_tokens(
     Path(id): Path<String>,
     Json(data): Json<UpdateApiToken>,
 ) -> Result<impl IntoResponse> {
+    require_unscoped(&user)?;
+
+    if let Some(ranges) = &data.allowed_ip_ranges {
+        validate_ip_ranges(ranges)?;
+    }
+
     let num_affected = api_tokens::update_api_token(&pool, &id, &data.into(), &user.id).await?;
 
     if num_affected > 0 {
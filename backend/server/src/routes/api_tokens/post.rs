@@ -1,3 +1,4 @@
+use auth::scope::{require_unscoped, validate_ip_ranges};
 use axum::{
     extract::{Path, State},
     response::IntoResponse,
@@ -22,7 +23,9 @@ use crate::{schemas::CreatedApiToken, Result};
     path = "/apiTokens",
     tag = "apiTokens",
     responses(
-        (status = 200, description = "Database ID of the API token", body = CreatedApiToken)
+        (status = 200, description = "Database ID of the API token", body = CreatedApiToken),
+        (status = 400, description = "Invalid IP address or CIDR range"),
+        (status = 403, description = "Authenticated with a restricted API token")
     ),
     request_body(
         description = "API token to create", content_type = "application/json", content = CreateApiToken
@@ -34,6 +37,12 @@ pub async fn create_api_tokens(
     Extension(user): Extension<User>,
     Json(data): Json<CreateApiToken>,
 ) -> Result<impl IntoResponse> {
+    require_unscoped(&user)?;
+
+    if let Some(ranges) = &data.allowed_ip_ranges {
+        validate_ip_ranges(ranges)?;
+    }
+
     let token = crypto.generate_random_alphanumeric_string(32);
     let hashed_token = hash_password(&token)?;
 
@@ -49,6 +58,7 @@ pub async fn create_api_tokens(
     tag = "apiTokens",
     responses(
         (status = 200, description = "Token was generated and returned value is API token", body = String),
+        (status = 403, description = "Authenticated with a restricted API token"),
         (status = 404, description = "Token not found or not linked to user")
     ),
     params(
@@ -61,6 +71,8 @@ pub async fn regenerate_api_tokens(
     Extension(user): Extension<User>,
     Path(id): Path<String>,
 ) -> Result<impl IntoResponse> {
+    require_unscoped(&user)?;
+
     let token = crypto.generate_random_alphanumeric_string(32);
     let hashed_token = hash_password(&token)?;
 
@@ -81,3 +93,140 @@ pub async fn regenerate_api_tokens(
         Ok(StatusCode::NOT_FOUND.into_response())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, USER_AGENT};
+
+    use crate::{state::test::create_state, test_utils::*};
+
+    /// Creates an API token as the admin test user and returns its value
+    async fn create_token(pool: &PgPool, body: Value) -> String {
+        let response = json_request(Method::POST, "/api/v1/apiTokens", pool.clone(), body).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let created = json_response::<Value>(response).await;
+        format!(
+            "{}_{}",
+            created["id"].as_str().unwrap(),
+            created["token"].as_str().unwrap()
+        )
+    }
+
+    async fn get_with_token(pool: PgPool, uri: &str, token: &str) -> http::Response<Body> {
+        request_with_token(pool, Method::GET, uri, token, Body::empty()).await
+    }
+
+    async fn request_with_token(
+        pool: PgPool,
+        method: Method,
+        uri: &str,
+        token: &str,
+        body: Body,
+    ) -> http::Response<Body> {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(AUTHORIZATION, format!("Token {token}"))
+            .header(USER_AGENT, TEST_USER_AGENT)
+            .header(CONTENT_TYPE, "application/json")
+            .body(body)
+            .unwrap();
+
+        router_with_state(create_state(pool))
+            .oneshot(request)
+            .await
+            .unwrap()
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_token_restricted_to_permissions_when_using_it_then_denies_other_permissions(
+        pool: PgPool,
+    ) {
+        let token = create_token(
+            &pool,
+            json!({ "note": "soar", "permissions": ["request_view"] }),
+        )
+        .await;
+
+        let response = get_with_token(pool.clone(), "/api/v1/requests", &token).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = get_with_token(pool, "/api/v1/sources", &token).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_token_restricted_to_ip_ranges_when_using_it_from_elsewhere_then_returns_401_error(
+        pool: PgPool,
+    ) {
+        let token = create_token(
+            &pool,
+            json!({ "note": "siem", "allowedIpRanges": ["10.0.0.0/8"] }),
+        )
+        .await;
+
+        let response = get_with_token(pool, "/api/v1/requests", &token).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_invalid_ip_range_when_creating_token_then_returns_400_error(pool: PgPool) {
+        let response = json_request(
+            Method::POST,
+            "/api/v1/apiTokens",
+            pool,
+            json!({ "note": "siem", "allowedIpRanges": ["localhost"] }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_restricted_token_when_managing_tokens_then_returns_403_error(pool: PgPool) {
+        let token = create_token(
+            &pool,
+            json!({ "note": "soar", "permissions": ["request_view"] }),
+        )
+        .await;
+        let id = token.split('_').next().unwrap().to_string();
+
+        let response = request_with_token(
+            pool.clone(),
+            Method::POST,
+            "/api/v1/apiTokens",
+            &token,
+            Body::from(json!({ "note": "unrestricted" }).to_string()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = request_with_token(
+            pool.clone(),
+            Method::PATCH,
+            &format!("/api/v1/apiTokens/{id}"),
+            &token,
+            Body::from(json!({ "permissions": null }).to_string()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = request_with_token(
+            pool.clone(),
+            Method::POST,
+            &format!("/api/v1/apiTokens/{id}/regenerate"),
+            &token,
+            Body::empty(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // The restrictions of the token are left as is
+        let response = get_with_token(pool, "/api/v1/sources", &token).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+}
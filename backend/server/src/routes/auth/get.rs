@@ -1,9 +1,8 @@
 use axum::{extract::State, response::IntoResponse, Json};
 
 use crate::{
-    config::Config,
     schemas::{AuthService, AuthServiceKind},
-    Result,
+    Result, ServerState,
 };
 
 /// Get all enabled auth services
@@ -15,23 +14,23 @@ use crate::{
         (status = 200, description = "List of enabled auth services successfully", body = [AuthService]),
     ),
 )]
-pub async fn get_enabled_auth(State(config): State<Config>) -> Result<impl IntoResponse> {
-    Ok(Json([
-        AuthService {
-            enabled: config.auth.google.enabled,
-            kind: AuthServiceKind::OpenId {
-                name: "google".to_string(),
-            },
-        },
-        AuthService {
-            enabled: config.auth.microsoft.enabled,
+pub async fn get_enabled_auth(State(state): State<ServerState>) -> Result<impl IntoResponse> {
+    let mut services = Vec::new();
+
+    for (name, open_id) in &state.config.auth.openid {
+        services.push(AuthService {
+            enabled: state.auth_state.provider(name).await.is_ok(),
             kind: AuthServiceKind::OpenId {
-                name: "microsoft".to_string(),
+                name: name.clone(),
+                label: open_id.name.clone(),
             },
-        },
-        AuthService {
-            enabled: true,
-            kind: AuthServiceKind::Password,
-        },
-    ]))
+        });
+    }
+
+    services.push(AuthService {
+        enabled: true,
+        kind: AuthServiceKind::Password,
+    });
+
+    Ok(Json(services))
 }
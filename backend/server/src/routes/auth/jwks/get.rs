@@ -0,0 +1,42 @@
+use axum::{extract::State, response::IntoResponse, Json};
+
+use crate::{Result, ServerState};
+
+/// Get the public keys verifying the JWTs, as a JSON Web Key Set
+#[utoipa::path(
+    get,
+    path = "/auth/jwks",
+    tag = "auth",
+    responses(
+        (status = 200, description = "JSON Web Key Set", body = serde_json::Value),
+    ),
+)]
+pub async fn get_jwks(State(state): State<ServerState>) -> Result<impl IntoResponse> {
+    let jwt_manager = &state.auth_state.jwt_manager;
+
+    // Keys are only loaded once a JWT is signed or verified
+    if jwt_manager.jwks().keys.is_empty() {
+        jwt_manager.sync_keys().await?;
+    }
+
+    Ok(Json(jwt_manager.jwks()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::*;
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_no_key_when_getting_jwks_then_generates_signing_key(pool: PgPool) {
+        let response = request(Method::GET, "/api/v1/auth/jwks", pool).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let jwks = json_response::<Value>(response).await;
+        let keys = jwks["keys"].as_array().unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0]["kty"], "OKP");
+        assert_eq!(keys[0]["alg"], "EdDSA");
+        assert_eq!(keys[0]["use"], "sig");
+    }
+}
@@ -0,0 +1,306 @@
+use auth::{
+    mfa,
+    openid::parse_referer_origin,
+    password::{consume_user_token, record_user_token_failure, validate_user_token},
+    throttling::LoginAttempt,
+    webauthn::RelyingParty,
+};
+use axum::{
+    extract::{ConnectInfo, OriginalUri, State},
+    http::HeaderMap,
+    response::IntoResponse,
+    Json,
+};
+use axum_extra::{headers::UserAgent, TypedHeader};
+use database::{
+    logic::{mfa as mfa_logic, users},
+    schemas::auth::UserTokenKind,
+};
+use std::net::SocketAddr;
+
+use crate::{
+    routes::auth::login::post::create_login_session,
+    schemas::{LoginUserResponse, MfaLoginRequest, MfaTokenRequest},
+    Error, Result, ServerState,
+};
+
+/// Complete a login with a second factor, enrolling TOTP if it is required and the user had none
+#[utoipa::path(
+    post,
+    path = "/auth/login/mfa",
+    tag = "auth",
+    responses(
+        (status = 200, description = "User logged in successfully", body = LoginUserResponse),
+        (status = 400, description = "No second factor provided"),
+        (status = 401, description = "Invalid or expired token or invalid second factor, the token being used up after too many invalid second factors"),
+        (status = 429, description = "Too many invalid second factors for the account, retry after the number of seconds of the `Retry-After` header"),
+    ),
+    request_body(
+        description = "Token of the second factor challenge and one second factor",
+        content_type = "application/json",
+        content = MfaLoginRequest
+    )
+)]
+pub async fn login_mfa(
+    State(state): State<ServerState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    TypedHeader(user_agent): TypedHeader<UserAgent>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+    Json(data): Json<MfaLoginRequest>,
+) -> Result<impl IntoResponse> {
+    let user_id =
+        validate_user_token(&state.pool, &data.token, UserTokenKind::MfaChallenge).await?;
+
+    let Some(user) = users::get_user_with_password(&state.pool, &user_id).await? else {
+        return Err(Error::InvalidCredentials);
+    };
+
+    let attempt = LoginAttempt {
+        email: &user.email,
+        addr,
+        user_agent: user_agent.to_string(),
+        uri: uri.to_string(),
+        method: "POST".to_string(),
+    };
+    let throttle = &state.auth_state.login_throttle;
+    throttle.check_mfa(&attempt).await?;
+
+    let has_mfa = mfa_logic::user_has_mfa(&state.pool, &user_id).await?;
+
+    let mut recovery_codes = None;
+
+    let valid = if let Some(code) = &data.totp_code {
+        if has_mfa {
+            mfa::verify_totp(&state.pool, &state.crypto, &user_id, code).await?
+        } else if mfa::confirm_totp(&state.pool, &state.crypto, &user_id, code).await? {
+            recovery_codes = Some(mfa::ensure_recovery_codes(&state.pool, &user_id).await?);
+            true
+        } else {
+            false
+        }
+    } else if let Some(code) = &data.recovery_code {
+        has_mfa && mfa::use_recovery_code(&state.pool, &user_id, code).await?
+    } else if let Some(assertion) = &data.webauthn {
+        let origin = parse_referer_origin(&headers, &state.config.auth)?;
+        let relying_party = RelyingParty::new(&origin, &state.config.auth.mfa.issuer)?;
+
+        if relying_party.client_challenge(&assertion.client_data_json, "webauthn.get")?
+            != data.token
+        {
+            return Err(Error::InvalidCredentials);
+        }
+
+        let Some(credential) = mfa_logic::get_user_webauthn_credential(
+            &state.pool,
+            &user_id,
+            &assertion.credential_id,
+        )
+        .await?
+        else {
+            return Err(Error::InvalidCredentials);
+        };
+
+        let sign_count = relying_party.verify_assertion(&credential, assertion)?;
+        mfa_logic::use_webauthn_credential(&state.pool, &credential.id, sign_count.into()).await?;
+
+        true
+    } else {
+        return Err(Error::BadRequest("No second factor provided".to_string()));
+    };
+
+    if !valid {
+        // Codes being short, they could otherwise be guessed with enough attempts
+        record_user_token_failure(
+            &state.pool,
+            &data.token,
+            state.config.auth.mfa.max_challenge_attempts,
+        )
+        .await?;
+        throttle.record_mfa_failure(&state.pool, &attempt).await?;
+
+        return Err(Error::InvalidCredentials);
+    }
+
+    consume_user_token(&state.pool, &data.token, UserTokenKind::MfaChallenge).await?;
+    throttle.record_mfa_success(&attempt).await?;
+
+    if !user.enabled {
+        return Err(Error::DisabledUser);
+    }
+
+    let tokens = create_login_session(&state, user, &addr, &user_agent).await?;
+
+    Ok(Json(LoginUserResponse {
+        jwt_token: Some(tokens.jwt_token),
+        refresh_token: Some(tokens.refresh_token),
+        mfa_challenge: None,
+        recovery_codes,
+    }))
+}
+
+/// Start the TOTP enrollment of a user needing a second factor to login but having none
+#[utoipa::path(
+    post,
+    path = "/auth/login/mfa/totp",
+    tag = "auth",
+    responses(
+        (status = 200, description = "TOTP secret to add to an authenticator app", body = TotpSetup),
+        (status = 400, description = "User already enrolled a second factor"),
+        (status = 401, description = "Invalid or expired token"),
+    ),
+    request_body(
+        description = "Token of the second factor challenge",
+        content_type = "application/json",
+        content = MfaTokenRequest
+    )
+)]
+pub async fn setup_login_totp(
+    State(state): State<ServerState>,
+    Json(data): Json<MfaTokenRequest>,
+) -> Result<impl IntoResponse> {
+    let user_id =
+        validate_user_token(&state.pool, &data.token, UserTokenKind::MfaChallenge).await?;
+
+    // Otherwise stealing the password would be enough to replace the second factor
+    if mfa_logic::user_has_mfa(&state.pool, &user_id).await? {
+        return Err(Error::BadRequest(
+            "Second factor already enrolled".to_string(),
+        ));
+    }
+
+    let Some(user) = users::get_user_with_password(&state.pool, &user_id).await? else {
+        return Err(Error::InvalidCredentials);
+    };
+
+    let setup = mfa::setup_totp(
+        &state.pool,
+        &state.crypto,
+        &state.config.auth.mfa.issuer,
+        &user.id,
+        &user.email,
+    )
+    .await?;
+
+    Ok(Json(setup))
+}
+
+#[cfg(test)]
+mod tests {
+    use auth::{
+        config::LoginThrottling, mfa::generate_recovery_codes, password::create_user_token,
+        throttling::LoginThrottle,
+    };
+    use axum::http::header::{CONTENT_TYPE, USER_AGENT};
+
+    use super::*;
+    use crate::{state::test::create_state, test_utils::*};
+
+    fn create_throttled_state(
+        pool: PgPool,
+        max_challenge_attempts: u32,
+        max_account_attempts: u32,
+    ) -> ServerState {
+        let mut state = create_state(pool);
+        state.config.auth.mfa.max_challenge_attempts = max_challenge_attempts;
+        state.auth_state.login_throttle = LoginThrottle::postgres(
+            LoginThrottling {
+                max_account_attempts,
+                base_delay: 0,
+                ..Default::default()
+            },
+            state.pool.clone(),
+        );
+
+        state
+    }
+
+    /// Enrolls the test user in TOTP, returning their ID and a recovery code
+    async fn enroll_test_user(pool: &PgPool) -> (String, String) {
+        let response = request(Method::GET, "/api/v1/health", pool.clone()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let user = users::get_user_from_email(pool, "test@test.test")
+            .await
+            .unwrap()
+            .unwrap();
+        sqlx::query!(
+            "INSERT INTO user_totp (user_id, secret, enabled) VALUES ($1, '', TRUE)",
+            user.id
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+        let codes = generate_recovery_codes(pool, &user.id).await.unwrap();
+
+        (user.id, codes[0].clone())
+    }
+
+    async fn create_challenge(pool: &PgPool, user_id: &str) -> String {
+        create_user_token(pool, user_id, UserTokenKind::MfaChallenge, 300)
+            .await
+            .unwrap()
+    }
+
+    async fn login_mfa(state: &ServerState, token: &str, recovery_code: &str) -> StatusCode {
+        let body = json!({ "token": token, "recoveryCode": recovery_code });
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/api/v1/auth/login/mfa")
+            .header(CONTENT_TYPE, "application/json")
+            .header(USER_AGENT, TEST_USER_AGENT)
+            .body(Body::from(serde_json::to_vec(&body).unwrap()))
+            .unwrap();
+
+        router_with_state(state.clone())
+            .oneshot(request)
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_too_many_invalid_codes_when_completing_login_then_challenge_is_used_up(
+        pool: PgPool,
+    ) {
+        let (user_id, recovery_code) = enroll_test_user(&pool).await;
+        let state = create_throttled_state(pool.clone(), 3, 100);
+
+        let token = create_challenge(&pool, &user_id).await;
+        for _ in 0..3 {
+            let status = login_mfa(&state, &token, "wrong-code").await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+        }
+
+        let status = login_mfa(&state, &token, &recovery_code).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let token = create_challenge(&pool, &user_id).await;
+        let status = login_mfa(&state, &token, &recovery_code).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_invalid_codes_across_challenges_when_completing_login_then_locks_account(
+        pool: PgPool,
+    ) {
+        let (user_id, recovery_code) = enroll_test_user(&pool).await;
+        let state = create_throttled_state(pool.clone(), 100, 2);
+
+        for _ in 0..2 {
+            // A new login, and so a new challenge, does not forget the invalid codes
+            let token = create_challenge(&pool, &user_id).await;
+            let status = login_mfa(&state, &token, "wrong-code").await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+        }
+
+        let token = create_challenge(&pool, &user_id).await;
+        let status = login_mfa(&state, &token, &recovery_code).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+
+        let logs = users::get_user_logs(&pool, &user_id).await.unwrap();
+        assert!(logs.iter().any(|log| log.event.is_some()));
+    }
+}
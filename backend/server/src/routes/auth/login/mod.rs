@@ -4,6 +4,10 @@ use crate::ServerState;
 
 pub mod post;
 
+pub mod mfa;
+
 pub fn router() -> Router<ServerState> {
-    Router::new().route("/", post(post::login))
+    Router::new()
+        .route("/", post(post::login))
+        .nest("/mfa", mfa::router())
 }
@@ -1,4 +1,10 @@
-use auth::openid::{parse_and_validate_referer, CreateUserClaims};
+use auth::{
+    openid::{parse_referer_origin, CreateUserClaims},
+    password::{create_user_token, verify_user_password},
+    sessions::{create_session, SessionTokens},
+    throttling::LoginAttempt,
+    webauthn::RelyingParty,
+};
 use axum::{
     extract::{ConnectInfo, OriginalUri, State},
     http::HeaderMap,
@@ -6,23 +12,141 @@ use axum::{
     Json,
 };
 use axum_extra::{headers::UserAgent, TypedHeader};
-use database::{logic::users, schemas::users::UserLog};
-use shared::crypto::verify_password;
-use std::net::SocketAddr;
+use database::{
+    logic::{mfa, server_config, users},
+    schemas::{
+        auth::UserTokenKind,
+        users::{Role, UpdateUser, UserLog, UserWithPassword},
+    },
+};
+use std::{net::SocketAddr, str::FromStr};
 
 use crate::{
     config::AUTH_PROVIDER,
-    schemas::{LoginUserRequest, LoginUserResponse},
+    schemas::{LoginUserRequest, LoginUserResponse, MfaChallenge, MfaMethod},
     Error, Result, ServerState,
 };
 
-/// Login to authenticate and generate a new JWT
+/// Starts the session of a user whose login is completed
+pub async fn create_login_session(
+    state: &ServerState,
+    user: UserWithPassword,
+    addr: &SocketAddr,
+    user_agent: &UserAgent,
+) -> Result<SessionTokens> {
+    let claims = CreateUserClaims {
+        sub: user.id.clone(),
+        email: user.email,
+        email_verified: Some(user.verified),
+        name: user.name,
+        given_name: user.given_name,
+        family_name: user.family_name,
+        picture: None,
+        locale: user.locale,
+        provider: AUTH_PROVIDER.to_string(),
+    };
+
+    Ok(create_session(
+        &state.pool,
+        &state.auth_state.jwt_manager,
+        claims,
+        user.roles,
+        user.id,
+        &addr.to_string(),
+        &user_agent.to_string(),
+    )
+    .await?)
+}
+
+/// Makes the user an enabled admin if their verified email is one of the configured admin emails
+async fn grant_configured_admin(
+    state: &ServerState,
+    mut user: UserWithPassword,
+) -> Result<UserWithPassword> {
+    let admin = Role::Admin.to_string();
+
+    if !user.verified
+        || !state.config.auth.is_admin_email(&user.email)
+        || (user.enabled && user.roles.contains(&admin))
+    {
+        return Ok(user);
+    }
+
+    if !user.roles.contains(&admin) {
+        user.roles.push(admin);
+    }
+    user.enabled = true;
+
+    let update_user = UpdateUser {
+        enabled: Some(true),
+        roles: Some(
+            user.roles
+                .iter()
+                .filter_map(|role| Role::from_str(role).ok())
+                .collect(),
+        ),
+        owner_user_id: None,
+    };
+    users::update_user(&state.pool, &user.id, &update_user, &user.id).await?;
+
+    Ok(user)
+}
+
+/// Starts the second factor step of a login, the user needing to enroll one if they have none
+async fn create_mfa_challenge(
+    state: &ServerState,
+    user_id: &str,
+    has_mfa: bool,
+    origin: &str,
+) -> Result<MfaChallenge> {
+    let token = create_user_token(
+        &state.pool,
+        user_id,
+        UserTokenKind::MfaChallenge,
+        state.config.auth.mfa.challenge_expiration,
+    )
+    .await?;
+
+    let mut methods = Vec::new();
+
+    if mfa::get_user_totp(&state.pool, user_id)
+        .await?
+        .is_some_and(|totp| totp.enabled)
+    {
+        methods.push(MfaMethod::Totp);
+    }
+
+    let credentials = mfa::get_user_webauthn_credentials(&state.pool, user_id).await?;
+    let webauthn = if credentials.is_empty() {
+        None
+    } else {
+        methods.push(MfaMethod::WebAuthn);
+        let relying_party = RelyingParty::new(origin, &state.config.auth.mfa.issuer)?;
+        Some(relying_party.request_options(&token, &credentials))
+    };
+
+    if mfa::count_unused_user_recovery_codes(&state.pool, user_id).await? > 0 {
+        methods.push(MfaMethod::RecoveryCode);
+    }
+
+    Ok(MfaChallenge {
+        token,
+        methods,
+        enrollment_required: !has_mfa,
+        webauthn,
+    })
+}
+
+/// Login to authenticate and start a new session, or start the second factor step of the login
+/// if the user enrolled one or if it is required
 #[utoipa::path(
     post,
     path = "/auth/login",
     tag = "auth",
     responses(
-        (status = 200, description = "User logged in successfully", body = LoginUserResponse),
+        (status = 200, description = "User logged in successfully or second factor needed", body = LoginUserResponse),
+        (status = 401, description = "Invalid credentials, disabled user or unverified email"),
+        (status = 429, description = "Too many failed logins for the account or the IP address, retry after the number of seconds of the `Retry-After` header"),
     ),
     request_body(
         description = "User information needed to authenticate a user",
@@ -38,42 +162,243 @@ pub async fn login(
     headers: HeaderMap,
     Json(data): Json<LoginUserRequest>,
 ) -> Result<impl IntoResponse> {
-    let Some(user) = users::get_user_from_email(&state.pool, &data.email).await? else {
-        return Err(Error::InvalidCredentials);
+    let attempt = LoginAttempt {
+        email: &data.email,
+        addr,
+        user_agent: user_agent.to_string(),
+        uri: uri.to_string(),
+        method: "POST".to_string(),
     };
+    let throttle = &state.auth_state.login_throttle;
+    throttle.check(&attempt).await?;
 
-    if !verify_password(&data.password, &user.password.unwrap())? {
+    let Some(user) = verify_user_password(&state.pool, &data.email, &data.password).await? else {
+        throttle.record_failure(&state.pool, &attempt).await?;
         return Err(Error::InvalidCredentials);
-    }
+    };
+
+    throttle.record_success(&attempt).await?;
+
+    let user = grant_configured_admin(&state, user).await?;
 
     if !user.enabled {
         return Err(Error::DisabledUser);
     }
 
+    if state.config.auth.password.require_email_verification && !user.verified {
+        return Err(Error::UnverifiedUser);
+    }
+
     let user_log = UserLog {
         user_id: user.id.clone(),
         ip_address: addr.to_string(),
         user_agent: user_agent.to_string(),
         uri: uri.to_string(),
         method: "GET".to_string(),
+        event: None,
     };
     users::create_user_log(&state.pool, &user_log).await?;
 
-    parse_and_validate_referer(&headers, &state.config.auth)?;
+    let origin = parse_referer_origin(&headers, &state.config.auth)?;
 
-    let claims = CreateUserClaims {
-        sub: user.id.clone(),
-        email: user.email,
-        email_verified: Some(user.verified),
-        name: user.name,
-        given_name: user.given_name,
-        family_name: user.family_name,
-        picture: None,
-        locale: user.locale,
-        provider: AUTH_PROVIDER.to_string(),
-    };
+    let has_mfa = mfa::user_has_mfa(&state.pool, &user.id).await?;
+    let mfa_required = *server_config::get_config_with_defaults_and_db_results(&state.pool)
+        .await?
+        .mfa_required
+        .get_value();
+
+    if has_mfa || mfa_required {
+        let mfa_challenge = create_mfa_challenge(&state, &user.id, has_mfa, &origin).await?;
+
+        return Ok(Json(LoginUserResponse {
+            jwt_token: None,
+            refresh_token: None,
+            mfa_challenge: Some(mfa_challenge),
+            recovery_codes: None,
+        })
+        .into_response());
+    }
+
+    let tokens = create_login_session(&state, user, &addr, &user_agent).await?;
+
+    Ok(Json(LoginUserResponse {
+        jwt_token: Some(tokens.jwt_token),
+        refresh_token: Some(tokens.refresh_token),
+        mfa_challenge: None,
+        recovery_codes: None,
+    })
+    .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use auth::{config::LoginThrottling, throttling::LoginThrottle};
+    use axum::http::header::{CONTENT_TYPE, REFERER, RETRY_AFTER, USER_AGENT};
+    use cache::{Cache, CacheImpl, CreateCacheEntry, InMemoryCacheConfig};
+    use database::schemas::users::{CreateUser, User, UserLogEvent};
+    use shared::crypto::hash_password;
+
+    use super::*;
+    use crate::{state::test::create_state, test_utils::*};
+
+    const EMAIL: &str = "jane@example.com";
+    const PASSWORD: &str = "correct horse battery staple";
 
-    let jwt_token = claims.generate_jwt(&state.auth_state.jwt_manager, user.roles, user.id)?;
+    async fn login(state: &ServerState, password: &str) -> http::Response<Body> {
+        let body = json!({ "email": EMAIL, "password": password });
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/api/v1/auth/login")
+            .header(CONTENT_TYPE, "application/json")
+            .header(REFERER, "http://localhost:5173/auth/login")
+            .header(USER_AGENT, TEST_USER_AGENT)
+            .body(Body::from(serde_json::to_vec(&body).unwrap()))
+            .unwrap();
 
-    Ok(Json(LoginUserResponse { jwt_token }).into_response())
+        router_with_state(state.clone())
+            .oneshot(request)
+            .await
+            .unwrap()
+    }
+
+    /// Creates the local user and a state locking accounts after two failed logins
+    async fn create_throttled_state(pool: &PgPool) -> (User, ServerState) {
+        let user = users::create_or_update_user(
+            pool,
+            &CreateUser {
+                auth_id: None,
+                provider: AUTH_PROVIDER.to_string(),
+                enabled: true,
+                email: EMAIL.to_string(),
+                verified: true,
+                name: "Jane".to_string(),
+                given_name: None,
+                family_name: None,
+                locale: None,
+                picture: None,
+                roles: Default::default(),
+                replace_roles: false,
+                hashed_password: Some(hash_password(PASSWORD).unwrap()),
+            },
+        )
+        .await
+        .unwrap();
+
+        let mut state = create_state(pool.clone());
+        state.auth_state.login_throttle = LoginThrottle::postgres(
+            LoginThrottling {
+                max_account_attempts: 2,
+                base_delay: 0,
+                ..Default::default()
+            },
+            pool.clone(),
+        );
+
+        (user, state)
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_too_many_failed_logins_when_logging_in_then_locks_account_until_unlocked(
+        pool: PgPool,
+    ) {
+        let (user, state) = create_throttled_state(&pool).await;
+
+        for _ in 0..2 {
+            let response = login(&state, "wrong password").await;
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+
+        let response = login(&state, PASSWORD).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key(RETRY_AFTER));
+
+        let logs = users::get_user_logs(&pool, &user.id).await.unwrap();
+        assert_eq!(
+            logs.iter().filter_map(|log| log.event).collect::<Vec<_>>(),
+            vec![UserLogEvent::AccountLocked]
+        );
+
+        state
+            .auth_state
+            .login_throttle
+            .unlock_account(EMAIL)
+            .await
+            .unwrap();
+
+        let response = login(&state, PASSWORD).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_locked_account_when_filling_and_clearing_cache_then_account_stays_locked(
+        pool: PgPool,
+    ) {
+        let (_, mut state) = create_throttled_state(&pool).await;
+        state.cache = CacheImpl::bounded_in_memory(InMemoryCacheConfig {
+            max_entries: Some(10),
+            ..Default::default()
+        });
+
+        for _ in 0..2 {
+            let response = login(&state, "wrong password").await;
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+
+        let mut cache = state.cache.clone();
+        for i in 0..100 {
+            cache
+                .set(CreateCacheEntry {
+                    key: vec![
+                        "failed_logins".to_string(),
+                        "account".to_string(),
+                        i.to_string(),
+                    ],
+                    value: i,
+                    expiration: None,
+                })
+                .await
+                .unwrap();
+        }
+        cache.clear().await.unwrap();
+
+        let response = login(&state, PASSWORD).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_admin_email_when_logging_in_then_user_is_made_an_enabled_admin(pool: PgPool) {
+        let user = users::create_or_update_user(
+            &pool,
+            &CreateUser {
+                auth_id: None,
+                provider: AUTH_PROVIDER.to_string(),
+                enabled: false,
+                email: EMAIL.to_string(),
+                verified: true,
+                name: "Jane".to_string(),
+                given_name: None,
+                family_name: None,
+                locale: None,
+                picture: None,
+                roles: Default::default(),
+                replace_roles: false,
+                hashed_password: Some(hash_password(PASSWORD).unwrap()),
+            },
+        )
+        .await
+        .unwrap();
+
+        let mut state = create_state(pool.clone());
+        state.config.auth.admin_emails = vec![EMAIL.to_string()];
+
+        let response = login(&state, PASSWORD).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let user = users::get_user(&pool, &user.id).await.unwrap().unwrap();
+        assert!(user.enabled);
+        assert_eq!(user.roles, ["admin"]);
+    }
 }
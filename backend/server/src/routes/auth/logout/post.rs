@@ -0,0 +1,28 @@
+use auth::sessions;
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+
+use crate::{schemas::RefreshTokenRequest, Result, ServerState};
+
+/// Logout by revoking the session of the refresh token, its JWT no longer being accepted
+#[utoipa::path(
+    post,
+    path = "/auth/logout",
+    tag = "auth",
+    responses(
+        (status = 204, description = "Session revoked successfully"),
+        (status = 401, description = "Invalid refresh token"),
+    ),
+    request_body(
+        description = "Refresh token of the session",
+        content_type = "application/json",
+        content = RefreshTokenRequest
+    )
+)]
+pub async fn logout(
+    State(state): State<ServerState>,
+    Json(data): Json<RefreshTokenRequest>,
+) -> Result<impl IntoResponse> {
+    sessions::revoke_session(&state.pool, &data.refresh_token).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
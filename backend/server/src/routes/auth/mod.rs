@@ -4,14 +4,24 @@ use crate::ServerState;
 
 pub mod get;
 
+pub mod jwks;
 pub mod login;
+pub mod logout;
 pub mod openid;
+pub mod password;
+pub mod refresh;
 pub mod signup;
+pub mod verify;
 
 pub fn router() -> Router<ServerState> {
     Router::new()
         .route("/", get(get::get_enabled_auth))
+        .nest("/jwks", jwks::router())
         .nest("/login", login::router())
+        .nest("/logout", logout::router())
         .nest("/openid", openid::router())
+        .nest("/password", password::router())
+        .nest("/refresh", refresh::router())
         .nest("/signup", signup::router())
+        .nest("/verify", verify::router())
 }
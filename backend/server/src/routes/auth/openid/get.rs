@@ -0,0 +1,409 @@
+use auth::openid::{logic, RedirectCallbackQuery, RedirectLoginQuery};
+use axum::{
+    extract::{ConnectInfo, OriginalUri, Path, Query, State},
+    http::HeaderMap,
+    response::IntoResponse,
+};
+use axum_extra::{headers::UserAgent, TypedHeader};
+use std::net::SocketAddr;
+use tracing::info;
+
+use crate::{Error, Result, ServerState};
+
+/// Redirect to the OpenID Connect provider's login page
+#[utoipa::path(
+    get,
+    path = "/auth/openid/{provider}",
+    tag = "auth",
+    responses(
+        (status = 302, description = "Redirect to the provider's login page"),
+        (status = 404, description = "Provider not configured, disabled or unavailable"),
+    ),
+    params(
+        ("provider" = String, Path, description = "Name of the OpenID Connect provider in the configuration"),
+        RedirectLoginQuery
+    )
+)]
+pub async fn openid_redirect_login(
+    State(state): State<ServerState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    TypedHeader(user_agent): TypedHeader<UserAgent>,
+    headers: HeaderMap,
+    Path(provider): Path<String>,
+    Query(query): Query<RedirectLoginQuery>,
+) -> Result<impl IntoResponse> {
+    let Ok((_, response)) = state.auth_state.provider(&provider).await else {
+        return Err(Error::NotFound);
+    };
+
+    response
+        .handle_redirect_login(
+            &query,
+            &addr,
+            &user_agent,
+            &state.pool,
+            &state.config.auth,
+            &provider,
+            headers,
+        )
+        .await
+        .map_err(Into::into)
+}
+
+/// OpenID Connect provider callback
+#[utoipa::path(
+    get,
+    path = "/auth/openid/{provider}/redirect",
+    tag = "auth",
+    responses(
+        (status = 302, description = "Redirect to Indicator Aggregator frontend"),
+        (status = 404, description = "Provider not configured, disabled or unavailable"),
+    ),
+    params(
+        ("provider" = String, Path, description = "Name of the OpenID Connect provider in the configuration"),
+        RedirectCallbackQuery
+    )
+)]
+pub async fn openid_auth_redirect_callback(
+    State(state): State<ServerState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    TypedHeader(user_agent): TypedHeader<UserAgent>,
+    Path(provider): Path<String>,
+    Query(query): Query<RedirectCallbackQuery>,
+    OriginalUri(uri): OriginalUri,
+) -> Result<impl IntoResponse> {
+    info!(provider, "OpenID Connect callback");
+
+    logic(
+        &state.pool,
+        &state.auth_state,
+        &query,
+        &provider,
+        &addr,
+        &user_agent,
+        &uri,
+    )
+    .await
+    .map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use auth::config::{ClaimMappings, OpenId, RoleMapping, RoleMappings};
+    use axum::http::header::{LOCATION, REFERER, USER_AGENT};
+    use database::{
+        logic::users,
+        schemas::users::{CreateUser, User},
+    };
+    use std::collections::HashMap;
+    use url::Url;
+
+    use crate::{state::test::create_state, test_utils::*};
+
+    async fn create_state_with_provider(
+        pool: PgPool,
+        claims: Value,
+        roles: RoleMappings,
+    ) -> crate::ServerState {
+        let issuer = spawn_mock_openid_provider(claims).await;
+
+        let mut state = create_state(pool);
+        state.config.auth.openid.insert(
+            "mock".to_string(),
+            OpenId {
+                enabled: true,
+                name: "Mock".to_string(),
+                redirect_uri: "http://localhost:8080/api/v1/auth/openid/mock/redirect".to_string(),
+                openid_url: format!("{issuer}/.well-known/openid-configuration"),
+                client_id: MOCK_OPENID_CLIENT_ID.to_string(),
+                client_secret: "mock-client-secret".to_string(),
+                scopes: vec!["openid".to_string(), "groups".to_string()],
+                prompt: None,
+                claims: ClaimMappings {
+                    subject: "uid".to_string(),
+                    email: "mail".to_string(),
+                    name: "preferred_username".to_string(),
+                    ..Default::default()
+                },
+                roles,
+            },
+        );
+        state.auth_state = auth::config::State::new(
+            state.config.auth.clone(),
+            state.auth_state.jwt_manager,
+            state.auth_state.login_throttle,
+        )
+        .await;
+
+        state
+    }
+
+    async fn get(state: &crate::ServerState, uri: &str) -> http::Response<Body> {
+        let request = Request::builder()
+            .uri(uri)
+            .header(REFERER, "http://localhost:5173/auth/login")
+            .header(USER_AGENT, TEST_USER_AGENT)
+            .body(Body::empty())
+            .unwrap();
+
+        router_with_state(state.clone())
+            .oneshot(request)
+            .await
+            .unwrap()
+    }
+
+    fn location(response: &http::Response<Body>) -> Url {
+        Url::parse(response.headers()[LOCATION].to_str().unwrap()).unwrap()
+    }
+
+    /// Goes through the whole login flow and returns the logged in user
+    async fn login(state: &crate::ServerState) -> User {
+        let response = get(state, "/api/v1/auth/openid/mock").await;
+        let params: HashMap<_, _> = location(&response).query_pairs().into_owned().collect();
+
+        let response = get(
+            state,
+            &format!(
+                "/api/v1/auth/openid/mock/redirect?code={}&state={}",
+                params["nonce"], params["state"]
+            ),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::FOUND);
+
+        users::get_user_by_auth_id(&state.pool, "mock-user-id", "mock")
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    async fn create_mock_user(pool: &PgPool, roles: &[Role]) {
+        let create_user = CreateUser {
+            auth_id: Some("mock-user-id".to_string()),
+            provider: "mock".to_string(),
+            enabled: true,
+            email: "jane@example.com".to_string(),
+            verified: true,
+            name: "jane".to_string(),
+            given_name: None,
+            family_name: None,
+            locale: None,
+            picture: None,
+            roles: roles.iter().copied().collect(),
+            replace_roles: true,
+            hashed_password: None,
+        };
+
+        users::create_or_update_user(pool, &create_user)
+            .await
+            .unwrap();
+    }
+
+    fn group_mappings(source_of_truth: bool) -> RoleMappings {
+        RoleMappings {
+            source_of_truth,
+            rules: vec![
+                RoleMapping {
+                    claim: "groups".to_string(),
+                    value: "soc-analysts".to_string(),
+                    role: Role::Analyst,
+                },
+                RoleMapping {
+                    claim: "groups".to_string(),
+                    value: "detection-engineers".to_string(),
+                    role: Role::SourceEditor,
+                },
+            ],
+        }
+    }
+
+    fn sorted(mut roles: Vec<String>) -> Vec<String> {
+        roles.sort();
+        roles
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_configured_provider_when_logging_in_then_redirects_with_token_and_maps_claims(
+        pool: PgPool,
+    ) {
+        create_mock_user(&pool, &[]).await;
+        let claims = json!({
+            "uid": "mock-user-id",
+            "mail": "jane@example.com",
+            "preferred_username": "jane",
+            "email_verified": "true",
+        });
+        let state = create_state_with_provider(pool.clone(), claims, Default::default()).await;
+
+        let response = get(&state, "/api/v1/auth/openid/mock?next=/history").await;
+
+        assert_eq!(response.status(), StatusCode::FOUND);
+        let authorization_url = location(&response);
+        let params: HashMap<_, _> = authorization_url.query_pairs().into_owned().collect();
+        assert_eq!(authorization_url.path(), "/authorize");
+        assert_eq!(params["scope"], "openid groups");
+        assert_eq!(params["client_id"], MOCK_OPENID_CLIENT_ID);
+        assert!(!params.contains_key("prompt"));
+
+        // The mock provider uses the code as the ID token nonce
+        let response = get(
+            &state,
+            &format!(
+                "/api/v1/auth/openid/mock/redirect?code={}&state={}",
+                params["nonce"], params["state"]
+            ),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::FOUND);
+        let redirect = location(&response);
+        let params: HashMap<_, _> = redirect.query_pairs().into_owned().collect();
+        assert_eq!(
+            redirect.as_str().split('?').next(),
+            Some("http://localhost:5173/auth")
+        );
+        assert!(params.contains_key("token"));
+        assert_eq!(params["next"], "/history");
+
+        let user = users::get_user_by_auth_id(&pool, "mock-user-id", "mock")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(user.email, "jane@example.com");
+        assert_eq!(user.name, "jane");
+        assert_eq!(user.provider, "mock");
+        assert!(user.verified);
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_mismatched_nonce_when_logging_in_then_unauthorized(pool: PgPool) {
+        let claims = json!({ "uid": "mock-user-id", "mail": "jane@example.com" });
+        let state = create_state_with_provider(pool, claims, Default::default()).await;
+
+        let response = get(&state, "/api/v1/auth/openid/mock").await;
+        let params: HashMap<_, _> = location(&response).query_pairs().into_owned().collect();
+
+        let response = get(
+            &state,
+            &format!(
+                "/api/v1/auth/openid/mock/redirect?code=wrong-nonce&state={}",
+                params["state"]
+            ),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_unknown_provider_when_logging_in_then_not_found(pool: PgPool) {
+        let response = request(Method::GET, "/api/v1/auth/openid/unknown", pool).await;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_provider_not_discovered_at_startup_when_logging_in_then_discovers_it(
+        pool: PgPool,
+    ) {
+        let claims = json!({ "uid": "mock-user-id", "mail": "jane@example.com" });
+        let mut state = create_state_with_provider(pool, claims, Default::default()).await;
+        // As if the provider was unavailable when the server started
+        state.auth_state.providers = Default::default();
+
+        let user = login(&state).await;
+
+        assert_eq!(user.email, "jane@example.com");
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_role_mappings_when_logging_in_then_adds_matching_roles(pool: PgPool) {
+        create_mock_user(&pool, &[Role::Viewer]).await;
+        let claims = json!({
+            "uid": "mock-user-id",
+            "mail": "jane@example.com",
+            "groups": ["soc-analysts", "others"],
+        });
+        let state = create_state_with_provider(pool, claims, group_mappings(false)).await;
+
+        let user = login(&state).await;
+
+        assert_eq!(sorted(user.roles), ["analyst", "viewer"]);
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_admin_email_when_logging_in_then_only_verified_email_is_made_an_admin(
+        pool: PgPool,
+    ) {
+        for (email_verified, expected_roles) in [("false", vec![]), ("true", vec!["admin"])] {
+            let claims = json!({
+                "uid": "mock-user-id",
+                "mail": "jane@example.com",
+                "email_verified": email_verified,
+            });
+            let mut state =
+                create_state_with_provider(pool.clone(), claims, Default::default()).await;
+            state.config.auth.admin_emails = vec!["Jane@Example.com".to_string()];
+            state.auth_state.auth = state.config.auth.clone();
+
+            let user = login(&state).await;
+
+            assert_eq!(user.roles, expected_roles);
+            assert_eq!(user.enabled, email_verified == "true");
+        }
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_same_subject_at_another_provider_when_logging_in_then_ignores_its_user(
+        pool: PgPool,
+    ) {
+        let create_user = CreateUser {
+            auth_id: Some("mock-user-id".to_string()),
+            provider: "other".to_string(),
+            enabled: true,
+            email: "john@example.com".to_string(),
+            verified: true,
+            name: "john".to_string(),
+            given_name: None,
+            family_name: None,
+            locale: None,
+            picture: None,
+            roles: Default::default(),
+            replace_roles: false,
+            hashed_password: None,
+        };
+        users::create_or_update_user(&pool, &create_user)
+            .await
+            .unwrap();
+        let claims = json!({ "uid": "mock-user-id", "mail": "jane@example.com" });
+        let state = create_state_with_provider(pool, claims, Default::default()).await;
+
+        let user = login(&state).await;
+
+        assert_eq!(user.email, "jane@example.com");
+        assert!(!user.enabled);
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_provider_source_of_truth_when_logging_in_then_replaces_roles(pool: PgPool) {
+        create_mock_user(&pool, &[Role::Viewer]).await;
+        let claims = json!({
+            "uid": "mock-user-id",
+            "mail": "jane@example.com",
+            "groups": "detection-engineers",
+        });
+        let state = create_state_with_provider(pool, claims, group_mappings(true)).await;
+
+        let user = login(&state).await;
+
+        assert_eq!(user.roles, ["source_editor"]);
+    }
+}
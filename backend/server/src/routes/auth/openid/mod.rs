@@ -1,12 +1,14 @@
-use axum::Router;
+use axum::{routing::get, Router};
 
 use crate::ServerState;
 
-pub mod google;
-pub mod microsoft;
+pub mod get;
 
 pub fn router() -> Router<ServerState> {
     Router::new()
-        .nest("/google", google::router())
-        .nest("/microsoft", microsoft::router())
+        .route("/:provider", get(get::openid_redirect_login))
+        .route(
+            "/:provider/redirect",
+            get(get::openid_auth_redirect_callback),
+        )
 }
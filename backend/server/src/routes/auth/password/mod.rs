@@ -0,0 +1,11 @@
+use axum::{routing::post, Router};
+
+use crate::ServerState;
+
+pub mod post;
+
+pub fn router() -> Router<ServerState> {
+    Router::new()
+        .route("/forgot", post(post::forgot_password))
+        .route("/reset", post(post::reset_password))
+}
@@ -0,0 +1,154 @@
+use auth::{openid::parse_referer_origin, password::consume_user_token};
+use axum::{extract::State, http::HeaderMap, response::IntoResponse, Json};
+use database::{
+    logic::{sessions, users},
+    schemas::auth::UserTokenKind,
+};
+use reqwest::StatusCode;
+use shared::crypto::hash_password;
+
+use crate::{
+    emails::{send_password_reset, Recipient},
+    schemas::{ResetPasswordRequest, UserEmailRequest},
+    Result, ServerState,
+};
+
+/// Send a password reset link, if the local account exists
+#[utoipa::path(
+    post,
+    path = "/auth/password/forgot",
+    tag = "auth",
+    responses(
+        (status = 204, description = "Password reset link sent if the account exists"),
+    ),
+    request_body(
+        description = "Email of the local account",
+        content_type = "application/json",
+        content = UserEmailRequest
+    )
+)]
+pub async fn forgot_password(
+    State(state): State<ServerState>,
+    headers: HeaderMap,
+    Json(data): Json<UserEmailRequest>,
+) -> Result<impl IntoResponse> {
+    let origin = parse_referer_origin(&headers, &state.config.auth)?;
+
+    // The response is the same whether the account exists or not to avoid disclosing it
+    if let Some(user) = users::get_user_from_email(&state.pool, &data.email).await? {
+        if user.password.is_some() {
+            let recipient = Recipient {
+                user_id: &user.id,
+                name: &user.name,
+                email: &user.email,
+            };
+            send_password_reset(&state, &recipient, &origin).await?;
+        }
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Reset the password of a local account with the token sent by email, also verifying its email
+/// and signing it out everywhere
+#[utoipa::path(
+    post,
+    path = "/auth/password/reset",
+    tag = "auth",
+    responses(
+        (status = 204, description = "Password reset successfully"),
+        (status = 400, description = "Password not meeting the password policy"),
+        (status = 401, description = "Invalid, expired or already used token"),
+    ),
+    request_body(
+        description = "Password reset token and new password",
+        content_type = "application/json",
+        content = ResetPasswordRequest
+    )
+)]
+pub async fn reset_password(
+    State(state): State<ServerState>,
+    Json(data): Json<ResetPasswordRequest>,
+) -> Result<impl IntoResponse> {
+    state.config.auth.password.policy.validate(&data.password)?;
+
+    let user_id =
+        consume_user_token(&state.pool, &data.token, UserTokenKind::PasswordReset).await?;
+
+    users::update_user_password(&state.pool, &user_id, &hash_password(&data.password)?).await?;
+    users::set_user_verified(&state.pool, &user_id).await?;
+    // Signs out whoever may have used the previous password
+    sessions::revoke_user_sessions(&state.pool, &user_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use auth::password::{create_user_token, verify_user_password};
+    use database::{logic::users, schemas::auth::UserTokenKind};
+
+    use crate::test_utils::*;
+
+    const NEW_PASSWORD: &str = "correct horse battery staple";
+
+    async fn create_reset_token(pool: &PgPool) -> String {
+        let response = request(Method::GET, "/api/v1/health", pool.clone()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let user = users::get_user_from_email(pool, "test@test.test")
+            .await
+            .unwrap()
+            .unwrap();
+
+        create_user_token(pool, &user.id, UserTokenKind::PasswordReset, 3600)
+            .await
+            .unwrap()
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_reset_token_when_resetting_password_then_changes_password(pool: PgPool) {
+        let token = create_reset_token(&pool).await;
+
+        let response = json_request(
+            Method::POST,
+            "/api/v1/auth/password/reset",
+            pool.clone(),
+            json!({ "token": token, "password": NEW_PASSWORD }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let user = verify_user_password(&pool, "test@test.test", NEW_PASSWORD)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(user.verified);
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_weak_password_when_resetting_password_then_returns_400_error(pool: PgPool) {
+        let token = create_reset_token(&pool).await;
+
+        let response = json_request(
+            Method::POST,
+            "/api/v1/auth/password/reset",
+            pool.clone(),
+            json!({ "token": token, "password": "short" }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // The token is left unused
+        let response = json_request(
+            Method::POST,
+            "/api/v1/auth/password/reset",
+            pool,
+            json!({ "token": token, "password": NEW_PASSWORD }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    }
+}
@@ -0,0 +1,51 @@
+use auth::sessions;
+use axum::{extract::State, response::IntoResponse, Json};
+
+use crate::{schemas::RefreshTokenRequest, Result, ServerState};
+
+/// Get a new JWT and refresh token, the refresh token being only usable once
+#[utoipa::path(
+    post,
+    path = "/auth/refresh",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Session refreshed successfully", body = SessionTokens),
+        (status = 401, description = "Invalid, expired, revoked or already used refresh token"),
+    ),
+    request_body(
+        description = "Refresh token of the session",
+        content_type = "application/json",
+        content = RefreshTokenRequest
+    )
+)]
+pub async fn refresh_session(
+    State(state): State<ServerState>,
+    Json(data): Json<RefreshTokenRequest>,
+) -> Result<impl IntoResponse> {
+    let tokens = sessions::refresh_session(
+        &state.pool,
+        &state.auth_state.jwt_manager,
+        &data.refresh_token,
+    )
+    .await?;
+
+    Ok(Json(tokens))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::*;
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_invalid_refresh_token_when_refreshing_then_returns_401_error(pool: PgPool) {
+        let response = json_request(
+            Method::POST,
+            "/api/v1/auth/refresh",
+            pool,
+            json!({ "refreshToken": "unknown_secret" }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -1,4 +1,4 @@
-use auth::openid::parse_and_validate_referer;
+use auth::openid::parse_referer_origin;
 use axum::{
     extract::{ConnectInfo, OriginalUri, State},
     http::HeaderMap,
@@ -14,15 +14,21 @@ use reqwest::StatusCode;
 use shared::crypto::hash_password;
 use std::net::SocketAddr;
 
-use crate::{config::AUTH_PROVIDER, schemas::SignupUserRequest, Result, ServerState};
+use crate::{
+    config::AUTH_PROVIDER,
+    emails::{send_email_verification, Recipient},
+    schemas::SignupUserRequest,
+    Result, ServerState,
+};
 
-/// Signup to create a new user
+/// Signup to create a new user, sending them an email to verify their email
 #[utoipa::path(
     post,
     path = "/auth/signup",
     tag = "auth",
     responses(
-        (status = 201, description = "User created successfully"),
+        (status = 201, description = "User created successfully, or already existing"),
+        (status = 400, description = "Password not meeting the password policy"),
     ),
     request_body(
         description = "User information needed to create new user",
@@ -38,6 +44,9 @@ pub async fn signup(
     headers: HeaderMap,
     Json(data): Json<SignupUserRequest>,
 ) -> Result<impl IntoResponse> {
+    let origin = parse_referer_origin(&headers, &state.config.auth)?;
+    state.config.auth.password.policy.validate(&data.password)?;
+
     let create_user = CreateUser {
         auth_id: None,
         provider: AUTH_PROVIDER.to_string(),
@@ -50,20 +59,94 @@ pub async fn signup(
         enabled: false,
         locale: None,
         roles: Default::default(),
+        replace_roles: false,
         hashed_password: Some(hash_password(&data.password)?),
     };
-    let user = users::create_or_update_user(&state.pool, &create_user).await?;
+
+    // The response is the same whether the account exists or not to avoid disclosing it
+    let Some(user) = users::create_user(&state.pool, &create_user).await? else {
+        return Ok(StatusCode::CREATED);
+    };
 
     let user_log = UserLog {
-        user_id: user.id,
+        user_id: user.id.clone(),
         ip_address: addr.to_string(),
         user_agent: user_agent.to_string(),
         uri: uri.to_string(),
         method: "GET".to_string(),
+        event: None,
     };
     users::create_user_log(&state.pool, &user_log).await?;
 
-    parse_and_validate_referer(&headers, &state.config.auth)?;
+    let recipient = Recipient {
+        user_id: &user.id,
+        name: &user.name,
+        email: &user.email,
+    };
+    send_email_verification(&state, &recipient, &origin).await?;
 
     Ok(StatusCode::CREATED)
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::http::header::{CONTENT_TYPE, REFERER, USER_AGENT};
+
+    use super::*;
+    use crate::{state::test::create_state, test_utils::*};
+
+    async fn signup(state: &ServerState, name: &str, password: &str) -> StatusCode {
+        let body = json!({ "email": "test@test.test", "name": name, "password": password });
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/api/v1/auth/signup")
+            .header(CONTENT_TYPE, "application/json")
+            .header(REFERER, "http://localhost:5173/auth/signup")
+            .header(USER_AGENT, TEST_USER_AGENT)
+            .body(Body::from(serde_json::to_vec(&body).unwrap()))
+            .unwrap();
+
+        router_with_state(state.clone())
+            .oneshot(request)
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_existing_user_when_signing_up_then_returns_201_without_updating_user(
+        pool: PgPool,
+    ) {
+        let response = request(Method::GET, "/api/v1/health", pool.clone()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let state = create_state(pool.clone());
+
+        let status = signup(&state, "Mallory", "correct horse battery staple").await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let user = users::get_user_from_email(&pool, "test@test.test")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(user.name, "Test User");
+        assert!(user.enabled);
+        assert!(user.password.is_none());
+        assert!(state.mailer.sent().await.is_empty());
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_weak_password_when_signing_up_then_returns_400_error(pool: PgPool) {
+        let state = create_state(pool.clone());
+
+        let status = signup(&state, "Jane", "short").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        assert!(users::get_user_from_email(&pool, "test@test.test")
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
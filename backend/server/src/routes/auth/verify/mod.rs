@@ -0,0 +1,11 @@
+use axum::{routing::post, Router};
+
+use crate::ServerState;
+
+pub mod post;
+
+pub fn router() -> Router<ServerState> {
+    Router::new()
+        .route("/", post(post::verify_email))
+        .route("/resend", post(post::resend_email_verification))
+}
@@ -0,0 +1,123 @@
+use auth::{openid::parse_referer_origin, password::consume_user_token};
+use axum::{extract::State, http::HeaderMap, response::IntoResponse, Json};
+use database::{logic::users, schemas::auth::UserTokenKind};
+use reqwest::StatusCode;
+
+use crate::{
+    emails::{send_email_verification, Recipient},
+    schemas::{UserEmailRequest, UserTokenRequest},
+    Result, ServerState,
+};
+
+/// Verify the email of a local account with the token sent by email
+#[utoipa::path(
+    post,
+    path = "/auth/verify",
+    tag = "auth",
+    responses(
+        (status = 204, description = "Email verified successfully"),
+        (status = 401, description = "Invalid, expired or already used token"),
+    ),
+    request_body(
+        description = "Email verification token",
+        content_type = "application/json",
+        content = UserTokenRequest
+    )
+)]
+pub async fn verify_email(
+    State(state): State<ServerState>,
+    Json(data): Json<UserTokenRequest>,
+) -> Result<impl IntoResponse> {
+    let user_id =
+        consume_user_token(&state.pool, &data.token, UserTokenKind::EmailVerification).await?;
+
+    users::set_user_verified(&state.pool, &user_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Send a new email verification link, if the local account exists and is not verified
+#[utoipa::path(
+    post,
+    path = "/auth/verify/resend",
+    tag = "auth",
+    responses(
+        (status = 204, description = "Email verification link sent if needed"),
+    ),
+    request_body(
+        description = "Email of the local account",
+        content_type = "application/json",
+        content = UserEmailRequest
+    )
+)]
+pub async fn resend_email_verification(
+    State(state): State<ServerState>,
+    headers: HeaderMap,
+    Json(data): Json<UserEmailRequest>,
+) -> Result<impl IntoResponse> {
+    let origin = parse_referer_origin(&headers, &state.config.auth)?;
+
+    // The response is the same whether the account exists or not to avoid disclosing it
+    if let Some(user) = users::get_user_from_email(&state.pool, &data.email).await? {
+        if user.password.is_some() && !user.verified {
+            let recipient = Recipient {
+                user_id: &user.id,
+                name: &user.name,
+                email: &user.email,
+            };
+            send_email_verification(&state, &recipient, &origin).await?;
+        }
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use auth::password::create_user_token;
+    use database::{logic::users, schemas::auth::UserTokenKind};
+
+    use crate::test_utils::*;
+
+    async fn verify(pool: &PgPool, token: &str) -> StatusCode {
+        json_request(
+            Method::POST,
+            "/api/v1/auth/verify",
+            pool.clone(),
+            json!({ "token": token }),
+        )
+        .await
+        .status()
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_used_or_expired_token_when_verifying_email_then_returns_401_error(pool: PgPool) {
+        let response = request(Method::GET, "/api/v1/health", pool.clone()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let user = users::get_user_from_email(&pool, "test@test.test")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!user.verified);
+
+        let expired_token = create_user_token(&pool, &user.id, UserTokenKind::EmailVerification, 0)
+            .await
+            .unwrap();
+        assert_eq!(
+            verify(&pool, &expired_token).await,
+            StatusCode::UNAUTHORIZED
+        );
+
+        let token = create_user_token(&pool, &user.id, UserTokenKind::EmailVerification, 3600)
+            .await
+            .unwrap();
+        assert_eq!(verify(&pool, &token).await, StatusCode::NO_CONTENT);
+
+        let user = users::get_user(&pool, &user.id).await.unwrap().unwrap();
+        assert!(user.verified);
+
+        assert_eq!(verify(&pool, &token).await, StatusCode::UNAUTHORIZED);
+    }
+}
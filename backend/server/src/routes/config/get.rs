@@ -1,5 +1,10 @@
-use axum::{extract::State, response::IntoResponse, Json};
-use database::{logic::server_config::get_config_with_defaults_and_db_results, PgPool};
+use auth::require_permission;
+use axum::{extract::State, response::IntoResponse, Extension, Json};
+use database::{
+    logic::server_config::get_config_with_defaults_and_db_results,
+    schemas::users::{Permission, User},
+    PgPool,
+};
 
 use crate::Result;
 
@@ -9,10 +14,16 @@ use crate::Result;
     tag = "config",
     path = "/config",
     responses(
-        (status = 200, description = "Server configuration", body = ServerConfig)
+        (status = 200, description = "Server configuration", body = ServerConfig),
+        (status = 403, description = "Missing the config_view permission"),
     )
 )]
-pub async fn get_config(State(pool): State<PgPool>) -> Result<impl IntoResponse> {
+pub async fn get_config(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::ConfigView)?;
+
     let config = get_config_with_defaults_and_db_results(&pool).await?;
 
     Ok(Json(config))
@@ -1,9 +1,10 @@
+use auth::require_permission;
 use axum::{extract::State, http::StatusCode, response::IntoResponse, Extension, Json};
 use database::{
     logic::server_config,
     schemas::{
         server_config::{UpdateServerConfig, SERVER_CONFIG_ENTRIES},
-        users::User,
+        users::{Permission, User},
     },
     PgPool,
 };
@@ -15,7 +16,7 @@ use crate::Result;
     put,
     tag = "config",
     path = "/config",
-    responses((status = 204, description = "Server configuration updated")),
+    responses((status = 204, description = "Server configuration updated"), (status = 403, description = "Missing the config_edit permission"),),
     request_body(
         description = "Server configuration",
         content_type = "application/json",
@@ -27,6 +28,8 @@ pub async fn update_config(
     Extension(user): Extension<User>,
     Json(data): Json<Vec<UpdateServerConfig>>,
 ) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::ConfigEdit)?;
+
     let filtered_data = data
         .into_iter()
         .filter(|config| SERVER_CONFIG_ENTRIES.contains(&config.key.as_str()));
@@ -183,4 +186,24 @@ mod tests {
         let count = get_count_server_config(&pool).await;
         assert_eq!(count, 0);
     }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_user_without_config_edit_permission_when_calling_update_config_endpoint_then_returns_403_error(
+        pool: PgPool,
+    ) {
+        let request_body = json!([{"key": "javascript_source_template", "value": "test"}]);
+        let response = json_request_with_roles(
+            Method::PUT,
+            "/api/v1/config",
+            pool.clone(),
+            request_body,
+            &[Role::SourceEditor],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let count = get_count_server_config(&pool).await;
+        assert_eq!(count, 0);
+    }
 }
@@ -1,9 +1,15 @@
+use auth::require_permission;
 use axum::{
     extract::{Path, State},
     http::StatusCode,
     response::IntoResponse,
+    Extension,
+};
+use database::{
+    logic::ignore_lists,
+    schemas::users::{Permission, User},
+    PgPool,
 };
-use database::{logic::ignore_lists, PgPool};
 
 use crate::Result;
 
@@ -15,6 +21,7 @@ use crate::Result;
     responses(
         (status = 204, description = "Ignore list deleted successfully"),
         (status = 404, description = "Ignore list not found"),
+        (status = 403, description = "Missing the ignore_list_edit permission"),
     ),
     params(
         ("id" = String, Path, description = "Ignore list database ID"),
@@ -22,9 +29,12 @@ use crate::Result;
 )]
 pub async fn delete_list(
     State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
     Path(list_id): Path<String>,
 ) -> Result<impl IntoResponse> {
-    let num_affected = ignore_lists::delete_list(&pool, &list_id).await?;
+    require_permission(&user, Permission::IgnoreListEdit)?;
+
+    let num_affected = ignore_lists::delete_list(&pool, &list_id, user.visible_team_ids()).await?;
 
     if num_affected > 0 {
         Ok(StatusCode::NO_CONTENT)
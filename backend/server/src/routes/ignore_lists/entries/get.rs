@@ -1,11 +1,16 @@
+use auth::require_permission;
 use axum::{
     extract::{Path, State},
     response::IntoResponse,
-    Json,
+    Extension, Json,
+};
+use database::{
+    logic::ignore_lists,
+    schemas::users::{Permission, User},
+    PgPool,
 };
-use database::{logic::ignore_lists, PgPool};
 
-use crate::Result;
+use crate::{Error, Result};
 
 /// Get all of the ignore list entries for a specific list
 #[utoipa::path(
@@ -14,6 +19,8 @@ use crate::Result;
     tag = "ignoreLists",
     responses(
         (status = 200, description = "Ignore list entries retrieved successfully", body = [IgnoreListEntry]),
+        (status = 404, description = "Ignore list not found"),
+        (status = 403, description = "Missing the ignore_list_view permission"),
     ),
     params(
         ("id" = String, Path, description = "Ignore list database ID"),
@@ -21,9 +28,97 @@ use crate::Result;
 )]
 pub async fn get_list_entries(
     State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
     Path(list_id): Path<String>,
 ) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::IgnoreListView)?;
+
+    ignore_lists::get_list(&pool, &list_id, user.visible_team_ids())
+        .await?
+        .ok_or(Error::NotFound)?;
+
     let list_entries = ignore_lists::get_list_entries(&pool, &list_id).await?;
 
     Ok(Json(list_entries))
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::*;
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_ignore_list_of_another_team_when_accessing_entries_then_returns_404_error(
+        pool: PgPool,
+    ) {
+        let team_ids = create_teams_with_member(&pool, &[Role::SourceEditor]).await;
+
+        let mut list_ids = Vec::new();
+        for (name, team_id) in [("blue", &team_ids[0]), ("red", &team_ids[1])] {
+            let response = json_request(
+                Method::POST,
+                "/api/v1/ignoreLists",
+                pool.clone(),
+                json!({
+                    "name": name,
+                    "description": "",
+                    "enabled": true,
+                    "global": false,
+                    "teamId": team_id
+                }),
+            )
+            .await;
+            assert_eq!(response.status(), StatusCode::OK);
+            let list = json_response::<Value>(response).await;
+            let list_id = list["id"].as_str().unwrap().to_string();
+
+            let response = json_request(
+                Method::PUT,
+                &format!("/api/v1/ignoreLists/{list_id}/entries"),
+                pool.clone(),
+                json!([{"data": format!("{name}.com"), "indicatorKind": "DOMAIN"}]),
+            )
+            .await;
+            assert_eq!(response.status(), StatusCode::CREATED);
+
+            list_ids.push(list_id);
+        }
+
+        let response = request_with_roles(
+            Method::GET,
+            &format!("/api/v1/ignoreLists/{}/entries", list_ids[0]),
+            pool.clone(),
+            &[Role::SourceEditor],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = request_with_roles(
+            Method::GET,
+            &format!("/api/v1/ignoreLists/{}/entries", list_ids[1]),
+            pool.clone(),
+            &[Role::SourceEditor],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = json_request_with_roles(
+            Method::PUT,
+            &format!("/api/v1/ignoreLists/{}/entries", list_ids[1]),
+            pool.clone(),
+            json!([]),
+            &[Role::SourceEditor],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = request(
+            Method::GET,
+            &format!("/api/v1/ignoreLists/{}/entries", list_ids[1]),
+            pool,
+        )
+        .await;
+        let entries = json_response::<Vec<Value>>(response).await;
+        assert_eq!(entries.len(), 1);
+    }
+}
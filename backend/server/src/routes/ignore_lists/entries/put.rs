@@ -1,3 +1,4 @@
+use auth::require_permission;
 use axum::{
     extract::{Path, State},
     http::StatusCode,
@@ -6,11 +7,14 @@ use axum::{
 };
 use database::{
     logic::ignore_lists,
-    schemas::{ignore_lists::CreateIngoreListEntry, users::User},
+    schemas::{
+        ignore_lists::CreateIngoreListEntry,
+        users::{Permission, User},
+    },
     PgPool,
 };
 
-use crate::Result;
+use crate::{Error, Result};
 
 /// Replace all ignore list entries for a specific list
 #[utoipa::path(
@@ -19,6 +23,8 @@ use crate::Result;
     tag = "ignoreLists",
     responses(
         (status = 201, description = "Ignore list entries replaced successfully"),
+        (status = 404, description = "Ignore list not found"),
+        (status = 403, description = "Missing the ignore_list_edit permission"),
     ),
     params(
         ("id" = String, Path, description = "Ignore list database ID"),
@@ -33,6 +39,12 @@ pub async fn put_ignore_list_entries(
     Path(list_id): Path<String>,
     Json(data): Json<Vec<CreateIngoreListEntry>>,
 ) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::IgnoreListEdit)?;
+
+    ignore_lists::get_list(&pool, &list_id, user.visible_team_ids())
+        .await?
+        .ok_or(Error::NotFound)?;
+
     let mut transaction = pool.begin().await?;
 
     // TODO: don't unset and set everything... then set the updated_user_id accordingly
@@ -1,9 +1,14 @@
+use auth::require_permission;
 use axum::{
     extract::{Path, State},
     response::IntoResponse,
-    Json,
+    Extension, Json,
+};
+use database::{
+    logic::ignore_lists,
+    schemas::users::{Permission, User},
+    PgPool,
 };
-use database::{logic::ignore_lists, PgPool};
 
 use crate::{Error, Result};
 
@@ -14,10 +19,16 @@ use crate::{Error, Result};
     tag = "ignoreLists",
     responses(
         (status = 200, description = "Ignore lists retrieved successfully", body = [IgnoreList]),
+        (status = 403, description = "Missing the ignore_list_view permission"),
     ),
 )]
-pub async fn get_lists(State(pool): State<PgPool>) -> Result<impl IntoResponse> {
-    let lists = ignore_lists::get_lists(&pool).await?;
+pub async fn get_lists(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::IgnoreListView)?;
+
+    let lists = ignore_lists::get_lists(&pool, user.visible_team_ids()).await?;
 
     Ok(Json(lists))
 }
@@ -30,6 +41,7 @@ pub async fn get_lists(State(pool): State<PgPool>) -> Result<impl IntoResponse>
     responses(
         (status = 200, description = "Ignore list retrieved successfully", body = IgnoreList),
         (status = 404, description = "Ignore list not found"),
+        (status = 403, description = "Missing the ignore_list_view permission"),
     ),
     params(
         ("id" = String, Path, description = "Ignore list database ID"),
@@ -37,9 +49,12 @@ pub async fn get_lists(State(pool): State<PgPool>) -> Result<impl IntoResponse>
 )]
 pub async fn get_list(
     State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
     Path(list_id): Path<String>,
 ) -> Result<impl IntoResponse> {
-    let list = ignore_lists::get_list(&pool, &list_id)
+    require_permission(&user, Permission::IgnoreListView)?;
+
+    let list = ignore_lists::get_list(&pool, &list_id, user.visible_team_ids())
         .await?
         .ok_or(Error::NotFound)?;
 
@@ -53,10 +68,16 @@ pub async fn get_list(
     tag = "ignoreLists",
     responses(
         (status = 200, description = "Global ignore list retrieved successfully", body = [IgnoreList]),
+        (status = 403, description = "Missing the ignore_list_view permission"),
     ),
 )]
-pub async fn get_global_lists(State(pool): State<PgPool>) -> Result<impl IntoResponse> {
-    let lists = ignore_lists::get_global_lists(&pool).await?;
+pub async fn get_global_lists(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::IgnoreListView)?;
+
+    let lists = ignore_lists::get_global_lists(&pool, user.visible_team_ids()).await?;
 
     Ok(Json(lists))
 }
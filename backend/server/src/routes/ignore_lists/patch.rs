@@ -1,3 +1,4 @@
+use auth::{require_permission, teams::require_resource_team};
 use axum::{
     extract::{Path, State},
     http::StatusCode,
@@ -6,7 +7,10 @@ use axum::{
 };
 use database::{
     logic::ignore_lists,
-    schemas::{ignore_lists::UpdateIgnoreList, users::User},
+    schemas::{
+        ignore_lists::UpdateIgnoreList,
+        users::{Permission, User},
+    },
     PgPool,
 };
 
@@ -20,6 +24,7 @@ use crate::Result;
     responses(
         (status = 204, description = "Ignore list updated successfully", body = ()),
         (status = 404, description = "Ignore list not found"),
+        (status = 403, description = "Missing the ignore_list_edit permission or not a member of the team"),
     ),
     params(
         ("id" = String, Path, description = "Ignore list database ID"),
@@ -32,7 +37,14 @@ pub async fn patch_list(
     Path(list_id): Path<String>,
     Json(data): Json<UpdateIgnoreList>,
 ) -> Result<impl IntoResponse> {
-    let num_affected = ignore_lists::update_list(&pool, &list_id, data, &user.id).await?;
+    require_permission(&user, Permission::IgnoreListEdit)?;
+
+    if let Some(team_id) = &data.team_id {
+        require_resource_team(&user, Some(team_id))?;
+    }
+
+    let num_affected =
+        ignore_lists::update_list(&pool, &list_id, data, &user.id, user.visible_team_ids()).await?;
 
     if num_affected > 0 {
         Ok(StatusCode::NO_CONTENT)
@@ -1,7 +1,11 @@
+use auth::{require_permission, teams::require_resource_team};
 use axum::{extract::State, response::IntoResponse, Extension, Json};
 use database::{
     logic::ignore_lists,
-    schemas::{ignore_lists::CreateIgnoreList, users::User},
+    schemas::{
+        ignore_lists::CreateIgnoreList,
+        users::{Permission, User},
+    },
     PgPool,
 };
 
@@ -14,14 +18,18 @@ use crate::Result;
     tag = "ignoreLists",
     responses(
         (status = 200, description = "Ignore list created successfully", body = IdSlug),
+        (status = 403, description = "Missing the ignore_list_edit permission or not a member of the team"),
     ),
     params(CreateIgnoreList),
 )]
 pub async fn create_list(
     State(pool): State<PgPool>,
     Extension(user): Extension<User>,
-    Json(list): Json<CreateIgnoreList>,
+    Json(mut list): Json<CreateIgnoreList>,
 ) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::IgnoreListEdit)?;
+    list.team_id = require_resource_team(&user, list.team_id.as_deref())?;
+
     let created_list = ignore_lists::create_list(&pool, list, &user.id).await?;
 
     Ok(Json(created_list))
@@ -1,12 +1,17 @@
+use auth::require_permission;
 use axum::{
     extract::{Path, State},
     http::StatusCode,
     response::IntoResponse,
     Extension, Json,
 };
-use database::{logic::ignore_lists, schemas::users::User, PgPool};
+use database::{
+    logic::ignore_lists,
+    schemas::users::{Permission, User},
+    PgPool,
+};
 
-use crate::Result;
+use crate::{Error, Result};
 
 /// Updates the list of sources affected by an ignore list
 #[utoipa::path(
@@ -15,6 +20,8 @@ use crate::Result;
     tag = "ignoreLists",
     responses(
         (status = 201, description = "Ignore list providers updated successfully"),
+        (status = 404, description = "Ignore list not found"),
+        (status = 403, description = "Missing the ignore_list_edit permission"),
     ),
     params(
         ("id" = String, Path, description = "Ignore list database ID"),
@@ -29,6 +36,12 @@ pub async fn put_ignore_list_providers(
     Path(ignore_list_id): Path<String>,
     Json(provider_ids): Json<Vec<String>>,
 ) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::IgnoreListEdit)?;
+
+    ignore_lists::get_list(&pool, &ignore_list_id, user.visible_team_ids())
+        .await?
+        .ok_or(Error::NotFound)?;
+
     let mut transaction = pool.begin().await?;
 
     // TODO: don't unset and set everything... then set the updated_user_id accordingly
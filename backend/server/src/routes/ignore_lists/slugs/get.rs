@@ -1,8 +1,14 @@
+use auth::require_permission;
 use axum::{
     extract::{Path, State},
     response::IntoResponse,
+    Extension,
+};
+use database::{
+    logic::ignore_lists,
+    schemas::users::{Permission, User},
+    PgPool,
 };
-use database::{logic::ignore_lists, PgPool};
 
 use crate::{Error, Result};
 
@@ -14,6 +20,7 @@ use crate::{Error, Result};
     responses(
         (status = 200, description = "Ignore list ID retrieved successfully", body = String),
         (status = 404, description = "Ignore list not found"),
+        (status = 403, description = "Missing the ignore_list_view permission"),
     ),
     params(
         ("slug" = String, Path, description = "Ignore list slug"),
@@ -21,11 +28,63 @@ use crate::{Error, Result};
 )]
 pub async fn get_ignore_list_id_from_slug(
     State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
     Path(slug): Path<String>,
 ) -> Result<impl IntoResponse> {
-    let ignore_list_id = ignore_lists::get_ignore_list_id_from_slug(&pool, &slug)
-        .await?
-        .ok_or(Error::NotFound)?;
+    require_permission(&user, Permission::IgnoreListView)?;
+
+    let ignore_list_id =
+        ignore_lists::get_ignore_list_id_from_slug(&pool, &slug, user.visible_team_ids())
+            .await?
+            .ok_or(Error::NotFound)?;
 
     Ok(ignore_list_id)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::*;
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_ignore_list_of_another_team_when_getting_id_from_slug_then_returns_404_error(
+        pool: PgPool,
+    ) {
+        let team_ids = create_teams_with_member(&pool, &[Role::Viewer]).await;
+
+        for (name, team_id) in [("blue", &team_ids[0]), ("red", &team_ids[1])] {
+            let response = json_request(
+                Method::POST,
+                "/api/v1/ignoreLists",
+                pool.clone(),
+                json!({
+                    "name": name,
+                    "description": "",
+                    "enabled": true,
+                    "global": false,
+                    "teamId": team_id
+                }),
+            )
+            .await;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let response = request_with_roles(
+            Method::GET,
+            "/api/v1/ignoreLists/slugs/blue",
+            pool.clone(),
+            &[Role::Viewer],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = request_with_roles(
+            Method::GET,
+            "/api/v1/ignoreLists/slugs/red",
+            pool,
+            &[Role::Viewer],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -1,9 +1,14 @@
+use auth::require_permission;
 use axum::{
     extract::{Path, State},
     response::IntoResponse,
-    Json,
+    Extension, Json,
+};
+use database::{
+    logic::ignore_lists,
+    schemas::users::{Permission, User},
+    PgPool,
 };
-use database::{logic::ignore_lists, PgPool};
 
 use crate::Result;
 
@@ -14,6 +19,7 @@ use crate::Result;
     tag = "ignoreLists",
     responses(
         (status = 200, description = "Ignore list sources retrieved successfully", body = [Source]),
+        (status = 403, description = "Missing the ignore_list_view permission"),
     ),
     params(
         ("id" = String, Path, description = "Ignore list database ID"),
@@ -21,9 +27,12 @@ use crate::Result;
 )]
 pub async fn get_list_sources(
     State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
     Path(list_id): Path<String>,
 ) -> Result<impl IntoResponse> {
-    let sources = ignore_lists::get_list_sources(&pool, &list_id).await?;
+    require_permission(&user, Permission::IgnoreListView)?;
+
+    let sources = ignore_lists::get_list_sources(&pool, &list_id, user.visible_team_ids()).await?;
 
     Ok(Json(sources))
 }
@@ -1,12 +1,17 @@
+use auth::require_permission;
 use axum::{
     extract::{Path, State},
     http::StatusCode,
     response::IntoResponse,
     Extension, Json,
 };
-use database::{logic::ignore_lists, schemas::users::User, PgPool};
+use database::{
+    logic::{ignore_lists, sources},
+    schemas::users::{Permission, User},
+    PgPool,
+};
 
-use crate::Result;
+use crate::{Error, Result};
 
 /// Updates the list of sources affected by an ignore list
 #[utoipa::path(
@@ -15,6 +20,9 @@ use crate::Result;
     tag = "ignoreLists",
     responses(
         (status = 201, description = "Ignore list sources updated successfully"),
+        (status = 400, description = "Sources not found or not part of the team of the ignore list"),
+        (status = 404, description = "Ignore list not found"),
+        (status = 403, description = "Missing the ignore_list_edit permission"),
     ),
     params(
         ("id" = String, Path, description = "Ignore list database ID"),
@@ -29,10 +37,39 @@ pub async fn put_ignore_list_sources(
     Path(ignore_list_id): Path<String>,
     Json(source_ids): Json<Vec<String>>,
 ) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::IgnoreListEdit)?;
+
+    let list = ignore_lists::get_list(&pool, &ignore_list_id, user.visible_team_ids())
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let usable_source_ids = sources::get_list_usable_source_ids(
+        &pool,
+        &source_ids,
+        list.team_id.as_deref(),
+        user.visible_team_ids(),
+    )
+    .await?;
+
+    if let Some(source_id) = source_ids
+        .iter()
+        .find(|source_id| !usable_source_ids.contains(source_id))
+    {
+        return Err(Error::BadRequest(format!(
+            "Source {source_id} is not found or not part of the team of the ignore list"
+        )));
+    }
+
     let mut transaction = pool.begin().await?;
 
     // TODO: don't unset and set everything... then set the updated_user_id accordingly
-    ignore_lists::delete_all_ignore_list_sources(&mut *transaction, &ignore_list_id).await?;
+    // The sources of the other teams are left as is
+    ignore_lists::delete_all_ignore_list_sources(
+        &mut *transaction,
+        &ignore_list_id,
+        user.visible_team_ids(),
+    )
+    .await?;
     ignore_lists::add_ignore_list_sources(
         &mut *transaction,
         &ignore_list_id,
@@ -23,6 +23,7 @@ pub mod secrets;
 pub mod server;
 pub mod sources;
 pub mod stats;
+pub mod teams;
 pub mod users;
 
 pub fn router(state: ServerState) -> Router {
@@ -47,6 +48,7 @@ pub fn router(state: ServerState) -> Router {
                 .nest("/secrets", secrets::router())
                 .nest("/sources", sources::router())
                 .nest("/stats", stats::router())
+                .nest("/teams", teams::router())
                 .nest("/users", users::router())
                 .route_layer(from_fn_with_state(
                     state.clone(),
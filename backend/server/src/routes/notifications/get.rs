@@ -1,5 +1,10 @@
-use axum::{extract::State, response::IntoResponse, Json};
-use database::{logic::notifications, PgPool};
+use auth::require_permission;
+use axum::{extract::State, response::IntoResponse, Extension, Json};
+use database::{
+    logic::{notifications, server_config},
+    schemas::users::{Permission, User},
+    PgPool,
+};
 
 use crate::Result;
 
@@ -10,10 +15,65 @@ use crate::Result;
     tag = "notifications",
     responses(
         (status = 200, description = "Notifications retrieved successfully", body = [NotificationKind]),
+        (status = 403, description = "Missing the source_view permission"),
     ),
 )]
-pub async fn get_notifications(State(pool): State<PgPool>) -> Result<impl IntoResponse> {
-    let count = notifications::get_sources_without_secrets_set(&pool).await?;
+pub async fn get_notifications(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::SourceView)?;
 
-    Ok(Json(count))
+    let threshold = *server_config::get_config_with_defaults_and_db_results(&pool)
+        .await?
+        .task_failure_notification_threshold
+        .get_value();
+
+    let mut notifications = notifications::get_sources_without_secrets_set(&pool).await?;
+    notifications
+        .extend(notifications::get_sources_with_failing_tasks(&pool, threshold as i32).await?);
+
+    Ok(Json(notifications))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::*;
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_source_with_consecutive_failed_task_runs_when_calling_notifications_endpoint_then_returns_failing_source_task(
+        pool: PgPool,
+    ) {
+        let source_id: String = sqlx::query_scalar!(
+            "UPDATE sources SET enabled = TRUE, task_enabled = TRUE WHERE slug = 'dns' RETURNING id"
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        for error in ["first", "second", "third"] {
+            sqlx::query!(
+                "INSERT INTO task_runs (source_id, trigger, status, error, trace_id) VALUES ($1, 'scheduled', 'failed', $2, 'test')",
+                source_id,
+                error
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let response = request(Method::GET, "/api/v1/notifications", pool).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let actual = json_response::<Value>(response).await;
+        let failing = actual
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|n| n["kind"] == "FAILING_SOURCE_TASK")
+            .unwrap();
+        assert_eq!(failing["content"]["id"], source_id);
+        assert_eq!(failing["content"]["numConsecutiveFailures"], 3);
+    }
 }
@@ -37,6 +37,16 @@ All endpoints are protected except for auth endpoints. You either need to authen
 
 Only this documentation and the `/auth` endpoints are not protected.
 
+## Authorization
+
+Users are granted permissions through their roles, endpoints responding with a `403` status code when the user is missing the permission they require:
+- `viewer`: `request_view`, `source_view`, `provider_view`, `ignore_list_view`, `stats_view` and `config_view`
+- `analyst`: the `viewer` permissions and `request_create`
+- `source_editor`: the `analyst` permissions, `source_edit`, `provider_edit`, `ignore_list_edit`, `secret_view`, `secret_edit` and `runner_view`
+- `admin`: every permission, including `secret_value_view`, `config_edit`, `user_view` and `user_edit`
+
+Users can always access their own user data, such as their logs, requests and API tokens. The users logging in with one of the verified emails of the `auth.admin_emails` setting are made admins.
+
 ## Clients
 
 There's no official API client yet, but thanks to the OpenAPI documentation, you can generate your own HTTP client using something like [OpenAPI Generator](https://openapi-generator.tech/) with the JSON from the OpenAPI docs located at `/api/v1/docs/openspi.json`.",
@@ -54,12 +64,19 @@ There's no official API client yet, but thanks to the OpenAPI documentation, you
         routes::api_tokens::post::create_api_tokens,
         routes::api_tokens::post::regenerate_api_tokens,
         routes::auth::get::get_enabled_auth,
+        routes::auth::jwks::get::get_jwks,
+        routes::auth::login::mfa::post::login_mfa,
+        routes::auth::login::mfa::post::setup_login_totp,
         routes::auth::login::post::login,
-        routes::auth::openid::google::get::google_auth_redirect_callback,
-        routes::auth::openid::google::get::google_redirect_login,
-        routes::auth::openid::microsoft::get::microsoft_auth_redirect_callback,
-        routes::auth::openid::microsoft::get::microsoft_redirect_login,
+        routes::auth::logout::post::logout,
+        routes::auth::openid::get::openid_auth_redirect_callback,
+        routes::auth::openid::get::openid_redirect_login,
+        routes::auth::password::post::forgot_password,
+        routes::auth::password::post::reset_password,
+        routes::auth::refresh::post::refresh_session,
         routes::auth::signup::post::signup,
+        routes::auth::verify::post::resend_email_verification,
+        routes::auth::verify::post::verify_email,
         routes::config::get::get_config,
         routes::config::put::update_config,
         routes::favicon::get::get_favicon,
@@ -94,47 +111,96 @@ There's no official API client yet, but thanks to the OpenAPI documentation, you
         routes::requests::get::get_request,
         routes::requests::get::get_request_data,
         routes::requests::get::get_requests,
+        routes::runners::status::get::get_runners_status,
         routes::runners::status::get::get_runners_status_sse,
         routes::secrets::delete::delete_secret,
         routes::secrets::get::get_secret_value,
         routes::secrets::get::get_secrets,
         routes::secrets::patch::patch_secret,
         routes::secrets::post::create_secret,
+        routes::sources::cache_invalidations::get::get_source_cache_invalidations,
+        routes::sources::task_runs::get::get_source_task_runs,
+        routes::sources::task_runs::post::trigger_source_task_run,
         routes::sources::delete::delete_source,
         routes::sources::get::get_source,
         routes::sources::get::get_sources,
         routes::sources::ignore_lists::get::get_source_ignore_lists,
         routes::sources::ignore_lists::put::put_source_ignore_lists,
         routes::sources::patch::patch_source,
+        routes::sources::playground::post::run_source_playground,
         routes::sources::post::create_source,
         routes::sources::requests::get::get_source_requests,
         routes::sources::secrets::get::get_source_secrets,
         routes::sources::secrets::put::put_source_sources,
         routes::sources::slugs::get::get_source_id_from_slug,
+        routes::stats::cache::get::cache_stats,
         routes::stats::count::get::count,
         routes::stats::count::get::count_requests_by_hour,
         routes::stats::count::get::count_requests_by_kind,
         routes::stats::count::get::count_requests_by_providers,
         routes::stats::count::get::count_requests_by_sources,
+        routes::teams::delete::delete_team,
+        routes::teams::get::get_team,
+        routes::teams::get::get_teams,
+        routes::teams::members::get::get_team_members,
+        routes::teams::members::put::put_team_members,
+        routes::teams::patch::patch_team,
+        routes::teams::post::create_team,
+        routes::users::approve::post::approve_user,
+        routes::users::delete::delete_user_api_token,
         routes::users::delete::delete_user_api_tokens,
         routes::users::get::get_user,
         routes::users::get::get_user_api_tokens,
         routes::users::get::get_user_logs,
         routes::users::get::get_users,
         routes::users::ignore_lists::get::get_user_ignore_lists,
+        routes::users::lockout::delete::delete_user_lockout,
+        routes::users::lockout::get::get_user_lockout,
+        routes::users::mfa::delete::delete_user_totp,
+        routes::users::mfa::delete::delete_user_webauthn_credential,
+        routes::users::mfa::get::get_user_mfa,
+        routes::users::mfa::post::get_user_webauthn_creation_options,
+        routes::users::mfa::post::regenerate_user_recovery_codes,
+        routes::users::mfa::post::register_user_webauthn_credential,
+        routes::users::mfa::post::setup_user_totp,
+        routes::users::mfa::put::confirm_user_totp,
+        routes::users::password::put::change_password,
         routes::users::patch::update_user,
+        routes::users::post::create_service_account,
+        routes::users::post::create_service_account_api_token,
         routes::users::providers::get::get_user_providers,
         routes::users::requests::get::get_user_requests,
+        routes::users::sessions::delete::delete_user_session,
+        routes::users::sessions::delete::delete_user_sessions,
+        routes::users::sessions::get::get_user_sessions,
         routes::users::sources::get::get_user_sources,
+        routes::users::teams::get::get_user_teams,
     ),
     components(
         schemas(
+            auth::mfa::TotpSetup,
             auth::openid::RedirectCallbackQuery,
             auth::openid::RedirectLoginQuery,
+            auth::sessions::SessionTokens,
+            auth::throttling::FailedLogins,
+            auth::webauthn::WebAuthnAssertion,
+            auth::webauthn::WebAuthnCreationOptions,
+            auth::webauthn::WebAuthnRegistration,
+            auth::webauthn::WebAuthnRequestOptions,
+            cache::CacheStats,
             db_schemas::IdSlug,
             db_schemas::api_tokens::ApiToken,
             db_schemas::api_tokens::CreateApiToken,
             db_schemas::api_tokens::UpdateApiToken,
+            db_schemas::feeds::FeedCompression,
+            db_schemas::feeds::FeedConfig,
+            db_schemas::feeds::FeedFormat,
+            db_schemas::feeds::FeedIndicatorMapping,
+            db_schemas::feeds::FeedMetadataMapping,
+            db_schemas::http_templates::HttpHeader,
+            db_schemas::http_templates::HttpKindMapping,
+            db_schemas::http_templates::HttpMethod,
+            db_schemas::http_templates::HttpTemplateConfig,
             db_schemas::ignore_lists::CreateIgnoreList,
             db_schemas::ignore_lists::CreateIngoreListEntry,
             db_schemas::ignore_lists::IgnoreList,
@@ -142,6 +208,8 @@ There's no official API client yet, but thanks to the OpenAPI documentation, you
             db_schemas::ignore_lists::UpdateIgnoreList,
             db_schemas::indicators::Indicator,
             db_schemas::indicators::IndicatorKind,
+            db_schemas::mfa::WebAuthnCredential,
+            db_schemas::notifications::FailingSourceTask,
             db_schemas::notifications::MinimalSource,
             db_schemas::notifications::NotificationKind,
             db_schemas::providers::CreateProvider,
@@ -161,21 +229,34 @@ There's no official API client yet, but thanks to the OpenAPI documentation, you
             db_schemas::server_config::ServerConfigEntryU32,
             db_schemas::server_config::ServerConfigKind,
             db_schemas::server_config::UpdateServerConfig,
+            db_schemas::sessions::UserSession,
+            db_schemas::sources::CacheInvalidationReason,
             db_schemas::sources::CreateSource,
             db_schemas::sources::Source,
+            db_schemas::sources::SourceCacheInvalidation,
             db_schemas::sources::SourceKind,
             db_schemas::sources::UpdateSource,
             db_schemas::stats::Count,
             db_schemas::stats::CountPerHour,
             db_schemas::stats::CountPerId,
             db_schemas::stats::CountPerIdWrapper,
+            db_schemas::task_runs::TaskRun,
+            db_schemas::task_runs::TaskRunStatus,
+            db_schemas::task_runs::TaskRunTrigger,
+            db_schemas::teams::CreateTeam,
+            db_schemas::teams::Team,
+            db_schemas::teams::UpdateTeam,
+            db_schemas::users::CreateServiceAccount,
             db_schemas::users::DbUserLog,
+            db_schemas::users::Permission,
+            db_schemas::users::Role,
             db_schemas::users::UpdateUser,
             db_schemas::users::User,
+            db_schemas::users::UserLogEvent,
             db_schemas::users::UserWithNumLogs,
-            routes::auth::openid::google::get::GoogleCallbackContent,
             schemas::AuthService,
             schemas::AuthServiceKind,
+            schemas::ChangePasswordRequest,
             schemas::CreatedApiToken,
             schemas::Data,
             schemas::DataCache,
@@ -184,11 +265,32 @@ There's no official API client yet, but thanks to the OpenAPI documentation, you
             schemas::DataTiming,
             schemas::LoginUserRequest,
             schemas::LoginUserResponse,
+            schemas::MfaChallenge,
+            schemas::MfaLoginRequest,
+            schemas::MfaMethod,
+            schemas::MfaStatus,
+            schemas::MfaTokenRequest,
+            schemas::RecoveryCodesResponse,
+            schemas::RefreshTokenRequest,
             schemas::RequestExecuteParam,
+            schemas::ResetPasswordRequest,
             schemas::SignupUserRequest,
+            schemas::SourcePlaygroundRequest,
             schemas::SseDoneData,
             schemas::SseStartData,
+            schemas::TotpCodeRequest,
+            schemas::UserEmailRequest,
+            schemas::UserTokenRequest,
+            sources_schemas::RunnerInstance,
+            sources_schemas::RunnerSync,
+            sources_schemas::SourceCodeError,
+            sources_schemas::SourceEnvironment,
+            sources_schemas::SourceEnvironmentStatus,
             sources_schemas::SourceError,
+            sources_schemas::SourceLog,
+            sources_schemas::SourceLogLevel,
+            sources_schemas::SourcePackage,
+            sources_schemas::SourcePlaygroundResult,
         )
     ),
     tags(
@@ -206,6 +308,7 @@ There's no official API client yet, but thanks to the OpenAPI documentation, you
         (name = "secrets", description = "Secrets management for sources"),
         (name = "sources", description = "Sources management"),
         (name = "stats", description = "General statistics about the service"),
+        (name = "teams", description = "Teams management, scoping sources, secrets, ignore lists and requests"),
         (name = "users", description = "User management"),
     ),
     modifiers(&SecurityAddon)
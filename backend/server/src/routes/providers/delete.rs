@@ -1,9 +1,15 @@
+use auth::require_permission;
 use axum::{
     extract::{Path, State},
     http::StatusCode,
     response::IntoResponse,
+    Extension,
+};
+use database::{
+    logic::providers,
+    schemas::users::{Permission, User},
+    PgPool,
 };
-use database::{logic::providers, PgPool};
 
 use crate::Result;
 
@@ -15,6 +21,7 @@ use crate::Result;
     responses(
         (status = 204, description = "Provider deleted successfully"),
         (status = 404, description = "Provider not found"),
+        (status = 403, description = "Missing the provider_edit permission"),
     ),
     params(
         ("id" = String, Path, description = "Provider database ID"),
@@ -22,8 +29,11 @@ use crate::Result;
 )]
 pub async fn delete_provider(
     State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
     Path(provider_id): Path<String>,
 ) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::ProviderEdit)?;
+
     let num_affected = providers::delete_provider(&pool, &provider_id).await?;
 
     if num_affected > 0 {
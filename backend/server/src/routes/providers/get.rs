@@ -1,9 +1,14 @@
+use auth::require_permission;
 use axum::{
     extract::{Path, State},
     response::IntoResponse,
-    Json,
+    Extension, Json,
+};
+use database::{
+    logic::providers,
+    schemas::users::{Permission, User},
+    PgPool,
 };
-use database::{logic::providers, PgPool};
 
 use crate::{Error, Result};
 
@@ -14,9 +19,15 @@ use crate::{Error, Result};
     tag = "providers",
     responses(
         (status = 200, description = "Providers retrieved successfully", body = [Provider]),
+        (status = 403, description = "Missing the provider_view permission"),
     ),
 )]
-pub async fn get_providers(State(pool): State<PgPool>) -> Result<impl IntoResponse> {
+pub async fn get_providers(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::ProviderView)?;
+
     let providers = providers::get_providers(&pool).await?;
 
     Ok(Json(providers))
@@ -30,6 +41,7 @@ pub async fn get_providers(State(pool): State<PgPool>) -> Result<impl IntoRespon
     responses(
         (status = 200, description = "Provider retrieved successfully", body = Provider),
         (status = 404, description = "Provider not found"),
+        (status = 403, description = "Missing the provider_view permission"),
     ),
     params(
         ("id" = String, Path, description = "Provider database ID"),
@@ -37,8 +49,11 @@ pub async fn get_providers(State(pool): State<PgPool>) -> Result<impl IntoRespon
 )]
 pub async fn get_provider(
     State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
     Path(provider_id): Path<String>,
 ) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::ProviderView)?;
+
     let provider = providers::get_provider(&pool, &provider_id)
         .await?
         .ok_or(Error::NotFound)?;
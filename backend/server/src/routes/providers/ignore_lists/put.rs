@@ -1,10 +1,15 @@
+use auth::require_permission;
 use axum::{
     extract::{Path, State},
     http::StatusCode,
     response::IntoResponse,
     Extension, Json,
 };
-use database::{logic::providers, schemas::users::User, PgPool};
+use database::{
+    logic::providers,
+    schemas::users::{Permission, User},
+    PgPool,
+};
 
 use crate::Result;
 
@@ -15,6 +20,7 @@ use crate::Result;
     tag = "providers",
     responses(
         (status = 201, description = "Provider ignore lists replaced successfully"),
+        (status = 403, description = "Missing the provider_edit permission"),
     ),
     params(
         ("id" = String, Path, description = "Provider database ID"),
@@ -31,6 +37,8 @@ pub async fn put_provider_ignore_lists(
     Path(provider_id): Path<String>,
     Json(ignore_list_ids): Json<Vec<String>>,
 ) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::ProviderEdit)?;
+
     let mut transaction = pool.begin().await?;
 
     // TODO: don't unset and set everything... then set the updated_user_id accordingly
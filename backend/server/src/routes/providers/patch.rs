@@ -1,3 +1,4 @@
+use auth::require_permission;
 use axum::{
     extract::{Path, State},
     http::StatusCode,
@@ -6,7 +7,10 @@ use axum::{
 };
 use database::{
     logic::providers,
-    schemas::{providers::PatchProvider, users::User},
+    schemas::{
+        providers::PatchProvider,
+        users::{Permission, User},
+    },
     PgPool,
 };
 
@@ -20,6 +24,7 @@ use crate::Result;
     responses(
         (status = 204, description = "Provider updated successfully"),
         (status = 404, description = "Provider not found"),
+        (status = 403, description = "Missing the provider_edit permission"),
     ),
     params(
         ("id" = String, Path, description = "Provider database ID"),
@@ -36,6 +41,8 @@ pub async fn patch_provider(
     Path(provider_id): Path<String>,
     Json(provider): Json<PatchProvider>,
 ) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::ProviderEdit)?;
+
     let num_affected = providers::patch_provider(&pool, &provider_id, provider, &user.id).await?;
 
     if num_affected > 0 {
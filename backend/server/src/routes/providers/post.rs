@@ -1,6 +1,12 @@
+use auth::require_permission;
 use axum::{extract::State, response::IntoResponse, Extension, Json};
 use database::{
-    logic::providers, schemas::providers::CreateProvider, schemas::users::User, PgPool,
+    logic::providers,
+    schemas::{
+        providers::CreateProvider,
+        users::{Permission, User},
+    },
+    PgPool,
 };
 
 use crate::Result;
@@ -12,6 +18,7 @@ use crate::Result;
     tag = "providers",
     responses(
         (status = 200, description = "Provider created successfully", body = IdSlug),
+        (status = 403, description = "Missing the provider_edit permission"),
     ),
     request_body(
         description = "Provider to create",
@@ -24,6 +31,8 @@ pub async fn create_provider(
     Extension(user): Extension<User>,
     Json(provider): Json<CreateProvider>,
 ) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::ProviderEdit)?;
+
     let created_provider = providers::create_provider(&pool, provider, &user.id).await?;
 
     Ok(Json(created_provider))
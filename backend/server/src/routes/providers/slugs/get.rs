@@ -1,8 +1,14 @@
+use auth::require_permission;
 use axum::{
     extract::{Path, State},
     response::IntoResponse,
+    Extension,
+};
+use database::{
+    logic::providers,
+    schemas::users::{Permission, User},
+    PgPool,
 };
-use database::{logic::providers, PgPool};
 
 use crate::{Error, Result};
 
@@ -14,6 +20,7 @@ use crate::{Error, Result};
     responses(
         (status = 200, description = "Provider ID retrieved successfully", body = String),
         (status = 404, description = "Provider not found"),
+        (status = 403, description = "Missing the provider_view permission"),
     ),
     params(
         ("slug" = String, Path, description = "Provider slug"),
@@ -21,8 +28,11 @@ use crate::{Error, Result};
 )]
 pub async fn get_provider_id_from_slug(
     State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
     Path(slug): Path<String>,
 ) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::ProviderView)?;
+
     let provider_id = providers::get_provider_id_from_slug(&pool, &slug)
         .await?
         .ok_or(Error::NotFound)?;
@@ -1,9 +1,14 @@
+use auth::require_permission;
 use axum::{
     extract::{Path, State},
     response::IntoResponse,
-    Json,
+    Extension, Json,
+};
+use database::{
+    logic::sources,
+    schemas::users::{Permission, User},
+    PgPool,
 };
-use database::{logic::sources, PgPool};
 
 use crate::Result;
 
@@ -14,6 +19,7 @@ use crate::Result;
     tag = "providers",
     responses(
         (status = 200, description = "Provider sources retrieved successfully", body = [Source]),
+        (status = 403, description = "Missing the provider_view permission"),
     ),
     params(
         ("id" = String, Path, description = "Provider database ID"),
@@ -21,9 +27,13 @@ use crate::Result;
 )]
 pub async fn get_provider_sources(
     State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
     Path(provider_id): Path<String>,
 ) -> Result<impl IntoResponse> {
-    let sources = sources::get_provider_sources(&pool, &provider_id).await?;
+    require_permission(&user, Permission::ProviderView)?;
+
+    let sources =
+        sources::get_provider_sources(&pool, &provider_id, user.visible_team_ids()).await?;
 
     Ok(Json(sources))
 }
@@ -1,4 +1,4 @@
-use auth::require_roles;
+use auth::{require_permission, scope::require_request_scope, teams::require_resource_team};
 use axum::{
     extract::State,
     response::{
@@ -8,14 +8,17 @@ use axum::{
     Extension, Json,
 };
 use axum_extra::extract::Query;
-use database::schemas::{indicators::Indicator, users::User};
+use database::schemas::{
+    indicators::Indicator,
+    users::{Permission, User},
+};
 use futures_util::future::join;
 use sources::{integrations, schemas::SourceError};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::{info_span, Instrument};
 
 use crate::{
-    integrations::{get_indicator, handle_indicator_request},
+    integrations::{get_indicator, handle_indicator_request, CacheLookup},
     schemas::{DataSource, RequestExecuteParam, SseDoneData, SseStartData},
     Result, ServerState,
 };
@@ -28,14 +31,17 @@ use crate::{
     params(RequestExecuteParam),
     responses(
         (status = 200, description = "Data retrieved successfully", body = [Data]),
+        (status = 403, description = "Missing the request_create permission, outside of the API token scope or not a member of the team"),
     )
 )]
 pub async fn request(
     State(state): State<ServerState>,
     Extension(user): Extension<User>,
-    Query(request): Query<RequestExecuteParam>,
+    Query(mut request): Query<RequestExecuteParam>,
 ) -> Result<impl IntoResponse> {
-    require_roles(&user.roles, &["request_create"])?;
+    require_permission(&user, Permission::RequestCreate)?;
+    require_request_scope(&user, &request.kind, &mut request.source_ids)?;
+    request.team_id = require_resource_team(&user, request.team_id.as_deref())?;
 
     let should_ignore_errors = request.ignore_errors;
 
@@ -83,17 +89,21 @@ pub async fn request(
                 )),
              )
         ),
+        (status = 403, description = "Missing the request_create permission, outside of the API token scope or not a member of the team"),
     )
 )]
 pub async fn sse_handler(
     State(state): State<ServerState>,
     Extension(user): Extension<User>,
-    Query(request): Query<RequestExecuteParam>,
+    Query(mut request): Query<RequestExecuteParam>,
 ) -> Result<impl IntoResponse> {
-    require_roles(&user.roles, &["request_create"])?;
+    require_permission(&user, Permission::RequestCreate)?;
+    require_request_scope(&user, &request.kind, &mut request.source_ids)?;
+    request.team_id = require_resource_team(&user, request.team_id.as_deref())?;
 
     let should_ignore_errors = request.ignore_errors;
     let source_ids = request.source_ids.clone();
+    let team_id = request.team_id.clone();
     let indicator: Indicator = request.into();
     let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
 
@@ -102,8 +112,14 @@ pub async fn sse_handler(
             &state.pool,
             &indicator,
             &source_ids,
+            team_id.as_deref(),
+        ),
+        database::logic::requests::create_request(
+            &state.pool,
+            &indicator,
+            &user.id,
+            team_id.as_deref(),
         ),
-        database::logic::requests::create_request(&state.pool, &indicator, &user.id),
     )
     .await;
 
@@ -171,6 +187,7 @@ pub async fn sse_handler(
                         &state,
                         request_id,
                         integration.as_ref(),
+                        CacheLookup::Pending,
                     )
                     .await
                     .map(|data| {
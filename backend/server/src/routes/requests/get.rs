@@ -1,10 +1,14 @@
-use auth::require_roles;
+use auth::require_permission;
 use axum::{
     extract::{Path, State},
     response::IntoResponse,
     Extension, Json,
 };
-use database::{logic::requests, schemas::users::User, PgPool};
+use database::{
+    logic::requests,
+    schemas::users::{Permission, User},
+    PgPool,
+};
 
 use crate::{Error, Result};
 
@@ -15,15 +19,16 @@ use crate::{Error, Result};
     tag = "requests",
     responses(
         (status = 200, description = "List of requests", body = [Request]),
+        (status = 403, description = "Missing the request_view permission"),
     )
 )]
 pub async fn get_requests(
     Extension(user): Extension<User>,
     State(pool): State<PgPool>,
 ) -> Result<impl IntoResponse> {
-    require_roles(&user.roles, &["request_view"])?;
+    require_permission(&user, Permission::RequestView)?;
 
-    let requests = requests::get_requests(&pool).await?;
+    let requests = requests::get_requests(&pool, user.visible_team_ids()).await?;
 
     Ok(Json(requests))
 }
@@ -36,6 +41,7 @@ pub async fn get_requests(
     responses(
         (status = 200, description = "Request data", body = Request),
         (status = 404, description = "Request not found"),
+        (status = 403, description = "Missing the request_view permission"),
     ),
     params(
         ("id" = String, Path, description = "Request ID"),
@@ -46,9 +52,9 @@ pub async fn get_request(
     State(pool): State<PgPool>,
     Path(request_id): Path<String>,
 ) -> Result<impl IntoResponse> {
-    require_roles(&user.roles, &["request_view"])?;
+    require_permission(&user, Permission::RequestView)?;
 
-    let request = requests::get_request(&pool, &request_id)
+    let request = requests::get_request(&pool, &request_id, user.visible_team_ids())
         .await?
         .ok_or(Error::NotFound)?;
 
@@ -62,6 +68,7 @@ pub async fn get_request(
     tag = "requests",
     responses(
         (status = 200, description = "Request data", body = [SourceRequest]),
+        (status = 403, description = "Missing the request_view permission"),
     ),
     params(
         ("id" = String, Path, description = "Request ID"),
@@ -72,9 +79,10 @@ pub async fn get_request_data(
     State(pool): State<PgPool>,
     Path(request_id): Path<String>,
 ) -> Result<impl IntoResponse> {
-    require_roles(&user.roles, &["request_view"])?;
+    require_permission(&user, Permission::RequestView)?;
 
-    let request_data = requests::get_request_source_requests(&pool, &request_id).await?;
+    let request_data =
+        requests::get_request_source_requests(&pool, &request_id, user.visible_team_ids()).await?;
 
     Ok(Json(request_data))
 }
@@ -1,10 +1,44 @@
+use auth::require_permission;
 use axum::{
     extract::State,
     response::{sse::KeepAlive, IntoResponse, Sse},
+    Extension, Json,
 };
-use database::{schemas::sources::SourceKind, PgPool};
+use database::{
+    schemas::{
+        sources::SourceKind,
+        users::{Permission, User},
+    },
+    PgPool,
+};
+use sources::runners::RunnerPool;
+
+use crate::{
+    runners::{connect_runners, stream_health_check},
+    Result,
+};
+
+/// Get the status of every runner instance, along with the last synchronization of its sources' code
+#[utoipa::path(
+    get,
+    path = "/runners/status",
+    tag = "runners",
+    responses(
+        (status = 200, description = "Status of the runner instances", body = [RunnerInstance]),
+        (status = 403, description = "Missing the runner_view permission"),
+    )
+)]
+pub async fn get_runners_status(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+    State(runners): State<RunnerPool>,
+) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::RunnerView)?;
+
+    connect_runners(&pool, &runners).await?;
 
-use crate::{runners::stream_health_check, Result};
+    Ok(Json(runners.instances()?))
+}
 
 /// Get a stream of Server-Sent Events (SSE) for the status of all runners
 ///
@@ -17,12 +51,41 @@ use crate::{runners::stream_health_check, Result};
     tag = "runners",
     responses(
         (status = 200, description = "SSE stream for the indicator", content_type = "text/event-stream"),
+        (status = 403, description = "Missing the runner_view permission"),
     )
 )]
-pub async fn get_runners_status_sse(State(pool): State<PgPool>) -> Result<impl IntoResponse> {
-    let python_runner_status_stream = stream_health_check(&pool, SourceKind::Python).await?;
+pub async fn get_runners_status_sse(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+    State(runners): State<RunnerPool>,
+) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::RunnerView)?;
+
+    let python_runner_status_stream =
+        stream_health_check(&pool, &runners, SourceKind::Python).await?;
 
     Ok(Sse::new(python_runner_status_stream)
         .keep_alive(KeepAlive::default())
         .into_response())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::*;
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_unreachable_runners_when_calling_runners_status_endpoint_then_returns_unsynced_instances(
+        pool: PgPool,
+    ) {
+        let response = request(Method::GET, "/api/v1/runners/status", pool).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = json_response::<Value>(response).await;
+        let instances = body.as_array().unwrap();
+
+        assert!(instances
+            .iter()
+            .all(|instance| instance["status"] != "SERVING" && instance["sync"].is_null()));
+    }
+}
@@ -5,5 +5,7 @@ use crate::ServerState;
 pub mod get;
 
 pub fn router() -> Router<ServerState> {
-    Router::new().route("/sse", get(get::get_runners_status_sse))
+    Router::new()
+        .route("/", get(get::get_runners_status))
+        .route("/sse", get(get::get_runners_status_sse))
 }
@@ -1,9 +1,15 @@
+use auth::require_permission;
 use axum::{
     extract::{Path, State},
     http::StatusCode,
     response::IntoResponse,
+    Extension,
+};
+use database::{
+    logic::secrets,
+    schemas::users::{Permission, User},
+    PgPool,
 };
-use database::{logic::secrets, PgPool};
 
 use crate::Result;
 
@@ -15,6 +21,7 @@ use crate::Result;
     responses(
         (status = 204, description = "Secret deleted successfully"),
         (status = 404, description = "Secret not found"),
+        (status = 403, description = "Missing the secret_edit permission"),
     ),
     params(
         ("id" = String, Path, description = "Secret database ID"),
@@ -22,9 +29,12 @@ use crate::Result;
 )]
 pub async fn delete_secret(
     State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
     Path(secret_id): Path<String>,
 ) -> Result<impl IntoResponse> {
-    let num_affected = secrets::delete_secret(&pool, &secret_id).await?;
+    require_permission(&user, Permission::SecretEdit)?;
+
+    let num_affected = secrets::delete_secret(&pool, &secret_id, user.visible_team_ids()).await?;
 
     if num_affected > 0 {
         Ok(StatusCode::NO_CONTENT)
@@ -1,9 +1,14 @@
+use auth::require_permission;
 use axum::{
     extract::{Path, State},
     response::IntoResponse,
-    Json,
+    Extension, Json,
+};
+use database::{
+    logic::secrets,
+    schemas::users::{Permission, User},
+    PgPool,
 };
-use database::{logic::secrets, PgPool};
 
 use crate::{Result, ServerState};
 
@@ -14,10 +19,16 @@ use crate::{Result, ServerState};
     tag = "secrets",
     responses(
         (status = 200, description = "Secrets retrieved successfully", body = [Secret]),
+        (status = 403, description = "Missing the secret_view permission"),
     )
 )]
-pub async fn get_secrets(State(pool): State<PgPool>) -> Result<impl IntoResponse> {
-    let secrets = secrets::get_secrets(&pool).await?;
+pub async fn get_secrets(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::SecretView)?;
+
+    let secrets = secrets::get_secrets(&pool, user.visible_team_ids()).await?;
 
     Ok(Json(secrets))
 }
@@ -30,6 +41,7 @@ pub async fn get_secrets(State(pool): State<PgPool>) -> Result<impl IntoResponse
     responses(
         (status = 200, description = "Secret value retrieved successfully", body = String),
         (status = 404, description = "Secret not found"),
+        (status = 403, description = "Missing the secret_value_view permission"),
     ),
     params(
         ("id" = String, Path, description = "Secret database ID"),
@@ -37,15 +49,133 @@ pub async fn get_secrets(State(pool): State<PgPool>) -> Result<impl IntoResponse
 )]
 pub async fn get_secret_value(
     State(state): State<ServerState>,
+    Extension(user): Extension<User>,
     Path(id): Path<String>,
 ) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::SecretValueView)?;
+
     let value = secrets::get_secret_value(
         &state.pool,
         &id,
         &state.crypto,
         &state.config.encryption.db_key,
+        user.visible_team_ids(),
     )
     .await?;
 
     Ok(value)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::*;
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_source_editor_when_calling_secrets_endpoints_then_can_only_list_secrets(
+        pool: PgPool,
+    ) {
+        let response = request_with_roles(
+            Method::GET,
+            "/api/v1/secrets",
+            pool.clone(),
+            &[Role::SourceEditor],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = request_with_roles(
+            Method::GET,
+            "/api/v1/secrets/unknown/value",
+            pool,
+            &[Role::SourceEditor],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_team_member_when_listing_secrets_then_only_sees_shared_and_team_ones(
+        pool: PgPool,
+    ) {
+        // Creates the source editor user before adding them to a team
+        let response = request_with_roles(
+            Method::GET,
+            "/api/v1/secrets",
+            pool.clone(),
+            &[Role::SourceEditor],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let mut team_ids = Vec::new();
+        for name in ["Blue team", "Red team"] {
+            let response = json_request(
+                Method::POST,
+                "/api/v1/teams",
+                pool.clone(),
+                json!({"name": name, "description": ""}),
+            )
+            .await;
+            assert_eq!(response.status(), StatusCode::OK);
+            let team = json_response::<Value>(response).await;
+            team_ids.push(team["id"].as_str().unwrap().to_string());
+        }
+
+        let editor_id: String =
+            sqlx::query_scalar("SELECT id FROM users WHERE email = 'source_editor@test.test'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+
+        let response = json_request(
+            Method::PUT,
+            &format!("/api/v1/teams/{}/members", team_ids[0]),
+            pool.clone(),
+            json!([editor_id]),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        for (name, team_id) in [
+            ("shared", ""),
+            ("blue", team_ids[0].as_str()),
+            ("red", team_ids[1].as_str()),
+        ] {
+            let response = json_request(
+                Method::POST,
+                "/api/v1/secrets",
+                pool.clone(),
+                json!({"name": name, "value": "secret", "teamId": team_id}),
+            )
+            .await;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let response = request_with_roles(
+            Method::GET,
+            "/api/v1/secrets",
+            pool.clone(),
+            &[Role::SourceEditor],
+        )
+        .await;
+        let mut names = json_response::<Vec<Value>>(response)
+            .await
+            .into_iter()
+            .map(|secret| secret["name"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, ["blue", "shared"]);
+
+        let response = json_request_with_roles(
+            Method::POST,
+            "/api/v1/secrets",
+            pool,
+            json!({"name": "stolen", "value": "secret", "teamId": team_ids[1]}),
+            &[Role::SourceEditor],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+}
@@ -1,3 +1,4 @@
+use auth::{require_permission, teams::require_resource_team};
 use axum::{
     extract::{Path, State},
     http::StatusCode,
@@ -6,10 +7,14 @@ use axum::{
 };
 use database::{
     logic::secrets,
-    schemas::{secrets::UpdateSecret, users::User},
+    schemas::{
+        secrets::UpdateSecret,
+        sources::CacheInvalidationReason,
+        users::{Permission, User},
+    },
 };
 
-use crate::{Result, ServerState};
+use crate::{integrations::invalidate_source_cache, Result, ServerState};
 
 /// Partially update a secret by its ID
 #[utoipa::path(
@@ -19,6 +24,7 @@ use crate::{Result, ServerState};
     responses(
         (status = 204, description = "Secret updated successfully"),
         (status = 404, description = "Secret not found"),
+        (status = 403, description = "Missing the secret_edit permission or not a member of the team"),
     ),
     params(
         ("id" = String, Path, description = "Secret database ID"),
@@ -31,6 +37,14 @@ pub async fn patch_secret(
     Path(secret_id): Path<String>,
     Json(secret): Json<UpdateSecret>,
 ) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::SecretEdit)?;
+
+    if let Some(team_id) = &secret.team_id {
+        require_resource_team(&user, Some(team_id))?;
+    }
+
+    let value_updated = secret.value.is_some();
+
     let num_affected = secrets::patch_secret(
         &state.pool,
         &secret_id,
@@ -38,9 +52,22 @@ pub async fn patch_secret(
         &state.crypto,
         &state.config.encryption.db_key,
         &user.id,
+        user.visible_team_ids(),
     )
     .await?;
 
+    if num_affected > 0 && value_updated {
+        for source_id in secrets::get_secret_source_ids(&state.pool, &secret_id).await? {
+            invalidate_source_cache(
+                &state,
+                &source_id,
+                CacheInvalidationReason::SecretUpdated,
+                &user.id,
+            )
+            .await?;
+        }
+    }
+
     if num_affected > 0 {
         Ok(StatusCode::NO_CONTENT)
     } else {
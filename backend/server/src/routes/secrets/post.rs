@@ -1,7 +1,11 @@
+use auth::{require_permission, teams::require_resource_team};
 use axum::{extract::State, response::IntoResponse, Extension, Json};
 use database::{
     logic::secrets,
-    schemas::{secrets::CreateSecret, users::User},
+    schemas::{
+        secrets::CreateSecret,
+        users::{Permission, User},
+    },
 };
 
 use crate::{Result, ServerState};
@@ -13,6 +17,7 @@ use crate::{Result, ServerState};
     tag = "secrets",
     responses(
         (status = 200, description = "Secret created successfully", body = String),
+        (status = 403, description = "Missing the secret_edit permission or not a member of the team"),
     ),
     request_body(
         description = "Secret to create", content_type = "application/json", content = CreateSecret
@@ -21,8 +26,11 @@ use crate::{Result, ServerState};
 pub async fn create_secret(
     State(state): State<ServerState>,
     Extension(user): Extension<User>,
-    Json(secret): Json<CreateSecret>,
+    Json(mut secret): Json<CreateSecret>,
 ) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::SecretEdit)?;
+    secret.team_id = require_resource_team(&user, secret.team_id.as_deref())?;
+
     let secret_id = secrets::create_secret(
         &state.pool,
         secret,
@@ -0,0 +1,41 @@
+use auth::require_permission;
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use database::{
+    logic::sources,
+    schemas::users::{Permission, User},
+    PgPool,
+};
+
+use crate::Result;
+
+/// Get the history of cache invalidations of a specific source
+#[utoipa::path(
+    get,
+    path = "/sources/{id}/cacheInvalidations",
+    tag = "sources",
+    responses(
+        (status = 200, description = "List of source cache invalidations", body = [SourceCacheInvalidation]),
+        (status = 404, description = "Source not found"),
+        (status = 403, description = "Missing the source_view permission"),
+    ),
+    params(
+        ("id" = String, Path, description = "Source database ID"),
+    )
+)]
+pub async fn get_source_cache_invalidations(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+    Path(source_id): Path<String>,
+) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::SourceView)?;
+
+    sources::get_source(&pool, &source_id, user.visible_team_ids()).await?;
+
+    let cache_invalidations = sources::get_source_cache_invalidations(&pool, &source_id).await?;
+
+    Ok(Json(cache_invalidations))
+}
@@ -0,0 +1,9 @@
+use axum::{routing::get, Router};
+
+use crate::ServerState;
+
+pub mod get;
+
+pub fn router() -> Router<ServerState> {
+    Router::new().route("/", get(get::get_source_cache_invalidations))
+}
@@ -1,11 +1,18 @@
+use ::sources::runners::RunnerPool;
+use auth::require_permission;
 use axum::{
     extract::{Path, State},
     http::StatusCode,
     response::IntoResponse,
+    Extension,
+};
+use database::{
+    logic::sources,
+    schemas::users::{Permission, User},
+    PgPool,
 };
-use database::{logic::sources, schemas::sources::SourceKind, PgPool};
 
-use crate::{runners::send_delete_request, Result};
+use crate::{background_tasks::Scheduler, runners::send_delete_request, Result};
 
 /// Delete a specific source by ID
 #[utoipa::path(
@@ -15,6 +22,7 @@ use crate::{runners::send_delete_request, Result};
     responses(
         (status = 204, description = "Source deleted successfully"),
         (status = 404, description = "Source not found"),
+        (status = 403, description = "Missing the source_edit permission"),
     ),
     params(
         ("id" = String, Path, description = "Source database ID"),
@@ -22,17 +30,23 @@ use crate::{runners::send_delete_request, Result};
 )]
 pub async fn delete_source(
     State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+    State(scheduler): State<Scheduler>,
+    State(runners): State<RunnerPool>,
     Path(source_id): Path<String>,
 ) -> Result<impl IntoResponse> {
-    let source = sources::get_source(&pool, &source_id).await?;
+    require_permission(&user, Permission::SourceEdit)?;
+
+    let source = sources::get_source(&pool, &source_id, user.visible_team_ids()).await?;
 
-    if source.kind != SourceKind::System {
-        send_delete_request(&pool, source.kind, &source_id).await?;
+    if source.kind.runs_on_runner() {
+        send_delete_request(&pool, &runners, source.kind, &source_id).await?;
     }
 
     let num_affected = sources::delete_source(&pool, &source_id).await?;
 
     if num_affected > 0 {
+        scheduler.reconcile();
         Ok(StatusCode::NO_CONTENT)
     } else {
         Ok(StatusCode::NOT_FOUND)
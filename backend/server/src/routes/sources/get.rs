@@ -1,9 +1,14 @@
+use auth::require_permission;
 use axum::{
     extract::{Path, State},
     response::IntoResponse,
-    Json,
+    Extension, Json,
+};
+use database::{
+    logic::sources,
+    schemas::users::{Permission, User},
+    PgPool,
 };
-use database::{logic::sources, PgPool};
 
 use crate::Result;
 
@@ -14,10 +19,16 @@ use crate::Result;
     tag = "sources",
     responses(
         (status = 200, description = "Sources retrieved successfully", body = [Source]),
+        (status = 403, description = "Missing the source_view permission"),
     ),
 )]
-pub async fn get_sources(State(pool): State<PgPool>) -> Result<impl IntoResponse> {
-    let sources = sources::get_sources(&pool).await?;
+pub async fn get_sources(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::SourceView)?;
+
+    let sources = sources::get_sources(&pool, user.visible_team_ids()).await?;
 
     Ok(Json(sources))
 }
@@ -30,6 +41,7 @@ pub async fn get_sources(State(pool): State<PgPool>) -> Result<impl IntoResponse
     responses(
         (status = 200, description = "Source retrieved successfully", body = Source),
         (status = 404, description = "Source not found"),
+        (status = 403, description = "Missing the source_view permission"),
     ),
     params(
         ("id" = String, Path, description = "Source database ID"),
@@ -37,9 +49,12 @@ pub async fn get_sources(State(pool): State<PgPool>) -> Result<impl IntoResponse
 )]
 pub async fn get_source(
     State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
     Path(source_id): Path<String>,
 ) -> Result<impl IntoResponse> {
-    let source = sources::get_source(&pool, &source_id).await?;
+    require_permission(&user, Permission::SourceView)?;
+
+    let source = sources::get_source(&pool, &source_id, user.visible_team_ids()).await?;
 
     Ok(Json(source))
 }
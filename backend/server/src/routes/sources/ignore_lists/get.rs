@@ -1,9 +1,14 @@
+use auth::require_permission;
 use axum::{
     extract::{Path, State},
     response::IntoResponse,
-    Json,
+    Extension, Json,
+};
+use database::{
+    logic::sources,
+    schemas::users::{Permission, User},
+    PgPool,
 };
-use database::{logic::sources, PgPool};
 
 use crate::Result;
 
@@ -15,6 +20,7 @@ use crate::Result;
     responses(
         (status = 200, description = "Source ignore lists retrieved successfully", body = [IgnoreList]),
         (status = 404, description = "Source not found"),
+        (status = 403, description = "Missing the source_view permission"),
     ),
     params(
         ("id" = String, Path, description = "Source database ID"),
@@ -22,9 +28,13 @@ use crate::Result;
 )]
 pub async fn get_source_ignore_lists(
     State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
     Path(source_id): Path<String>,
 ) -> Result<impl IntoResponse> {
-    let ignore_lists = sources::get_source_ignore_lists(&pool, &source_id).await?;
+    require_permission(&user, Permission::SourceView)?;
+
+    let ignore_lists =
+        sources::get_source_ignore_lists(&pool, &source_id, user.visible_team_ids()).await?;
 
     Ok(Json(ignore_lists))
 }
@@ -1,12 +1,17 @@
+use auth::require_permission;
 use axum::{
     extract::{Path, State},
     http::StatusCode,
     response::IntoResponse,
     Extension, Json,
 };
-use database::{logic::sources, schemas::users::User, PgPool};
+use database::{
+    logic::{ignore_lists, sources},
+    schemas::users::{Permission, User},
+    PgPool,
+};
 
-use crate::Result;
+use crate::{Error, Result};
 
 /// Replace the ignore lists for a specific source
 #[utoipa::path(
@@ -15,7 +20,9 @@ use crate::Result;
     tag = "sources",
     responses(
         (status = 201, description = "Source ignore lists replaced successfully"),
+        (status = 400, description = "Ignore lists neither shared nor part of the team of the source"),
         (status = 404, description = "Source not found"),
+        (status = 403, description = "Missing the source_edit permission"),
     ),
     params(
         ("id" = String, Path, description = "Source database ID"),
@@ -32,6 +39,23 @@ pub async fn put_source_ignore_lists(
     Path(source_id): Path<String>,
     Json(ignore_list_ids): Json<Vec<String>>,
 ) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::SourceEdit)?;
+
+    let source = sources::get_source(&pool, &source_id, user.visible_team_ids()).await?;
+
+    let usable_list_ids =
+        ignore_lists::get_team_usable_list_ids(&pool, &ignore_list_ids, source.team_id.as_deref())
+            .await?;
+
+    if let Some(list_id) = ignore_list_ids
+        .iter()
+        .find(|list_id| !usable_list_ids.contains(list_id))
+    {
+        return Err(Error::BadRequest(format!(
+            "Ignore list {list_id} is neither shared nor part of the team of the source"
+        )));
+    }
+
     let mut transaction = pool.begin().await?;
 
     // TODO: don't unset and set everything... then set the updated_user_id accordingly
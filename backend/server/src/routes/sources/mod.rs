@@ -7,10 +7,13 @@ pub mod get;
 pub mod patch;
 pub mod post;
 
+pub mod cache_invalidations;
 pub mod ignore_lists;
+pub mod playground;
 pub mod requests;
 pub mod secrets;
 pub mod slugs;
+pub mod task_runs;
 
 pub fn router() -> Router<ServerState> {
     let source_router = Router::new()
@@ -22,9 +25,12 @@ pub fn router() -> Router<ServerState> {
         )
         .nest("/ignoreLists", ignore_lists::router())
         .nest("/secrets", secrets::router())
-        .nest("/requests", requests::router());
+        .nest("/requests", requests::router())
+        .nest("/cacheInvalidations", cache_invalidations::router())
+        .nest("/taskRuns", task_runs::router());
     Router::new()
         .nest("/:id", source_router)
         .route("/", get(get::get_sources).post(post::create_source))
         .route("/slugs/:slug", get(slugs::get::get_source_id_from_slug))
+        .nest("/playground", playground::router())
 }
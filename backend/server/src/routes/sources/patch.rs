@@ -1,3 +1,4 @@
+use auth::{require_permission, teams::require_resource_team};
 use axum::{
     extract::{Path, State},
     http::StatusCode,
@@ -7,13 +8,17 @@ use axum::{
 use database::{
     logic::sources,
     schemas::{
-        sources::{SourceKind, UpdateSource},
-        users::User,
+        sources::{CacheInvalidationReason, UpdateSource},
+        users::{Permission, User},
     },
-    PgPool,
 };
 
-use crate::{runners::send_update_request, Result};
+use crate::{
+    background_tasks::TaskSchedule,
+    integrations::{invalidate_source_cache, validate_source_config},
+    runners::send_update_request,
+    Result, ServerState,
+};
 
 /// Partially update a specific source by ID
 #[utoipa::path(
@@ -22,7 +27,9 @@ use crate::{runners::send_update_request, Result};
     tag = "sources",
     responses(
         (status = 204, description = "Source updated successfully"),
+        (status = 400, description = "Invalid cron expression, feed or HTTP config"),
         (status = 404, description = "Source not found"),
+        (status = 403, description = "Missing the source_edit permission or not a member of the team"),
     ),
     params(
         ("id" = String, Path, description = "Source database ID"),
@@ -34,24 +41,236 @@ use crate::{runners::send_update_request, Result};
     )
 )]
 pub async fn patch_source(
-    State(pool): State<PgPool>,
+    State(state): State<ServerState>,
     Extension(user): Extension<User>,
     Path(source_id): Path<String>,
     Json(source): Json<UpdateSource>,
 ) -> Result<impl IntoResponse> {
-    let num_affected = sources::update_source(&pool, &source_id, source, &user.id).await?;
+    require_permission(&user, Permission::SourceEdit)?;
+
+    if let Some(task_cron) = source.task_cron.as_deref().filter(|c| !c.is_empty()) {
+        TaskSchedule::parse_cron(task_cron)?;
+    }
+
+    if let Some(team_id) = &source.team_id {
+        require_resource_team(&user, Some(team_id))?;
+    }
+
+    if source.kind.is_some() || source.config.is_some() {
+        let existing =
+            sources::get_source(&state.pool, &source_id, user.visible_team_ids()).await?;
+
+        validate_source_config(
+            source.kind.as_ref().unwrap_or(&existing.kind),
+            source.config.as_deref().unwrap_or(&existing.config),
+        )?;
+    }
+
+    let invalidates_cache = source.invalidates_cache();
+
+    let num_affected = sources::update_source(
+        &state.pool,
+        &source_id,
+        source,
+        &user.id,
+        user.visible_team_ids(),
+    )
+    .await?;
 
-    let source = sources::get_source(&pool, &source_id).await?;
+    if num_affected == 0 {
+        return Ok(StatusCode::NOT_FOUND);
+    }
+
+    state.scheduler.reconcile();
+
+    let source = sources::get_source(&state.pool, &source_id, None).await?;
 
-    if source.kind != SourceKind::System {
+    if source.kind.runs_on_runner() {
         if let Some(source_code) = source.source_code {
-            send_update_request(&pool, source.kind, &source_id, &source_code).await?;
+            send_update_request(
+                &state.pool,
+                &state.runners,
+                source.kind,
+                &source_id,
+                &source_code,
+            )
+            .await?;
         }
     }
 
-    if num_affected > 0 {
-        Ok(StatusCode::NO_CONTENT)
-    } else {
-        Ok(StatusCode::NOT_FOUND)
+    if invalidates_cache {
+        invalidate_source_cache(
+            &state,
+            &source_id,
+            CacheInvalidationReason::SourceUpdated,
+            &user.id,
+        )
+        .await?;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::*;
+
+    async fn get_source_id(pool: &PgPool, slug: &str) -> String {
+        sqlx::query_scalar!("SELECT id FROM sources WHERE slug = $1", slug)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    async fn get_count_source_cache_invalidations(pool: &PgPool, source_id: &str) -> i64 {
+        sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!: _" FROM source_cache_invalidations WHERE source_id = $1"#,
+            source_id
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_request_with_config_values_when_calling_patch_source_endpoint_then_records_cache_invalidation(
+        pool: PgPool,
+    ) {
+        let source_id = get_source_id(&pool, "dns").await;
+
+        let request_body = json!({"configValues": []});
+        let response = json_request(
+            Method::PATCH,
+            &format!("/api/v1/sources/{source_id}"),
+            pool.clone(),
+            request_body,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let response = request(
+            Method::GET,
+            &format!("/api/v1/sources/{source_id}/cacheInvalidations"),
+            pool.clone(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let actual = json_response::<Value>(response).await;
+        assert_eq!(actual.as_array().unwrap().len(), 1);
+        assert_eq!(actual[0]["reason"], "SOURCE_UPDATED");
+        assert_eq!(actual[0]["numEntries"], 0);
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_request_with_description_only_when_calling_patch_source_endpoint_then_does_not_invalidate_cache(
+        pool: PgPool,
+    ) {
+        let source_id = get_source_id(&pool, "dns").await;
+
+        let request_body = json!({"description": "test"});
+        let response = json_request(
+            Method::PATCH,
+            &format!("/api/v1/sources/{source_id}"),
+            pool.clone(),
+            request_body,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let count = get_count_source_cache_invalidations(&pool, &source_id).await;
+        assert_eq!(count, 0);
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_request_with_invalid_task_cron_when_calling_patch_source_endpoint_then_returns_bad_request(
+        pool: PgPool,
+    ) {
+        let source_id = get_source_id(&pool, "dns").await;
+
+        let request_body = json!({"taskCron": "every minute"});
+        let response = json_request(
+            Method::PATCH,
+            &format!("/api/v1/sources/{source_id}"),
+            pool.clone(),
+            request_body,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let request_body = json!({"taskCron": "*/5 * * * *"});
+        let response = json_request(
+            Method::PATCH,
+            &format!("/api/v1/sources/{source_id}"),
+            pool.clone(),
+            request_body,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let task_cron =
+            sqlx::query_scalar!("SELECT task_cron FROM sources WHERE id = $1", source_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(task_cron.as_deref(), Some("*/5 * * * *"));
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_request_with_invalid_feed_config_when_calling_patch_source_endpoint_then_returns_bad_request(
+        pool: PgPool,
+    ) {
+        let source_id = get_source_id(&pool, "dns").await;
+
+        let request_body = json!({"kind": "FEED", "config": [{"url": "https://example.com/feed.txt", "format": "TXT", "indicators": []}]});
+        let response = json_request(
+            Method::PATCH,
+            &format!("/api/v1/sources/{source_id}"),
+            pool.clone(),
+            request_body,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let request_body = json!({"kind": "FEED", "config": [{"url": "https://example.com/feed.txt", "format": "TXT", "indicators": [{"field": "", "kind": "URL"}]}]});
+        let response = json_request(
+            Method::PATCH,
+            &format!("/api/v1/sources/{source_id}"),
+            pool.clone(),
+            request_body,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_viewer_when_calling_patch_source_endpoint_then_returns_403_error(pool: PgPool) {
+        let source_id = get_source_id(&pool, "dns").await;
+
+        let request_body = json!({"description": "test"});
+        let response = json_request_with_roles(
+            Method::PATCH,
+            &format!("/api/v1/sources/{source_id}"),
+            pool.clone(),
+            request_body.clone(),
+            &[Role::Viewer],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = json_request_with_roles(
+            Method::PATCH,
+            &format!("/api/v1/sources/{source_id}"),
+            pool,
+            request_body,
+            &[Role::Viewer, Role::SourceEditor],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
     }
 }
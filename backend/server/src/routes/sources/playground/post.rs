@@ -0,0 +1,99 @@
+use std::time::Duration;
+
+use ::sources::runners::{test_fetch_data, RunnerPool};
+use auth::require_permission;
+use axum::{extract::State, response::IntoResponse, Extension, Json};
+use database::{
+    logic::server_config::get_config_with_defaults_and_db_results,
+    schemas::users::{Permission, User},
+    PgPool,
+};
+
+use crate::{schemas::SourcePlaygroundRequest, Error, Result};
+
+/// Maximum time given to the runner to run the source's code
+const PLAYGROUND_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Run the code of a source on an indicator without saving it, the code and the request not being
+/// stored anywhere
+#[utoipa::path(
+    post,
+    path = "/sources/playground",
+    tag = "sources",
+    responses(
+        (status = 200, description = "Source's code ran, returning its data or error along with its logs", body = SourcePlaygroundResult),
+        (status = 400, description = "Source kind is not executed by a runner or its runner is disabled"),
+        (status = 408, description = "Source's code took too long to run"),
+        (status = 422, description = "Indicator data does not match its kind"),
+        (status = 403, description = "Missing the source_edit permission"),
+    ),
+    request_body(
+        description = "Source's code and indicator to run it on",
+        content_type = "application/json",
+        content = SourcePlaygroundRequest
+    )
+)]
+pub async fn run_source_playground(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+    State(runners): State<RunnerPool>,
+    Json(request): Json<SourcePlaygroundRequest>,
+) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::SourceEdit)?;
+
+    if !request.indicator.validate() {
+        return Err(Error::InvalidIndicatorKind(request.indicator.kind));
+    }
+
+    if !request.kind.runs_on_runner() {
+        return Err(Error::BadRequest(format!(
+            "{} sources are not executed by a runner",
+            request.kind
+        )));
+    }
+
+    let config = get_config_with_defaults_and_db_results(&pool).await?;
+
+    if !config.runner_enabled(&request.kind) {
+        return Err(Error::BadRequest(format!(
+            "{} runner is disabled",
+            request.kind
+        )));
+    }
+
+    let client = runners.client(&request.kind, &config)?;
+
+    let result = tokio::time::timeout(
+        PLAYGROUND_TIMEOUT,
+        test_fetch_data(client, &request.indicator, request.source_code),
+    )
+    .await
+    .map_err(|_| Error::Timeout)??;
+
+    Ok(Json(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::*;
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_source_not_executed_by_runner_when_running_playground_then_returns_bad_request(
+        pool: PgPool,
+    ) {
+        let response = json_request(
+            Method::POST,
+            "/api/v1/sources/playground",
+            pool,
+            json!({
+                "kind": "HTTP",
+                "sourceCode": "def fetch_data(data, kind):\n    return data",
+                "indicator": { "data": "example.com", "kind": "DOMAIN" },
+            }),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -1,14 +1,21 @@
+use ::sources::runners::RunnerPool;
+use auth::{require_permission, teams::require_resource_team};
 use axum::{extract::State, response::IntoResponse, Extension, Json};
 use database::{
     logic::sources,
     schemas::{
-        sources::{CreateSource, SourceKind},
-        users::User,
+        sources::CreateSource,
+        users::{Permission, User},
     },
     PgPool,
 };
 
-use crate::{runners::send_update_request, Result};
+use crate::{
+    background_tasks::{Scheduler, TaskSchedule},
+    integrations::validate_source_config,
+    runners::send_update_request,
+    Result,
+};
 
 /// Create a new source
 #[utoipa::path(
@@ -17,6 +24,8 @@ use crate::{runners::send_update_request, Result};
     tag = "sources",
     responses(
         (status = 200, description = "Source created successfully", body = IdSlug),
+        (status = 400, description = "Invalid cron expression, feed or HTTP config"),
+        (status = 403, description = "Missing the source_edit permission or not a member of the team"),
     ),
     request_body(
         description = "Source to create",
@@ -26,16 +35,36 @@ use crate::{runners::send_update_request, Result};
 )]
 pub async fn create_source(
     State(pool): State<PgPool>,
+    State(scheduler): State<Scheduler>,
+    State(runners): State<RunnerPool>,
     Extension(user): Extension<User>,
-    Json(source): Json<CreateSource>,
+    Json(mut source): Json<CreateSource>,
 ) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::SourceEdit)?;
+    source.team_id = require_resource_team(&user, source.team_id.as_deref())?;
+
+    if let Some(task_cron) = source.task_cron.as_deref().filter(|c| !c.is_empty()) {
+        TaskSchedule::parse_cron(task_cron)?;
+    }
+
+    validate_source_config(&source.kind, &source.config)?;
+
     let created_source = sources::create_source(&pool, &source, &user.id).await?;
 
-    if source.kind != SourceKind::System {
+    if source.kind.runs_on_runner() {
         if let Some(source_code) = source.source_code {
-            send_update_request(&pool, source.kind, &created_source.id, &source_code).await?;
+            send_update_request(
+                &pool,
+                &runners,
+                source.kind,
+                &created_source.id,
+                &source_code,
+            )
+            .await?;
         }
     }
 
+    scheduler.reconcile();
+
     Ok(Json(created_source))
 }
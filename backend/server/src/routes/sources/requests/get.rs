@@ -1,9 +1,14 @@
+use auth::require_permission;
 use axum::{
     extract::{Path, State},
     response::IntoResponse,
-    Json,
+    Extension, Json,
+};
+use database::{
+    logic::requests,
+    schemas::users::{Permission, User},
+    PgPool,
 };
-use database::{logic::requests, PgPool};
 
 use crate::Result;
 
@@ -14,6 +19,7 @@ use crate::Result;
     tag = "sources",
     responses(
         (status = 200, description = "List of source requests", body = [Request]),
+        (status = 403, description = "Missing the request_view permission"),
     ),
     params(
         ("id" = String, Path, description = "Source database ID"),
@@ -21,9 +27,13 @@ use crate::Result;
 )]
 pub async fn get_source_requests(
     State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
     Path(source_id): Path<String>,
 ) -> Result<impl IntoResponse> {
-    let requests = requests::get_source_requests(&pool, &source_id).await?;
+    require_permission(&user, Permission::RequestView)?;
+
+    let requests =
+        requests::get_source_requests(&pool, &source_id, user.visible_team_ids()).await?;
 
     Ok(Json(requests))
 }
@@ -1,9 +1,14 @@
+use auth::require_permission;
 use axum::{
     extract::{Path, State},
     response::IntoResponse,
-    Json,
+    Extension, Json,
+};
+use database::{
+    logic::{secrets, sources},
+    schemas::users::{Permission, User},
+    PgPool,
 };
-use database::{logic::secrets, PgPool};
 
 use crate::Result;
 
@@ -15,6 +20,7 @@ use crate::Result;
     responses(
         (status = 200, description = "Source secrets retrieved successfully", body = [SourceSecret]),
         (status = 404, description = "Source not found"),
+        (status = 403, description = "Missing the secret_view permission"),
     ),
     params(
         ("id" = String, Path, description = "Source database ID"),
@@ -22,8 +28,13 @@ use crate::Result;
 )]
 pub async fn get_source_secrets(
     State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
     Path(source_id): Path<String>,
 ) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::SecretView)?;
+
+    sources::get_source(&pool, &source_id, user.visible_team_ids()).await?;
+
     let secrets = secrets::get_source_secrets(&pool, &source_id).await?;
 
     Ok(Json(secrets))
@@ -1,3 +1,4 @@
+use auth::require_permission;
 use axum::{
     extract::{Path, State},
     http::StatusCode,
@@ -5,12 +6,15 @@ use axum::{
     Extension, Json,
 };
 use database::{
-    logic::secrets,
-    schemas::{secrets::CreateSourceSecret, users::User},
-    PgPool,
+    logic::{secrets, sources},
+    schemas::{
+        secrets::CreateSourceSecret,
+        sources::CacheInvalidationReason,
+        users::{Permission, User},
+    },
 };
 
-use crate::Result;
+use crate::{integrations::invalidate_source_cache, Error, Result, ServerState};
 
 /// Sets the source secrets for a specific source
 #[utoipa::path(
@@ -19,7 +23,9 @@ use crate::Result;
     tag = "sources",
     responses(
         (status = 201, description = "Source secrets set successfully"),
+        (status = 400, description = "Secrets neither shared nor part of the team of the source"),
         (status = 404, description = "Source not found"),
+        (status = 403, description = "Missing the secret_edit permission"),
     ),
     params(
         ("id" = String, Path, description = "Source database ID"),
@@ -31,12 +37,33 @@ use crate::Result;
     )
 )]
 pub async fn put_source_sources(
-    State(pool): State<PgPool>,
+    State(state): State<ServerState>,
     Extension(user): Extension<User>,
     Path(source_id): Path<String>,
     Json(source_secrets): Json<Vec<CreateSourceSecret>>,
 ) -> Result<impl IntoResponse> {
-    let mut transaction = pool.begin().await?;
+    require_permission(&user, Permission::SecretEdit)?;
+
+    let source = sources::get_source(&state.pool, &source_id, user.visible_team_ids()).await?;
+
+    let secret_ids = source_secrets
+        .iter()
+        .filter_map(|source_secret| source_secret.secret_id.clone())
+        .collect::<Vec<_>>();
+    let usable_secret_ids =
+        secrets::get_team_usable_secret_ids(&state.pool, &secret_ids, source.team_id.as_deref())
+            .await?;
+
+    if let Some(secret_id) = secret_ids
+        .iter()
+        .find(|secret_id| !usable_secret_ids.contains(secret_id))
+    {
+        return Err(Error::BadRequest(format!(
+            "Secret {secret_id} is neither shared nor part of the team of the source"
+        )));
+    }
+
+    let mut transaction = state.pool.begin().await?;
 
     // TODO: don't unset and set everything... then set the updated_user_id accordingly
     secrets::delete_all_source_secrets(&mut *transaction, &source_id).await?;
@@ -44,5 +71,13 @@ pub async fn put_source_sources(
 
     transaction.commit().await?;
 
+    invalidate_source_cache(
+        &state,
+        &source_id,
+        CacheInvalidationReason::SourceSecretsUpdated,
+        &user.id,
+    )
+    .await?;
+
     Ok(StatusCode::CREATED)
 }
@@ -1,8 +1,14 @@
+use auth::require_permission;
 use axum::{
     extract::{Path, State},
     response::IntoResponse,
+    Extension,
+};
+use database::{
+    logic::sources,
+    schemas::users::{Permission, User},
+    PgPool,
 };
-use database::{logic::sources, PgPool};
 
 use crate::{Error, Result};
 
@@ -14,6 +20,7 @@ use crate::{Error, Result};
     responses(
         (status = 200, description = "Source ID retrieved successfully", body = String),
         (status = 404, description = "Source not found"),
+        (status = 403, description = "Missing the source_view permission"),
     ),
     params(
         ("slug" = String, Path, description = "Source slug"),
@@ -21,11 +28,14 @@ use crate::{Error, Result};
 )]
 pub async fn get_source_id_from_slug(
     State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
     Path(slug): Path<String>,
 ) -> Result<impl IntoResponse> {
-    let source_id = sources::get_source_id_from_slug(&pool, &slug)
+    require_permission(&user, Permission::SourceView)?;
+
+    let source_id = sources::get_source_id_from_slug(&pool, &slug, user.visible_team_ids())
         .await?
-        .ok_or(Error::NotFound);
+        .ok_or(Error::NotFound)?;
 
     Ok(source_id)
 }
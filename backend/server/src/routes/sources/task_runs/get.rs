@@ -0,0 +1,43 @@
+use auth::require_permission;
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use database::{
+    logic::{sources, task_runs},
+    schemas::users::{Permission, User},
+    PgPool,
+};
+
+use crate::{schemas::GetTaskRunsParams, Result};
+
+/// Get the most recent runs of the background task of a specific source
+#[utoipa::path(
+    get,
+    path = "/sources/{id}/taskRuns",
+    tag = "sources",
+    responses(
+        (status = 200, description = "List of source task runs, most recent first", body = [TaskRun]),
+        (status = 404, description = "Source not found"),
+        (status = 403, description = "Missing the source_view permission"),
+    ),
+    params(
+        ("id" = String, Path, description = "Source database ID"),
+        GetTaskRunsParams,
+    )
+)]
+pub async fn get_source_task_runs(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+    Path(source_id): Path<String>,
+    Query(GetTaskRunsParams { limit }): Query<GetTaskRunsParams>,
+) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::SourceView)?;
+
+    sources::get_source(&pool, &source_id, user.visible_team_ids()).await?;
+
+    let task_runs = task_runs::get_source_task_runs(&pool, &source_id, limit.unwrap_or(50)).await?;
+
+    Ok(Json(task_runs))
+}
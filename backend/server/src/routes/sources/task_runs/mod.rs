@@ -0,0 +1,13 @@
+use axum::{routing::get, Router};
+
+use crate::ServerState;
+
+pub mod get;
+pub mod post;
+
+pub fn router() -> Router<ServerState> {
+    Router::new().route(
+        "/",
+        get(get::get_source_task_runs).post(post::trigger_source_task_run),
+    )
+}
@@ -0,0 +1,115 @@
+use auth::require_permission;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use database::{
+    logic::sources,
+    schemas::users::{Permission, User},
+};
+
+use crate::{Result, ServerState};
+
+/// Execute the background task of a specific source right away, without waiting for its schedule
+#[utoipa::path(
+    post,
+    path = "/sources/{id}/taskRuns",
+    tag = "sources",
+    responses(
+        (status = 202, description = "Task run started, returning its database ID", body = String),
+        (status = 400, description = "Source is disabled or has no background task"),
+        (status = 404, description = "Source not found"),
+        (status = 409, description = "Task is already running"),
+        (status = 403, description = "Missing the source_edit permission"),
+    ),
+    params(
+        ("id" = String, Path, description = "Source database ID"),
+    )
+)]
+pub async fn trigger_source_task_run(
+    State(state): State<ServerState>,
+    Extension(user): Extension<User>,
+    Path(source_id): Path<String>,
+) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::SourceEdit)?;
+
+    sources::get_source(&state.pool, &source_id, user.visible_team_ids()).await?;
+
+    let run_id = state
+        .scheduler
+        .trigger(&state, &source_id, &user.id)
+        .await?;
+
+    Ok((StatusCode::ACCEPTED, Json(run_id)))
+}
+
+#[cfg(test)]
+mod tests {
+    use database::logic::advisory_locks::{self, AdvisoryLock};
+
+    use crate::test_utils::*;
+
+    async fn get_source_id(pool: &PgPool, slug: &str) -> String {
+        sqlx::query_scalar!("SELECT id FROM sources WHERE slug = $1", slug)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_source_without_background_task_when_triggering_task_run_then_returns_bad_request(
+        pool: PgPool,
+    ) {
+        let source_id = get_source_id(&pool, "dns").await;
+
+        let response = request(
+            Method::POST,
+            &format!("/api/v1/sources/{source_id}/taskRuns"),
+            pool.clone(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let num_task_runs = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!" FROM task_runs WHERE source_id = $1"#,
+            source_id
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(num_task_runs, 0);
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_unknown_source_when_triggering_task_run_then_returns_not_found(pool: PgPool) {
+        let response = request(Method::POST, "/api/v1/sources/unknown/taskRuns", pool).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_task_running_on_another_instance_when_triggering_task_run_then_returns_conflict(
+        pool: PgPool,
+    ) {
+        let source_id = get_source_id(&pool, "urlhaus").await;
+
+        let lock = AdvisoryLock::try_acquire(&pool, &advisory_locks::task_run_lock_key(&source_id))
+            .await
+            .unwrap()
+            .unwrap();
+
+        let response = request(
+            Method::POST,
+            &format!("/api/v1/sources/{source_id}/taskRuns"),
+            pool.clone(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        lock.release().await.unwrap();
+    }
+}
@@ -0,0 +1,56 @@
+use auth::require_permission;
+use axum::{extract::State, response::IntoResponse, Extension, Json};
+use cache::Cache;
+use database::schemas::users::{Permission, User};
+
+use crate::{Result, ServerState};
+
+/// Usage statistics of the cache, such as its size and hit/miss/eviction counters
+#[utoipa::path(
+    get,
+    tag = "stats",
+    path = "/stats/cache",
+    responses(
+        (status = 200, description = "Statistics of the cache", body = CacheStats),
+        (status = 403, description = "Missing the stats_view permission"),
+    )
+)]
+pub async fn cache_stats(
+    State(state): State<ServerState>,
+    Extension(user): Extension<User>,
+) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::StatsView)?;
+
+    let stats = state.cache.stats().await?;
+
+    Ok(Json(stats))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::*;
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_empty_cache_when_calling_cache_stats_endpoint_then_returns_zeroed_stats(
+        pool: PgPool,
+    ) {
+        let response = request(Method::GET, "/api/v1/stats/cache", pool).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = json_response::<Value>(response).await;
+        assert_eq!(
+            &body,
+            &json!({
+                "entries": 0,
+                "sizeBytes": 0,
+                "maxEntries": null,
+                "maxBytes": null,
+                "hits": 0,
+                "misses": 0,
+                "evictions": 0,
+                "expirations": 0,
+            })
+        );
+    }
+}
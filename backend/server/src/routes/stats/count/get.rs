@@ -1,5 +1,10 @@
-use axum::{extract::State, response::IntoResponse, Json};
-use database::{logic::stats, PgPool};
+use auth::require_permission;
+use axum::{extract::State, response::IntoResponse, Extension, Json};
+use database::{
+    logic::stats,
+    schemas::users::{Permission, User},
+    PgPool,
+};
 
 use crate::Result;
 
@@ -9,11 +14,17 @@ use crate::Result;
     tag = "stats",
     path = "/stats/count",
     responses(
-        (status = 200, description = "Count of various items", body = [Count])
+        (status = 200, description = "Count of various items", body = [Count]),
+        (status = 403, description = "Missing the stats_view permission"),
     )
 )]
-pub async fn count(State(pool): State<PgPool>) -> Result<impl IntoResponse> {
-    let count = stats::count(&pool).await?;
+pub async fn count(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::StatsView)?;
+
+    let count = stats::count(&pool, user.visible_team_ids()).await?;
 
     Ok(Json(count))
 }
@@ -24,11 +35,17 @@ pub async fn count(State(pool): State<PgPool>) -> Result<impl IntoResponse> {
     tag = "stats",
     path = "/stats/count/requests/sources",
     responses(
-        (status = 200, body = [CountPerIdWrapper])
+        (status = 200, body = [CountPerIdWrapper]),
+        (status = 403, description = "Missing the stats_view permission"),
     )
 )]
-pub async fn count_requests_by_sources(State(pool): State<PgPool>) -> Result<impl IntoResponse> {
-    let count = stats::requests_per_source_last_day(&pool).await?;
+pub async fn count_requests_by_sources(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::StatsView)?;
+
+    let count = stats::requests_per_source_last_day(&pool, user.visible_team_ids()).await?;
 
     Ok(Json(count))
 }
@@ -39,11 +56,17 @@ pub async fn count_requests_by_sources(State(pool): State<PgPool>) -> Result<imp
     tag = "stats",
     path = "/stats/count/requests/providers",
     responses(
-        (status = 200, body = [CountPerIdWrapper])
+        (status = 200, body = [CountPerIdWrapper]),
+        (status = 403, description = "Missing the stats_view permission"),
     )
 )]
-pub async fn count_requests_by_providers(State(pool): State<PgPool>) -> Result<impl IntoResponse> {
-    let count = stats::requests_per_provider_last_day(&pool).await?;
+pub async fn count_requests_by_providers(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::StatsView)?;
+
+    let count = stats::requests_per_provider_last_day(&pool, user.visible_team_ids()).await?;
 
     Ok(Json(count))
 }
@@ -54,11 +77,17 @@ pub async fn count_requests_by_providers(State(pool): State<PgPool>) -> Result<i
     tag = "stats",
     path = "/stats/count/requests/kinds",
     responses(
-        (status = 200, body = [CountPerIdWrapper])
+        (status = 200, body = [CountPerIdWrapper]),
+        (status = 403, description = "Missing the stats_view permission"),
     )
 )]
-pub async fn count_requests_by_kind(State(pool): State<PgPool>) -> Result<impl IntoResponse> {
-    let count = stats::requests_per_kind_last_day(&pool).await?;
+pub async fn count_requests_by_kind(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::StatsView)?;
+
+    let count = stats::requests_per_kind_last_day(&pool, user.visible_team_ids()).await?;
 
     Ok(Json(count))
 }
@@ -69,11 +98,75 @@ pub async fn count_requests_by_kind(State(pool): State<PgPool>) -> Result<impl I
     tag = "stats",
     path = "/stats/count/requests",
     responses(
-        (status = 200, body = [CountPerHour])
+        (status = 200, body = [CountPerHour]),
+        (status = 403, description = "Missing the stats_view permission"),
     )
 )]
-pub async fn count_requests_by_hour(State(pool): State<PgPool>) -> Result<impl IntoResponse> {
-    let count = stats::requests_per_hour_last_day(&pool).await?;
+pub async fn count_requests_by_hour(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::StatsView)?;
+
+    let count = stats::requests_per_hour_last_day(&pool, user.visible_team_ids()).await?;
 
     Ok(Json(count))
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::*;
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_team_member_when_counting_then_ignores_other_teams(pool: PgPool) {
+        let team_ids = create_teams_with_member(&pool, &[Role::Viewer]).await;
+
+        for (name, team_id) in [("blue", &team_ids[0]), ("red", &team_ids[1])] {
+            let response = json_request(
+                Method::POST,
+                "/api/v1/ignoreLists",
+                pool.clone(),
+                json!({
+                    "name": name,
+                    "description": "",
+                    "enabled": true,
+                    "global": false,
+                    "teamId": team_id
+                }),
+            )
+            .await;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let response = request(Method::GET, "/api/v1/stats/count", pool.clone()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let all = json_response::<Value>(response).await;
+
+        let response = request_with_roles(
+            Method::GET,
+            "/api/v1/stats/count",
+            pool.clone(),
+            &[Role::Viewer],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let visible = json_response::<Value>(response).await;
+
+        assert_eq!(
+            visible["ignoreLists"].as_i64().unwrap() + 1,
+            all["ignoreLists"].as_i64().unwrap()
+        );
+
+        for uri in ["", "/sources", "/providers", "/kinds"] {
+            let response = request_with_roles(
+                Method::GET,
+                &format!("/api/v1/stats/count/requests{uri}"),
+                pool.clone(),
+                &[Role::Viewer],
+            )
+            .await;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+    }
+}
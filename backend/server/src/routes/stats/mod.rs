@@ -2,8 +2,11 @@ use axum::Router;
 
 use crate::ServerState;
 
+pub mod cache;
 pub mod count;
 
 pub fn router() -> Router<ServerState> {
-    Router::new().nest("/count", count::router())
+    Router::new()
+        .nest("/cache", cache::router())
+        .nest("/count", count::router())
 }
@@ -0,0 +1,87 @@
+use auth::require_permission;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+};
+use database::{
+    logic::teams,
+    schemas::users::{Permission, User},
+    PgPool,
+};
+
+use crate::{Error, Result};
+
+/// Delete a team by its ID, along with its requests. Its sources, secrets and ignore lists need
+/// to be deleted or moved to another team beforehand.
+#[utoipa::path(
+    delete,
+    path = "/teams/{id}",
+    tag = "teams",
+    responses(
+        (status = 204, description = "Team deleted successfully"),
+        (status = 404, description = "Team not found"),
+        (status = 403, description = "Missing the team_edit permission"),
+        (status = 409, description = "Team still owns sources, secrets or ignore lists"),
+    ),
+    params(
+        ("id" = String, Path, description = "Team database ID"),
+    )
+)]
+pub async fn delete_team(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+    Path(team_id): Path<String>,
+) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::TeamEdit)?;
+
+    let num_affected = teams::delete_team(&pool, &team_id)
+        .await
+        .map_err(|err| match err {
+            database::Error::Database(err) if err.code() == Some("23503".into()) => {
+                Error::Conflict("Team still owns sources, secrets or ignore lists".to_string())
+            }
+            err => err.into(),
+        })?;
+
+    if num_affected > 0 {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Ok(StatusCode::NOT_FOUND)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::*;
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_team_owning_secret_when_deleting_team_then_returns_409_error(pool: PgPool) {
+        let response = json_request(
+            Method::POST,
+            "/api/v1/teams",
+            pool.clone(),
+            json!({"name": "Blue team", "description": "Defenders"}),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let team_id = json_response::<Value>(response).await["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        let response = json_request(
+            Method::POST,
+            "/api/v1/secrets",
+            pool.clone(),
+            json!({"name": "token", "value": "secret", "teamId": team_id}),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = request(Method::DELETE, &format!("/api/v1/teams/{team_id}"), pool).await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+}
@@ -0,0 +1,51 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use database::{logic::teams, schemas::users::User, PgPool};
+
+use crate::{Error, Result};
+
+/// Get the teams of the user, or every team if they can manage them
+#[utoipa::path(
+    get,
+    path = "/teams",
+    tag = "teams",
+    responses(
+        (status = 200, description = "Teams retrieved successfully", body = [Team]),
+    )
+)]
+pub async fn get_teams(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+) -> Result<impl IntoResponse> {
+    let teams = teams::get_teams(&pool, user.visible_team_ids()).await?;
+
+    Ok(Json(teams))
+}
+
+/// Get a team by its database ID
+#[utoipa::path(
+    get,
+    path = "/teams/{id}",
+    tag = "teams",
+    responses(
+        (status = 200, description = "Team retrieved successfully", body = Team),
+        (status = 404, description = "Team not found"),
+    ),
+    params(
+        ("id" = String, Path, description = "Team database ID"),
+    )
+)]
+pub async fn get_team(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+    Path(team_id): Path<String>,
+) -> Result<impl IntoResponse> {
+    let team = teams::get_team(&pool, &team_id, user.visible_team_ids())
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    Ok(Json(team))
+}
@@ -0,0 +1,35 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use database::{logic::teams, schemas::users::User, PgPool};
+
+use crate::{Error, Result};
+
+/// Get the members of a team
+#[utoipa::path(
+    get,
+    path = "/teams/{id}/members",
+    tag = "teams",
+    responses(
+        (status = 200, description = "Team members retrieved successfully", body = [User]),
+        (status = 404, description = "Team not found"),
+    ),
+    params(
+        ("id" = String, Path, description = "Team database ID"),
+    )
+)]
+pub async fn get_team_members(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+    Path(team_id): Path<String>,
+) -> Result<impl IntoResponse> {
+    teams::get_team(&pool, &team_id, user.visible_team_ids())
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let members = teams::get_team_members(&pool, &team_id).await?;
+
+    Ok(Json(members))
+}
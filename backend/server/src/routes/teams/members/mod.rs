@@ -0,0 +1,10 @@
+use axum::{routing::get, Router};
+
+use crate::ServerState;
+
+pub mod get;
+pub mod put;
+
+pub fn router() -> Router<ServerState> {
+    Router::new().route("/", get(get::get_team_members).put(put::put_team_members))
+}
@@ -0,0 +1,53 @@
+use auth::require_permission;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use database::{
+    logic::teams,
+    schemas::users::{Permission, User},
+    PgPool,
+};
+
+use crate::{Error, Result};
+
+/// Sets the members of a team, keeping the existing memberships of the users still part of it
+#[utoipa::path(
+    put,
+    path = "/teams/{id}/members",
+    tag = "teams",
+    responses(
+        (status = 201, description = "Team members updated successfully"),
+        (status = 404, description = "Team not found"),
+        (status = 403, description = "Missing the team_edit permission"),
+    ),
+    params(
+        ("id" = String, Path, description = "Team database ID"),
+    ),
+    request_body(
+        content_type = "application/json", content = Vec<String>, description = "List of user database IDs"
+    )
+)]
+pub async fn put_team_members(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+    Path(team_id): Path<String>,
+    Json(user_ids): Json<Vec<String>>,
+) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::TeamEdit)?;
+
+    teams::get_team(&pool, &team_id, None)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let mut transaction = pool.begin().await?;
+
+    teams::delete_other_team_members(&mut *transaction, &team_id, &user_ids).await?;
+    teams::add_team_members(&mut *transaction, &team_id, &user_ids, &user.id).await?;
+
+    transaction.commit().await?;
+
+    Ok(StatusCode::CREATED)
+}
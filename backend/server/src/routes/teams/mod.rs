@@ -0,0 +1,25 @@
+use axum::{routing::get, Router};
+
+use crate::ServerState;
+
+pub mod delete;
+pub mod get;
+pub mod patch;
+pub mod post;
+
+pub mod members;
+
+pub fn router() -> Router<ServerState> {
+    let team_router = Router::new()
+        .route(
+            "/",
+            get(get::get_team)
+                .delete(delete::delete_team)
+                .patch(patch::patch_team),
+        )
+        .nest("/members", members::router());
+
+    Router::new()
+        .nest("/:id", team_router)
+        .route("/", get(get::get_teams).post(post::create_team))
+}
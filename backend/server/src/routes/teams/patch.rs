@@ -0,0 +1,49 @@
+use auth::require_permission;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use database::{
+    logic::teams,
+    schemas::{
+        teams::UpdateTeam,
+        users::{Permission, User},
+    },
+    PgPool,
+};
+
+use crate::Result;
+
+/// Partially update a team by its ID
+#[utoipa::path(
+    patch,
+    path = "/teams/{id}",
+    tag = "teams",
+    responses(
+        (status = 204, description = "Team updated successfully"),
+        (status = 404, description = "Team not found"),
+        (status = 403, description = "Missing the team_edit permission"),
+    ),
+    params(
+        ("id" = String, Path, description = "Team database ID"),
+        UpdateTeam
+    )
+)]
+pub async fn patch_team(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+    Path(team_id): Path<String>,
+    Json(data): Json<UpdateTeam>,
+) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::TeamEdit)?;
+
+    let num_affected = teams::update_team(&pool, &team_id, data, &user.id).await?;
+
+    if num_affected > 0 {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Ok(StatusCode::NOT_FOUND)
+    }
+}
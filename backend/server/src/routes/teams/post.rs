@@ -0,0 +1,58 @@
+use auth::require_permission;
+use axum::{extract::State, response::IntoResponse, Extension, Json};
+use database::{
+    logic::teams,
+    schemas::{
+        teams::CreateTeam,
+        users::{Permission, User},
+    },
+    PgPool,
+};
+
+use crate::Result;
+
+/// Create a new team
+#[utoipa::path(
+    post,
+    path = "/teams",
+    tag = "teams",
+    responses(
+        (status = 200, description = "Team created successfully", body = IdSlug),
+        (status = 403, description = "Missing the team_edit permission"),
+        (status = 409, description = "Team with the same name already exists"),
+    ),
+    request_body(
+        description = "Team to create", content_type = "application/json", content = CreateTeam
+    )
+)]
+pub async fn create_team(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+    Json(team): Json<CreateTeam>,
+) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::TeamEdit)?;
+
+    let created_team = teams::create_team(&pool, team, &user.id).await?;
+
+    Ok(Json(created_team))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::*;
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_source_editor_when_creating_team_then_returns_403_error(pool: PgPool) {
+        let response = json_request_with_roles(
+            Method::POST,
+            "/api/v1/teams",
+            pool,
+            json!({"name": "Blue team", "description": "Defenders"}),
+            &[Role::SourceEditor],
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+}
@@ -0,0 +1,91 @@
+use auth::require_permission;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+};
+use database::{
+    logic::users,
+    schemas::users::{Permission, UpdateUser, User},
+};
+
+use crate::{
+    emails::{send_account_approved, Recipient},
+    Error, Result, ServerState,
+};
+
+/// Approve a user waiting for an admin to enable their account, notifying them by email
+#[utoipa::path(
+    post,
+    path = "/users/{id}/approve",
+    tag = "users",
+    responses(
+        (status = 204, description = "User approved successfully"),
+        (status = 404, description = "User not found"),
+        (status = 409, description = "User already enabled"),
+        (status = 403, description = "Missing the user_edit permission"),
+    ),
+    params(
+        ("id" = String, Path, description = "User database ID"),
+    )
+)]
+pub async fn approve_user(
+    State(state): State<ServerState>,
+    Extension(user): Extension<User>,
+    Path(user_id): Path<String>,
+) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::UserEdit)?;
+
+    let Some(approved_user) = users::get_user(&state.pool, &user_id).await? else {
+        return Err(Error::NotFound);
+    };
+
+    if approved_user.enabled {
+        return Err(Error::Conflict("User already enabled".to_string()));
+    }
+
+    let update_user = UpdateUser {
+        enabled: Some(true),
+        roles: None,
+        owner_user_id: None,
+    };
+    users::update_user(&state.pool, &user_id, &update_user, &user.id).await?;
+
+    let recipient = Recipient {
+        user_id: &approved_user.id,
+        name: &approved_user.name,
+        email: &approved_user.email,
+    };
+    send_account_approved(&state, &recipient).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use database::logic::users;
+
+    use crate::test_utils::*;
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_enabled_user_when_approving_then_returns_409_error(pool: PgPool) {
+        let response = request(Method::GET, "/api/v1/health", pool.clone()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let user = users::get_user_from_email(&pool, "test@test.test")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(user.enabled);
+
+        let response = request(
+            Method::POST,
+            &format!("/api/v1/users/{}/approve", user.id),
+            pool,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+}
@@ -1,12 +1,49 @@
+use auth::require_self_or_permission;
 use axum::{
     extract::{Path, State},
     http::StatusCode,
     response::IntoResponse,
+    Extension,
+};
+use database::{
+    logic::api_tokens,
+    schemas::users::{Permission, User},
+    PgPool,
 };
-use database::{logic::api_tokens, PgPool};
 
 use crate::Result;
 
+/// Delete an API token of a user
+#[utoipa::path(
+    delete,
+    path = "/users/{id}/apiTokens/{token_id}",
+    tag = "apiTokens",
+    responses(
+        (status = 204, description = "API token deleted successfully"),
+        (status = 404, description = "API token not found"),
+        (status = 403, description = "Missing the user_edit permission to access another user"),
+    ),
+    params(
+        ("id" = String, Path, description = "User database ID"),
+        ("token_id" = String, Path, description = "API token database ID"),
+    )
+)]
+pub async fn delete_user_api_token(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+    Path((id, token_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse> {
+    require_self_or_permission(&user, &id, Permission::UserEdit)?;
+
+    let num_affected = api_tokens::delete_api_token(&pool, &token_id, &id).await?;
+
+    if num_affected > 0 {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Ok(StatusCode::NOT_FOUND)
+    }
+}
+
 /// Delete all API tokens for a user
 #[utoipa::path(
     delete,
@@ -15,6 +52,7 @@ use crate::Result;
     responses(
         (status = 204, description = "User's API tokens deleted successfully"),
         (status = 404, description = "User not found"),
+        (status = 403, description = "Missing the user_edit permission to access another user"),
     ),
     params(
         ("id" = String, Path, description = "User database ID"),
@@ -22,8 +60,11 @@ use crate::Result;
 )]
 pub async fn delete_user_api_tokens(
     State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
     Path(id): Path<String>,
 ) -> Result<impl IntoResponse> {
+    require_self_or_permission(&user, &id, Permission::UserEdit)?;
+
     let num_affected = api_tokens::delete_all_user_api_tokens(&pool, &id).await?;
 
     if num_affected > 0 {
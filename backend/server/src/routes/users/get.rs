@@ -1,10 +1,12 @@
+use auth::{require_permission, require_self_or_permission};
 use axum::{
     extract::{Path, State},
     response::IntoResponse,
-    Json,
+    Extension, Json,
 };
 use database::{
     logic::{api_tokens, users},
+    schemas::users::{Permission, User},
     PgPool,
 };
 
@@ -16,10 +18,16 @@ use crate::{Error, Result};
     path = "/users",
     tag = "users",
     responses(
-        (status = 200, description = "List of users", body = [UserWithNumLogs])
+        (status = 200, description = "List of users", body = [UserWithNumLogs]),
+        (status = 403, description = "Missing the user_view permission"),
     )
 )]
-pub async fn get_users(State(pool): State<PgPool>) -> Result<impl IntoResponse> {
+pub async fn get_users(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::UserView)?;
+
     let users = users::get_users(&pool).await?;
 
     Ok(Json(users))
@@ -31,7 +39,8 @@ pub async fn get_users(State(pool): State<PgPool>) -> Result<impl IntoResponse>
     path = "/users/{id}/logs",
     tag = "users",
     responses(
-        (status = 200, description = "List of logs", body = [DbUserLog])
+        (status = 200, description = "List of logs", body = [DbUserLog]),
+        (status = 403, description = "Missing the user_view permission to access another user"),
     ),
     params(
         ("id" = String, Path, description = "User database ID"),
@@ -39,8 +48,11 @@ pub async fn get_users(State(pool): State<PgPool>) -> Result<impl IntoResponse>
 )]
 pub async fn get_user_logs(
     State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
     Path(user_id): Path<String>,
 ) -> Result<impl IntoResponse> {
+    require_self_or_permission(&user, &user_id, Permission::UserView)?;
+
     let logs = users::get_user_logs(&pool, &user_id).await?;
 
     Ok(Json(logs))
@@ -54,6 +66,7 @@ pub async fn get_user_logs(
     responses(
         (status = 200, description = "User retrieved successfully", body = User),
         (status = 404, description = "User not found"),
+        (status = 403, description = "Missing the user_view permission to access another user"),
     ),
     params(
         ("id" = String, Path, description = "User database ID"),
@@ -61,8 +74,11 @@ pub async fn get_user_logs(
 )]
 pub async fn get_user(
     State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
     Path(user_id): Path<String>,
 ) -> Result<impl IntoResponse> {
+    require_self_or_permission(&user, &user_id, Permission::UserView)?;
+
     let user = users::get_user(&pool, &user_id)
         .await?
         .ok_or(Error::NotFound)?;
@@ -76,7 +92,8 @@ pub async fn get_user(
     path = "/users/{id}/apiTokens",
     tag = "apiTokens",
     responses(
-        (status = 200, description = "List of users' API tokens", body = [ApiToken])
+        (status = 200, description = "List of users' API tokens", body = [ApiToken]),
+        (status = 403, description = "Missing the user_view permission to access another user"),
     ),
     params(
         ("id" = String, Path, description = "User database ID"),
@@ -84,9 +101,106 @@ pub async fn get_user(
 )]
 pub async fn get_user_api_tokens(
     State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
     Path(id): Path<String>,
 ) -> Result<impl IntoResponse> {
+    require_self_or_permission(&user, &id, Permission::UserView)?;
+
     let users = api_tokens::get_user_api_keys(&pool, &id).await?;
 
     Ok(Json(users))
 }
+
+#[cfg(test)]
+mod tests {
+    use database::{logic::users, schemas::users::CreateUser};
+
+    use crate::test_utils::*;
+
+    async fn get_user_id(pool: &PgPool, email: &str) -> String {
+        sqlx::query_scalar!("SELECT id FROM users WHERE email = $1", email)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_viewer_when_calling_get_users_endpoint_then_returns_403_error(pool: PgPool) {
+        let response =
+            request_with_roles(Method::GET, "/api/v1/users", pool, &[Role::Viewer]).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_viewer_when_calling_get_user_endpoint_then_only_returns_themselves(
+        pool: PgPool,
+    ) {
+        let response =
+            request_with_roles(Method::GET, "/api/v1/health", pool.clone(), &[Role::Viewer]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let viewer_id = get_user_id(&pool, "viewer@test.test").await;
+        let admin_id = get_user_id(&pool, "test@test.test").await;
+
+        let response = request_with_roles(
+            Method::GET,
+            &format!("/api/v1/users/{viewer_id}"),
+            pool.clone(),
+            &[Role::Viewer],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let actual = json_response::<Value>(response).await;
+        assert_eq!(actual["roles"], json!(["viewer"]));
+
+        let response = request_with_roles(
+            Method::GET,
+            &format!("/api/v1/users/{admin_id}"),
+            pool,
+            &[Role::Viewer],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_first_user_when_calling_get_users_endpoint_then_user_is_not_an_admin(
+        pool: PgPool,
+    ) {
+        let create_user = CreateUser {
+            auth_id: None,
+            provider: "mock".to_string(),
+            enabled: false,
+            email: "jane@example.com".to_string(),
+            verified: true,
+            name: "jane".to_string(),
+            given_name: None,
+            family_name: None,
+            locale: None,
+            picture: None,
+            roles: Default::default(),
+            replace_roles: false,
+            hashed_password: None,
+        };
+        users::create_or_update_user(&pool, &create_user)
+            .await
+            .unwrap();
+
+        let response = request(Method::GET, "/api/v1/users", pool).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let actual = json_response::<Value>(response).await;
+        let user = actual
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|user| user["user"]["email"] == "jane@example.com")
+            .unwrap();
+        assert_eq!(user["user"]["roles"], json!([]));
+        assert_eq!(user["user"]["enabled"], false);
+    }
+}
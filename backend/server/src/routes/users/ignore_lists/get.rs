@@ -1,9 +1,14 @@
+use auth::require_self_or_permission;
 use axum::{
     extract::{Path, State},
     response::IntoResponse,
-    Json,
+    Extension, Json,
+};
+use database::{
+    logic::ignore_lists,
+    schemas::users::{Permission, User},
+    PgPool,
 };
-use database::{logic::ignore_lists, PgPool};
 
 use crate::Result;
 
@@ -14,6 +19,7 @@ use crate::Result;
     tag = "users",
     responses(
         (status = 200, description = "User's ignore lists retrieved successfully", body = [IgnoreList]),
+        (status = 403, description = "Missing the user_view permission to access another user"),
     ),
     params(
         ("id" = String, Path, description = "User database ID"),
@@ -21,9 +27,13 @@ use crate::Result;
 )]
 pub async fn get_user_ignore_lists(
     State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
     Path(user_id): Path<String>,
 ) -> Result<impl IntoResponse> {
-    let ignore_lists = ignore_lists::get_user_ignore_lists(&pool, &user_id).await?;
+    require_self_or_permission(&user, &user_id, Permission::UserView)?;
+
+    let ignore_lists =
+        ignore_lists::get_user_ignore_lists(&pool, &user_id, user.visible_team_ids()).await?;
 
     Ok(Json(ignore_lists))
 }
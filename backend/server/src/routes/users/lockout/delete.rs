@@ -0,0 +1,48 @@
+use auth::require_permission;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+};
+use database::{
+    logic::users,
+    schemas::users::{Permission, User},
+};
+
+use crate::{Error, Result, ServerState};
+
+/// Unlock the account of a user locked after too many failed logins and forget these failed
+/// logins
+#[utoipa::path(
+    delete,
+    path = "/users/{id}/lockout",
+    tag = "users",
+    responses(
+        (status = 204, description = "Account unlocked successfully"),
+        (status = 403, description = "Missing the user_edit permission"),
+        (status = 404, description = "User not found"),
+    ),
+    params(
+        ("id" = String, Path, description = "User database ID"),
+    ),
+)]
+pub async fn delete_user_lockout(
+    State(state): State<ServerState>,
+    Extension(user): Extension<User>,
+    Path(user_id): Path<String>,
+) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::UserEdit)?;
+
+    let Some(target_user) = users::get_user(&state.pool, &user_id).await? else {
+        return Err(Error::NotFound);
+    };
+
+    state
+        .auth_state
+        .login_throttle
+        .unlock_account(&target_user.email)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
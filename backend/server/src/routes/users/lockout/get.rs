@@ -0,0 +1,74 @@
+use auth::require_self_or_permission;
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use database::{
+    logic::users,
+    schemas::users::{Permission, User},
+};
+
+use crate::{Error, Result, ServerState};
+
+/// Get the recent failed logins of a user and whether their account is locked because of them
+#[utoipa::path(
+    get,
+    path = "/users/{id}/lockout",
+    tag = "users",
+    responses(
+        (status = 200, description = "Failed logins of the user", body = FailedLogins),
+        (status = 403, description = "Missing the user_view permission"),
+        (status = 404, description = "User not found"),
+    ),
+    params(
+        ("id" = String, Path, description = "User database ID"),
+    ),
+)]
+pub async fn get_user_lockout(
+    State(state): State<ServerState>,
+    Extension(user): Extension<User>,
+    Path(user_id): Path<String>,
+) -> Result<impl IntoResponse> {
+    require_self_or_permission(&user, &user_id, Permission::UserView)?;
+
+    let Some(target_user) = users::get_user(&state.pool, &user_id).await? else {
+        return Err(Error::NotFound);
+    };
+
+    let failed_logins = state
+        .auth_state
+        .login_throttle
+        .get_account_failed_logins(&target_user.email)
+        .await?;
+
+    Ok(Json(failed_logins))
+}
+
+#[cfg(test)]
+mod tests {
+    use database::logic::users;
+
+    use crate::test_utils::*;
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_viewer_when_getting_other_user_lockout_then_returns_403_error(pool: PgPool) {
+        let response = request(Method::GET, "/api/v1/health", pool.clone()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let admin = users::get_user_from_email(&pool, "test@test.test")
+            .await
+            .unwrap()
+            .unwrap();
+
+        let response = request_with_roles(
+            Method::GET,
+            &format!("/api/v1/users/{}/lockout", admin.id),
+            pool,
+            &[Role::Viewer],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+}
@@ -0,0 +1,13 @@
+use axum::{routing::get, Router};
+
+use crate::ServerState;
+
+pub mod delete;
+pub mod get;
+
+pub fn router() -> Router<ServerState> {
+    Router::new().route(
+        "/",
+        get(get::get_user_lockout).delete(delete::delete_user_lockout),
+    )
+}
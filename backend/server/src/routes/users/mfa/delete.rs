@@ -0,0 +1,75 @@
+use auth::{mfa, require_self_or_permission};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+};
+use database::{
+    logic::mfa as mfa_logic,
+    schemas::users::{Permission, User},
+    PgPool,
+};
+
+use crate::{Error, Result};
+
+/// Remove the TOTP of a user, such as when they lost their authenticator app
+#[utoipa::path(
+    delete,
+    path = "/users/{id}/mfa/totp",
+    tag = "users",
+    responses(
+        (status = 204, description = "TOTP removed"),
+        (status = 403, description = "Missing the user_edit permission"),
+        (status = 404, description = "User without TOTP"),
+    ),
+    params(
+        ("id" = String, Path, description = "User database ID"),
+    ),
+)]
+pub async fn delete_user_totp(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+    Path(user_id): Path<String>,
+) -> Result<impl IntoResponse> {
+    require_self_or_permission(&user, &user_id, Permission::UserEdit)?;
+
+    if mfa_logic::delete_user_totp(&pool, &user_id).await? == 0 {
+        return Err(Error::NotFound);
+    }
+
+    mfa::cleanup_recovery_codes(&pool, &user_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Remove a security key of a user
+#[utoipa::path(
+    delete,
+    path = "/users/{id}/mfa/webauthn/{credential_id}",
+    tag = "users",
+    responses(
+        (status = 204, description = "Security key removed"),
+        (status = 403, description = "Missing the user_edit permission"),
+        (status = 404, description = "Security key not found"),
+    ),
+    params(
+        ("id" = String, Path, description = "User database ID"),
+        ("credential_id" = String, Path, description = "Security key database ID"),
+    ),
+)]
+pub async fn delete_user_webauthn_credential(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+    Path((user_id, credential_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse> {
+    require_self_or_permission(&user, &user_id, Permission::UserEdit)?;
+
+    if mfa_logic::delete_webauthn_credential(&pool, &credential_id, &user_id).await? == 0 {
+        return Err(Error::NotFound);
+    }
+
+    mfa::cleanup_recovery_codes(&pool, &user_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,72 @@
+use auth::require_self_or_permission;
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use database::{
+    logic::mfa,
+    schemas::users::{Permission, User},
+    PgPool,
+};
+
+use crate::{schemas::MfaStatus, Result};
+
+/// Get the second factors enrolled by a user
+#[utoipa::path(
+    get,
+    path = "/users/{id}/mfa",
+    tag = "users",
+    responses(
+        (status = 200, description = "Second factors of the user", body = MfaStatus),
+        (status = 403, description = "Missing the user_view permission"),
+    ),
+    params(
+        ("id" = String, Path, description = "User database ID"),
+    ),
+)]
+pub async fn get_user_mfa(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+    Path(user_id): Path<String>,
+) -> Result<impl IntoResponse> {
+    require_self_or_permission(&user, &user_id, Permission::UserView)?;
+
+    let totp_enabled = mfa::get_user_totp(&pool, &user_id)
+        .await?
+        .is_some_and(|totp| totp.enabled);
+    let recovery_codes_left = mfa::count_unused_user_recovery_codes(&pool, &user_id).await?;
+    let webauthn_credentials = mfa::get_user_webauthn_credentials(&pool, &user_id).await?;
+
+    Ok(Json(MfaStatus {
+        totp_enabled,
+        recovery_codes_left,
+        webauthn_credentials,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use database::logic::users;
+
+    use crate::test_utils::*;
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_user_without_mfa_when_getting_mfa_then_returns_nothing_enrolled(pool: PgPool) {
+        let response = request(Method::GET, "/api/v1/health", pool.clone()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let user = users::get_user_from_email(&pool, "test@test.test")
+            .await
+            .unwrap()
+            .unwrap();
+
+        let response = request(Method::GET, &format!("/api/v1/users/{}/mfa", user.id), pool).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            json_response::<Value>(response).await,
+            json!({ "totpEnabled": false, "recoveryCodesLeft": 0, "webauthnCredentials": [] })
+        );
+    }
+}
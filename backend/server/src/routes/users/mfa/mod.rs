@@ -0,0 +1,43 @@
+use axum::{
+    routing::{delete, get, post},
+    Router,
+};
+use database::schemas::users::User;
+
+use crate::{Error, Result, ServerState};
+
+pub mod delete;
+pub mod get;
+pub mod post;
+pub mod put;
+
+/// Second factors can only be enrolled by the user themselves, even admins being limited to
+/// removing them
+fn require_self(user: &User, user_id: &str) -> Result<()> {
+    if user.id == user_id {
+        Ok(())
+    } else {
+        Err(Error::Forbidden)
+    }
+}
+
+pub fn router() -> Router<ServerState> {
+    Router::new()
+        .route("/", get(get::get_user_mfa))
+        .route(
+            "/totp",
+            post(post::setup_user_totp)
+                .put(put::confirm_user_totp)
+                .delete(delete::delete_user_totp),
+        )
+        .route("/recoveryCodes", post(post::regenerate_user_recovery_codes))
+        .route("/webauthn", post(post::register_user_webauthn_credential))
+        .route(
+            "/webauthn/options",
+            post(post::get_user_webauthn_creation_options),
+        )
+        .route(
+            "/webauthn/:credential_id",
+            delete(delete::delete_user_webauthn_credential),
+        )
+}
@@ -0,0 +1,185 @@
+use auth::{
+    mfa,
+    openid::parse_referer_origin,
+    password::{consume_user_token, create_user_token},
+    webauthn::{RelyingParty, WebAuthnRegistration},
+};
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    response::IntoResponse,
+    Extension, Json,
+};
+use database::{
+    logic::mfa as mfa_logic,
+    schemas::{auth::UserTokenKind, mfa::CreateWebAuthnCredential, users::User},
+};
+
+use super::require_self;
+use crate::{schemas::RecoveryCodesResponse, Error, Result, ServerState};
+
+/// Start the TOTP enrollment of the logged in user, needing to be confirmed with a code
+#[utoipa::path(
+    post,
+    path = "/users/{id}/mfa/totp",
+    tag = "users",
+    responses(
+        (status = 200, description = "TOTP secret to add to an authenticator app", body = TotpSetup),
+        (status = 400, description = "TOTP already enabled"),
+        (status = 403, description = "Enrolling TOTP for another user"),
+    ),
+    params(
+        ("id" = String, Path, description = "User database ID"),
+    ),
+)]
+pub async fn setup_user_totp(
+    State(state): State<ServerState>,
+    Extension(user): Extension<User>,
+    Path(user_id): Path<String>,
+) -> Result<impl IntoResponse> {
+    require_self(&user, &user_id)?;
+
+    let setup = mfa::setup_totp(
+        &state.pool,
+        &state.crypto,
+        &state.config.auth.mfa.issuer,
+        &user.id,
+        &user.email,
+    )
+    .await?;
+
+    Ok(Json(setup))
+}
+
+/// Replace the recovery codes of the logged in user
+#[utoipa::path(
+    post,
+    path = "/users/{id}/mfa/recoveryCodes",
+    tag = "users",
+    responses(
+        (status = 200, description = "New recovery codes", body = RecoveryCodesResponse),
+        (status = 400, description = "User without a second factor"),
+        (status = 403, description = "Replacing the recovery codes of another user"),
+    ),
+    params(
+        ("id" = String, Path, description = "User database ID"),
+    ),
+)]
+pub async fn regenerate_user_recovery_codes(
+    State(state): State<ServerState>,
+    Extension(user): Extension<User>,
+    Path(user_id): Path<String>,
+) -> Result<impl IntoResponse> {
+    require_self(&user, &user_id)?;
+
+    if !mfa_logic::user_has_mfa(&state.pool, &user.id).await? {
+        return Err(Error::BadRequest(
+            "User without a second factor".to_string(),
+        ));
+    }
+
+    let recovery_codes = mfa::generate_recovery_codes(&state.pool, &user.id).await?;
+
+    Ok(Json(RecoveryCodesResponse { recovery_codes }))
+}
+
+/// Get the options to register a new security key for the logged in user
+#[utoipa::path(
+    post,
+    path = "/users/{id}/mfa/webauthn/options",
+    tag = "users",
+    responses(
+        (status = 200, description = "Options to pass to the browser", body = WebAuthnCreationOptions),
+        (status = 403, description = "Registering a security key for another user"),
+    ),
+    params(
+        ("id" = String, Path, description = "User database ID"),
+    ),
+)]
+pub async fn get_user_webauthn_creation_options(
+    State(state): State<ServerState>,
+    Extension(user): Extension<User>,
+    Path(user_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse> {
+    require_self(&user, &user_id)?;
+
+    let origin = parse_referer_origin(&headers, &state.config.auth)?;
+    let relying_party = RelyingParty::new(&origin, &state.config.auth.mfa.issuer)?;
+
+    let challenge = create_user_token(
+        &state.pool,
+        &user.id,
+        UserTokenKind::WebauthnRegistration,
+        state.config.auth.mfa.webauthn_registration_expiration,
+    )
+    .await?;
+    let credentials = mfa_logic::get_user_webauthn_credentials(&state.pool, &user.id).await?;
+
+    Ok(Json(relying_party.creation_options(
+        &challenge,
+        &user.id,
+        &user.email,
+        &user.name,
+        &credentials,
+    )))
+}
+
+/// Register a new security key for the logged in user
+#[utoipa::path(
+    post,
+    path = "/users/{id}/mfa/webauthn",
+    tag = "users",
+    responses(
+        (status = 200, description = "Security key registered, with recovery codes if it is the first second factor", body = RecoveryCodesResponse),
+        (status = 400, description = "Invalid or unsupported security key"),
+        (status = 401, description = "Invalid or expired challenge"),
+        (status = 403, description = "Registering a security key for another user"),
+    ),
+    params(
+        ("id" = String, Path, description = "User database ID"),
+    ),
+    request_body(
+        description = "Security key created by the browser",
+        content_type = "application/json",
+        content = WebAuthnRegistration
+    )
+)]
+pub async fn register_user_webauthn_credential(
+    State(state): State<ServerState>,
+    Extension(user): Extension<User>,
+    Path(user_id): Path<String>,
+    headers: HeaderMap,
+    Json(data): Json<WebAuthnRegistration>,
+) -> Result<impl IntoResponse> {
+    require_self(&user, &user_id)?;
+
+    let origin = parse_referer_origin(&headers, &state.config.auth)?;
+    let relying_party = RelyingParty::new(&origin, &state.config.auth.mfa.issuer)?;
+
+    let challenge = relying_party.client_challenge(&data.client_data_json, "webauthn.create")?;
+    let challenge_user_id =
+        consume_user_token(&state.pool, &challenge, UserTokenKind::WebauthnRegistration).await?;
+
+    if challenge_user_id != user.id {
+        return Err(Error::Forbidden);
+    }
+
+    let credential = relying_party.verify_registration(&data)?;
+
+    mfa_logic::create_webauthn_credential(
+        &state.pool,
+        &CreateWebAuthnCredential {
+            user_id: user.id.clone(),
+            name: data.name,
+            credential_id: credential.credential_id,
+            public_key: credential.public_key,
+            sign_count: credential.sign_count.into(),
+        },
+    )
+    .await?;
+
+    let recovery_codes = mfa::ensure_recovery_codes(&state.pool, &user.id).await?;
+
+    Ok(Json(RecoveryCodesResponse { recovery_codes }))
+}
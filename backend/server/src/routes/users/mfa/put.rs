@@ -0,0 +1,49 @@
+use auth::mfa;
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use database::schemas::users::User;
+
+use super::require_self;
+use crate::{
+    schemas::{RecoveryCodesResponse, TotpCodeRequest},
+    Error, Result, ServerState,
+};
+
+/// Confirm the TOTP enrollment of the logged in user with a code of the authenticator app
+#[utoipa::path(
+    put,
+    path = "/users/{id}/mfa/totp",
+    tag = "users",
+    responses(
+        (status = 200, description = "TOTP enabled, with recovery codes if it is the first second factor", body = RecoveryCodesResponse),
+        (status = 401, description = "Invalid code or no pending enrollment"),
+        (status = 403, description = "Enrolling TOTP for another user"),
+    ),
+    params(
+        ("id" = String, Path, description = "User database ID"),
+    ),
+    request_body(
+        description = "Code shown by the authenticator app",
+        content_type = "application/json",
+        content = TotpCodeRequest
+    )
+)]
+pub async fn confirm_user_totp(
+    State(state): State<ServerState>,
+    Extension(user): Extension<User>,
+    Path(user_id): Path<String>,
+    Json(data): Json<TotpCodeRequest>,
+) -> Result<impl IntoResponse> {
+    require_self(&user, &user_id)?;
+
+    if !mfa::confirm_totp(&state.pool, &state.crypto, &user.id, &data.code).await? {
+        return Err(Error::InvalidCredentials);
+    }
+
+    let recovery_codes = mfa::ensure_recovery_codes(&state.pool, &user.id).await?;
+
+    Ok(Json(RecoveryCodesResponse { recovery_codes }))
+}
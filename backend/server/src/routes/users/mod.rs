@@ -1,30 +1,66 @@
-use axum::{routing::get, Router};
+use axum::{
+    routing::{delete, get, post},
+    Router,
+};
+use database::{logic::users, PgPool};
 
-use crate::ServerState;
+use crate::{Error, Result, ServerState};
 
 pub mod delete;
 pub mod get;
 pub mod patch;
+pub mod post;
 
+pub mod approve;
 pub mod ignore_lists;
+pub mod lockout;
+pub mod mfa;
+pub mod password;
 pub mod providers;
 pub mod requests;
+pub mod sessions;
 pub mod sources;
+pub mod teams;
 
 pub fn router() -> Router<ServerState> {
     let user_router = Router::new()
         .route("/logs", get(get::get_user_logs))
         .route(
             "/apiTokens",
-            get(get::get_user_api_tokens).delete(delete::delete_user_api_tokens),
+            get(get::get_user_api_tokens)
+                .post(post::create_service_account_api_token)
+                .delete(delete::delete_user_api_tokens),
+        )
+        .route(
+            "/apiTokens/:token_id",
+            delete(delete::delete_user_api_token),
         )
         .route("/", get(get::get_user).patch(patch::update_user))
+        .nest("/approve", approve::router())
         .nest("/ignoreLists", ignore_lists::router())
+        .nest("/lockout", lockout::router())
+        .nest("/mfa", mfa::router())
+        .nest("/password", password::router())
         .nest("/providers", providers::router())
         .nest("/requests", requests::router())
-        .nest("/sources", sources::router());
+        .nest("/sessions", sessions::router())
+        .nest("/sources", sources::router())
+        .nest("/teams", teams::router());
 
     Router::new()
         .route("/", get(get::get_users))
+        .route("/serviceAccounts", post(post::create_service_account))
         .nest("/:id", user_router)
 }
+
+/// Ensures that the user set as responsible for a service account exists
+async fn require_owner(pool: &PgPool, owner_user_id: Option<&str>) -> Result<()> {
+    let Some(owner_user_id) = owner_user_id else {
+        return Ok(());
+    };
+
+    match users::get_user(pool, owner_user_id).await? {
+        Some(owner) if !owner.service_account => Ok(()),
+        _ => Err(Error::BadRequest("Owner not found".to_string())),
+    }
+}
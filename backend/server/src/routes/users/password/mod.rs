@@ -0,0 +1,9 @@
+use axum::{routing::put, Router};
+
+use crate::ServerState;
+
+pub mod put;
+
+pub fn router() -> Router<ServerState> {
+    Router::new().route("/", put(put::change_password))
+}
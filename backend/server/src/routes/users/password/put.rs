@@ -0,0 +1,118 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use database::{logic::users, schemas::users::User};
+use shared::crypto::{hash_password, verify_password};
+
+use crate::{schemas::ChangePasswordRequest, Error, Result, ServerState};
+
+/// Change the password of the logged in user
+#[utoipa::path(
+    put,
+    path = "/users/{id}/password",
+    tag = "users",
+    responses(
+        (status = 204, description = "Password changed successfully"),
+        (status = 400, description = "User not using password authentication or new password not meeting the password policy"),
+        (status = 401, description = "Invalid current password"),
+        (status = 403, description = "Changing the password of another user"),
+    ),
+    params(
+        ("id" = String, Path, description = "User database ID"),
+    ),
+    request_body(
+        description = "Current and new passwords",
+        content_type = "application/json",
+        content = ChangePasswordRequest
+    )
+)]
+pub async fn change_password(
+    State(state): State<ServerState>,
+    Extension(user): Extension<User>,
+    Path(user_id): Path<String>,
+    Json(data): Json<ChangePasswordRequest>,
+) -> Result<impl IntoResponse> {
+    // Not even admins can change the password of other users, who can reset it by email
+    if user.id != user_id {
+        return Err(Error::Forbidden);
+    }
+
+    let Some(password) = users::get_user_with_password(&state.pool, &user.id)
+        .await?
+        .and_then(|user| user.password)
+    else {
+        return Err(Error::BadRequest(
+            "User not using password authentication".to_string(),
+        ));
+    };
+
+    if !verify_password(&data.current_password, &password)? {
+        return Err(Error::InvalidCredentials);
+    }
+
+    state
+        .config
+        .auth
+        .password
+        .policy
+        .validate(&data.new_password)?;
+
+    users::update_user_password(&state.pool, &user.id, &hash_password(&data.new_password)?).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use database::logic::users;
+
+    use crate::test_utils::*;
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_other_user_when_changing_password_then_returns_403_error(pool: PgPool) {
+        let response =
+            request_with_roles(Method::GET, "/api/v1/health", pool.clone(), &[Role::Viewer]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let viewer = users::get_user_from_email(&pool, "viewer@test.test")
+            .await
+            .unwrap()
+            .unwrap();
+
+        let response = json_request(
+            Method::PUT,
+            &format!("/api/v1/users/{}/password", viewer.id),
+            pool,
+            json!({ "currentPassword": "password", "newPassword": "a-new-long-password" }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_user_without_password_when_changing_password_then_returns_400_error(
+        pool: PgPool,
+    ) {
+        let response = request(Method::GET, "/api/v1/health", pool.clone()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let user = users::get_user_from_email(&pool, "test@test.test")
+            .await
+            .unwrap()
+            .unwrap();
+
+        let response = json_request(
+            Method::PUT,
+            &format!("/api/v1/users/{}/password", user.id),
+            pool,
+            json!({ "currentPassword": "password", "newPassword": "a-new-long-password" }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -1,3 +1,4 @@
+use auth::require_permission;
 use axum::{
     extract::{Path, State},
     http::StatusCode,
@@ -6,10 +7,11 @@ use axum::{
 };
 use database::{
     logic::users,
-    schemas::users::{UpdateUser, User},
+    schemas::users::{Permission, UpdateUser, User},
     PgPool,
 };
 
+use super::require_owner;
 use crate::Result;
 
 /// Update a user by ID
@@ -19,7 +21,9 @@ use crate::Result;
     tag = "users",
     responses(
         (status = 204, description = "User updated successfully"),
+        (status = 400, description = "Owner not found"),
         (status = 404, description = "User not found"),
+        (status = 403, description = "Missing the user_edit permission"),
     ),
     params(
         ("id" = String, Path, description = "User database ID"),
@@ -36,6 +40,9 @@ pub async fn update_user(
     Path(user_id): Path<String>,
     Json(update_user): Json<UpdateUser>,
 ) -> Result<impl IntoResponse> {
+    require_permission(&user, Permission::UserEdit)?;
+    require_owner(&pool, update_user.owner_user_id.as_deref()).await?;
+
     users::update_user(&pool, &user_id, &update_user, &user.id).await?;
 
     Ok(StatusCode::NO_CONTENT)
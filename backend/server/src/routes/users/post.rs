@@ -0,0 +1,247 @@
+use auth::{
+    require_permission,
+    scope::{require_unscoped, validate_ip_ranges},
+};
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use database::{
+    logic::{api_tokens, users},
+    schemas::{
+        api_tokens::CreateApiToken,
+        users::{CreateServiceAccount, Permission, User},
+    },
+    PgPool,
+};
+use shared::crypto::{hash_password, Crypto};
+
+use super::require_owner;
+use crate::{schemas::CreatedApiToken, Error, Result};
+
+/// Create a service account, only able to authenticate with the API tokens created for it
+#[utoipa::path(
+    post,
+    path = "/users/serviceAccounts",
+    tag = "users",
+    responses(
+        (status = 200, description = "Service account created successfully", body = User),
+        (status = 400, description = "Owner not found"),
+        (status = 403, description = "Missing the user_edit permission or authenticated with a restricted API token"),
+        (status = 409, description = "Service account with the same name already exists"),
+    ),
+    request_body(
+        description = "Service account to create",
+        content_type = "application/json",
+        content = CreateServiceAccount
+    )
+)]
+pub async fn create_service_account(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+    Json(data): Json<CreateServiceAccount>,
+) -> Result<impl IntoResponse> {
+    require_unscoped(&user)?;
+    require_permission(&user, Permission::UserEdit)?;
+    require_owner(&pool, data.owner_user_id.as_deref()).await?;
+
+    let service_account = users::create_service_account(&pool, &data, &user.id).await?;
+
+    Ok(Json(service_account))
+}
+
+/// Create an API token for a service account
+#[utoipa::path(
+    post,
+    path = "/users/{id}/apiTokens",
+    tag = "apiTokens",
+    responses(
+        (status = 200, description = "Database ID and value of the API token", body = CreatedApiToken),
+        (status = 400, description = "User is not a service account or invalid IP address or CIDR range"),
+        (status = 403, description = "Missing the user_edit permission or authenticated with a restricted API token"),
+        (status = 404, description = "User not found"),
+    ),
+    params(
+        ("id" = String, Path, description = "Service account database ID"),
+    ),
+    request_body(
+        description = "API token to create", content_type = "application/json", content = CreateApiToken
+    )
+)]
+pub async fn create_service_account_api_token(
+    State(pool): State<PgPool>,
+    State(crypto): State<Crypto>,
+    Extension(user): Extension<User>,
+    Path(id): Path<String>,
+    Json(data): Json<CreateApiToken>,
+) -> Result<impl IntoResponse> {
+    require_unscoped(&user)?;
+    require_permission(&user, Permission::UserEdit)?;
+
+    let Some(service_account) = users::get_user(&pool, &id).await? else {
+        return Err(Error::NotFound);
+    };
+
+    // Other users create their own tokens through `/apiTokens`
+    if !service_account.service_account {
+        return Err(Error::BadRequest(
+            "User is not a service account".to_string(),
+        ));
+    }
+
+    if let Some(ranges) = &data.allowed_ip_ranges {
+        validate_ip_ranges(ranges)?;
+    }
+
+    let token = crypto.generate_random_alphanumeric_string(32);
+    let hashed_token = hash_password(&token)?;
+
+    let id =
+        api_tokens::update_or_create_api_token(&pool, data, &service_account.id, &hashed_token)
+            .await?;
+
+    Ok(Json(CreatedApiToken { id, token }))
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, USER_AGENT};
+
+    use crate::{state::test::create_state, test_utils::*};
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_service_account_when_using_its_api_token_then_requests_are_attributed_to_it(
+        pool: PgPool,
+    ) {
+        let response = json_request(
+            Method::POST,
+            "/api/v1/users/serviceAccounts",
+            pool.clone(),
+            json!({ "name": "SOAR", "roles": ["analyst"] }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let service_account = json_response::<Value>(response).await;
+        assert_eq!(service_account["serviceAccount"], true);
+        assert_eq!(service_account["email"], "soar@service-account.invalid");
+        let id = service_account["id"].as_str().unwrap();
+
+        let response = json_request(
+            Method::POST,
+            &format!("/api/v1/users/{id}/apiTokens"),
+            pool.clone(),
+            json!({ "note": "playbooks" }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Only the service account itself can get it without the user_view permission
+        let created = json_response::<Value>(response).await;
+        let request = Request::builder()
+            .uri(format!("/api/v1/users/{id}"))
+            .header(
+                AUTHORIZATION,
+                format!(
+                    "Token {}_{}",
+                    created["id"].as_str().unwrap(),
+                    created["token"].as_str().unwrap()
+                ),
+            )
+            .header(USER_AGENT, TEST_USER_AGENT)
+            .body(Body::empty())
+            .unwrap();
+
+        let response = router_with_state(create_state(pool))
+            .oneshot(request)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(json_response::<Value>(response).await["id"], id);
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_human_user_when_creating_api_token_for_them_then_returns_400_error(
+        pool: PgPool,
+    ) {
+        let response = request(Method::GET, "/api/v1/health", pool.clone()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let admin = database::logic::users::get_user_from_email(&pool, "test@test.test")
+            .await
+            .unwrap()
+            .unwrap();
+
+        let response = json_request(
+            Method::POST,
+            &format!("/api/v1/users/{}/apiTokens", admin.id),
+            pool,
+            json!({ "note": "playbooks" }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_restricted_token_when_managing_service_accounts_then_returns_403_error(
+        pool: PgPool,
+    ) {
+        let response = json_request(
+            Method::POST,
+            "/api/v1/apiTokens",
+            pool.clone(),
+            json!({ "note": "soar", "permissions": ["user_edit"] }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let created = json_response::<Value>(response).await;
+        let token = format!(
+            "{}_{}",
+            created["id"].as_str().unwrap(),
+            created["token"].as_str().unwrap()
+        );
+
+        let response = json_request(
+            Method::POST,
+            "/api/v1/users/serviceAccounts",
+            pool.clone(),
+            json!({ "name": "SOAR", "roles": ["analyst"] }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let id = json_response::<Value>(response).await["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        for (uri, body) in [
+            (
+                "/api/v1/users/serviceAccounts".to_string(),
+                json!({ "name": "SIEM", "roles": ["admin"] }),
+            ),
+            (
+                format!("/api/v1/users/{id}/apiTokens"),
+                json!({ "note": "unrestricted" }),
+            ),
+        ] {
+            let request = Request::builder()
+                .method(Method::POST)
+                .uri(uri)
+                .header(AUTHORIZATION, format!("Token {token}"))
+                .header(USER_AGENT, TEST_USER_AGENT)
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+
+            let response = router_with_state(create_state(pool.clone()))
+                .oneshot(request)
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        }
+    }
+}
@@ -1,9 +1,14 @@
+use auth::require_self_or_permission;
 use axum::{
     extract::{Path, State},
     response::IntoResponse,
-    Json,
+    Extension, Json,
+};
+use database::{
+    logic::providers,
+    schemas::users::{Permission, User},
+    PgPool,
 };
-use database::{logic::providers, PgPool};
 
 use crate::Result;
 
@@ -14,6 +19,7 @@ use crate::Result;
     tag = "users",
     responses(
         (status = 200, description = "User's providers retrieved successfully", body = [Provider]),
+        (status = 403, description = "Missing the user_view permission to access another user"),
     ),
     params(
         ("id" = String, Path, description = "User database ID"),
@@ -21,8 +27,11 @@ use crate::Result;
 )]
 pub async fn get_user_providers(
     State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
     Path(user_id): Path<String>,
 ) -> Result<impl IntoResponse> {
+    require_self_or_permission(&user, &user_id, Permission::UserView)?;
+
     let providers = providers::get_user_providers(&pool, &user_id).await?;
 
     Ok(Json(providers))
@@ -1,9 +1,14 @@
+use auth::require_self_or_permission;
 use axum::{
     extract::{Path, State},
     response::IntoResponse,
-    Json,
+    Extension, Json,
+};
+use database::{
+    logic::requests,
+    schemas::users::{Permission, User},
+    PgPool,
 };
-use database::{logic::requests, PgPool};
 
 use crate::Result;
 
@@ -14,6 +19,7 @@ use crate::Result;
     tag = "users",
     responses(
         (status = 200, description = "User's requests retrieved successfully", body = [Request]),
+        (status = 403, description = "Missing the user_view permission to access another user"),
     ),
     params(
         ("id" = String, Path, description = "User database ID"),
@@ -21,9 +27,12 @@ use crate::Result;
 )]
 pub async fn get_user_requests(
     State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
     Path(user_id): Path<String>,
 ) -> Result<impl IntoResponse> {
-    let requests = requests::get_user_requests(&pool, &user_id).await?;
+    require_self_or_permission(&user, &user_id, Permission::UserView)?;
+
+    let requests = requests::get_user_requests(&pool, &user_id, user.visible_team_ids()).await?;
 
     Ok(Json(requests))
 }
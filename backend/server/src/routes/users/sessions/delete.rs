@@ -0,0 +1,98 @@
+use auth::require_self_or_permission;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+};
+use database::{
+    logic::sessions,
+    schemas::users::{Permission, User},
+    PgPool,
+};
+
+use crate::{Error, Result};
+
+/// Revoke a session of a user, its JWT and refresh token no longer being accepted
+#[utoipa::path(
+    delete,
+    path = "/users/{id}/sessions/{session_id}",
+    tag = "users",
+    responses(
+        (status = 204, description = "Session revoked successfully"),
+        (status = 403, description = "Missing the user_edit permission"),
+        (status = 404, description = "Session not found or already revoked"),
+    ),
+    params(
+        ("id" = String, Path, description = "User database ID"),
+        ("session_id" = String, Path, description = "Session database ID"),
+    ),
+)]
+pub async fn delete_user_session(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+    Path((user_id, session_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse> {
+    require_self_or_permission(&user, &user_id, Permission::UserEdit)?;
+
+    if sessions::revoke_user_session(&pool, &session_id, &user_id).await? == 0 {
+        return Err(Error::NotFound);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Sign a user out everywhere by revoking all their sessions
+#[utoipa::path(
+    delete,
+    path = "/users/{id}/sessions",
+    tag = "users",
+    responses(
+        (status = 204, description = "Sessions revoked successfully"),
+        (status = 403, description = "Missing the user_edit permission"),
+    ),
+    params(
+        ("id" = String, Path, description = "User database ID"),
+    ),
+)]
+pub async fn delete_user_sessions(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+    Path(user_id): Path<String>,
+) -> Result<impl IntoResponse> {
+    require_self_or_permission(&user, &user_id, Permission::UserEdit)?;
+
+    sessions::revoke_user_sessions(&pool, &user_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use database::logic::users;
+
+    use crate::test_utils::*;
+
+    #[tracing_test::traced_test]
+    #[sqlx::test(migrations = "../database/migrations")]
+    async fn given_viewer_when_signing_out_other_user_everywhere_then_returns_403_error(
+        pool: PgPool,
+    ) {
+        let response = request(Method::GET, "/api/v1/health", pool.clone()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let admin = users::get_user_from_email(&pool, "test@test.test")
+            .await
+            .unwrap()
+            .unwrap();
+
+        let response = request_with_roles(
+            Method::DELETE,
+            &format!("/api/v1/users/{}/sessions", admin.id),
+            pool,
+            &[Role::Viewer],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+}
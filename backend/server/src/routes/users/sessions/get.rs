@@ -0,0 +1,38 @@
+use auth::require_self_or_permission;
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use database::{
+    logic::sessions,
+    schemas::users::{Permission, User},
+    PgPool,
+};
+
+use crate::Result;
+
+/// Get the sessions of a user that can still be refreshed
+#[utoipa::path(
+    get,
+    path = "/users/{id}/sessions",
+    tag = "users",
+    responses(
+        (status = 200, description = "Sessions of the user", body = [UserSession]),
+        (status = 403, description = "Missing the user_view permission"),
+    ),
+    params(
+        ("id" = String, Path, description = "User database ID"),
+    ),
+)]
+pub async fn get_user_sessions(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+    Path(user_id): Path<String>,
+) -> Result<impl IntoResponse> {
+    require_self_or_permission(&user, &user_id, Permission::UserView)?;
+
+    let sessions = sessions::get_active_user_sessions(&pool, &user_id).await?;
+
+    Ok(Json(sessions))
+}
@@ -0,0 +1,18 @@
+use axum::{
+    routing::{delete, get},
+    Router,
+};
+
+use crate::ServerState;
+
+pub mod delete;
+pub mod get;
+
+pub fn router() -> Router<ServerState> {
+    Router::new()
+        .route(
+            "/",
+            get(get::get_user_sessions).delete(delete::delete_user_sessions),
+        )
+        .route("/:session_id", delete(delete::delete_user_session))
+}
@@ -0,0 +1,38 @@
+use auth::require_self_or_permission;
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use database::{
+    logic::teams,
+    schemas::users::{Permission, User},
+    PgPool,
+};
+
+use crate::Result;
+
+/// Get the teams a specific user is a member of
+#[utoipa::path(
+    get,
+    path = "/users/{id}/teams",
+    tag = "users",
+    responses(
+        (status = 200, description = "User's teams retrieved successfully", body = [Team]),
+        (status = 403, description = "Missing the user_view permission to access another user"),
+    ),
+    params(
+        ("id" = String, Path, description = "User database ID"),
+    )
+)]
+pub async fn get_user_teams(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<User>,
+    Path(user_id): Path<String>,
+) -> Result<impl IntoResponse> {
+    require_self_or_permission(&user, &user_id, Permission::UserView)?;
+
+    let teams = teams::get_user_teams(&pool, &user_id).await?;
+
+    Ok(Json(teams))
+}
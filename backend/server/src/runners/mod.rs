@@ -1,43 +1,36 @@
 use axum::response::sse::Event;
-use common::{
-    health::pb::{health_client::HealthClient, HealthCheckRequest},
-    runner_client::RunnerClient,
-    DeleteRequest, InitRequest, UpdateRequest,
+use common::{source_code_hash, DeleteRequest, UpdateRequest};
+use database::{
+    logic::server_config::get_config_with_defaults_and_db_results, schemas::sources::SourceKind,
+    PgPool,
 };
-use database::{logic::server_config::get_config_with_defaults_and_db_results, PgPool};
-use database::{logic::sources::get_source_code_by_kind, schemas::sources::SourceKind};
-use futures_util::{Stream, StreamExt, TryStreamExt};
-use tonic::transport::Endpoint;
+use futures_util::{stream, Stream};
+use sources::runners::RunnerPool;
+use tracing::warn;
 
 use crate::Result;
 
-pub async fn send_init_request(pool: &PgPool, source_kind: SourceKind) -> Result<()> {
-    let updates = get_source_code_by_kind(pool, &source_kind).await?;
-
-    let request = InitRequest {
-        updates: updates
-            .into_iter()
-            .flat_map(|s| {
-                s.source_code.map(|source_code| common::UpdateRequest {
-                    source: s.id.to_string(),
-                    source_code,
-                })
-            })
-            .collect(),
-    };
-
+/// Connects to the instances of the enabled runners, each of them being initialized with the
+/// sources' code as soon as it is serving
+pub async fn connect_runners(pool: &PgPool, runners: &RunnerPool) -> Result<()> {
     let config = get_config_with_defaults_and_db_results(pool).await?;
-    let endpoint = Endpoint::from_shared(config.runner_endpoint(&source_kind).to_string())?;
 
-    let mut client = RunnerClient::connect(endpoint).await?;
+    for source_kind in [SourceKind::Python] {
+        if !config.runner_enabled(&source_kind) {
+            continue;
+        }
 
-    client.init(request).await?;
+        if let Err(err) = runners.group(&source_kind, &config) {
+            warn!(?source_kind, ?err, "unable to connect to runner");
+        }
+    }
 
     Ok(())
 }
 
 pub async fn send_delete_request(
     pool: &PgPool,
+    runners: &RunnerPool,
     source_kind: SourceKind,
     source_id: &str,
 ) -> Result<()> {
@@ -46,17 +39,21 @@ pub async fn send_delete_request(
     };
 
     let config = get_config_with_defaults_and_db_results(pool).await?;
-    let endpoint = Endpoint::from_shared(config.runner_endpoint(&source_kind).to_string())?;
-
-    let mut client = RunnerClient::connect(endpoint).await?;
 
-    client.delete(request).await?;
+    runners
+        .group(&source_kind, &config)?
+        .broadcast(|mut client| {
+            let request = request.clone();
+            async move { client.delete(request).await }
+        })
+        .await?;
 
     Ok(())
 }
 
 pub async fn send_update_request(
     pool: &PgPool,
+    runners: &RunnerPool,
     source_kind: SourceKind,
     source_id: &str,
     source_code: &str,
@@ -64,41 +61,46 @@ pub async fn send_update_request(
     let request = UpdateRequest {
         source: source_id.to_string(),
         source_code: source_code.to_string(),
+        hash: source_code_hash(source_code),
     };
 
     let config = get_config_with_defaults_and_db_results(pool).await?;
-    let endpoint = Endpoint::from_shared(config.runner_endpoint(&source_kind).to_string())?;
-
-    let mut client = RunnerClient::connect(endpoint).await?;
 
-    client.update(request).await?;
+    runners
+        .group(&source_kind, &config)?
+        .broadcast(|mut client| {
+            let request = request.clone();
+            async move { client.update(request).await }
+        })
+        .await?;
 
     Ok(())
 }
 
+/// Status of a runner, serving as long as one of its instances is serving
 pub async fn stream_health_check(
     pool: &PgPool,
+    runners: &RunnerPool,
     source_kind: SourceKind,
 ) -> Result<impl Stream<Item = Result<Event>>> {
     let config = get_config_with_defaults_and_db_results(pool).await?;
-    let endpoint = Endpoint::from_shared(config.runner_endpoint(&source_kind).to_string())?;
-    let connection = endpoint.connect().await?;
+    let mut status = runners.group(&source_kind, &config)?.status();
+    status.mark_changed();
 
-    let mut client = HealthClient::new(connection);
+    let id = source_kind.to_string();
 
-    let request = HealthCheckRequest {
-        service: "".to_string(),
-    };
+    let sse_stream = stream::unfold(status, move |mut status| {
+        let id = id.clone();
 
-    let stream = client.watch(request).await?.into_inner();
+        async move {
+            status.changed().await.ok()?;
 
-    let sse_stream = stream.into_stream().map(move |data| {
-        data.map(|d| {
-            Event::default()
-                .id(source_kind.to_string())
-                .data(d.status().as_str_name())
-        })
-        .map_err(Into::into)
+            let event = Event::default()
+                .id(id)
+                .data(status.borrow_and_update().as_str_name());
+
+            Some((Ok(event), status))
+        }
     });
 
     Ok(sse_stream)
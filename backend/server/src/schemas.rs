@@ -2,10 +2,13 @@ use serde::Deserialize;
 use sources::schemas::SourceError;
 use utoipa::IntoParams;
 
+use auth::webauthn::{WebAuthnAssertion, WebAuthnRequestOptions};
 use chrono::NaiveDateTime;
 use database::schemas::{
     indicators::{Indicator, IndicatorKind},
+    mfa::WebAuthnCredential,
     requests::CreateSourceRequest,
+    sources::SourceKind,
 };
 use serde::Serialize;
 use typeshare::typeshare;
@@ -18,6 +21,13 @@ pub struct GetFaviconParams {
     pub url: String,
 }
 
+/// Request to get the runs of a source's background task
+#[derive(Deserialize, IntoParams)]
+pub struct GetTaskRunsParams {
+    /// Maximum number of runs to return, defaults to 50
+    pub limit: Option<i64>,
+}
+
 /// Request to get the data for a specific indicator from enabled sources supporting the indicator kind
 #[derive(Deserialize, ToSchema, IntoParams, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -33,6 +43,9 @@ pub struct RequestExecuteParam {
     /// Ignore errors, will remove all sources that return an error from the response
     #[serde(default)]
     pub ignore_errors: bool,
+    /// Team the request is made for, only querying its sources and the shared ones. An empty
+    /// string makes a shared request and it defaults to the first team of the user.
+    pub team_id: Option<String>,
 }
 
 impl From<RequestExecuteParam> for Indicator {
@@ -44,6 +57,19 @@ impl From<RequestExecuteParam> for Indicator {
     }
 }
 
+/// Request to run the code of a source on an indicator without saving it
+#[derive(Deserialize, ToSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct SourcePlaygroundRequest {
+    /// Kind of the source, which must be executed by a runner
+    pub kind: SourceKind,
+    /// Code of the source, sent to the runner without being stored
+    pub source_code: String,
+    /// Indicator to run the source's code on
+    pub indicator: Indicator,
+}
+
 /// Data from a source
 #[derive(Serialize, Debug, ToSchema, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -233,12 +259,108 @@ pub struct LoginUserRequest {
 }
 
 /// Response from login request
-#[derive(Serialize, ToSchema, Debug, Clone)]
+#[derive(Serialize, ToSchema, Debug)]
 #[serde(rename_all = "camelCase")]
 #[typeshare]
 pub struct LoginUserResponse {
-    /// The JWT token created from login request that can be used to authenticate yourself
-    pub jwt_token: String,
+    /// The JWT token created from login request that can be used to authenticate yourself,
+    /// missing until the second factor step of the login is completed
+    pub jwt_token: Option<String>,
+    /// Single use token to get new tokens with `/auth/refresh` once the JWT expires, missing
+    /// like the JWT
+    pub refresh_token: Option<String>,
+    /// Second factor needed to complete the login with `/auth/login/mfa`
+    pub mfa_challenge: Option<MfaChallenge>,
+    /// Recovery codes generated when enrolling a second factor during the login, only shown once
+    pub recovery_codes: Option<Vec<String>>,
+}
+
+/// Second authentication factor
+#[derive(Serialize, ToSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub enum MfaMethod {
+    Totp,
+    RecoveryCode,
+    WebAuthn,
+}
+
+/// Second factor step of a login, after the password was verified
+#[derive(Serialize, ToSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct MfaChallenge {
+    /// Token to send with the second factor, also signed by security keys
+    pub token: String,
+    /// Second factors enrolled by the user
+    pub methods: Vec<MfaMethod>,
+    /// Whether the user needs to enroll TOTP, with `/auth/login/mfa/totp`, since a second factor
+    /// is required
+    pub enrollment_required: bool,
+    /// Options to sign the challenge with a security key, if the user registered any
+    pub webauthn: Option<WebAuthnRequestOptions>,
+}
+
+/// Token of the second factor step of a login
+#[derive(Deserialize, ToSchema, Debug, Clone)]
+#[typeshare]
+pub struct MfaTokenRequest {
+    /// The token of the second factor challenge
+    pub token: String,
+}
+
+/// Second factor completing a login, only one of them being needed
+#[derive(Deserialize, ToSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct MfaLoginRequest {
+    /// The token of the second factor challenge
+    pub token: String,
+    /// Code of an authenticator app, also confirming its enrollment when it is required
+    pub totp_code: Option<String>,
+    /// One of the recovery codes of the user
+    pub recovery_code: Option<String>,
+    /// Signature of the challenge by a security key
+    pub webauthn: Option<WebAuthnAssertion>,
+}
+
+/// Code of an authenticator app
+#[derive(Deserialize, ToSchema, Debug, Clone)]
+#[typeshare]
+pub struct TotpCodeRequest {
+    /// The code shown by the authenticator app
+    pub code: String,
+}
+
+/// Recovery codes usable once each instead of a second factor
+#[derive(Serialize, ToSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct RecoveryCodesResponse {
+    /// The new recovery codes, only shown once, empty if the user already had some
+    pub recovery_codes: Vec<String>,
+}
+
+/// Second factors enrolled by a user
+#[derive(Serialize, ToSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct MfaStatus {
+    /// Whether an authenticator app is enrolled
+    pub totp_enabled: bool,
+    /// Number of recovery codes not used yet
+    pub recovery_codes_left: i64,
+    /// Security keys registered
+    pub webauthn_credentials: Vec<WebAuthnCredential>,
+}
+
+/// Refresh token of a session
+#[derive(Deserialize, ToSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct RefreshTokenRequest {
+    /// The refresh token received when logging in or when last refreshing the session
+    pub refresh_token: String,
 }
 
 /// Data needed to signup/create a new user
@@ -254,12 +376,54 @@ pub struct SignupUserRequest {
     pub password: String,
 }
 
+/// Single use token sent by email
+#[derive(Deserialize, ToSchema, Debug, Clone)]
+#[typeshare]
+pub struct UserTokenRequest {
+    /// The token received by email
+    pub token: String,
+}
+
+/// Email of a local account
+#[derive(Deserialize, ToSchema, Debug, Clone)]
+#[typeshare]
+pub struct UserEmailRequest {
+    /// The email of the local account
+    pub email: String,
+}
+
+/// Data needed to reset the password of a local account
+#[derive(Deserialize, ToSchema, Debug, Clone)]
+#[typeshare]
+pub struct ResetPasswordRequest {
+    /// The password reset token received by email
+    pub token: String,
+    /// The new password of the user
+    pub password: String,
+}
+
+/// Data needed to change the password of the logged in user
+#[derive(Deserialize, ToSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct ChangePasswordRequest {
+    /// The current password of the user
+    pub current_password: String,
+    /// The new password of the user
+    pub new_password: String,
+}
+
 /// Kind of authentication service
 #[derive(Serialize, ToSchema, Debug, Clone)]
 #[serde(tag = "kind", content = "content", rename_all = "camelCase")]
 #[typeshare]
 pub enum AuthServiceKind {
-    OpenId { name: String },
+    OpenId {
+        /// Name of the provider in the configuration, used in its URLs
+        name: String,
+        /// Name of the provider displayed to users
+        label: String,
+    },
     Password,
 }
 
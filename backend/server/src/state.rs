@@ -1,12 +1,19 @@
-use auth::{jwt::JwtManager, openid::OpenIdKeys};
+use std::time::Duration;
+
+use auth::{jwt::JwtManager, throttling::LoginThrottle};
 use axum::extract::FromRef;
 use cache::CacheImpl;
 use database::PgPool;
+use mail::MailerImpl;
 use shared::crypto::Crypto;
-use sources::FetchState;
+use sources::{runners::RunnerPool, FetchState};
 use tracing::instrument;
 
-use crate::{config::Config, Result};
+use crate::{
+    background_tasks::Scheduler,
+    config::{CacheBackend, Config},
+    Result,
+};
 
 #[derive(Clone)]
 pub struct ServerState {
@@ -15,6 +22,9 @@ pub struct ServerState {
     pub crypto: Crypto,
     pub cache: CacheImpl,
     pub auth_state: auth::config::State,
+    pub scheduler: Scheduler,
+    pub runners: RunnerPool,
+    pub mailer: MailerImpl,
 }
 
 impl FromRef<ServerState> for PgPool {
@@ -29,6 +39,24 @@ impl FromRef<ServerState> for Crypto {
     }
 }
 
+impl FromRef<ServerState> for Scheduler {
+    fn from_ref(state: &ServerState) -> Self {
+        state.scheduler.clone()
+    }
+}
+
+impl FromRef<ServerState> for RunnerPool {
+    fn from_ref(state: &ServerState) -> Self {
+        state.runners.clone()
+    }
+}
+
+impl FromRef<ServerState> for MailerImpl {
+    fn from_ref(state: &ServerState) -> Self {
+        state.mailer.clone()
+    }
+}
+
 impl FromRef<ServerState> for Config {
     fn from_ref(state: &ServerState) -> Self {
         state.config.clone()
@@ -46,41 +74,49 @@ impl ServerState {
 
         let crypto = Crypto::new(&config.encryption.server_key);
 
-        let cache = if let Some(url) = &config.cache.redis_url {
-            CacheImpl::redis(url).expect("Failed to connect to redis")
-        } else {
-            CacheImpl::in_memory()
+        let cache = match config.cache.backend() {
+            CacheBackend::Memory => CacheImpl::bounded_in_memory(config.cache.memory.clone()),
+            CacheBackend::Redis => CacheImpl::redis(
+                config
+                    .cache
+                    .redis_url
+                    .as_deref()
+                    .expect("Redis URL must be set to use the Redis cache"),
+                config.cache.redis_key_prefix.clone(),
+            )
+            .expect("Failed to connect to redis"),
+            CacheBackend::Postgres => {
+                CacheImpl::postgres(pool.clone(), config.cache.postgres.clone())
+            }
+            CacheBackend::Tiered => CacheImpl::tiered(
+                config.cache.memory.clone(),
+                pool.clone(),
+                config.cache.postgres.clone(),
+            ),
         };
+        cache.spawn_sweeper();
 
-        let jwt_manager = JwtManager::new(&config.auth.jwt);
+        let jwt_manager = JwtManager::new(&config.auth.jwt, pool.clone(), crypto.clone());
+        jwt_manager.spawn_key_sync();
+        auth::sessions::spawn_sweeper(pool.clone());
 
-        let google_keys = if config.auth.google.enabled {
-            Some(
-                OpenIdKeys::new(&config.auth.google.openid_url)
-                    .await
-                    .unwrap()
-                    .into(),
-            )
-        } else {
-            None
-        };
+        let login_throttle =
+            LoginThrottle::postgres(config.auth.login_throttling.clone(), pool.clone());
+        login_throttle.spawn_sweeper();
 
-        let microsoft_keys = if config.auth.google.enabled {
-            Some(
-                OpenIdKeys::new(&config.auth.microsoft.openid_url)
-                    .await
-                    .unwrap()
-                    .into(),
-            )
-        } else {
-            None
-        };
+        let auth_state =
+            auth::config::State::new(config.auth.clone(), jwt_manager, login_throttle).await;
 
-        let auth_state = auth::config::State {
-            auth: config.auth.clone(),
-            google_keys,
-            microsoft_keys,
-            jwt_manager,
+        let runners = RunnerPool::new(
+            pool.clone(),
+            Duration::from_secs(config.server.runners.sync_interval),
+        );
+
+        let mailer = match &config.mail.smtp {
+            Some(smtp) => {
+                MailerImpl::smtp(smtp, &config.mail.from).expect("Failed to setup SMTP mailer")
+            }
+            None => MailerImpl::log(),
         };
 
         Self {
@@ -89,6 +125,9 @@ impl ServerState {
             crypto,
             cache,
             auth_state,
+            scheduler: Default::default(),
+            runners,
+            mailer,
         }
     }
 
@@ -103,6 +142,7 @@ impl ServerState {
 
         Ok(FetchState::new(
             self.pool.clone(),
+            self.runners.clone(),
             secrets,
             source_id.to_string(),
         ))
@@ -121,17 +161,28 @@ pub mod test {
 
         let auth_state = auth::config::State {
             auth: config.auth.clone(),
-            google_keys: None,
-            microsoft_keys: None,
-            jwt_manager: JwtManager::new(&config.auth.jwt),
+            providers: Default::default(),
+            jwt_manager: JwtManager::new(&config.auth.jwt, pool.clone(), crypto.clone()),
+            login_throttle: LoginThrottle::postgres(
+                config.auth.login_throttling.clone(),
+                pool.clone(),
+            ),
         };
 
+        let runners = RunnerPool::new(
+            pool.clone(),
+            Duration::from_secs(config.server.runners.sync_interval),
+        );
+
         ServerState {
             pool,
             config,
             crypto,
             cache,
             auth_state,
+            scheduler: Default::default(),
+            runners,
+            mailer: MailerImpl::in_memory(),
         }
     }
 }
@@ -9,12 +9,15 @@ pub enum Error {
     RateLimited,
     ResponseError,
     MissingSourceCode,
+    MissingBackgroundTask,
     Timeout,
     InternalError,
+    InvalidConfig(String),
     Postgres(database::Error),
     Reqwest(reqwest::Error),
     ZipError(ZipError),
     IoError(std::io::Error),
+    SerdeJson(serde_json::Error),
     InvalidHeaderValue(reqwest::header::InvalidHeaderValue),
     TonicTransportError(tonic::transport::Error),
     TonicStatus(tonic::Status),
@@ -46,6 +49,12 @@ impl From<std::io::Error> for Error {
     }
 }
 
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Self::SerdeJson(error)
+    }
+}
+
 impl From<reqwest::header::InvalidHeaderValue> for Error {
     fn from(error: reqwest::header::InvalidHeaderValue) -> Self {
         Self::InvalidHeaderValue(error)
@@ -0,0 +1,216 @@
+use database::schemas::ingestion::FeedValidators;
+use reqwest::{
+    header::{HeaderMap, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+    RequestBuilder, StatusCode,
+};
+use serde::{
+    de::{DeserializeOwned, SeqAccess, Visitor},
+    Deserializer,
+};
+use std::{fmt, io::Read};
+use tokio::{sync::mpsc, task::JoinHandle};
+use tracing::{info, warn, Span};
+
+use crate::{Error, Result};
+
+/// Response of a feed download, unless the feed did not change since the previous download
+pub enum FeedResponse {
+    NotModified {
+        headers: String,
+    },
+    Modified {
+        headers: String,
+        validators: FeedValidators,
+        response: reqwest::Response,
+    },
+}
+
+/// Downloads a feed, sending the validators of the previous download to skip it if the feed did
+/// not change since
+pub async fn conditional_get(
+    request: RequestBuilder,
+    validators: &FeedValidators,
+) -> Result<FeedResponse> {
+    let mut request = request;
+
+    if let Some(etag) = &validators.etag {
+        request = request.header(IF_NONE_MATCH, etag);
+    }
+
+    if let Some(last_modified) = &validators.last_modified {
+        request = request.header(IF_MODIFIED_SINCE, last_modified);
+    }
+
+    let response = request.send().await?;
+    let headers = headers_to_string(response.headers());
+
+    if response.status() == StatusCode::NOT_MODIFIED {
+        info!("feed not modified since the previous download");
+        return Ok(FeedResponse::NotModified { headers });
+    }
+
+    let response = response.error_for_status()?;
+
+    let header_value = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(ToString::to_string)
+    };
+
+    let validators = FeedValidators {
+        etag: header_value(ETAG),
+        last_modified: header_value(LAST_MODIFIED),
+    };
+
+    Ok(FeedResponse::Modified {
+        headers,
+        validators,
+        response,
+    })
+}
+
+fn headers_to_string(headers: &HeaderMap) -> String {
+    headers
+        .iter()
+        .map(|(k, v)| format!("{}: {}", k, String::from_utf8_lossy(v.as_bytes())))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Groups parsed entries in chunks, sending them to be ingested once a chunk is full
+pub struct ChunkSender<T> {
+    chunk: Vec<T>,
+    chunk_size: usize,
+    sender: mpsc::Sender<Vec<T>>,
+}
+
+impl<T> ChunkSender<T> {
+    /// Adds an entry to the current chunk, returns false if the ingestion stopped, in which case
+    /// parsing should stop as well
+    pub fn push(&mut self, entry: T) -> bool {
+        self.chunk.push(entry);
+
+        if self.chunk.len() < self.chunk_size {
+            return true;
+        }
+
+        self.flush()
+    }
+
+    fn flush(&mut self) -> bool {
+        if self.chunk.is_empty() {
+            return true;
+        }
+
+        let chunk = std::mem::replace(&mut self.chunk, Vec::with_capacity(self.chunk_size));
+
+        self.sender.blocking_send(chunk).is_ok()
+    }
+}
+
+/// Parses a feed on a blocking thread, receiving its entries in chunks through a bounded channel
+/// so that ingesting a chunk overlaps with parsing the next one. The downloaded body is held in
+/// memory as is, possibly compressed, but its parsed entries are never all held at once
+pub fn parse_in_chunks<T, F>(
+    chunk_size: usize,
+    parse: F,
+) -> (mpsc::Receiver<Vec<T>>, JoinHandle<Result<()>>)
+where
+    T: Send + 'static,
+    F: FnOnce(&mut ChunkSender<T>) -> Result<()> + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel(2);
+    let span = Span::current();
+
+    let handle = tokio::task::spawn_blocking(move || {
+        let _entered = span.entered();
+
+        let mut sender = ChunkSender {
+            chunk: Vec::with_capacity(chunk_size),
+            chunk_size,
+            sender,
+        };
+
+        parse(&mut sender)?;
+        sender.flush();
+
+        Ok(())
+    });
+
+    (receiver, handle)
+}
+
+/// Parses a JSON array one element at a time, skipping the elements that cannot be parsed
+pub fn parse_json_array<R, T>(reader: R, sender: &mut ChunkSender<T>) -> Result<()>
+where
+    R: Read,
+    T: DeserializeOwned,
+{
+    for_each_json_element(reader, |value| match serde_json::from_value(value) {
+        Ok(entry) => sender.push(entry),
+        Err(error) => {
+            warn!("error parsing entry: {}", error);
+            true
+        }
+    })
+}
+
+/// Calls `f` with each element of a JSON array, one element at a time, until `f` returns false
+pub fn for_each_json_element<R, F>(reader: R, f: F) -> Result<()>
+where
+    R: Read,
+    F: FnMut(serde_json::Value) -> bool,
+{
+    serde_json::Deserializer::from_reader(reader)
+        .deserialize_seq(ArrayVisitor(f))
+        .map_err(Into::into)
+}
+
+struct ArrayVisitor<F>(F);
+
+impl<'de, F: FnMut(serde_json::Value) -> bool> Visitor<'de> for ArrayVisitor<F> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an array of entries")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(mut self, mut seq: A) -> std::result::Result<(), A::Error> {
+        while let Some(value) = seq.next_element::<serde_json::Value>()? {
+            if !(self.0)(value) {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Waits for the parsing of a feed to be done, returning its error if it failed
+pub async fn parsed(handle: JoinHandle<Result<()>>) -> Result<()> {
+    handle.await.map_err(|_| Error::InternalError)?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn given_json_array_when_parsing_in_chunks_then_skips_invalid_entries() {
+        let data = r#"[1, 2, "three", 4, 5, 6, 7]"#;
+
+        let (mut chunks, handle) =
+            parse_in_chunks(2, move |sender| parse_json_array(data.as_bytes(), sender));
+
+        let mut received: Vec<Vec<u32>> = Vec::new();
+        while let Some(chunk) = chunks.recv().await {
+            received.push(chunk);
+        }
+
+        parsed(handle).await.unwrap();
+
+        assert_eq!(vec![vec![1, 2], vec![4, 5], vec![6, 7]], received);
+    }
+}
@@ -1,4 +1,5 @@
 use serde::de::DeserializeOwned;
+use serde_json_path::JsonPath;
 use tracing::{error, instrument};
 
 use crate::{Error, Result};
@@ -25,3 +26,12 @@ where
         }
     }
 }
+
+/// Parses a JSONPath, a plain key being a shorthand for `$.key`
+pub(crate) fn json_path(path: &str) -> std::result::Result<JsonPath, serde_json_path::ParseError> {
+    if path.starts_with('$') {
+        JsonPath::parse(path)
+    } else {
+        JsonPath::parse(&format!("$['{}']", path.replace('\'', "\\'")))
+    }
+}
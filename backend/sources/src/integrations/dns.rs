@@ -85,7 +85,7 @@ async fn reverse_dns_lookup(
 
             if matches!(err.kind(), &ResolveErrorKind::NoRecordsFound { .. }) {
                 Error::NotFound
-            } else if matches!(err.kind(), &ResolveErrorKind::Timeout { .. }) {
+            } else if matches!(err.kind(), &ResolveErrorKind::Timeout) {
                 Error::Timeout
             } else {
                 Error::InternalError
@@ -0,0 +1,539 @@
+use async_trait::async_trait;
+use database::{
+    logic::{
+        feeds::{
+            deactivate_unseen_feed_entries, get_feed_entries, get_feed_validators,
+            insert_feed_refresh, upsert_feed_entries,
+        },
+        sources::get_source,
+    },
+    schemas::{
+        feeds::{FeedCompression, FeedConfig, FeedFormat, NewFeedEntry},
+        indicators::{Indicator, IndicatorKind},
+        ingestion::IngestionCounts,
+    },
+};
+use flate2::read::GzDecoder;
+use serde_json::{Map, Value};
+use std::io::{BufRead, BufReader, Read};
+use tracing::{info, instrument, warn};
+
+use crate::{
+    feeds::{self, ChunkSender, FeedResponse},
+    helpers::json_path,
+    schemas::TaskSummary,
+    Error, FetchState, Result, Source,
+};
+
+/// Source whose indicators come from a feed described by the source's config instead of code
+pub struct Feed;
+
+#[async_trait]
+impl Source for Feed {
+    #[instrument(skip_all, err)]
+    async fn fetch_data(&self, indicator: &Indicator, state: &FetchState) -> Result<Value> {
+        let data = get_feed_entries(
+            &state.pool,
+            &state.source_id,
+            &indicator.db_kind(),
+            &normalize(indicator.kind, &indicator.data),
+        )
+        .await?;
+
+        Ok(serde_json::json!(data))
+    }
+
+    fn has_background_task(&self) -> bool {
+        true
+    }
+
+    #[instrument(skip_all, err)]
+    async fn background_task(&self, state: &FetchState) -> Result<TaskSummary> {
+        let source = get_source(&state.pool, &state.source_id, None).await?;
+        let config = parse_feed_config(&source.config).map_err(Error::InvalidConfig)?;
+
+        let previous_validators = get_feed_validators(&state.pool, &state.source_id).await?;
+
+        let request = reqwest::Client::new().get(&config.url);
+
+        let (headers, validators, response) =
+            match feeds::conditional_get(request, &previous_validators).await? {
+                FeedResponse::NotModified { headers } => {
+                    let counts = IngestionCounts::default();
+                    insert_feed_refresh(
+                        &state.source_id,
+                        &headers,
+                        &previous_validators,
+                        true,
+                        counts,
+                        &state.pool,
+                    )
+                    .await?;
+
+                    return Ok(TaskSummary {
+                        records_ingested: Some(0),
+                        changes: Some(counts),
+                    });
+                }
+                FeedResponse::Modified {
+                    headers,
+                    validators,
+                    response,
+                } => (headers, validators, response),
+            };
+
+        let bytes = response.bytes().await?;
+
+        let (mut chunks, parser) =
+            feeds::parse_in_chunks(5_000, move |sender| match config.compression {
+                None => parse_feed(&config, &*bytes, sender),
+                Some(FeedCompression::Gzip) => parse_feed(&config, GzDecoder::new(&*bytes), sender),
+                Some(FeedCompression::Zip) => {
+                    let mut zip_archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))?;
+                    let archive_file = zip_archive.by_index(0)?;
+
+                    info!(filename = archive_file.name(), "unzipping file");
+
+                    parse_feed(&config, archive_file, sender)
+                }
+            });
+
+        let mut transaction = state.pool.begin().await?;
+
+        let mut num_entries = 0;
+        let mut counts = IngestionCounts::default();
+
+        while let Some(chunk) = chunks.recv().await {
+            num_entries += chunk.len();
+            counts += upsert_feed_entries(&state.source_id, chunk, &mut *transaction).await?;
+        }
+
+        feeds::parsed(parser).await?;
+
+        // Most likely a change of format, better keep the current entries than removing them all
+        if num_entries == 0 {
+            return Err(Error::ResponseError);
+        }
+
+        counts.removed =
+            deactivate_unseen_feed_entries(&state.source_id, &mut *transaction).await? as i32;
+
+        insert_feed_refresh(
+            &state.source_id,
+            &headers,
+            &validators,
+            false,
+            counts,
+            &mut *transaction,
+        )
+        .await?;
+
+        transaction.commit().await?;
+
+        info!(num_entries, ?counts, "feed ingested");
+
+        Ok(TaskSummary {
+            records_ingested: Some(num_entries as i32),
+            changes: Some(counts),
+        })
+    }
+}
+
+/// Parses and validates the feed definition of a source's config
+pub fn parse_feed_config(config: &[Value]) -> std::result::Result<FeedConfig, String> {
+    let config = FeedConfig::from_source_config(config)
+        .map_err(|err| format!("invalid feed config: {err}"))?;
+
+    reqwest::Url::parse(&config.url).map_err(|err| format!("invalid feed URL: {err}"))?;
+
+    if config.indicators.is_empty() {
+        return Err("a feed must contain at least one kind of indicator".to_string());
+    }
+
+    match config.format {
+        FeedFormat::Csv => {
+            for value in [&config.delimiter, &config.comment_prefix]
+                .into_iter()
+                .flatten()
+            {
+                if value.len() != 1 {
+                    return Err(format!(
+                        "CSV delimiter and comment prefix must be a single character, got {value:?}"
+                    ));
+                }
+            }
+
+            if !config.has_headers.unwrap_or(true) {
+                for field in fields(&config) {
+                    field.parse::<usize>().map_err(|_| {
+                        format!("column {field:?} must be an index since the CSV has no headers")
+                    })?;
+                }
+            }
+        }
+        FeedFormat::Json => {
+            for path in config.entries_path.iter().chain(fields(&config)) {
+                json_path(path).map_err(|err| format!("invalid JSONPath {path:?}: {err}"))?;
+            }
+        }
+        FeedFormat::Txt => {
+            if config.indicators.len() != 1 {
+                return Err("a TXT feed contains a single kind of indicator".to_string());
+            }
+        }
+    }
+
+    Ok(config)
+}
+
+/// Fields of the entries referenced by a feed definition
+fn fields(config: &FeedConfig) -> impl Iterator<Item = &String> {
+    config
+        .indicators
+        .iter()
+        .map(|mapping| &mapping.field)
+        .chain(config.metadata.iter().map(|mapping| &mapping.field))
+}
+
+/// Normalizes the data of an indicator so that feed entries match requested indicators
+fn normalize(kind: IndicatorKind, data: &str) -> String {
+    let data = data.trim();
+
+    match kind {
+        IndicatorKind::Url => data.to_string(),
+        _ => data.to_lowercase(),
+    }
+}
+
+/// Adds the indicators of a feed entry to the chunk being parsed, returns false if the ingestion
+/// stopped
+fn push_entry<'a>(
+    indicators: impl Iterator<Item = (IndicatorKind, &'a str)>,
+    metadata: Map<String, Value>,
+    sender: &mut ChunkSender<NewFeedEntry>,
+) -> bool {
+    let metadata = Value::Object(metadata);
+
+    for (kind, data) in indicators {
+        let data = normalize(kind, data);
+
+        if data.is_empty() {
+            continue;
+        }
+
+        let entry = NewFeedEntry {
+            kind: kind.to_string(),
+            data,
+            metadata: metadata.clone(),
+        };
+
+        if !sender.push(entry) {
+            return false;
+        }
+    }
+
+    true
+}
+
+fn parse_feed<R: Read>(
+    config: &FeedConfig,
+    reader: R,
+    sender: &mut ChunkSender<NewFeedEntry>,
+) -> Result<()> {
+    match config.format {
+        FeedFormat::Csv => parse_csv(config, reader, sender),
+        FeedFormat::Json => parse_json(config, reader, sender),
+        FeedFormat::Txt => parse_txt(config, reader, sender),
+    }
+}
+
+fn parse_csv<R: Read>(
+    config: &FeedConfig,
+    reader: R,
+    sender: &mut ChunkSender<NewFeedEntry>,
+) -> Result<()> {
+    let delimiter = config.delimiter.as_deref().unwrap_or(",");
+    let comment_prefix = config.comment_prefix.as_deref().unwrap_or("#");
+    let has_headers = config.has_headers.unwrap_or(true);
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter.as_bytes()[0])
+        .comment(comment_prefix.as_bytes().first().copied())
+        .has_headers(has_headers)
+        .flexible(true)
+        .from_reader(reader);
+
+    let headers = if has_headers {
+        Some(reader.headers().map_err(|_| Error::ResponseError)?.clone())
+    } else {
+        None
+    };
+
+    let column = |field: &str| match &headers {
+        Some(headers) => headers
+            .iter()
+            .position(|header| header.trim() == field)
+            .ok_or_else(|| Error::InvalidConfig(format!("unknown CSV column {field:?}"))),
+        None => field
+            .parse()
+            .map_err(|_| Error::InvalidConfig(format!("invalid CSV column index {field:?}"))),
+    };
+
+    let indicators = config
+        .indicators
+        .iter()
+        .map(|mapping| Ok((mapping.kind, column(&mapping.field)?)))
+        .collect::<Result<Vec<_>>>()?;
+    let metadata = config
+        .metadata
+        .iter()
+        .map(|mapping| Ok((mapping.name.clone(), column(&mapping.field)?)))
+        .collect::<Result<Vec<_>>>()?;
+
+    for result in reader.records() {
+        let record = match result {
+            Ok(record) => record,
+            Err(error) => {
+                warn!("error parsing entry: {}", error);
+                continue;
+            }
+        };
+
+        let values = metadata
+            .iter()
+            .filter_map(|(name, index)| {
+                record
+                    .get(*index)
+                    .map(|value| (name.clone(), Value::String(value.trim().to_string())))
+            })
+            .collect();
+
+        let entry_indicators = indicators
+            .iter()
+            .filter_map(|(kind, index)| record.get(*index).map(|data| (*kind, data)));
+
+        if !push_entry(entry_indicators, values, sender) {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_json<R: Read>(
+    config: &FeedConfig,
+    reader: R,
+    sender: &mut ChunkSender<NewFeedEntry>,
+) -> Result<()> {
+    let invalid_path = |err: serde_json_path::ParseError| Error::InvalidConfig(err.to_string());
+
+    let indicators = config
+        .indicators
+        .iter()
+        .map(|mapping| {
+            Ok((
+                mapping.kind,
+                json_path(&mapping.field).map_err(invalid_path)?,
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let metadata = config
+        .metadata
+        .iter()
+        .map(|mapping| {
+            Ok((
+                mapping.name.clone(),
+                json_path(&mapping.field).map_err(invalid_path)?,
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut parse_entry = |entry: Value| {
+        let values = metadata
+            .iter()
+            .filter_map(|(name, path)| {
+                path.query(&entry)
+                    .first()
+                    .map(|value| (name.clone(), value.clone()))
+            })
+            .collect();
+
+        let entry_indicators = indicators.iter().flat_map(|(kind, path)| {
+            path.query(&entry)
+                .all()
+                .into_iter()
+                .filter_map(|value| value.as_str())
+                .map(|data| (*kind, data))
+                .collect::<Vec<_>>()
+        });
+
+        push_entry(entry_indicators, values, sender)
+    };
+
+    // Without a path to the entries, the feed is an array parsed one entry at a time
+    let Some(entries_path) = &config.entries_path else {
+        return feeds::for_each_json_element(reader, parse_entry);
+    };
+
+    let entries_path = json_path(entries_path).map_err(invalid_path)?;
+    let document: Value = serde_json::from_reader(reader)?;
+
+    for entries in entries_path.query(&document).all() {
+        let entries = match entries {
+            Value::Array(entries) => entries.clone(),
+            entry => vec![entry.clone()],
+        };
+
+        for entry in entries {
+            if !parse_entry(entry) {
+                return Ok(());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_txt<R: Read>(
+    config: &FeedConfig,
+    reader: R,
+    sender: &mut ChunkSender<NewFeedEntry>,
+) -> Result<()> {
+    let comment_prefix = config.comment_prefix.as_deref().unwrap_or("#");
+    let kind = config.indicators[0].kind;
+
+    for line in BufReader::new(reader).lines() {
+        let line = line?;
+        let data = line.trim();
+
+        if data.starts_with(comment_prefix) {
+            continue;
+        }
+
+        if !push_entry([(kind, data)].into_iter(), Map::new(), sender) {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn parse(config: Value, data: &'static str) -> Vec<(String, String, Value)> {
+        let config = parse_feed_config(&[config]).unwrap();
+
+        let (mut chunks, handle) = feeds::parse_in_chunks(2, move |sender| {
+            parse_feed(&config, data.as_bytes(), sender)
+        });
+
+        let mut received = Vec::new();
+        while let Some(chunk) = chunks.recv().await {
+            received.extend(
+                chunk
+                    .into_iter()
+                    .map(|entry| (entry.kind, entry.data, entry.metadata)),
+            );
+        }
+
+        feeds::parsed(handle).await.unwrap();
+
+        received
+    }
+
+    #[tokio::test]
+    async fn given_csv_feed_when_parsing_then_maps_columns_to_indicators_and_metadata() {
+        let config = serde_json::json!({
+            "url": "https://example.com/feed.csv",
+            "format": "CSV",
+            "indicators": [{ "field": "domain", "kind": "DOMAIN" }, { "field": "ip", "kind": "IPV4" }],
+            "metadata": [{ "name": "threat", "field": "threat" }],
+        });
+        let data =
+            "# comment\ndomain,ip,threat\nExample.com,1.2.3.4,phishing\n\"\",5.6.7.8,malware\n";
+
+        let entries = parse(config, data).await;
+
+        assert_eq!(
+            vec![
+                (
+                    "DOMAIN".to_string(),
+                    "example.com".to_string(),
+                    serde_json::json!({ "threat": "phishing" })
+                ),
+                (
+                    "IPV4".to_string(),
+                    "1.2.3.4".to_string(),
+                    serde_json::json!({ "threat": "phishing" })
+                ),
+                (
+                    "IPV4".to_string(),
+                    "5.6.7.8".to_string(),
+                    serde_json::json!({ "threat": "malware" })
+                ),
+            ],
+            entries
+        );
+    }
+
+    #[tokio::test]
+    async fn given_json_feed_with_entries_path_when_parsing_then_selects_entries_with_json_paths() {
+        let config = serde_json::json!({
+            "url": "https://example.com/feed.json",
+            "format": "JSON",
+            "entriesPath": "$.data",
+            "indicators": [{ "field": "$.hashes[*]", "kind": "SHA256" }],
+            "metadata": [{ "name": "family", "field": "family" }],
+        });
+        let data =
+            r#"{ "data": [{ "hashes": ["AB", "cd"], "family": "emotet" }, { "family": "none" }] }"#;
+
+        let entries = parse(config, data).await;
+
+        assert_eq!(
+            vec![
+                (
+                    "SHA256".to_string(),
+                    "ab".to_string(),
+                    serde_json::json!({ "family": "emotet" })
+                ),
+                (
+                    "SHA256".to_string(),
+                    "cd".to_string(),
+                    serde_json::json!({ "family": "emotet" })
+                ),
+            ],
+            entries
+        );
+    }
+
+    #[tokio::test]
+    async fn given_txt_feed_when_parsing_then_skips_comments_and_empty_lines() {
+        let config = serde_json::json!({
+            "url": "https://example.com/feed.txt",
+            "format": "TXT",
+            "indicators": [{ "field": "", "kind": "URL" }],
+        });
+        let data = "# header\nhttps://example.com/Path\n\nhttp://example.org\n";
+
+        let entries = parse(config, data).await;
+
+        assert_eq!(
+            vec![
+                (
+                    "URL".to_string(),
+                    "https://example.com/Path".to_string(),
+                    serde_json::json!({})
+                ),
+                (
+                    "URL".to_string(),
+                    "http://example.org".to_string(),
+                    serde_json::json!({})
+                ),
+            ],
+            entries
+        );
+    }
+}
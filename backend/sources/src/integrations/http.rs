@@ -0,0 +1,208 @@
+use async_trait::async_trait;
+use database::{
+    logic::sources::get_source_config,
+    schemas::{
+        http_templates::{HttpMethod, HttpTemplateConfig},
+        indicators::Indicator,
+    },
+};
+use reqwest::header::{HeaderName, CONTENT_TYPE};
+use serde_json::Value;
+use serde_json_path::JsonPath;
+use tracing::instrument;
+
+use crate::{handle_response, helpers::json_path, Error, FetchState, Result, Source};
+
+/// Source sending a single HTTP request described by the source's config instead of code
+pub struct Http;
+
+#[async_trait]
+impl Source for Http {
+    #[instrument(skip_all, err)]
+    async fn fetch_data(&self, indicator: &Indicator, state: &FetchState) -> Result<Value> {
+        let config = get_source_config(&state.pool, &state.source_id).await?;
+        let config = parse_http_config(&config).map_err(Error::InvalidConfig)?;
+
+        let kind = config
+            .kinds
+            .iter()
+            .find(|mapping| mapping.kind == indicator.kind)
+            .map(|mapping| mapping.value.clone())
+            .unwrap_or_else(|| indicator.kind.to_string().to_lowercase());
+
+        let resolve = |name: &str| match name {
+            "indicator" => Ok(indicator.data.clone()),
+            "kind" => Ok(kind.clone()),
+            _ => name
+                .strip_prefix("secrets.")
+                .and_then(|secret| state.secrets.get(secret))
+                .cloned()
+                .ok_or_else(|| format!("unknown placeholder or missing secret {name:?}")),
+        };
+
+        let url = render(&config.url, Escape::Url, resolve).map_err(Error::InvalidConfig)?;
+
+        let mut request = match config.method {
+            HttpMethod::Get => reqwest::Client::new().get(url),
+            HttpMethod::Post => reqwest::Client::new().post(url),
+        };
+
+        for header in &config.headers {
+            let value =
+                render(&header.value, Escape::None, resolve).map_err(Error::InvalidConfig)?;
+            request = request.header(&header.name, value);
+        }
+
+        if let Some(body) = &config.body {
+            let body = render(body, Escape::Json, resolve).map_err(Error::InvalidConfig)?;
+            request = request.header(CONTENT_TYPE, "application/json").body(body);
+        }
+
+        let data = handle_response(request.send().await?).await?;
+
+        let response_path = config
+            .response_path
+            .as_deref()
+            .map(json_path)
+            .transpose()
+            .map_err(|err| Error::InvalidConfig(err.to_string()))?;
+
+        Ok(extract(data, response_path.as_ref()))
+    }
+}
+
+/// Parses and validates the HTTP request definition of a source's config
+pub fn parse_http_config(config: &[Value]) -> std::result::Result<HttpTemplateConfig, String> {
+    let config = HttpTemplateConfig::from_source_config(config)
+        .map_err(|err| format!("invalid HTTP config: {err}"))?;
+
+    // Secrets are only known when fetching data, any name is accepted here
+    let resolve = |name: &str| match name {
+        "indicator" | "kind" => Ok("placeholder".to_string()),
+        _ if name.starts_with("secrets.") => Ok("placeholder".to_string()),
+        _ => Err(format!("unknown placeholder {name:?}")),
+    };
+
+    let url = render(&config.url, Escape::Url, resolve)?;
+    reqwest::Url::parse(&url).map_err(|err| format!("invalid URL: {err}"))?;
+
+    for header in &config.headers {
+        HeaderName::from_bytes(header.name.as_bytes())
+            .map_err(|_| format!("invalid header name {:?}", header.name))?;
+        render(&header.value, Escape::None, resolve)?;
+    }
+
+    if let Some(body) = &config.body {
+        render(body, Escape::Json, resolve)?;
+    }
+
+    if let Some(path) = &config.response_path {
+        json_path(path).map_err(|err| format!("invalid JSONPath {path:?}: {err}"))?;
+    }
+
+    Ok(config)
+}
+
+/// How the values replacing placeholders are escaped, depending on where the template is used
+#[derive(Clone, Copy)]
+enum Escape {
+    Url,
+    Json,
+    None,
+}
+
+/// Replaces the `{{name}}` placeholders of a template by the values returned by `resolve`
+fn render<F>(template: &str, escape: Escape, resolve: F) -> std::result::Result<String, String>
+where
+    F: Fn(&str) -> std::result::Result<String, String>,
+{
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            return Err(format!("unclosed placeholder in {template:?}"));
+        };
+
+        let value = resolve(rest[start + 2..start + end].trim())?;
+
+        rendered.push_str(&rest[..start]);
+
+        match escape {
+            Escape::Url => rendered.extend(url::form_urlencoded::byte_serialize(value.as_bytes())),
+            Escape::Json => {
+                let value = Value::String(value).to_string();
+                rendered.push_str(&value[1..value.len() - 1]);
+            }
+            Escape::None => rendered.push_str(&value),
+        }
+
+        rest = &rest[start + end + 2..];
+    }
+
+    rendered.push_str(rest);
+
+    Ok(rendered)
+}
+
+/// Selects the data to return from a response, a single node being returned as is and multiple
+/// ones as an array
+fn extract(data: Value, path: Option<&JsonPath>) -> Value {
+    let Some(path) = path else {
+        return data;
+    };
+
+    match path.query(&data).all().as_slice() {
+        [] => Value::Null,
+        [node] => (*node).clone(),
+        nodes => Value::Array(nodes.iter().map(|node| (*node).clone()).collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_template_with_placeholders_when_rendering_then_escapes_values() {
+        let resolve = |name: &str| match name {
+            "indicator" => Ok("https://example.com/?a=b".to_string()),
+            "secrets.API_KEY" => Ok("\"secret\"".to_string()),
+            _ => Err(format!("unknown placeholder {name:?}")),
+        };
+
+        assert_eq!(
+            Ok(
+                "https://api.example.com/https%3A%2F%2Fexample.com%2F%3Fa%3Db?key=%22secret%22"
+                    .to_string()
+            ),
+            render(
+                "https://api.example.com/{{indicator}}?key={{ secrets.API_KEY }}",
+                Escape::Url,
+                resolve
+            )
+        );
+        assert_eq!(
+            Ok(r#"{"key": "\"secret\""}"#.to_string()),
+            render(r#"{"key": "{{secrets.API_KEY}}"}"#, Escape::Json, resolve)
+        );
+        assert!(render("{{secrets.OTHER}}", Escape::None, resolve).is_err());
+        assert!(render("{{indicator", Escape::None, resolve).is_err());
+    }
+
+    #[test]
+    fn given_response_path_when_extracting_then_returns_selected_nodes() {
+        let data = serde_json::json!({ "data": { "tags": ["a", "b"], "score": 3 } });
+
+        let score = json_path("$.data.score").unwrap();
+        let tags = json_path("$.data.tags[*]").unwrap();
+        let missing = json_path("missing").unwrap();
+
+        assert_eq!(serde_json::json!(3), extract(data.clone(), Some(&score)));
+        assert_eq!(
+            serde_json::json!(["a", "b"]),
+            extract(data.clone(), Some(&tags))
+        );
+        assert_eq!(Value::Null, extract(data, Some(&missing)));
+    }
+}
@@ -1,7 +1,7 @@
 use async_trait::async_trait;
 use tracing::{info, instrument};
 
-use crate::{FetchState, Indicator, Result, Source};
+use crate::{schemas::TaskSummary, FetchState, Indicator, Result, Source};
 
 pub struct Megatron;
 
@@ -20,7 +20,11 @@ impl Source for Megatron {
         Ok(serde_json::Value::Null)
     }
 
-    async fn background_task(&self, _state: &FetchState) -> Result<()> {
+    fn has_background_task(&self) -> bool {
+        true
+    }
+
+    async fn background_task(&self, _state: &FetchState) -> Result<TaskSummary> {
         info!("fetching data");
 
         let client = reqwest::Client::new();
@@ -30,6 +34,6 @@ impl Source for Megatron {
             .send()
             .await?;
 
-        Ok(())
+        Ok(TaskSummary::default())
     }
 }
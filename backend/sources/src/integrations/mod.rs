@@ -3,14 +3,19 @@ use tracing::error;
 
 use super::Source;
 
+pub use feed::parse_feed_config;
+pub use http::parse_http_config;
+
 mod abuse_ip_db;
 mod alienvault_otx;
 mod asn_cymru;
 mod certificate_transparency;
 mod dns;
+mod feed;
 mod gsb_lookup;
 mod gsb_update;
 mod have_i_been_pwned;
+mod http;
 mod internet_archive;
 mod internet_db;
 mod malware_bazaar;
@@ -67,6 +72,10 @@ pub fn source(name: &str, source_kind: &SourceKind) -> Option<Box<dyn Source>> {
         Some(Box::new(urlscan_submit::UrlscanSubmit))
     } else if name == virus_total::VirusTotal.source_name() {
         Some(Box::new(virus_total::VirusTotal))
+    } else if *source_kind == SourceKind::Feed {
+        Some(Box::new(feed::Feed))
+    } else if *source_kind == SourceKind::Http {
+        Some(Box::new(http::Http))
     } else if *source_kind == SourceKind::Python {
         Some(Box::new(python::Python))
     } else {
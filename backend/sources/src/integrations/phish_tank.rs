@@ -1,19 +1,24 @@
 use async_trait::async_trait;
 use database::{
     logic::phish_tank::{
-        get_phish_tank_by_domain, get_phish_tank_by_url, insert_entries, insert_phish_tank_refresh,
+        deactivate_unseen_entries, get_phish_tank_by_domain, get_phish_tank_by_url,
+        get_phish_tank_validators, insert_phish_tank_refresh, upsert_entries,
     },
     schemas::{
         indicators::{Indicator, IndicatorKind},
+        ingestion::IngestionCounts,
         phish_tank::Entry,
     },
 };
 use flate2::read::GzDecoder;
-use futures_util::future::join_all;
-use std::io::Read;
+use std::io::BufReader;
 use tracing::{info, instrument};
 
-use crate::{FetchState, Result, Source};
+use crate::{
+    feeds::{self, FeedResponse},
+    schemas::TaskSummary,
+    Error, FetchState, Result, Source,
+};
 
 pub struct PhishTank;
 
@@ -38,44 +43,81 @@ impl Source for PhishTank {
         Ok(serde_json::json!(data))
     }
 
+    fn has_background_task(&self) -> bool {
+        true
+    }
+
     #[instrument(skip_all, err)]
-    async fn background_task(&self, state: &FetchState) -> Result<()> {
+    async fn background_task(&self, state: &FetchState) -> Result<TaskSummary> {
         info!("fetching data");
 
-        // TODO: do a HEAD request and check if the file has changed
+        let previous_validators = get_phish_tank_validators(&state.pool).await?;
 
-        let client = reqwest::Client::new();
-        let response = client
+        let request = reqwest::Client::new()
             .get("http://data.phishtank.com/data/online-valid.json.gz")
-            .header(reqwest::header::USER_AGENT, "phishtank/username")
-            .send()
-            .await?;
-
-        let headers: String = response
-            .headers()
-            .into_iter()
-            .map(|(k, v)| format!("{}: {}", k, v.to_str().unwrap()))
-            .collect::<Vec<String>>()
-            .join("\n");
+            .header(reqwest::header::USER_AGENT, "phishtank/username");
+
+        let (headers, validators, response) =
+            match feeds::conditional_get(request, &previous_validators).await? {
+                FeedResponse::NotModified { headers } => {
+                    let counts = IngestionCounts::default();
+                    insert_phish_tank_refresh(
+                        &headers,
+                        &previous_validators,
+                        true,
+                        counts,
+                        &state.pool,
+                    )
+                    .await?;
+
+                    return Ok(TaskSummary {
+                        records_ingested: Some(0),
+                        changes: Some(counts),
+                    });
+                }
+                FeedResponse::Modified {
+                    headers,
+                    validators,
+                    response,
+                } => (headers, validators, response),
+            };
 
         let bytes = response.bytes().await?;
 
-        let mut buffer = String::new();
+        let (mut chunks, parser) = feeds::parse_in_chunks(9_000, move |sender| {
+            let decoder = BufReader::new(GzDecoder::new(&*bytes));
+
+            feeds::parse_json_array::<_, Entry>(decoder, sender)
+        });
+
+        let mut transaction = state.pool.begin().await?;
+
+        let mut num_entries = 0;
+        let mut counts = IngestionCounts::default();
+
+        while let Some(chunk) = chunks.recv().await {
+            num_entries += chunk.len();
+            counts += upsert_entries(chunk, &mut *transaction).await?;
+        }
+
+        feeds::parsed(parser).await?;
 
-        let mut decoder = GzDecoder::new(&*bytes);
+        // Most likely a change of format, better keep the current entries than removing them all
+        if num_entries == 0 {
+            return Err(Error::ResponseError);
+        }
 
-        decoder.read_to_string(&mut buffer)?;
+        counts.removed = deactivate_unseen_entries(&mut *transaction).await? as i32;
 
-        let data: Vec<Entry> = serde_json::from_str(&buffer).unwrap();
+        insert_phish_tank_refresh(&headers, &validators, false, counts, &mut *transaction).await?;
 
-        join_all(
-            data.chunks(9_000)
-                .map(|chunk| insert_entries(chunk, &state.pool)),
-        )
-        .await;
+        transaction.commit().await?;
 
-        insert_phish_tank_refresh(&headers, &state.pool).await?;
+        info!(num_entries, ?counts, "feed ingested");
 
-        Ok(())
+        Ok(TaskSummary {
+            records_ingested: Some(num_entries as i32),
+            changes: Some(counts),
+        })
     }
 }
@@ -1,10 +1,9 @@
 use async_trait::async_trait;
 use common::{BackgroundTaskRequest, FetchDataRequest};
 use database::schemas::sources::SourceKind;
-use tonic::transport::Endpoint;
 use tracing::instrument;
 
-use crate::{FetchState, Indicator, Result, Source};
+use crate::{runners, schemas::TaskSummary, FetchState, Indicator, Result, Source};
 
 pub struct Python;
 
@@ -25,30 +24,26 @@ impl Source for Python {
         };
 
         let config = state.get_server_config().await?;
-        let endpoint =
-            Endpoint::from_shared(config.runner_endpoint(&SourceKind::Python).to_string())?;
+        let client = state.runners.client(&SourceKind::Python, &config)?;
 
-        let mut client = common::runner_client::RunnerClient::connect(endpoint).await?;
-
-        let data = client.fetch_data(request).await?.into_inner().data;
+        runners::fetch_data(client, request).await
+    }
 
-        Ok(serde_json::Value::String(data))
+    fn has_background_task(&self) -> bool {
+        true
     }
 
     #[instrument(skip_all, err)]
-    async fn background_task(&self, state: &FetchState) -> Result<()> {
+    async fn background_task(&self, state: &FetchState) -> Result<TaskSummary> {
         let request = BackgroundTaskRequest {
             source: state.source_id.to_string(),
         };
 
         let config = state.get_server_config().await?;
-        let endpoint =
-            Endpoint::from_shared(config.runner_endpoint(&SourceKind::Python).to_string())?;
-
-        let mut client = common::runner_client::RunnerClient::connect(endpoint).await?;
+        let mut client = state.runners.client(&SourceKind::Python, &config)?;
 
         client.background_task(request).await?;
 
-        Ok(())
+        Ok(TaskSummary::default())
     }
 }
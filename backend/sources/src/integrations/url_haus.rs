@@ -1,16 +1,22 @@
 use async_trait::async_trait;
 use database::{
-    logic::url_haus::{insert_entries, insert_url_haus_refresh},
+    logic::url_haus::{
+        deactivate_unseen_entries, get_url_haus_validators, insert_url_haus_refresh, upsert_entries,
+    },
     schemas::{
         indicators::{Indicator, IndicatorKind},
+        ingestion::IngestionCounts,
         url_haus::Entry,
     },
 };
-use futures_util::future::join_all;
-use std::io::Read;
+use std::io::{BufRead, BufReader, Read};
 use tracing::{info, instrument, warn};
 
-use crate::{Error, FetchState, Result, Source};
+use crate::{
+    feeds::{self, FeedResponse},
+    schemas::TaskSummary,
+    Error, FetchState, Result, Source,
+};
 
 pub struct UrlHaus;
 
@@ -35,54 +41,108 @@ impl Source for UrlHaus {
         Ok(serde_json::json!(data))
     }
 
-    #[instrument(skip_all, err)]
-    async fn background_task(&self, state: &FetchState) -> Result<()> {
-        let response = reqwest::get("https://urlhaus.abuse.ch/downloads/csv/").await?;
+    fn has_background_task(&self) -> bool {
+        true
+    }
 
-        let headers: String = response
-            .headers()
-            .into_iter()
-            .map(|(k, v)| format!("{}: {}", k, v.to_str().unwrap()))
-            .collect::<Vec<String>>()
-            .join("\n");
+    #[instrument(skip_all, err)]
+    async fn background_task(&self, state: &FetchState) -> Result<TaskSummary> {
+        let previous_validators = get_url_haus_validators(&state.pool).await?;
+
+        let request = reqwest::Client::new().get("https://urlhaus.abuse.ch/downloads/csv/");
+
+        let (headers, validators, response) =
+            match feeds::conditional_get(request, &previous_validators).await? {
+                FeedResponse::NotModified { headers } => {
+                    let counts = IngestionCounts::default();
+                    insert_url_haus_refresh(
+                        &headers,
+                        &previous_validators,
+                        true,
+                        counts,
+                        &state.pool,
+                    )
+                    .await?;
+
+                    return Ok(TaskSummary {
+                        records_ingested: Some(0),
+                        changes: Some(counts),
+                    });
+                }
+                FeedResponse::Modified {
+                    headers,
+                    validators,
+                    response,
+                } => (headers, validators, response),
+            };
 
         let bytes = response.bytes().await?;
 
-        let mut buffer = String::new();
-
-        {
+        let (mut chunks, parser) = feeds::parse_in_chunks(7_000, move |sender| {
             let mut zip_archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))?;
-            let mut archive_file = zip_archive.by_index(0)?;
+            let archive_file = zip_archive.by_index(0)?;
 
             info!(filename = archive_file.name(), "unzipping file");
 
-            archive_file.read_to_string(&mut buffer)?;
-        }
+            let mut lines = BufReader::new(archive_file);
+
+            // The CSV header is the last line of the comments preceding the entries
+            let header = loop {
+                let mut line = String::new();
 
-        let (_, data) = buffer
-            .split_once("#\r\n#\r\n# ")
-            .ok_or(Error::ResponseError)?;
+                if lines.read_line(&mut line)? == 0 {
+                    return Err(Error::ResponseError);
+                }
 
-        let mut reader = csv::Reader::from_reader(data.as_bytes());
+                if let Some(header) = line.strip_prefix("# id,") {
+                    break format!("id,{header}");
+                }
+            };
 
-        join_all(
-            reader
-                .deserialize()
-                .flat_map(|result| match result {
-                    Ok(entry) => Some(entry),
-                    Err(error) => {
-                        warn!("error parsing entry: {}", error);
-                        None
+            let mut reader = csv::Reader::from_reader(header.as_bytes().chain(lines));
+
+            for result in reader.deserialize::<Entry>() {
+                match result {
+                    Ok(entry) => {
+                        if !sender.push(entry) {
+                            break;
+                        }
                     }
-                })
-                .collect::<Vec<Entry>>()
-                .chunks(7_000)
-                .map(|chunk| insert_entries(chunk, &state.pool)),
-        )
-        .await;
+                    Err(error) => warn!("error parsing entry: {}", error),
+                }
+            }
+
+            Ok(())
+        });
+
+        let mut transaction = state.pool.begin().await?;
+
+        let mut num_entries = 0;
+        let mut counts = IngestionCounts::default();
+
+        while let Some(chunk) = chunks.recv().await {
+            num_entries += chunk.len();
+            counts += upsert_entries(chunk, &mut *transaction).await?;
+        }
+
+        feeds::parsed(parser).await?;
+
+        // Most likely a change of format, better keep the current entries than removing them all
+        if num_entries == 0 {
+            return Err(Error::ResponseError);
+        }
+
+        counts.removed = deactivate_unseen_entries(&mut *transaction).await? as i32;
+
+        insert_url_haus_refresh(&headers, &validators, false, counts, &mut *transaction).await?;
+
+        transaction.commit().await?;
 
-        insert_url_haus_refresh(&headers, &state.pool).await?;
+        info!(num_entries, ?counts, "feed ingested");
 
-        Ok(())
+        Ok(TaskSummary {
+            records_ingested: Some(num_entries as i32),
+            changes: Some(counts),
+        })
     }
 }
@@ -1,11 +1,14 @@
-#![allow(clippy::blocks_in_conditions)]
+#![allow(clippy::blocks_in_conditions, clippy::result_large_err)]
 
 use async_trait::async_trait;
 use database::schemas::indicators::Indicator;
+use schemas::TaskSummary;
 
 pub mod error;
+mod feeds;
 mod helpers;
 pub mod integrations;
+pub mod runners;
 pub mod schemas;
 pub mod state;
 
@@ -25,7 +28,12 @@ pub trait Source: Send + Sync {
         state: &FetchState,
     ) -> Result<serde_json::Value>;
 
-    async fn background_task(&self, _state: &FetchState) -> Result<()> {
-        todo!()
+    /// Whether the source has a background task, the default one failing
+    fn has_background_task(&self) -> bool {
+        false
+    }
+
+    async fn background_task(&self, _state: &FetchState) -> Result<TaskSummary> {
+        Err(Error::MissingBackgroundTask)
     }
 }
@@ -0,0 +1,707 @@
+use common::{
+    health::pb::{
+        health_check_response::ServingStatus, health_client::HealthClient, HealthCheckRequest,
+    },
+    merge_fetch_data_replies,
+    runner_client::RunnerClient,
+    source_code_hash, DeleteRequest, Empty, EnvironmentStatus, FetchDataErrorKind, FetchDataReply,
+    FetchDataRequest, InitRequest, LogLevel, TestFetchDataRequest, UpdateRequest,
+};
+use database::{
+    logic::sources::get_source_code_by_kind,
+    schemas::{indicators::Indicator, server_config::ServerConfig, sources::SourceKind},
+    PgPool,
+};
+use futures_util::future::join_all;
+use std::{
+    collections::{BTreeMap, HashMap},
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{
+    sync::{mpsc, watch},
+    task::AbortHandle,
+    time::MissedTickBehavior,
+};
+use tonic::transport::{Channel, Endpoint};
+use tower::discover::Change;
+use tracing::{error, info, instrument, warn};
+
+use crate::{
+    schemas::{
+        RunnerInstance, RunnerSync, SourceCodeError, SourceEnvironment, SourceEnvironmentStatus,
+        SourceError, SourceLog, SourceLogLevel, SourcePackage, SourcePlaygroundResult,
+    },
+    Error, Result,
+};
+
+const MIN_RECONNECT_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Long lived gRPC channels to the runners, one per runner kind, shared by the whole server. Each
+/// channel balances requests between the instances of a runner reporting themselves as serving
+/// through the gRPC health service, ejecting the others until they recover.
+#[derive(Clone)]
+pub struct RunnerPool {
+    pool: PgPool,
+    sync_interval: Duration,
+    groups: Arc<Mutex<HashMap<SourceKind, Arc<RunnerGroup>>>>,
+}
+
+impl RunnerPool {
+    /// Sources held by the serving runner instances are compared with the database at every
+    /// `sync_interval`
+    pub fn new(pool: PgPool, sync_interval: Duration) -> Self {
+        Self {
+            pool,
+            sync_interval,
+            groups: Default::default(),
+        }
+    }
+
+    /// Instances of a runner kind, rebuilt whenever their addresses changed in the server config
+    pub fn group(&self, kind: &SourceKind, config: &ServerConfig) -> Result<Arc<RunnerGroup>> {
+        let addresses = config
+            .runner_endpoints(kind)
+            .into_iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+
+        let mut groups = self.groups.lock().map_err(|_| Error::InternalError)?;
+
+        if let Some(group) = groups.get(kind).filter(|g| g.addresses == addresses) {
+            return Ok(group.clone());
+        }
+
+        info!(?kind, ?addresses, "connecting to runners");
+
+        let group = Arc::new(RunnerGroup::new(
+            kind,
+            addresses,
+            &self.pool,
+            self.sync_interval,
+        )?);
+        groups.insert(kind.clone(), group.clone());
+
+        Ok(group)
+    }
+
+    /// Client balancing requests between the healthy instances of a runner kind
+    pub fn client(
+        &self,
+        kind: &SourceKind,
+        config: &ServerConfig,
+    ) -> Result<RunnerClient<Channel>> {
+        let group = self.group(kind, config)?;
+
+        // Requests would wait forever for an instance to be available otherwise
+        if *group.status().borrow() != ServingStatus::Serving {
+            return Err(Error::TonicStatus(tonic::Status::unavailable(format!(
+                "no healthy {kind} runner"
+            ))));
+        }
+
+        Ok(RunnerClient::new(group.channel.clone()))
+    }
+
+    /// Instances of the runners connected to so far
+    pub fn instances(&self) -> Result<Vec<RunnerInstance>> {
+        let groups = self.groups.lock().map_err(|_| Error::InternalError)?;
+
+        let mut instances = groups
+            .iter()
+            .flat_map(|(kind, group)| {
+                group.endpoints.list.iter().map(|endpoint| RunnerInstance {
+                    kind: kind.clone(),
+                    address: endpoint.address.clone(),
+                    status: endpoint.status.borrow().as_str_name().to_string(),
+                    sync: endpoint.sync.lock().ok().and_then(|sync| sync.clone()),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        instances.sort_by(|a, b| {
+            (a.kind.to_string(), &a.address).cmp(&(b.kind.to_string(), &b.address))
+        });
+
+        Ok(instances)
+    }
+}
+
+/// Instances of a runner kind
+pub struct RunnerGroup {
+    addresses: Vec<String>,
+    channel: Channel,
+    endpoints: Arc<RunnerEndpoints>,
+    watchers: Vec<AbortHandle>,
+}
+
+impl RunnerGroup {
+    fn new(
+        kind: &SourceKind,
+        addresses: Vec<String>,
+        pool: &PgPool,
+        sync_interval: Duration,
+    ) -> Result<Self> {
+        let endpoints = addresses
+            .iter()
+            .map(|address| {
+                let endpoint = Endpoint::from_shared(address.clone())?
+                    .connect_timeout(Duration::from_secs(5))
+                    .http2_keep_alive_interval(Duration::from_secs(30))
+                    .keep_alive_while_idle(true);
+
+                Ok(RunnerEndpoint {
+                    address: address.clone(),
+                    channel: endpoint.connect_lazy(),
+                    endpoint,
+                    status: watch::Sender::new(ServingStatus::Unknown),
+                    sync: Mutex::new(None),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let endpoints = Arc::new(RunnerEndpoints {
+            list: endpoints,
+            status: watch::Sender::new(ServingStatus::Unknown),
+        });
+
+        let (channel, balance) = Channel::balance_channel(addresses.len().max(1));
+
+        let watchers = (0..addresses.len())
+            .map(|index| {
+                tokio::spawn(watch_endpoint(
+                    kind.clone(),
+                    endpoints.clone(),
+                    index,
+                    balance.clone(),
+                    pool.clone(),
+                    sync_interval,
+                ))
+                .abort_handle()
+            })
+            .collect();
+
+        Ok(Self {
+            addresses,
+            channel,
+            endpoints,
+            watchers,
+        })
+    }
+
+    /// Serving if any instance is serving
+    pub fn status(&self) -> watch::Receiver<ServingStatus> {
+        self.endpoints.status.subscribe()
+    }
+
+    /// Sends a request to every serving instance, since each of them holds its own copy of the
+    /// sources' code. The other instances receive the latest code once they recover, and any
+    /// request lost along the way is caught up by the periodic synchronization.
+    pub async fn broadcast<F, Fut>(&self, send: F) -> Result<()>
+    where
+        F: Fn(RunnerClient<Channel>) -> Fut,
+        Fut: Future<Output = std::result::Result<tonic::Response<Empty>, tonic::Status>>,
+    {
+        let serving = self
+            .endpoints
+            .list
+            .iter()
+            .filter(|endpoint| *endpoint.status.borrow() == ServingStatus::Serving)
+            .collect::<Vec<_>>();
+
+        let results = join_all(
+            serving
+                .iter()
+                .map(|endpoint| send(RunnerClient::new(endpoint.channel.clone()))),
+        )
+        .await;
+
+        let mut first_error = None;
+
+        for (endpoint, result) in serving.iter().zip(results) {
+            if let Err(err) = result {
+                warn!(address = endpoint.address, ?err, "runner request failed");
+                first_error.get_or_insert(err);
+            }
+        }
+
+        match first_error {
+            Some(err) => Err(err.into()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for RunnerGroup {
+    fn drop(&mut self) {
+        for watcher in &self.watchers {
+            watcher.abort();
+        }
+    }
+}
+
+struct RunnerEndpoints {
+    list: Vec<RunnerEndpoint>,
+    status: watch::Sender<ServingStatus>,
+}
+
+impl RunnerEndpoints {
+    fn set_status(&self, index: usize, status: ServingStatus) {
+        self.list[index].status.send_replace(status);
+
+        let statuses = self
+            .list
+            .iter()
+            .map(|endpoint| *endpoint.status.borrow())
+            .collect::<Vec<_>>();
+
+        let aggregate = if statuses.contains(&ServingStatus::Serving) {
+            ServingStatus::Serving
+        } else if statuses.contains(&ServingStatus::NotServing) {
+            ServingStatus::NotServing
+        } else {
+            ServingStatus::Unknown
+        };
+
+        self.status.send_if_modified(|current| {
+            let modified = *current != aggregate;
+            *current = aggregate;
+            modified
+        });
+    }
+}
+
+struct RunnerEndpoint {
+    address: String,
+    endpoint: Endpoint,
+    /// Dedicated to this instance, for health checks and broadcasted requests
+    channel: Channel,
+    /// Serving only once the instance has the sources' code and was added to the balanced channel
+    status: watch::Sender<ServingStatus>,
+    /// Last synchronization of the sources' code with the instance
+    sync: Mutex<Option<RunnerSync>>,
+}
+
+/// Watches the health of a runner instance, adding it to the balanced channel once it is serving
+/// and has the sources' code, removing it otherwise, and reconnecting with an exponential backoff
+/// when the connection is lost. While serving, its sources are periodically compared with the
+/// database to re-sync any drift.
+#[instrument(skip(endpoints, balance, pool), fields(address = endpoints.list[index].address))]
+async fn watch_endpoint(
+    kind: SourceKind,
+    endpoints: Arc<RunnerEndpoints>,
+    index: usize,
+    balance: mpsc::Sender<Change<String, Endpoint>>,
+    pool: PgPool,
+    sync_interval: Duration,
+) {
+    let endpoint = &endpoints.list[index];
+    let mut backoff = MIN_RECONNECT_BACKOFF;
+
+    loop {
+        let request = HealthCheckRequest {
+            service: "".to_string(),
+        };
+
+        match HealthClient::new(endpoint.channel.clone())
+            .watch(request)
+            .await
+        {
+            Ok(response) => {
+                let mut stream = response.into_inner();
+
+                let mut sync = tokio::time::interval(sync_interval);
+                sync.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                sync.reset();
+
+                loop {
+                    let in_balance = *endpoint.status.borrow() == ServingStatus::Serving;
+
+                    let message = tokio::select! {
+                        message = stream.message() => message,
+                        _ = sync.tick(), if in_balance => {
+                            endpoint.sync_sources(&pool, &kind).await;
+                            continue;
+                        }
+                    };
+
+                    let status = match message {
+                        Ok(Some(response)) => response.status(),
+                        Ok(None) => break,
+                        Err(err) => {
+                            warn!(?err, "runner health check failed");
+                            break;
+                        }
+                    };
+
+                    if status == ServingStatus::Serving && !in_balance {
+                        if !endpoint.sync_sources(&pool, &kind).await {
+                            break;
+                        }
+
+                        let change =
+                            Change::Insert(endpoint.address.clone(), endpoint.endpoint.clone());
+                        if balance.send(change).await.is_err() {
+                            return;
+                        }
+
+                        info!("runner serving");
+                        backoff = MIN_RECONNECT_BACKOFF;
+                        sync.reset();
+                    } else if status != ServingStatus::Serving && in_balance {
+                        let change = Change::Remove(endpoint.address.clone());
+                        if balance.send(change).await.is_err() {
+                            return;
+                        }
+
+                        warn!(?status, "runner not serving");
+                    }
+
+                    endpoints.set_status(index, status);
+                }
+            }
+            Err(err) => warn!(?err, "unable to reach runner"),
+        }
+
+        if *endpoint.status.borrow() == ServingStatus::Serving
+            && balance
+                .send(Change::Remove(endpoint.address.clone()))
+                .await
+                .is_err()
+        {
+            return;
+        }
+
+        endpoints.set_status(index, ServingStatus::Unknown);
+
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
+    }
+}
+
+impl RunnerEndpoint {
+    /// Compares the sources held by the instance with the database and re-syncs the drift,
+    /// returns whether the instance is in sync
+    async fn sync_sources(&self, pool: &PgPool, kind: &SourceKind) -> bool {
+        let sync = match sync_sources(pool, kind, &self.channel).await {
+            Ok(sync) => {
+                if !sync.missing.is_empty() || !sync.stale.is_empty() || !sync.extra.is_empty() {
+                    warn!(
+                        missing = ?sync.missing,
+                        stale = ?sync.stale,
+                        extra = ?sync.extra,
+                        "re-synced drifted runner sources"
+                    );
+                }
+
+                sync
+            }
+            Err(err) => {
+                error!(?err, "unable to sync runner sources");
+
+                RunnerSync {
+                    checked_at: chrono::Utc::now().naive_utc(),
+                    num_sources: 0,
+                    missing: Vec::new(),
+                    stale: Vec::new(),
+                    extra: Vec::new(),
+                    environments: Vec::new(),
+                    error: Some(err.to_string()),
+                }
+            }
+        };
+
+        let in_sync = sync.error.is_none();
+
+        if let Ok(mut current) = self.sync.lock() {
+            *current = Some(sync);
+        }
+
+        in_sync
+    }
+}
+
+/// Brings the sources held by a runner instance in line with the database, sending the code of
+/// the missing and outdated sources and deleting the others
+async fn sync_sources(pool: &PgPool, kind: &SourceKind, channel: &Channel) -> Result<RunnerSync> {
+    let checked_at = chrono::Utc::now().naive_utc();
+    let mut client = RunnerClient::new(channel.clone());
+
+    let expected = get_source_code_by_kind(pool, kind)
+        .await?
+        .into_iter()
+        .filter_map(|s| {
+            s.source_code.map(|source_code| UpdateRequest {
+                hash: source_code_hash(&source_code),
+                source: s.id,
+                source_code,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let versions = client.list_sources(Empty {}).await?.into_inner().sources;
+
+    let environments = versions
+        .iter()
+        .filter_map(|version| {
+            let environment = version.environment.as_ref()?;
+
+            Some(SourceEnvironment {
+                source: version.source.clone(),
+                requirements: environment.requirements.clone(),
+                status: match environment.status() {
+                    EnvironmentStatus::Installing => SourceEnvironmentStatus::Installing,
+                    EnvironmentStatus::Ready => SourceEnvironmentStatus::Ready,
+                    EnvironmentStatus::Failed => SourceEnvironmentStatus::Failed,
+                },
+                packages: environment
+                    .packages
+                    .iter()
+                    .map(|package| SourcePackage {
+                        name: package.name.clone(),
+                        version: package.version.clone(),
+                    })
+                    .collect(),
+                error: Some(environment.error.clone()).filter(|error| !error.is_empty()),
+            })
+        })
+        .collect();
+
+    let mut actual = versions
+        .into_iter()
+        .map(|version| (version.source, version.hash))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut sync = RunnerSync {
+        checked_at,
+        num_sources: expected.len() as i32,
+        missing: Vec::new(),
+        stale: Vec::new(),
+        extra: Vec::new(),
+        environments,
+        error: None,
+    };
+
+    let mut updates = Vec::new();
+
+    for update in expected {
+        match actual.remove(&update.source) {
+            None => sync.missing.push(update.source.clone()),
+            Some(hash) if hash != update.hash => sync.stale.push(update.source.clone()),
+            Some(_) => continue,
+        }
+
+        updates.push(update);
+    }
+
+    sync.extra = actual.into_keys().collect();
+
+    if !updates.is_empty() {
+        client.init(InitRequest { updates }).await?;
+    }
+
+    for source in &sync.extra {
+        client
+            .delete(DeleteRequest {
+                source: source.clone(),
+            })
+            .await?;
+    }
+
+    Ok(sync)
+}
+
+/// Fetches data from a source executed by a runner, gathering the results streamed by the runner
+/// and recording the logs of the source's code
+#[instrument(skip(client), err)]
+pub(crate) async fn fetch_data(
+    mut client: RunnerClient<Channel>,
+    request: FetchDataRequest,
+) -> Result<serde_json::Value> {
+    let mut stream = client.fetch_data_stream(request).await?.into_inner();
+    let mut replies = Vec::new();
+
+    while let Some(reply) = stream.message().await? {
+        replies.push(reply);
+    }
+
+    fetch_data_result(merge_fetch_data_replies(replies))
+}
+
+/// Runs source code that is not stored by the runner on an indicator, for trying a source before
+/// it is saved
+#[instrument(skip(client, source_code), err)]
+pub async fn test_fetch_data(
+    mut client: RunnerClient<Channel>,
+    indicator: &Indicator,
+    source_code: String,
+) -> Result<SourcePlaygroundResult> {
+    let reply = client
+        .test_fetch_data(TestFetchDataRequest {
+            indicator: Some(common::Indicator {
+                data: indicator.data.clone(),
+                kind: indicator.db_kind(),
+            }),
+            source_code,
+        })
+        .await?
+        .into_inner();
+
+    let duration_ms = reply.duration_ms.try_into().unwrap_or(i32::MAX);
+    let reply = reply.reply.unwrap_or_default();
+
+    let logs = reply
+        .logs
+        .iter()
+        .map(|log| SourceLog {
+            level: match log.level() {
+                LogLevel::Info => SourceLogLevel::Info,
+                LogLevel::Warning => SourceLogLevel::Warning,
+                LogLevel::Error => SourceLogLevel::Error,
+            },
+            message: log.message.clone(),
+        })
+        .collect();
+
+    let (data, error) = match reply.error {
+        Some(error) => (
+            None,
+            Some(SourceCodeError {
+                kind: fetch_data_error(error.kind()).into(),
+                traceback: Some(error.traceback).filter(|traceback| !traceback.is_empty()),
+                message: error.message,
+            }),
+        ),
+        None => match serde_json::from_str(&reply.data) {
+            Ok(data) => (Some(data), None),
+            Err(err) => (
+                None,
+                Some(SourceCodeError {
+                    kind: SourceError::ResponseError,
+                    message: format!("invalid JSON returned by the runner: {err}"),
+                    traceback: None,
+                }),
+            ),
+        },
+    };
+
+    Ok(SourcePlaygroundResult {
+        data,
+        error,
+        logs,
+        duration_ms,
+    })
+}
+
+fn fetch_data_result(reply: FetchDataReply) -> Result<serde_json::Value> {
+    for log in &reply.logs {
+        match log.level() {
+            LogLevel::Info => info!(message = log.message, "source log"),
+            LogLevel::Warning => warn!(message = log.message, "source log"),
+            LogLevel::Error => error!(message = log.message, "source log"),
+        }
+    }
+
+    if let Some(error) = reply.error {
+        warn!(kind = ?error.kind(), message = error.message, "source failed to fetch data");
+
+        return Err(fetch_data_error(error.kind()));
+    }
+
+    Ok(serde_json::from_str(&reply.data)?)
+}
+
+fn fetch_data_error(kind: FetchDataErrorKind) -> Error {
+    match kind {
+        FetchDataErrorKind::NotFound => Error::NotFound,
+        FetchDataErrorKind::RateLimited => Error::RateLimited,
+        FetchDataErrorKind::Unauthorized => Error::Unauthorized,
+        FetchDataErrorKind::Timeout => Error::Timeout,
+        FetchDataErrorKind::Internal => Error::InternalError,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(addresses: &str) -> ServerConfig {
+        let mut config = ServerConfig::default();
+        config.python_runner_grpc_address.value = Some(addresses.to_string());
+        config
+    }
+
+    #[tokio::test]
+    async fn given_same_addresses_when_getting_group_then_reuses_it_until_they_change() {
+        let runners = RunnerPool::new(
+            PgPool::connect_lazy("postgres://localhost/test").unwrap(),
+            Duration::from_secs(60),
+        );
+
+        let first = runners
+            .group(
+                &SourceKind::Python,
+                &config("http://[::1]:1, http://[::1]:2"),
+            )
+            .unwrap();
+        let second = runners
+            .group(
+                &SourceKind::Python,
+                &config("http://[::1]:1,http://[::1]:2,"),
+            )
+            .unwrap();
+        let third = runners
+            .group(&SourceKind::Python, &config("http://[::1]:1"))
+            .unwrap();
+
+        assert_eq!(vec!["http://[::1]:1", "http://[::1]:2"], first.addresses);
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(!Arc::ptr_eq(&first, &third));
+    }
+
+    #[tokio::test]
+    async fn given_unreachable_runner_when_getting_client_then_returns_unavailable() {
+        let runners = RunnerPool::new(
+            PgPool::connect_lazy("postgres://localhost/test").unwrap(),
+            Duration::from_secs(60),
+        );
+        let config = config("http://[::1]:1");
+
+        let mut status = runners
+            .group(&SourceKind::Python, &config)
+            .unwrap()
+            .status();
+        status.mark_changed();
+        status.changed().await.unwrap();
+
+        assert_eq!(ServingStatus::Unknown, *status.borrow());
+        assert!(matches!(
+            runners.client(&SourceKind::Python, &config),
+            Err(Error::TonicStatus(status)) if status.code() == tonic::Code::Unavailable
+        ));
+    }
+
+    #[test]
+    fn given_runner_reply_when_converting_then_parses_data_or_maps_error() {
+        let reply = FetchDataReply {
+            data: r#"{"score":3}"#.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::json!({ "score": 3 }),
+            fetch_data_result(reply).unwrap()
+        );
+
+        let reply = FetchDataReply {
+            error: Some(common::FetchDataError {
+                kind: FetchDataErrorKind::NotFound as i32,
+                message: "NotFound: unknown domain".to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert!(matches!(fetch_data_result(reply), Err(Error::NotFound)));
+    }
+}
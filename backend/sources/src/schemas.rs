@@ -1,10 +1,143 @@
-use database::schemas::sources::SourceKind;
+use chrono::NaiveDateTime;
+use database::schemas::{ingestion::IngestionCounts, sources::SourceKind};
 use serde::Serialize;
 use typeshare::typeshare;
 use utoipa::ToSchema;
 
 use crate::Error;
 
+/// Summary of an execution of a source's background task
+#[derive(Debug, Default, Clone)]
+pub struct TaskSummary {
+    /// Number of records read by the execution, if the task ingests records
+    pub records_ingested: Option<i32>,
+    /// Number of records added, updated and removed by the execution, if the task ingests records
+    pub changes: Option<IngestionCounts>,
+}
+
+/// Instance of a runner, as seen by this server
+#[derive(Serialize, Debug, ToSchema, Clone)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct RunnerInstance {
+    /// Kind of sources executed by the runner
+    pub kind: SourceKind,
+    /// gRPC address of the instance
+    pub address: String,
+    /// Health of the instance, `SERVING` only once it received the sources' code
+    pub status: String,
+    /// Last synchronization of the sources' code with the instance, if any
+    pub sync: Option<RunnerSync>,
+}
+
+/// Synchronization of the sources' code with a runner instance, the drift found being re-synced
+#[derive(Serialize, Debug, ToSchema, Clone)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct RunnerSync {
+    /// When the instance's sources were compared with the database
+    pub checked_at: NaiveDateTime,
+    /// Number of sources expected on the instance
+    pub num_sources: i32,
+    /// IDs of the sources missing from the instance
+    pub missing: Vec<String>,
+    /// IDs of the sources whose code on the instance was outdated
+    pub stale: Vec<String>,
+    /// IDs of the sources on the instance that no longer exist or no longer have code
+    pub extra: Vec<String>,
+    /// Environments of the sources declaring requirements, as installed on the instance
+    pub environments: Vec<SourceEnvironment>,
+    /// Error that prevented the synchronization, if any
+    pub error: Option<String>,
+}
+
+/// Status of the installation of a source's requirements
+#[derive(Serialize, Debug, ToSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[typeshare]
+pub enum SourceEnvironmentStatus {
+    Installing,
+    Ready,
+    Failed,
+}
+
+/// Package installed in the environment of a source
+#[derive(Serialize, Debug, ToSchema, Clone)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct SourcePackage {
+    /// Name of the package
+    pub name: String,
+    /// Installed version of the package
+    pub version: String,
+}
+
+/// Isolated environment of a runner instance, containing the packages required by a source
+#[derive(Serialize, Debug, ToSchema, Clone)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct SourceEnvironment {
+    /// ID of the source
+    pub source: String,
+    /// Requirements declared by the source
+    pub requirements: Vec<String>,
+    /// Status of the installation of the requirements
+    pub status: SourceEnvironmentStatus,
+    /// Packages installed in the environment, once it is ready
+    pub packages: Vec<SourcePackage>,
+    /// Why the requirements could not be installed, if they failed
+    pub error: Option<String>,
+}
+
+/// Level of a log emitted by a source's code
+#[derive(Serialize, Debug, ToSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[typeshare]
+pub enum SourceLogLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+/// Log emitted by a source's code, such as what it printed
+#[derive(Serialize, Debug, ToSchema, Clone)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct SourceLog {
+    /// Level of the log, `ERROR` for what was printed to stderr
+    pub level: SourceLogLevel,
+    /// Content of the log
+    pub message: String,
+}
+
+/// Error raised by a source's code
+#[derive(Serialize, Debug, ToSchema, Clone)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct SourceCodeError {
+    /// Kind of the error, as it would be reported when fetching data from the source
+    pub kind: SourceError,
+    /// Message of the error, including the type of the exception
+    pub message: String,
+    /// Formatted stack trace of the exception, if any
+    pub traceback: Option<String>,
+}
+
+/// Result of running a source's code that was not saved, nothing about it being stored
+#[derive(Serialize, Debug, ToSchema, Clone)]
+#[serde(rename_all = "camelCase")]
+#[typeshare]
+pub struct SourcePlaygroundResult {
+    /// Data returned by the source, partial results being gathered in an array
+    pub data: Option<serde_json::Value>,
+    /// Error raised by the source, if any
+    pub error: Option<SourceCodeError>,
+    /// Logs emitted by the source while fetching data
+    pub logs: Vec<SourceLog>,
+    /// Time spent running the source's code, in milliseconds
+    pub duration_ms: i32,
+}
+
 /// Error encountered when fetching data from a source
 #[derive(Serialize, Debug, ToSchema, Clone)]
 #[serde(tag = "kind", content = "content", rename_all = "SCREAMING_SNAKE_CASE")]
@@ -35,7 +168,7 @@ impl From<Error> for SourceError {
             Error::NotFound => SourceError::NotFound,
             Error::Unauthorized => SourceError::Unauthorized,
             Error::Reqwest(_) => SourceError::RequestError,
-            Error::ResponseError => SourceError::ResponseError,
+            Error::ResponseError | Error::SerdeJson(_) => SourceError::ResponseError,
             Error::Postgres(_) => SourceError::DatabaseError,
             Error::MissingSourceCode => SourceError::MissingSourceCode,
             Error::RateLimited => SourceError::RateLimited,
@@ -3,10 +3,11 @@ use database::{
 };
 use std::collections::HashMap;
 
-use crate::Result;
+use crate::{runners::RunnerPool, Result};
 
 pub struct FetchState {
     pub pool: database::PgPool,
+    pub runners: RunnerPool,
     pub secrets: HashMap<String, String>,
     pub source_id: String,
 }
@@ -14,11 +15,13 @@ pub struct FetchState {
 impl FetchState {
     pub fn new(
         pool: database::PgPool,
+        runners: RunnerPool,
         secrets: HashMap<String, String>,
         source_id: String,
     ) -> Self {
         Self {
             pool,
+            runners,
             secrets,
             source_id,
         }
@@ -5,6 +5,7 @@ import config from "@/lib/config";
 import { store } from "@/atoms";
 import { userAtom } from "@/atoms/auth";
 import { router } from "@/navigation";
+import { SessionTokens } from "@/types/backendTypes";
 
 type GenericOptions = {
   params?: unknown;
@@ -17,11 +18,51 @@ type DataOptions = GenericOptions & {
   form?: FormData;
 };
 
+let pendingRefresh: Promise<boolean> | undefined;
+
+// Exchanges the refresh token for a new access token, sharing a single
+// request between concurrent callers since refresh tokens are single use
+const refreshSession = () => {
+  pendingRefresh ??= (async () => {
+    const user = store.get(userAtom);
+    if (!user?.refreshToken) return false;
+
+    try {
+      const response = await fetch(
+        config.rest_server_base_url + "/auth/refresh",
+        {
+          method: "POST",
+          headers: { "Content-Type": "application/json" },
+          body: JSON.stringify({ refreshToken: user.refreshToken }),
+        },
+      );
+
+      if (!response.ok) return false;
+
+      const tokens = (await response.json()) as SessionTokens;
+      store.set(userAtom, {
+        ...user,
+        token: tokens.jwtToken,
+        refreshToken: tokens.refreshToken,
+      });
+
+      return true;
+    } catch {
+      return false;
+    }
+  })().finally(() => {
+    pendingRefresh = undefined;
+  });
+
+  return pendingRefresh;
+};
+
 const axiosKiller = async <T>(
   endpoint: string,
   method: "POST" | "GET" | "PATCH" | "DELETE" | "PUT",
   options?: DataOptions,
-) => {
+  retried = false,
+): Promise<T | string> => {
   const token = store.get(userAtom)?.token;
 
   const headers = {
@@ -55,6 +96,15 @@ const axiosKiller = async <T>(
     keepalive: true,
   });
 
+  if (
+    response.status === 401 &&
+    !retried &&
+    !endpoint.startsWith("/auth") &&
+    (await refreshSession())
+  ) {
+    return axiosKiller<T>(endpoint, method, options, true);
+  }
+
   if (response.status === 404) {
     throw notFound();
   }
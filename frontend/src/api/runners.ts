@@ -1,11 +1,13 @@
-import { useQuery } from "@tanstack/react-query";
+import { queryOptions, useQuery } from "@tanstack/react-query";
 import { fetchEventSource } from "@microsoft/fetch-event-source";
 
+import { fetcher } from "@/api";
 import config from "@/lib/config";
-import { SourceKind } from "@/types/backendTypes";
+import { Permission, RunnerInstance, SourceKind } from "@/types/backendTypes";
 import { store } from "@/atoms";
 import { userAtom } from "@/atoms/auth";
 import { queryClient } from "@/lib/query";
+import { userHasPermissions } from "@/lib/auth";
 
 export type RunnerStatus =
   | "UNKNOWN"
@@ -20,6 +22,7 @@ interface RunnersStatus {
 
 export const useRunnersStatus = () =>
   useQuery<RunnersStatus>({
+    enabled: userHasPermissions(store.get(userAtom), [Permission.RunnerView]),
     refetchOnWindowFocus: false,
     queryKey: ["runners", "status"],
     queryFn: async ({ queryKey }) =>
@@ -48,3 +51,11 @@ export const useRunnersStatus = () =>
         });
       }),
   });
+
+export const runnerInstancesQueryOptions = queryOptions({
+  queryKey: ["runners", "instances"],
+  queryFn: async ({ signal }) =>
+    await fetcher.get<RunnerInstance[]>("/runners/status", {
+      signal,
+    }),
+});
@@ -8,6 +8,8 @@ import {
   IgnoreList,
   Request,
   Source,
+  SourcePlaygroundRequest,
+  SourcePlaygroundResult,
   SourceSecret,
   UpdateSource,
 } from "@/types/backendTypes";
@@ -115,6 +117,14 @@ export const useCreateSourceMutation = () =>
       ]),
   });
 
+export const useSourcePlaygroundMutation = () =>
+  useMutation({
+    mutationFn: async (data: SourcePlaygroundRequest) =>
+      await fetcher.post<SourcePlaygroundResult>("/sources/playground", {
+        data,
+      }),
+  });
+
 export const useDeleteSourceMutation = () =>
   useMutation({
     mutationFn: async (id: string) => await fetcher.delete(`/sources/${id}`),
@@ -1,4 +1,5 @@
 import { atomWithLocalStorage } from "@/atoms";
+import { Permission } from "@/types/backendTypes";
 
 export interface User {
   id: string;
@@ -7,7 +8,9 @@ export interface User {
   givenName?: string;
   familyName?: string;
   roles: string[];
+  permissions: Permission[];
   token: string;
+  refreshToken?: string;
   initials: string;
 }
 
@@ -2,18 +2,19 @@ import { Link, LinkProps } from "@tanstack/react-router";
 import { useAtomValue } from "jotai";
 
 import { userAtom } from "@/atoms/auth";
-import { userHasRoles } from "@/lib/auth";
+import { userHasPermissions } from "@/lib/auth";
+import { Permission } from "@/types/backendTypes";
 
 interface Props
   extends LinkProps,
     Pick<React.HTMLAttributes<HTMLAnchorElement>, "title"> {
-  roles?: string[];
+  permissions?: Permission[];
 }
 
-const AuthLink: React.FC<Props> = ({ roles = [], ...props }) => {
+const AuthLink: React.FC<Props> = ({ permissions = [], ...props }) => {
   const user = useAtomValue(userAtom);
 
-  if (user && !userHasRoles(user, roles)) {
+  if (user && !userHasPermissions(user, permissions)) {
     return <>{props.children}</>;
   }
 
@@ -1,19 +1,20 @@
 import { useAtomValue } from "jotai";
 
 import { userAtom } from "@/atoms/auth";
-import { userHasRoles } from "@/lib/auth";
+import { userHasPermissions } from "@/lib/auth";
+import { Permission } from "@/types/backendTypes";
 
 interface Props {
-  roles: string[];
+  permissions: Permission[];
 }
 
 const AuthVisible: React.FC<React.PropsWithChildren<Props>> = ({
-  roles,
+  permissions,
   children,
 }) => {
   const user = useAtomValue(userAtom);
 
-  if (user && !userHasRoles(user, roles)) {
+  if (user && !userHasPermissions(user, permissions)) {
     return null;
   }
 
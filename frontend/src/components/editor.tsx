@@ -44,6 +44,8 @@ const SourceKindLanguageMap: { [key in SourceKind]: string } = {
   [SourceKind.Python]: "python",
   [SourceKind.System]: "rust",
   [SourceKind.JavaScript]: "javascript",
+  [SourceKind.Feed]: "json",
+  [SourceKind.Http]: "json",
 };
 
 const Editor: React.FC<Props> = ({ value, onChange, ...props }) => {
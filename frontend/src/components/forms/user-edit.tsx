@@ -15,13 +15,13 @@ import {
 } from "@/components/ui/form";
 import { cn } from "@/lib/utils";
 import { Button } from "@/components/ui/button";
-import { User } from "@/types/backendTypes";
+import { Role, User } from "@/types/backendTypes";
 import { ArrayField } from "@/components/forms/inputs";
 import { useTranslation } from "@/i18n";
 
 const formSchema = z.object({
   enabled: z.boolean(),
-  roles: z.array(z.string()),
+  roles: z.array(z.nativeEnum(Role)),
 });
 
 export type FormSchema = z.infer<typeof formSchema>;
@@ -35,11 +35,11 @@ const UserEdit: React.FC<Props> = ({ user, onSubmit }) => {
   const { t } = useTranslation();
   const form = useForm<FormSchema>({
     resolver: zodResolver(formSchema),
-    defaultValues: { enabled: user.enabled, roles: user.roles },
+    defaultValues: { enabled: user.enabled, roles: user.roles as Role[] },
   });
 
   useEffect(() => {
-    form.reset({ enabled: user.enabled, roles: user.roles });
+    form.reset({ enabled: user.enabled, roles: user.roles as Role[] });
     // eslint-disable-next-line react-hooks/exhaustive-deps
   }, [form]);
 
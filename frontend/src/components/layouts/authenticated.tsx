@@ -43,7 +43,8 @@ import config from "@/lib/config";
 import { useWindowWidth } from "@/hooks/useWindowWidth";
 import { userAtom } from "@/atoms/auth";
 import { useTranslation } from "@/i18n";
-import { userHasAnyRoles } from "@/lib/auth";
+import { userHasAnyPermissions } from "@/lib/auth";
+import { Permission } from "@/types/backendTypes";
 
 type Page =
   | "home"
@@ -125,7 +126,7 @@ export const Layout: React.FC = () => {
             isCollapsed={isCollapsed}
             links={[
               {
-                roles: ["request_create"],
+                permissions: [Permission.RequestCreate],
                 title: t("requests"),
                 icon: Send,
                 to: "/request",
@@ -133,7 +134,7 @@ export const Layout: React.FC = () => {
                 preload: false,
               },
               {
-                roles: ["request_view"],
+                permissions: [Permission.RequestView],
                 title: t("history"),
                 label: statsCount.data.history,
                 icon: History,
@@ -142,7 +143,10 @@ export const Layout: React.FC = () => {
               },
             ]}
           />
-          {userHasAnyRoles(user, ["request_create", "request_view"]) && (
+          {userHasAnyPermissions(user, [
+            Permission.RequestCreate,
+            Permission.RequestView,
+          ]) && (
             <div className={cn("mx-4", isCollapsed && "mx-2")}>
               <Separator />
             </div>
@@ -151,6 +155,7 @@ export const Layout: React.FC = () => {
             isCollapsed={isCollapsed}
             links={[
               {
+                permissions: [Permission.SourceView],
                 title: t("sources"),
                 label: statsCount.data.sources,
                 to: "/sources",
@@ -158,6 +163,7 @@ export const Layout: React.FC = () => {
                 icon: Database,
               },
               {
+                permissions: [Permission.ProviderView],
                 title: t("providers"),
                 label: statsCount.data.providers,
                 to: "/providers",
@@ -165,6 +171,7 @@ export const Layout: React.FC = () => {
                 icon: Globe,
               },
               {
+                permissions: [Permission.IgnoreListView],
                 title: t("ignore.lists"),
                 label: statsCount.data.ignoreLists,
                 to: "/lists",
@@ -180,6 +187,7 @@ export const Layout: React.FC = () => {
             isCollapsed={isCollapsed}
             links={[
               {
+                permissions: [Permission.UserView],
                 title: t("users"),
                 label: statsCount.data.users,
                 to: "/users",
@@ -187,6 +195,7 @@ export const Layout: React.FC = () => {
                 icon: Users,
               },
               {
+                permissions: [Permission.ConfigView],
                 title: t("config"),
                 to: "/config",
                 variant: page === "config" ? "default" : "ghost",
@@ -376,6 +385,45 @@ export const Layout: React.FC = () => {
                         </React.Fragment>
                       );
                     }
+                    if (kind === "FAILING_SOURCE_TASK") {
+                      return (
+                        <React.Fragment key={index}>
+                          {index !== 0 && <Separator />}
+                          <div className="flex items-center justify-between gap-2">
+                            <div className="flex flex-col">
+                              <h3 className="text-sm">
+                                <Trans id="notification.failing.task.title" />
+                              </h3>
+                              <p className="text-xs opacity-50">
+                                <Trans
+                                  id="notification.failing.task.description"
+                                  name={
+                                    <span className="font-semibold">
+                                      {content.name}
+                                    </span>
+                                  }
+                                  number={
+                                    <span className="font-semibold">
+                                      {content.numConsecutiveFailures}
+                                    </span>
+                                  }
+                                />
+                              </p>
+                            </div>
+                            <PopoverClose asChild>
+                              <Link
+                                to="/sources/$slug/edit"
+                                params={{ slug: content.slug }}
+                              >
+                                <Button variant="ghost" size="sm">
+                                  <Wrench size={14} />
+                                </Button>
+                              </Link>
+                            </PopoverClose>
+                          </div>
+                        </React.Fragment>
+                      );
+                    }
                     return null;
                   })}
                   {notifications.data.length === 0 && (
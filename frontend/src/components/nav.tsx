@@ -10,7 +10,8 @@ import {
   TooltipTrigger,
 } from "@/components/ui/tooltip";
 import { userAtom } from "@/atoms/auth";
-import { userHasRoles } from "@/lib/auth";
+import { userHasPermissions } from "@/lib/auth";
+import { Permission } from "@/types/backendTypes";
 
 interface BaseNavLinkProps {
   title: string;
@@ -30,7 +31,7 @@ type ExtraNavLinkProps =
     }
   | {
       to: string;
-      roles?: string[];
+      permissions?: Permission[];
       preload?: LinkProps["preload"];
     };
 
@@ -59,7 +60,8 @@ const Nav: React.FC<NavProps> = ({ links, isCollapsed }) => {
   const user = useAtomValue(userAtom);
 
   const canViewAnyLinks = links.some(
-    (link) => !("roles" in link) || userHasRoles(user, link.roles),
+    (link) =>
+      !("permissions" in link) || userHasPermissions(user, link.permissions),
   );
 
   if (!canViewAnyLinks) {
@@ -74,7 +76,8 @@ const Nav: React.FC<NavProps> = ({ links, isCollapsed }) => {
       <nav className="grid gap-1 px-2 group-[[data-collapsed=true]]:justify-center group-[[data-collapsed=true]]:px-2">
         {links
           .filter(
-            (link) => !("roles" in link) || userHasRoles(user, link.roles),
+            (link) =>
+      !("permissions" in link) || userHasPermissions(user, link.permissions),
           )
           .map((link, index) =>
             isCollapsed ? (
@@ -3,6 +3,7 @@ import { LucideIcon } from "lucide-react";
 
 import { TransId, useTranslation } from "@/i18n";
 import { Trans, AuthLink } from "@/components";
+import { Permission } from "@/types/backendTypes";
 
 interface Props {
   Icon: LucideIcon;
@@ -11,7 +12,7 @@ interface Props {
   subCount: number;
   subTitle: TransId;
   to: LinkProps["to"];
-  roles?: string[];
+  permissions?: Permission[];
 }
 
 const StatsCounter: React.FC<Props> = ({
@@ -21,12 +22,12 @@ const StatsCounter: React.FC<Props> = ({
   subCount,
   subTitle,
   to,
-  roles,
+  permissions,
 }) => {
   const { t } = useTranslation();
 
   return (
-    <AuthLink to={to} title={`${t("search.results")}...`} roles={roles}>
+    <AuthLink to={to} title={`${t("search.results")}...`} permissions={permissions}>
       <div className="flex items-center gap-4 rounded-xl border p-4 shadow-sm transition duration-100 ease-in-out hover:bg-muted">
         <div className="rounded-xl border bg-primary p-2">
           <Icon size={32} className="text-white dark:text-black" />
@@ -3,7 +3,7 @@ import { ExternalLink } from "lucide-react";
 
 import { DataTable } from "@/components";
 import { Button } from "@/components/ui/button";
-import { DbUserLog } from "@/types/backendTypes";
+import { DbUserLog, UserLogEvent } from "@/types/backendTypes";
 import { useTranslation } from "@/i18n";
 import config from "@/lib/config";
 
@@ -46,6 +46,13 @@ const UserLogTable: React.FC<Props> = ({ data }) => {
           header: t("user.agent"),
           accessorKey: "userAgent",
         },
+        {
+          header: t("event"),
+          accessorKey: "event",
+          accessorFn: ({ event }) =>
+            event === UserLogEvent.AccountLocked ? t("account.locked") : "",
+          size: 80,
+        },
         {
           header: "",
           accessorKey: "traceId",
@@ -3,13 +3,14 @@ import { toast } from "sonner";
 
 import { User, userAtom } from "@/atoms/auth";
 import { store } from "@/atoms";
+import { Permission } from "@/types/backendTypes";
 
 export type BeforeLoadFn = (
-  roles?: string[],
+  permissions?: Permission[],
 ) => (opts: { location: ParsedLocation; cause?: string }) => void;
 
 export const beforeLoadAuthenticated: BeforeLoadFn =
-  (roles) =>
+  (permissions) =>
   ({ location, cause }) => {
     const user = store.get(userAtom);
 
@@ -22,19 +23,19 @@ export const beforeLoadAuthenticated: BeforeLoadFn =
       });
     }
 
-    if (roles && roles.length > 0) {
-      const hasRoles = userHasRoles(user, roles);
+    if (permissions && permissions.length > 0) {
+      const hasPermissions = userHasPermissions(user, permissions);
 
-      if (!hasRoles) {
+      if (!hasPermissions) {
         if (cause !== "preload") {
-          const missingRoles = roles
-            .filter((role) => !user.roles.includes(role))
+          const missingPermissions = permissions
+            .filter((permission) => !user.permissions?.includes(permission))
             .join(", ");
           toast.error(
-            `You don't have the required roles to access the page ${window.location.pathname}`,
+            `You don't have the required permissions to access the page ${window.location.pathname}`,
             {
-              id: `no-roles${missingRoles}`,
-              description: `Missing roles: ${missingRoles}`,
+              id: `no-permissions${missingPermissions}`,
+              description: `Missing permissions: ${missingPermissions}`,
             },
           );
         }
@@ -60,8 +61,13 @@ export function parseJwt<T>(token: string): T {
   return JSON.parse(jsonPayload) as T;
 }
 
-export const userHasRoles = (user?: User, roles?: string[]) =>
-  !roles || roles.every((role) => user?.roles.includes(role));
+export const userHasPermissions = (user?: User, permissions?: Permission[]) =>
+  !permissions ||
+  permissions.every((permission) => user?.permissions?.includes(permission));
 
-export const userHasAnyRoles = (user?: User, roles?: string[]) =>
-  !roles || roles.some((role) => user?.roles.includes(role));
+export const userHasAnyPermissions = (
+  user?: User,
+  permissions?: Permission[],
+) =>
+  !permissions ||
+  permissions.some((permission) => user?.permissions?.includes(permission));
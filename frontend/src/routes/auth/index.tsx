@@ -18,6 +18,7 @@ const SaveUserData: React.FC = () => {
   useEffect(() => {
     const search = new URLSearchParams(location.search);
     const token = search.get("token");
+    const refreshToken = search.get("refreshToken") ?? undefined;
     const disabled = search.get("disabled");
     const next = search.get("next");
 
@@ -40,12 +41,14 @@ const SaveUserData: React.FC = () => {
 
           setUser({
             token,
+            refreshToken,
             name: claims.name,
             givenName: claims.given_name,
             familyName: claims.family_name,
             email: claims.email,
             id: claims.sub,
             roles: claims.roles,
+            permissions: claims.permissions ?? [],
             initials: initials.toUpperCase(),
           });
 
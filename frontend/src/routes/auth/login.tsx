@@ -5,6 +5,7 @@ import {
   useNavigate,
 } from "@tanstack/react-router";
 import { useSuspenseQuery } from "@tanstack/react-query";
+import { KeyRound } from "lucide-react";
 
 import { Button } from "@/components/ui/button";
 import config from "@/lib/config";
@@ -13,6 +14,17 @@ import { store } from "@/atoms";
 import { userAtom } from "@/atoms/auth";
 import { authServicesQueryOptions, useUserLogin } from "@/api/auth";
 
+const OpenIdProviderIcon: React.FC<{ name: string }> = ({ name }) => {
+  switch (name) {
+    case "google":
+      return <Icons.Google />;
+    case "microsoft":
+      return <Icons.Microsoft />;
+    default:
+      return <KeyRound size={16} />;
+  }
+};
+
 const Login: React.FC = () => {
   const { next } = Route.useSearch();
 
@@ -23,24 +35,26 @@ const Login: React.FC = () => {
   const authServices = useSuspenseQuery(authServicesQueryOptions);
 
   const handleOnSubmit = async (data: Forms.Login.FormSchema) => {
-    const { jwtToken } = await login.mutateAsync(data);
+    const { jwtToken, refreshToken } = await login.mutateAsync(data);
+
+    // Second factor step not supported yet by the frontend
+    if (!jwtToken) {
+      return;
+    }
 
     await navigate({
       to: "/auth/",
       search: {
         token: jwtToken,
+        refreshToken: refreshToken ?? undefined,
         next: next,
       },
     });
   };
 
-  const getOpenIdAuthService = (name: string) =>
-    authServices.data.find(
-      (i) => i.kind.kind === "openId" && i.kind.content.name === name,
-    );
-
-  const googleEnabled = getOpenIdAuthService("google")?.enabled;
-  const microsoftEnabled = getOpenIdAuthService("google")?.enabled;
+  const openIdProviders = authServices.data.flatMap((i) =>
+    i.enabled && i.kind.kind === "openId" ? [i.kind.content] : [],
+  );
 
   return (
     <>
@@ -53,27 +67,23 @@ const Login: React.FC = () => {
         </p>
       </div>
       <div className="grid gap-2">
-        {googleEnabled && (
-          <Button type="button" className="gap-2" asChild variant="outline">
+        {openIdProviders.map(({ name, label }) => (
+          <Button
+            key={name}
+            type="button"
+            className="gap-2"
+            asChild
+            variant="outline"
+          >
             <a
-              href={`${config.rest_server_base_url}/auth/openid/google${query}`}
+              href={`${config.rest_server_base_url}/auth/openid/${name}${query}`}
             >
-              <Icons.Google />
-              Google
+              <OpenIdProviderIcon name={name} />
+              {label}
             </a>
           </Button>
-        )}
-        {microsoftEnabled && (
-          <Button type="button" className="gap-2" asChild variant="outline">
-            <a
-              href={`${config.rest_server_base_url}/auth/openid/microsoft${query}`}
-            >
-              <Icons.Microsoft />
-              Microsoft
-            </a>
-          </Button>
-        )}
-        {(microsoftEnabled ?? googleEnabled) && (
+        ))}
+        {openIdProviders.length > 0 && (
           <div className="relative my-8 flex items-center justify-center">
             <hr className="flex-1" />
             <div className="absolute self-center bg-background px-6 text-center text-xs italic text-muted-foreground">
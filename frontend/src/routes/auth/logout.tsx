@@ -6,11 +6,17 @@ import { userAtom } from "@/atoms/auth";
 import { store } from "@/atoms";
 import { Trans } from "@/components";
 import { queryClient } from "@/lib/query";
+import { fetcher } from "@/api";
 
 const Logout: React.FC = () => {
   const navigate = useNavigate();
 
   useEffect(() => {
+    const refreshToken = store.get(userAtom)?.refreshToken;
+    if (refreshToken) {
+      fetcher.post("/auth/logout", { data: { refreshToken } }).catch(() => {});
+    }
+
     queryClient.clear();
     store.set(userAtom, undefined);
     setTimeout(() => {
@@ -11,6 +11,7 @@ import {
   ApiTokensTable,
 } from "@/components";
 import { beforeLoadAuthenticated } from "@/lib/auth";
+import { Permission } from "@/types/backendTypes";
 import { userApiTokensQueryOptions } from "@/api/apiTokens";
 import { userAtom } from "@/atoms/auth";
 import { store } from "@/atoms";
@@ -35,7 +36,7 @@ const ConfigComponent: React.FC = () => {
 
 export const Route = createFileRoute("/config")({
   component: ConfigComponent,
-  beforeLoad: beforeLoadAuthenticated(),
+  beforeLoad: beforeLoadAuthenticated([Permission.ConfigView]),
   loader: async ({ context: { queryClient } }) =>
     await Promise.all([
       queryClient.ensureQueryData(secretsQueryOptions),
@@ -3,7 +3,7 @@ import {
   UseSuspenseQueryResult,
   useSuspenseQuery,
 } from "@tanstack/react-query";
-import { ExternalLink, UserIcon } from "lucide-react";
+import { Bot, ExternalLink, UserIcon } from "lucide-react";
 import dayjs from "dayjs";
 
 import {
@@ -21,7 +21,12 @@ import {
 import { Button } from "@/components/ui/button";
 import config from "@/lib/config";
 import { Badge } from "@/components/ui/badge";
-import { DataCacheAction, SourceError, User } from "@/types/backendTypes";
+import {
+  DataCacheAction,
+  Permission,
+  SourceError,
+  User,
+} from "@/types/backendTypes";
 import { beforeLoadAuthenticated } from "@/lib/auth";
 import { userQueryOptions } from "@/api/users";
 
@@ -48,8 +53,10 @@ const HistoryComponent: React.FC = () => {
             <Link to="/users/$id" params={{ id: request.data.userId }}>
               <FullBadge
                 value={user.data.name}
-                Icon={UserIcon}
-                label="requester"
+                Icon={user.data.serviceAccount ? Bot : UserIcon}
+                label={
+                  user.data.serviceAccount ? "service.account" : "requester"
+                }
                 valueBadgeProps={{
                   variant: "secondary",
                 }}
@@ -117,7 +124,7 @@ const NotFoundHistory: React.FC = () => {
 export const Route = createFileRoute("/history/$id")({
   component: HistoryComponent,
   notFoundComponent: NotFoundHistory,
-  beforeLoad: beforeLoadAuthenticated(["request_view"]),
+  beforeLoad: beforeLoadAuthenticated([Permission.RequestView]),
   loader: async ({ context: { queryClient }, params: { id } }) => {
     const [request] = await Promise.all([
       queryClient.ensureQueryData(requestQueryOptions(id)),
@@ -22,7 +22,7 @@ const HistoryHomeComponent: React.FC = () => (
       description="history.empty.description"
       image={EmptyImage}
       extra={
-        <AuthVisible roles={["request_create"]}>
+        <AuthVisible permissions={[Permission.RequestCreate]}>
           <Link to="/request">
             <Button className="gap-2" size="sm" variant="secondary">
               <Send size={16} />
@@ -37,5 +37,5 @@ const HistoryHomeComponent: React.FC = () => (
 
 export const Route = createFileRoute("/history/")({
   component: HistoryHomeComponent,
-  beforeLoad: beforeLoadAuthenticated(["request_view"]),
+  beforeLoad: beforeLoadAuthenticated([Permission.RequestView]),
 });
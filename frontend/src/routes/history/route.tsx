@@ -5,16 +5,19 @@ import { useAtomValue } from "jotai";
 
 import { requestsQueryOptions } from "@/api/requests";
 import { GenericPanelSearch, SearchResults } from "@/components";
-import { beforeLoadAuthenticated, userHasRoles } from "@/lib/auth";
+import { beforeLoadAuthenticated, userHasPermissions } from "@/lib/auth";
 import { useTranslation } from "@/i18n";
 import { userAtom } from "@/atoms/auth";
+import { Permission } from "@/types/backendTypes";
 
 const SourcesComponents: React.FC = () => {
   const user = useAtomValue(userAtom);
   const requests = useSuspenseQuery(requestsQueryOptions);
   const { t } = useTranslation();
 
-  const userHasRequestCreateRole = userHasRoles(user, ["request_create"]);
+  const userCanCreateRequest = userHasPermissions(user, [
+    Permission.RequestCreate,
+  ]);
 
   return (
     <GenericPanelSearch
@@ -24,13 +27,13 @@ const SourcesComponents: React.FC = () => {
         data.kind.toLowerCase().includes(searchValue.toLowerCase())
       }
       searchPlaceholder="history.search.placeholder"
-      createLinkTo={userHasRequestCreateRole ? "/request" : undefined}
+      createLinkTo={userCanCreateRequest ? "/request" : undefined}
       createLinkToDataKey="data"
       CreateLinkIcon={Send}
       Item={SearchResults.History}
       empty={{
         title: "history.search.empty.title",
-        description: userHasRequestCreateRole
+        description: userCanCreateRequest
           ? "history.search.empty.description"
           : "history.search.empty.description.viewer",
         extra: t("history.search.empty.extra"),
@@ -41,7 +44,7 @@ const SourcesComponents: React.FC = () => {
 
 export const Route = createFileRoute("/history")({
   component: SourcesComponents,
-  beforeLoad: beforeLoadAuthenticated(["request_view"]),
+  beforeLoad: beforeLoadAuthenticated([Permission.RequestView]),
   loader: ({ context: { queryClient } }) =>
     queryClient.ensureQueryData(requestsQueryOptions),
 });
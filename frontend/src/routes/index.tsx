@@ -22,7 +22,7 @@ import {
 } from "@/api/stats";
 import { requestsQueryOptions } from "@/api/requests";
 import { useRunnersStatus } from "@/api/runners";
-import { SourceKind } from "@/types/backendTypes";
+import { Permission, SourceKind } from "@/types/backendTypes";
 import { indicatorKindMapping } from "@/lib/data";
 import { configQueryOptions } from "@/api/config";
 import {
@@ -34,7 +34,7 @@ import {
   Trans,
   AuthVisible,
 } from "@/components";
-import { beforeLoadAuthenticated } from "@/lib/auth";
+import { beforeLoadAuthenticated, userHasPermissions } from "@/lib/auth";
 import { userAtom } from "@/atoms/auth";
 import { useTranslation } from "@/i18n";
 import { store } from "@/atoms";
@@ -76,34 +76,36 @@ const IndexComponent: React.FC = () => {
       <div className="mx-4">
         <Separator />
       </div>
-      <AuthVisible roles={["request_create"]}>
+      <AuthVisible permissions={[Permission.RequestCreate]}>
         <h4 className="z-20 mx-4 -mb-4 flex items-center gap-2 font-medium">
           <Send size={16} /> <Trans id="home.perform.request.title" />
         </h4>
         <Forms.RequestForm.default sources={sources.data} />
       </AuthVisible>
-      <AuthVisible roles={["request_view"]}>
+      <AuthVisible permissions={[Permission.RequestView]}>
         <HistoryComponent />
       </AuthVisible>
-      <div>
-        <h4 className="z-20 mx-4 flex items-center gap-2 font-medium">
-          <Server size={16} /> <Trans id="home.runners.title" />
-        </h4>
-        <div className="grid grid-cols-1 gap-4 p-4 lg:grid-cols-2">
-          <RunnerStatus
-            sourceKind={SourceKind.Python}
-            status={runnersStatus.data?.PYTHON}
-            address={config.data?.python_runner_grpc_address}
-            enabled={config.data?.python_runner_enabled}
-          />
-          <RunnerStatus
-            sourceKind={SourceKind.JavaScript}
-            status={runnersStatus.data?.JAVA_SCRIPT}
-            address={config.data?.javascript_runner_grpc_address}
-            enabled={config.data?.javascript_runner_enabled}
-          />
+      <AuthVisible permissions={[Permission.RunnerView]}>
+        <div>
+          <h4 className="z-20 mx-4 flex items-center gap-2 font-medium">
+            <Server size={16} /> <Trans id="home.runners.title" />
+          </h4>
+          <div className="grid grid-cols-1 gap-4 p-4 lg:grid-cols-2">
+            <RunnerStatus
+              sourceKind={SourceKind.Python}
+              status={runnersStatus.data?.PYTHON}
+              address={config.data?.python_runner_grpc_address}
+              enabled={config.data?.python_runner_enabled}
+            />
+            <RunnerStatus
+              sourceKind={SourceKind.JavaScript}
+              status={runnersStatus.data?.JAVA_SCRIPT}
+              address={config.data?.javascript_runner_grpc_address}
+              enabled={config.data?.javascript_runner_enabled}
+            />
+          </div>
         </div>
-      </div>
+      </AuthVisible>
       <div className="mx-4">
         <Separator />
       </div>
@@ -119,7 +121,7 @@ const IndexComponent: React.FC = () => {
             subCount={statsCount.data.historyLast24hrs}
             subTitle="home.stats.requests.subtitle"
             to="/history"
-            roles={["request_view"]}
+            permissions={[Permission.RequestView]}
           />
           <StatsCounter
             Icon={Database}
@@ -240,7 +242,7 @@ export const Route = createFileRoute("/")({
       queryClient.ensureQueryData(statsCountRequestsByKindsQueryOptions),
     ];
 
-    if (user?.roles.includes("request_view")) {
+    if (userHasPermissions(user, [Permission.RequestView])) {
       promises.push(queryClient.ensureQueryData(requestsQueryOptions));
     }
 
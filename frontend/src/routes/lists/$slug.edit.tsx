@@ -16,6 +16,7 @@ import {
   ignoreListSlugQueryOptions,
 } from "@/api/ignoreLists";
 import { beforeLoadAuthenticated } from "@/lib/auth";
+import { Permission } from "@/types/backendTypes";
 
 const ListEditComponent: React.FC = () => {
   const { slug } = Route.useParams();
@@ -94,7 +95,7 @@ const ListEditComponent: React.FC = () => {
 
 export const Route = createFileRoute("/lists/$slug/edit")({
   component: ListEditComponent,
-  beforeLoad: beforeLoadAuthenticated(),
+  beforeLoad: beforeLoadAuthenticated([Permission.IgnoreListEdit]),
   loader: async ({ context: { queryClient }, params: { slug } }) => {
     const id = await queryClient.ensureQueryData(
       ignoreListSlugQueryOptions(slug),
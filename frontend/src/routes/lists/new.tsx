@@ -9,6 +9,7 @@ import {
   useIgnoreListEntryPut,
 } from "@/api/ignoreLists";
 import { beforeLoadAuthenticated } from "@/lib/auth";
+import { Permission } from "@/types/backendTypes";
 
 const ListsNewComponent: React.FC = () => {
   const navigate = useNavigate();
@@ -53,7 +54,7 @@ interface IgnoreListSearch {
 
 export const Route = createFileRoute("/lists/new")({
   component: ListsNewComponent,
-  beforeLoad: beforeLoadAuthenticated(),
+  beforeLoad: beforeLoadAuthenticated([Permission.IgnoreListEdit]),
   validateSearch: (search: Record<string, unknown>): IgnoreListSearch => {
     return {
       name: search.name as string,
@@ -4,6 +4,7 @@ import { useSuspenseQuery } from "@tanstack/react-query";
 import { GenericPanelSearch, SearchResults, Trans } from "@/components";
 import { ignoreListsQueryOptions } from "@/api/ignoreLists";
 import { beforeLoadAuthenticated } from "@/lib/auth";
+import { Permission } from "@/types/backendTypes";
 
 const ListsComponent: React.FC = () => {
   const sources = useSuspenseQuery(ignoreListsQueryOptions);
@@ -28,7 +29,7 @@ const ListsComponent: React.FC = () => {
 
 export const Route = createFileRoute("/lists")({
   component: ListsComponent,
-  beforeLoad: beforeLoadAuthenticated(),
+  beforeLoad: beforeLoadAuthenticated([Permission.IgnoreListView]),
   loader: (opts) =>
     opts.context.queryClient.ensureQueryData(ignoreListsQueryOptions),
 });
@@ -16,7 +16,7 @@ import {
   usePutProviderIgnoreListsMutation,
   usePutProviderSourcesMutation,
 } from "@/api/providers";
-import { Provider } from "@/types/backendTypes";
+import { Permission, Provider } from "@/types/backendTypes";
 import { beforeLoadAuthenticated } from "@/lib/auth";
 
 const ProviderEditComponent: React.FC = () => {
@@ -86,7 +86,7 @@ const ProviderEditComponent: React.FC = () => {
 
 export const Route = createFileRoute("/providers/$slug/edit")({
   component: ProviderEditComponent,
-  beforeLoad: beforeLoadAuthenticated(),
+  beforeLoad: beforeLoadAuthenticated([Permission.ProviderEdit]),
   loader: async ({ context: { queryClient }, params: { slug } }) => {
     const id = await queryClient.ensureQueryData(
       providerSlugQueryOptions(slug),
@@ -4,6 +4,7 @@ import { toast } from "sonner";
 import { Forms, Trans } from "@/components";
 import { useProviderCreate } from "@/api/providers";
 import { beforeLoadAuthenticated } from "@/lib/auth";
+import { Permission } from "@/types/backendTypes";
 
 const ProvidersNewComponent: React.FC = () => {
   const navigate = useNavigate();
@@ -27,7 +28,7 @@ interface ProviderSearch {
 
 export const Route = createFileRoute("/providers/new")({
   component: ProvidersNewComponent,
-  beforeLoad: beforeLoadAuthenticated(),
+  beforeLoad: beforeLoadAuthenticated([Permission.ProviderEdit]),
   validateSearch: (search: Record<string, unknown>): ProviderSearch => {
     return {
       name: search.name as string,
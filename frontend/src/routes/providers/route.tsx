@@ -4,6 +4,7 @@ import { useSuspenseQuery } from "@tanstack/react-query";
 import { providersQueryOptions } from "@/api/providers";
 import { GenericPanelSearch, SearchResults, Trans } from "@/components";
 import { beforeLoadAuthenticated } from "@/lib/auth";
+import { Permission } from "@/types/backendTypes";
 
 const ProvidersComponent: React.FC = () => {
   const providers = useSuspenseQuery(providersQueryOptions);
@@ -28,7 +29,7 @@ const ProvidersComponent: React.FC = () => {
 
 export const Route = createFileRoute("/providers")({
   component: ProvidersComponent,
-  beforeLoad: beforeLoadAuthenticated(),
+  beforeLoad: beforeLoadAuthenticated([Permission.ProviderView]),
   loader: (opts) =>
     opts.context.queryClient.ensureQueryData(providersQueryOptions),
 });
@@ -2,7 +2,7 @@ import { createFileRoute, useNavigate } from "@tanstack/react-router";
 import { useEffect, useRef, useState } from "react";
 import { useSuspenseQuery } from "@tanstack/react-query";
 
-import { IndicatorKind } from "@/types/backendTypes";
+import { IndicatorKind, Permission } from "@/types/backendTypes";
 import { sourcesQueryOptions } from "@/api/sources";
 import { RequestDataView, Forms } from "@/components";
 import { ModifiedRequest, useRequest } from "@/api/requests";
@@ -109,7 +109,7 @@ interface IndicatorRequest {
 
 export const Route = createFileRoute("/request")({
   component: RequestComponent,
-  beforeLoad: beforeLoadAuthenticated(["request_create"]),
+  beforeLoad: beforeLoadAuthenticated([Permission.RequestCreate]),
   validateSearch: (search: IndicatorRequest): IndicatorRequest => search,
   loader: ({ context: { queryClient } }) =>
     queryClient.ensureQueryData(sourcesQueryOptions),
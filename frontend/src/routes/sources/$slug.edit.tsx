@@ -13,7 +13,7 @@ import {
   usePutSourceSecretsMutation,
   useUpdateSourceMutation,
 } from "@/api/sources";
-import { SourceKind } from "@/types/backendTypes";
+import { Permission, SourceKind } from "@/types/backendTypes";
 import { beforeLoadAuthenticated } from "@/lib/auth";
 
 const SourceEditComponent: React.FC = () => {
@@ -84,7 +84,7 @@ const SourceEditComponent: React.FC = () => {
 
 export const Route = createFileRoute("/sources/$slug/edit")({
   component: SourceEditComponent,
-  beforeLoad: beforeLoadAuthenticated(),
+  beforeLoad: beforeLoadAuthenticated([Permission.SourceEdit]),
   loader: async ({ context: { queryClient }, params: { slug } }) => {
     const id = await queryClient.ensureQueryData(sourceSlugQueryOptions(slug));
 
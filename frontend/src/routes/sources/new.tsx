@@ -7,7 +7,7 @@ import {
   usePutSourceIgnoreListMutation,
   usePutSourceSecretsMutation,
 } from "@/api/sources";
-import { SourceKind } from "@/types/backendTypes";
+import { Permission, SourceKind } from "@/types/backendTypes";
 import { beforeLoadAuthenticated } from "@/lib/auth";
 
 const SourceNewComponent: React.FC = () => {
@@ -52,6 +52,6 @@ interface SourceSearch {
 
 export const Route = createFileRoute("/sources/new")({
   component: SourceNewComponent,
-  beforeLoad: beforeLoadAuthenticated(),
+  beforeLoad: beforeLoadAuthenticated([Permission.SourceEdit]),
   validateSearch: (search: SourceSearch): SourceSearch => search,
 });
@@ -4,6 +4,7 @@ import { useSuspenseQuery } from "@tanstack/react-query";
 import { sourcesQueryOptions } from "@/api/sources";
 import { GenericPanelSearch, SearchResults, Trans } from "@/components";
 import { beforeLoadAuthenticated } from "@/lib/auth";
+import { Permission } from "@/types/backendTypes";
 
 const SourcesComponents: React.FC = () => {
   const sources = useSuspenseQuery(sourcesQueryOptions);
@@ -28,7 +29,7 @@ const SourcesComponents: React.FC = () => {
 
 export const Route = createFileRoute("/sources")({
   component: SourcesComponents,
-  beforeLoad: beforeLoadAuthenticated(),
+  beforeLoad: beforeLoadAuthenticated([Permission.SourceView]),
   loader: ({ context: { queryClient } }) =>
     queryClient.ensureQueryData(sourcesQueryOptions),
 });
@@ -8,7 +8,7 @@ import { toast } from "sonner";
 import { useUserPatch, userQueryOptions } from "@/api/users";
 import { Forms, Trans } from "@/components";
 import { beforeLoadAuthenticated } from "@/lib/auth";
-import { User } from "@/types/backendTypes";
+import { Permission, User } from "@/types/backendTypes";
 
 const UserEditComponent: React.FC = () => {
   const { id } = Route.useParams();
@@ -34,7 +34,7 @@ const UserEditComponent: React.FC = () => {
 
 export const Route = createFileRoute("/users/$id/edit")({
   component: UserEditComponent,
-  beforeLoad: beforeLoadAuthenticated(),
+  beforeLoad: beforeLoadAuthenticated([Permission.UserEdit]),
   loader: async ({ context: { queryClient }, params: { id } }) => {
     await queryClient.ensureQueryData(userQueryOptions(id));
   },
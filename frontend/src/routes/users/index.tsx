@@ -4,6 +4,7 @@ import { UserCircle } from "lucide-react";
 import EmptyImage from "@/assets/diversity-29.svg";
 import { Empty, SectionPanelHeader, Trans } from "@/components";
 import { beforeLoadAuthenticated } from "@/lib/auth";
+import { Permission } from "@/types/backendTypes";
 
 const UserHomeComponent: React.FC = () => (
   <>
@@ -27,5 +28,5 @@ const UserHomeComponent: React.FC = () => (
 
 export const Route = createFileRoute("/users/")({
   component: UserHomeComponent,
-  beforeLoad: beforeLoadAuthenticated(),
+  beforeLoad: beforeLoadAuthenticated([Permission.UserView]),
 });
@@ -9,6 +9,8 @@ export type CacheKey = string[];
 export interface Claims {
   /** Database ID of the user */
   sub: string;
+  /** Database ID of the session the JWT was issued for */
+  sid: string;
   /** Email of the user */
   email: string;
   /** Whether the user has verified their email */
@@ -23,10 +25,82 @@ export interface Claims {
   locale?: string;
   /** Roles of the user granting access to parts of the platform */
   roles: string[];
+  /** Permissions granted by the roles of the user */
+  permissions: Permission[];
   /** Provider of the user */
   provider: string;
 }
 
+/** Short lived JWT authenticating the requests and the token used to get a new one */
+export interface SessionTokens {
+  /** The JWT that can be used to authenticate yourself */
+  jwtToken: string;
+  /** Single use token to get new tokens with `/auth/refresh` once the JWT expires */
+  refreshToken: string;
+}
+
+/** Secret to add to an authenticator app, either by hand or by scanning the URI as a QR code */
+export interface TotpSetup {
+  /** Base32 encoded secret */
+  secret: string;
+  /** `otpauth://` URI containing the secret */
+  uri: string;
+}
+
+/** Recent failed logins of an account or an IP address */
+export interface FailedLogins {
+  /** Number of failed logins since the last successful one, or since they were forgotten */
+  attempts: number;
+  /** Time until which logins are refused, if locked */
+  lockedUntil?: NaiveDateTime;
+}
+
+/** Options to pass to `navigator.credentials.create`, binary values being base64 URL encoded */
+export interface WebAuthnCreationOptions {
+  challenge: string;
+  rpId: string;
+  rpName: string;
+  userId: string;
+  userName: string;
+  userDisplayName: string;
+  /** COSE identifiers of the supported public key algorithms */
+  algorithms: number[];
+  /** Credentials already registered by the user */
+  excludeCredentials: string[];
+  timeout: number;
+}
+
+/** Options to pass to `navigator.credentials.get`, binary values being base64 URL encoded */
+export interface WebAuthnRequestOptions {
+  challenge: string;
+  rpId: string;
+  /** Credentials registered by the user */
+  allowCredentials: string[];
+  timeout: number;
+}
+
+/**
+ * Security key created with `navigator.credentials.create`, binary values being base64 URL
+ * encoded
+ */
+export interface WebAuthnRegistration {
+  /** Name given by the user to the security key */
+  name: string;
+  clientDataJson: string;
+  attestationObject: string;
+}
+
+/**
+ * Signature of the challenge returned by `navigator.credentials.get`, binary values being base64
+ * URL encoded
+ */
+export interface WebAuthnAssertion {
+  credentialId: string;
+  clientDataJson: string;
+  authenticatorData: string;
+  signature: string;
+}
+
 /** A cache entry, encapsulating the value and the timestamp of when it was created */
 export interface CacheEntry<T> {
   /** Timestamp of when the cache entry was created */
@@ -37,6 +111,26 @@ export interface CacheEntry<T> {
   expiration?: number;
 }
 
+/** Statistics about the usage of the cache */
+export interface CacheStats {
+  /** Number of entries currently in the cache */
+  entries: number;
+  /** Total size in bytes of the entries currently in the cache */
+  sizeBytes: number;
+  /** Maximum number of entries kept in the cache, if bounded */
+  maxEntries?: number;
+  /** Maximum total size in bytes of the entries kept in the cache, if bounded */
+  maxBytes?: number;
+  /** Number of lookups that returned a valid entry */
+  hits: number;
+  /** Number of lookups that did not return a valid entry */
+  misses: number;
+  /** Number of entries removed to keep the cache within its limits */
+  evictions: number;
+  /** Number of entries removed because they expired */
+  expirations: number;
+}
+
 /** An token used for authentication with the API */
 export interface ApiToken {
   /** The database ID of the token */
@@ -49,6 +143,20 @@ export interface ApiToken {
   note: string;
   /** The time the token expires */
   expiresAt?: NaiveDateTime;
+  /** Permissions the token is restricted to, among the ones of its user, all of them if missing */
+  permissions?: string[];
+  /** Database IDs of the sources the token can request, all of them if missing */
+  sourceIds?: string[];
+  /** Indicator kinds the token can request, all of them if missing */
+  indicatorKinds?: string[];
+  /** IP addresses or CIDR ranges the token can be used from, any of them if missing */
+  allowedIpRanges?: string[];
+  /** The time the token was last used */
+  lastUsedAt?: NaiveDateTime;
+  /** IP address of the client that last used the token */
+  lastUsedIpAddress?: string;
+  /** Number of requests authenticated with the token */
+  requestCount: number;
 }
 
 /** Parameters for creating a new API token */
@@ -57,6 +165,14 @@ export interface CreateApiToken {
   note: string;
   /** The time the token expires */
   expiresAt?: NaiveDateTime;
+  /** Permissions the token is restricted to, among the ones of its user, all of them if missing */
+  permissions?: Permission[];
+  /** Database IDs of the sources the token can request, all of them if missing */
+  sourceIds?: string[];
+  /** Indicator kinds the token can request, all of them if missing */
+  indicatorKinds?: IndicatorKind[];
+  /** IP addresses or CIDR ranges the token can be used from, any of them if missing */
+  allowedIpRanges?: string[];
 }
 
 /** Parameters for updating an API token */
@@ -65,6 +181,126 @@ export interface UpdateApiToken {
   note?: string;
   /** The time the token expires */
   expiresAt?: NaiveDateTime;
+  /** Permissions the token is restricted to, among the ones of its user */
+  permissions?: Permission[];
+  /** Database IDs of the sources the token can request */
+  sourceIds?: string[];
+  /** Indicator kinds the token can request */
+  indicatorKinds?: IndicatorKind[];
+  /** IP addresses or CIDR ranges the token can be used from */
+  allowedIpRanges?: string[];
+}
+
+/** Field of a feed entry containing an indicator */
+export interface FeedIndicatorMapping {
+  /**
+   * Column name, or index if the CSV has no headers, or JSONPath relative to the JSON entry,
+   * ignored for TXT feeds
+   */
+  field: string;
+  /** Kind of the indicators contained in the field */
+  kind: IndicatorKind;
+}
+
+/** Field of a feed entry stored as metadata of the entry's indicators */
+export interface FeedMetadataMapping {
+  /** Name of the metadata */
+  name: string;
+  /** Column name, or index if the CSV has no headers, or JSONPath relative to the JSON entry */
+  field: string;
+}
+
+/** Format of the content of a feed */
+export enum FeedFormat {
+  /** Comma separated values, or any other delimiter, one entry per row */
+  Csv = "CSV",
+  /** Array of JSON objects, one entry per object */
+  Json = "JSON",
+  /** Plain text, one indicator per line */
+  Txt = "TXT",
+}
+
+/** Compression of a feed */
+export enum FeedCompression {
+  Gzip = "GZIP",
+  /** Archive whose first file is the feed */
+  Zip = "ZIP",
+}
+
+/** Declarative definition of a feed source, stored as the first element of the source's config */
+export interface FeedConfig {
+  /** URL the feed is downloaded from */
+  url: string;
+  /** Format of the feed */
+  format: FeedFormat;
+  /** Compression of the feed, if any */
+  compression?: FeedCompression;
+  /** Delimiter between the columns of a CSV feed, defaults to a comma */
+  delimiter?: string;
+  /** Whether the first row of a CSV feed contains the column names, defaults to true */
+  hasHeaders?: boolean;
+  /** Prefix of the lines to ignore in CSV and TXT feeds, defaults to `#` */
+  commentPrefix?: string;
+  /** JSONPath to the array of entries of a JSON feed, defaults to the whole document */
+  entriesPath?: string;
+  /** Fields of the entries containing indicators */
+  indicators: FeedIndicatorMapping[];
+  /** Fields of the entries stored with their indicators */
+  metadata?: FeedMetadataMapping[];
+  /**
+   * Interval in seconds between refreshes of the feed, used if the source's task has no
+   * interval or cron expression of its own
+   */
+  refreshInterval?: number;
+}
+
+/** Header sent by an HTTP source, its value being a template */
+export interface HttpHeader {
+  /** Name of the header */
+  name: string;
+  /** Template of the value of the header, usually referencing a secret */
+  value: string;
+}
+
+/** Value of the `{{kind}}` placeholder for an indicator kind */
+export interface HttpKindMapping {
+  /** Kind of the indicator */
+  kind: IndicatorKind;
+  /** Value replacing the `{{kind}}` placeholder, such as a path segment or a query parameter */
+  value: string;
+}
+
+/** HTTP method of the request sent by an HTTP source */
+export enum HttpMethod {
+  Get = "GET",
+  Post = "POST",
+}
+
+/**
+ * Declarative definition of an HTTP source, stored as the first element of the source's config.
+ * 
+ * Templates may contain the `{{indicator}}`, `{{kind}}` and `{{secrets.NAME}}` placeholders, the
+ * latter being replaced by the value of the source's secret named `NAME`.
+ */
+export interface HttpTemplateConfig {
+  /** HTTP method of the request, defaults to GET */
+  method?: HttpMethod;
+  /** Template of the URL of the request, the values replacing placeholders are URL encoded */
+  url: string;
+  /** Headers of the request */
+  headers?: HttpHeader[];
+  /**
+   * Template of the JSON body of the request, the values replacing placeholders are JSON
+   * escaped
+   */
+  body?: string;
+  /** Values of the `{{kind}}` placeholder per indicator kind, defaults to the lowercased kind */
+  kinds?: HttpKindMapping[];
+  /**
+   * JSONPath selecting the data to return from the JSON response, defaults to the whole
+   * response
+   */
+  responsePath?: string;
 }
 
 /** List of indicators to ignore when processing requests against sources */
@@ -89,6 +325,8 @@ export interface IgnoreList {
   createdUserId: string;
   /** Database ID of the user who last updated the ignore list */
   updatedUserId?: string;
+  /** Database ID of the team owning the ignore list, shared with everyone when not set */
+  teamId?: string;
 }
 
 /** Parameters for creating a new ignore list */
@@ -101,6 +339,8 @@ export interface CreateIgnoreList {
   enabled: boolean;
   /** Whether the ignore list is global and used to ignore all requests, regardless of it's source */
   global: boolean;
+  /** Database ID of the team owning the ignore list, an empty string sharing it with everyone. Defaults to the first team of the user. */
+  teamId?: string;
 }
 
 /** Parameters for updating an ignore list */
@@ -113,6 +353,8 @@ export interface UpdateIgnoreList {
   enabled?: boolean;
   /** Whether the ignore list is global and used to ignore all requests, regardless of it's source */
   global?: boolean;
+  /** Database ID of the team to move the ignore list to, an empty string sharing it with everyone */
+  teamId?: string;
 }
 
 /** Entry in an ignore list */
@@ -174,6 +416,22 @@ export interface IdSlug {
   slug: string;
 }
 
+/** A WebAuthn security key registered by a user as second factor */
+export interface WebAuthnCredential {
+  /** The database ID of the security key */
+  id: string;
+  /** The time the security key was registered */
+  createdAt: NaiveDateTime;
+  /** The time the security key was last updated */
+  updatedAt: NaiveDateTime;
+  /** Name given by the user to the security key */
+  name: string;
+  /** Base64 URL encoded ID of the credential generated by the security key */
+  credentialId: string;
+  /** The time the security key was last used to login */
+  lastUsedAt?: NaiveDateTime;
+}
+
 /** A source with minimal information needed for displaying the notification */
 export interface MinimalSource {
   /** Database ID of the source */
@@ -186,6 +444,20 @@ export interface MinimalSource {
   numMissingSecrets: number;
 }
 
+/** A source whose background task failed multiple times in a row */
+export interface FailingSourceTask {
+  /** Database ID of the source */
+  id: string;
+  /** Name of the source */
+  name: string;
+  /** URL friendly name of the provider */
+  slug: string;
+  /** Number of failed runs of the task since its last successful run */
+  numConsecutiveFailures: number;
+  /** Error of the last failed run of the task */
+  lastError?: string;
+}
+
 /** Source provider, organization or service that provides indicators with the number of sources it has */
 export interface Provider {
   /** Database ID of the provider */
@@ -264,6 +536,8 @@ export interface Request {
   traceId: string;
   /** Database ID of the user who requested the data */
   userId: string;
+  /** Database ID of the team the request was made for, visible to everyone when not set */
+  teamId?: string;
 }
 
 /** Response of a source following a user request against it and snapshot values of the source (in case the source gets deleted/modified after the request) */
@@ -322,6 +596,8 @@ export interface Secret {
   createdUserId: string;
   /** Database ID of the user who last updated the secret */
   updatedUserId?: string;
+  /** Database ID of the team owning the secret, shared with everyone when not set */
+  teamId?: string;
   /** The number of sources that use this secret */
   numSources: number;
 }
@@ -358,6 +634,8 @@ export interface CreateSecret {
   description?: string;
   /** The time the secret expires */
   expiresAt?: NaiveDateTime;
+  /** Database ID of the team owning the secret, an empty string sharing it with everyone. Defaults to the first team of the user. */
+  teamId?: string;
 }
 
 /** Parameters for updating a secret */
@@ -370,6 +648,8 @@ export interface UpdateSecret {
   description?: string;
   /** The time the secret expires */
   expiresAt?: NaiveDateTime;
+  /** Database ID of the team to move the secret to, an empty string sharing it with everyone */
+  teamId?: string;
 }
 
 /** Parameters for creating a new source secret */
@@ -398,6 +678,8 @@ export enum ServerConfigCategory {
   Proxy = "PROXY",
   Sse = "SSE",
   Runners = "RUNNERS",
+  Tasks = "TASKS",
+  Auth = "AUTH",
 }
 
 /** Configuration entry for the server */
@@ -445,6 +727,30 @@ export interface ServerConfig {
   javascript_runner_enabled: ServerConfigEntryBool;
   python_runner_grpc_address: ServerConfigEntryString;
   python_runner_enabled: ServerConfigEntryBool;
+  task_failure_notification_threshold: ServerConfigEntryU32;
+  mfa_required: ServerConfigEntryBool;
+}
+
+/** A login of a user, kept alive by refreshing its short lived JWTs */
+export interface UserSession {
+  /** The database ID of the session */
+  id: string;
+  /** The time the user logged in */
+  createdAt: string;
+  /** The time the session was last updated */
+  updatedAt: string;
+  /** IP address of the client that logged in */
+  ipAddress: string;
+  /** User agent of the client that logged in */
+  userAgent: string;
+  /** The time the session was last refreshed */
+  lastUsedAt: string;
+  /** The time after which the session can no longer be refreshed */
+  expiresAt: string;
+  /** The time the session was revoked, if it was */
+  revokedAt?: string;
+  /** The database ID of the user of the session */
+  userId: string;
 }
 
 /** Kind of the source, related to the language used for corelating data from the source */
@@ -452,6 +758,10 @@ export enum SourceKind {
   System = "SYSTEM",
   Python = "PYTHON",
   JavaScript = "JAVA_SCRIPT",
+  /** Generic feed source, configured through the source's config */
+  Feed = "FEED",
+  /** Generic HTTP source, configured through the source's config */
+  Http = "HTTP",
 }
 
 /** A place where indicator data is retrieved from */
@@ -484,6 +794,11 @@ export interface Source {
   taskEnabled: boolean;
   /** Interval in seconds between the source's background task executions */
   taskInterval?: number;
+  /**
+   * Cron expression of the source's background task executions, taking precedence over the
+   * interval
+   */
+  taskCron?: string;
   /** Configuration of the source */
   config: Value[];
   /** Values of the source's configuration */
@@ -508,6 +823,8 @@ export interface Source {
   createdUserId: string;
   /** Database ID of the user who last updated the source */
   updatedUserId?: string;
+  /** Database ID of the team owning the source, shared with everyone when not set */
+  teamId?: string;
 }
 
 /** Parameters to create a source */
@@ -532,6 +849,11 @@ export interface CreateSource {
   taskEnabled: boolean;
   /** Interval in seconds between the source's background task executions */
   taskInterval?: number;
+  /**
+   * Cron expression of the source's background task executions, taking precedence over the
+   * interval
+   */
+  taskCron?: string;
   /** Configuration of the source */
   config: Value[];
   /** Values of the source's configuration */
@@ -552,6 +874,8 @@ export interface CreateSource {
   kind: SourceKind;
   /** Source code of the source */
   sourceCode?: string;
+  /** Database ID of the team owning the source, an empty string sharing it with everyone. Defaults to the first team of the user. */
+  teamId?: string;
 }
 
 /** Parameters to partially update a source */
@@ -576,6 +900,11 @@ export interface UpdateSource {
   taskEnabled?: boolean;
   /** Interval in seconds between the source's background task executions */
   taskInterval?: number;
+  /**
+   * Cron expression of the source's background task executions, taking precedence over the
+   * interval, an empty string removes it
+   */
+  taskCron?: string;
   /** Configuration of the source */
   config?: Value[];
   /** Values of the source's configuration */
@@ -596,6 +925,36 @@ export interface UpdateSource {
   kind?: SourceKind;
   /** Source code of the source */
   sourceCode?: string;
+  /** Database ID of the team to move the source to, an empty string sharing it with everyone */
+  teamId?: string;
+}
+
+/** Reason why the cached data of a source was invalidated */
+export enum CacheInvalidationReason {
+  /** The source's code, configuration or supported indicators were modified */
+  SourceUpdated = "SOURCE_UPDATED",
+  /** The secrets linked to the source were modified */
+  SourceSecretsUpdated = "SOURCE_SECRETS_UPDATED",
+  /** The value of a secret linked to the source was modified */
+  SecretUpdated = "SECRET_UPDATED",
+}
+
+/** Record of the cached data of a source being invalidated */
+export interface SourceCacheInvalidation {
+  /** Database ID of the cache invalidation */
+  id: string;
+  /** Timestamp of when the cache was invalidated */
+  createdAt: NaiveDateTime;
+  /** Reason why the cache was invalidated */
+  reason: CacheInvalidationReason;
+  /** Number of cache entries removed */
+  numEntries: number;
+  /** Trace ID of the request that caused the invalidation */
+  traceId: string;
+  /** Database ID of the source whose cache was invalidated */
+  sourceId: string;
+  /** Database ID of the user who caused the invalidation */
+  userId: string;
 }
 
 /** Overview of the number of providers, sources, and indicators */
@@ -650,6 +1009,92 @@ export interface CountPerHour {
   timeWindow: DateTime<Utc>;
 }
 
+/** Outcome of a source's background task execution */
+export enum TaskRunStatus {
+  /** The execution has not finished yet */
+  Running = "RUNNING",
+  /** The execution finished without errors */
+  Succeeded = "SUCCEEDED",
+  /** The execution finished with an error */
+  Failed = "FAILED",
+}
+
+/** What caused a source's background task to be executed */
+export enum TaskRunTrigger {
+  /** Executed by the scheduler following the source's interval or cron expression */
+  Scheduled = "SCHEDULED",
+  /** Executed on demand by a user */
+  Manual = "MANUAL",
+}
+
+/** Record of an execution of a source's background task */
+export interface TaskRun {
+  /** Database ID of the task run */
+  id: string;
+  /** Timestamp of when the execution started */
+  startedAt: NaiveDateTime;
+  /** Timestamp of when the execution ended, if it has ended */
+  endedAt?: NaiveDateTime;
+  /** Outcome of the execution */
+  status: TaskRunStatus;
+  /** What caused the execution */
+  trigger: TaskRunTrigger;
+  /** Error encountered by the execution, if it failed */
+  error?: string;
+  /** Number of records ingested by the execution, if the task reports it */
+  recordsIngested?: number;
+  /** Number of records seen for the first time by the execution, if the task reports it */
+  recordsAdded?: number;
+  /** Number of records changed by the execution, if the task reports it */
+  recordsUpdated?: number;
+  /** Number of records removed by the execution, if the task reports it */
+  recordsRemoved?: number;
+  /** Trace ID of the execution */
+  traceId: string;
+  /** Database ID of the source whose task was executed */
+  sourceId: string;
+  /** Database ID of the user who triggered the execution, if triggered manually */
+  userId?: string;
+}
+
+/** Group of users sharing sources, secrets, ignore lists and requests */
+export interface Team {
+  /** Database ID of the team */
+  id: string;
+  /** Timestamp of when the team was created */
+  createdAt: NaiveDateTime;
+  /** Timestamp of when the team was last updated */
+  updatedAt: NaiveDateTime;
+  /** Name of the team */
+  name: string;
+  /** URL friendly name of the team */
+  slug: string;
+  /** Description of the team */
+  description: string;
+  /** Database ID of the user who created the team */
+  createdUserId: string;
+  /** Database ID of the user who last updated the team */
+  updatedUserId?: string;
+  /** Number of users member of the team */
+  numMembers: number;
+}
+
+/** Parameters for creating a new team */
+export interface CreateTeam {
+  /** Name of the team */
+  name: string;
+  /** Description of the team */
+  description: string;
+}
+
+/** Parameters for updating a team */
+export interface UpdateTeam {
+  /** Name of the team */
+  name?: string;
+  /** Description of the team */
+  description?: string;
+}
+
 /** User able to query and make modifications to Indicator Aggregator */
 export interface User {
   /** Database ID of the user */
@@ -682,6 +1127,13 @@ export interface User {
   roles: string[];
   /** User that last modified the user */
   lastModifiedUserId?: string;
+  /**
+   * Whether the user is a service account used by integrations, unable to login and only
+   * authenticating with API tokens
+   */
+  serviceAccount: boolean;
+  /** User responsible for the service account */
+  ownerUserId?: string;
 }
 
 /** What to update in a user */
@@ -689,7 +1141,19 @@ export interface UpdateUser {
   /** Wheter to enable the user or not */
   enabled?: boolean;
   /** Roles to assign to the user, if any */
-  roles?: HashSet<string>;
+  roles?: HashSet<Role>;
+  /** User responsible for the service account, if it is one */
+  ownerUserId?: string;
+}
+
+/** Data needed to create a service account */
+export interface CreateServiceAccount {
+  /** Name of the service account, usually the integration using it */
+  name: string;
+  /** Roles of the service account */
+  roles: HashSet<Role>;
+  /** User responsible for the service account, defaults to the one creating it */
+  ownerUserId?: string;
 }
 
 /** Container for a user and the number of logs they have */
@@ -700,6 +1164,64 @@ export interface UserWithNumLogs {
   numLogs: number;
 }
 
+/** Role of a user, granting them a set of permissions */
+export enum Role {
+  /** Every permission, including managing users, teams and the server configuration */
+  Admin = "admin",
+  /** Analyst permissions, and managing sources, providers, ignore lists, secrets and runners */
+  SourceEditor = "source_editor",
+  /** Viewer permissions, and executing requests */
+  Analyst = "analyst",
+  /** Read-only access to requests, sources, providers, ignore lists, statistics and configuration */
+  Viewer = "viewer",
+}
+
+/** Fine-grained action a user is allowed to do, granted through their roles */
+export enum Permission {
+  /** View requests and their results */
+  RequestView = "request_view",
+  /** Execute requests to sources */
+  RequestCreate = "request_create",
+  /** View sources and their task runs */
+  SourceView = "source_view",
+  /** Create, update and delete sources, run their tasks and test their code */
+  SourceEdit = "source_edit",
+  /** View providers */
+  ProviderView = "provider_view",
+  /** Create, update and delete providers */
+  ProviderEdit = "provider_edit",
+  /** View ignore lists and their entries */
+  IgnoreListView = "ignore_list_view",
+  /** Create, update and delete ignore lists */
+  IgnoreListEdit = "ignore_list_edit",
+  /** View secrets, without their value */
+  SecretView = "secret_view",
+  /** Create, update and delete secrets, and assign them to sources */
+  SecretEdit = "secret_edit",
+  /** View the value of secrets */
+  SecretValueView = "secret_value_view",
+  /** View the status of the runners */
+  RunnerView = "runner_view",
+  /** View statistics about the service */
+  StatsView = "stats_view",
+  /** View the server configuration */
+  ConfigView = "config_view",
+  /** Update the server configuration */
+  ConfigEdit = "config_edit",
+  /** View users and their activity */
+  UserView = "user_view",
+  /** Enable, disable and assign roles to users, and revoke their API tokens */
+  UserEdit = "user_edit",
+  /** Create, update and delete teams, manage their members and access the resources of every team */
+  TeamEdit = "team_edit",
+}
+
+/** Security event related to the account of a user, logged along with the request causing it */
+export enum UserLogEvent {
+  /** The account was temporarily locked after too many failed logins */
+  AccountLocked = "ACCOUNT_LOCKED",
+}
+
 /** User request log */
 export interface DbUserLog {
   /** Database ID of the log */
@@ -720,6 +1242,8 @@ export interface DbUserLog {
   method: string;
   /** Opentelemetry trace ID of the request, can be used to correlate logs with a tool like Jaeger */
   traceId: string;
+  /** Security event caused by the request, if any */
+  event?: UserLogEvent;
 }
 
 /** Request to get the data for a specific indicator from enabled sources supporting the indicator kind */
@@ -732,6 +1256,18 @@ export interface RequestExecuteParam {
   sourceIds?: string[];
   /** Ignore errors, will remove all sources that return an error from the response */
   ignoreErrors?: boolean;
+  /** Team the request is made for, only querying its sources and the shared ones. An empty string makes a shared request and it defaults to the first team of the user. */
+  teamId?: string;
+}
+
+/** Request to run the code of a source on an indicator without saving it */
+export interface SourcePlaygroundRequest {
+  /** Kind of the source, which must be executed by a runner */
+  kind: SourceKind;
+  /** Code of the source, sent to the runner without being stored */
+  sourceCode: string;
+  /** Indicator to run the source's code on */
+  indicator: Indicator;
 }
 
 /** Partial information about a source for SSE start events */
@@ -774,6 +1310,36 @@ export interface DataTiming {
   endedAt: NaiveDateTime;
 }
 
+/** Synchronization of the sources' code with a runner instance, the drift found being re-synced */
+export interface RunnerSync {
+  /** When the instance's sources were compared with the database */
+  checkedAt: NaiveDateTime;
+  /** Number of sources expected on the instance */
+  numSources: number;
+  /** IDs of the sources missing from the instance */
+  missing: string[];
+  /** IDs of the sources whose code on the instance was outdated */
+  stale: string[];
+  /** IDs of the sources on the instance that no longer exist or no longer have code */
+  extra: string[];
+  /** Environments of the sources declaring requirements, as installed on the instance */
+  environments: SourceEnvironment[];
+  /** Error that prevented the synchronization, if any */
+  error?: string;
+}
+
+/** Instance of a runner, as seen by this server */
+export interface RunnerInstance {
+  /** Kind of sources executed by the runner */
+  kind: SourceKind;
+  /** gRPC address of the instance */
+  address: string;
+  /** Health of the instance, `SERVING` only once it received the sources' code */
+  status: string;
+  /** Last synchronization of the sources' code with the instance, if any */
+  sync?: RunnerSync;
+}
+
 /** Error encountered when fetching data from a source */
 export type SourceError =
   | { kind: "UNSUPPORTED_INDICATOR"; content?: undefined }
@@ -793,6 +1359,72 @@ export type SourceError =
   | { kind: "MISSING_SOURCE_CODE"; content?: undefined }
   | { kind: "RATE_LIMITED"; content?: undefined };
 
+/** Status of the installation of a source's requirements */
+export enum SourceEnvironmentStatus {
+  Installing = "INSTALLING",
+  Ready = "READY",
+  Failed = "FAILED",
+}
+
+/** Package installed in the environment of a source */
+export interface SourcePackage {
+  /** Name of the package */
+  name: string;
+  /** Installed version of the package */
+  version: string;
+}
+
+/** Isolated environment of a runner instance, containing the packages required by a source */
+export interface SourceEnvironment {
+  /** ID of the source */
+  source: string;
+  /** Requirements declared by the source */
+  requirements: string[];
+  /** Status of the installation of the requirements */
+  status: SourceEnvironmentStatus;
+  /** Packages installed in the environment, once it is ready */
+  packages: SourcePackage[];
+  /** Why the requirements could not be installed, if they failed */
+  error?: string;
+}
+
+/** Level of a log emitted by a source's code */
+export enum SourceLogLevel {
+  Info = "INFO",
+  Warning = "WARNING",
+  Error = "ERROR",
+}
+
+/** Log emitted by a source's code, such as what it printed */
+export interface SourceLog {
+  /** Level of the log, `ERROR` for what was printed to stderr */
+  level: SourceLogLevel;
+  /** Content of the log */
+  message: string;
+}
+
+/** Error raised by a source's code */
+export interface SourceCodeError {
+  /** Kind of the error, as it would be reported when fetching data from the source */
+  kind: SourceError;
+  /** Message of the error, including the type of the exception */
+  message: string;
+  /** Formatted stack trace of the exception, if any */
+  traceback?: string;
+}
+
+/** Result of running a source's code that was not saved, nothing about it being stored */
+export interface SourcePlaygroundResult {
+  /** Data returned by the source, partial results being gathered in an array */
+  data?: Value;
+  /** Error raised by the source, if any */
+  error?: SourceCodeError;
+  /** Logs emitted by the source while fetching data */
+  logs: SourceLog[];
+  /** Time spent running the source's code, in milliseconds */
+  durationMs: number;
+}
+
 /** Data from a source */
 export interface Data {
   /** Information about the source */
@@ -843,8 +1475,88 @@ export interface LoginUserRequest {
 
 /** Response from login request */
 export interface LoginUserResponse {
-  /** The JWT token created from login request that can be used to authenticate yourself */
-  jwtToken: string;
+  /**
+   * The JWT token created from login request that can be used to authenticate yourself,
+   * missing until the second factor step of the login is completed
+   */
+  jwtToken?: string;
+  /**
+   * Single use token to get new tokens with `/auth/refresh` once the JWT expires, missing
+   * like the JWT
+   */
+  refreshToken?: string;
+  /** Second factor needed to complete the login with `/auth/login/mfa` */
+  mfaChallenge?: MfaChallenge;
+  /** Recovery codes generated when enrolling a second factor during the login, only shown once */
+  recoveryCodes?: string[];
+}
+
+/** Second authentication factor */
+export enum MfaMethod {
+  Totp = "totp",
+  RecoveryCode = "recoveryCode",
+  WebAuthn = "webAuthn",
+}
+
+/** Second factor step of a login, after the password was verified */
+export interface MfaChallenge {
+  /** Token to send with the second factor, also signed by security keys */
+  token: string;
+  /** Second factors enrolled by the user */
+  methods: MfaMethod[];
+  /**
+   * Whether the user needs to enroll TOTP, with `/auth/login/mfa/totp`, since a second factor
+   * is required
+   */
+  enrollmentRequired: boolean;
+  /** Options to sign the challenge with a security key, if the user registered any */
+  webauthn?: WebAuthnRequestOptions;
+}
+
+/** Token of the second factor step of a login */
+export interface MfaTokenRequest {
+  /** The token of the second factor challenge */
+  token: string;
+}
+
+/** Second factor completing a login, only one of them being needed */
+export interface MfaLoginRequest {
+  /** The token of the second factor challenge */
+  token: string;
+  /** Code of an authenticator app, also confirming its enrollment when it is required */
+  totpCode?: string;
+  /** One of the recovery codes of the user */
+  recoveryCode?: string;
+  /** Signature of the challenge by a security key */
+  webauthn?: WebAuthnAssertion;
+}
+
+/** Code of an authenticator app */
+export interface TotpCodeRequest {
+  /** The code shown by the authenticator app */
+  code: string;
+}
+
+/** Recovery codes usable once each instead of a second factor */
+export interface RecoveryCodesResponse {
+  /** The new recovery codes, only shown once, empty if the user already had some */
+  recoveryCodes: string[];
+}
+
+/** Second factors enrolled by a user */
+export interface MfaStatus {
+  /** Whether an authenticator app is enrolled */
+  totpEnabled: boolean;
+  /** Number of recovery codes not used yet */
+  recoveryCodesLeft: number;
+  /** Security keys registered */
+  webauthnCredentials: WebAuthnCredential[];
+}
+
+/** Refresh token of a session */
+export interface RefreshTokenRequest {
+  /** The refresh token received when logging in or when last refreshing the session */
+  refreshToken: string;
 }
 
 /** Data needed to signup/create a new user */
@@ -857,12 +1569,43 @@ export interface SignupUserRequest {
   password: string;
 }
 
+/** Single use token sent by email */
+export interface UserTokenRequest {
+  /** The token received by email */
+  token: string;
+}
+
+/** Email of a local account */
+export interface UserEmailRequest {
+  /** The email of the local account */
+  email: string;
+}
+
+/** Data needed to reset the password of a local account */
+export interface ResetPasswordRequest {
+  /** The password reset token received by email */
+  token: string;
+  /** The new password of the user */
+  password: string;
+}
+
+/** Data needed to change the password of the logged in user */
+export interface ChangePasswordRequest {
+  /** The current password of the user */
+  currentPassword: string;
+  /** The new password of the user */
+  newPassword: string;
+}
+
 /** Kind of authentication service */
 export type AuthServiceKind =
   | {
       kind: "openId";
       content: {
+        /** Name of the provider in the configuration, used in its URLs */
         name: string;
+        /** Name of the provider displayed to users */
+        label: string;
       };
     }
   | { kind: "password"; content?: undefined };
@@ -876,7 +1619,6 @@ export interface AuthService {
 }
 
 /** Enum containing the different kinds of notifications and it's content */
-export type NotificationKind = {
-  kind: "MISSING_REQUIRED_SOURCE_SECRET";
-  content: MinimalSource;
-};
+export type NotificationKind =
+  | { kind: "MISSING_REQUIRED_SOURCE_SECRET"; content: MinimalSource }
+  | { kind: "FAILING_SOURCE_TASK"; content: FailingSourceTask };